@@ -0,0 +1,306 @@
+// src/artist.rs
+use anyhow::{anyhow, Result};
+use lru::LruCache;
+use serde_json::Value;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::{ArtistInfoSource, Config, Language};
+use crate::musickit::MusicKitClient;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const CACHE_SIZE: usize = 32;
+const LASTFM_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+// Last.fm stopped serving artist photos; every artist gets this placeholder
+const LASTFM_PLACEHOLDER_IMAGE: &str = "2a96cbd8b46e442fc41c2b86b821562f";
+// Tried in order until a page reads like it's about a musician
+const WIKIPEDIA_QUALIFIERS: [&str; 5] = ["", " (band)", " (musician)", " (singer)", " (rapper)"];
+const MUSIC_WORDS: [&str; 15] = [
+    "band",
+    "singer",
+    "musician",
+    "rapper",
+    "composer",
+    "songwriter",
+    "duo",
+    "group",
+    "producer",
+    "orchestra",
+    "歌手",
+    "バンド",
+    "ミュージシャン",
+    "作曲家",
+    "ユニット",
+];
+
+/// A short biography for the artist panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtistInfo {
+    pub name: String,
+    pub summary: String,
+    pub image_url: Option<String>,
+    // "You might also like" artists, when the source has them
+    pub similar: Vec<String>,
+    // Shown as the credit line
+    pub source: &'static str,
+}
+
+/// Looks up artists on the configured source. Results, including misses, are
+/// cached for the session so reopening the panel doesn't refetch.
+#[derive(Clone)]
+pub struct ArtistInfoClient {
+    client: reqwest::Client,
+    source: ArtistInfoSource,
+    language: &'static str,
+    lastfm_api_key: Option<String>,
+    musickit: Option<MusicKitClient>,
+    cache: Arc<Mutex<LruCache<String, Option<ArtistInfo>>>>,
+}
+
+impl ArtistInfoClient {
+    pub fn new(config: &Config) -> Self {
+        let language = match config.general.language {
+            Language::English => "en",
+            Language::Japanese => "ja",
+        };
+        Self {
+            client: crate::network::client(Some(REQUEST_TIMEOUT), None),
+            source: config.artist_info.source,
+            language,
+            lastfm_api_key: config
+                .artist_info
+                .lastfm_api_key
+                .clone()
+                .filter(|k| !k.is_empty()),
+            musickit: MusicKitClient::from_config(&config.musickit),
+            cache: Arc::new(Mutex::new(LruCache::new(
+                NonZeroUsize::new(CACHE_SIZE).expect("cache size is non-zero"),
+            ))),
+        }
+    }
+
+    /// `None` when the source has nothing on the artist.
+    pub async fn fetch(&self, artist: &str) -> Result<Option<ArtistInfo>> {
+        let key = artist.trim().to_lowercase();
+        if key.is_empty() {
+            return Ok(None);
+        }
+        if let Some(cached) = self
+            .cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+        {
+            return Ok(cached.clone());
+        }
+        crate::network::ensure_online("artist info")?;
+        let info = match self.source {
+            ArtistInfoSource::Wikipedia => self.wikipedia(artist.trim()).await?,
+            ArtistInfoSource::LastFm => self.lastfm(artist.trim()).await?,
+            ArtistInfoSource::AppleMusic => {
+                self.musickit
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Set musickit.developer_token to use Apple Music"))?
+                    .artist(artist.trim())
+                    .await?
+            }
+        };
+        self.cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .put(key, info.clone());
+        Ok(info)
+    }
+
+    async fn wikipedia(&self, artist: &str) -> Result<Option<ArtistInfo>> {
+        let base = format!(
+            "https://{}.wikipedia.org/api/rest_v1/page/summary/",
+            self.language
+        );
+        for qualifier in WIKIPEDIA_QUALIFIERS {
+            let mut url = reqwest::Url::parse(&base)?;
+            url.path_segments_mut()
+                .map_err(|_| anyhow!("Invalid Wikipedia URL"))?
+                .pop_if_empty()
+                .push(&format!("{}{}", artist, qualifier));
+            let response = self.client.get(url).send().await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                continue;
+            }
+            let json: Value = response.error_for_status()?.json().await?;
+            if let Some(info) = parse_wikipedia_summary(&json) {
+                return Ok(Some(info));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn lastfm(&self, artist: &str) -> Result<Option<ArtistInfo>> {
+        let api_key = self
+            .lastfm_api_key
+            .as_deref()
+            .ok_or_else(|| anyhow!("Set artist_info.lastfm_api_key to use Last.fm"))?;
+        let json: Value = self
+            .client
+            .get(LASTFM_URL)
+            .query(&[
+                ("method", "artist.getinfo"),
+                ("artist", artist),
+                ("api_key", api_key),
+                ("format", "json"),
+                ("autocorrect", "1"),
+                ("lang", self.language),
+            ])
+            .send()
+            .await?
+            .json()
+            .await?;
+        parse_lastfm_artist(&json)
+    }
+}
+
+// Disambiguation pages and articles about something else (a film, a city)
+// are skipped, so "Queen" falls through to "Queen (band)".
+fn parse_wikipedia_summary(json: &Value) -> Option<ArtistInfo> {
+    if json["type"].as_str() != Some("standard") {
+        return None;
+    }
+    let summary = json["extract"].as_str()?.trim();
+    let description = json["description"].as_str().unwrap_or_default();
+    let about = format!("{} {}", description, summary).to_lowercase();
+    if summary.is_empty() || !MUSIC_WORDS.iter().any(|word| about.contains(word)) {
+        return None;
+    }
+    Some(ArtistInfo {
+        name: json["title"].as_str().unwrap_or_default().to_string(),
+        summary: summary.to_string(),
+        image_url: json["thumbnail"]["source"].as_str().map(str::to_string),
+        similar: Vec::new(),
+        source: "Wikipedia",
+    })
+}
+
+// Error 6 is Last.fm's "artist not found"; anything else is a real failure
+fn parse_lastfm_artist(json: &Value) -> Result<Option<ArtistInfo>> {
+    match json["error"].as_u64() {
+        Some(6) => return Ok(None),
+        Some(_) => {
+            return Err(anyhow!(
+                "Last.fm: {}",
+                json["message"].as_str().unwrap_or("request failed")
+            ))
+        }
+        None => {}
+    }
+    let artist = &json["artist"];
+    let summary = strip_html(artist["bio"]["summary"].as_str().unwrap_or_default());
+    if summary.is_empty() {
+        return Ok(None);
+    }
+    let image_url = artist["image"]
+        .as_array()
+        .and_then(|images| images.last())
+        .and_then(|image| image["#text"].as_str())
+        .filter(|url| !url.is_empty() && !url.contains(LASTFM_PLACEHOLDER_IMAGE))
+        .map(str::to_string);
+    Ok(Some(ArtistInfo {
+        name: artist["name"].as_str().unwrap_or_default().to_string(),
+        summary,
+        image_url,
+        similar: names(&artist["similar"]["artist"]),
+        source: "Last.fm",
+    }))
+}
+
+fn names(artists: &Value) -> Vec<String> {
+    artists
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|artist| artist["name"].as_str().map(str::to_string))
+        .collect()
+}
+
+// Last.fm bios are HTML and end in a "Read more on Last.fm" link
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">");
+    text.trim()
+        .trim_end_matches("Read more on Last.fm")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn wikipedia_summaries_must_be_about_a_musician() {
+        let band = json!({
+            "type": "standard",
+            "title": "Queen (band)",
+            "description": "British rock band",
+            "extract": "Queen are a British rock band formed in London in 1970.",
+            "thumbnail": { "source": "https://upload.wikimedia.org/queen.jpg" }
+        });
+        let info = parse_wikipedia_summary(&band).unwrap();
+        assert_eq!(info.name, "Queen (band)");
+        assert_eq!(
+            info.image_url.as_deref(),
+            Some("https://upload.wikimedia.org/queen.jpg")
+        );
+
+        let monarch = json!({
+            "type": "standard",
+            "title": "Queen",
+            "description": "Female monarch",
+            "extract": "A queen is the female ruler of a kingdom."
+        });
+        assert!(parse_wikipedia_summary(&monarch).is_none());
+        let disambiguation =
+            json!({ "type": "disambiguation", "extract": "Queen may refer to a band." });
+        assert!(parse_wikipedia_summary(&disambiguation).is_none());
+    }
+
+    #[test]
+    fn lastfm_bios_lose_their_markup_and_placeholder_images() {
+        let json = json!({
+            "artist": {
+                "name": "Nujabes",
+                "bio": {
+                    "summary": "Jun Seba &amp; friends made <b>jazz</b> hip hop. <a href=\"https://www.last.fm/music/Nujabes\">Read more on Last.fm</a>"
+                },
+                "image": [
+                    { "#text": "https://lastfm.freetls.fastly.net/i/u/300x300/2a96cbd8b46e442fc41c2b86b821562f.png", "size": "extralarge" }
+                ],
+                "similar": { "artist": [{ "name": "Fat Jon" }, { "name": "DJ Okawari" }] }
+            }
+        });
+        let info = parse_lastfm_artist(&json).unwrap().unwrap();
+        assert_eq!(info.summary, "Jun Seba & friends made jazz hip hop.");
+        assert_eq!(info.image_url, None);
+        assert_eq!(info.similar, ["Fat Jon", "DJ Okawari"]);
+
+        assert_eq!(parse_lastfm_artist(&json!({ "error": 6 })).unwrap(), None);
+        assert!(
+            parse_lastfm_artist(&json!({ "error": 10, "message": "Invalid API key" })).is_err()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+// src/artwork/animated.rs
+use anyhow::{anyhow, Context, Result};
+use image::codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder};
+use image::{AnimationDecoder, DynamicImage, ImageFormat};
+use std::io::Cursor;
+use std::process::Stdio;
+use std::time::Duration;
+
+// Every frame is kept decoded, so loops are capped at a few seconds of small
+// frames (~35 MB worst case).
+pub(super) const MAX_FRAMES: usize = 72;
+pub(super) const MAX_FRAME_PIXELS: u32 = 360;
+const VIDEO_SECONDS: &str = "6";
+const VIDEO_FPS: u32 = 12;
+const FFMPEG_TIMEOUT: Duration = Duration::from_secs(30);
+// Browsers treat near-zero GIF delays as 100ms; so do we
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+pub struct AnimationFrame {
+    pub image: DynamicImage,
+    pub delay: Duration,
+}
+
+// Motion artwork is served as HLS or MP4 rather than as an image format.
+pub(super) fn is_video_source(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    [".m3u8", ".mp4", ".m4v", ".mov"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+}
+
+async fn ffmpeg(source: &str, output_args: &[&str]) -> Result<Vec<u8>> {
+    let input = match source.strip_prefix("file://") {
+        Some(path) => path,
+        None => {
+            crate::network::ensure_online("video artwork download")?;
+            source
+        }
+    };
+    let child = tokio::process::Command::new("ffmpeg")
+        .args(["-v", "error", "-i", input])
+        .args(output_args)
+        .arg("pipe:1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Video artwork needs ffmpeg on PATH")?;
+    let output = tokio::time::timeout(FFMPEG_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| anyhow!("ffmpeg timed out on {}", source))??;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+// The first frame of a video, as PNG bytes
+pub(super) async fn video_still(source: &str) -> Result<Vec<u8>> {
+    ffmpeg(
+        source,
+        &["-frames:v", "1", "-f", "image2pipe", "-vcodec", "png"],
+    )
+    .await
+}
+
+// The start of a video, as GIF bytes for `decode_frames`
+pub(super) async fn video_clip(source: &str) -> Result<Vec<u8>> {
+    let filter = format!(
+        "fps={},scale={}:{}:force_original_aspect_ratio=decrease",
+        VIDEO_FPS, MAX_FRAME_PIXELS, MAX_FRAME_PIXELS
+    );
+    ffmpeg(source, &["-t", VIDEO_SECONDS, "-vf", &filter, "-f", "gif"]).await
+}
+
+// Frames of an animated GIF, APNG, or WebP. Empty for still images.
+pub(super) fn decode_frames(bytes: &[u8]) -> Result<Vec<AnimationFrame>> {
+    let frames = match image::guess_format(bytes)? {
+        ImageFormat::Gif => GifDecoder::new(Cursor::new(bytes))?.into_frames(),
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(Cursor::new(bytes))?;
+            if !decoder.is_apng()? {
+                return Ok(Vec::new());
+            }
+            decoder.apng()?.into_frames()
+        }
+        ImageFormat::WebP => {
+            let decoder = WebPDecoder::new(Cursor::new(bytes))?;
+            if !decoder.has_animation() {
+                return Ok(Vec::new());
+            }
+            decoder.into_frames()
+        }
+        _ => return Ok(Vec::new()),
+    };
+
+    frames
+        .take(MAX_FRAMES)
+        .map(|frame| {
+            let frame = frame?;
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            let delay = Duration::from_millis(u64::from(numer) / u64::from(denom.max(1)));
+            Ok(AnimationFrame {
+                image: DynamicImage::ImageRgba8(frame.into_buffer()),
+                delay: if delay < MIN_FRAME_DELAY {
+                    DEFAULT_FRAME_DELAY
+                } else {
+                    delay
+                },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, Rgba, RgbaImage};
+
+    #[test]
+    fn decodes_gif_frames_with_their_delays() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut bytes);
+            for (shade, ms) in [(0, 0), (255, 250)] {
+                let image = RgbaImage::from_pixel(4, 4, Rgba([shade, shade, shade, 255]));
+                let delay = Delay::from_numer_denom_ms(ms, 1);
+                encoder
+                    .encode_frame(Frame::from_parts(image, 0, 0, delay))
+                    .unwrap();
+            }
+        }
+
+        let frames = decode_frames(&bytes).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].delay, DEFAULT_FRAME_DELAY);
+        assert_eq!(frames[1].delay, Duration::from_millis(250));
+
+        let mut still = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(RgbaImage::new(4, 4))
+            .write_to(&mut still, ImageFormat::Png)
+            .unwrap();
+        assert!(decode_frames(still.get_ref()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn recognises_video_sources() {
+        assert!(is_video_source(
+            "https://mvod.itunes.apple.com/itunes-assets/abc/P123_Video.m3u8?token=1"
+        ));
+        assert!(is_video_source("file:///tmp/cover.MP4"));
+        assert!(!is_video_source(
+            "https://is1-ssl.mzstatic.com/600x600bb.jpg"
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+use super::ArtworkResolution;
+use anyhow::Result;
+use image::DynamicImage;
+use ratatui_image::picker::{Picker, ProtocolType};
+use ratatui_image::protocol::StatefulProtocol;
+
+// Cells this tall (in pixels) mean a high-DPI display
+const HIGH_DPI_CELL_HEIGHT: u16 = 28;
+
+pub struct ArtworkConverter {
+    picker: Picker,
+}
+
+impl ArtworkConverter {
+    pub fn with_mode(mode: &str) -> Result<Self> {
+        let is_zellij = std::env::var("ZELLIJ").is_ok();
+
+        let picker = match mode.to_lowercase().as_str() {
+            "halfblocks" => Picker::halfblocks(),
+            "sixel" => {
+                // Try and query for sixel, fallback to halfblocks but try to be high-res
+                Picker::from_query_stdio().unwrap_or_else(|_| Picker::halfblocks())
+            }
+            _ => {
+                if is_zellij && (mode == "auto" || mode.is_empty()) {
+                    Picker::halfblocks()
+                } else {
+                    // Modern terminals: query for best protocol and font-size
+                    Picker::from_query_stdio().unwrap_or_else(|_| Picker::halfblocks())
+                }
+            }
+        };
+        Ok(Self { picker })
+    }
+
+    pub fn resolution(&self) -> ArtworkResolution {
+        match self.picker.protocol_type() {
+            ProtocolType::Halfblocks => ArtworkResolution::Thumbnail,
+            _ if self.picker.font_size().1 >= HIGH_DPI_CELL_HEIGHT => ArtworkResolution::High,
+            _ => ArtworkResolution::Standard,
+        }
+    }
+
+    // Half blocks redraw too coarsely for animation to be worth it
+    pub fn supports_animation(&self) -> bool {
+        self.picker.protocol_type() != ProtocolType::Halfblocks
+    }
+
+    // A resize can come from a font size change, which changes the pixels
+    // per cell. Asks the terminal again and reports whether they changed;
+    // half blocks don't depend on them.
+    pub fn refresh_font_size(&mut self) -> bool {
+        let protocol = self.picker.protocol_type();
+        if protocol == ProtocolType::Halfblocks {
+            return false;
+        }
+        let Ok(mut picker) = Picker::from_query_stdio() else {
+            return false;
+        };
+        if picker.font_size() == self.picker.font_size() {
+            return false;
+        }
+        picker.set_protocol_type(protocol);
+        self.picker = picker;
+        true
+    }
+
+    pub fn create_protocol(&mut self, img: DynamicImage) -> StatefulProtocol {
+        self.picker.new_resize_protocol(img)
+    }
+}
@@ -0,0 +1,154 @@
+// src/artwork/crt.rs
+use crate::config::CrtConfig;
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+
+// Scan line pitch at the 600px reference size
+const SCANLINE_ROWS: f32 = 3.0;
+const SCANLINE_MAX_DARKEN: f32 = 0.6;
+const NOISE_MAX: f32 = 48.0;
+
+// Phosphor glow, scan lines, barrel distortion, then grain, each skipped when
+// its intensity is zero.
+pub(super) fn apply_crt(img: DynamicImage, crt: &CrtConfig) -> DynamicImage {
+    let mut out = img.to_rgba8();
+    let scale = out.width().max(out.height()) as f32 / 600.0;
+
+    let glow = crt.glow.clamp(0.0, 1.0);
+    if glow > 0.0 {
+        add_glow(&mut out, glow, scale);
+    }
+    let scanlines = crt.scanlines.clamp(0.0, 1.0);
+    if scanlines > 0.0 {
+        add_scanlines(&mut out, scanlines, scale);
+    }
+    let curvature = crt.curvature.clamp(0.0, 1.0);
+    if curvature > 0.0 {
+        out = barrel_distort(&out, curvature);
+    }
+    let noise = crt.noise.clamp(0.0, 1.0);
+    if noise > 0.0 {
+        add_noise(&mut out, noise);
+    }
+    DynamicImage::ImageRgba8(out)
+}
+
+// Screen-blends a blurred copy over the image so bright areas bleed
+fn add_glow(img: &mut RgbaImage, intensity: f32, scale: f32) {
+    let blurred = imageops::fast_blur(img, (8.0 * scale).max(1.0));
+    for (pixel, halo) in img.pixels_mut().zip(blurred.pixels()) {
+        for c in 0..3 {
+            let base = pixel[c] as f32 / 255.0;
+            let bloom = halo[c] as f32 / 255.0 * intensity;
+            pixel[c] = ((1.0 - (1.0 - base) * (1.0 - bloom)) * 255.0) as u8;
+        }
+    }
+}
+
+// Darkens the lower half of every scan line
+fn add_scanlines(img: &mut RgbaImage, intensity: f32, scale: f32) {
+    let pitch = ((SCANLINE_ROWS * scale).round() as u32).max(2);
+    let factor = 1.0 - intensity * SCANLINE_MAX_DARKEN;
+    for (_, y, pixel) in img.enumerate_pixels_mut() {
+        if y % pitch >= pitch / 2 {
+            for c in 0..3 {
+                pixel[c] = (pixel[c] as f32 * factor) as u8;
+            }
+        }
+    }
+}
+
+// Bulges the picture like a curved tube; the corners fall off into black
+fn barrel_distort(img: &RgbaImage, intensity: f32) -> RgbaImage {
+    let (width, height) = img.dimensions();
+    let k = intensity * 0.25;
+    RgbaImage::from_fn(width, height, |x, y| {
+        let u = (x as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+        let v = (y as f32 + 0.5) / height as f32 * 2.0 - 1.0;
+        let bulge = 1.0 + k * (u * u + v * v);
+        let (su, sv) = (u * bulge, v * bulge);
+        if su.abs() > 1.0 || sv.abs() > 1.0 {
+            return Rgba([0, 0, 0, 255]);
+        }
+        let sx = (((su + 1.0) / 2.0 * width as f32) as u32).min(width - 1);
+        let sy = (((sv + 1.0) / 2.0 * height as f32) as u32).min(height - 1);
+        *img.get_pixel(sx, sy)
+    })
+}
+
+// Static grain, hashed from the pixel position so a cover always renders the
+// same and stays cacheable
+fn add_noise(img: &mut RgbaImage, intensity: f32) {
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        let mut h = x.wrapping_mul(0x9E37_79B1) ^ y.wrapping_mul(0x85EB_CA6B);
+        h ^= h >> 15;
+        h = h.wrapping_mul(0x2C1B_3C6D);
+        h ^= h >> 12;
+        let grain = ((h & 0xFF) as f32 / 127.5 - 1.0) * intensity * NOISE_MAX;
+        for c in 0..3 {
+            pixel[c] = (pixel[c] as f32 + grain).clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn only(config: CrtConfig) -> CrtConfig {
+        CrtConfig {
+            enabled: true,
+            ..config
+        }
+    }
+
+    fn gray(size: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            size,
+            size,
+            Rgba([200, 200, 200, 255]),
+        ))
+    }
+
+    #[test]
+    fn scanlines_darken_alternate_rows() {
+        let crt = only(CrtConfig {
+            scanlines: 1.0,
+            glow: 0.0,
+            curvature: 0.0,
+            noise: 0.0,
+            ..CrtConfig::default()
+        });
+        let out = apply_crt(gray(600), &crt).to_rgba8();
+        assert_eq!(out.get_pixel(10, 0)[0], 200);
+        assert!(out.get_pixel(10, 2)[0] < 100);
+    }
+
+    #[test]
+    fn curvature_blacks_out_corners_and_keeps_the_centre() {
+        let crt = only(CrtConfig {
+            scanlines: 0.0,
+            glow: 0.0,
+            curvature: 1.0,
+            noise: 0.0,
+            ..CrtConfig::default()
+        });
+        let out = apply_crt(gray(100), &crt).to_rgba8();
+        assert_eq!(*out.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        assert_eq!(*out.get_pixel(50, 50), Rgba([200, 200, 200, 255]));
+    }
+
+    #[test]
+    fn noise_is_stable_between_renders() {
+        let crt = only(CrtConfig {
+            scanlines: 0.0,
+            glow: 0.0,
+            curvature: 0.0,
+            noise: 1.0,
+            ..CrtConfig::default()
+        });
+        let first = apply_crt(gray(32), &crt).to_rgba8();
+        let second = apply_crt(gray(32), &crt).to_rgba8();
+        assert_eq!(first, second);
+        assert!(first.pixels().any(|p| p[0] != 200));
+    }
+}
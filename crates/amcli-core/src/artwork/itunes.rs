@@ -0,0 +1,276 @@
+// src/artwork/itunes.rs
+use crate::lyrics::matching::strip_trailing_qualifiers;
+use anyhow::{anyhow, Result};
+use lru::LruCache;
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use unicode_normalization::UnicodeNormalization;
+
+const SEARCH_URL: &str = "https://itunes.apple.com/search";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+// Apple asks for roughly 20 calls a minute before it starts returning 403s
+const REQUESTS_PER_MINUTE: usize = 20;
+const MIN_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+const CACHE_SIZE: usize = 64;
+
+// Credits and release notes that make the search miss, matched in lowercase
+const FEATURING_MARKERS: [&str; 4] = [" feat. ", " feat ", " ft. ", " featuring "];
+const VERSION_WORDS: [&str; 6] = ["remaster", "version", "edit", "mono", "stereo", "live"];
+
+// Sliding-window rate limit plus exponential backoff after failures.
+struct Limiter {
+    recent: VecDeque<Instant>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Limiter {
+    fn new() -> Self {
+        Self {
+            recent: VecDeque::new(),
+            failures: 0,
+            retry_at: None,
+        }
+    }
+
+    fn acquire(&mut self, now: Instant) -> Result<()> {
+        if let Some(retry_at) = self.retry_at.filter(|at| now < *at) {
+            return Err(anyhow!(
+                "iTunes lookups paused for {}s after errors",
+                (retry_at - now).as_secs() + 1
+            ));
+        }
+        while self
+            .recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= REQUESTS_PER_MINUTE {
+            return Err(anyhow!("iTunes lookup rate limit reached"));
+        }
+        self.recent.push_back(now);
+        Ok(())
+    }
+
+    fn succeeded(&mut self) {
+        self.failures = 0;
+        self.retry_at = None;
+    }
+
+    fn failed(&mut self, now: Instant) {
+        let delay = MIN_BACKOFF
+            .saturating_mul(1 << self.failures.min(16))
+            .min(MAX_BACKOFF);
+        self.failures += 1;
+        self.retry_at = Some(now + delay);
+    }
+}
+
+// Cover art from the iTunes Search API, for tracks the player has no artwork
+// for. Answers (including misses) are cached by normalized query.
+pub struct ItunesArtworkSearch {
+    client: reqwest::Client,
+    cache: Mutex<LruCache<String, Option<String>>>,
+    limiter: Mutex<Limiter>,
+}
+
+impl Default for ItunesArtworkSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ItunesArtworkSearch {
+    pub fn new() -> Self {
+        Self {
+            client: crate::network::client(Some(REQUEST_TIMEOUT), None),
+            cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(CACHE_SIZE).expect("cache capacity must be non-zero"),
+            )),
+            limiter: Mutex::new(Limiter::new()),
+        }
+    }
+
+    pub async fn artwork_url(&self, artist: &str, title: &str) -> Result<Option<String>> {
+        let query = search_query(artist, title);
+        if query.is_empty() {
+            return Ok(None);
+        }
+
+        if let Some(cached) = self
+            .cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&query)
+        {
+            return Ok(cached.clone());
+        }
+
+        crate::network::ensure_online("iTunes artwork search")?;
+        self.limiter
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .acquire(Instant::now())?;
+
+        let result = self
+            .fetch(&query, 1)
+            .await
+            .map(|urls| urls.into_iter().next());
+        let mut limiter = self.limiter.lock().unwrap_or_else(|e| e.into_inner());
+        match &result {
+            Ok(url) => {
+                limiter.succeeded();
+                self.cache
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .put(query, url.clone());
+            }
+            Err(_) => limiter.failed(Instant::now()),
+        }
+        result
+    }
+
+    /// Up to `limit` covers for the track, best match first, skipping
+    /// repeats (a single and its album often share one). Not cached: it's
+    /// for looking past a wrong first result.
+    pub async fn candidates(&self, artist: &str, title: &str, limit: usize) -> Result<Vec<String>> {
+        let query = search_query(artist, title);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        crate::network::ensure_online("iTunes artwork search")?;
+        self.limiter
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .acquire(Instant::now())?;
+        let result = self.fetch(&query, limit).await;
+        let mut limiter = self.limiter.lock().unwrap_or_else(|e| e.into_inner());
+        match &result {
+            Ok(_) => limiter.succeeded(),
+            Err(_) => limiter.failed(Instant::now()),
+        }
+        let mut urls = result?;
+        let mut seen = std::collections::HashSet::new();
+        urls.retain(|url| seen.insert(url.clone()));
+        Ok(urls)
+    }
+
+    async fn fetch(&self, query: &str, limit: usize) -> Result<Vec<String>> {
+        let url = format!(
+            "{}?term={}&entity=song&limit={}",
+            SEARCH_URL,
+            urlencoding::encode(query),
+            limit
+        );
+        let json: serde_json::Value = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(json["results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|result| result["artworkUrl100"].as_str())
+            .map(|s| s.replace("100x100bb", "600x600bb"))
+            .collect())
+    }
+}
+
+// "Artist Title" with featuring credits, bracketed qualifiers, and version
+// suffixes removed, in NFC so decomposed tags match the catalog.
+fn search_query(artist: &str, title: &str) -> String {
+    let artist: String = artist.nfc().collect();
+    let title: String = title.nfc().collect();
+    let artist = strip_featuring(&artist);
+    let title = strip_version_suffix(strip_featuring(strip_trailing_qualifiers(&title)));
+    format!("{} {}", artist, title)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn strip_featuring(value: &str) -> &str {
+    // ASCII lowercasing keeps byte offsets valid for slicing
+    let lower = value.to_ascii_lowercase();
+    FEATURING_MARKERS
+        .iter()
+        .filter_map(|marker| lower.find(marker))
+        .filter(|&index| index > 0)
+        .min()
+        .map_or(value, |index| value[..index].trim_end())
+}
+
+fn strip_version_suffix(value: &str) -> &str {
+    let Some(index) = value.rfind(" - ") else {
+        return value;
+    };
+    let suffix = value[index..].to_lowercase();
+    if index > 0 && VERSION_WORDS.iter().any(|word| suffix.contains(word)) {
+        value[..index].trim_end()
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_query_drops_credits_and_version_notes() {
+        assert_eq!(
+            search_query(
+                "Daft Punk feat. Pharrell Williams",
+                "Get Lucky (Radio Edit)"
+            ),
+            "Daft Punk Get Lucky"
+        );
+        assert_eq!(
+            search_query("The Beatles", "Let It Be - Remastered 2009"),
+            "The Beatles Let It Be"
+        );
+        assert_eq!(
+            search_query("Calvin Harris", "One Kiss ft. Dua Lipa [Extended]"),
+            "Calvin Harris One Kiss"
+        );
+        // Dashes that are part of the title stay
+        assert_eq!(
+            search_query("Nirvana", "Smells Like Teen Spirit - Nevermind"),
+            "Nirvana Smells Like Teen Spirit - Nevermind"
+        );
+        // Decomposed kana from file tags become the composed form
+        assert_eq!(
+            search_query("ヨルシカ", "たた\u{3099}君に晴れ"),
+            "ヨルシカ ただ君に晴れ"
+        );
+    }
+
+    #[test]
+    fn limiter_caps_requests_and_backs_off() {
+        let start = Instant::now();
+        let mut limiter = Limiter::new();
+        for _ in 0..REQUESTS_PER_MINUTE {
+            limiter.acquire(start).unwrap();
+        }
+        assert!(limiter.acquire(start).is_err());
+        assert!(limiter.acquire(start + Duration::from_secs(60)).is_ok());
+
+        let mut limiter = Limiter::new();
+        limiter.failed(start);
+        assert!(limiter.acquire(start + Duration::from_secs(1)).is_err());
+        assert!(limiter.acquire(start + MIN_BACKOFF).is_ok());
+        limiter.failed(start);
+        assert!(limiter.acquire(start + MIN_BACKOFF).is_err());
+        limiter.succeeded();
+        assert!(limiter.acquire(start).is_ok());
+    }
+}
@@ -0,0 +1,825 @@
+pub mod animated;
+pub mod cache;
+pub mod converter;
+mod crt;
+pub mod itunes;
+pub mod overrides;
+pub mod recent;
+mod worker;
+
+use crate::config::{CrtConfig, MosaicShape};
+use anyhow::{anyhow, Result};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use image::{imageops::FilterType, DynamicImage, Rgba, RgbaImage};
+use lazy_static::lazy_static;
+use ratatui::style::Color;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::{path::PathBuf, time::Duration};
+use tokio::sync::Semaphore;
+
+const PIXELATION_BLOCK_SIZE: u32 = 8;
+// Covers the download and the body, unless `[network] timeout_secs` is set
+const ARTWORK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// The size the mosaic block size was tuned at
+const PIXELATION_REFERENCE_SIZE: u32 = 600;
+// Covers downloaded and processed at once, across every manager, so a burst
+// of track changes doesn't swamp a low-power machine
+const MAX_CONCURRENT_LOADS: usize = 2;
+// Each cover in the idle album grid, and the space and frame around it
+const GRID_TILE_SIZE: u32 = 200;
+const GRID_GAP: u32 = 12;
+const GRID_FRAME: u32 = 6;
+
+static LOAD_PERMITS: Semaphore = Semaphore::const_new(MAX_CONCURRENT_LOADS);
+
+// A load other callers can await too. Errors are strings so the result can
+// be cloned to each of them.
+type SharedLoad = Shared<BoxFuture<'static, Result<DynamicImage, String>>>;
+
+lazy_static! {
+    // iTunes/Apple Music CDN: .../600x600bb.jpg
+    static ref MZSTATIC_SIZE: Regex = Regex::new(r"/\d+x\d+bb\.").unwrap();
+    // Subsonic getCoverArt: ...&size=600
+    static ref COVER_ART_SIZE: Regex = Regex::new(r"([?&]size=)\d+").unwrap();
+}
+
+/// How much artwork detail the terminal can show. Graphics protocols on
+/// high-DPI displays get large covers; halfblocks only need a thumbnail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtworkResolution {
+    Thumbnail,
+    Standard,
+    High,
+}
+
+impl ArtworkResolution {
+    pub fn pixels(self) -> u32 {
+        match self {
+            Self::Thumbnail => 300,
+            Self::Standard => 600,
+            Self::High => 1200,
+        }
+    }
+
+    // Asks servers that can resize for covers at this size; other URLs are
+    // left alone and downscaled after loading.
+    fn sized_url(self, url: &str) -> Cow<'_, str> {
+        let pixels = self.pixels();
+        if url.contains("mzstatic.com") {
+            MZSTATIC_SIZE.replace(url, format!("/{}x{}bb.", pixels, pixels))
+        } else if url.contains("getCoverArt") {
+            COVER_ART_SIZE.replace(url, format!("${{1}}{}", pixels))
+        } else {
+            Cow::Borrowed(url)
+        }
+    }
+}
+
+/// How a cover is recoloured and post-processed for the current theme.
+#[derive(Debug, Clone)]
+pub struct ArtworkStyle {
+    pub theme_name: String,
+    pub dark: Color,
+    pub light: Color,
+    pub is_retro: bool,
+    pub mosaic: Option<Mosaic>,
+    // Only applied to retro themes
+    pub crt: Option<CrtConfig>,
+}
+
+/// Dot-matrix look of the mosaic effect. Sizes are in pixels at a 600px
+/// cover and scale with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mosaic {
+    pub tile_size: u32,
+    pub gap: u32,
+    pub shape: MosaicShape,
+}
+
+impl Default for Mosaic {
+    fn default() -> Self {
+        Self {
+            tile_size: PIXELATION_BLOCK_SIZE,
+            gap: 0,
+            shape: MosaicShape::Square,
+        }
+    }
+}
+
+impl ArtworkStyle {
+    fn cache_key(&self, url: &str, resolution: ArtworkResolution) -> String {
+        let mosaic = match &self.mosaic {
+            Some(m) => format!("{}-{}-{}", m.tile_size, m.gap, m.shape.label()),
+            None => "false".to_string(),
+        };
+        let crt = match &self.crt {
+            Some(crt) if self.is_retro => format!(
+                "crt-{}-{}-{}-{}",
+                crt.scanlines, crt.glow, crt.curvature, crt.noise
+            ),
+            _ => "crt-off".to_string(),
+        };
+        format!(
+            "{}-{}-mosaic-{}-retro-{}-{}-{}px",
+            self.theme_name,
+            url,
+            mosaic,
+            self.is_retro,
+            crt,
+            resolution.pixels()
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct ArtworkManager {
+    cache: cache::ArtworkCache,
+    client: reqwest::Client,
+    // Loads under way, by cache key, so asking twice fetches once
+    in_flight: Arc<Mutex<HashMap<String, SharedLoad>>>,
+}
+
+impl ArtworkManager {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self {
+            cache: cache::ArtworkCache::new(cache_dir, 100),
+            client: crate::network::client(Some(ARTWORK_REQUEST_TIMEOUT), None),
+            in_flight: Arc::default(),
+        }
+    }
+
+    pub async fn get_artwork_themed_v2(
+        &self,
+        url: &str,
+        style: &ArtworkStyle,
+        resolution: ArtworkResolution,
+    ) -> Result<DynamicImage> {
+        let themed_url = style.cache_key(url, resolution);
+
+        if let Some(img) = self.cache.get(&themed_url) {
+            crate::diagnostics::ARTWORK_CACHE.hit();
+            return Ok(img);
+        }
+        crate::diagnostics::ARTWORK_CACHE.miss();
+
+        let load = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            in_flight
+                .entry(themed_url.clone())
+                .or_insert_with(|| self.spawn_load(themed_url, url, style, resolution))
+                .clone()
+        };
+        load.await.map_err(|e| anyhow!(e))
+    }
+
+    // Runs on its own task, so it finishes (and frees its permit) even when
+    // everyone waiting on it has been cancelled
+    fn spawn_load(
+        &self,
+        key: String,
+        url: &str,
+        style: &ArtworkStyle,
+        resolution: ArtworkResolution,
+    ) -> SharedLoad {
+        let manager = self.clone();
+        let (url, style) = (url.to_string(), style.clone());
+        let task = tokio::spawn(async move {
+            let result = manager
+                .load_themed(&url, &style, resolution)
+                .await
+                .map_err(|e| e.to_string());
+            manager
+                .in_flight
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&key);
+            result
+        });
+        task.map(|joined| joined.unwrap_or_else(|e| Err(e.to_string())))
+            .boxed()
+            .shared()
+    }
+
+    /// A cover already themed this way, without loading anything.
+    pub fn cached_artwork(
+        &self,
+        url: &str,
+        style: &ArtworkStyle,
+        resolution: ArtworkResolution,
+    ) -> Option<DynamicImage> {
+        self.cache.get(&style.cache_key(url, resolution))
+    }
+
+    // The untouched cover is cached too, so restyling it for another theme
+    // skips the download and decode
+    async fn load_themed(
+        &self,
+        url: &str,
+        style: &ArtworkStyle,
+        resolution: ArtworkResolution,
+    ) -> Result<DynamicImage> {
+        let _permit = LOAD_PERMITS.acquire().await?;
+        let pixels = resolution.pixels();
+        let source_key = format!("source-{}-{}px", url, pixels);
+        let img = match self.cache.get(&source_key) {
+            Some(img) => img,
+            None => {
+                let img = load_artwork_image(&self.client, &resolution.sized_url(url)).await?;
+                let img = worker::run(move || downscale(img, pixels)).await?;
+                self.cache.insert(source_key, img.clone());
+                img
+            }
+        };
+        let theme = style.clone();
+        let themed_img = worker::run(move || style_artwork(img, &theme)).await?;
+
+        self.cache
+            .insert(style.cache_key(url, resolution), themed_img.clone());
+        Ok(themed_img)
+    }
+
+    /// Frames of an animated cover (GIF, APNG, WebP, or video through
+    /// ffmpeg), styled like the still. `None` when the source isn't animated.
+    pub async fn get_animated_artwork_themed(
+        &self,
+        url: &str,
+        style: &ArtworkStyle,
+    ) -> Result<Option<Vec<animated::AnimationFrame>>> {
+        let _permit = LOAD_PERMITS.acquire().await?;
+        let bytes = if animated::is_video_source(url) {
+            animated::video_clip(url).await?
+        } else {
+            load_artwork_bytes(&self.client, url).await?
+        };
+        let style = style.clone();
+        let frames = worker::run(move || -> Result<Vec<_>> {
+            Ok(animated::decode_frames(&bytes)?
+                .into_iter()
+                .map(|frame| animated::AnimationFrame {
+                    image: style_artwork(
+                        downscale(frame.image, animated::MAX_FRAME_PIXELS),
+                        &style,
+                    ),
+                    delay: frame.delay,
+                })
+                .collect())
+        })
+        .await??;
+        Ok((frames.len() > 1).then_some(frames))
+    }
+}
+
+fn downscale(img: DynamicImage, max: u32) -> DynamicImage {
+    if img.width() > max || img.height() > max {
+        img.resize(max, max, FilterType::Triangle)
+    } else {
+        img
+    }
+}
+
+/// Lays `covers` out left to right in rows of `columns`, on `background`,
+/// with a `highlight` frame around the one at `selected`.
+pub fn compose_grid(
+    covers: &[DynamicImage],
+    columns: u32,
+    selected: usize,
+    background: Color,
+    highlight: Color,
+) -> DynamicImage {
+    let columns = columns.max(1);
+    let rows = (covers.len() as u32).div_ceil(columns).max(1);
+    let step = GRID_TILE_SIZE + GRID_GAP;
+    let rgba = |color: Color| {
+        let (r, g, b) = extract_rgb(color);
+        Rgba([r as u8, g as u8, b as u8, 255])
+    };
+    let mut grid = RgbaImage::from_pixel(
+        columns * step + GRID_GAP,
+        rows * step + GRID_GAP,
+        rgba(background),
+    );
+    for (i, cover) in covers.iter().enumerate() {
+        let x = GRID_GAP + (i as u32 % columns) * step;
+        let y = GRID_GAP + (i as u32 / columns) * step;
+        if i == selected {
+            let frame = RgbaImage::from_pixel(
+                GRID_TILE_SIZE + 2 * GRID_FRAME,
+                GRID_TILE_SIZE + 2 * GRID_FRAME,
+                rgba(highlight),
+            );
+            image::imageops::overlay(
+                &mut grid,
+                &frame,
+                (x - GRID_FRAME).into(),
+                (y - GRID_FRAME).into(),
+            );
+        }
+        let tile = cover.resize_to_fill(GRID_TILE_SIZE, GRID_TILE_SIZE, FilterType::Triangle);
+        image::imageops::overlay(&mut grid, &tile.to_rgba8(), x.into(), y.into());
+    }
+    DynamicImage::ImageRgba8(grid)
+}
+
+fn style_artwork(img: DynamicImage, style: &ArtworkStyle) -> DynamicImage {
+    // Apply duotone theme only for retro themes
+    let processed_img = if style.is_retro {
+        apply_duotone_theme(img, style.dark, style.light)
+    } else {
+        img
+    };
+
+    // Optionally apply mosaic effect on top
+    let processed_img = match &style.mosaic {
+        Some(mosaic) => apply_pixelation(processed_img, mosaic),
+        None => processed_img,
+    };
+
+    // CRT post-processing goes last so scan lines cross the mosaic blocks
+    match &style.crt {
+        Some(crt) if style.is_retro => crt::apply_crt(processed_img, crt),
+        _ => processed_img,
+    }
+}
+
+async fn load_artwork_image(client: &reqwest::Client, source: &str) -> Result<DynamicImage> {
+    let bytes = if animated::is_video_source(source) {
+        animated::video_still(source).await?
+    } else {
+        load_artwork_bytes(client, source).await?
+    };
+    Ok(worker::run(move || image::load_from_memory(&bytes)).await??)
+}
+
+async fn load_artwork_bytes(client: &reqwest::Client, source: &str) -> Result<Vec<u8>> {
+    let bytes = if let Some(path) = source.strip_prefix("file://") {
+        tokio::fs::read(path).await?
+    } else {
+        crate::network::ensure_online("artwork download")?;
+        client
+            .get(source)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?
+            .to_vec()
+    };
+    Ok(bytes)
+}
+
+fn apply_pixelation(img: DynamicImage, mosaic: &Mosaic) -> DynamicImage {
+    let source = img.to_rgba8();
+    let (width, height) = source.dimensions();
+    let tile_size = mosaic.tile_size.max(1);
+    let block_size = pixelation_block_size(width, height, tile_size);
+    // Gaps scale with the tiles and always leave at least one lit pixel
+    let gap = (mosaic.gap * block_size / tile_size).min(block_size - 1);
+    // Transparent, so the panel background shows between the dots
+    let mut output = RgbaImage::new(width, height);
+
+    for block_y in (0..height).step_by(block_size as usize) {
+        for block_x in (0..width).step_by(block_size as usize) {
+            let x_end = (block_x + block_size).min(width);
+            let y_end = (block_y + block_size).min(height);
+            let color = average_block_color(&source, block_x, block_y, x_end, y_end);
+
+            for y in block_y..y_end {
+                for x in block_x..x_end {
+                    if is_lit(x - block_x, y - block_y, block_size, gap, mosaic.shape) {
+                        output.put_pixel(x, y, color);
+                    }
+                }
+            }
+        }
+    }
+
+    DynamicImage::ImageRgba8(output)
+}
+
+// Whether a pixel inside a block belongs to the dot rather than the gap around it
+fn is_lit(x: u32, y: u32, block_size: u32, gap: u32, shape: MosaicShape) -> bool {
+    let lit = (block_size - gap) as f32;
+    let centre = block_size as f32 / 2.0;
+    let (dx, dy) = (x as f32 + 0.5 - centre, y as f32 + 0.5 - centre);
+    match shape {
+        MosaicShape::Square => dx.abs() < lit / 2.0 && dy.abs() < lit / 2.0,
+        MosaicShape::Circle => dx * dx + dy * dy <= (lit / 2.0) * (lit / 2.0),
+    }
+}
+
+// Scaled up for large covers so the mosaic looks the same at any resolution
+fn pixelation_block_size(width: u32, height: u32, tile_size: u32) -> u32 {
+    (tile_size * width.max(height) / PIXELATION_REFERENCE_SIZE).max(tile_size)
+}
+
+fn average_block_color(
+    img: &RgbaImage,
+    x_start: u32,
+    y_start: u32,
+    x_end: u32,
+    y_end: u32,
+) -> Rgba<u8> {
+    let mut sum_r = 0_u64;
+    let mut sum_g = 0_u64;
+    let mut sum_b = 0_u64;
+    let mut sum_a = 0_u64;
+    let mut count = 0_u64;
+
+    for y in y_start..y_end {
+        for x in x_start..x_end {
+            let pixel = img.get_pixel(x, y);
+            let alpha = pixel[3] as u64;
+
+            sum_r += pixel[0] as u64 * alpha;
+            sum_g += pixel[1] as u64 * alpha;
+            sum_b += pixel[2] as u64 * alpha;
+            sum_a += alpha;
+            count += 1;
+        }
+    }
+
+    if count == 0 || sum_a == 0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+
+    let avg_a = ((sum_a + count / 2) / count) as u8;
+    let avg_r = ((sum_r + sum_a / 2) / sum_a) as u8;
+    let avg_g = ((sum_g + sum_a / 2) / sum_a) as u8;
+    let avg_b = ((sum_b + sum_a / 2) / sum_a) as u8;
+
+    Rgba([avg_r, avg_g, avg_b, avg_a])
+}
+
+fn get_relative_luminance(r: f32, g: f32, b: f32) -> f32 {
+    // Relative luminance formula (ITU-R BT.709)
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+fn apply_duotone_theme(img: DynamicImage, dark: Color, light: Color) -> DynamicImage {
+    let (_d_r, _d_g, _d_b) = extract_rgb(dark);
+    let (l_r, l_g, l_b) = extract_rgb(light);
+
+    let mut grayscale = img.grayscale().to_rgba8();
+    let (width, height) = grayscale.dimensions();
+
+    // Calculate perceived brightness of the light theme color
+    let luminance = get_relative_luminance(l_r, l_g, l_b);
+
+    // Normalize brightness and gamma based on the theme color's luminance.
+    // Darker colors (like Red) need higher brightness boost and lower gamma
+    // to bring out mid-tone details that would otherwise be lost.
+    //
+    // Reference Luminance (Amber-like): ~150-180
+    // Red Alert Luminance: ~54
+    // Cyan VFD Luminance: ~255
+
+    let base_brightness = 0.8;
+    let base_gamma = 0.45;
+
+    let (brightness_factor, gamma) = if luminance < 160.0 {
+        // Boost factor: scale from 1.0 (at 160) up to 1.4 (at 50)
+        let boost = (1.4 - (luminance - 50.0) * (0.4 / 110.0)).clamp(1.0, 1.4);
+        (base_brightness * boost, base_gamma * (1.0 / boost.sqrt()))
+    } else {
+        (base_brightness, base_gamma)
+    };
+
+    // Define black point threshold - MORE AGGRESSIVE
+    // Pixels below this threshold map to pure BLACK/GRAY (no color tint)
+    // Pixels above this threshold map to the theme color
+    let black_point = 0.35;
+
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = grayscale.get_pixel(x, y);
+            let raw_intensity = pixel[0] as f32 / 255.0;
+
+            // Apply linear scaling then gamma correction
+            let intensity = (raw_intensity * brightness_factor).powf(gamma);
+
+            let (r, g, b) = if intensity < black_point {
+                // CRITICAL: Dark regions map to pure BLACK to DARK GRAY
+                // NO THEME COLOR TINT in the shadows!
+                // This creates the true "black point" with high contrast
+                let shadow_value = (intensity / black_point * 10.0).clamp(0.0, 10.0) as u8;
+                (shadow_value, shadow_value, shadow_value)
+            } else {
+                // Bright regions map to theme color
+                // Remap intensity from [black_point, 1.0] to [0.0, 1.0]
+                let remapped = (intensity - black_point) / (1.0 - black_point);
+
+                let r = (l_r * remapped).clamp(0.0, 255.0) as u8;
+                let g = (l_g * remapped).clamp(0.0, 255.0) as u8;
+                let b = (l_b * remapped).clamp(0.0, 255.0) as u8;
+                (r, g, b)
+            };
+
+            grayscale.put_pixel(x, y, Rgba([r, g, b, 255]));
+        }
+    }
+
+    DynamicImage::ImageRgba8(grayscale)
+}
+
+fn extract_rgb(color: Color) -> (f32, f32, f32) {
+    match color {
+        Color::Rgb(r, g, b) => (r as f32, g as f32, b as f32),
+        Color::Indexed(idx) => {
+            // Approximate indexed colors based on standard terminal palette
+            // Using darker values to reduce gamma/brightness
+            match idx {
+                0 => (0.0, 0.0, 0.0),        // Black
+                1 => (160.0, 35.0, 35.0),    // Red (darker)
+                2 => (35.0, 160.0, 35.0),    // Green
+                3 => (160.0, 160.0, 0.0),    // Yellow
+                4 => (20.0, 100.0, 200.0),   // Blue (darker dodger blue)
+                5 => (160.0, 70.0, 160.0),   // Magenta
+                6 => (40.0, 170.0, 160.0),   // Cyan (darker turquoise)
+                7 => (180.0, 180.0, 180.0),  // White
+                8 => (90.0, 90.0, 90.0),     // Bright Black (gray)
+                9 => (200.0, 70.0, 50.0),    // Bright Red
+                10 => (100.0, 190.0, 100.0), // Bright Green
+                11 => (200.0, 200.0, 70.0),  // Bright Yellow
+                12 => (80.0, 160.0, 210.0),  // Bright Blue
+                13 => (200.0, 130.0, 200.0), // Bright Magenta
+                14 => (120.0, 190.0, 190.0), // Bright Cyan
+                15 => (220.0, 220.0, 220.0), // Bright White
+                _ => (255.0, 176.0, 0.0),    // Default to amber
+            }
+        }
+        Color::Reset => (180.0, 180.0, 180.0), // Default to light gray
+        _ => (255.0, 176.0, 0.0),              // Default to amber for any other variant
+    }
+}
+
+#[allow(dead_code)]
+fn lerp(a: f32, b: f32, t: f32) -> u8 {
+    (a + (b - a) * t).clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn grid_frames_only_the_selected_cover() {
+        let cover = |shade| {
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+                50,
+                50,
+                Rgba([shade, shade, shade, 255]),
+            ))
+        };
+        let covers = [cover(10), cover(20), cover(30)];
+        let grid = compose_grid(&covers, 2, 1, Color::Rgb(0, 0, 0), Color::Rgb(255, 0, 0));
+        let grid = grid.to_rgba8();
+        let step = GRID_TILE_SIZE + GRID_GAP;
+        assert_eq!(
+            grid.dimensions(),
+            (2 * step + GRID_GAP, 2 * step + GRID_GAP)
+        );
+
+        // Third cover starts the second row
+        assert_eq!(grid.get_pixel(GRID_GAP, GRID_GAP + step)[0], 30);
+        let framed = |x: u32, y: u32| grid.get_pixel(x - 1, y - 1).0 == [255, 0, 0, 255];
+        assert!(framed(GRID_GAP + step, GRID_GAP));
+        assert!(!framed(GRID_GAP, GRID_GAP));
+        // The empty fourth slot is background
+        assert_eq!(
+            grid.get_pixel(GRID_GAP + step, GRID_GAP + step).0,
+            [0, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn album_sized_images_use_the_original_mosaic_block_size() {
+        assert_eq!(pixelation_block_size(600, 600, PIXELATION_BLOCK_SIZE), 8);
+    }
+
+    #[test]
+    fn high_resolution_covers_keep_the_mosaic_proportions() {
+        assert_eq!(pixelation_block_size(1200, 1200, PIXELATION_BLOCK_SIZE), 16);
+        assert_eq!(pixelation_block_size(300, 300, PIXELATION_BLOCK_SIZE), 8);
+    }
+
+    #[test]
+    fn requests_covers_at_the_terminal_resolution() {
+        let itunes = "https://is1-ssl.mzstatic.com/image/thumb/Music/ab/cd/600x600bb.jpg";
+        assert_eq!(
+            ArtworkResolution::High.sized_url(itunes),
+            "https://is1-ssl.mzstatic.com/image/thumb/Music/ab/cd/1200x1200bb.jpg"
+        );
+        let subsonic = "https://music.example.com/rest/getCoverArt?id=al-1&size=600&u=me";
+        assert_eq!(
+            ArtworkResolution::Thumbnail.sized_url(subsonic),
+            "https://music.example.com/rest/getCoverArt?id=al-1&size=300&u=me"
+        );
+        assert_eq!(
+            ArtworkResolution::High.sized_url("file:///tmp/cover.img"),
+            "file:///tmp/cover.img"
+        );
+    }
+
+    #[test]
+    fn pixelation_averages_block_colours_without_filters() {
+        let mut img = RgbaImage::new(PIXELATION_BLOCK_SIZE, PIXELATION_BLOCK_SIZE);
+
+        for y in 0..PIXELATION_BLOCK_SIZE {
+            for x in 0..PIXELATION_BLOCK_SIZE {
+                let color = if x < PIXELATION_BLOCK_SIZE / 2 {
+                    Rgba([0, 0, 0, 255])
+                } else {
+                    Rgba([255, 255, 255, 255])
+                };
+                img.put_pixel(x, y, color);
+            }
+        }
+
+        let pixelated =
+            apply_pixelation(DynamicImage::ImageRgba8(img), &Mosaic::default()).to_rgba8();
+
+        for pixel in pixelated.pixels() {
+            assert_eq!(*pixel, Rgba([128, 128, 128, 255]));
+        }
+    }
+
+    #[test]
+    fn pixelation_preserves_independent_partial_edge_blocks() {
+        let mut img = RgbaImage::new(PIXELATION_BLOCK_SIZE + 1, 1);
+
+        for x in 0..PIXELATION_BLOCK_SIZE {
+            let color = if x < PIXELATION_BLOCK_SIZE / 2 {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            };
+            img.put_pixel(x, 0, color);
+        }
+        img.put_pixel(PIXELATION_BLOCK_SIZE, 0, Rgba([255, 0, 0, 255]));
+
+        let pixelated =
+            apply_pixelation(DynamicImage::ImageRgba8(img), &Mosaic::default()).to_rgba8();
+
+        assert_eq!(*pixelated.get_pixel(0, 0), Rgba([128, 128, 128, 255]));
+        assert_eq!(
+            *pixelated.get_pixel(PIXELATION_BLOCK_SIZE, 0),
+            Rgba([255, 0, 0, 255])
+        );
+    }
+
+    #[test]
+    fn pixelation_preserves_image_dimensions() {
+        let img = RgbaImage::from_pixel(97, 53, Rgba([64, 128, 192, 255]));
+        let pixelated = apply_pixelation(DynamicImage::ImageRgba8(img), &Mosaic::default());
+
+        assert_eq!(pixelated.width(), 97);
+        assert_eq!(pixelated.height(), 53);
+    }
+
+    #[test]
+    fn mosaic_gaps_and_circles_leave_the_background_clear() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(16, 16, Rgba([200, 0, 0, 255])));
+        let clear = Rgba([0, 0, 0, 0]);
+        let lit = Rgba([200, 0, 0, 255]);
+
+        let squares = Mosaic {
+            tile_size: 8,
+            gap: 2,
+            shape: MosaicShape::Square,
+        };
+        let out = apply_pixelation(img.clone(), &squares).to_rgba8();
+        assert_eq!(*out.get_pixel(0, 4), clear);
+        assert_eq!(*out.get_pixel(1, 4), lit);
+        assert_eq!(*out.get_pixel(6, 6), lit);
+        assert_eq!(*out.get_pixel(7, 6), clear);
+
+        let circles = Mosaic {
+            shape: MosaicShape::Circle,
+            ..squares
+        };
+        let out = apply_pixelation(img, &circles).to_rgba8();
+        assert_eq!(*out.get_pixel(1, 1), clear);
+        assert_eq!(*out.get_pixel(1, 4), lit);
+        assert_eq!(*out.get_pixel(4, 4), lit);
+    }
+
+    #[tokio::test]
+    async fn loads_local_file_artwork_sources() {
+        let path = std::env::temp_dir().join("amcli-local-artwork-source-test.png");
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 3, Rgba([1, 2, 3, 255])));
+        let mut bytes = Cursor::new(Vec::new());
+        img.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+        tokio::fs::write(&path, bytes.into_inner()).await.unwrap();
+
+        let loaded = load_artwork_image(
+            &reqwest::Client::new(),
+            &format!("file://{}", path.display()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(loaded.width(), 2);
+        assert_eq!(loaded.height(), 3);
+
+        tokio::fs::remove_file(path).await.ok();
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_for_one_cover_share_a_load() {
+        let path = std::env::temp_dir().join("amcli-shared-artwork-load-test.png");
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba([9, 8, 7, 255])));
+        let mut bytes = Cursor::new(Vec::new());
+        img.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+        tokio::fs::write(&path, bytes.into_inner()).await.unwrap();
+
+        let manager = ArtworkManager::new(std::env::temp_dir().join("amcli-shared-load-cache"));
+        let style = ArtworkStyle {
+            theme_name: "test".into(),
+            dark: Color::Black,
+            light: Color::White,
+            is_retro: false,
+            mosaic: None,
+            crt: None,
+        };
+        let url = format!("file://{}", path.display());
+        // Hold every permit so both requests queue up behind the same load
+        let permits = LOAD_PERMITS
+            .acquire_many(MAX_CONCURRENT_LOADS as u32)
+            .await
+            .unwrap();
+        let requests: Vec<_> = (0..2)
+            .map(|_| {
+                let (manager, url, style) = (manager.clone(), url.clone(), style.clone());
+                tokio::spawn(async move {
+                    manager
+                        .get_artwork_themed_v2(&url, &style, ArtworkResolution::Thumbnail)
+                        .await
+                })
+            })
+            .collect();
+        let waiting = || {
+            let in_flight = manager.in_flight.lock().unwrap();
+            (
+                in_flight.len(),
+                in_flight.values().next().and_then(Shared::strong_count),
+            )
+        };
+        while waiting() != (1, Some(3)) {
+            tokio::task::yield_now().await;
+        }
+        drop(permits);
+
+        for request in requests {
+            assert_eq!(request.await.unwrap().unwrap().width(), 4);
+        }
+        assert!(manager.in_flight.lock().unwrap().is_empty());
+        tokio::fs::remove_file(path).await.ok();
+    }
+
+    #[tokio::test]
+    async fn restyling_a_cover_reuses_the_loaded_source() {
+        let path = std::env::temp_dir().join("amcli-restyled-artwork-test.png");
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba([200, 100, 50, 255])));
+        let mut bytes = Cursor::new(Vec::new());
+        img.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+        tokio::fs::write(&path, bytes.into_inner()).await.unwrap();
+
+        let manager = ArtworkManager::new(std::env::temp_dir().join("amcli-restyle-cache"));
+        let url = format!("file://{}", path.display());
+        let modern = ArtworkStyle {
+            theme_name: "modern".into(),
+            dark: Color::Black,
+            light: Color::White,
+            is_retro: false,
+            mosaic: None,
+            crt: None,
+        };
+        let retro = ArtworkStyle {
+            theme_name: "amber".into(),
+            dark: Color::Rgb(40, 20, 0),
+            light: Color::Rgb(255, 176, 0),
+            is_retro: true,
+            ..modern.clone()
+        };
+        let resolution = ArtworkResolution::Thumbnail;
+        manager
+            .get_artwork_themed_v2(&url, &modern, resolution)
+            .await
+            .unwrap();
+        assert!(manager.cached_artwork(&url, &retro, resolution).is_none());
+
+        // The file is gone, so the retro cover can only come from the cache
+        tokio::fs::remove_file(&path).await.unwrap();
+        let themed = manager
+            .get_artwork_themed_v2(&url, &retro, resolution)
+            .await
+            .unwrap();
+        assert_ne!(themed.to_rgba8(), img.to_rgba8());
+        assert!(manager.cached_artwork(&url, &retro, resolution).is_some());
+        assert!(manager.cached_artwork(&url, &modern, resolution).is_some());
+    }
+}
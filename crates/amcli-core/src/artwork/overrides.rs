@@ -0,0 +1,203 @@
+// src/artwork/overrides.rs
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::player::Track;
+use crate::store::JsonStore;
+
+/// What the user said about one track's cover.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArtworkOverride {
+    /// Shown instead of whatever the player or search finds: a URL or a
+    /// `file://` path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Covers marked wrong; they're never shown for this track again.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rejected: Vec<String>,
+}
+
+/// Per-track artwork corrections, keyed by
+/// [`bookmarks::track_key`](crate::bookmarks::track_key) so they follow
+/// Music's persistent ID rather than the text the search matched on, plus
+/// whole-album images under [`album_key`]. Stored as JSON in amcli's data
+/// directory.
+#[derive(Debug, Default)]
+pub struct ArtworkOverrides {
+    tracks: JsonStore<BTreeMap<String, ArtworkOverride>>,
+}
+
+/// Where an image for every track on `track`'s album is filed. Albums have
+/// no persistent ID of their own, so this is the album artist and title.
+pub fn album_key(track: &Track) -> String {
+    format!("album\u{1f}{}\u{1f}{}", track.artist, track.album)
+}
+
+/// An image the user named, ready to store: URLs are kept as they are, and
+/// files must exist and become absolute `file://` URLs.
+pub fn image_source(input: &str) -> Result<String> {
+    if ["http://", "https://", "file://"]
+        .iter()
+        .any(|scheme| input.starts_with(scheme))
+    {
+        return Ok(input.to_string());
+    }
+    let path = crate::playlist::expand_home(input);
+    let path = std::fs::canonicalize(&path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    if !path.is_file() {
+        bail!("{} is not a file", path.display());
+    }
+    Ok(format!("file://{}", path.display()))
+}
+
+impl ArtworkOverrides {
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("amcli/artwork-overrides.json")
+    }
+
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        Ok(Self {
+            tracks: JsonStore::load(path).await?,
+        })
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        self.tracks.save().await
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ArtworkOverride> {
+        self.tracks.get(key)
+    }
+
+    /// The image picked for the track, or else for its album, unless it
+    /// was marked wrong for this track.
+    pub fn chosen(&self, key: &str, album_key: &str) -> Option<String> {
+        let track = self.tracks.get(key);
+        track.and_then(|entry| entry.image.clone()).or_else(|| {
+            self.tracks
+                .get(album_key)
+                .and_then(|entry| entry.image.clone())
+                .filter(|url| !is_rejected(track, url))
+        })
+    }
+
+    /// The cover to show given what the player `found`: the chosen image if
+    /// there is one, otherwise `found` unless it was marked wrong.
+    pub fn resolve(&self, key: &str, album_key: &str, found: Option<String>) -> Option<String> {
+        self.chosen(key, album_key)
+            .or_else(|| found.filter(|url| !is_rejected(self.tracks.get(key), url)))
+    }
+
+    /// Marks `url` wrong for the track, dropping it as the chosen image.
+    pub fn reject(&mut self, key: &str, url: &str) {
+        let entry = self.tracks.entry(key.to_string()).or_default();
+        if entry.image.as_deref() == Some(url) {
+            entry.image = None;
+        }
+        if !entry.rejected.iter().any(|rejected| rejected == url) {
+            entry.rejected.push(url.to_string());
+        }
+    }
+
+    pub fn set_image(&mut self, key: &str, url: &str) {
+        self.tracks.entry(key.to_string()).or_default().image = Some(url.to_string());
+    }
+
+    /// Forgets everything said about `key`, returning whether there was
+    /// anything.
+    pub fn clear(&mut self, key: &str) -> bool {
+        self.tracks.remove(key).is_some()
+    }
+}
+
+fn is_rejected(entry: Option<&ArtworkOverride>, url: &str) -> bool {
+    entry.is_some_and(|entry| entry.rejected.iter().any(|rejected| rejected == url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rejected_covers_stay_hidden_and_chosen_ones_win() {
+        let path = std::env::temp_dir().join(format!(
+            "amcli-artwork-overrides-{}.json",
+            std::process::id()
+        ));
+        let mut overrides = ArtworkOverrides::load(path.clone()).await.unwrap();
+        let found = || Some("https://itunes/wrong.jpg".to_string());
+        assert_eq!(overrides.resolve("ID1", "ALBUM", found()), found());
+
+        overrides.reject("ID1", "https://itunes/wrong.jpg");
+        assert_eq!(overrides.resolve("ID1", "ALBUM", found()), None);
+        assert_eq!(
+            overrides.resolve("ID1", "ALBUM", Some("file:///right.jpg".into())),
+            Some("file:///right.jpg".into())
+        );
+        // Other tracks with the same search result are untouched
+        assert_eq!(overrides.resolve("ID2", "ALBUM", found()), found());
+
+        overrides.set_image("ID1", "https://itunes/second.jpg");
+        overrides.save().await.unwrap();
+        let reloaded = ArtworkOverrides::load(path.clone()).await.unwrap();
+        assert_eq!(
+            reloaded.resolve("ID1", "ALBUM", found()),
+            Some("https://itunes/second.jpg".into())
+        );
+
+        // Rejecting the chosen image falls back to the player again
+        overrides.reject("ID1", "https://itunes/second.jpg");
+        assert_eq!(overrides.resolve("ID1", "ALBUM", None), None);
+        assert_eq!(overrides.get("ID1").unwrap().rejected.len(), 2);
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[test]
+    fn album_images_cover_every_track_but_defer_to_the_track() {
+        let mut overrides = ArtworkOverrides::default();
+        overrides.set_image("ALBUM", "file:///album.png");
+        assert_eq!(
+            overrides.chosen("ID1", "ALBUM"),
+            Some("file:///album.png".into())
+        );
+        assert_eq!(
+            overrides.resolve("ID2", "ALBUM", Some("https://found".into())),
+            Some("file:///album.png".into())
+        );
+
+        overrides.set_image("ID1", "file:///track.png");
+        assert_eq!(
+            overrides.chosen("ID1", "ALBUM"),
+            Some("file:///track.png".into())
+        );
+        // Marked wrong for one track, the album image stays on the others
+        overrides.reject("ID2", "file:///album.png");
+        assert_eq!(overrides.chosen("ID2", "ALBUM"), None);
+        assert_eq!(
+            overrides.chosen("ID3", "ALBUM"),
+            Some("file:///album.png".into())
+        );
+
+        assert!(overrides.clear("ALBUM"));
+        assert!(!overrides.clear("ALBUM"));
+    }
+
+    #[test]
+    fn named_images_must_be_urls_or_existing_files() {
+        assert_eq!(
+            image_source("https://example.com/cover.jpg").unwrap(),
+            "https://example.com/cover.jpg"
+        );
+        let file = std::env::temp_dir().join(format!("amcli-cover-{}.png", std::process::id()));
+        std::fs::write(&file, b"png").unwrap();
+        let source = image_source(&file.to_string_lossy()).unwrap();
+        assert!(source.starts_with("file:///"));
+        assert!(image_source("/nonexistent/cover.png").is_err());
+        assert!(image_source(&std::env::temp_dir().to_string_lossy()).is_err());
+        std::fs::remove_file(&file).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+// src/artwork/recent.rs
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::player::Track;
+use crate::store::JsonStore;
+
+// About four rows of the idle grid on a wide terminal
+const MAX_RECENT_ALBUMS: usize = 16;
+
+/// An album that was played, with the cover it was shown with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentAlbum {
+    pub artist: String,
+    pub album: String,
+    pub artwork_url: String,
+}
+
+/// Albums played lately, newest first, for the idle album grid. Stored as
+/// JSON in amcli's data directory.
+#[derive(Debug, Default)]
+pub struct RecentAlbums {
+    albums: JsonStore<Vec<RecentAlbum>>,
+}
+
+impl RecentAlbums {
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("amcli/recent-albums.json")
+    }
+
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        Ok(Self {
+            albums: JsonStore::load(path).await?,
+        })
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        self.albums.save().await
+    }
+
+    pub fn albums(&self) -> &[RecentAlbum] {
+        &self.albums
+    }
+
+    /// Moves `track`'s album to the front with `artwork_url` as its cover.
+    /// Returns whether anything changed; singles without an album name are
+    /// skipped.
+    pub fn record(&mut self, track: &Track, artwork_url: &str) -> bool {
+        if track.album.is_empty() {
+            return false;
+        }
+        let album = RecentAlbum {
+            artist: track.artist.clone(),
+            album: track.album.clone(),
+            artwork_url: artwork_url.to_string(),
+        };
+        if self.albums.first() == Some(&album) {
+            return false;
+        }
+        self.albums
+            .retain(|seen| seen.artist != album.artist || seen.album != album.album);
+        self.albums.insert(0, album);
+        self.albums.truncate(MAX_RECENT_ALBUMS);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn track(artist: &str, album: &str) -> Track {
+        Track {
+            name: "Song".into(),
+            artist: artist.into(),
+            album: album.into(),
+            duration: Duration::from_secs(200),
+            position: Duration::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn albums_move_to_the_front_and_persist() {
+        let path =
+            std::env::temp_dir().join(format!("amcli-recent-albums-{}.json", std::process::id()));
+        let mut recent = RecentAlbums::load(path.clone()).await.unwrap();
+        assert!(recent.record(&track("A", "One"), "https://a/1.jpg"));
+        assert!(recent.record(&track("B", "Two"), "https://b/2.jpg"));
+        // Another track off the same album
+        assert!(!recent.record(&track("B", "Two"), "https://b/2.jpg"));
+        assert!(!recent.record(&track("C", ""), "https://c/3.jpg"));
+        assert!(recent.record(&track("A", "One"), "file:///picked.jpg"));
+
+        let names: Vec<_> = recent.albums().iter().map(|a| a.album.as_str()).collect();
+        assert_eq!(names, ["One", "Two"]);
+        assert_eq!(recent.albums()[0].artwork_url, "file:///picked.jpg");
+
+        for i in 0..MAX_RECENT_ALBUMS {
+            recent.record(&track("D", &i.to_string()), "https://d.jpg");
+        }
+        assert_eq!(recent.albums().len(), MAX_RECENT_ALBUMS);
+
+        recent.save().await.unwrap();
+        let reloaded = RecentAlbums::load(path.clone()).await.unwrap();
+        assert_eq!(reloaded.albums(), recent.albums());
+        let _ = std::fs::remove_file(path);
+    }
+}
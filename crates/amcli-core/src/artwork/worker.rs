@@ -0,0 +1,59 @@
+// src/artwork/worker.rs
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tokio::sync::{oneshot, Semaphore};
+
+// Decoding and recolouring a large cover can take a core for a noticeable
+// moment; a couple of dedicated threads keep that off the tokio workers that
+// poll the player
+const MAX_THREADS: usize = 2;
+// Jobs submitted but not yet finished; further callers wait their turn
+const MAX_QUEUED_JOBS: usize = 8;
+
+static JOB_SLOTS: Semaphore = Semaphore::const_new(MAX_QUEUED_JOBS);
+
+lazy_static! {
+    static ref POOL: ThreadPool = ThreadPoolBuilder::new()
+        .num_threads(
+            std::thread::available_parallelism()
+                .map_or(1, |n| n.get())
+                .min(MAX_THREADS),
+        )
+        .thread_name(|i| format!("amcli-artwork-{}", i))
+        .panic_handler(|_| tracing::warn!("Artwork worker panicked"))
+        .build()
+        .expect("artwork worker pool");
+}
+
+/// Runs CPU-heavy image work on the artwork pool and waits for it.
+pub(super) async fn run<T, F>(job: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let _slot = JOB_SLOTS.acquire().await?;
+    let (sender, receiver) = oneshot::channel();
+    POOL.spawn(move || {
+        let _ = sender.send(job());
+    });
+    receiver
+        .await
+        .map_err(|_| anyhow!("Artwork worker stopped before finishing"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn jobs_run_on_the_artwork_threads() {
+        let name = run(|| std::thread::current().name().map(str::to_string))
+            .await
+            .unwrap();
+        assert!(name.unwrap().starts_with("amcli-artwork-"));
+
+        let panicked: Result<()> = run(|| panic!("bad cover")).await;
+        assert!(panicked.is_err());
+    }
+}
@@ -0,0 +1,144 @@
+// src/bookmarks.rs
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::player::Track;
+use crate::store::JsonStore;
+
+/// A named position within a track.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    #[serde(with = "seconds")]
+    pub position: Duration,
+}
+
+/// Bookmarks for every track, keyed by [`track_key`] and kept in position
+/// order. Stored as JSON in amcli's data directory.
+#[derive(Debug, Default)]
+pub struct Bookmarks {
+    tracks: JsonStore<BTreeMap<String, Vec<Bookmark>>>,
+}
+
+/// The player's ID for the track (Music's persistent ID, a file path) when it
+/// has one, so bookmarks survive retagging; otherwise its metadata.
+pub fn track_key(id: Option<&str>, track: &Track) -> String {
+    match id.filter(|id| !id.is_empty()) {
+        Some(id) => id.to_string(),
+        None => format!("{}\u{1f}{}\u{1f}{}", track.artist, track.album, track.name),
+    }
+}
+
+impl Bookmarks {
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("amcli/bookmarks.json")
+    }
+
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        Ok(Self {
+            tracks: JsonStore::load(path).await?,
+        })
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        self.tracks.save().await
+    }
+
+    pub fn for_track(&self, key: &str) -> &[Bookmark] {
+        self.tracks.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Replaces any bookmark of the same name.
+    pub fn add(&mut self, key: &str, bookmark: Bookmark) {
+        let bookmarks = self.tracks.entry(key.to_string()).or_default();
+        bookmarks.retain(|b| b.name != bookmark.name);
+        let at = bookmarks.partition_point(|b| b.position <= bookmark.position);
+        bookmarks.insert(at, bookmark);
+    }
+
+    /// Removes the named bookmark, returning it.
+    pub fn remove(&mut self, key: &str, name: &str) -> Option<Bookmark> {
+        let bookmarks = self.tracks.get_mut(key)?;
+        let index = bookmarks.iter().position(|b| b.name == name)?;
+        let removed = bookmarks.remove(index);
+        if bookmarks.is_empty() {
+            self.tracks.remove(key);
+        }
+        Some(removed)
+    }
+
+    /// The first bookmark after `position`.
+    pub fn next(&self, key: &str, position: Duration) -> Option<&Bookmark> {
+        self.for_track(key).iter().find(|b| b.position > position)
+    }
+
+    /// The last bookmark before `position`.
+    pub fn previous(&self, key: &str, position: Duration) -> Option<&Bookmark> {
+        self.for_track(key)
+            .iter()
+            .rev()
+            .find(|b| b.position < position)
+    }
+}
+
+// Positions are stored as fractional seconds
+mod seconds {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(value.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_secs_f64(
+            f64::deserialize(deserializer)?.max(0.0),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(name: &str, secs: u64) -> Bookmark {
+        Bookmark {
+            name: name.into(),
+            position: Duration::from_secs(secs),
+        }
+    }
+
+    #[tokio::test]
+    async fn bookmarks_stay_sorted_and_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("amcli-bookmarks-{}.json", std::process::id()));
+        let mut bookmarks = Bookmarks::load(path.clone()).await.unwrap();
+        bookmarks.add("mix", bookmark("drop", 1800));
+        bookmarks.add("mix", bookmark("intro", 30));
+        bookmarks.add("mix", bookmark("outro", 3400));
+        bookmarks.add("mix", bookmark("drop", 1750));
+        assert_eq!(
+            bookmarks.next("mix", Duration::from_secs(30)),
+            Some(&bookmark("drop", 1750))
+        );
+        assert_eq!(bookmarks.previous("mix", Duration::from_secs(30)), None);
+        assert_eq!(
+            bookmarks.remove("mix", "outro"),
+            Some(bookmark("outro", 3400))
+        );
+        bookmarks.save().await.unwrap();
+
+        let loaded = Bookmarks::load(path.clone()).await.unwrap();
+        assert_eq!(
+            loaded.for_track("mix"),
+            [bookmark("intro", 30), bookmark("drop", 1750)]
+        );
+        assert!(loaded.for_track("other").is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+}
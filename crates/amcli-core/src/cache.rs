@@ -0,0 +1,178 @@
+// src/cache.rs
+use crate::config::GeneralConfig;
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Overrides the cache root, ahead of `general.cache_dir`.
+pub const ENV_VAR: &str = "AMCLI_CACHE_DIR";
+
+// What lives under the root, by the name `amcli cache` uses for it. Only
+// these are ever cleared, so a root pointed at a shared folder stays safe.
+const CATEGORIES: [(&str, &str); 3] = [
+    ("artwork", "artwork"),
+    ("updates", "update-check.json"),
+    ("library", "library.json"),
+];
+
+// Set once at startup, like the network settings, so callers that build
+// their own paths don't need the config
+static CONFIGURED: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Applies `general.cache_dir`. Call it before creating the artwork manager
+/// or the updater.
+pub fn configure(config: &GeneralConfig) {
+    let dir = config
+        .cache_dir
+        .as_deref()
+        .map(crate::playlist::expand_home);
+    *CONFIGURED.write().unwrap_or_else(|e| e.into_inner()) = dir;
+}
+
+/// Where amcli caches things: `$AMCLI_CACHE_DIR`, then `general.cache_dir`,
+/// then `amcli` in the platform cache directory (`$XDG_CACHE_HOME` or
+/// `~/.cache` on Linux, `~/Library/Caches` on macOS).
+pub fn root() -> PathBuf {
+    let configured = CONFIGURED.read().unwrap_or_else(|e| e.into_inner()).clone();
+    resolve(
+        std::env::var_os(ENV_VAR).map(PathBuf::from),
+        configured,
+        dirs::cache_dir(),
+    )
+}
+
+fn resolve(
+    env: Option<PathBuf>,
+    configured: Option<PathBuf>,
+    platform: Option<PathBuf>,
+) -> PathBuf {
+    env.filter(|dir| !dir.as_os_str().is_empty())
+        .or(configured)
+        .unwrap_or_else(|| platform.unwrap_or_else(std::env::temp_dir).join("amcli"))
+}
+
+/// Themed and downloaded cover art.
+pub fn artwork_dir() -> PathBuf {
+    root().join(CATEGORIES[0].1)
+}
+
+/// The last answer from the GitHub release check.
+pub fn update_check_path() -> PathBuf {
+    root().join(CATEGORIES[1].1)
+}
+
+/// The player's playlists and albums, for the quick switcher.
+pub fn library_index_path() -> PathBuf {
+    root().join(CATEGORIES[2].1)
+}
+
+/// Disk usage of one cache category.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usage {
+    pub category: &'static str,
+    pub path: PathBuf,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// How much each category takes up under `root`.
+pub fn usage(root: &Path) -> Vec<Usage> {
+    CATEGORIES
+        .iter()
+        .map(|&(category, name)| {
+            let path = root.join(name);
+            let (files, bytes) = measure(&path);
+            Usage {
+                category,
+                path,
+                files,
+                bytes,
+            }
+        })
+        .collect()
+}
+
+/// Deletes one category, or all of them for `None`. Returns what was freed.
+pub fn clear(root: &Path, category: Option<&str>) -> Result<Vec<Usage>> {
+    if let Some(category) = category {
+        if !CATEGORIES.iter().any(|&(name, _)| name == category) {
+            let names: Vec<&str> = CATEGORIES.iter().map(|&(name, _)| name).collect();
+            bail!(
+                "Unknown cache category {}; expected one of {}",
+                category,
+                names.join(", ")
+            );
+        }
+    }
+    let mut freed = usage(root);
+    freed.retain(|usage| category.is_none_or(|category| usage.category == category));
+    for usage in &freed {
+        if usage.path.is_dir() {
+            std::fs::remove_dir_all(&usage.path)?;
+        } else if usage.path.exists() {
+            std::fs::remove_file(&usage.path)?;
+        }
+    }
+    Ok(freed)
+}
+
+// (files, bytes), following directories but not symlinks
+fn measure(path: &Path) -> (u64, u64) {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return (0, 0);
+    };
+    if !metadata.is_dir() {
+        return (1, metadata.len());
+    }
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| measure(&entry.path()))
+        .fold((0, 0), |(files, bytes), (f, b)| (files + f, bytes + b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_environment_beats_the_config_which_beats_the_platform() {
+        let path = PathBuf::from;
+        let platform = Some(path("/home/me/.cache"));
+        assert_eq!(
+            resolve(Some(path("/env")), Some(path("/config")), platform.clone()),
+            path("/env")
+        );
+        assert_eq!(
+            resolve(Some(path("")), Some(path("/config")), platform.clone()),
+            path("/config")
+        );
+        assert_eq!(resolve(None, None, platform), path("/home/me/.cache/amcli"));
+    }
+
+    #[test]
+    fn usage_and_clear_stay_within_known_categories() {
+        let root = std::env::temp_dir().join(format!("amcli-cache-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("artwork/themed")).unwrap();
+        std::fs::write(root.join("artwork/a.png"), [0; 100]).unwrap();
+        std::fs::write(root.join("artwork/themed/b.png"), [0; 50]).unwrap();
+        std::fs::write(root.join("update-check.json"), "{}").unwrap();
+        std::fs::write(root.join("notes.txt"), "not ours").unwrap();
+
+        let stats = usage(&root);
+        assert_eq!((stats[0].files, stats[0].bytes), (2, 150));
+        assert_eq!((stats[1].files, stats[1].bytes), (1, 2));
+
+        assert!(clear(&root, Some("lyrics")).is_err());
+        let freed = clear(&root, Some("artwork")).unwrap();
+        assert_eq!(freed.len(), 1);
+        assert!(!root.join("artwork").exists());
+        assert!(root.join("update-check.json").exists());
+
+        clear(&root, None).unwrap();
+        assert!(!root.join("update-check.json").exists());
+        assert!(root.join("notes.txt").exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
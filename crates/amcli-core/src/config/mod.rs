@@ -0,0 +1,1319 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    #[serde(rename = "en")]
+    #[default]
+    English,
+    #[serde(rename = "jp")]
+    Japanese,
+}
+
+impl Language {
+    #[allow(dead_code)]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Japanese => "jp",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            Language::English => Language::Japanese,
+            Language::Japanese => Language::English,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClockFormat {
+    #[serde(rename = "off")]
+    Off,
+    #[serde(rename = "auto")]
+    #[default]
+    Auto,
+    #[serde(rename = "12h")]
+    TwelveHour,
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+}
+
+// Colors the terminal can show. Auto checks COLORTERM and friends; on
+// 256-color terminals themes switch to fallback palettes.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    #[serde(rename = "auto")]
+    #[default]
+    Auto,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+}
+
+// Dot shape of the mosaic effect
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MosaicShape {
+    #[serde(rename = "square")]
+    #[default]
+    Square,
+    #[serde(rename = "circle")]
+    Circle,
+}
+
+impl MosaicShape {
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Square => Self::Circle,
+            Self::Circle => Self::Square,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Square => "Square",
+            Self::Circle => "Circle",
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerBackend {
+    #[serde(rename = "apple_music")]
+    #[default]
+    AppleMusic,
+    // Episodes playing in the Podcasts app
+    #[serde(rename = "podcasts")]
+    Podcasts,
+    // Plays local files directly; needs the `internal-player` build feature
+    #[serde(rename = "internal")]
+    Internal,
+    // Streams from a Subsonic-compatible server through the internal engine
+    #[serde(rename = "subsonic")]
+    Subsonic,
+    // Runs as a Spotify Connect device; needs the `spotify` build feature
+    #[serde(rename = "spotify")]
+    Spotify,
+    // Shows whatever app owns macOS Now Playing, controlled through media keys
+    #[serde(rename = "now_playing")]
+    NowPlaying,
+    // An external `amcli-player-<name>` executable, see PLUGINS.md
+    #[serde(rename = "plugin")]
+    Plugin,
+    // Whatever is AirPlayed to shairport-sync, read from its metadata pipe
+    #[serde(rename = "shairport")]
+    Shairport,
+}
+
+impl PlayerBackend {
+    const ALL: [PlayerBackend; 7] = [
+        PlayerBackend::AppleMusic,
+        PlayerBackend::Podcasts,
+        PlayerBackend::NowPlaying,
+        PlayerBackend::Internal,
+        PlayerBackend::Subsonic,
+        PlayerBackend::Spotify,
+        PlayerBackend::Shairport,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayerBackend::AppleMusic => "Apple Music",
+            PlayerBackend::Podcasts => "Podcasts",
+            PlayerBackend::NowPlaying => "Now Playing",
+            PlayerBackend::Internal => "Local Files",
+            PlayerBackend::Subsonic => "Subsonic",
+            PlayerBackend::Spotify => "Spotify",
+            PlayerBackend::Plugin => "Plugin",
+            PlayerBackend::Shairport => "AirPlay",
+        }
+    }
+
+    // Whether this build was compiled with the backend's feature
+    pub fn is_available(&self) -> bool {
+        let internal = matches!(self, PlayerBackend::Internal | PlayerBackend::Subsonic);
+        let spotify = *self == PlayerBackend::Spotify;
+        (!internal || cfg!(feature = "internal-player")) && (!spotify || cfg!(feature = "spotify"))
+    }
+
+    // Next backend this build can run, wrapping around
+    pub fn next(&self) -> Self {
+        let start = Self::ALL.iter().position(|b| b == self).unwrap_or(0);
+        (1..=Self::ALL.len())
+            .map(|offset| Self::ALL[(start + offset) % Self::ALL.len()])
+            .find(|backend| backend.is_available())
+            .unwrap_or(*self)
+    }
+}
+
+/// The contents of `~/.config/amcli/config.toml`, or `config.<name>.toml`
+/// for a named profile.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Config {
+    // The profile this was read from and saves back to; `None` for
+    // config.toml. Not stored in the file itself.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    pub artwork: ArtworkConfig,
+    pub ui: UIConfig,
+    #[serde(default)]
+    pub general: GeneralConfig,
+    #[serde(default)]
+    pub keybindings: KeybindingsConfig,
+    #[serde(default)]
+    pub player: PlayerConfig,
+    #[serde(default)]
+    pub lyrics: LyricsConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub artist_info: ArtistInfoConfig,
+    #[serde(default)]
+    pub musickit: MusicKitConfig,
+    #[serde(default)]
+    pub scrobble: ScrobbleConfig,
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    #[serde(default)]
+    pub hotkeys: HotkeysConfig,
+    #[serde(default)]
+    pub startup: StartupConfig,
+    #[serde(default)]
+    pub updates: UpdatesConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub controls: ControlsConfig,
+    #[serde(default)]
+    pub focus: FocusConfig,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alarms: Vec<Alarm>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stations: Vec<Station>,
+}
+
+// What happens when amcli starts, before the first key press
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct StartupConfig {
+    // Start playback when the player is paused
+    #[serde(default)]
+    pub resume: bool,
+    #[serde(default)]
+    pub view: StartupView,
+    // Bring back the theme, queue, big text, and focused panel from last time
+    #[serde(default)]
+    pub restore_session: bool,
+    // Open the player app when it isn't running
+    #[serde(default)]
+    pub launch_player: bool,
+}
+
+// Opt-in, since it's the only request amcli makes on its own behalf
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct UpdatesConfig {
+    // Look for a newer GitHub release at most once a day
+    #[serde(default)]
+    pub check: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ControlsConfig {
+    #[serde(default)]
+    pub longform: LongformConfig,
+}
+
+// Podcast-style skip steps for episodes, audiobooks, and long mixes
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LongformConfig {
+    #[serde(default = "default_longform_enabled")]
+    pub enabled: bool,
+    // Anything at least this long counts, whatever the player says it is
+    #[serde(default = "default_longform_min_minutes")]
+    pub min_minutes: u64,
+    #[serde(default = "default_longform_forward")]
+    pub forward_seconds: u32,
+    #[serde(default = "default_longform_backward")]
+    pub backward_seconds: u32,
+}
+
+impl Default for LongformConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_longform_enabled(),
+            min_minutes: default_longform_min_minutes(),
+            forward_seconds: default_longform_forward(),
+            backward_seconds: default_longform_backward(),
+        }
+    }
+}
+
+// The focus timer `p` starts: work intervals with music, breaks without
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FocusConfig {
+    #[serde(default = "default_focus_work_minutes")]
+    pub work_minutes: u64,
+    #[serde(default = "default_focus_break_minutes")]
+    pub break_minutes: u64,
+    #[serde(default = "default_focus_long_break_minutes")]
+    pub long_break_minutes: u64,
+    // Every this many work intervals the break is a long one; 0 for never
+    #[serde(default = "default_focus_long_break_every")]
+    pub long_break_every: u32,
+    // Playlist to start for work; without one, play resumes whatever is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_playlist: Option<String>,
+    // Playlist to switch to for breaks; without one, breaks pause
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub break_playlist: Option<String>,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: default_focus_work_minutes(),
+            break_minutes: default_focus_break_minutes(),
+            long_break_minutes: default_focus_long_break_minutes(),
+            long_break_every: default_focus_long_break_every(),
+            work_playlist: None,
+            break_playlist: None,
+        }
+    }
+}
+
+// The same timings the F12 overlay shows, for scraping
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct MetricsConfig {
+    // Address to serve Prometheus metrics on, e.g. "127.0.0.1:9464"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prometheus: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum StartupView {
+    #[serde(rename = "default")]
+    #[default]
+    Default,
+    // Focus on the lyrics panel
+    #[serde(rename = "lyrics")]
+    Lyrics,
+    // Up Next open and focused
+    #[serde(rename = "queue")]
+    Queue,
+}
+
+// Where the artist panel gets its biography
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ArtistInfoConfig {
+    #[serde(default)]
+    pub source: ArtistInfoSource,
+    // Needed for the Last.fm source: https://www.last.fm/api/account/create
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lastfm_api_key: Option<String>,
+}
+
+// Accounts `amcli history backfill` sends plays to
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ScrobbleConfig {
+    // A user token from https://listenbrainz.org/settings/
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listenbrainz_token: Option<String>,
+    // A Last.fm API account; the key falls back to artist_info.lastfm_api_key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lastfm_api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lastfm_api_secret: Option<String>,
+    // Saved by `amcli lastfm-login`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lastfm_session_key: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ArtistInfoSource {
+    #[serde(rename = "wikipedia")]
+    #[default]
+    Wikipedia,
+    #[serde(rename = "lastfm")]
+    LastFm,
+    // Apple's editorial notes; needs `[musickit] developer_token`
+    #[serde(rename = "apple_music")]
+    AppleMusic,
+}
+
+// Apple Music catalog access, for lookups AppleScript can't do
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MusicKitConfig {
+    // A MusicKit developer token (a signed JWT) from an Apple Developer account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub developer_token: Option<String>,
+    // Two-letter catalog region, e.g. "us" or "jp"
+    #[serde(default = "default_storefront")]
+    pub storefront: String,
+}
+
+fn default_storefront() -> String {
+    "us".into()
+}
+
+impl Default for MusicKitConfig {
+    fn default() -> Self {
+        Self {
+            developer_token: None,
+            storefront: default_storefront(),
+        }
+    }
+}
+
+// Now-playing publisher for Home Assistant and other MQTT consumers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MqttConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_mqtt_host")]
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    // "playing", "paused", or "stopped"
+    #[serde(default = "default_mqtt_state_topic")]
+    pub state_topic: String,
+    // Title, artist, album, and duration as JSON
+    #[serde(default = "default_mqtt_track_topic")]
+    pub track_topic: String,
+    #[serde(default = "default_mqtt_artwork_topic")]
+    pub artwork_topic: String,
+    // "online" while amcli runs; the broker sets "offline" if it drops away
+    #[serde(default = "default_mqtt_availability_topic")]
+    pub availability_topic: String,
+}
+
+fn default_mqtt_host() -> String {
+    "localhost".into()
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "amcli".into()
+}
+
+fn default_mqtt_state_topic() -> String {
+    "amcli/state".into()
+}
+
+fn default_mqtt_track_topic() -> String {
+    "amcli/track".into()
+}
+
+fn default_mqtt_artwork_topic() -> String {
+    "amcli/artwork".into()
+}
+
+fn default_mqtt_availability_topic() -> String {
+    "amcli/availability".into()
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_mqtt_host(),
+            port: default_mqtt_port(),
+            username: None,
+            password: None,
+            client_id: default_mqtt_client_id(),
+            state_topic: default_mqtt_state_topic(),
+            track_topic: default_mqtt_track_topic(),
+            artwork_topic: default_mqtt_artwork_topic(),
+            availability_topic: default_mqtt_availability_topic(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct NetworkConfig {
+    // Skip iTunes artwork search, remote covers, and online lyrics
+    #[serde(default)]
+    pub offline: bool,
+    // http://, https://, socks5://, or socks5h:// proxy for all requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    // Overrides each client's own request timeout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerConfig {
+    #[serde(default)]
+    pub backend: PlayerBackend,
+    // Folder (or single file) the internal player queues; defaults to ~/Music
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub music_dir: Option<PathBuf>,
+    // Folders the file browser starts from; defaults to `music_dir`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub browse_roots: Vec<PathBuf>,
+    // Seconds the internal player overlaps one track's end with the next's
+    // start; 0 plays them back to back, gaplessly
+    #[serde(default)]
+    pub crossfade_secs: u8,
+    // Which ReplayGain (or Opus R128) tag evens out loudness in the internal
+    // player; `V` switches it on and off while playing
+    #[serde(default)]
+    pub replay_gain: ReplayGainMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subsonic: Option<SubsonicConfig>,
+    #[serde(default)]
+    pub spotify: SpotifyConfig,
+    #[serde(default)]
+    pub shairport: ShairportConfig,
+    // Name of the plugin backend, i.e. `amcli-player-<plugin>` on PATH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+    // Take macOS media keys and remote commands even while unfocused
+    #[serde(default)]
+    pub media_keys: bool,
+    // Register on the Linux session bus as an MPRIS player (internal and
+    // Subsonic backends only)
+    #[serde(default = "default_mpris")]
+    pub mpris: bool,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            backend: PlayerBackend::default(),
+            music_dir: None,
+            browse_roots: Vec::new(),
+            crossfade_secs: 0,
+            replay_gain: ReplayGainMode::default(),
+            subsonic: None,
+            spotify: SpotifyConfig::default(),
+            shairport: ShairportConfig::default(),
+            plugin: None,
+            media_keys: false,
+            mpris: default_mpris(),
+        }
+    }
+}
+
+fn default_mpris() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubsonicConfig {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SpotifyConfig {
+    // Name shown in other Spotify clients' device pickers
+    #[serde(default = "default_spotify_device_name")]
+    pub device_name: String,
+}
+
+impl Default for SpotifyConfig {
+    fn default() -> Self {
+        Self {
+            device_name: default_spotify_device_name(),
+        }
+    }
+}
+
+fn default_spotify_device_name() -> String {
+    "amcli".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShairportConfig {
+    // The `pipe_name` from shairport-sync.conf's `metadata` section
+    #[serde(default = "default_shairport_metadata_pipe")]
+    pub metadata_pipe: PathBuf,
+}
+
+impl Default for ShairportConfig {
+    fn default() -> Self {
+        Self {
+            metadata_pipe: default_shairport_metadata_pipe(),
+        }
+    }
+}
+
+fn default_shairport_metadata_pipe() -> PathBuf {
+    PathBuf::from("/tmp/shairport-sync-metadata")
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LyricsConfig {
+    // Directories of .lrc/.txt files, searched in order before any download
+    #[serde(default = "default_lyrics_local_paths")]
+    pub local_paths: Vec<String>,
+    // External providers, see PLUGINS.md
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<LyricsPluginConfig>,
+    #[serde(default)]
+    pub show_next: ShowNextConfig,
+}
+
+fn default_lyrics_local_paths() -> Vec<String> {
+    vec!["~/Music/Lyrics".into()]
+}
+
+impl Default for LyricsConfig {
+    fn default() -> Self {
+        Self {
+            local_paths: default_lyrics_local_paths(),
+            plugins: Vec::new(),
+            show_next: ShowNextConfig::default(),
+        }
+    }
+}
+
+// Calls out the upcoming lyric line so singers can read ahead
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ShowNextConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub style: ShowNextStyle,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ShowNextStyle {
+    // Brighter than the other upcoming lines, in place
+    #[serde(rename = "highlight")]
+    #[default]
+    Highlight,
+    // Repeated in a "NEXT:" line under the lyrics
+    #[serde(rename = "slot")]
+    Slot,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LyricsPluginConfig {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    // Lower runs first; the built-in providers use 5 (Netease) and 10 (LRCLIB)
+    #[serde(default = "default_lyrics_plugin_priority")]
+    pub priority: u8,
+}
+
+fn default_lyrics_plugin_priority() -> u8 {
+    20
+}
+
+// Action name -> key sequences, e.g. `go_to_start = ["g g"]` or
+// `settings = ["<leader> s"]`. Listed actions replace their default keys.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeybindingsConfig {
+    #[serde(default = "default_leader")]
+    pub leader: String,
+    #[serde(default = "default_chord_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(flatten)]
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for KeybindingsConfig {
+    fn default() -> Self {
+        Self {
+            leader: default_leader(),
+            timeout_ms: default_chord_timeout_ms(),
+            bindings: BTreeMap::new(),
+        }
+    }
+}
+
+// System-wide shortcuts, e.g. `next = "ctrl+alt+right"`. Listing any
+// replaces the whole default set; an empty string turns one off.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct HotkeysConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub bindings: BTreeMap<String, String>,
+}
+
+// `[[stations]]`: internet radio for the internal player, picked with `w` or
+// added with `:station add`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Station {
+    pub name: String,
+    // The stream itself, or a .pls / .m3u playlist pointing at it
+    pub url: String,
+}
+
+// `[[alarms]]`: start or pause playback at a time of day
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Alarm {
+    // "HH:MM", local time
+    pub at: String,
+    #[serde(default)]
+    pub action: AlarmAction,
+    // Playlist to start; without one, play resumes whatever is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playlist: Option<String>,
+    // "mon" through "sun"; empty means every day
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<String>,
+    #[serde(default = "default_alarm_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayGainMode {
+    Off,
+    // Every track at the same loudness
+    #[default]
+    Track,
+    // Whole albums at the same loudness, keeping the level changes between
+    // their tracks; tracks without album gain fall back to their own
+    Album,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlarmAction {
+    #[default]
+    Play,
+    Pause,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GeneralConfig {
+    #[serde(default)]
+    pub language: Language,
+    // Cache root; `AMCLI_CACHE_DIR` overrides it, see `cache::root`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<String>,
+    // Log each track as it starts, for `amcli session export`
+    #[serde(default = "default_play_history")]
+    pub play_history: bool,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self {
+            language: Language::English,
+            cache_dir: None,
+            play_history: default_play_history(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArtworkConfig {
+    pub enabled: bool,
+    pub cache_size: usize,
+    pub mode: String,
+    #[serde(default = "default_album")]
+    pub album: bool,
+    #[serde(default = "default_mosaic")]
+    pub mosaic: bool,
+    // Mosaic tile and gap sizes in pixels at a 600px cover
+    #[serde(default = "default_mosaic_tile_size")]
+    pub mosaic_tile_size: u32,
+    #[serde(default)]
+    pub mosaic_gap: u32,
+    #[serde(default)]
+    pub mosaic_shape: MosaicShape,
+    // Loop animated covers on terminals with a graphics protocol
+    #[serde(default)]
+    pub animated: bool,
+    #[serde(default)]
+    pub crt: CrtConfig,
+}
+
+// CRT post-processing for artwork under the retro themes. Intensities run
+// from 0.0 (off) to 1.0.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CrtConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_crt_scanlines")]
+    pub scanlines: f32,
+    #[serde(default = "default_crt_glow")]
+    pub glow: f32,
+    #[serde(default = "default_crt_curvature")]
+    pub curvature: f32,
+    #[serde(default = "default_crt_noise")]
+    pub noise: f32,
+}
+
+impl Default for CrtConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scanlines: default_crt_scanlines(),
+            glow: default_crt_glow(),
+            curvature: default_crt_curvature(),
+            noise: default_crt_noise(),
+        }
+    }
+}
+
+fn default_crt_scanlines() -> f32 {
+    0.5
+}
+
+fn default_crt_glow() -> f32 {
+    0.4
+}
+
+fn default_crt_curvature() -> f32 {
+    0.3
+}
+
+fn default_crt_noise() -> f32 {
+    0.15
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UIConfig {
+    pub color_theme: String,
+    pub show_help_on_start: bool,
+    #[serde(default = "default_boot_splash")]
+    pub boot_splash: bool,
+    #[serde(default)]
+    pub clock: ClockFormat,
+    // Disables marquees, blinking, throbbers, scanlines, and the boot splash
+    #[serde(default)]
+    pub reduced_motion: bool,
+    // Fast MM:SS.d position readout on themes that support it
+    #[serde(default = "default_ticker")]
+    pub ticker: bool,
+    // Where the sound is going (AirPlay speakers or the output device) on
+    // the progress bar
+    #[serde(default = "default_show_output_device")]
+    pub show_output_device: bool,
+    #[serde(default)]
+    pub color_depth: ColorDepth,
+    // Current lyric (or track title) in block letters across the whole screen
+    #[serde(default)]
+    pub big_text: bool,
+    // Covers of recently played albums instead of "INSERT TAPE OR DISC"
+    // while nothing is playing
+    #[serde(default)]
+    pub idle_grid: bool,
+    // Let the terminal's own background show through, for every theme or the
+    // listed ones
+    #[serde(default)]
+    pub transparent_background: TransparentBackground,
+    // Day/night theme switching; absent means the theme only changes by hand
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<ThemeSchedule>,
+    // Themes saved from the theme editor, offered after the built-in ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_themes: Vec<CustomTheme>,
+}
+
+/// `true`/`false` for all themes, or a list of theme names.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TransparentBackground {
+    All(bool),
+    Themes(Vec<String>),
+}
+
+impl Default for TransparentBackground {
+    fn default() -> Self {
+        Self::All(false)
+    }
+}
+
+impl TransparentBackground {
+    pub fn applies_to(&self, theme: &str) -> bool {
+        match self {
+            Self::All(enabled) => *enabled,
+            Self::Themes(names) => names.iter().any(|n| n.eq_ignore_ascii_case(theme)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ThemeSchedule {
+    pub day: String,
+    pub night: String,
+    // "HH:MM", local time
+    #[serde(default = "default_day_start")]
+    pub day_start: String,
+    #[serde(default = "default_night_start")]
+    pub night_start: String,
+    // Follow the macOS light/dark appearance instead of the clock
+    #[serde(default)]
+    pub follow_system: bool,
+}
+
+fn default_day_start() -> String {
+    "07:00".into()
+}
+
+fn default_night_start() -> String {
+    "19:00".into()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CustomTheme {
+    pub name: String,
+    pub primary: HexColor,
+    pub dim: HexColor,
+    pub accent: HexColor,
+    pub alert: HexColor,
+    pub bg: HexColor,
+    // Chassis, VFD glow, and artwork duotone of the built-in retro themes
+    #[serde(default)]
+    pub retro: bool,
+}
+
+/// An RGB color written as `"#rrggbb"` in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HexColor(pub [u8; 3]);
+
+impl TryFrom<String> for HexColor {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        let hex = value.strip_prefix('#').unwrap_or(&value);
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(anyhow!("Expected a #rrggbb color, got {:?}", value));
+        }
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| anyhow!("Expected a #rrggbb color, got {:?}", value))
+        };
+        Ok(Self([channel(0)?, channel(2)?, channel(4)?]))
+    }
+}
+
+impl From<HexColor> for String {
+    fn from(color: HexColor) -> Self {
+        let [r, g, b] = color.0;
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+fn default_album() -> bool {
+    true
+}
+
+fn default_mosaic() -> bool {
+    true
+}
+
+fn default_mosaic_tile_size() -> u32 {
+    8
+}
+
+fn default_ticker() -> bool {
+    true
+}
+
+fn default_play_history() -> bool {
+    true
+}
+
+fn default_show_output_device() -> bool {
+    true
+}
+
+fn default_boot_splash() -> bool {
+    true
+}
+
+fn default_alarm_enabled() -> bool {
+    true
+}
+
+fn default_longform_enabled() -> bool {
+    true
+}
+
+fn default_longform_min_minutes() -> u64 {
+    20
+}
+
+fn default_longform_forward() -> u32 {
+    30
+}
+
+fn default_longform_backward() -> u32 {
+    15
+}
+
+fn default_focus_work_minutes() -> u64 {
+    25
+}
+
+fn default_focus_break_minutes() -> u64 {
+    5
+}
+
+fn default_focus_long_break_minutes() -> u64 {
+    15
+}
+
+fn default_focus_long_break_every() -> u32 {
+    4
+}
+
+fn default_leader() -> String {
+    "space".into()
+}
+
+fn default_chord_timeout_ms() -> u64 {
+    600
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            profile: None,
+            artwork: ArtworkConfig {
+                enabled: true,
+                cache_size: 100,
+                mode: "auto".into(),
+                album: true,
+                mosaic: true,
+                mosaic_tile_size: default_mosaic_tile_size(),
+                mosaic_gap: 0,
+                mosaic_shape: MosaicShape::Square,
+                animated: false,
+                crt: CrtConfig::default(),
+            },
+            ui: UIConfig {
+                color_theme: "default".into(),
+                show_help_on_start: true,
+                boot_splash: true,
+                clock: ClockFormat::Auto,
+                reduced_motion: false,
+                ticker: true,
+                show_output_device: true,
+                color_depth: ColorDepth::Auto,
+                big_text: false,
+                idle_grid: false,
+                transparent_background: TransparentBackground::default(),
+                schedule: None,
+                custom_themes: Vec::new(),
+            },
+            general: GeneralConfig::default(),
+            keybindings: KeybindingsConfig::default(),
+            player: PlayerConfig::default(),
+            lyrics: LyricsConfig::default(),
+            network: NetworkConfig::default(),
+            artist_info: ArtistInfoConfig::default(),
+            scrobble: ScrobbleConfig::default(),
+            musickit: MusicKitConfig::default(),
+            mqtt: MqttConfig::default(),
+            hotkeys: HotkeysConfig::default(),
+            startup: StartupConfig::default(),
+            updates: UpdatesConfig::default(),
+            metrics: MetricsConfig::default(),
+            controls: ControlsConfig::default(),
+            focus: FocusConfig::default(),
+            alarms: Vec::new(),
+            stations: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    fn config_dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("amcli")
+    }
+
+    /// Where `profile`'s config lives, creating the directory if needed.
+    pub async fn path(profile: Option<&str>) -> Result<PathBuf> {
+        Self::get_config_path(profile).await
+    }
+
+    async fn get_config_path(profile: Option<&str>) -> Result<PathBuf> {
+        let config_dir = Self::config_dir();
+
+        if !tokio::fs::try_exists(&config_dir).await.unwrap_or(false) {
+            tokio::fs::create_dir_all(&config_dir).await?;
+        }
+
+        Ok(config_dir.join(profile_file_name(profile)?))
+    }
+
+    /// Reads the config file, writing the defaults first if there isn't one.
+    pub async fn load() -> Result<Self> {
+        Self::load_profile(None).await
+    }
+
+    /// Reads `config.<name>.toml`, or `config.toml` for `None`. A profile
+    /// without a file yet starts as a copy of `config.toml`.
+    pub async fn load_profile(profile: Option<&str>) -> Result<Self> {
+        let config_path = Self::get_config_path(profile).await?;
+
+        if tokio::fs::try_exists(&config_path).await.unwrap_or(false) {
+            let content = tokio::fs::read_to_string(config_path).await?;
+            let mut config: Config = toml::from_str(&content)?;
+            config.profile = profile.map(str::to_string);
+            Ok(config)
+        } else {
+            let mut config = match profile {
+                Some(_) => Box::pin(Self::load_profile(None)).await?,
+                None => Config::default(),
+            };
+            config.profile = profile.map(str::to_string);
+            config.save().await?;
+            Ok(config)
+        }
+    }
+
+    /// Named profiles with a file in the config directory, sorted.
+    pub async fn profiles() -> Result<Vec<String>> {
+        let mut profiles = Vec::new();
+        let mut entries = match tokio::fs::read_dir(Self::config_dir()).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(profiles),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let profile = name
+                .to_str()
+                .and_then(|n| n.strip_prefix("config."))
+                .and_then(|n| n.strip_suffix(".toml"));
+            if let Some(profile) = profile.filter(|p| profile_file_name(Some(p)).is_ok()) {
+                profiles.push(profile.to_string());
+            }
+        }
+        profiles.sort();
+        Ok(profiles)
+    }
+
+    /// The setting at a dotted key such as `ui.color_theme`, or a whole
+    /// section such as `artwork.crt`.
+    pub fn get(&self, key: &str) -> Result<toml::Value> {
+        let mut value = toml::Value::try_from(self)?;
+        for part in key.split('.') {
+            value = match value {
+                toml::Value::Table(mut table) => table.remove(part),
+                _ => None,
+            }
+            .ok_or_else(|| anyhow!("Unknown setting: {}", key))?;
+        }
+        Ok(value)
+    }
+
+    /// Sets a dotted key from TOML (`false`, `12`, `["a", "b"]`), or from
+    /// plain text for string settings. Settings the schema doesn't know, and
+    /// values of the wrong type, are rejected and leave `self` unchanged.
+    pub fn set(&mut self, key: &str, raw: &str) -> Result<()> {
+        let new_value = toml::from_str::<toml::Table>(&format!("value = {}", raw))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string()));
+
+        let mut root = toml::Value::try_from(&*self)?;
+        let (parents, name) = match key.rsplit_once('.') {
+            Some((parents, name)) => (Some(parents), name),
+            None => (None, key),
+        };
+        let mut table = root
+            .as_table_mut()
+            .ok_or_else(|| anyhow!("Unknown setting: {}", key))?;
+        for part in parents.into_iter().flat_map(|p| p.split('.')) {
+            table = table
+                .get_mut(part)
+                .and_then(toml::Value::as_table_mut)
+                .ok_or_else(|| anyhow!("Unknown setting: {}", key))?;
+        }
+        table.insert(name.to_string(), new_value.clone());
+
+        let mut updated: Config = root
+            .try_into()
+            .map_err(|e| anyhow!("Invalid value for {}: {}", key, e))?;
+        updated.profile = self.profile.clone();
+        // Unknown keys are dropped on the way through rather than rejected
+        if updated.get(key).ok().as_ref() != Some(&new_value) {
+            return Err(anyhow!("Unknown setting: {}", key));
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        let config_path = Self::get_config_path(self.profile.as_deref()).await?;
+        let content = toml::to_string_pretty(self)?;
+        tokio::fs::write(config_path, content).await?;
+        Ok(())
+    }
+}
+
+/// Fails for names [`Config::load_profile`] won't accept.
+pub fn check_profile_name(name: &str) -> Result<()> {
+    profile_file_name(Some(name)).map(|_| ())
+}
+
+// `config.toml`, or `config.<name>.toml` for a profile. Names are kept to
+// letters, digits, `-`, and `_` so they can't reach outside the directory.
+fn profile_file_name(profile: Option<&str>) -> Result<String> {
+    match profile {
+        None => Ok("config.toml".into()),
+        Some(name)
+            if !name.is_empty()
+                && name != "default"
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            Ok(format!("config.{}.toml", name))
+        }
+        Some(name) => Err(anyhow!(
+            "Invalid profile name {:?}: use letters, digits, '-', and '_'",
+            name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_read_and_written_by_dotted_key() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.get("ui.color_theme").unwrap(),
+            toml::Value::String("default".into())
+        );
+        assert!(config.get("artwork.crt").unwrap().is_table());
+
+        config.set("artwork.mosaic", "false").unwrap();
+        assert!(!config.artwork.mosaic);
+        config.set("ui.color_theme", "green vfd").unwrap();
+        assert_eq!(config.ui.color_theme, "green vfd");
+        config
+            .set("network.proxy", "socks5://localhost:1080")
+            .unwrap();
+        assert_eq!(
+            config.network.proxy.as_deref(),
+            Some("socks5://localhost:1080")
+        );
+
+        assert!(config.set("artwork.mosaic", "maybe").is_err());
+        assert!(config.set("ui.colour_theme", "modern").is_err());
+        assert!(config.set("nope.color_theme", "modern").is_err());
+        assert!(config.artwork.mosaic_tile_size > 0);
+        assert!(config.get("ui.colour_theme").is_err());
+    }
+
+    #[test]
+    fn profiles_map_to_their_own_files() {
+        assert_eq!(profile_file_name(None).unwrap(), "config.toml");
+        assert_eq!(profile_file_name(Some("work")).unwrap(), "config.work.toml");
+        assert_eq!(
+            profile_file_name(Some("late-night_2")).unwrap(),
+            "config.late-night_2.toml"
+        );
+        for bad in ["", "default", "../work", "a b", "x.toml"] {
+            assert!(check_profile_name(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn keybindings_round_trip_alongside_leader_settings() {
+        let mut config = Config::default();
+        config
+            .keybindings
+            .bindings
+            .insert("go_to_start".into(), vec!["g g".into()]);
+
+        let content = toml::to_string_pretty(&config).unwrap();
+        let parsed: Config = toml::from_str(&content).unwrap();
+
+        assert_eq!(parsed.keybindings.leader, "space");
+        assert_eq!(parsed.keybindings.bindings["go_to_start"], vec!["g g"]);
+    }
+
+    #[test]
+    fn configs_without_new_sections_still_load() {
+        let content = r#"
+            [artwork]
+            enabled = true
+            cache_size = 100
+            mode = "auto"
+
+            [ui]
+            color_theme = "default"
+            show_help_on_start = true
+        "#;
+        let config: Config = toml::from_str(content).unwrap();
+        assert!(config.keybindings.bindings.is_empty());
+    }
+
+    #[test]
+    fn custom_themes_round_trip_as_hex_colors() {
+        let mut config = Config::default();
+        config.ui.custom_themes.push(CustomTheme {
+            name: "Dusk".into(),
+            primary: HexColor([255, 176, 0]),
+            dim: HexColor([80, 60, 20]),
+            accent: HexColor([255, 215, 0]),
+            alert: HexColor([255, 50, 50]),
+            bg: HexColor([0, 0, 0]),
+            retro: true,
+        });
+
+        let content = toml::to_string_pretty(&config).unwrap();
+        assert!(content.contains(r##"primary = "#ffb000""##));
+        let parsed: Config = toml::from_str(&content).unwrap();
+        assert_eq!(parsed.ui.custom_themes, config.ui.custom_themes);
+
+        assert!(HexColor::try_from("#12345".to_string()).is_err());
+        assert!(HexColor::try_from("#gg0000".to_string()).is_err());
+    }
+
+    #[test]
+    fn transparent_background_takes_a_flag_or_theme_names() {
+        let ui = |value: &str| -> UIConfig {
+            toml::from_str(&format!(
+                "color_theme = \"default\"\nshow_help_on_start = true\ntransparent_background = {}",
+                value
+            ))
+            .unwrap()
+        };
+        assert!(ui("true").transparent_background.applies_to("MODERN"));
+        let listed = ui(r#"["amber vfd"]"#).transparent_background;
+        assert!(listed.applies_to("AMBER VFD"));
+        assert!(!listed.applies_to("MODERN"));
+        assert!(!TransparentBackground::default().applies_to("MODERN"));
+    }
+
+    #[test]
+    fn backend_cycle_skips_backends_missing_from_the_build() {
+        assert_eq!(PlayerBackend::AppleMusic.next(), PlayerBackend::Podcasts);
+        let mut backend = PlayerBackend::NowPlaying;
+        for _ in 0..PlayerBackend::ALL.len() {
+            backend = backend.next();
+            assert!(backend.is_available());
+        }
+        if !cfg!(feature = "internal-player") && !cfg!(feature = "spotify") {
+            assert_eq!(PlayerBackend::NowPlaying.next(), PlayerBackend::Shairport);
+            assert_eq!(PlayerBackend::Shairport.next(), PlayerBackend::AppleMusic);
+        }
+    }
+}
@@ -0,0 +1,368 @@
+// src/diagnostics.rs
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Every `osascript` run, AppleScript and JXA alike.
+pub static OSASCRIPT: Timer = Timer::new();
+/// Themed artwork lookups in [`crate::artwork::ArtworkManager`].
+pub static ARTWORK_CACHE: HitCounter = HitCounter::new();
+/// Lookups in [`crate::lyrics::LyricsManager`]'s cache.
+pub static LYRICS_CACHE: HitCounter = HitCounter::new();
+/// Each lyrics provider's lookups, by provider name.
+pub static LYRICS_PROVIDERS: Registry<Outcomes> = Registry::new();
+/// Status polls, by player backend.
+pub static PLAYER_POLLS: Registry<Timer> = Registry::new();
+
+/// Upper bounds of the latency histogram buckets, in milliseconds. Anything
+/// slower only shows in the total.
+pub const BUCKETS_MS: [u64; 9] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500];
+
+/// Counts calls and keeps the latest, average, and longest duration, plus a
+/// latency histogram. Cheap enough to record on every call.
+#[derive(Debug, Default)]
+pub struct Timer {
+    calls: AtomicU64,
+    total_micros: AtomicU64,
+    last_micros: AtomicU64,
+    max_micros: AtomicU64,
+    buckets: [AtomicU64; BUCKETS_MS.len()],
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimerSummary {
+    pub calls: u64,
+    pub last: Duration,
+    pub average: Duration,
+    pub max: Duration,
+}
+
+impl Timer {
+    pub const fn new() -> Self {
+        Self {
+            calls: AtomicU64::new(0),
+            total_micros: AtomicU64::new(0),
+            last_micros: AtomicU64::new(0),
+            max_micros: AtomicU64::new(0),
+            buckets: [const { AtomicU64::new(0) }; BUCKETS_MS.len()],
+        }
+    }
+
+    pub fn record(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.last_micros.store(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+        if let Some(bucket) = BUCKETS_MS.iter().position(|ms| micros <= ms * 1000) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Calls at or under each of [`BUCKETS_MS`], cumulative like Prometheus
+    /// buckets.
+    pub fn histogram(&self) -> [u64; BUCKETS_MS.len()] {
+        let mut total = 0;
+        let mut cumulative = [0; BUCKETS_MS.len()];
+        for (bucket, count) in self.buckets.iter().zip(cumulative.iter_mut()) {
+            total += bucket.load(Ordering::Relaxed);
+            *count = total;
+        }
+        cumulative
+    }
+
+    fn total(&self) -> Duration {
+        Duration::from_micros(self.total_micros.load(Ordering::Relaxed))
+    }
+
+    pub fn summary(&self) -> TimerSummary {
+        let calls = self.calls.load(Ordering::Relaxed);
+        let total = self.total_micros.load(Ordering::Relaxed);
+        TimerSummary {
+            calls,
+            last: Duration::from_micros(self.last_micros.load(Ordering::Relaxed)),
+            average: Duration::from_micros(total.checked_div(calls).unwrap_or(0)),
+            max: Duration::from_micros(self.max_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Cache hits and misses.
+#[derive(Debug, Default)]
+pub struct HitCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl HitCounter {
+    pub const fn new() -> Self {
+        Self {
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `(hits, lookups)`
+    pub fn counts(&self) -> (u64, u64) {
+        let hits = self.hits.load(Ordering::Relaxed);
+        (hits, hits + self.misses.load(Ordering::Relaxed))
+    }
+}
+
+/// How a lookup against a remote source ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Found,
+    Missing,
+    // Errors and timeouts
+    Failed,
+}
+
+/// Results and latency of one source's lookups.
+#[derive(Debug, Default)]
+pub struct Outcomes {
+    pub latency: Timer,
+    found: AtomicU64,
+    missing: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl Outcomes {
+    pub fn record(&self, outcome: Outcome, elapsed: Duration) {
+        self.latency.record(elapsed);
+        let counter = match outcome {
+            Outcome::Found => &self.found,
+            Outcome::Missing => &self.missing,
+            Outcome::Failed => &self.failed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `(found, missing, failed)`
+    pub fn counts(&self) -> (u64, u64, u64) {
+        (
+            self.found.load(Ordering::Relaxed),
+            self.missing.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// One metric per name (provider, backend), created on first use.
+#[derive(Debug)]
+pub struct Registry<T> {
+    entries: Mutex<BTreeMap<String, Arc<T>>>,
+}
+
+impl<T> Registry<T> {
+    pub const fn new() -> Self {
+        Self {
+            entries: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Arc<T>>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Everything recorded so far, sorted by name.
+    pub fn entries(&self) -> Vec<(String, Arc<T>)> {
+        self.lock()
+            .iter()
+            .map(|(name, metric)| (name.clone(), metric.clone()))
+            .collect()
+    }
+}
+
+impl<T: Default> Registry<T> {
+    pub fn get(&self, name: &str) -> Arc<T> {
+        self.lock().entry(name.to_string()).or_default().clone()
+    }
+}
+
+impl<T> Default for Registry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Every metric in the Prometheus text format.
+pub fn prometheus() -> String {
+    let mut out = String::new();
+    write_histogram(&mut out, "amcli_osascript_duration_seconds", "", &OSASCRIPT);
+    for (player, timer) in PLAYER_POLLS.entries() {
+        let labels = format!("player=\"{}\"", escape_label(&player));
+        write_histogram(
+            &mut out,
+            "amcli_player_poll_duration_seconds",
+            &labels,
+            &timer,
+        );
+    }
+    for (cache, counter) in [("artwork", &ARTWORK_CACHE), ("lyrics", &LYRICS_CACHE)] {
+        let (hits, lookups) = counter.counts();
+        for (result, count) in [("hit", hits), ("miss", lookups - hits)] {
+            let _ = writeln!(
+                out,
+                "amcli_cache_lookups_total{{cache=\"{}\",result=\"{}\"}} {}",
+                cache, result, count
+            );
+        }
+    }
+    for (provider, outcomes) in LYRICS_PROVIDERS.entries() {
+        let provider = escape_label(&provider);
+        let (found, missing, failed) = outcomes.counts();
+        for (result, count) in [("found", found), ("missing", missing), ("failed", failed)] {
+            let _ = writeln!(
+                out,
+                "amcli_lyrics_provider_lookups_total{{provider=\"{}\",result=\"{}\"}} {}",
+                provider, result, count
+            );
+        }
+        let labels = format!("provider=\"{}\"", provider);
+        write_histogram(
+            &mut out,
+            "amcli_lyrics_provider_duration_seconds",
+            &labels,
+            &outcomes.latency,
+        );
+    }
+    out
+}
+
+fn write_histogram(out: &mut String, name: &str, labels: &str, timer: &Timer) {
+    let with = |extra: &str| match (labels.is_empty(), extra.is_empty()) {
+        (true, true) => String::new(),
+        (true, false) => format!("{{{}}}", extra),
+        (false, true) => format!("{{{}}}", labels),
+        (false, false) => format!("{{{},{}}}", labels, extra),
+    };
+    for (ms, count) in BUCKETS_MS.iter().zip(timer.histogram()) {
+        let le = format!("le=\"{}\"", *ms as f64 / 1000.0);
+        let _ = writeln!(out, "{}_bucket{} {}", name, with(&le), count);
+    }
+    let calls = timer.summary().calls;
+    let _ = writeln!(out, "{}_bucket{} {}", name, with("le=\"+Inf\""), calls);
+    let _ = writeln!(
+        out,
+        "{}_sum{} {}",
+        name,
+        with(""),
+        timer.total().as_secs_f64()
+    );
+    let _ = writeln!(out, "{}_count{} {}", name, with(""), calls);
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+static SERVING: AtomicBool = AtomicBool::new(false);
+
+/// Serves [`prometheus`] at `http://<addr>/metrics` in the background. Only
+/// the first call binds; profile switches keep the same listener.
+pub async fn serve_prometheus(addr: &str) -> Result<()> {
+    if SERVING.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            SERVING.store(false, Ordering::SeqCst);
+            return Err(e.into());
+        }
+    };
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = [0; 1024];
+                let Ok(read) = stream.read(&mut request).await else {
+                    return;
+                };
+                let request = String::from_utf8_lossy(&request[..read]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let response = if path == "/metrics" {
+                    let body = prometheus();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_track_last_average_and_max() {
+        let timer = Timer::new();
+        assert_eq!(timer.summary(), TimerSummary::default());
+        for ms in [30, 90, 60] {
+            timer.record(Duration::from_millis(ms));
+        }
+        assert_eq!(
+            timer.summary(),
+            TimerSummary {
+                calls: 3,
+                last: Duration::from_millis(60),
+                average: Duration::from_millis(60),
+                max: Duration::from_millis(90),
+            }
+        );
+
+        let counter = HitCounter::new();
+        counter.hit();
+        counter.hit();
+        counter.miss();
+        assert_eq!(counter.counts(), (2, 3));
+    }
+
+    #[test]
+    fn histograms_and_registries_export_for_prometheus() {
+        let timer = Timer::new();
+        for ms in [3, 40, 40, 3000] {
+            timer.record(Duration::from_millis(ms));
+        }
+        assert_eq!(timer.histogram(), [1, 1, 1, 3, 3, 3, 3, 3, 3]);
+
+        PLAYER_POLLS
+            .get("Test \"Player\"")
+            .record(Duration::from_millis(7));
+        LYRICS_PROVIDERS
+            .get("test-provider")
+            .record(Outcome::Missing, Duration::from_millis(200));
+        let text = prometheus();
+        assert!(text.contains(
+            "amcli_player_poll_duration_seconds_bucket{player=\"Test \\\"Player\\\"\",le=\"0.01\"} 1"
+        ));
+        assert!(text.contains(
+            "amcli_lyrics_provider_lookups_total{provider=\"test-provider\",result=\"missing\"} 1"
+        ));
+        assert!(text.contains(
+            "amcli_lyrics_provider_duration_seconds_count{provider=\"test-provider\"} 1"
+        ));
+        assert!(text.contains("amcli_osascript_duration_seconds_bucket{le=\"+Inf\"}"));
+    }
+}
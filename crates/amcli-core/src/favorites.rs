@@ -0,0 +1,141 @@
+// src/favorites.rs
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::player::{QueueEntry, Track};
+use crate::store::JsonStore;
+
+/// A track starred in amcli, with enough metadata to list and find it again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Favorite {
+    /// The track's [`track_key`](crate::bookmarks::track_key).
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub artist: String,
+    #[serde(default)]
+    pub album: String,
+    #[serde(default)]
+    pub duration_secs: u64,
+}
+
+impl Favorite {
+    /// The player's ID for the track, when the key is one rather than metadata.
+    pub fn track_id(&self) -> Option<&str> {
+        (!self.key.contains('\u{1f}')).then_some(self.key.as_str())
+    }
+
+    pub fn to_entry(&self) -> QueueEntry {
+        QueueEntry {
+            track: Track {
+                name: self.name.clone(),
+                artist: self.artist.clone(),
+                album: self.album.clone(),
+                duration: Duration::from_secs(self.duration_secs),
+                position: Duration::ZERO,
+            },
+            location: None,
+        }
+    }
+}
+
+/// Tracks starred in amcli, oldest first. Kept apart from Music's loved
+/// tracks so it can serve as a scratch list. Stored as JSON in amcli's data
+/// directory.
+#[derive(Debug, Default)]
+pub struct Favorites {
+    items: JsonStore<Vec<Favorite>>,
+}
+
+impl Favorites {
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("amcli/favorites.json")
+    }
+
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        Ok(Self {
+            items: JsonStore::load(path).await?,
+        })
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        self.items.save().await
+    }
+
+    pub fn items(&self) -> &[Favorite] {
+        &self.items
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.items.iter().any(|f| f.key == key)
+    }
+
+    /// Stars the track, or unstars it if it already is. Returns whether it's
+    /// starred now.
+    pub fn toggle(&mut self, key: &str, track: &Track) -> bool {
+        if let Some(index) = self.items.iter().position(|f| f.key == key) {
+            self.items.remove(index);
+            return false;
+        }
+        self.items.push(Favorite {
+            key: key.to_string(),
+            name: track.name.clone(),
+            artist: track.artist.clone(),
+            album: track.album.clone(),
+            duration_secs: track.duration.as_secs(),
+        });
+        true
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Favorite> {
+        (index < self.items.len()).then(|| self.items.remove(index))
+    }
+
+    /// The list as export entries, for [`crate::playlist::write_export`].
+    pub fn entries(&self) -> Vec<QueueEntry> {
+        self.items.iter().map(Favorite::to_entry).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmarks::track_key;
+
+    fn track(name: &str) -> Track {
+        Track {
+            name: name.into(),
+            artist: "Miles Davis".into(),
+            album: "Kind of Blue".into(),
+            duration: Duration::from_secs(337),
+            position: Duration::from_secs(12),
+        }
+    }
+
+    #[tokio::test]
+    async fn favorites_toggle_and_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("amcli-favorites-{}.json", std::process::id()));
+        let mut favorites = Favorites::load(path.clone()).await.unwrap();
+        let so_what = track("So What");
+        let by_metadata = track_key(None, &so_what);
+        assert!(favorites.toggle(&by_metadata, &so_what));
+        assert!(favorites.toggle("ABCD1234", &track("Blue in Green")));
+        assert!(favorites.toggle("EF567890", &track("All Blues")));
+        assert!(!favorites.toggle("EF567890", &track("All Blues")));
+        favorites.save().await.unwrap();
+
+        let loaded = Favorites::load(path.clone()).await.unwrap();
+        std::fs::remove_file(&path).ok();
+        let names: Vec<&str> = loaded.items().iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["So What", "Blue in Green"]);
+        assert!(loaded.contains("ABCD1234"));
+        assert_eq!(loaded.items()[0].track_id(), None);
+        assert_eq!(loaded.items()[1].track_id(), Some("ABCD1234"));
+        assert_eq!(loaded.entries()[0].track.position, Duration::ZERO);
+    }
+}
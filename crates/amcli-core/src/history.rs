@@ -0,0 +1,371 @@
+// src/history.rs
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::io::AsyncWriteExt;
+
+use crate::player::Track;
+
+/// A track as it started playing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Play {
+    /// Unix time in seconds.
+    pub at: i64,
+    pub name: String,
+    #[serde(default)]
+    pub artist: String,
+    #[serde(default)]
+    pub album: String,
+    #[serde(default)]
+    pub duration_secs: u64,
+}
+
+impl Play {
+    pub fn new(track: &Track, at: DateTime<Local>) -> Self {
+        Self {
+            at: at.timestamp(),
+            name: track.name.clone(),
+            artist: track.artist.clone(),
+            album: track.album.clone(),
+            duration_secs: track.duration.as_secs(),
+        }
+    }
+
+    pub fn started(&self) -> Option<DateTime<Local>> {
+        Local.timestamp_opt(self.at, 0).single()
+    }
+
+    fn is_same_track(&self, other: &Play) -> bool {
+        self.name == other.name && self.artist == other.artist && self.album == other.album
+    }
+}
+
+/// Every track amcli has seen start, one JSON object per line in amcli's
+/// data directory. Only ever appended to, so it's cheap to write on each play.
+#[derive(Debug, Clone)]
+pub struct PlayHistory {
+    path: PathBuf,
+}
+
+impl PlayHistory {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("amcli/history.jsonl")
+    }
+
+    pub async fn append(&self, play: &Play) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut line = serde_json::to_string(play)?;
+        line.push('\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Plays started on `day` in local time, oldest first.
+    pub async fn day(&self, day: NaiveDate) -> Result<Vec<Play>> {
+        self.between(Some(day), Some(day)).await
+    }
+
+    /// Plays started from `since` through `until` in local time, either end
+    /// open when `None`, oldest first. Lines that don't parse (a write cut
+    /// short) are skipped, and so is a track logged again straight after
+    /// itself, as happens when amcli restarts mid-song.
+    pub async fn between(
+        &self,
+        since: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> Result<Vec<Play>> {
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let in_range = |day: NaiveDate| {
+            since.is_none_or(|since| day >= since) && until.is_none_or(|until| day <= until)
+        };
+        let mut plays: Vec<Play> = Vec::new();
+        for play in content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Play>(line).ok())
+            .filter(|play| play.started().is_some_and(|at| in_range(at.date_naive())))
+        {
+            if plays.last().is_some_and(|last| last.is_same_track(&play)) {
+                continue;
+            }
+            plays.push(play);
+        }
+        Ok(plays)
+    }
+}
+
+/// How `amcli history export` writes plays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryFormat {
+    /// A row per play with its start time, for spreadsheets.
+    Csv,
+    /// The same records as the history file, which `amcli history backfill
+    /// --from` reads back.
+    Json,
+}
+
+impl FromStr for HistoryFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => bail!("Unknown history format \"{}\" (csv or json)", s),
+        }
+    }
+}
+
+pub fn format_plays(plays: &[Play], format: HistoryFormat) -> Result<String> {
+    match format {
+        HistoryFormat::Csv => {
+            let mut out = String::from("played_at,artist,title,album,duration_secs\n");
+            for play in plays {
+                let played_at = play.started().map(|at| at.to_rfc3339()).unwrap_or_default();
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{}",
+                    played_at,
+                    crate::playlist::csv_field(&play.artist),
+                    crate::playlist::csv_field(&play.name),
+                    crate::playlist::csv_field(&play.album),
+                    play.duration_secs
+                );
+            }
+            Ok(out)
+        }
+        HistoryFormat::Json => {
+            let mut out = serde_json::to_string_pretty(plays)?;
+            out.push('\n');
+            Ok(out)
+        }
+    }
+}
+
+/// Plays from a JSON file written by `amcli history export`.
+pub async fn read_export(path: &Path) -> Result<Vec<Play>> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("{} isn't a JSON history export", path.display()))
+}
+
+/// How `amcli session export` lays out a setlist.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetlistFormat {
+    /// "00:00  Artist – Title" lines under a one-line heading.
+    Text,
+    /// A numbered Markdown list, for show notes and blog posts.
+    Markdown,
+}
+
+impl FromStr for SetlistFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" | "txt" => Ok(Self::Text),
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => bail!("Unknown setlist format \"{}\" (text or markdown)", s),
+        }
+    }
+}
+
+/// `plays` as a setlist, each timed from when the first one started. Hours
+/// are only shown when the set runs that long.
+pub fn format_setlist(plays: &[Play], format: SetlistFormat) -> String {
+    let Some(first) = plays.first() else {
+        return String::new();
+    };
+    let start = first.started().unwrap_or_else(Local::now);
+    let day = start.format("%A, %B %-d, %Y");
+    let long = plays.last().is_some_and(|last| last.at - first.at >= 3600);
+    let offset = |play: &Play| {
+        let secs = (play.at - first.at).max(0);
+        let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+        if long {
+            format!("{}:{:02}:{:02}", h, m, s)
+        } else {
+            format!("{:02}:{:02}", m, s)
+        }
+    };
+
+    let mut out = String::new();
+    match format {
+        SetlistFormat::Text => {
+            let _ = writeln!(out, "Setlist · {} · from {}", day, start.format("%H:%M"));
+            let _ = writeln!(out);
+            for play in plays {
+                let title = if play.artist.is_empty() {
+                    play.name.clone()
+                } else {
+                    format!("{} – {}", play.artist, play.name)
+                };
+                let _ = writeln!(out, "{}  {}", offset(play), title);
+            }
+        }
+        SetlistFormat::Markdown => {
+            let _ = writeln!(out, "# Setlist · {}", day);
+            let _ = writeln!(out);
+            let _ = writeln!(out, "Started at {}.", start.format("%H:%M"));
+            let _ = writeln!(out);
+            for (i, play) in plays.iter().enumerate() {
+                let _ = write!(out, "{}. `{}` **{}**", i + 1, offset(play), play.name);
+                if !play.artist.is_empty() {
+                    let _ = write!(out, " — {}", play.artist);
+                }
+                if !play.album.is_empty() {
+                    let _ = write!(out, " · *{}*", play.album);
+                }
+                let _ = writeln!(out);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn play(name: &str, at: DateTime<Local>) -> Play {
+        let track = Track {
+            name: name.into(),
+            artist: "Miles Davis".into(),
+            album: "Kind of Blue".into(),
+            duration: Duration::from_secs(337),
+            position: Duration::ZERO,
+        };
+        Play::new(&track, at)
+    }
+
+    fn at(day: u32, h: u32, m: u32, s: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, day, h, m, s).unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_days_plays_are_read_back_without_restart_repeats() {
+        let path = std::env::temp_dir().join(format!("amcli-history-{}.jsonl", std::process::id()));
+        let history = PlayHistory::new(path.clone());
+        history
+            .append(&play("Flamenco Sketches", at(14, 23, 50, 0)))
+            .await
+            .unwrap();
+        history
+            .append(&play("So What", at(15, 21, 4, 0)))
+            .await
+            .unwrap();
+        history
+            .append(&play("So What", at(15, 21, 6, 0)))
+            .await
+            .unwrap();
+        history
+            .append(&play("Blue in Green", at(15, 21, 13, 22)))
+            .await
+            .unwrap();
+        tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .await
+            .unwrap()
+            .write_all(b"{\"at\":")
+            .await
+            .unwrap();
+
+        let plays = history
+            .day(NaiveDate::from_ymd_opt(2026, 10, 15).unwrap())
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        let names: Vec<&str> = plays.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["So What", "Blue in Green"]);
+    }
+
+    #[tokio::test]
+    async fn exports_cover_a_date_range_and_read_back() {
+        let path =
+            std::env::temp_dir().join(format!("amcli-history-range-{}.jsonl", std::process::id()));
+        let history = PlayHistory::new(path.clone());
+        for (name, day) in [
+            ("So What", 13),
+            ("Freddie Freeloader", 14),
+            ("All Blues", 16),
+        ] {
+            history
+                .append(&play(name, at(day, 21, 0, 0)))
+                .await
+                .unwrap();
+        }
+        let date = |day| NaiveDate::from_ymd_opt(2026, 10, day);
+        let plays = history.between(date(14), None).await.unwrap();
+        let names: Vec<&str> = plays.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Freddie Freeloader", "All Blues"]);
+        assert_eq!(history.between(None, date(13)).await.unwrap().len(), 1);
+
+        let csv = format_plays(&plays[..1], HistoryFormat::Csv).unwrap();
+        assert_eq!(
+            csv,
+            format!(
+                "played_at,artist,title,album,duration_secs\n\
+                 {},Miles Davis,Freddie Freeloader,Kind of Blue,337\n",
+                at(14, 21, 0, 0).to_rfc3339()
+            )
+        );
+
+        let export = path.with_extension("json");
+        tokio::fs::write(&export, format_plays(&plays, HistoryFormat::Json).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(read_export(&export).await.unwrap(), plays);
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&export).ok();
+    }
+
+    #[test]
+    fn setlists_are_timed_from_the_first_track() {
+        let plays = [
+            play("So What", at(15, 21, 4, 0)),
+            play("Blue in Green", at(15, 21, 13, 22)),
+        ];
+        assert_eq!(
+            format_setlist(&plays, SetlistFormat::Text),
+            "Setlist · Thursday, October 15, 2026 · from 21:04\n\
+             \n\
+             00:00  Miles Davis – So What\n\
+             09:22  Miles Davis – Blue in Green\n"
+        );
+        let markdown = format_setlist(&plays, SetlistFormat::Markdown);
+        assert!(markdown.starts_with("# Setlist · Thursday, October 15, 2026\n"));
+        assert!(markdown.contains("2. `09:22` **Blue in Green** — Miles Davis · *Kind of Blue*\n"));
+
+        let long = [
+            play("So What", at(15, 21, 4, 0)),
+            play("All Blues", at(15, 22, 10, 5)),
+        ];
+        assert!(
+            format_setlist(&long, SetlistFormat::Text).contains("1:06:05  Miles Davis – All Blues")
+        );
+        assert_eq!(format_setlist(&[], SetlistFormat::Markdown), "");
+    }
+}
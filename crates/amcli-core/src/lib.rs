@@ -0,0 +1,87 @@
+//! The parts of amcli that don't depend on the terminal UI: player control,
+//! lyrics lookup, album artwork, and configuration.
+//!
+//! The `amcli` binary is a thin TUI shell over this crate, and other tools
+//! (menu bar apps, chat bots, scripts) can reuse the same pieces:
+//!
+//! ```no_run
+//! use amcli_core::config::Config;
+//! use amcli_core::player::create_player;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let config = Config::load().await?;
+//! let player = create_player(&config.player).await?;
+//! if let Some(track) = player.get_current_track().await? {
+//!     println!("{} - {}", track.artist, track.name);
+//! }
+//! player.toggle().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! - [`player`]: the [`player::MediaPlayer`] trait and its backends (Apple
+//!   Music, Podcasts, macOS Now Playing, shairport-sync, plugins, a demo
+//!   player, and the feature-gated internal, Subsonic, and Spotify players).
+//! - [`history`]: a log of every track played, its CSV/JSON export, and
+//!   setlists made from it.
+//! - [`library`]: a cached index of the player's playlists and albums, and
+//!   fuzzy search over it.
+//! - [`lyrics`]: [`lyrics::LyricsManager`] and the online and external
+//!   providers it races.
+//! - [`artwork`]: artwork download, caching, and conversion for terminal
+//!   image protocols.
+//! - [`artist`]: artist biographies from Wikipedia, Last.fm, or Apple Music.
+//! - [`bookmarks`]: named positions within tracks, saved between sessions.
+//! - [`cache`]: where cached artwork, update checks, and the library index
+//!   live, and their disk usage.
+//! - [`config`]: the `config.toml` schema and its load/save helpers.
+//! - [`diagnostics`]: osascript timings and cache hit counts for the debug
+//!   overlay.
+//! - [`favorites`]: tracks starred in amcli, apart from the player's loves.
+//! - [`mpris`]: amcli as an MPRIS player on the Linux session bus, so desktop
+//!   media controls can drive the internal and Subsonic players.
+//! - [`mqtt`]: a now-playing publisher for Home Assistant and other MQTT
+//!   consumers.
+//! - [`musickit`]: Apple Music catalog search and editorial metadata through
+//!   the MusicKit API.
+//! - [`network`]: the offline switch and the proxy, timeout, and User-Agent
+//!   shared by every HTTP client.
+//! - [`notes`]: short notes on tracks, saved between sessions.
+//! - [`playlist`]: M3U export.
+//! - [`remote`]: macOS media keys and remote commands.
+//! - [`scrobble`]: sending the play history to Last.fm or ListenBrainz.
+//! - [`session`]: the theme and layout restored at startup.
+//! - [`store`]: the JSON files behind bookmarks, favorites, notes, and
+//!   artwork corrections.
+//! - [`update`]: the daily GitHub release check and `amcli self-update`.
+//! - [`widget`]: now-playing over a Unix socket for `amcli widget` and
+//!   desktop bars such as sketchybar and Übersicht.
+//!
+//! # Features
+//!
+//! - `internal-player`: play local files and Subsonic streams with rodio.
+//! - `spotify`: run as a Spotify Connect device through librespot.
+
+pub mod artist;
+pub mod artwork;
+pub mod bookmarks;
+pub mod cache;
+pub mod config;
+pub mod diagnostics;
+pub mod favorites;
+pub mod history;
+pub mod library;
+pub mod lyrics;
+pub mod mpris;
+pub mod mqtt;
+pub mod musickit;
+pub mod network;
+pub mod notes;
+pub mod player;
+pub mod playlist;
+pub mod remote;
+pub mod scrobble;
+pub mod session;
+pub mod store;
+pub mod update;
+pub mod widget;
@@ -0,0 +1,358 @@
+// src/library.rs
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::player::MediaPlayer;
+
+// Tracks read per request while indexing; a page is one round of AppleScript
+// for the Music app
+const PAGE_SIZE: usize = 500;
+// Refreshes only fetch tracks they haven't seen, so edits to known tracks are
+// picked up by re-reading everything this often
+const REBUILD_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// fzf-style weights: every matched character scores, more so at the start of
+// a word or right after the previous match, and skipped characters cost a
+// little
+const MATCH_SCORE: i32 = 16;
+const WORD_START_BONUS: i32 = 8;
+const CONSECUTIVE_BONUS: i32 = 8;
+const GAP_PENALTY: i32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    Playlist,
+    Album,
+    Track,
+}
+
+/// Something in the player's library that can be started on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryItem {
+    pub kind: ItemKind,
+    pub name: String,
+    /// Empty for playlists.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub artist: String,
+    /// The album a track is on; empty for albums and playlists.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub album: String,
+    /// The player's stable ID for a track, such as Music's persistent ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The ID of a track whose cover stands for an album.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artwork: Option<String>,
+}
+
+impl LibraryItem {
+    pub fn new(kind: ItemKind, name: impl Into<String>, artist: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            artist: artist.into(),
+            album: String::new(),
+            id: None,
+            artwork: None,
+        }
+    }
+
+    pub fn track(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        artist: impl Into<String>,
+        album: impl Into<String>,
+    ) -> Self {
+        Self {
+            album: album.into(),
+            id: Some(id.into()),
+            ..Self::new(ItemKind::Track, name, artist)
+        }
+    }
+}
+
+/// The player's playlists, albums, and tracks, cached so searching them never
+/// waits on the player. Stored as JSON in amcli's cache directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryIndex {
+    /// Unix seconds of the last refresh; 0 when there hasn't been one.
+    #[serde(default)]
+    pub refreshed_at: u64,
+    /// Unix seconds of the last refresh that re-read every track.
+    #[serde(default)]
+    pub rebuilt_at: u64,
+    #[serde(default)]
+    pub items: Vec<LibraryItem>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+impl LibraryIndex {
+    /// A freshly read library.
+    pub fn new(items: Vec<LibraryItem>) -> Self {
+        Self {
+            refreshed_at: now(),
+            rebuilt_at: now(),
+            items,
+        }
+    }
+
+    /// Empty when nothing has been indexed yet.
+    pub async fn load(path: &Path) -> Result<Self> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(path, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    /// Time since the last refresh.
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.refreshed_at))
+    }
+
+    fn known_tracks(&self) -> HashMap<&str, &LibraryItem> {
+        self.items
+            .iter()
+            .filter(|item| item.kind == ItemKind::Track)
+            .filter_map(|item| Some((item.id.as_deref()?, item)))
+            .collect()
+    }
+}
+
+/// Brings `index` up to date with `player`'s library. Players that list their
+/// track IDs are read a page at a time, and only pages holding tracks the
+/// index hasn't seen are fetched, except for a full re-read once a week.
+/// Other players are read in one go with
+/// [`get_library`](MediaPlayer::get_library).
+pub async fn refresh(player: &dyn MediaPlayer, index: &LibraryIndex) -> Result<LibraryIndex> {
+    let ids = match player.get_library_track_ids().await {
+        Ok(ids) => ids,
+        Err(_) => return Ok(LibraryIndex::new(player.get_library().await?)),
+    };
+    let rebuild = now().saturating_sub(index.rebuilt_at) >= REBUILD_INTERVAL.as_secs();
+    let known = if rebuild {
+        HashMap::new()
+    } else {
+        index.known_tracks()
+    };
+    let mut fetched: HashMap<String, LibraryItem> = HashMap::new();
+    for (page, chunk) in ids.chunks(PAGE_SIZE).enumerate() {
+        if chunk.iter().all(|id| known.contains_key(id.as_str())) {
+            continue;
+        }
+        let tracks = player
+            .get_library_tracks(page * PAGE_SIZE, PAGE_SIZE)
+            .await?;
+        fetched.extend(
+            tracks
+                .into_iter()
+                .filter_map(|track| Some((track.id.clone()?, track))),
+        );
+    }
+    let tracks: Vec<LibraryItem> = ids
+        .iter()
+        .filter_map(|id| {
+            fetched
+                .remove(id)
+                .or_else(|| known.get(id.as_str()).map(|&track| track.clone()))
+        })
+        .collect();
+
+    let mut items: Vec<LibraryItem> = player
+        .get_playlists()
+        .await?
+        .into_iter()
+        .map(|name| LibraryItem::new(ItemKind::Playlist, name, ""))
+        .collect();
+    items.extend(albums(&tracks));
+    items.extend(tracks);
+    Ok(LibraryIndex {
+        refreshed_at: now(),
+        rebuilt_at: if rebuild { now() } else { index.rebuilt_at },
+        items,
+    })
+}
+
+// Each album once, in the order its first track appears, with that track's
+// cover standing for it
+fn albums(tracks: &[LibraryItem]) -> Vec<LibraryItem> {
+    let mut seen = HashSet::new();
+    tracks
+        .iter()
+        .filter(|track| !track.album.is_empty() && seen.insert(track.album.as_str()))
+        .map(|track| LibraryItem {
+            artwork: track.id.clone(),
+            ..LibraryItem::new(ItemKind::Album, &track.album, &track.artist)
+        })
+        .collect()
+}
+
+/// How well `query` fuzzy-matches `text`, or `None` when it doesn't. Each
+/// space-separated word of the query has to appear in order, case aside,
+/// though not necessarily side by side; the words themselves can match in
+/// any order.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    query
+        .split_whitespace()
+        .map(|word| word_score(word, &text))
+        .sum()
+}
+
+// Greedy leftmost match, as in fzf's fast algorithm
+fn word_score(word: &str, text: &[char]) -> Option<i32> {
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for c in word.chars().flat_map(char::to_lowercase) {
+        let found = next + text[next..].iter().position(|&t| t == c)?;
+        score += MATCH_SCORE;
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        match previous {
+            Some(previous) if previous + 1 == found => score += CONSECUTIVE_BONUS,
+            Some(previous) => score -= GAP_PENALTY * (found - previous - 1) as i32,
+            None => {}
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// The `limit` best matches for `query`, best first. Equal scores go to the
+/// shorter name, then keep their order in `items`. An empty query keeps
+/// every item in order.
+pub fn rank<'a>(
+    query: &str,
+    items: impl IntoIterator<Item = &'a LibraryItem>,
+    limit: usize,
+) -> Vec<&'a LibraryItem> {
+    let mut scored: Vec<_> = items
+        .into_iter()
+        .filter_map(|item| {
+            let text = format!("{} {}", item.name, item.artist);
+            fuzzy_score(query, &text).map(|score| (score, item))
+        })
+        .collect();
+    if !query.trim().is_empty() {
+        scored.sort_by_key(|&(score, item)| (-score, item.name.chars().count()));
+    }
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matches_need_every_word_in_order() {
+        assert!(fuzzy_score("abrd", "Abbey Road").is_some());
+        assert!(fuzzy_score("road abbey", "Abbey Road").is_some());
+        assert!(fuzzy_score("drab", "Abbey Road").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        // Word starts and runs beat scattered letters
+        assert!(fuzzy_score("ar", "Abbey Road") > fuzzy_score("ar", "Baroque"));
+        assert!(fuzzy_score("road", "Abbey Road") > fuzzy_score("road", "R o a d"));
+    }
+
+    #[test]
+    fn ranking_prefers_close_short_matches() {
+        let items = [
+            LibraryItem::new(ItemKind::Playlist, "Running Mix", ""),
+            LibraryItem::new(ItemKind::Album, "Kind of Blue", "Miles Davis"),
+            LibraryItem::new(ItemKind::Album, "Blue", "Joni Mitchell"),
+            LibraryItem::new(ItemKind::Track, "Blue in Green", "Miles Davis"),
+        ];
+        let names = |query: &str| -> Vec<&str> {
+            rank(query, &items, 10)
+                .iter()
+                .map(|item| item.name.as_str())
+                .collect()
+        };
+        assert_eq!(names("blue"), ["Blue", "Kind of Blue", "Blue in Green"]);
+        assert_eq!(names("miles blue"), ["Kind of Blue", "Blue in Green"]);
+        assert_eq!(names("").len(), 4);
+        assert_eq!(rank("", &items, 2).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn refreshes_only_fetch_pages_with_new_tracks() {
+        let demo = crate::player::demo::DemoPlayer::new();
+        let index = refresh(&demo, &LibraryIndex::default()).await.unwrap();
+        let tracks = || {
+            index
+                .items
+                .iter()
+                .filter(|item| item.kind == ItemKind::Track)
+        };
+        assert!(tracks().count() > 1);
+        let first = tracks().next().unwrap().clone();
+        let album = index
+            .items
+            .iter()
+            .find(|item| item.kind == ItemKind::Album && item.name == first.album)
+            .unwrap();
+        assert_eq!(album.artwork, first.id);
+
+        // Every ID is known, so the stale copy isn't re-read
+        let mut stale = index.clone();
+        for item in &mut stale.items {
+            if item.id == first.id {
+                item.name = "Stale".into();
+            }
+        }
+        let kept = refresh(&demo, &stale).await.unwrap();
+        assert!(kept.items.iter().any(|item| item.name == "Stale"));
+
+        // An unseen track brings its page back in
+        stale
+            .items
+            .retain(|item| item.id.is_none() || item.id != tracks().next_back().unwrap().id);
+        let updated = refresh(&demo, &stale).await.unwrap();
+        assert!(!updated.items.iter().any(|item| item.name == "Stale"));
+        assert_eq!(updated.items.len(), index.items.len());
+
+        // And so does a weekly rebuild
+        stale.rebuilt_at = 0;
+        let rebuilt = refresh(&demo, &stale).await.unwrap();
+        assert!(rebuilt.rebuilt_at > 0);
+        assert!(!rebuilt.items.iter().any(|item| item.name == "Stale"));
+    }
+
+    #[tokio::test]
+    async fn indexes_round_trip() {
+        let path = std::env::temp_dir().join(format!("amcli-library-{}.json", std::process::id()));
+        assert_eq!(
+            LibraryIndex::load(&path).await.unwrap(),
+            LibraryIndex::default()
+        );
+        let index = LibraryIndex::new(vec![LibraryItem::new(ItemKind::Playlist, "Chill", "")]);
+        assert!(index.age() < Duration::from_secs(5));
+        index.save(&path).await.unwrap();
+        assert_eq!(LibraryIndex::load(&path).await.unwrap(), index);
+        let _ = std::fs::remove_file(path);
+    }
+}
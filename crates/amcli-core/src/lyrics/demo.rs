@@ -0,0 +1,61 @@
+// src/lyrics/demo.rs
+use crate::lyrics::parser::parse_lrc;
+use crate::lyrics::provider::LyricsProvider;
+use crate::lyrics::Lyrics;
+use crate::player::demo::DEMO_TRACKS;
+use crate::player::Track;
+use anyhow::Result;
+use async_trait::async_trait;
+
+// Serves the lyrics bundled with the demo player, so `--demo` never touches
+// the network.
+#[derive(Default)]
+pub struct DemoLyricsProvider;
+
+#[async_trait]
+impl LyricsProvider for DemoLyricsProvider {
+    async fn get_lyrics(&self, track: &Track) -> Result<Option<Lyrics>> {
+        DEMO_TRACKS
+            .iter()
+            .find(|demo| demo.name == track.name && demo.artist == track.artist)
+            .map(|demo| parse_lrc(demo.lyrics))
+            .transpose()
+    }
+
+    fn priority(&self) -> u8 {
+        0
+    }
+
+    fn name(&self) -> &str {
+        "Demo"
+    }
+
+    fn needs_network(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn every_demo_track_has_synced_lyrics() {
+        for demo in DEMO_TRACKS {
+            let track = Track {
+                name: demo.name.to_string(),
+                artist: demo.artist.to_string(),
+                album: demo.album.to_string(),
+                duration: std::time::Duration::from_secs(demo.seconds),
+                position: std::time::Duration::ZERO,
+            };
+            let lyrics = DemoLyricsProvider
+                .get_lyrics(&track)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(lyrics.lines.len() > 10, "{} is missing lyrics", demo.name);
+            assert!(lyrics.lines.last().unwrap().timestamp < track.duration);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+// src/lyrics/external.rs
+use crate::config::LyricsPluginConfig;
+use crate::lyrics::parser::parse_lrc;
+use crate::lyrics::provider::LyricsProvider;
+use crate::lyrics::Lyrics;
+use crate::player::Track;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Deserialize)]
+struct Reply {
+    #[serde(default)]
+    synced: Option<String>,
+    #[serde(default)]
+    plain: Option<String>,
+}
+
+// A lyrics source outside amcli: one run of the command per lookup, with the
+// track as JSON on stdin and the lyrics as JSON on stdout. See PLUGINS.md.
+pub struct ExternalProvider {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    priority: u8,
+}
+
+impl ExternalProvider {
+    pub fn new(config: &LyricsPluginConfig) -> Self {
+        let name = std::path::Path::new(&config.command)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| config.command.clone());
+        Self {
+            name,
+            command: crate::playlist::expand_home(&config.command)
+                .to_string_lossy()
+                .into_owned(),
+            args: config.args.clone(),
+            priority: config.priority,
+        }
+    }
+}
+
+fn parse_reply(stdout: &[u8]) -> Result<Option<Lyrics>> {
+    let reply: Option<Reply> =
+        serde_json::from_slice(stdout).context("Malformed lyrics plugin output")?;
+    let reply = reply.unwrap_or_default();
+    match reply
+        .synced
+        .into_iter()
+        .chain(reply.plain)
+        .find(|text| !text.trim().is_empty())
+    {
+        Some(text) => Ok(Some(parse_lrc(&text)?)),
+        None => Ok(None),
+    }
+}
+
+#[async_trait]
+impl LyricsProvider for ExternalProvider {
+    async fn get_lyrics(&self, track: &Track) -> Result<Option<Lyrics>> {
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", self.command))?;
+
+        let request = json!({
+            "protocol": PROTOCOL_VERSION,
+            "track": {
+                "name": track.name,
+                "artist": track.artist,
+                "album": track.album,
+                "duration": track.duration.as_secs_f64(),
+            },
+        });
+        if let Some(mut stdin) = child.stdin.take() {
+            // A plugin that ignores the request may exit before reading it
+            let _ = stdin.write_all(request.to_string().as_bytes()).await;
+        }
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{} failed: {}",
+                self.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        parse_reply(&output.stdout)
+    }
+
+    fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Plugins are the user's own commands, so they stay on in offline mode
+    fn needs_network(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn plugin(script: &str) -> ExternalProvider {
+        ExternalProvider::new(&LyricsPluginConfig {
+            command: "sh".into(),
+            args: vec!["-c".into(), script.into()],
+            priority: 1,
+        })
+    }
+
+    fn track() -> Track {
+        Track {
+            name: "So What".into(),
+            artist: "Miles Davis".into(),
+            album: "Kind of Blue".into(),
+            duration: Duration::from_secs(562),
+            position: Duration::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_synced_lyrics_from_the_plugin() {
+        // Echo the track name back to prove the request arrived on stdin
+        let provider = plugin(
+            r#"name=$(sed 's/.*"name":"\([^"]*\)".*/\1/'); printf '{"synced":"[00:01.00]%s"}' "$name""#,
+        );
+        let lyrics = provider.get_lyrics(&track()).await.unwrap().unwrap();
+        assert_eq!(lyrics.lines[0].text, "So What");
+        assert_eq!(lyrics.lines[0].timestamp, Duration::from_secs(1));
+        assert_eq!(provider.name(), "sh");
+    }
+
+    #[tokio::test]
+    async fn null_is_a_miss_and_a_nonzero_exit_is_a_failure() {
+        let provider = plugin("cat > /dev/null; echo null");
+        assert!(provider.get_lyrics(&track()).await.unwrap().is_none());
+
+        let provider = plugin("cat > /dev/null; echo offline >&2; exit 3");
+        let err = provider.get_lyrics(&track()).await.unwrap_err();
+        assert!(err.to_string().contains("offline"));
+    }
+}
@@ -0,0 +1,129 @@
+// src/lyrics/local.rs
+use crate::lyrics::parser::parse_lrc;
+use crate::lyrics::provider::LyricsProvider;
+use crate::lyrics::Lyrics;
+use crate::player::Track;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// Reads lyrics files from `lyrics.local_paths`, searching each directory in
+/// order. See [`LyricsManager::set_local`](crate::lyrics::LyricsManager::set_local). Within a directory it tries `Artist - Title`, `Artist/Album/Title`,
+/// `Artist/Title`, then `Title`, each as an `.lrc` file.
+pub struct LocalLyricsProvider {
+    dirs: Vec<PathBuf>,
+}
+
+impl LocalLyricsProvider {
+    pub fn new(paths: &[String]) -> Self {
+        Self {
+            dirs: paths
+                .iter()
+                .map(|path| crate::playlist::expand_home(path))
+                .collect(),
+        }
+    }
+
+    fn candidates(&self, track: &Track) -> Vec<PathBuf> {
+        let artist = file_name(&track.artist);
+        let album = file_name(&track.album);
+        let title = file_name(&track.name);
+        let stems = [
+            PathBuf::from(format!("{} - {}", artist, title)),
+            [&artist, &album, &title].iter().collect(),
+            [&artist, &title].iter().collect(),
+            PathBuf::from(&title),
+        ];
+        self.dirs
+            .iter()
+            .flat_map(|dir| {
+                stems
+                    .iter()
+                    .map(move |stem| dir.join(stem).with_extension("lrc"))
+            })
+            .collect()
+    }
+}
+
+// Path separators can't appear in a file name
+fn file_name(part: &str) -> String {
+    part.trim().replace(['/', ':'], "_")
+}
+
+#[async_trait]
+impl LyricsProvider for LocalLyricsProvider {
+    async fn get_lyrics(&self, track: &Track) -> Result<Option<Lyrics>> {
+        for path in self.candidates(track) {
+            let Ok(content) = tokio::fs::read_to_string(&path).await else {
+                continue;
+            };
+            // Untimed files parse to nothing; keep looking
+            let lyrics = parse_lrc(&content)?;
+            if !lyrics.lines.is_empty() {
+                tracing::debug!("Lyrics file: {}", path.display());
+                return Ok(Some(lyrics));
+            }
+        }
+        Ok(None)
+    }
+
+    // Never ordered against the others; the manager asks it first
+    fn priority(&self) -> u8 {
+        0
+    }
+
+    fn name(&self) -> &str {
+        "Local"
+    }
+
+    fn needs_network(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn paths_are_searched_in_order() {
+        let root = std::env::temp_dir().join(format!("amcli-local-lyrics-{}", std::process::id()));
+        let (first, second) = (root.join("beets"), root.join("plex"));
+        tokio::fs::create_dir_all(second.join("AC_DC"))
+            .await
+            .unwrap();
+        tokio::fs::create_dir_all(&first).await.unwrap();
+        tokio::fs::write(second.join("AC_DC/Thunderstruck.lrc"), "[00:01.00]second")
+            .await
+            .unwrap();
+
+        let provider = LocalLyricsProvider::new(&[
+            first.to_string_lossy().into_owned(),
+            second.to_string_lossy().into_owned(),
+        ]);
+        let track = Track {
+            name: "Thunderstruck".into(),
+            artist: "AC/DC".into(),
+            album: "The Razors Edge".into(),
+            duration: Duration::from_secs(292),
+            position: Duration::ZERO,
+        };
+        let lyrics = provider.get_lyrics(&track).await.unwrap().unwrap();
+        assert_eq!(lyrics.lines[0].text, "second");
+
+        // An earlier directory wins, even with a looser file name
+        tokio::fs::write(first.join("Thunderstruck.lrc"), "[00:01.00]first")
+            .await
+            .unwrap();
+        let lyrics = provider.get_lyrics(&track).await.unwrap().unwrap();
+        assert_eq!(lyrics.lines[0].text, "first");
+
+        let other = Track {
+            name: "Back in Black".into(),
+            ..track
+        };
+        assert!(provider.get_lyrics(&other).await.unwrap().is_none());
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+}
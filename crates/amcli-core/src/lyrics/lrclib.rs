@@ -6,38 +6,39 @@ use crate::lyrics::Lyrics;
 use crate::player::Track;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::Client;
 use serde_json::Value;
 use std::time::Duration;
 
 const LRCLIB_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// LRCLIB asks clients to identify themselves; `[network] user_agent` wins
+const LRCLIB_USER_AGENT: &str = concat!(
+    "AMCLI v",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/juntaochi/amcli)"
+);
 pub(crate) const LRCLIB_PRIORITY: u8 = 10;
 
 pub struct LrclibProvider {
     client: Client,
 }
 
+impl Default for LrclibProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LrclibProvider {
     pub fn new() -> Self {
         Self {
-            client: Client::builder()
-                .timeout(LRCLIB_REQUEST_TIMEOUT)
-                .build()
-                .unwrap_or_default(),
+            client: crate::network::client(Some(LRCLIB_REQUEST_TIMEOUT), Some(LRCLIB_USER_AGENT)),
         }
     }
 
     fn headers() -> HeaderMap {
         let mut headers = HeaderMap::new();
-        headers.insert(
-            USER_AGENT,
-            HeaderValue::from_static(concat!(
-                "AMCLI v",
-                env!("CARGO_PKG_VERSION"),
-                " (https://github.com/juntaochi/amcli)"
-            )),
-        );
         headers.insert(
             "Lrclib-Client",
             HeaderValue::from_static(concat!("AMCLI v", env!("CARGO_PKG_VERSION"))),
@@ -180,7 +181,7 @@ mod tests {
         let version = env!("CARGO_PKG_VERSION");
 
         assert_eq!(
-            headers.get(USER_AGENT).unwrap().to_str().unwrap(),
+            LRCLIB_USER_AGENT,
             format!("AMCLI v{} (https://github.com/juntaochi/amcli)", version)
         );
         assert_eq!(
@@ -149,7 +149,7 @@ fn normalize_text(value: &str) -> String {
         .collect()
 }
 
-fn strip_trailing_qualifiers(value: &str) -> &str {
+pub(crate) fn strip_trailing_qualifiers(value: &str) -> &str {
     let mut trimmed = value.trim();
     loop {
         let Some(stripped) = strip_one_trailing_qualifier(trimmed) else {
@@ -9,6 +9,9 @@ use std::time::Duration;
 
 const PROVIDER_TIMEOUT: Duration = Duration::from_secs(12);
 
+pub mod demo;
+pub mod external;
+pub mod local;
 pub mod lrclib;
 pub(crate) mod matching;
 pub mod netease;
@@ -21,7 +24,7 @@ pub struct LyricLine {
     pub timestamp: Duration,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Lyrics {
     pub lines: Vec<LyricLine>,
     pub metadata: HashMap<String, String>,
@@ -50,9 +53,12 @@ impl Lyrics {
     }
 }
 
+/// Looks up lyrics across the registered providers, with an LRU cache in front.
 #[derive(Clone)]
 pub struct LyricsManager {
     providers: Vec<std::sync::Arc<dyn provider::LyricsProvider>>,
+    // `lyrics.local_paths`, read before any provider is asked
+    local: Option<std::sync::Arc<dyn provider::LyricsProvider>>,
     cache: std::sync::Arc<Mutex<LruCache<String, Lyrics>>>,
     // Session-calibrated primary provider, chosen on the first race that yields a
     // clear winner. `None` until then, so early lookups keep racing.
@@ -70,6 +76,7 @@ impl LyricsManager {
     pub fn new(capacity: usize) -> Self {
         Self {
             providers: Vec::new(),
+            local: None,
             cache: std::sync::Arc::new(Mutex::new(LruCache::new(
                 NonZeroUsize::new(capacity).expect("lyrics cache capacity must be non-zero"),
             ))),
@@ -81,6 +88,13 @@ impl LyricsManager {
         self.providers.push(std::sync::Arc::from(provider));
     }
 
+    /// Lyrics files on disk. They're checked before the providers rather than
+    /// racing them: a local miss would always answer first and keep the race
+    /// from ever calibrating.
+    pub fn set_local(&mut self, local: local::LocalLyricsProvider) {
+        self.local = Some(std::sync::Arc::new(local));
+    }
+
     pub async fn get_lyrics(&self, track: &Track) -> Result<Option<Lyrics>> {
         let cache_key = matching::track_cache_key(track);
 
@@ -88,9 +102,11 @@ impl LyricsManager {
         if let Ok(mut cache) = self.cache.lock() {
             if let Some(cached) = cache.get(&cache_key) {
                 tracing::debug!("Lyrics cache hit for: {} - {}", track.name, track.artist);
+                crate::diagnostics::LYRICS_CACHE.hit();
                 return Ok(Some(cached.clone()));
             }
         }
+        crate::diagnostics::LYRICS_CACHE.miss();
 
         tracing::debug!(
             "Lyrics cache miss, querying providers for: {} - {}",
@@ -98,11 +114,22 @@ impl LyricsManager {
             track.artist
         );
 
+        if let Some(local) = self.local.clone() {
+            if let Probe::Hit(lyrics) = probe_provider(local, track).await {
+                if let Ok(mut cache) = self.cache.lock() {
+                    cache.put(cache_key, lyrics.clone());
+                }
+                return Ok(Some(lyrics));
+            }
+        }
+
         // Once a provider has won a race it becomes the session primary: a single
         // request in the common case. Until then, race every provider concurrently.
-        let result = match self.calibrated_primary() {
+        // Offline lookups race just the local providers and never calibrate
+        let offline = crate::network::is_offline();
+        let result = match self.calibrated_primary().filter(|_| !offline) {
             Some(primary) => self.fetch_sequential(track, primary).await,
-            None => self.fetch_race(track).await,
+            None => self.fetch_race(track, offline).await,
         };
 
         match &result {
@@ -133,8 +160,10 @@ impl LyricsManager {
 
     // Provider indices ordered by their static priority (lower first). Used as the
     // race ordering and as the fallback order once a primary is calibrated.
-    fn priority_order(&self) -> Vec<usize> {
-        let mut order: Vec<usize> = (0..self.providers.len()).collect();
+    fn priority_order(&self, offline: bool) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.providers.len())
+            .filter(|&i| !offline || !self.providers[i].needs_network())
+            .collect();
         order.sort_by_key(|&i| self.providers[i].priority());
         order
     }
@@ -142,7 +171,11 @@ impl LyricsManager {
     // Calibrated path: try the primary first, then the rest as fallback.
     async fn fetch_sequential(&self, track: &Track, primary: usize) -> Result<Option<Lyrics>> {
         let mut order = vec![primary];
-        order.extend(self.priority_order().into_iter().filter(|&i| i != primary));
+        order.extend(
+            self.priority_order(false)
+                .into_iter()
+                .filter(|&i| i != primary),
+        );
 
         let mut saw_miss = false;
         let mut saw_fail = false;
@@ -167,9 +200,9 @@ impl LyricsManager {
     // there the latency signal is ambiguous, so leave calibration open and re-race
     // next track. A rival that failed or timed out before the hit is a reachability
     // win and locks immediately.
-    async fn fetch_race(&self, track: &Track) -> Result<Option<Lyrics>> {
+    async fn fetch_race(&self, track: &Track, offline: bool) -> Result<Option<Lyrics>> {
         let mut probes: futures::stream::FuturesUnordered<_> = self
-            .priority_order()
+            .priority_order(offline)
             .into_iter()
             .map(|idx| {
                 let provider = self.providers[idx].clone();
@@ -185,7 +218,7 @@ impl LyricsManager {
                 Probe::Hit(lyrics) => {
                     // `saw_miss` here means a healthy rival answered (empty) before
                     // this hit — the ambiguous "cold song" case — so we don't lock.
-                    if saw_fail || !saw_miss {
+                    if !offline && (saw_fail || !saw_miss) {
                         if let Ok(mut primary) = self.primary.lock() {
                             *primary = Some(idx);
                         }
@@ -220,7 +253,19 @@ async fn probe_provider(
     provider: std::sync::Arc<dyn provider::LyricsProvider>,
     track: &Track,
 ) -> Probe {
-    match tokio::time::timeout(PROVIDER_TIMEOUT, provider.get_lyrics(track)).await {
+    use crate::diagnostics::{Outcome, LYRICS_PROVIDERS};
+
+    let started = std::time::Instant::now();
+    let result = tokio::time::timeout(PROVIDER_TIMEOUT, provider.get_lyrics(track)).await;
+    let outcome = match &result {
+        Ok(Ok(Some(lyrics))) if !lyrics.lines.is_empty() => Outcome::Found,
+        Ok(Ok(_)) => Outcome::Missing,
+        _ => Outcome::Failed,
+    };
+    LYRICS_PROVIDERS
+        .get(provider.name())
+        .record(outcome, started.elapsed());
+    match result {
         Ok(Ok(Some(lyrics))) if !lyrics.lines.is_empty() => Probe::Hit(lyrics),
         Ok(Ok(_)) => {
             tracing::debug!("Provider {} returned no lyrics", provider.name());
@@ -399,6 +444,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn provider_priorities_try_netease_before_lrclib() {
         assert!(NETEASE_PRIORITY < LRCLIB_PRIORITY);
     }
@@ -555,4 +601,42 @@ mod tests {
         // One source is down, but a reachable source simply had no match → Ok(None).
         assert!(manager.get_lyrics(&track("A", 1)).await.unwrap().is_none());
     }
+
+    #[tokio::test]
+    async fn local_files_are_read_before_the_race() {
+        let dir = std::env::temp_dir().join(format!("amcli-lyrics-dir-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(
+            dir.join("Same Artist - Same Song.lrc"),
+            "[00:01.00]from disk",
+        )
+        .await
+        .unwrap();
+        let (online, online_calls) = probe_provider_for("online", 5, 0, TestOutcome::Hit);
+        let mut manager = LyricsManager::new(4);
+        manager.add_provider(online);
+        manager.set_local(local::LocalLyricsProvider::new(&[dir
+            .to_string_lossy()
+            .into_owned()]));
+
+        let lyrics = manager.get_lyrics(&track("A", 1)).await.unwrap().unwrap();
+        assert_eq!(lyrics.lines[0].text, "from disk");
+        assert_eq!(online_calls.load(Ordering::SeqCst), 0);
+
+        // A local miss falls through to the providers, which still calibrate
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+        assert!(manager.get_lyrics(&track("B", 2)).await.unwrap().is_some());
+        assert_eq!(manager.calibrated_primary(), Some(0));
+    }
+
+    #[test]
+    fn offline_lookups_only_use_local_providers() {
+        let (online, _) = probe_provider_for("online", 5, 0, TestOutcome::Miss);
+        let mut manager = LyricsManager::new(4);
+        manager.add_provider(online);
+        manager.add_provider(Box::new(crate::lyrics::demo::DemoLyricsProvider));
+
+        assert_eq!(manager.priority_order(false), vec![1, 0]);
+        assert_eq!(manager.priority_order(true), vec![1]);
+    }
 }
@@ -47,14 +47,19 @@ impl SongMatch {
     }
 }
 
+impl Default for NeteaseProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NeteaseProvider {
     pub fn new() -> Self {
         Self {
-            client: Client::builder()
-                .timeout(Duration::from_secs(5))
-                .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-                .build()
-                .unwrap_or_default(),
+            client: crate::network::client(
+                Some(Duration::from_secs(5)),
+                Some("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"),
+            ),
         }
     }
 
@@ -0,0 +1,21 @@
+// src/lyrics/provider.rs
+use crate::lyrics::Lyrics;
+use crate::player::Track;
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// A source of lyrics for [`LyricsManager`](crate::lyrics::LyricsManager).
+#[async_trait]
+pub trait LyricsProvider: Send + Sync {
+    /// `Ok(None)` means the source answered but has nothing for this track;
+    /// `Err` means it couldn't be reached.
+    async fn get_lyrics(&self, track: &Track) -> Result<Option<Lyrics>>;
+    /// Lower is preferred when ordering providers.
+    fn priority(&self) -> u8;
+    fn name(&self) -> &str;
+    /// Whether lookups go over the network; such providers are skipped in
+    /// offline mode.
+    fn needs_network(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,676 @@
+// src/mpris/mod.rs
+use anyhow::{bail, Result};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, watch};
+
+use crate::player::{PlaybackState, Track};
+use crate::remote::RemoteCommand;
+
+mod dbus;
+
+use dbus::{Message, Value};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.amcli";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+// RequestName's DO_NOT_QUEUE, and its "someone else has it" answer
+const DO_NOT_QUEUE: u32 = 4;
+const NAME_EXISTS: u32 = 3;
+
+const INTROSPECTION: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node>
+  <interface name="org.freedesktop.DBus.Introspectable">
+    <method name="Introspect"><arg direction="out" type="s"/></method>
+  </interface>
+  <interface name="org.freedesktop.DBus.Peer">
+    <method name="Ping"/>
+  </interface>
+  <interface name="org.freedesktop.DBus.Properties">
+    <method name="Get"><arg direction="in" type="s"/><arg direction="in" type="s"/><arg direction="out" type="v"/></method>
+    <method name="GetAll"><arg direction="in" type="s"/><arg direction="out" type="a{sv}"/></method>
+    <method name="Set"><arg direction="in" type="s"/><arg direction="in" type="s"/><arg direction="in" type="v"/></method>
+    <signal name="PropertiesChanged"><arg type="s"/><arg type="a{sv}"/><arg type="as"/></signal>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2">
+    <method name="Raise"/>
+    <method name="Quit"/>
+    <property name="CanQuit" type="b" access="read"/>
+    <property name="CanRaise" type="b" access="read"/>
+    <property name="HasTrackList" type="b" access="read"/>
+    <property name="Identity" type="s" access="read"/>
+    <property name="SupportedUriSchemes" type="as" access="read"/>
+    <property name="SupportedMimeTypes" type="as" access="read"/>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2.Player">
+    <method name="Next"/>
+    <method name="Previous"/>
+    <method name="Pause"/>
+    <method name="PlayPause"/>
+    <method name="Stop"/>
+    <method name="Play"/>
+    <method name="Seek"><arg direction="in" type="x"/></method>
+    <method name="SetPosition"><arg direction="in" type="o"/><arg direction="in" type="x"/></method>
+    <method name="OpenUri"><arg direction="in" type="s"/></method>
+    <signal name="Seeked"><arg type="x"/></signal>
+    <property name="PlaybackStatus" type="s" access="read"/>
+    <property name="Rate" type="d" access="read"/>
+    <property name="Metadata" type="a{sv}" access="read"/>
+    <property name="Volume" type="d" access="readwrite"/>
+    <property name="Position" type="x" access="read"/>
+    <property name="MinimumRate" type="d" access="read"/>
+    <property name="MaximumRate" type="d" access="read"/>
+    <property name="CanGoNext" type="b" access="read"/>
+    <property name="CanGoPrevious" type="b" access="read"/>
+    <property name="CanPlay" type="b" access="read"/>
+    <property name="CanPause" type="b" access="read"/>
+    <property name="CanSeek" type="b" access="read"/>
+    <property name="CanControl" type="b" access="read"/>
+  </interface>
+</node>
+"#;
+
+/// What the desktop sees of the player, updated on every poll.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MprisState {
+    pub state: PlaybackState,
+    pub track: Option<Track>,
+    pub artwork_url: Option<String>,
+    pub volume: u8,
+}
+
+impl Default for MprisState {
+    fn default() -> Self {
+        Self {
+            state: PlaybackState::Stopped,
+            track: None,
+            artwork_url: None,
+            volume: 0,
+        }
+    }
+}
+
+/// Registers amcli on the session bus as an MPRIS player, so desktop media
+/// controls, `playerctl`, and KDE Connect can see and drive whatever it's
+/// playing. Their commands arrive as [`RemoteCommand`]s. The name is
+/// released once the server is dropped.
+pub struct MprisServer {
+    state: watch::Sender<MprisState>,
+    commands: mpsc::UnboundedReceiver<RemoteCommand>,
+}
+
+impl MprisServer {
+    pub async fn spawn() -> Result<Self> {
+        let stream = dbus::connect_session().await?;
+        let (reader, writer) = stream.into_split();
+        Self::spawn_on(reader, writer).await
+    }
+
+    async fn spawn_on(
+        mut reader: impl AsyncRead + Unpin + Send + 'static,
+        writer: impl AsyncWrite + Unpin + Send + 'static,
+    ) -> Result<Self> {
+        let mut connection = Connection { writer, serial: 0 };
+        connection
+            .call(
+                &mut reader,
+                Message::method_call(
+                    "org.freedesktop.DBus",
+                    "/org/freedesktop/DBus",
+                    "org.freedesktop.DBus",
+                    "Hello",
+                ),
+            )
+            .await?;
+        // A second amcli (say, another profile's) gets its own name
+        for name in [
+            BUS_NAME.to_string(),
+            format!("{}.instance{}", BUS_NAME, std::process::id()),
+        ] {
+            let reply = connection
+                .call(
+                    &mut reader,
+                    Message::method_call(
+                        "org.freedesktop.DBus",
+                        "/org/freedesktop/DBus",
+                        "org.freedesktop.DBus",
+                        "RequestName",
+                    )
+                    .with_body(vec![Value::str(&name), Value::UInt32(DO_NOT_QUEUE)]),
+                )
+                .await?;
+            if reply.body.first().and_then(Value::as_i64) != Some(NAME_EXISTS as i64) {
+                tracing::debug!("MPRIS: registered as {}", name);
+                break;
+            }
+        }
+
+        let (state, state_receiver) = watch::channel(MprisState::default());
+        let (command_sender, commands) = mpsc::unbounded_channel();
+        let (message_sender, messages) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                match dbus::read_message(&mut reader).await {
+                    Ok(message) => {
+                        if message_sender.send(message).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::debug!("MPRIS: bus connection closed: {}", e);
+                        return;
+                    }
+                }
+            }
+        });
+        tokio::spawn(connection.serve(messages, state_receiver, command_sender));
+        Ok(Self { state, commands })
+    }
+
+    /// Only changes to the status, track, or volume are announced; the
+    /// position is answered when asked.
+    pub fn publish(&self, state: MprisState) {
+        self.state.send_if_modified(|current| {
+            if *current == state {
+                return false;
+            }
+            *current = state;
+            true
+        });
+    }
+
+    /// The next pending command, without waiting.
+    pub fn try_next(&mut self) -> Option<RemoteCommand> {
+        self.commands.try_recv().ok()
+    }
+}
+
+struct Connection<W> {
+    writer: W,
+    serial: u32,
+}
+
+impl<W: AsyncWrite + Unpin> Connection<W> {
+    async fn send(&mut self, message: &Message) -> Result<u32> {
+        self.serial += 1;
+        dbus::write_message(&mut self.writer, message, self.serial).await?;
+        Ok(self.serial)
+    }
+
+    // Only used before the reader task starts, so replies can be read inline
+    async fn call(
+        &mut self,
+        reader: &mut (impl AsyncRead + Unpin),
+        message: Message,
+    ) -> Result<Message> {
+        let serial = self.send(&message).await?;
+        loop {
+            let reply = dbus::read_message(reader).await?;
+            if reply.reply_serial != Some(serial) {
+                continue;
+            }
+            if reply.kind == dbus::ERROR {
+                bail!(
+                    "{}: {}",
+                    reply.error_name.unwrap_or_default(),
+                    reply
+                        .body
+                        .first()
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                );
+            }
+            return Ok(reply);
+        }
+    }
+
+    async fn serve(
+        mut self,
+        mut messages: mpsc::UnboundedReceiver<Message>,
+        mut state: watch::Receiver<MprisState>,
+        commands: mpsc::UnboundedSender<RemoteCommand>,
+    ) {
+        let mut player = Player::default();
+        loop {
+            let result = tokio::select! {
+                message = messages.recv() => match message {
+                    Some(message) => self.handle(&message, &mut player, &commands).await,
+                    None => return,
+                },
+                changed = state.changed() => match changed {
+                    Ok(()) => {
+                        let next = state.borrow_and_update().clone();
+                        self.announce(&mut player, next).await
+                    }
+                    // The server was dropped: amcli is quitting
+                    Err(_) => return,
+                },
+            };
+            if let Err(e) = result {
+                tracing::debug!("MPRIS: {}", e);
+                return;
+            }
+        }
+    }
+
+    async fn handle(
+        &mut self,
+        call: &Message,
+        player: &mut Player,
+        commands: &mpsc::UnboundedSender<RemoteCommand>,
+    ) -> Result<()> {
+        if call.kind != dbus::METHOD_CALL {
+            return Ok(());
+        }
+        let reply = match handle_call(call, player) {
+            Ok((body, command)) => {
+                if let Some(command) = command {
+                    let _ = commands.send(command);
+                }
+                Message::reply_to(call, body)
+            }
+            Err(e) => Message::error_to(call, e.name, &e.text),
+        };
+        if call.flags & dbus::NO_REPLY_EXPECTED == 0 {
+            self.send(&reply).await?;
+        }
+        Ok(())
+    }
+
+    async fn announce(&mut self, player: &mut Player, next: MprisState) -> Result<()> {
+        let changed = player.update(next);
+        if changed.is_empty() {
+            return Ok(());
+        }
+        let signal = Message::signal(OBJECT_PATH, PROPERTIES_INTERFACE, "PropertiesChanged")
+            .with_body(vec![
+                Value::str(PLAYER_INTERFACE),
+                Value::dict(changed),
+                Value::string_array(Vec::<String>::new()),
+            ]);
+        self.send(&signal).await?;
+        Ok(())
+    }
+}
+
+struct CallError {
+    name: &'static str,
+    text: String,
+}
+
+impl CallError {
+    fn new(name: &'static str, text: impl Into<String>) -> Self {
+        Self {
+            name,
+            text: text.into(),
+        }
+    }
+}
+
+// The published state, plus a track ID that changes with each new track
+#[derive(Default)]
+struct Player {
+    state: MprisState,
+    track_number: u64,
+}
+
+impl Player {
+    // Properties that changed, for PropertiesChanged
+    fn update(&mut self, next: MprisState) -> Vec<(String, Value)> {
+        let previous = std::mem::replace(&mut self.state, next);
+        let same_track = match (&previous.track, &self.state.track) {
+            (Some(a), Some(b)) => a.name == b.name && a.artist == b.artist && a.album == b.album,
+            (None, None) => true,
+            _ => false,
+        };
+        if !same_track {
+            self.track_number += 1;
+        }
+        let mut changed = Vec::new();
+        if previous.state != self.state.state {
+            changed.push(("PlaybackStatus".to_string(), self.playback_status()));
+        }
+        if !same_track || previous.artwork_url != self.state.artwork_url {
+            changed.push(("Metadata".to_string(), self.metadata()));
+            changed.push(("CanSeek".to_string(), Value::Bool(self.can_seek())));
+        }
+        if previous.volume != self.state.volume {
+            changed.push(("Volume".to_string(), self.volume()));
+        }
+        changed
+    }
+
+    fn track_id(&self) -> String {
+        format!("/org/amcli/track/{}", self.track_number)
+    }
+
+    fn playback_status(&self) -> Value {
+        Value::str(match self.state.state {
+            PlaybackState::Playing => "Playing",
+            PlaybackState::Paused => "Paused",
+            PlaybackState::Stopped => "Stopped",
+        })
+    }
+
+    fn metadata(&self) -> Value {
+        let Some(track) = &self.state.track else {
+            return Value::dict([(
+                "mpris:trackid".to_string(),
+                Value::ObjectPath("/org/mpris/MediaPlayer2/TrackList/NoTrack".into()),
+            )]);
+        };
+        let mut entries = vec![
+            (
+                "mpris:trackid".to_string(),
+                Value::ObjectPath(self.track_id()),
+            ),
+            ("xesam:title".to_string(), Value::str(&track.name)),
+            (
+                "xesam:artist".to_string(),
+                Value::string_array([&track.artist]),
+            ),
+            ("xesam:album".to_string(), Value::str(&track.album)),
+        ];
+        if !track.duration.is_zero() {
+            entries.push((
+                "mpris:length".to_string(),
+                Value::Int64(track.duration.as_micros() as i64),
+            ));
+        }
+        if let Some(url) = &self.state.artwork_url {
+            entries.push(("mpris:artUrl".to_string(), Value::str(url)));
+        }
+        Value::dict(entries)
+    }
+
+    fn volume(&self) -> Value {
+        Value::Double(self.state.volume as f64 / 100.0)
+    }
+
+    fn can_seek(&self) -> bool {
+        self.state
+            .track
+            .as_ref()
+            .is_some_and(|track| !track.duration.is_zero())
+    }
+
+    fn property(&self, interface: &str, name: &str) -> Option<Value> {
+        let has_track = self.state.track.is_some();
+        Some(match (interface, name) {
+            (ROOT_INTERFACE, "CanQuit") => Value::Bool(true),
+            (ROOT_INTERFACE, "CanRaise") => Value::Bool(false),
+            (ROOT_INTERFACE, "HasTrackList") => Value::Bool(false),
+            (ROOT_INTERFACE, "Identity") => Value::str("amcli"),
+            (ROOT_INTERFACE, "SupportedUriSchemes") => Value::string_array(Vec::<String>::new()),
+            (ROOT_INTERFACE, "SupportedMimeTypes") => Value::string_array(Vec::<String>::new()),
+            (PLAYER_INTERFACE, "PlaybackStatus") => self.playback_status(),
+            (PLAYER_INTERFACE, "Rate" | "MinimumRate" | "MaximumRate") => Value::Double(1.0),
+            (PLAYER_INTERFACE, "Metadata") => self.metadata(),
+            (PLAYER_INTERFACE, "Volume") => self.volume(),
+            (PLAYER_INTERFACE, "Position") => Value::Int64(
+                self.state
+                    .track
+                    .as_ref()
+                    .map_or(0, |track| track.position.as_micros() as i64),
+            ),
+            (PLAYER_INTERFACE, "CanGoNext" | "CanGoPrevious") => Value::Bool(has_track),
+            (PLAYER_INTERFACE, "CanPlay" | "CanPause" | "CanControl") => Value::Bool(true),
+            (PLAYER_INTERFACE, "CanSeek") => Value::Bool(self.can_seek()),
+            _ => return None,
+        })
+    }
+
+    fn properties(&self, interface: &str) -> Vec<(String, Value)> {
+        const ROOT: &[&str] = &[
+            "CanQuit",
+            "CanRaise",
+            "HasTrackList",
+            "Identity",
+            "SupportedUriSchemes",
+            "SupportedMimeTypes",
+        ];
+        const PLAYER: &[&str] = &[
+            "PlaybackStatus",
+            "Rate",
+            "Metadata",
+            "Volume",
+            "Position",
+            "MinimumRate",
+            "MaximumRate",
+            "CanGoNext",
+            "CanGoPrevious",
+            "CanPlay",
+            "CanPause",
+            "CanSeek",
+            "CanControl",
+        ];
+        let names = match interface {
+            ROOT_INTERFACE => ROOT,
+            PLAYER_INTERFACE => PLAYER,
+            _ => &[],
+        };
+        names
+            .iter()
+            .filter_map(|name| Some((name.to_string(), self.property(interface, name)?)))
+            .collect()
+    }
+}
+
+// The reply body, and the command for amcli to carry out, if any
+fn handle_call(
+    call: &Message,
+    player: &Player,
+) -> std::result::Result<(Vec<Value>, Option<RemoteCommand>), CallError> {
+    const UNKNOWN_METHOD: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+    const INVALID_ARGS: &str = "org.freedesktop.DBus.Error.InvalidArgs";
+    let arg = |i: usize| call.body.get(i);
+    let str_arg = |i: usize| {
+        arg(i)
+            .and_then(Value::as_str)
+            .ok_or_else(|| CallError::new(INVALID_ARGS, "missing argument"))
+    };
+    let member = call.member.as_deref().unwrap_or_default();
+    if call.path.as_deref() != Some(OBJECT_PATH) {
+        return Err(CallError::new(
+            "org.freedesktop.DBus.Error.UnknownObject",
+            format!("no object at {}", call.path.as_deref().unwrap_or_default()),
+        ));
+    }
+    let command = match (call.interface.as_deref().unwrap_or_default(), member) {
+        ("org.freedesktop.DBus.Introspectable", "Introspect") => {
+            return Ok((vec![Value::str(INTROSPECTION)], None))
+        }
+        ("org.freedesktop.DBus.Peer", "Ping") => return Ok((vec![], None)),
+        (PROPERTIES_INTERFACE, "Get") => {
+            let value = player
+                .property(str_arg(0)?, str_arg(1)?)
+                .ok_or_else(|| CallError::new(INVALID_ARGS, "no such property"))?;
+            return Ok((vec![Value::variant(value)], None));
+        }
+        (PROPERTIES_INTERFACE, "GetAll") => {
+            return Ok((vec![Value::dict(player.properties(str_arg(0)?))], None))
+        }
+        (PROPERTIES_INTERFACE, "Set") => match (str_arg(0)?, str_arg(1)?) {
+            (PLAYER_INTERFACE, "Volume") => {
+                let volume = arg(2)
+                    .and_then(Value::as_f64)
+                    .ok_or_else(|| CallError::new(INVALID_ARGS, "Volume is a double"))?;
+                RemoteCommand::SetVolume((volume.clamp(0.0, 1.0) * 100.0).round() as u8)
+            }
+            _ => {
+                return Err(CallError::new(
+                    "org.freedesktop.DBus.Error.PropertyReadOnly",
+                    "read-only property",
+                ))
+            }
+        },
+        (ROOT_INTERFACE, "Raise") => return Ok((vec![], None)),
+        (ROOT_INTERFACE, "Quit") => RemoteCommand::Quit,
+        (PLAYER_INTERFACE, "Play") => RemoteCommand::Play,
+        (PLAYER_INTERFACE, "Pause") => RemoteCommand::Pause,
+        (PLAYER_INTERFACE, "PlayPause") => RemoteCommand::TogglePlayPause,
+        (PLAYER_INTERFACE, "Stop") => RemoteCommand::Stop,
+        (PLAYER_INTERFACE, "Next") => RemoteCommand::Next,
+        (PLAYER_INTERFACE, "Previous") => RemoteCommand::Previous,
+        (PLAYER_INTERFACE, "Seek") => {
+            let micros = arg(0)
+                .and_then(Value::as_i64)
+                .ok_or_else(|| CallError::new(INVALID_ARGS, "Seek takes an offset"))?;
+            RemoteCommand::Seek((micros as f64 / 1_000_000.0).round() as i32)
+        }
+        (PLAYER_INTERFACE, "SetPosition") => {
+            // Ignored when it's for a track that has already moved on
+            if str_arg(0)? != player.track_id() {
+                return Ok((vec![], None));
+            }
+            let micros = arg(1)
+                .and_then(Value::as_i64)
+                .ok_or_else(|| CallError::new(INVALID_ARGS, "SetPosition takes a position"))?;
+            RemoteCommand::SetPosition(Duration::from_micros(micros.max(0) as u64))
+        }
+        (PLAYER_INTERFACE, "OpenUri") => {
+            return Err(CallError::new(
+                "org.freedesktop.DBus.Error.NotSupported",
+                "amcli can't open URIs",
+            ))
+        }
+        _ => {
+            return Err(CallError::new(
+                UNKNOWN_METHOD,
+                format!("no method {}", member),
+            ))
+        }
+    };
+    Ok((vec![], Some(command)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(interface: &str, member: &str, body: Vec<Value>) -> Message {
+        Message::method_call(BUS_NAME, OBJECT_PATH, interface, member).with_body(body)
+    }
+
+    fn playing() -> MprisState {
+        MprisState {
+            state: PlaybackState::Playing,
+            track: Some(Track {
+                name: "Song".into(),
+                artist: "Artist".into(),
+                album: "Album".into(),
+                duration: Duration::from_secs(200),
+                position: Duration::from_secs(20),
+            }),
+            artwork_url: Some("https://example.com/a.jpg".into()),
+            volume: 40,
+        }
+    }
+
+    #[test]
+    fn changes_announce_only_what_moved() {
+        let mut player = Player::default();
+        let changed: Vec<String> = player
+            .update(playing())
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(changed, ["PlaybackStatus", "Metadata", "CanSeek", "Volume"]);
+
+        // A later position on the same track says nothing
+        let mut later = playing();
+        later.track.as_mut().unwrap().position = Duration::from_secs(21);
+        assert!(player.update(later).is_empty());
+        assert_eq!(
+            player.property(PLAYER_INTERFACE, "Position"),
+            Some(Value::Int64(21_000_000))
+        );
+
+        let mut paused = playing();
+        paused.state = PlaybackState::Paused;
+        let changed = player.update(paused);
+        assert_eq!(
+            changed,
+            [("PlaybackStatus".to_string(), Value::str("Paused"))]
+        );
+    }
+
+    #[test]
+    fn calls_turn_into_commands() {
+        let mut player = Player::default();
+        player.update(playing());
+        let command = |message: Message| handle_call(&message, &player).ok().and_then(|(_, c)| c);
+
+        assert_eq!(
+            command(call(PLAYER_INTERFACE, "PlayPause", vec![])),
+            Some(RemoteCommand::TogglePlayPause)
+        );
+        assert_eq!(
+            command(call(
+                PLAYER_INTERFACE,
+                "Seek",
+                vec![Value::Int64(-15_000_000)]
+            )),
+            Some(RemoteCommand::Seek(-15))
+        );
+        assert_eq!(
+            command(call(
+                PLAYER_INTERFACE,
+                "SetPosition",
+                vec![
+                    Value::ObjectPath(player.track_id()),
+                    Value::Int64(60_000_000)
+                ]
+            )),
+            Some(RemoteCommand::SetPosition(Duration::from_secs(60)))
+        );
+        // For a track that's no longer playing
+        assert_eq!(
+            command(call(
+                PLAYER_INTERFACE,
+                "SetPosition",
+                vec![
+                    Value::ObjectPath("/org/amcli/track/0".into()),
+                    Value::Int64(0)
+                ]
+            )),
+            None
+        );
+        assert_eq!(
+            command(call(
+                PROPERTIES_INTERFACE,
+                "Set",
+                vec![
+                    Value::str(PLAYER_INTERFACE),
+                    Value::str("Volume"),
+                    Value::variant(Value::Double(0.25))
+                ]
+            )),
+            Some(RemoteCommand::SetVolume(25))
+        );
+
+        let (body, _) = handle_call(
+            &call(
+                PROPERTIES_INTERFACE,
+                "Get",
+                vec![Value::str(PLAYER_INTERFACE), Value::str("Metadata")],
+            ),
+            &player,
+        )
+        .ok()
+        .unwrap();
+        let Value::Variant(metadata) = &body[0] else {
+            panic!("Get returns a variant");
+        };
+        assert!(metadata.signature() == "a{sv}");
+        assert!(handle_call(&call(PLAYER_INTERFACE, "Shuffle", vec![]), &player).is_err());
+        assert!(handle_call(
+            &call(PLAYER_INTERFACE, "OpenUri", vec![Value::str("x")]),
+            &player
+        )
+        .is_err());
+    }
+}
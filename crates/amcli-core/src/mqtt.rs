@@ -0,0 +1,320 @@
+// src/mqtt.rs
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+
+use crate::config::MqttConfig;
+use crate::player::{PlaybackState, Track};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_MIN: Duration = Duration::from_secs(5);
+const RETRY_MAX: Duration = Duration::from_secs(60);
+
+/// What the publisher reports, already in wire form. Positions are left
+/// out so the broker only hears about changes a dashboard would show.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NowPlaying {
+    state: String,
+    track: String,
+    artwork_url: String,
+}
+
+impl NowPlaying {
+    pub fn new(state: PlaybackState, track: Option<&Track>, artwork_url: Option<&str>) -> Self {
+        let state = match state {
+            PlaybackState::Playing => "playing",
+            PlaybackState::Paused => "paused",
+            PlaybackState::Stopped => "stopped",
+        };
+        // `{}` when nothing is loaded, so templates can test a field's presence
+        let track = match track {
+            Some(track) => json!({
+                "title": track.name,
+                "artist": track.artist,
+                "album": track.album,
+                "duration": track.duration.as_secs(),
+            })
+            .to_string(),
+            None => "{}".to_string(),
+        };
+        Self {
+            state: state.to_string(),
+            track,
+            artwork_url: artwork_url.unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// Publishes now-playing updates to an MQTT broker from a background task.
+/// Messages are retained, so subscribers that connect later (such as Home
+/// Assistant after a restart) see the current state right away. The task
+/// reconnects on its own and stops when the publisher is dropped.
+pub struct MqttPublisher {
+    sender: watch::Sender<NowPlaying>,
+}
+
+impl MqttPublisher {
+    /// `None` unless `[mqtt] enabled` is set.
+    pub fn spawn(config: &MqttConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let (sender, receiver) =
+            watch::channel(NowPlaying::new(PlaybackState::Stopped, None, None));
+        tokio::spawn(run(config.clone(), receiver));
+        Some(Self { sender })
+    }
+
+    /// Only sends what changed since the last call.
+    pub fn publish(&self, now_playing: NowPlaying) {
+        self.sender.send_if_modified(|current| {
+            if *current == now_playing {
+                return false;
+            }
+            *current = now_playing;
+            true
+        });
+    }
+}
+
+async fn run(config: MqttConfig, mut receiver: watch::Receiver<NowPlaying>) {
+    let mut retry = RETRY_MIN;
+    loop {
+        match Connection::open(&config).await {
+            Ok(mut connection) => {
+                retry = RETRY_MIN;
+                match connection.publish_updates(&config, &mut receiver).await {
+                    // The publisher was dropped: amcli is quitting
+                    Ok(()) => {
+                        let _ = connection.close(&config).await;
+                        return;
+                    }
+                    Err(e) => tracing::warn!("MQTT connection lost: {}", e),
+                }
+            }
+            Err(e) => tracing::warn!(
+                "MQTT connect to {}:{} failed: {}",
+                config.host,
+                config.port,
+                e
+            ),
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(retry) => {}
+            // Nothing more to publish
+            Err(_) = receiver.changed() => return,
+        }
+        retry = (retry * 2).min(RETRY_MAX);
+    }
+}
+
+struct Connection {
+    stream: TcpStream,
+    // Payloads last sent per topic on this connection
+    sent: Vec<(String, String)>,
+}
+
+impl Connection {
+    async fn open(config: &MqttConfig) -> Result<Self> {
+        let mut stream = tokio::time::timeout(
+            CONNECT_TIMEOUT,
+            TcpStream::connect((config.host.as_str(), config.port)),
+        )
+        .await
+        .map_err(|_| anyhow!("timed out"))??;
+        stream.write_all(&connect_packet(config)).await?;
+        let mut connack = [0u8; 4];
+        tokio::time::timeout(CONNECT_TIMEOUT, stream.read_exact(&mut connack))
+            .await
+            .map_err(|_| anyhow!("no reply from the broker"))??;
+        check_connack(&connack)?;
+        let mut connection = Self {
+            stream,
+            sent: Vec::new(),
+        };
+        connection
+            .send(&config.availability_topic, "online".into())
+            .await?;
+        Ok(connection)
+    }
+
+    // Returns once the sending side is gone
+    async fn publish_updates(
+        &mut self,
+        config: &MqttConfig,
+        receiver: &mut watch::Receiver<NowPlaying>,
+    ) -> Result<()> {
+        loop {
+            let now_playing = receiver.borrow_and_update().clone();
+            self.send(&config.state_topic, now_playing.state).await?;
+            self.send(&config.track_topic, now_playing.track).await?;
+            self.send(&config.artwork_topic, now_playing.artwork_url)
+                .await?;
+            if receiver.changed().await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn send(&mut self, topic: &str, payload: String) -> Result<()> {
+        if self.sent.iter().any(|(t, p)| t == topic && *p == payload) {
+            return Ok(());
+        }
+        self.stream
+            .write_all(&publish_packet(topic, payload.as_bytes()))
+            .await?;
+        self.sent.retain(|(t, _)| t != topic);
+        self.sent.push((topic.to_string(), payload));
+        Ok(())
+    }
+
+    async fn close(&mut self, config: &MqttConfig) -> Result<()> {
+        self.send(&config.availability_topic, "offline".into())
+            .await?;
+        // DISCONNECT
+        self.stream.write_all(&[0xE0, 0x00]).await?;
+        Ok(())
+    }
+}
+
+// MQTT 3.1.1 packets. Everything is published at QoS 0 with the retain flag
+// set, which is all a now-playing feed needs, so there are no acknowledgements
+// to track after CONNACK.
+
+fn connect_packet(config: &MqttConfig) -> Vec<u8> {
+    let username = config.username.as_deref().filter(|u| !u.is_empty());
+    let password = config.password.as_deref().filter(|_| username.is_some());
+    // Clean session, plus a retained "offline" will on the availability topic
+    let mut flags = 0x02 | 0x04 | 0x20;
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if password.is_some() {
+        flags |= 0x40;
+    }
+
+    let mut body = Vec::new();
+    push_str(&mut body, "MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&0u16.to_be_bytes()); // no keep-alive timeout
+    push_str(&mut body, &config.client_id);
+    push_str(&mut body, &config.availability_topic);
+    push_str(&mut body, "offline");
+    for field in [username, password].into_iter().flatten() {
+        push_str(&mut body, field);
+    }
+    packet(0x10, body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_str(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(0x31, body)
+}
+
+fn check_connack(connack: &[u8; 4]) -> Result<()> {
+    if connack[0] != 0x20 {
+        return Err(anyhow!("unexpected reply from the broker"));
+    }
+    match connack[3] {
+        0 => Ok(()),
+        1 => Err(anyhow!("broker doesn't speak MQTT 3.1.1")),
+        2 => Err(anyhow!("client ID rejected")),
+        3 => Err(anyhow!("broker unavailable")),
+        4 => Err(anyhow!("bad username or password")),
+        5 => Err(anyhow!("not authorized")),
+        code => Err(anyhow!("connection refused ({})", code)),
+    }
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut packet = vec![header];
+    // Remaining length: seven bits per byte, high bit set while more follow
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend(body);
+    packet
+}
+
+fn push_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn packets_follow_the_mqtt_wire_format() {
+        assert_eq!(
+            publish_packet("a/b", b"on"),
+            [0x31, 7, 0, 3, b'a', b'/', b'b', b'o', b'n']
+        );
+        let long = packet(0x31, vec![0; 200]);
+        assert_eq!(&long[..3], [0x31, 0xC8, 0x01]);
+
+        let config = MqttConfig {
+            username: Some("ha".into()),
+            password: Some("pw".into()),
+            ..MqttConfig::default()
+        };
+        let connect = connect_packet(&config);
+        assert_eq!(&connect[2..9], [0, 4, b'M', b'Q', b'T', b'T', 4]);
+        assert_eq!(connect[9], 0xE6);
+        assert!(check_connack(&[0x20, 2, 0, 0]).is_ok());
+        assert!(check_connack(&[0x20, 2, 0, 4]).is_err());
+    }
+
+    #[tokio::test]
+    async fn publishes_retained_now_playing_to_the_broker() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = MqttConfig {
+            enabled: true,
+            host: "127.0.0.1".into(),
+            port: listener.local_addr().unwrap().port(),
+            ..MqttConfig::default()
+        };
+        let publisher = MqttPublisher::spawn(&config).unwrap();
+        let track = Track {
+            name: "Feather".into(),
+            artist: "Nujabes".into(),
+            album: "Modal Soul".into(),
+            duration: Duration::from_secs(175),
+            position: Duration::ZERO,
+        };
+        publisher.publish(NowPlaying::new(PlaybackState::Playing, Some(&track), None));
+
+        let (mut broker, _) = listener.accept().await.unwrap();
+        let mut header = [0u8; 2];
+        broker.read_exact(&mut header).await.unwrap();
+        let mut connect = vec![0u8; header[1] as usize];
+        broker.read_exact(&mut connect).await.unwrap();
+        broker.write_all(&[0x20, 2, 0, 0]).await.unwrap();
+        drop(publisher);
+
+        let mut received = Vec::new();
+        broker.read_to_end(&mut received).await.unwrap();
+        let received = String::from_utf8_lossy(&received);
+        assert!(received.contains("amcli/availabilityonline"));
+        assert!(received.contains("amcli/stateplaying"));
+        assert!(received.contains(r#""title":"Feather""#));
+        assert!(received.contains("amcli/availabilityoffline"));
+    }
+}
@@ -0,0 +1,232 @@
+// src/musickit.rs
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::artist::ArtistInfo;
+use crate::config::MusicKitConfig;
+
+const API_URL: &str = "https://api.music.apple.com/v1/catalog";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// Size requested from artwork URL templates
+const ARTWORK_SIZE: &str = "600";
+const SIMILAR_ARTISTS: usize = 5;
+
+/// A song from the Apple Music catalog, whether or not it's in the library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogSong {
+    pub id: String,
+    pub name: String,
+    pub artist: String,
+    pub album: String,
+    // music.apple.com link
+    pub url: Option<String>,
+}
+
+/// Apple Music API client for catalog search, editorial notes, and similar
+/// artists. Requests are signed with the developer token from `[musickit]`.
+#[derive(Clone)]
+pub struct MusicKitClient {
+    client: reqwest::Client,
+    token: String,
+    storefront: String,
+}
+
+impl MusicKitClient {
+    /// `None` without a developer token.
+    pub fn from_config(config: &MusicKitConfig) -> Option<Self> {
+        let token = config
+            .developer_token
+            .as_deref()
+            .map(str::trim)
+            .filter(|token| !token.is_empty())?;
+        Some(Self {
+            client: crate::network::client(Some(REQUEST_TIMEOUT), None),
+            token: token.to_string(),
+            storefront: config.storefront.trim().to_lowercase(),
+        })
+    }
+
+    async fn get(&self, path: &str, query: &[(&str, &str)]) -> Result<Value> {
+        crate::network::ensure_online("Apple Music catalog")?;
+        let response = self
+            .client
+            .get(format!("{}/{}/{}", API_URL, self.storefront, path))
+            .bearer_auth(&self.token)
+            .query(query)
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(anyhow!("Apple Music rejected the MusicKit developer token"));
+        }
+        Ok(response.error_for_status()?.json().await?)
+    }
+
+    /// Catalog songs matching `term`, best match first.
+    pub async fn search_songs(&self, term: &str, limit: usize) -> Result<Vec<CatalogSong>> {
+        let limit = limit.to_string();
+        let json = self
+            .get(
+                "search",
+                &[("term", term), ("types", "songs"), ("limit", &limit)],
+            )
+            .await?;
+        Ok(parse_songs(&json["results"]["songs"]["data"]))
+    }
+
+    /// The song radio station seeded by the catalog's closest match to
+    /// `name` by `artist`, or `None` when the catalog doesn't have it.
+    pub async fn song_station_url(&self, name: &str, artist: &str) -> Result<Option<String>> {
+        let songs = self
+            .search_songs(&format!("{} {}", name, artist), 1)
+            .await?;
+        Ok(songs.first().map(|song| self.station_url(&song.id)))
+    }
+
+    // Apple names each song's station "ra." plus the song's catalog ID
+    fn station_url(&self, song_id: &str) -> String {
+        format!(
+            "https://music.apple.com/{}/station/ra.{}",
+            self.storefront, song_id
+        )
+    }
+
+    /// Apple's editorial notes and artist photo, plus a few similar artists
+    /// for "you might also like". `None` when the catalog has no notes.
+    pub async fn artist(&self, name: &str) -> Result<Option<ArtistInfo>> {
+        let json = self
+            .get(
+                "search",
+                &[("term", name), ("types", "artists"), ("limit", "1")],
+            )
+            .await?;
+        let artist = &json["results"]["artists"]["data"][0];
+        let Some(mut info) = parse_artist(artist) else {
+            return Ok(None);
+        };
+        if let Some(id) = artist["id"].as_str() {
+            let limit = SIMILAR_ARTISTS.to_string();
+            // Similar artists are a bonus; the notes are still worth showing
+            match self
+                .get(
+                    &format!("artists/{}/view/similar-artists", id),
+                    &[("limit", &limit)],
+                )
+                .await
+            {
+                Ok(json) => info.similar = names(&json["data"]),
+                Err(e) => tracing::debug!("Similar artists lookup failed: {}", e),
+            }
+        }
+        Ok(Some(info))
+    }
+}
+
+fn parse_songs(data: &Value) -> Vec<CatalogSong> {
+    data.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|song| {
+            let attributes = &song["attributes"];
+            Some(CatalogSong {
+                id: song["id"].as_str()?.to_string(),
+                name: attributes["name"].as_str()?.to_string(),
+                artist: attributes["artistName"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                album: attributes["albumName"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                url: attributes["url"].as_str().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+// Prefers the longer editorial note, which most popular artists have
+fn parse_artist(artist: &Value) -> Option<ArtistInfo> {
+    let attributes = &artist["attributes"];
+    let notes = &attributes["editorialNotes"];
+    let summary = notes["standard"]
+        .as_str()
+        .or_else(|| notes["short"].as_str())
+        .map(str::trim)
+        .filter(|summary| !summary.is_empty())?;
+    Some(ArtistInfo {
+        name: attributes["name"].as_str().unwrap_or_default().to_string(),
+        summary: summary.to_string(),
+        image_url: attributes["artwork"]["url"].as_str().map(|template| {
+            template
+                .replace("{w}", ARTWORK_SIZE)
+                .replace("{h}", ARTWORK_SIZE)
+        }),
+        similar: Vec::new(),
+        source: "Apple Music",
+    })
+}
+
+fn names(data: &Value) -> Vec<String> {
+    data.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item["attributes"]["name"].as_str().map(str::to_string))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn clients_need_a_developer_token() {
+        let mut config = MusicKitConfig::default();
+        assert!(MusicKitClient::from_config(&config).is_none());
+        config.developer_token = Some("  ".into());
+        assert!(MusicKitClient::from_config(&config).is_none());
+        config.developer_token = Some("eyJhbGciOiJFUzI1NiJ9.token".into());
+        config.storefront = "JP".into();
+        let client = MusicKitClient::from_config(&config).unwrap();
+        assert_eq!(
+            client.station_url("1440857781"),
+            "https://music.apple.com/jp/station/ra.1440857781"
+        );
+    }
+
+    #[test]
+    fn catalog_results_become_songs_and_artist_notes() {
+        let songs = json!([
+            {
+                "id": "1440857781",
+                "attributes": {
+                    "name": "Feather",
+                    "artistName": "Nujabes",
+                    "albumName": "Modal Soul",
+                    "url": "https://music.apple.com/us/song/feather/1440857781"
+                }
+            },
+            { "id": "1", "attributes": {} }
+        ]);
+        let songs = parse_songs(&songs);
+        assert_eq!(songs.len(), 1);
+        assert_eq!(songs[0].artist, "Nujabes");
+
+        let artist = json!({
+            "id": "28673423",
+            "attributes": {
+                "name": "Nujabes",
+                "editorialNotes": { "short": "Jazz-inflected hip-hop." },
+                "artwork": { "url": "https://is1-ssl.mzstatic.com/image/{w}x{h}bb.jpg" }
+            }
+        });
+        let info = parse_artist(&artist).unwrap();
+        assert_eq!(info.summary, "Jazz-inflected hip-hop.");
+        assert_eq!(
+            info.image_url.as_deref(),
+            Some("https://is1-ssl.mzstatic.com/image/600x600bb.jpg")
+        );
+        assert!(parse_artist(&json!({ "attributes": { "name": "Nobody" } })).is_none());
+    }
+}
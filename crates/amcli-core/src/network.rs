@@ -0,0 +1,152 @@
+// src/network.rs
+use crate::config::NetworkConfig;
+use anyhow::{anyhow, Context, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+// Process-wide, so the settings toggle reaches clients that were built at
+// startup without threading a flag through every provider.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static SETTINGS: RwLock<Settings> = RwLock::new(Settings {
+    proxy: None,
+    timeout: None,
+    user_agent: None,
+});
+
+/// Turns off online artwork and lyrics lookups. Cached artwork and local
+/// lyrics providers keep working.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+// Guard for code paths that are about to make a request
+pub(crate) fn ensure_online(what: &str) -> Result<()> {
+    if is_offline() {
+        Err(anyhow!("Offline mode: skipped {}", what))
+    } else {
+        Ok(())
+    }
+}
+
+/// Applies `[network]` to every HTTP client built afterwards. Call it before
+/// creating players and lyrics providers.
+pub fn configure(config: &NetworkConfig) -> Result<()> {
+    let settings = Settings::from_config(config)?;
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = settings;
+    set_offline(config.offline);
+    Ok(())
+}
+
+// A client with the configured proxy, timeout, and User-Agent. `timeout` and
+// `user_agent` are the caller's own defaults, used when the config leaves
+// them unset.
+pub(crate) fn client(timeout: Option<Duration>, user_agent: Option<&str>) -> reqwest::Client {
+    SETTINGS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .builder(timeout, user_agent)
+        .build()
+        .unwrap_or_default()
+}
+
+// For endless responses such as radio streams, which a whole-request timeout
+// would cut off; the configured timeout bounds connecting instead
+#[cfg(feature = "internal-player")]
+pub(crate) fn stream_client(user_agent: Option<&str>) -> reqwest::Client {
+    let settings = SETTINGS.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let connect_timeout = settings.timeout.unwrap_or(Duration::from_secs(10));
+    Settings {
+        timeout: None,
+        ..settings
+    }
+    .builder(None, user_agent)
+    .connect_timeout(connect_timeout)
+    .build()
+    .unwrap_or_default()
+}
+
+#[derive(Debug, Clone)]
+struct Settings {
+    proxy: Option<reqwest::Proxy>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+}
+
+impl Settings {
+    // Proxy URLs are checked here so a typo fails at startup instead of
+    // quietly falling back to a direct connection
+    fn from_config(config: &NetworkConfig) -> Result<Self> {
+        let proxy = config
+            .proxy
+            .as_deref()
+            .filter(|url| !url.trim().is_empty())
+            .map(|url| reqwest::Proxy::all(url.trim()).context(format!("Invalid proxy {}", url)))
+            .transpose()?;
+        Ok(Self {
+            proxy,
+            timeout: config.timeout_secs.map(Duration::from_secs),
+            user_agent: config.user_agent.clone().filter(|ua| !ua.is_empty()),
+        })
+    }
+
+    fn builder(
+        &self,
+        timeout: Option<Duration>,
+        user_agent: Option<&str>,
+    ) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder();
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        if let Some(timeout) = self.timeout.or(timeout) {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(user_agent) = self.user_agent.as_deref().or(user_agent) {
+            builder = builder.user_agent(user_agent);
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_accept_http_and_socks_proxies_and_reject_garbage() {
+        for proxy in ["http://proxy.corp:8080", "socks5h://127.0.0.1:1080"] {
+            let config = NetworkConfig {
+                proxy: Some(proxy.into()),
+                ..NetworkConfig::default()
+            };
+            assert!(Settings::from_config(&config).unwrap().proxy.is_some());
+        }
+
+        let config = NetworkConfig {
+            proxy: Some("not a url".into()),
+            ..NetworkConfig::default()
+        };
+        assert!(Settings::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn configured_values_override_client_defaults() {
+        let config = NetworkConfig {
+            timeout_secs: Some(30),
+            user_agent: Some("corp-approved/1.0".into()),
+            ..NetworkConfig::default()
+        };
+        let settings = Settings::from_config(&config).unwrap();
+        assert_eq!(settings.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(settings.user_agent.as_deref(), Some("corp-approved/1.0"));
+
+        let defaults = Settings::from_config(&NetworkConfig::default()).unwrap();
+        assert!(defaults.proxy.is_none() && defaults.timeout.is_none());
+        assert!(defaults.user_agent.is_none());
+    }
+}
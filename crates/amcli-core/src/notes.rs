@@ -0,0 +1,70 @@
+// src/notes.rs
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::store::JsonStore;
+
+/// A short note for each annotated track, keyed by
+/// [`track_key`](crate::bookmarks::track_key). Stored as JSON in amcli's data
+/// directory.
+#[derive(Debug, Default)]
+pub struct Notes {
+    notes: JsonStore<BTreeMap<String, String>>,
+}
+
+impl Notes {
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("amcli/notes.json")
+    }
+
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        Ok(Self {
+            notes: JsonStore::load(path).await?,
+        })
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        self.notes.save().await
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.notes.get(key).map(String::as_str)
+    }
+
+    /// Replaces the track's note; a blank one removes it. Returns the old note.
+    pub fn set(&mut self, key: &str, note: &str) -> Option<String> {
+        let note = note.trim();
+        if note.is_empty() {
+            self.notes.remove(key)
+        } else {
+            self.notes.insert(key.to_string(), note.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn notes_are_replaced_cleared_and_round_trip() {
+        let path = std::env::temp_dir().join(format!("amcli-notes-{}.json", std::process::id()));
+        let mut notes = Notes::load(path.clone()).await.unwrap();
+        assert_eq!(notes.set("mix", "good opener"), None);
+        assert_eq!(
+            notes.set("mix", " opener, 124 BPM "),
+            Some("good opener".into())
+        );
+        notes.set("demo", "too quiet");
+        assert_eq!(notes.set("demo", "  "), Some("too quiet".into()));
+        notes.save().await.unwrap();
+
+        let loaded = Notes::load(path.clone()).await.unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.get("mix"), Some("opener, 124 BPM"));
+        assert_eq!(loaded.get("demo"), None);
+    }
+}
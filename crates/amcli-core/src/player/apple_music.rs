@@ -0,0 +1,1156 @@
+// src/player/apple_music.rs
+use super::{
+    MediaPlayer, Normalization, NormalizationKind, PlaybackOptions, PlaybackState, PlayerStatus,
+    QueueEntry, RepeatMode, Track,
+};
+use crate::library::LibraryItem;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::time::Duration;
+
+#[cfg(test)]
+use mockall::automock;
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait CommandRunner: Send + Sync {
+    async fn execute(&self, script: &str) -> Result<std::process::Output>;
+}
+
+pub struct OsascriptRunner;
+
+#[async_trait]
+impl CommandRunner for OsascriptRunner {
+    async fn execute(&self, script: &str) -> Result<std::process::Output> {
+        let started = std::time::Instant::now();
+        let output = tokio::process::Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .output()
+            .await;
+        crate::diagnostics::OSASCRIPT.record(started.elapsed());
+        output.map_err(|e| anyhow!(e))
+    }
+}
+
+use crate::artwork::itunes::ItunesArtworkSearch;
+use lru::LruCache;
+use sha2::{Digest, Sha256};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub struct AppleMusicController {
+    runner: Box<dyn CommandRunner>,
+    artwork_cache: Mutex<LruCache<String, Option<String>>>,
+    itunes: ItunesArtworkSearch,
+}
+
+impl Default for AppleMusicController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppleMusicController {
+    pub fn new() -> Self {
+        Self {
+            runner: Box::new(OsascriptRunner),
+            artwork_cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(20).expect("cache capacity must be non-zero"),
+            )),
+            itunes: ItunesArtworkSearch::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_runner(runner: Box<dyn CommandRunner>) -> Self {
+        Self {
+            runner,
+            artwork_cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(20).expect("cache capacity must be non-zero"),
+            )),
+            itunes: ItunesArtworkSearch::new(),
+        }
+    }
+
+    async fn execute_script(&self, script: &str) -> Result<String> {
+        let output = self.runner.execute(script).await?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(anyhow!(
+                "AppleScript failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    async fn export_current_track_artwork(&self, track: &Track) -> Result<ArtworkExport> {
+        let path = current_track_artwork_path(track);
+        let path_string = path.to_string_lossy();
+        let escaped_path = escape_applescript_string(&path_string);
+        // The player may have moved on since `track` was polled; exporting then
+        // would file the next track's cover under this one
+        let script = format!(
+            r#"
+            tell application "Music"
+                if player state is stopped then return "{changed}"
+                if name of current track is not "{}" then return "{changed}"
+                if artist of current track is not "{}" then return "{changed}"
+                if (count of artworks of current track) is 0 then return ""
+                set artwork_data to data of artwork 1 of current track
+            end tell
+
+            set output_path to "{}"
+            set output_file to POSIX file output_path
+            set file_ref to open for access output_file with write permission
+            try
+                set eof file_ref to 0
+                write artwork_data to file_ref
+                close access file_ref
+            on error err_msg number err_num
+                try
+                    close access file_ref
+                end try
+                error err_msg number err_num
+            end try
+            return output_path
+        "#,
+            escape_applescript_string(&track.name),
+            escape_applescript_string(&track.artist),
+            escaped_path,
+            changed = TRACK_CHANGED,
+        );
+
+        Ok(match self.execute_script(&script).await?.as_str() {
+            "" => ArtworkExport::NoArtwork,
+            TRACK_CHANGED => ArtworkExport::TrackChanged,
+            path => ArtworkExport::Exported(format!("file://{}", path)),
+        })
+    }
+}
+
+// Returned by the export script when the Music app is no longer on the track
+const TRACK_CHANGED: &str = "amcli:track-changed";
+
+enum ArtworkExport {
+    Exported(String),
+    // A definite answer: the track has no artwork of its own
+    NoArtwork,
+    // Says nothing about the requested track's artwork
+    TrackChanged,
+}
+
+pub(super) fn current_track_artwork_path(track: &Track) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(track.artist.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(track.album.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(track.name.as_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+    std::env::temp_dir().join(format!("amcli-current-artwork-{}.img", hash))
+}
+
+const QUEUE_LIMIT: usize = 500;
+
+const TRACK_IDS_SCRIPT: &str = r#"
+    tell application "Music"
+        set AppleScript's text item delimiters to linefeed
+        return (persistent ID of every track of library playlist 1) as text
+    end tell
+"#;
+
+const PLAYLISTS_SCRIPT: &str = r#"
+    tell application "Music"
+        set AppleScript's text item delimiters to linefeed
+        return (name of every user playlist whose special kind is none) as text
+    end tell
+"#;
+
+// Whole columns at once: asking for one property of a range of tracks is a
+// single Apple Event, where looping over them would be one per track
+fn library_page_script(offset: usize, limit: usize) -> String {
+    format!(
+        r#"
+        tell application "Music"
+            set _count to count of tracks of library playlist 1
+            if {first} > _count then return ""
+            set _last to {last}
+            if _last > _count then set _last to _count
+            set _page to a reference to (tracks {first} thru _last of library playlist 1)
+            set AppleScript's text item delimiters to linefeed
+            set _sep to character id 30
+            return ((persistent ID of _page) as text) & _sep & ((name of _page) as text) & _sep & ((artist of _page) as text) & _sep & ((album of _page) as text)
+        end tell
+    "#,
+        first = offset + 1,
+        last = offset + limit
+    )
+}
+
+// Columns split by character 30, with one line per track in each: persistent
+// ID, name, artist, album. Blank cells are kept as empty lines, so rows line
+// up across the columns.
+fn parse_library_page(output: &str) -> Vec<LibraryItem> {
+    let columns: Vec<Vec<&str>> = output
+        .split('\u{1e}')
+        .map(|column| column.split('\n').collect())
+        .collect();
+    let cell = |column: usize, row: usize| {
+        columns
+            .get(column)
+            .and_then(|cells| cells.get(row))
+            .copied()
+            .unwrap_or_default()
+    };
+    (0..columns[0].len())
+        .filter(|&row| !cell(0, row).is_empty())
+        .map(|row| LibraryItem::track(cell(0, row), cell(1, row), cell(2, row), cell(3, row)))
+        .collect()
+}
+
+// One tab-separated track per line: name, artist, album, duration, POSIX path.
+fn parse_queue(output: &str) -> Vec<QueueEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split('\t').collect();
+            if parts.len() < 5 {
+                return None;
+            }
+            let duration = parts[3].trim().replace(',', ".").parse::<f64>().ok()?;
+            Some(QueueEntry {
+                track: Track {
+                    name: parts[0].to_string(),
+                    artist: parts[1].to_string(),
+                    album: parts[2].to_string(),
+                    duration: Duration::from_secs_f64(duration.max(0.0)),
+                    position: Duration::ZERO,
+                },
+                location: Some(parts[4].trim())
+                    .filter(|p| !p.is_empty())
+                    .map(PathBuf::from),
+            })
+        })
+        .collect()
+}
+
+// Up Next edits land on the playing playlist (see `get_queue`), which
+// AppleScript only lets us change when it's an ordinary user playlist.
+const REQUIRE_EDITABLE_PLAYLIST: &str = r#"
+                set _pl to current playlist
+                if class of _pl is not user playlist or smart of _pl then error "Up Next can only be edited while a regular playlist is playing"
+"#;
+
+// Queue positions `lo..=hi` in their new order once `from` moves to `to`
+fn reordered_span(from: usize, to: usize) -> Vec<usize> {
+    let lo = from.min(to);
+    let mut span: Vec<usize> = (lo..=from.max(to)).collect();
+    let entry = span.remove(from - lo);
+    span.insert(to - lo, entry);
+    span
+}
+
+// Crossfade and Sound Check aren't in Music's scripting dictionary, so they're
+// read and set through the Playback pane of its settings window with GUI
+// scripting (needs Accessibility access). The window is closed again and the
+// previous app brought back to the front. `{body}` sees the controls as
+// `_crossfade`, `_duration`, and `_soundCheck` and sets `_out`.
+const PLAYBACK_SETTINGS_SCRIPT: &str = r#"
+set _front to name of (info for (path to frontmost application))
+set _out to ""
+tell application "Music" to activate
+tell application "System Events" to tell process "Music"
+    keystroke "," using command down
+    repeat 20 times
+        if exists window 1 then exit repeat
+        delay 0.1
+    end repeat
+    click button "Playback" of toolbar 1 of window 1
+    delay 0.3
+    set _crossfade to missing value
+    set _duration to missing value
+    set _soundCheck to missing value
+    repeat with _control in (entire contents of window 1)
+        try
+            if role of _control is "AXCheckBox" then
+                if name of _control starts with "Crossfade" then set _crossfade to contents of _control
+                if name of _control is "Sound Check" then set _soundCheck to contents of _control
+            else if role of _control is "AXSlider" then
+                set _duration to contents of _control
+            end if
+        end try
+    end repeat
+    {body}
+    keystroke "w" using command down
+end tell
+tell application _front to activate
+return _out
+"#;
+
+// Autoplay isn't scriptable either; its button sits at the top of the
+// Playing Next panel, which is opened for the moment if it isn't showing.
+// Without an Apple Music subscription there's no button. `{body}` sees it as
+// `_autoplay` and sets `_out`.
+const AUTOPLAY_SCRIPT: &str = r#"
+set _front to name of (info for (path to frontmost application))
+set _out to ""
+tell application "Music" to activate
+tell application "System Events" to tell process "Music"
+    set _autoplay to missing value
+    set _opened to false
+    repeat 2 times
+        repeat with _control in (entire contents of window 1)
+            try
+                if description of _control is "Autoplay" then
+                    set _autoplay to contents of _control
+                    exit repeat
+                end if
+            end try
+        end repeat
+        if _autoplay is not missing value or _opened then exit repeat
+        click (first menu item of menu "View" of menu bar 1 whose name contains "Playing Next")
+        set _opened to true
+        delay 0.5
+    end repeat
+    if _autoplay is missing value then error "Music has no Autoplay button (it needs an Apple Music subscription)"
+    {body}
+    if _opened then click (first menu item of menu "View" of menu bar 1 whose name contains "Playing Next")
+end tell
+tell application _front to activate
+return _out
+"#;
+
+// "kind<TAB>name" for each AirPlay device Music is playing to; the Mac itself
+// is the one of kind "computer"
+const AIRPLAY_DEVICES_SCRIPT: &str = r#"
+    tell application "Music"
+        set _out to {}
+        repeat with _device in (current AirPlay devices)
+            set end of _out to ((kind of _device) as string) & tab & (name of _device)
+        end repeat
+        set AppleScript's text item delimiters to linefeed
+        return _out as string
+    end tell
+"#;
+
+// (is the Mac itself, name) for each line of AIRPLAY_DEVICES_SCRIPT
+fn parse_airplay_devices(output: &str) -> Vec<(bool, String)> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(kind, name)| (kind.trim() == "computer", name.trim().to_string()))
+        .filter(|(_, name)| !name.is_empty())
+        .collect()
+}
+
+// "1|6|0": crossfade on, 6 seconds, Sound Check off
+fn parse_playback_options(output: &str) -> Result<PlaybackOptions> {
+    let fields: Vec<&str> = output.trim().split('|').collect();
+    let [crossfade, secs, sound_check] = fields[..] else {
+        return Err(anyhow!("Unexpected playback settings: {}", output));
+    };
+    let secs: f64 = secs
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Unexpected crossfade duration: {}", secs))?;
+    Ok(PlaybackOptions {
+        crossfade: crossfade.trim() == "1",
+        crossfade_secs: secs.round().clamp(1.0, 12.0) as u8,
+        sound_check: sound_check.trim() == "1",
+    })
+}
+
+fn escape_applescript_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[async_trait]
+impl MediaPlayer for AppleMusicController {
+    async fn play(&self) -> Result<()> {
+        self.execute_script(r#"tell application "Music" to play"#)
+            .await?;
+        Ok(())
+    }
+
+    async fn pause(&self) -> Result<()> {
+        self.execute_script(r#"tell application "Music" to pause"#)
+            .await?;
+        Ok(())
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        self.execute_script(r#"tell application "Music" to playpause"#)
+            .await?;
+        Ok(())
+    }
+
+    async fn next(&self) -> Result<()> {
+        self.execute_script(r#"tell application "Music" to next track"#)
+            .await?;
+        Ok(())
+    }
+
+    async fn previous(&self) -> Result<()> {
+        self.execute_script(r#"tell application "Music" to previous track"#)
+            .await?;
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.execute_script(r#"tell application "Music" to stop"#)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        let script = r#"
+            tell application "Music"
+                if player state is not stopped then
+                    set output to name of current track & "|" & ¬
+                                  artist of current track & "|" & ¬
+                                  album of current track & "|" & ¬
+                                  duration of current track & "|" & ¬
+                                  player position
+                    return output
+                else
+                    return ""
+                end if
+            end tell
+        "#;
+
+        let result = self.execute_script(script).await?;
+
+        if result.is_empty() {
+            return Ok(None);
+        }
+
+        let parts: Vec<&str> = result.split('|').collect();
+        if parts.len() < 5 {
+            return Err(anyhow!("Invalid track info format"));
+        }
+
+        Ok(Some(Track {
+            name: parts[0].to_string(),
+            artist: parts[1].to_string(),
+            album: parts[2].to_string(),
+            duration: Duration::from_secs_f64(parts[3].parse()?),
+            position: Duration::from_secs_f64(parts[4].parse()?),
+        }))
+    }
+
+    async fn get_track_id(&self, track: &Track) -> Result<Option<String>> {
+        let script = format!(
+            r#"
+            tell application "Music"
+                if player state is stopped then return ""
+                if name of current track is not "{}" then return ""
+                return persistent ID of current track
+            end tell
+        "#,
+            escape_applescript_string(&track.name)
+        );
+        let id = self.execute_script(&script).await?;
+        Ok((!id.is_empty()).then_some(id))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        let script = r#"tell application "Music" to return player state as string"#;
+        let state = self.execute_script(script).await?;
+
+        match state.as_str() {
+            "playing" => Ok(PlaybackState::Playing),
+            "paused" => Ok(PlaybackState::Paused),
+            "stopped" => Ok(PlaybackState::Stopped),
+            _ => Err(anyhow!("Unknown playback state: {}", state)),
+        }
+    }
+
+    // ⚡ Bolt Optimization:
+    // Combining playback state, volume, and track metadata into a single AppleScript
+    // execution. Spawning `osascript` processes is expensive (~100-200ms overhead per call).
+    // By fetching all status information at once and returning it delimited, we effectively
+    // halve the number of external processes spawned during the high-frequency UI update loop.
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        let script = r#"
+            tell application "Music"
+                set _state to player state as string
+                set _vol to sound volume as string
+                set _repeat to song repeat as string
+                if _state is not "stopped" then
+                    set _track to name of current track & ":::BOLT_SPLIT:::" & ¬
+                                  artist of current track & ":::BOLT_SPLIT:::" & ¬
+                                  album of current track & ":::BOLT_SPLIT:::" & ¬
+                                  duration of current track & ":::BOLT_SPLIT:::" & ¬
+                                  player position
+                else
+                    set _track to ""
+                end if
+                return _state & ":::BOLT_SPLIT:::" & _vol & ":::BOLT_SPLIT:::" & ¬
+                       _repeat & ":::BOLT_SPLIT:::" & _track
+            end tell
+        "#;
+
+        let result = self.execute_script(script).await?;
+        let parts: Vec<&str> = result.split(":::BOLT_SPLIT:::").collect();
+
+        if parts.len() < 3 {
+            return Err(anyhow!("Invalid status format"));
+        }
+
+        let state = match parts[0] {
+            "playing" => PlaybackState::Playing,
+            "paused" => PlaybackState::Paused,
+            _ => PlaybackState::Stopped,
+        };
+
+        let volume = parts[1].parse::<u8>().ok();
+        let repeat = match parts[2] {
+            "off" => Some(RepeatMode::Off),
+            "one" => Some(RepeatMode::One),
+            "all" => Some(RepeatMode::All),
+            _ => None,
+        };
+
+        let track = if parts.len() >= 8 {
+            Some(Track {
+                name: parts[3].to_string(),
+                artist: parts[4].to_string(),
+                album: parts[5].to_string(),
+                duration: Duration::from_secs_f64(parts[6].parse().unwrap_or(0.0)),
+                position: Duration::from_secs_f64(parts[7].parse().unwrap_or(0.0)),
+            })
+        } else {
+            None
+        };
+
+        Ok(PlayerStatus {
+            track,
+            volume,
+            state,
+            repeat,
+        })
+    }
+
+    async fn set_volume(&self, volume: u8) -> Result<()> {
+        let script = format!(
+            r#"tell application "Music" to set sound volume to {}"#,
+            volume
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        let script = r#"tell application "Music" to return sound volume"#;
+        let volume = self.execute_script(script).await?;
+        Ok(volume.parse()?)
+    }
+
+    async fn seek(&self, seconds: i32) -> Result<()> {
+        let script = format!(
+            r#"tell application "Music" to set player position to (player position + {})"#,
+            seconds
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn set_position(&self, position: Duration) -> Result<()> {
+        let script = format!(
+            r#"tell application "Music" to set player position to {}"#,
+            position.as_secs_f64()
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn set_shuffle(&self, enabled: bool) -> Result<()> {
+        let script = format!(
+            r#"tell application "Music" to set shuffle enabled to {}"#,
+            enabled
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn set_repeat(&self, mode: RepeatMode) -> Result<()> {
+        let mode_str = match mode {
+            RepeatMode::Off => "off",
+            RepeatMode::One => "one",
+            RepeatMode::All => "all",
+        };
+        let script = format!(
+            r#"tell application "Music" to set song repeat to {}"#,
+            mode_str
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn play_query(&self, query: &str) -> Result<bool> {
+        let script = format!(
+            r#"
+            tell application "Music"
+                set _results to (search library playlist 1 for "{}")
+                if (count of _results) is 0 then return "0"
+                play item 1 of _results
+                return (count of _results) as string
+            end tell
+        "#,
+            escape_applescript_string(query)
+        );
+        let count = self.execute_script(&script).await?;
+        Ok(count.parse::<usize>().unwrap_or(0) > 0)
+    }
+
+    async fn get_library_track_ids(&self) -> Result<Vec<String>> {
+        let output = self.execute_script(TRACK_IDS_SCRIPT).await?;
+        Ok(output
+            .lines()
+            .filter(|id| !id.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    async fn get_library_tracks(&self, offset: usize, limit: usize) -> Result<Vec<LibraryItem>> {
+        let output = self
+            .execute_script(&library_page_script(offset, limit))
+            .await?;
+        Ok(parse_library_page(&output))
+    }
+
+    async fn get_playlists(&self) -> Result<Vec<String>> {
+        let output = self.execute_script(PLAYLISTS_SCRIPT).await?;
+        Ok(output
+            .lines()
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    async fn play_track_id(&self, id: &str) -> Result<bool> {
+        let script = format!(
+            r#"
+            tell application "Music"
+                set _found to (every track of library playlist 1 whose persistent ID is "{}")
+                if (count of _found) is 0 then return "0"
+                play item 1 of _found
+                return "1"
+            end tell
+        "#,
+            escape_applescript_string(id)
+        );
+        Ok(self.execute_script(&script).await? == "1")
+    }
+
+    // The itmss:// scheme hands the link straight to Music instead of a browser
+    async fn play_playlist(&self, name: &str) -> Result<()> {
+        let script = format!(
+            r#"tell application "Music" to play playlist "{}""#,
+            escape_applescript_string(name)
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn play_catalog_url(&self, url: &str) -> Result<()> {
+        let url = url.replacen("https://", "itmss://", 1);
+        let script = format!(
+            r#"
+            tell application "Music"
+                open location "{}"
+                delay 1
+                play
+            end tell
+        "#,
+            escape_applescript_string(&url)
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn get_playback_options(&self) -> Result<PlaybackOptions> {
+        let body = r#"set _out to ((value of _crossfade) as string) & "|" & ((value of _duration) as string) & "|" & ((value of _soundCheck) as string)"#;
+        let output = self
+            .execute_script(&PLAYBACK_SETTINGS_SCRIPT.replace("{body}", body))
+            .await?;
+        parse_playback_options(&output)
+    }
+
+    async fn get_autoplay(&self) -> Result<bool> {
+        let body = r#"set _out to (value of _autoplay) as string"#;
+        let output = self
+            .execute_script(&AUTOPLAY_SCRIPT.replace("{body}", body))
+            .await?;
+        match output.trim() {
+            "1" | "true" => Ok(true),
+            "0" | "false" => Ok(false),
+            other => Err(anyhow!("Unexpected Autoplay state: {}", other)),
+        }
+    }
+
+    async fn set_autoplay(&self, enabled: bool) -> Result<()> {
+        let body = format!(
+            "if (value of _autoplay) is not {} then click _autoplay",
+            enabled as u8
+        );
+        self.execute_script(&AUTOPLAY_SCRIPT.replace("{body}", &body))
+            .await?;
+        Ok(())
+    }
+
+    // The Mac stands for whatever it's playing through, such as headphones
+    async fn get_output_device(&self) -> Result<String> {
+        let devices = parse_airplay_devices(&self.execute_script(AIRPLAY_DEVICES_SCRIPT).await?);
+        if devices.is_empty() {
+            return super::output::system_output_device().await;
+        }
+        let mut names = Vec::new();
+        for (is_computer, name) in devices {
+            if is_computer {
+                names.push(super::output::system_output_device().await.unwrap_or(name));
+            } else {
+                names.push(name);
+            }
+        }
+        Ok(names.join(", "))
+    }
+
+    // Music doesn't expose a track's Sound Check adjustment, and reading
+    // whether it's on opens the settings window, so that's left to the caller
+    async fn get_normalization(&self, _track: &Track) -> Result<Normalization> {
+        Ok(Normalization {
+            kind: NormalizationKind::SoundCheck,
+            enabled: None,
+            gain_db: None,
+        })
+    }
+
+    async fn set_playback_options(&self, options: PlaybackOptions) -> Result<()> {
+        // Checkboxes only take clicks, so each is clicked when it differs
+        let body = format!(
+            r#"if (value of _crossfade) is not {} then click _crossfade
+    set value of _duration to {}
+    if (value of _soundCheck) is not {} then click _soundCheck"#,
+            options.crossfade as u8,
+            options.crossfade_secs.clamp(1, 12),
+            options.sound_check as u8
+        );
+        self.execute_script(&PLAYBACK_SETTINGS_SCRIPT.replace("{body}", &body))
+            .await?;
+        Ok(())
+    }
+
+    // Music doesn't expose Up Next to AppleScript, so the rest of the current
+    // playlist stands in for it (accurate unless shuffle is on).
+    async fn launch(&self) -> Result<bool> {
+        let script = r#"
+            if application "Music" is running then return "running"
+            tell application "Music" to launch
+            return "launched"
+        "#;
+        Ok(self.execute_script(script).await? == "launched")
+    }
+
+    async fn get_queue(&self) -> Result<Vec<QueueEntry>> {
+        let script = format!(
+            r#"
+            tell application "Music"
+                if player state is stopped then return ""
+                set _tracks to tracks of current playlist
+                set _start to (index of current track) + 1
+                set _end to count of _tracks
+                if _end > _start + {} then set _end to _start + {}
+                set _out to ""
+                repeat with i from _start to _end
+                    set _t to item i of _tracks
+                    set _loc to ""
+                    try
+                        set _loc to POSIX path of (location of _t)
+                    end try
+                    set _out to _out & name of _t & tab & artist of _t & tab & album of _t & tab & duration of _t & tab & _loc & linefeed
+                end repeat
+                return _out
+            end tell
+        "#,
+            QUEUE_LIMIT - 1,
+            QUEUE_LIMIT - 1
+        );
+        let output = self.execute_script(&script).await?;
+        Ok(parse_queue(&output))
+    }
+
+    // Tracks can't be moved within a playlist, so everything from the first
+    // affected position on is re-added in the new order and the originals are
+    // deleted.
+    async fn move_queue_entry(&self, from: usize, to: usize) -> Result<()> {
+        if from == to {
+            return Ok(());
+        }
+        let span = reordered_span(from, to)
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let script = format!(
+            r#"
+            tell application "Music"
+                {}
+                set _tracks to tracks of _pl
+                set _base to (index of current track) + 1
+                set _count to count of _tracks
+                if _base + {} > _count then error "That track is no longer queued"
+                repeat with _q in {{{}}}
+                    duplicate item (_base + (contents of _q)) of _tracks to _pl
+                end repeat
+                repeat with i from _base + {} to _count
+                    duplicate item i of _tracks to _pl
+                end repeat
+                repeat with i from _count to _base + {} by -1
+                    delete item i of _tracks
+                end repeat
+            end tell
+        "#,
+            REQUIRE_EDITABLE_PLAYLIST,
+            from.max(to),
+            span,
+            from.max(to) + 1,
+            from.min(to)
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn remove_queue_entry(&self, index: usize) -> Result<()> {
+        let script = format!(
+            r#"
+            tell application "Music"
+                {}
+                delete track ((index of current track) + {}) of _pl
+            end tell
+        "#,
+            REQUIRE_EDITABLE_PLAYLIST,
+            index + 1
+        );
+        self.execute_script(&script).await?;
+        Ok(())
+    }
+
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>> {
+        let track_key = format!("{}|{}|{}", track.artist, track.album, track.name);
+
+        // Check LRU cache first (recover from poison — cache data is not critical)
+        {
+            let mut cache = self.artwork_cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(url) = cache.get(&track_key) {
+                return Ok(url.clone());
+            }
+        }
+
+        let has_no_artwork = match self.export_current_track_artwork(track).await {
+            Ok(ArtworkExport::Exported(url)) => {
+                let mut cache = self.artwork_cache.lock().unwrap_or_else(|e| e.into_inner());
+                cache.put(track_key, Some(url.clone()));
+                return Ok(Some(url));
+            }
+            Ok(ArtworkExport::NoArtwork) => true,
+            Ok(ArtworkExport::TrackChanged) => false,
+            Err(e) => {
+                tracing::debug!("Current track artwork export failed: {}", e);
+                false
+            }
+        };
+
+        // Fall back to the iTunes search, but remember the result only when
+        // the Music app said the track has no art of its own. After a failed
+        // export or a skip the track may still have embedded art, so it's
+        // asked again next time.
+        let artwork_url = self.itunes.artwork_url(&track.artist, &track.name).await?;
+        if has_no_artwork {
+            let mut cache = self.artwork_cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache.put(track_key, artwork_url.clone());
+        }
+
+        Ok(artwork_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn mock_output(stdout: &str, success: bool) -> std::process::Output {
+        std::process::Output {
+            status: ExitStatus::from_raw(if success { 0 } else { 1 }),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: if success { vec![] } else { b"error".to_vec() },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_play() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::eq(
+                r#"tell application "Music" to play"#,
+            ))
+            .times(1)
+            .returning(|_| Ok(mock_output("", true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        assert!(controller.play().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_volume() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::eq(
+                r#"tell application "Music" to return sound volume"#,
+            ))
+            .times(1)
+            .returning(|_| Ok(mock_output("75", true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        let volume = controller.get_volume().await.unwrap();
+        assert_eq!(volume, 75);
+    }
+
+    #[tokio::test]
+    async fn test_get_current_track() {
+        let mut mock = MockCommandRunner::new();
+        let output = "Song Name|Artist Name|Album Name|180.5|90.0";
+        mock.expect_execute()
+            .times(1)
+            .returning(move |_| Ok(mock_output(output, true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        let track = controller.get_current_track().await.unwrap().unwrap();
+        assert_eq!(track.name, "Song Name");
+        assert_eq!(track.artist, "Artist Name");
+        assert_eq!(track.duration.as_secs(), 180);
+        assert_eq!(track.position.as_secs(), 90);
+    }
+
+    #[tokio::test]
+    async fn track_ids_are_music_persistent_ids() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains(r#"if name of current track is not "Song Name""#)
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("4A3F1C2B9D8E7F60", true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        let track = Track {
+            name: "Song Name".into(),
+            artist: "Artist Name".into(),
+            album: "Album Name".into(),
+            duration: Duration::from_secs(180),
+            position: Duration::ZERO,
+        };
+        assert_eq!(
+            controller.get_track_id(&track).await.unwrap().as_deref(),
+            Some("4A3F1C2B9D8E7F60")
+        );
+    }
+
+    #[tokio::test]
+    async fn play_query_escapes_the_search_term() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains(r#"search library playlist 1 for "say \"hi\"""#)
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("3", true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        assert!(controller.play_query(r#"say "hi""#).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn catalog_links_open_in_music() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains(r#"open location "itmss://music.apple.com/us/station/ra.1""#)
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("", true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        controller
+            .play_catalog_url("https://music.apple.com/us/station/ra.1")
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn playback_settings_parse_from_the_settings_pane() {
+        assert_eq!(
+            parse_playback_options("1|6.0|0\n").unwrap(),
+            PlaybackOptions {
+                crossfade: true,
+                crossfade_secs: 6,
+                sound_check: false,
+            }
+        );
+        assert_eq!(parse_playback_options("0|0|1").unwrap().crossfade_secs, 1);
+        assert!(parse_playback_options("missing value").is_err());
+    }
+
+    #[tokio::test]
+    async fn output_device_lists_every_airplay_target() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains("current AirPlay devices")
+            }))
+            .returning(|_| {
+                Ok(mock_output(
+                    "HomePod\tKitchen\nAirPlay device\tLiving Room\n",
+                    true,
+                ))
+            });
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        assert_eq!(
+            controller.get_output_device().await.unwrap(),
+            "Kitchen, Living Room"
+        );
+        assert_eq!(
+            parse_airplay_devices("computer\tStudio Mac\ngarbage"),
+            [(true, "Studio Mac".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_queue_reads_tab_separated_tracks() {
+        let output = "Blue in Green\tMiles Davis\tKind of Blue\t337,4\t/Music/blue.m4a\n\
+                      So What\tMiles Davis\tKind of Blue\t562.0\t\n\
+                      garbage line";
+        let queue = parse_queue(output);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue[0].track.name, "Blue in Green");
+        assert_eq!(queue[0].track.duration.as_secs(), 337);
+        assert_eq!(queue[0].location, Some(PathBuf::from("/Music/blue.m4a")));
+        assert!(queue[1].location.is_none());
+    }
+
+    #[test]
+    fn parse_library_page_lines_up_the_columns() {
+        let output =
+            "A1\nB2\nC3\u{1e}So What\nUntitled\nTake Five\u{1e}Miles Davis\n\nDave Brubeck\
+                      \u{1e}Kind of Blue\nDemos\n";
+        assert_eq!(
+            parse_library_page(output),
+            [
+                LibraryItem::track("A1", "So What", "Miles Davis", "Kind of Blue"),
+                LibraryItem::track("B2", "Untitled", "", "Demos"),
+                // The output is trimmed, which drops a blank album at the end
+                LibraryItem::track("C3", "Take Five", "Dave Brubeck", ""),
+            ]
+        );
+        assert!(parse_library_page("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn library_pages_are_one_based_ranges() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains("tracks 501 thru _last") && script.contains("set _last to 1000")
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("", true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        assert!(controller
+            .get_library_tracks(500, 500)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn reordered_span_moves_one_entry_within_the_span() {
+        assert_eq!(reordered_span(2, 3), vec![3, 2]);
+        assert_eq!(reordered_span(4, 1), vec![4, 1, 2, 3]);
+        assert_eq!(reordered_span(1, 4), vec![2, 3, 4, 1]);
+    }
+
+    #[tokio::test]
+    async fn moving_a_queue_entry_rebuilds_the_playlist_tail() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains("repeat with _q in {1, 0}")
+                    && script.contains("repeat with i from _base + 2 to _count")
+                    && script.contains("repeat with i from _count to _base + 0 by -1")
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("", true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        controller.move_queue_entry(0, 1).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_artwork_url_prefers_current_track_artwork_export() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains("artworks of current track")
+                    && script.contains("data of artwork 1 of current track")
+                    && script.contains(r#"name of current track is not "Song Name""#)
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("/tmp/amcli-current-artwork.img", true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        let track = Track {
+            name: "Song Name".into(),
+            artist: "Artist Name".into(),
+            album: "Album Name".into(),
+            duration: Duration::from_secs(180),
+            position: Duration::from_secs(90),
+        };
+
+        let artwork_url = controller.get_artwork_url(&track).await.unwrap();
+
+        assert_eq!(
+            artwork_url.as_deref(),
+            Some("file:///tmp/amcli-current-artwork.img")
+        );
+    }
+
+    #[tokio::test]
+    async fn artwork_is_not_cached_when_the_track_changed_during_export() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .times(2)
+            .returning(|_| Ok(mock_output(TRACK_CHANGED, true)));
+
+        let controller = AppleMusicController::with_runner(Box::new(mock));
+        // No name or artist keeps the iTunes fallback off the network
+        let track = Track {
+            name: String::new(),
+            artist: String::new(),
+            album: "Album Name".into(),
+            duration: Duration::from_secs(180),
+            position: Duration::from_secs(90),
+        };
+
+        assert_eq!(controller.get_artwork_url(&track).await.unwrap(), None);
+        // Asked again rather than answered from a cached miss
+        assert_eq!(controller.get_artwork_url(&track).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,246 @@
+// Cue sheets: album rips and DJ mixes kept as one long file, split into
+// tracks by the times listed beside it.
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::is_audio_file;
+
+// Cue times count frames, 75 to the second, after minutes and seconds
+const FRAMES_PER_SECOND: u64 = 75;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueTrack {
+    pub file: PathBuf,
+    pub number: u32,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    // The sheet's own TITLE and PERFORMER, which name the album
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    // INDEX 01, where the track proper starts after any pregap
+    pub start: Duration,
+    // Where the next track in the same file starts; the last runs to the end
+    pub end: Option<Duration>,
+}
+
+impl CueTrack {
+    pub fn length(&self) -> Option<Duration> {
+        self.end.map(|end| end.saturating_sub(self.start))
+    }
+}
+
+// Files are resolved next to the sheet. Sheets from older rips often name a
+// .wav that has since been converted, so a lone audio file with the same stem
+// stands in for a missing one.
+pub fn read(path: &Path) -> Result<Vec<CueTrack>> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // Rippers on Windows write Latin-1 as often as UTF-8
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => e.into_bytes().iter().map(|&b| char::from(b)).collect(),
+    };
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut tracks = parse(&text, dir)?;
+    for track in &mut tracks {
+        if !track.file.exists() {
+            if let Some(found) = same_stem_audio(&track.file) {
+                track.file = found;
+            }
+        }
+    }
+    Ok(tracks)
+}
+
+pub fn parse(text: &str, dir: &Path) -> Result<Vec<CueTrack>> {
+    let (mut album, mut album_artist) = (None, None);
+    let mut file: Option<PathBuf> = None;
+    let mut tracks: Vec<CueTrack> = Vec::new();
+    // The track being read, and whether it has had its INDEX 01
+    let mut current: Option<(CueTrack, bool)> = None;
+    // TITLE and PERFORMER are the album's until the first TRACK
+    let mut in_tracks = false;
+
+    for line in text.lines() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match command.to_ascii_uppercase().as_str() {
+            "FILE" => file = Some(dir.join(file_name(rest))),
+            "TRACK" => {
+                in_tracks = true;
+                tracks.extend(
+                    current
+                        .take()
+                        .filter(|(_, indexed)| *indexed)
+                        .map(|(t, _)| t),
+                );
+                // Data tracks on enhanced CDs have nothing to play
+                if !rest.to_ascii_uppercase().ends_with("AUDIO") {
+                    continue;
+                }
+                let file = file
+                    .clone()
+                    .ok_or_else(|| anyhow!("TRACK comes before any FILE"))?;
+                let number = rest
+                    .split_whitespace()
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(tracks.len() as u32 + 1);
+                current = Some((
+                    CueTrack {
+                        file,
+                        number,
+                        title: None,
+                        performer: None,
+                        album: None,
+                        album_artist: None,
+                        start: Duration::ZERO,
+                        end: None,
+                    },
+                    false,
+                ));
+            }
+            "TITLE" => match &mut current {
+                Some((track, _)) => track.title = Some(unquote(rest)),
+                None if !in_tracks => album = Some(unquote(rest)),
+                None => {}
+            },
+            "PERFORMER" => match &mut current {
+                Some((track, _)) => track.performer = Some(unquote(rest)),
+                None if !in_tracks => album_artist = Some(unquote(rest)),
+                None => {}
+            },
+            "INDEX" => {
+                let mut parts = rest.split_whitespace();
+                if let (Some((track, indexed)), Some(1), Some(start)) = (
+                    &mut current,
+                    parts.next().and_then(|n| n.parse::<u32>().ok()),
+                    parts.next().and_then(parse_time),
+                ) {
+                    track.start = start;
+                    *indexed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    tracks.extend(current.filter(|(_, indexed)| *indexed).map(|(t, _)| t));
+    if tracks.is_empty() {
+        return Err(anyhow!("No audio tracks in the cue sheet"));
+    }
+
+    let starts: Vec<(PathBuf, Duration)> =
+        tracks.iter().map(|t| (t.file.clone(), t.start)).collect();
+    for (i, track) in tracks.iter_mut().enumerate() {
+        track.album = album.clone();
+        track.album_artist = album_artist.clone();
+        track.end = starts
+            .get(i + 1)
+            .filter(|(file, _)| *file == track.file)
+            .map(|(_, start)| *start);
+    }
+    Ok(tracks)
+}
+
+// `"Live at the Roxy.flac" WAVE`, or unquoted with the type last
+fn file_name(rest: &str) -> String {
+    if let Some(quoted) = rest.strip_prefix('"') {
+        return quoted.split('"').next().unwrap_or_default().to_string();
+    }
+    match rest.rsplit_once(char::is_whitespace) {
+        Some((name, _)) => name.trim().to_string(),
+        None => rest.to_string(),
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+// "mm:ss:ff", where minutes go past 99 on long mixes
+fn parse_time(value: &str) -> Option<Duration> {
+    let mut parts = value.split(':').map(|p| p.parse::<u64>().ok());
+    let (Some(Some(minutes)), Some(Some(seconds)), Some(Some(frames)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    let frames = (minutes * 60 + seconds) * FRAMES_PER_SECOND + frames;
+    Some(Duration::from_millis(frames * 1000 / FRAMES_PER_SECOND))
+}
+
+fn same_stem_audio(missing: &Path) -> Option<PathBuf> {
+    let stem = missing.file_stem()?;
+    let mut found = std::fs::read_dir(missing.parent()?)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.file_stem() == Some(stem) && is_audio_file(p));
+    let file = found.next()?;
+    found.next().is_none().then_some(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHEET: &str = r#"REM GENRE Jazz
+PERFORMER "Miles Davis"
+TITLE "Kind of Blue"
+FILE "Kind of Blue.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "So What"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Freddie Freeloader"
+    PERFORMER "Miles Davis Sextet"
+    INDEX 00 09:20:40
+    INDEX 01 09:22:00
+  TRACK 03 DATA
+    INDEX 01 20:00:00
+FILE bonus.wav WAVE
+  TRACK 04 AUDIO
+    TITLE "Flamenco Sketches (Alternate)"
+    INDEX 01 00:00:00
+"#;
+
+    #[test]
+    fn tracks_split_each_file_at_their_index() {
+        let tracks = parse(SHEET, Path::new("/rips")).unwrap();
+        assert_eq!(tracks.len(), 3);
+
+        let so_what = &tracks[0];
+        assert_eq!(so_what.file, Path::new("/rips/Kind of Blue.flac"));
+        assert_eq!(so_what.title.as_deref(), Some("So What"));
+        assert_eq!(so_what.album.as_deref(), Some("Kind of Blue"));
+        assert_eq!(so_what.album_artist.as_deref(), Some("Miles Davis"));
+        assert_eq!(so_what.length(), Some(Duration::from_secs(562)));
+
+        // The pregap belongs to the track before
+        let freddie = &tracks[1];
+        assert_eq!(freddie.performer.as_deref(), Some("Miles Davis Sextet"));
+        assert_eq!(freddie.start, Duration::from_secs(562));
+        assert_eq!(freddie.end, None);
+
+        let bonus = &tracks[2];
+        assert_eq!((bonus.number, bonus.start), (4, Duration::ZERO));
+        assert_eq!(bonus.file, Path::new("/rips/bonus.wav"));
+    }
+
+    #[test]
+    fn frames_and_long_mix_times_parse() {
+        assert_eq!(parse_time("00:01:75"), Some(Duration::from_secs(2)));
+        assert_eq!(
+            parse_time("123:04:15"),
+            Some(Duration::from_millis(7_384_200))
+        );
+        assert_eq!(parse_time("01:02"), None);
+        assert!(parse("TRACK 01 AUDIO\n", Path::new("/")).is_err());
+    }
+}
@@ -0,0 +1,536 @@
+// src/player/demo.rs
+use super::{
+    MediaPlayer, Normalization, NormalizationKind, PlaybackOptions, PlaybackState, PlayerStatus,
+    QueueEntry, RepeatMode, Track,
+};
+use crate::library::LibraryItem;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use image::{Rgb, RgbImage};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub struct DemoTrack {
+    pub name: &'static str,
+    pub artist: &'static str,
+    pub album: &'static str,
+    pub seconds: u64,
+    pub lyrics: &'static str,
+    // Sound Check's adjustment, in dB
+    gain_db: f32,
+    // Cover gradient, top-left to bottom-right
+    colors: [[u8; 3]; 2],
+}
+
+pub const DEMO_TRACKS: &[DemoTrack] = &[
+    DemoTrack {
+        name: "Neon Tide",
+        artist: "The Velvet Circuit",
+        album: "Night Drive Sessions",
+        seconds: 214,
+        lyrics: include_str!("../../assets/demo/neon-tide.lrc"),
+        gain_db: -4.1,
+        colors: [[255, 64, 160], [32, 16, 96]],
+    },
+    DemoTrack {
+        name: "Paper Satellites",
+        artist: "Mira Sol",
+        album: "Small Orbits",
+        seconds: 242,
+        lyrics: include_str!("../../assets/demo/paper-satellites.lrc"),
+        gain_db: -2.6,
+        colors: [[250, 214, 120], [40, 110, 180]],
+    },
+    DemoTrack {
+        name: "Cathode Bloom",
+        artist: "The Velvet Circuit",
+        album: "Night Drive Sessions",
+        seconds: 191,
+        lyrics: include_str!("../../assets/demo/cathode-bloom.lrc"),
+        gain_db: -5.3,
+        colors: [[120, 255, 140], [8, 40, 24]],
+    },
+    DemoTrack {
+        name: "Slow Weather",
+        artist: "Harbor Lights Ensemble",
+        album: "Tidal Rooms",
+        seconds: 267,
+        lyrics: include_str!("../../assets/demo/slow-weather.lrc"),
+        gain_db: 1.2,
+        colors: [[180, 196, 210], [44, 56, 72]],
+    },
+];
+
+// Every session opens on the same frame, mid-verse, so screenshots line up
+const START_POSITION: Duration = Duration::from_secs(38);
+const COVER_SIZE: u32 = 600;
+
+impl DemoTrack {
+    fn track(&self, position: Duration) -> Track {
+        Track {
+            name: self.name.to_string(),
+            artist: self.artist.to_string(),
+            album: self.album.to_string(),
+            duration: Duration::from_secs(self.seconds),
+            position,
+        }
+    }
+}
+
+struct DemoState {
+    index: usize,
+    // Play order as indexes into `DEMO_TRACKS`; queue edits rearrange it
+    order: Vec<usize>,
+    // Position as of `resumed_at`, or the paused position
+    position: Duration,
+    resumed_at: Option<Instant>,
+    volume: u8,
+    repeat: RepeatMode,
+    rate: f32,
+    balance: f32,
+    autoplay: bool,
+    options: PlaybackOptions,
+}
+
+impl DemoState {
+    fn position(&self) -> Duration {
+        self.position
+            + self
+                .resumed_at
+                .map(|at| at.elapsed().mul_f32(self.rate))
+                .unwrap_or_default()
+    }
+
+    fn seek_to(&mut self, position: Duration) {
+        self.position = position;
+        if self.resumed_at.is_some() {
+            self.resumed_at = Some(Instant::now());
+        }
+    }
+
+    // Tracks from the current one on, wrapping around the playlist
+    fn upcoming(&self) -> impl Iterator<Item = usize> + '_ {
+        let at = self
+            .order
+            .iter()
+            .position(|&i| i == self.index)
+            .unwrap_or(0);
+        self.order
+            .iter()
+            .cycle()
+            .skip(at)
+            .take(self.order.len())
+            .copied()
+    }
+
+    fn skip(&mut self, forward: bool) {
+        let next = if forward {
+            self.upcoming().nth(1)
+        } else {
+            self.upcoming().last()
+        };
+        self.index = next.unwrap_or(self.index);
+        self.seek_to(Duration::ZERO);
+    }
+
+    // Rotates the current track to the front so queue positions index `order`
+    // directly, one past the current track
+    fn queue_mut(&mut self) -> &mut Vec<usize> {
+        self.order = self.upcoming().collect();
+        &mut self.order
+    }
+
+    // Rolls over to the following track once the current one runs out
+    fn catch_up(&mut self) {
+        loop {
+            let duration = Duration::from_secs(DEMO_TRACKS[self.index].seconds);
+            let position = self.position();
+            if position < duration {
+                return;
+            }
+            if self.repeat != RepeatMode::One {
+                let next = self.upcoming().nth(1);
+                self.index = next.unwrap_or(self.index);
+            }
+            self.position = position - duration;
+            if self.resumed_at.is_some() {
+                self.resumed_at = Some(Instant::now());
+            }
+        }
+    }
+}
+
+// A fake player cycling through bundled tracks, for trying themes without a
+// music app, taking screenshots, and exercising the UI in tests.
+pub struct DemoPlayer {
+    state: Mutex<DemoState>,
+}
+
+impl Default for DemoPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoPlayer {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(DemoState {
+                index: 0,
+                order: (0..DEMO_TRACKS.len()).collect(),
+                position: START_POSITION,
+                resumed_at: Some(Instant::now()),
+                volume: 70,
+                repeat: RepeatMode::All,
+                rate: 1.0,
+                balance: 0.0,
+                autoplay: true,
+                options: PlaybackOptions {
+                    crossfade: false,
+                    crossfade_secs: 6,
+                    sound_check: true,
+                },
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, DemoState> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.catch_up();
+        state
+    }
+
+    // Jumps to the first track in play order that `matches`
+    fn start_first(&self, matches: impl Fn(&DemoTrack) -> bool) -> bool {
+        let mut state = self.state();
+        let found = state
+            .order
+            .iter()
+            .copied()
+            .find(|&i| matches(&DEMO_TRACKS[i]));
+        if let Some(index) = found {
+            state.index = index;
+            state.seek_to(Duration::ZERO);
+            state.resumed_at = Some(Instant::now());
+        }
+        found.is_some()
+    }
+}
+
+fn demo_track_id(index: usize) -> String {
+    format!("DEMO{:04}", index)
+}
+
+fn cover_path(index: usize) -> PathBuf {
+    std::env::temp_dir().join(format!("amcli-demo-cover-{}.png", index))
+}
+
+// Diagonal gradient with a few rings, so each cover is recognisable in every
+// artwork mode.
+fn render_cover(track: &DemoTrack) -> RgbImage {
+    let [from, to] = track.colors;
+    let center = COVER_SIZE as f32 / 2.0;
+    RgbImage::from_fn(COVER_SIZE, COVER_SIZE, |x, y| {
+        let t = (x + y) as f32 / (2 * COVER_SIZE) as f32;
+        let distance = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt();
+        let ring = ((distance / 36.0) as u32).is_multiple_of(2) && distance < center * 0.8;
+        let shade = if ring { 0.82 } else { 1.0 };
+        Rgb(std::array::from_fn(|c| {
+            let value = from[c] as f32 * (1.0 - t) + to[c] as f32 * t;
+            (value * shade) as u8
+        }))
+    })
+}
+
+#[async_trait]
+impl MediaPlayer for DemoPlayer {
+    async fn play(&self) -> Result<()> {
+        let mut state = self.state();
+        if state.resumed_at.is_none() {
+            state.resumed_at = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    async fn pause(&self) -> Result<()> {
+        let mut state = self.state();
+        state.position = state.position();
+        state.resumed_at = None;
+        Ok(())
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        let playing = self.state().resumed_at.is_some();
+        if playing {
+            self.pause().await
+        } else {
+            self.play().await
+        }
+    }
+
+    async fn next(&self) -> Result<()> {
+        self.state().skip(true);
+        Ok(())
+    }
+
+    async fn previous(&self) -> Result<()> {
+        self.state().skip(false);
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.pause().await?;
+        self.state().seek_to(Duration::ZERO);
+        Ok(())
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        let state = self.state();
+        Ok(Some(DEMO_TRACKS[state.index].track(state.position())))
+    }
+
+    async fn get_track_id(&self, track: &Track) -> Result<Option<String>> {
+        let index = self.state().index;
+        Ok((DEMO_TRACKS[index].name == track.name).then(|| demo_track_id(index)))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        Ok(if self.state().resumed_at.is_some() {
+            PlaybackState::Playing
+        } else {
+            PlaybackState::Paused
+        })
+    }
+
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        let state = self.state();
+        Ok(PlayerStatus {
+            track: Some(DEMO_TRACKS[state.index].track(state.position())),
+            volume: Some(state.volume),
+            state: if state.resumed_at.is_some() {
+                PlaybackState::Playing
+            } else {
+                PlaybackState::Paused
+            },
+            repeat: Some(state.repeat),
+        })
+    }
+
+    async fn set_volume(&self, volume: u8) -> Result<()> {
+        self.state().volume = volume.min(100);
+        Ok(())
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        Ok(self.state().volume)
+    }
+
+    async fn seek(&self, seconds: i32) -> Result<()> {
+        let mut state = self.state();
+        let position = state.position().as_secs_f64() + f64::from(seconds);
+        state.seek_to(Duration::from_secs_f64(position.max(0.0)));
+        Ok(())
+    }
+
+    async fn set_position(&self, position: Duration) -> Result<()> {
+        self.state().seek_to(position);
+        Ok(())
+    }
+
+    async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_repeat(&self, mode: RepeatMode) -> Result<()> {
+        self.state().repeat = mode;
+        Ok(())
+    }
+
+    async fn get_rate(&self) -> Result<f32> {
+        Ok(self.state().rate)
+    }
+
+    async fn set_rate(&self, rate: f32) -> Result<()> {
+        let mut state = self.state();
+        let position = state.position();
+        state.seek_to(position);
+        state.rate = rate;
+        Ok(())
+    }
+
+    async fn get_balance(&self) -> Result<f32> {
+        Ok(self.state().balance)
+    }
+
+    async fn set_balance(&self, balance: f32) -> Result<()> {
+        self.state().balance = balance.clamp(-1.0, 1.0);
+        Ok(())
+    }
+
+    async fn get_autoplay(&self) -> Result<bool> {
+        Ok(self.state().autoplay)
+    }
+
+    async fn set_autoplay(&self, enabled: bool) -> Result<()> {
+        self.state().autoplay = enabled;
+        Ok(())
+    }
+
+    async fn get_playback_options(&self) -> Result<PlaybackOptions> {
+        Ok(self.state().options)
+    }
+
+    async fn set_playback_options(&self, options: PlaybackOptions) -> Result<()> {
+        self.state().options = options;
+        Ok(())
+    }
+
+    async fn get_output_device(&self) -> Result<String> {
+        Ok("Living Room".to_string())
+    }
+
+    async fn get_normalization(&self, track: &Track) -> Result<Normalization> {
+        let gain_db = DEMO_TRACKS
+            .iter()
+            .find(|t| t.name == track.name)
+            .map(|t| t.gain_db);
+        Ok(Normalization {
+            kind: NormalizationKind::SoundCheck,
+            enabled: Some(self.state().options.sound_check),
+            gain_db,
+        })
+    }
+
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>> {
+        let Some(index) = DEMO_TRACKS.iter().position(|t| t.name == track.name) else {
+            return Ok(None);
+        };
+        let path = cover_path(index);
+        if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            let cover = tokio::task::spawn_blocking(move || render_cover(&DEMO_TRACKS[index]));
+            cover.await?.save(&path)?;
+        }
+        Ok(Some(format!("file://{}", path.display())))
+    }
+
+    async fn play_query(&self, query: &str) -> Result<bool> {
+        let query = query.to_lowercase();
+        Ok(self.start_first(|t| {
+            t.name.to_lowercase().contains(&query) || t.artist.to_lowercase().contains(&query)
+        }))
+    }
+
+    async fn play_album(&self, artist: &str, album: &str) -> Result<bool> {
+        Ok(self.start_first(|t| t.artist == artist && t.album == album))
+    }
+
+    async fn get_library_track_ids(&self) -> Result<Vec<String>> {
+        Ok((0..DEMO_TRACKS.len()).map(demo_track_id).collect())
+    }
+
+    async fn get_library_tracks(&self, offset: usize, limit: usize) -> Result<Vec<LibraryItem>> {
+        Ok(DEMO_TRACKS
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(limit)
+            .map(|(i, t)| LibraryItem::track(demo_track_id(i), t.name, t.artist, t.album))
+            .collect())
+    }
+
+    async fn play_track_id(&self, id: &str) -> Result<bool> {
+        let Some(index) = (0..DEMO_TRACKS.len()).find(|&i| demo_track_id(i) == id) else {
+            return Ok(false);
+        };
+        Ok(self.start_first(|t| std::ptr::eq(t, &DEMO_TRACKS[index])))
+    }
+
+    async fn get_queue(&self) -> Result<Vec<QueueEntry>> {
+        Ok(self
+            .state()
+            .upcoming()
+            .skip(1)
+            .map(|index| QueueEntry {
+                track: DEMO_TRACKS[index].track(Duration::ZERO),
+                location: None,
+            })
+            .collect())
+    }
+
+    async fn move_queue_entry(&self, from: usize, to: usize) -> Result<()> {
+        let mut state = self.state();
+        let order = state.queue_mut();
+        if from.max(to) + 1 >= order.len() {
+            return Err(anyhow!("That track is no longer queued"));
+        }
+        let entry = order.remove(from + 1);
+        order.insert(to + 1, entry);
+        Ok(())
+    }
+
+    async fn remove_queue_entry(&self, index: usize) -> Result<()> {
+        let mut state = self.state();
+        let order = state.queue_mut();
+        if index + 1 >= order.len() {
+            return Err(anyhow!("That track is no longer queued"));
+        }
+        order.remove(index + 1);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rotates_through_the_playlist() {
+        let player = DemoPlayer::new();
+        player.pause().await.unwrap();
+        let track = player.get_current_track().await.unwrap().unwrap();
+        assert_eq!(track.name, "Neon Tide");
+        assert_eq!(track.position.as_secs(), START_POSITION.as_secs());
+
+        player.previous().await.unwrap();
+        let queue = player.get_queue().await.unwrap();
+        assert_eq!(queue[0].track.name, "Neon Tide");
+
+        // Running off the end of a track moves on to the next one
+        player.set_position(Duration::from_secs(268)).await.unwrap();
+        let track = player.get_current_track().await.unwrap().unwrap();
+        assert_eq!(track.name, "Neon Tide");
+        assert_eq!(track.position, Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn queue_edits_change_what_plays_next() {
+        let player = DemoPlayer::new();
+        let names = |queue: Vec<QueueEntry>| -> Vec<String> {
+            queue.into_iter().map(|e| e.track.name).collect()
+        };
+        player.move_queue_entry(2, 0).await.unwrap();
+        player.remove_queue_entry(1).await.unwrap();
+        assert_eq!(
+            names(player.get_queue().await.unwrap()),
+            ["Slow Weather", "Cathode Bloom"]
+        );
+        assert!(player.remove_queue_entry(2).await.is_err());
+
+        player.next().await.unwrap();
+        let track = player.get_current_track().await.unwrap().unwrap();
+        assert_eq!(track.name, "Slow Weather");
+        player.previous().await.unwrap();
+        player.previous().await.unwrap();
+        let track = player.get_current_track().await.unwrap().unwrap();
+        assert_eq!(track.name, "Cathode Bloom");
+    }
+
+    #[test]
+    fn covers_differ_per_track() {
+        let first = render_cover(&DEMO_TRACKS[0]);
+        let second = render_cover(&DEMO_TRACKS[1]);
+        assert_eq!(first.dimensions(), (COVER_SIZE, COVER_SIZE));
+        assert_ne!(first.get_pixel(0, 0), second.get_pixel(0, 0));
+    }
+}
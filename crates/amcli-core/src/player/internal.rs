@@ -0,0 +1,1629 @@
+// src/player/internal.rs
+use super::cue::{self, CueTrack};
+use super::radio;
+use super::{
+    is_audio_file, is_cue_sheet, MediaPlayer, Normalization, NormalizationKind, PlaybackState,
+    QueueEntry, RepeatMode, Track,
+};
+use crate::config::{PlayerConfig, ReplayGainMode};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use lofty::prelude::*;
+use rodio::buffer::SamplesBuffer;
+use rodio::source::{SeekError, UniformSourceIterator};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f32::consts::FRAC_PI_2;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+const COVER_FILE_NAMES: &[&str] = &["cover", "folder", "front", "album"];
+const RESTART_THRESHOLD: Duration = Duration::from_secs(3);
+const QUEUE_LIMIT: usize = 500;
+
+pub type AudioSource = Box<dyn Source<Item = i16> + Send>;
+
+// Where the engine's tracks come from: local files or a streaming server.
+// Indices are positions in the library's current track list.
+#[async_trait]
+pub trait Library: Send + Sync {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    async fn load(&self, index: usize) -> Result<(AudioSource, Track)>;
+    async fn describe(&self, index: usize) -> Result<QueueEntry>;
+    async fn artwork_url(&self, index: usize) -> Result<Option<String>>;
+    // Point the library at tracks matching `query`, returning where to start.
+    async fn search(&self, query: &str) -> Result<Option<usize>>;
+    // Swap local files in as the track list, or add them at the end. Libraries
+    // streamed from a server can't.
+    fn replace_files(&self, _files: Vec<PathBuf>) -> Result<()> {
+        Err(anyhow!("only local libraries can queue files"))
+    }
+    fn append_files(&self, _files: Vec<PathBuf>) -> Result<()> {
+        Err(anyhow!("only local libraries can queue files"))
+    }
+    // What favorites, notes, and bookmarks are kept under; streamed tracks
+    // have nothing stable
+    fn track_id(&self, _index: usize) -> Option<String> {
+        None
+    }
+}
+
+struct QueueState {
+    metadata: HashMap<usize, Track>,
+    // Queue indices currently loaded into the sink, front is playing. The next
+    // track is appended ahead of time so rodio moves on without a gap.
+    loaded: VecDeque<usize>,
+    current: usize,
+    repeat: RepeatMode,
+    // Bumped whenever playback restarts so stale preloads are dropped
+    generation: u64,
+    preloading: bool,
+    // Sink and track positions when the rate last changed. rodio counts
+    // position in played time, so the track position is extrapolated from here.
+    rate_anchor: (Duration, Duration),
+    // The last source appended, waiting for the next track's opening to
+    // crossfade into
+    tail: Option<Arc<FadeHandle>>,
+    // How far into each preloaded track its source starts, when a crossfade
+    // played the opening
+    lead_ins: HashMap<usize, Duration>,
+    // A radio station playing in place of the library, kept after it stops so
+    // play tunes back in
+    stream: Option<Stream>,
+}
+
+#[derive(Clone)]
+struct Stream {
+    url: String,
+    station: String,
+    title: Arc<std::sync::Mutex<Option<String>>>,
+}
+
+impl Stream {
+    // The song the station last announced, filed under the station's name
+    fn track(&self, position: Duration) -> Track {
+        let title = self.title.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let (artist, name) = match &title {
+            Some(title) => radio::split_title(title),
+            None => ("", self.station.as_str()),
+        };
+        Track {
+            name: name.to_string(),
+            artist: artist.to_string(),
+            album: self.station.clone(),
+            duration: Duration::ZERO,
+            position,
+        }
+    }
+}
+
+struct Engine {
+    sink: Sink,
+    _handle: OutputStreamHandle,
+    library: Box<dyn Library>,
+    state: Mutex<QueueState>,
+    // f32 bits, shared with every source in the sink so changes are heard at once
+    balance: Arc<AtomicU32>,
+    // Zero for back-to-back gapless playback
+    crossfade: Duration,
+    crossfading: Arc<AtomicBool>,
+    // Which gain tag to apply, and whether to right now (`V` switches it,
+    // including for tracks already in the sink)
+    replay_gain: ReplayGainMode,
+    replay_gain_on: Arc<AtomicBool>,
+}
+
+// Plays a library through rodio. The UI polls every few hundred milliseconds,
+// and each poll reconciles the sink with the queue.
+pub struct InternalPlayer {
+    engine: Arc<Engine>,
+}
+
+impl InternalPlayer {
+    pub fn new(library: Box<dyn Library>, config: &PlayerConfig) -> Result<Self> {
+        let handle = spawn_output_stream()?;
+        let sink = Sink::try_new(&handle).context("Failed to open audio output")?;
+        sink.pause();
+
+        Ok(Self {
+            engine: Arc::new(Engine {
+                sink,
+                _handle: handle,
+                library,
+                state: Mutex::new(QueueState {
+                    metadata: HashMap::new(),
+                    loaded: VecDeque::new(),
+                    current: 0,
+                    repeat: RepeatMode::Off,
+                    generation: 0,
+                    preloading: false,
+                    rate_anchor: (Duration::ZERO, Duration::ZERO),
+                    tail: None,
+                    lead_ins: HashMap::new(),
+                    stream: None,
+                }),
+                balance: Arc::new(AtomicU32::new(0.0f32.to_bits())),
+                crossfade: Duration::from_secs(config.crossfade_secs.into()),
+                crossfading: Arc::new(AtomicBool::new(false)),
+                replay_gain: config.replay_gain,
+                replay_gain_on: Arc::new(AtomicBool::new(
+                    config.replay_gain != ReplayGainMode::Off,
+                )),
+            }),
+        })
+    }
+
+    pub fn local(music_dir: &Path, config: &PlayerConfig) -> Result<Self> {
+        Self::new(Box::new(LocalLibrary::new(music_dir)?), config)
+    }
+}
+
+impl Engine {
+    // Stopping flushes the sink; the next append waits for that before queueing.
+    async fn start(self: &Arc<Self>, state: &mut QueueState, index: usize) -> Result<()> {
+        self.flush(state);
+        state.stream = None;
+        state.current = index;
+
+        let (source, track) = self.load(index).await?;
+        self.append(state, source, &track, Duration::ZERO);
+        state.metadata.insert(index, track);
+        state.loaded.push_back(index);
+        self.sink.play();
+        self.preload(state);
+        Ok(())
+    }
+
+    fn flush(&self, state: &mut QueueState) {
+        state.generation += 1;
+        state.preloading = false;
+        self.sink.stop();
+        state.loaded.clear();
+        state.lead_ins.clear();
+        self.crossfading.store(false, Ordering::Relaxed);
+        state.rate_anchor = (Duration::ZERO, Duration::ZERO);
+    }
+
+    // Connects without holding the queue lock, so polls carry on meanwhile.
+    // The current library track stands in for the stream in `loaded`, so
+    // finishing and syncing work as usual.
+    async fn start_stream(&self, stream: Stream) -> Result<()> {
+        let opened = radio::open(&stream.url).await?;
+        let station = if stream.station.is_empty() {
+            opened.name.unwrap_or_else(|| stream.url.clone())
+        } else {
+            stream.station
+        };
+        let stream = Stream {
+            station,
+            title: opened.title,
+            ..stream
+        };
+        let reader = opened.reader;
+        let decoder = tokio::task::spawn_blocking(move || Decoder::new(reader))
+            .await?
+            .map_err(|e| anyhow!("Couldn't play {}: {}", stream.station, e))?;
+
+        let mut state = self.state.lock().await;
+        self.flush(&mut state);
+        let track = stream.track(Duration::ZERO);
+        state.stream = Some(stream);
+        self.append(&mut state, Box::new(decoder), &track, Duration::ZERO);
+        let current = state.current;
+        state.loaded.push_back(current);
+        self.sink.play();
+        Ok(())
+    }
+
+    // Loading can mean a download, so the next track is fetched in the background.
+    fn preload(self: &Arc<Self>, state: &mut QueueState) {
+        if state.loaded.len() != 1 || state.preloading || state.stream.is_some() {
+            return;
+        }
+        let Some(next) = following_index(state.current, self.library.len(), state.repeat) else {
+            return;
+        };
+        state.preloading = true;
+        let generation = state.generation;
+        let engine = Arc::clone(self);
+        tokio::spawn(async move {
+            let loaded = match engine.load(next).await {
+                Ok((source, track)) if !engine.crossfade.is_zero() => {
+                    let crossfade = engine.crossfade;
+                    tokio::task::spawn_blocking(move || read_opening(source, crossfade))
+                        .await
+                        .map_err(Into::into)
+                        .map(|(source, opening)| (source, track, Some(opening)))
+                }
+                loaded => loaded.map(|(source, track)| (source, track, None)),
+            };
+            let mut state = engine.state.lock().await;
+            if state.generation != generation {
+                return;
+            }
+            state.preloading = false;
+            match loaded {
+                Ok((mut source, track, opening)) => {
+                    let mut start = Duration::ZERO;
+                    if let Some(opening) = opening {
+                        let length = opening.total_duration().unwrap_or_default();
+                        let taken = state.tail.as_ref().is_some_and(|t| t.offer(opening));
+                        // Too late to mix in, so the track plays from the top
+                        if taken || source.try_seek(Duration::ZERO).is_err() {
+                            start = length;
+                            state.lead_ins.insert(next, length);
+                        }
+                    }
+                    engine.append(&mut state, source, &track, start);
+                    state.metadata.insert(next, track);
+                    state.loaded.push_back(next);
+                    if state.loaded.len() == 1 {
+                        state.current = next;
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to preload next track: {}", e),
+            }
+        });
+    }
+
+    // Tracks come levelled by their gain tags, so a crossfade mixes two
+    // tracks already at the same loudness
+    async fn load(&self, index: usize) -> Result<(AudioSource, Track)> {
+        let (source, track) = self.library.load(index).await?;
+        let tags = match self
+            .library
+            .describe(index)
+            .await
+            .ok()
+            .and_then(|e| e.location)
+        {
+            Some(path) => tokio::task::spawn_blocking(move || GainTags::read(&path))
+                .await
+                .unwrap_or_default(),
+            None => GainTags::default(),
+        };
+        let source = ReplayGained {
+            inner: source,
+            gain: tags.factor(self.replay_gain),
+            enabled: Arc::clone(&self.replay_gain_on),
+        };
+        Ok((Box::new(source), track))
+    }
+
+    // Catch up with sources the sink finished since the last poll.
+    fn sync(self: &Arc<Self>, state: &mut QueueState) {
+        let finished = state.loaded.len().saturating_sub(self.sink.len());
+        for _ in 0..finished {
+            state.loaded.pop_front();
+        }
+        if let Some(&front) = state.loaded.front() {
+            state.current = front;
+        }
+        if finished > 0 {
+            let lead_in = state.lead_ins.remove(&state.current).unwrap_or_default();
+            state.rate_anchor = (Duration::ZERO, lead_in);
+        }
+        self.preload(state);
+    }
+
+    // Every source goes in balanced and, with crossfade on, ready to fade into
+    // whatever is preloaded after it. `start` is how far into the track it is.
+    fn append(&self, state: &mut QueueState, source: AudioSource, track: &Track, start: Duration) {
+        let (source, tail) = CrossfadeTail::wrap(
+            source,
+            start,
+            track.duration,
+            self.crossfade,
+            &self.crossfading,
+        );
+        state.tail = tail;
+        self.sink.append(self.balanced(source));
+    }
+
+    fn balanced(&self, source: AudioSource) -> Balanced {
+        Balanced {
+            inner: source,
+            balance: Arc::clone(&self.balance),
+            channel: 0,
+        }
+    }
+
+    fn position(&self, state: &QueueState) -> Duration {
+        let (sink_pos, track_pos) = state.rate_anchor;
+        track_pos
+            + self
+                .sink
+                .get_pos()
+                .saturating_sub(sink_pos)
+                .mul_f32(self.sink.speed())
+    }
+}
+
+// Turns down the side the balance leans away from. Only stereo sources are
+// touched; samples are interleaved, so `channel` tracks which side is next.
+struct Balanced {
+    inner: AudioSource,
+    balance: Arc<AtomicU32>,
+    channel: u16,
+}
+
+impl Iterator for Balanced {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let channels = self.inner.channels();
+        let sample = self.inner.next()?;
+        let channel = self.channel % channels.max(1);
+        self.channel = (channel + 1) % channels.max(1);
+        let balance = f32::from_bits(self.balance.load(Ordering::Relaxed));
+        if channels != 2 || balance == 0.0 {
+            return Some(sample);
+        }
+        Some((f32::from(sample) * channel_gain(balance, channel == 1)) as i16)
+    }
+}
+
+impl Source for Balanced {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(position)?;
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+// Scales a track by its ReplayGain while it's switched on.
+struct ReplayGained {
+    inner: AudioSource,
+    gain: f32,
+    enabled: Arc<AtomicBool>,
+}
+
+impl Iterator for ReplayGained {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.inner.next()?;
+        if self.gain == 1.0 || !self.enabled.load(Ordering::Relaxed) {
+            return Some(sample);
+        }
+        let scaled = f32::from(sample) * self.gain;
+        Some(scaled.clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16)
+    }
+}
+
+impl Source for ReplayGained {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(position)
+    }
+}
+
+// The next track's opening, offered to the track before it to mix into its
+// tail, converted to that track's channels and sample rate.
+struct FadeHandle {
+    channels: u16,
+    sample_rate: u32,
+    incoming: std::sync::Mutex<Incoming>,
+}
+
+enum Incoming {
+    Waiting,
+    Ready(AudioSource),
+    // The tail began; anything offered now is too late to line up
+    Started,
+}
+
+impl FadeHandle {
+    // Whether the opening was taken in time
+    fn offer(&self, opening: SamplesBuffer<i16>) -> bool {
+        let mut incoming = self.incoming.lock().unwrap_or_else(|e| e.into_inner());
+        if !matches!(*incoming, Incoming::Waiting) {
+            return false;
+        }
+        let opening = UniformSourceIterator::new(opening, self.channels, self.sample_rate);
+        *incoming = Incoming::Ready(Box::new(opening));
+        true
+    }
+}
+
+// Fades a track out over its last seconds while mixing in the opening of the
+// next one, whose own source then picks up where the opening ended.
+struct CrossfadeTail {
+    inner: AudioSource,
+    // Samples handed out so far, counted from the start of the track
+    played: u64,
+    fade_from: u64,
+    fade_len: u64,
+    handle: Arc<FadeHandle>,
+    tail_started: bool,
+    mixing: Option<AudioSource>,
+    crossfading: Arc<AtomicBool>,
+}
+
+impl CrossfadeTail {
+    // Left as is when crossfade is off, or the track is too short (or its
+    // length unknown) to fade over
+    fn wrap(
+        inner: AudioSource,
+        start: Duration,
+        length: Duration,
+        fade: Duration,
+        crossfading: &Arc<AtomicBool>,
+    ) -> (AudioSource, Option<Arc<FadeHandle>>) {
+        if fade.is_zero() || length < fade * 2 {
+            return (inner, None);
+        }
+        let (channels, sample_rate) = (inner.channels(), inner.sample_rate());
+        let handle = Arc::new(FadeHandle {
+            channels,
+            sample_rate,
+            incoming: std::sync::Mutex::new(Incoming::Waiting),
+        });
+        let tail = Self {
+            inner,
+            played: sample_count(start, channels, sample_rate),
+            fade_from: sample_count(length - fade, channels, sample_rate),
+            fade_len: sample_count(fade, channels, sample_rate).max(1),
+            handle: Arc::clone(&handle),
+            tail_started: false,
+            mixing: None,
+            crossfading: Arc::clone(crossfading),
+        };
+        (Box::new(tail), Some(handle))
+    }
+
+    fn stop_mixing(&mut self) {
+        if self.mixing.take().is_some() {
+            self.crossfading.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Iterator for CrossfadeTail {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let channels = u64::from(self.inner.channels().max(1));
+        if !self.tail_started
+            && self.played >= self.fade_from
+            && self.played.is_multiple_of(channels)
+        {
+            self.tail_started = true;
+            let mut incoming = self
+                .handle
+                .incoming
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if let Incoming::Ready(opening) = std::mem::replace(&mut *incoming, Incoming::Started) {
+                self.mixing = Some(opening);
+                self.crossfading.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let sample = self.inner.next();
+        let position = self.played;
+        self.played += 1;
+        let Some(opening) = &mut self.mixing else {
+            return sample;
+        };
+        let Some(sample) = sample else {
+            self.stop_mixing();
+            return None;
+        };
+        // Equal-power curves, so the overlap doesn't dip in loudness
+        let progress = (position - self.fade_from) as f32 / self.fade_len as f32;
+        let angle = progress.min(1.0) * FRAC_PI_2;
+        let incoming = opening.next().unwrap_or(0);
+        let mixed = f32::from(sample) * angle.cos() + f32::from(incoming) * angle.sin();
+        Some(mixed.clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16)
+    }
+}
+
+impl Source for CrossfadeTail {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    // Seeking back out of the tail drops the overlap; the next track still
+    // starts after its opening
+    fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(position)?;
+        self.played = sample_count(position, self.inner.channels(), self.inner.sample_rate());
+        if self.played < self.fade_from {
+            self.stop_mixing();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CrossfadeTail {
+    fn drop(&mut self) {
+        self.stop_mixing();
+    }
+}
+
+// Whole frames' worth of interleaved samples in `duration`
+fn sample_count(duration: Duration, channels: u16, sample_rate: u32) -> u64 {
+    (duration.as_secs_f64() * f64::from(sample_rate)) as u64 * u64::from(channels)
+}
+
+// Decodes a track's first `length` ahead of time, to mix into the end of the
+// track before it
+fn read_opening(mut source: AudioSource, length: Duration) -> (AudioSource, SamplesBuffer<i16>) {
+    let (channels, sample_rate) = (source.channels(), source.sample_rate());
+    let count = sample_count(length, channels, sample_rate) as usize;
+    let opening: Vec<i16> = source.by_ref().take(count).collect();
+    (source, SamplesBuffer::new(channels, sample_rate, opening))
+}
+
+// Full level on the side leaned towards, fading the other out to silence at ±1
+fn channel_gain(balance: f32, right: bool) -> f32 {
+    if right {
+        (1.0 + balance).min(1.0)
+    } else {
+        (1.0 - balance).min(1.0)
+    }
+}
+
+// cpal streams aren't Send, so the stream lives on its own thread for the
+// lifetime of the process and only the handle comes back.
+fn spawn_output_stream() -> Result<OutputStreamHandle> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("amcli-audio".into())
+        .spawn(move || match OutputStream::try_default() {
+            Ok((_stream, handle)) => {
+                let _ = tx.send(Ok(handle));
+                loop {
+                    std::thread::park();
+                }
+            }
+            Err(e) => {
+                let _ = tx.send(Err(anyhow!("No audio output device: {}", e)));
+            }
+        })?;
+    rx.recv()?
+}
+
+fn following_index(current: usize, len: usize, repeat: RepeatMode) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    match repeat {
+        RepeatMode::One => Some(current),
+        RepeatMode::All => Some((current + 1) % len),
+        RepeatMode::Off => (current + 1 < len).then_some(current + 1),
+    }
+}
+
+#[async_trait]
+impl MediaPlayer for InternalPlayer {
+    async fn play(&self) -> Result<()> {
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        engine.sync(&mut state);
+        if state.loaded.is_empty() {
+            if let Some(stream) = state.stream.clone() {
+                drop(state);
+                return engine.start_stream(stream).await;
+            }
+            let current = state.current;
+            return engine.start(&mut state, current).await;
+        }
+        engine.sink.play();
+        Ok(())
+    }
+
+    async fn pause(&self) -> Result<()> {
+        self.engine.sink.pause();
+        Ok(())
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        if self.engine.sink.is_paused() || self.engine.sink.empty() {
+            self.play().await
+        } else {
+            self.pause().await
+        }
+    }
+
+    async fn next(&self) -> Result<()> {
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        engine.sync(&mut state);
+        let len = engine.library.len().max(1);
+        let next = (state.current + 1) % len;
+        engine.start(&mut state, next).await
+    }
+
+    async fn previous(&self) -> Result<()> {
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        engine.sync(&mut state);
+        if engine.position(&state) > RESTART_THRESHOLD && !state.loaded.is_empty() {
+            engine
+                .sink
+                .try_seek(Duration::ZERO)
+                .map_err(|e| anyhow!("Seek failed: {}", e))?;
+            state.rate_anchor = (Duration::ZERO, Duration::ZERO);
+            return Ok(());
+        }
+        let len = engine.library.len().max(1);
+        let previous = (state.current + len - 1) % len;
+        engine.start(&mut state, previous).await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        let mut state = self.engine.state.lock().await;
+        state.generation += 1;
+        state.preloading = false;
+        self.engine.sink.stop();
+        state.loaded.clear();
+        state.lead_ins.clear();
+        self.engine.crossfading.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        engine.sync(&mut state);
+        if state.loaded.is_empty() {
+            return Ok(None);
+        }
+        if let Some(stream) = &state.stream {
+            return Ok(Some(stream.track(engine.position(&state))));
+        }
+        Ok(state.metadata.get(&state.current).map(|track| Track {
+            position: engine.position(&state),
+            ..track.clone()
+        }))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        engine.sync(&mut state);
+        Ok(if state.loaded.is_empty() {
+            PlaybackState::Stopped
+        } else if engine.sink.is_paused() {
+            PlaybackState::Paused
+        } else {
+            PlaybackState::Playing
+        })
+    }
+
+    async fn set_volume(&self, volume: u8) -> Result<()> {
+        self.engine
+            .sink
+            .set_volume(f32::from(volume.min(100)) / 100.0);
+        Ok(())
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        Ok((self.engine.sink.volume() * 100.0)
+            .round()
+            .clamp(0.0, 100.0) as u8)
+    }
+
+    async fn seek(&self, seconds: i32) -> Result<()> {
+        let position = {
+            let state = self.engine.state.lock().await;
+            self.engine.position(&state)
+        };
+        let position = position.as_secs_f64() + f64::from(seconds);
+        self.set_position(Duration::from_secs_f64(position.max(0.0)))
+            .await
+    }
+
+    // Seeking sets the sink's position to the track position
+    async fn set_position(&self, position: Duration) -> Result<()> {
+        let mut state = self.engine.state.lock().await;
+        self.engine
+            .sink
+            .try_seek(position)
+            .map_err(|e| anyhow!("Seek failed: {}", e))?;
+        state.rate_anchor = (position, position);
+        Ok(())
+    }
+
+    // rodio speeds up by resampling, so the pitch rises with the rate
+    async fn set_rate(&self, rate: f32) -> Result<()> {
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        state.rate_anchor = (engine.sink.get_pos(), engine.position(&state));
+        engine.sink.set_speed(rate);
+        Ok(())
+    }
+
+    async fn get_rate(&self) -> Result<f32> {
+        Ok(self.engine.sink.speed())
+    }
+
+    fn is_crossfading(&self) -> bool {
+        self.engine.crossfading.load(Ordering::Relaxed)
+    }
+
+    async fn get_balance(&self) -> Result<f32> {
+        Ok(f32::from_bits(self.engine.balance.load(Ordering::Relaxed)))
+    }
+
+    async fn set_balance(&self, balance: f32) -> Result<()> {
+        self.engine
+            .balance
+            .store(balance.clamp(-1.0, 1.0).to_bits(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
+        Err(anyhow!("shuffle is not supported by the internal player"))
+    }
+
+    // The already-preloaded next track still plays; the new mode applies after it.
+    async fn set_repeat(&self, mode: RepeatMode) -> Result<()> {
+        self.engine.state.lock().await.repeat = mode;
+        Ok(())
+    }
+
+    async fn get_artwork_url(&self, _track: &Track) -> Result<Option<String>> {
+        let state = self.engine.state.lock().await;
+        if state.stream.is_some() {
+            return Ok(None);
+        }
+        let current = state.current;
+        drop(state);
+        self.engine.library.artwork_url(current).await
+    }
+
+    // Local files are identified by path; streamed tracks and radio have none
+    async fn get_track_id(&self, track: &Track) -> Result<Option<String>> {
+        let state = self.engine.state.lock().await;
+        if state.stream.is_some() {
+            return Ok(None);
+        }
+        let current = state.current;
+        drop(state);
+        let entry = self.engine.library.describe(current).await?;
+        if entry.track.name != track.name {
+            return Ok(None);
+        }
+        Ok(self.engine.library.track_id(current))
+    }
+
+    // The system default, which is what the engine opened
+    async fn get_output_device(&self) -> Result<String> {
+        use rodio::cpal::traits::{DeviceTrait, HostTrait};
+        tokio::task::spawn_blocking(|| {
+            let device = rodio::cpal::default_host()
+                .default_output_device()
+                .ok_or_else(|| anyhow!("No audio output device"))?;
+            Ok(device.name()?)
+        })
+        .await?
+    }
+
+    // The gain the current mode picks, with `off` reading track gain
+    async fn get_normalization(&self, _track: &Track) -> Result<Normalization> {
+        let state = self.engine.state.lock().await;
+        let (current, streaming) = (state.current, state.stream.is_some());
+        drop(state);
+        let location = match streaming {
+            true => None,
+            false => self.engine.library.describe(current).await?.location,
+        };
+        let tags = match location {
+            Some(path) => tokio::task::spawn_blocking(move || GainTags::read(&path)).await?,
+            None => GainTags::default(),
+        };
+        Ok(Normalization {
+            kind: NormalizationKind::ReplayGain,
+            enabled: Some(self.engine.replay_gain_on.load(Ordering::Relaxed)),
+            gain_db: tags.pick(self.engine.replay_gain).map(|(gain, _)| gain),
+        })
+    }
+
+    async fn set_normalization(&self, enabled: bool) -> Result<()> {
+        self.engine.replay_gain_on.store(enabled, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn play_query(&self, query: &str) -> Result<bool> {
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        match engine.library.search(query).await? {
+            Some(index) => {
+                // Searching may have replaced the library's track list
+                state.metadata.clear();
+                engine.start(&mut state, index).await.map(|_| true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // Counted after cue sheets are split into their tracks
+    async fn play_stream(&self, url: &str, name: &str) -> Result<()> {
+        let stream = Stream {
+            url: url.to_string(),
+            station: name.to_string(),
+            title: Arc::default(),
+        };
+        self.engine.start_stream(stream).await
+    }
+
+    async fn play_files(&self, paths: &[PathBuf]) -> Result<usize> {
+        let files = expand_files(paths).await?;
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        engine.library.replace_files(files)?;
+        state.metadata.clear();
+        engine.start(&mut state, 0).await?;
+        Ok(engine.library.len())
+    }
+
+    // When the queue had already run out, playback picks up with the first
+    // added track
+    async fn enqueue_files(&self, paths: &[PathBuf]) -> Result<usize> {
+        let files = expand_files(paths).await?;
+        let engine = &self.engine;
+        let mut state = engine.state.lock().await;
+        engine.sync(&mut state);
+        let first = engine.library.len();
+        engine.library.append_files(files)?;
+        let count = engine.library.len() - first;
+        if state.loaded.is_empty() && state.current + 1 == first {
+            engine.start(&mut state, first).await?;
+        } else {
+            engine.preload(&mut state);
+        }
+        Ok(count)
+    }
+
+    // A cue sheet is described as the album it splits, as long as its files
+    async fn describe_file(&self, path: &Path) -> Result<Track> {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            if !is_cue_sheet(&path) {
+                return Ok(read_track(&path, None));
+            }
+            let tracks = cue::read(&path)?;
+            let mut files: Vec<&Path> = tracks.iter().map(|t| t.file.as_path()).collect();
+            files.dedup();
+            let first = read_track(files[0], None);
+            Ok(Track {
+                name: tracks[0].album.clone().unwrap_or(first.album),
+                artist: tracks[0].album_artist.clone().unwrap_or(first.artist),
+                album: String::new(),
+                duration: files.iter().map(|f| read_track(f, None).duration).sum(),
+                position: Duration::ZERO,
+            })
+        })
+        .await?
+    }
+
+    async fn get_queue(&self) -> Result<Vec<QueueEntry>> {
+        let engine = &self.engine;
+        let current = {
+            let mut state = engine.state.lock().await;
+            engine.sync(&mut state);
+            if state.stream.is_some() {
+                return Ok(Vec::new());
+            }
+            state.current
+        };
+        let end = engine.library.len().min(current + 1 + QUEUE_LIMIT);
+        let mut queue = Vec::new();
+        for index in current + 1..end {
+            queue.push(engine.library.describe(index).await?);
+        }
+        Ok(queue)
+    }
+}
+
+// A file played whole, or one track of a cue sheet
+#[derive(Debug, Clone, PartialEq)]
+enum LocalEntry {
+    File(PathBuf),
+    Cue(CueTrack),
+}
+
+impl LocalEntry {
+    fn path(&self) -> &Path {
+        match self {
+            LocalEntry::File(path) => path,
+            LocalEntry::Cue(track) => &track.file,
+        }
+    }
+
+    // File tags fill in whatever the sheet leaves out
+    fn track(&self, decoded_duration: Option<Duration>) -> Track {
+        let file = read_track(self.path(), decoded_duration);
+        let LocalEntry::Cue(cue) = self else {
+            return file;
+        };
+        Track {
+            name: cue
+                .title
+                .clone()
+                .unwrap_or_else(|| format!("{} {:02}", file.name, cue.number)),
+            artist: cue
+                .performer
+                .clone()
+                .or_else(|| cue.album_artist.clone())
+                .unwrap_or(file.artist),
+            album: cue.album.clone().unwrap_or(file.album),
+            duration: cue
+                .length()
+                .unwrap_or_else(|| file.duration.saturating_sub(cue.start)),
+            position: Duration::ZERO,
+        }
+    }
+}
+
+// Every supported audio file below a folder, in path order, until files
+// picked in the browser take its place. Cue sheets are split into their tracks.
+pub struct LocalLibrary {
+    entries: RwLock<Vec<LocalEntry>>,
+}
+
+impl LocalLibrary {
+    pub fn new(music_dir: &Path) -> Result<Self> {
+        let entries = local_entries(collect_audio_files(music_dir)?);
+        if entries.is_empty() {
+            return Err(anyhow!("No audio files found in {}", music_dir.display()));
+        }
+        Ok(Self {
+            entries: RwLock::new(entries),
+        })
+    }
+
+    fn entry(&self, index: usize) -> Result<LocalEntry> {
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(index)
+            .cloned()
+            .ok_or_else(|| anyhow!("Track {} is no longer queued", index + 1))
+    }
+}
+
+#[async_trait]
+impl Library for LocalLibrary {
+    fn len(&self) -> usize {
+        self.entries.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    async fn load(&self, index: usize) -> Result<(AudioSource, Track)> {
+        let entry = self.entry(index)?;
+        tokio::task::spawn_blocking(move || {
+            let path = entry.path();
+            let file =
+                File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+            let mut decoder = Decoder::new(BufReader::new(file))
+                .with_context(|| format!("Unsupported audio file {}", path.display()))?;
+            let track = entry.track(decoder.total_duration());
+            let LocalEntry::Cue(cue) = &entry else {
+                return Ok((Box::new(decoder) as AudioSource, track));
+            };
+            if !cue.start.is_zero() {
+                decoder
+                    .try_seek(cue.start)
+                    .map_err(|e| anyhow!("Can't seek into {}: {}", path.display(), e))?;
+            }
+            let span = CueSpan::new(Box::new(decoder), cue.start, cue.length());
+            Ok((Box::new(span) as AudioSource, track))
+        })
+        .await?
+    }
+
+    async fn describe(&self, index: usize) -> Result<QueueEntry> {
+        let entry = self.entry(index)?;
+        tokio::task::spawn_blocking(move || QueueEntry {
+            track: entry.track(None),
+            location: Some(entry.path().to_path_buf()),
+        })
+        .await
+        .map_err(Into::into)
+    }
+
+    // A cue track shows its own file's art, for sheets spanning several files
+    async fn artwork_url(&self, index: usize) -> Result<Option<String>> {
+        let entry = self.entry(index)?;
+        tokio::task::spawn_blocking(move || artwork_url(entry.path())).await?
+    }
+
+    // Case-insensitive match against the path below the music directory, or a
+    // cue track's title
+    async fn search(&self, query: &str) -> Result<Option<usize>> {
+        let query = query.to_lowercase();
+        Ok(self
+            .entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .position(|entry| {
+                let title = match entry {
+                    LocalEntry::Cue(cue) => cue.title.as_deref().unwrap_or_default(),
+                    LocalEntry::File(_) => "",
+                };
+                entry
+                    .path()
+                    .to_string_lossy()
+                    .to_lowercase()
+                    .contains(&query)
+                    || title.to_lowercase().contains(&query)
+            }))
+    }
+
+    fn replace_files(&self, files: Vec<PathBuf>) -> Result<()> {
+        *self.entries.write().unwrap_or_else(|e| e.into_inner()) = local_entries(files);
+        Ok(())
+    }
+
+    fn append_files(&self, files: Vec<PathBuf>) -> Result<()> {
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(local_entries(files));
+        Ok(())
+    }
+
+    // Tracks split from one file by a cue sheet are told apart by number
+    fn track_id(&self, index: usize) -> Option<String> {
+        let entry = self.entry(index).ok()?;
+        let path = entry.path().to_string_lossy();
+        Some(match &entry {
+            LocalEntry::File(_) => path.into_owned(),
+            LocalEntry::Cue(cue) => format!("{}#{:02}", path, cue.number),
+        })
+    }
+}
+
+// One cue track of a longer file, already seeked to its start. Positions are
+// the track's own, and it ends where the next track begins.
+struct CueSpan {
+    inner: AudioSource,
+    start: Duration,
+    length: Option<Duration>,
+    remaining: Option<u64>,
+}
+
+impl CueSpan {
+    fn new(inner: AudioSource, start: Duration, length: Option<Duration>) -> Self {
+        let remaining = length.map(|l| sample_count(l, inner.channels(), inner.sample_rate()));
+        Self {
+            inner,
+            start,
+            length,
+            remaining,
+        }
+    }
+}
+
+impl Iterator for CueSpan {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.checked_sub(1)?;
+        }
+        self.inner.next()
+    }
+}
+
+impl Source for CueSpan {
+    fn current_frame_len(&self) -> Option<usize> {
+        let frame = self.inner.current_frame_len();
+        match self.remaining {
+            Some(remaining) => {
+                Some(frame.map_or(remaining as usize, |f| f.min(remaining as usize)))
+            }
+            None => frame,
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.length.or_else(|| {
+            self.inner
+                .total_duration()
+                .map(|total| total.saturating_sub(self.start))
+        })
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+        let position = self.length.map_or(position, |length| position.min(length));
+        self.inner.try_seek(self.start + position)?;
+        self.remaining = self.length.map(|length| {
+            sample_count(
+                length - position,
+                self.inner.channels(),
+                self.inner.sample_rate(),
+            )
+        });
+        Ok(())
+    }
+}
+
+// Cue sheets turn into their tracks; one that can't be read is skipped
+fn local_entries(files: Vec<PathBuf>) -> Vec<LocalEntry> {
+    let mut entries = Vec::new();
+    for path in files {
+        if !is_cue_sheet(&path) {
+            entries.push(LocalEntry::File(path));
+            continue;
+        }
+        match cue::read(&path) {
+            Ok(tracks) => entries.extend(tracks.into_iter().map(LocalEntry::Cue)),
+            Err(e) => tracing::warn!("Skipping cue sheet {}: {}", path.display(), e),
+        }
+    }
+    entries
+}
+
+// Files as given and the audio files below folders, erroring if that's none
+async fn expand_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let paths = paths.to_vec();
+    tokio::task::spawn_blocking(move || {
+        let mut files = Vec::new();
+        for path in &paths {
+            files.extend(collect_audio_files(path)?);
+        }
+        if files.is_empty() {
+            return Err(anyhow!("No audio files to play there"));
+        }
+        Ok(files)
+    })
+    .await?
+}
+
+// Cue sheets are listed in place of the audio files they split
+fn collect_audio_files(root: &Path) -> Result<Vec<PathBuf>> {
+    if root.is_file() {
+        return Ok(vec![root.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    let mut sheets = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries =
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if is_audio_file(&path) {
+                files.push(path);
+            } else if is_cue_sheet(&path) {
+                sheets.push(path);
+            }
+        }
+    }
+    let mut split = HashSet::new();
+    sheets.retain(|sheet| match cue::read(sheet) {
+        Ok(tracks) => {
+            split.extend(tracks.into_iter().map(|track| track.file));
+            true
+        }
+        Err(_) => false,
+    });
+    files.retain(|file| !split.contains(file));
+    files.extend(sheets);
+    files.sort();
+    Ok(files)
+}
+
+// Tags when present; otherwise the file stem and the folder name stand in.
+fn read_track(path: &Path, decoded_duration: Option<Duration>) -> Track {
+    let tagged = lofty::read_from_path(path).ok();
+    let tag = tagged
+        .as_ref()
+        .and_then(|f| f.primary_tag().or(f.first_tag()));
+    let text = |value: Option<std::borrow::Cow<'_, str>>| value.map(|v| v.into_owned());
+
+    let name = text(tag.and_then(|t| t.title())).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let artist = text(tag.and_then(|t| t.artist())).unwrap_or_default();
+    let album = text(tag.and_then(|t| t.album())).unwrap_or_else(|| {
+        path.parent()
+            .and_then(|p| p.file_name())
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let duration = tagged
+        .as_ref()
+        .map(|f| f.properties().duration())
+        .filter(|d| !d.is_zero())
+        .or(decoded_duration)
+        .unwrap_or_default();
+
+    Track {
+        name,
+        artist,
+        album,
+        duration,
+        position: Duration::ZERO,
+    }
+}
+
+// A file's loudness tags, in dB from the ReplayGain reference level, with
+// peaks as a fraction of full scale
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct GainTags {
+    track: Option<f32>,
+    album: Option<f32>,
+    track_peak: Option<f32>,
+    album_peak: Option<f32>,
+}
+
+impl GainTags {
+    // REPLAYGAIN_* as written by most taggers ("-6.20 dB"), or Opus's
+    // R128_*_GAIN, a Q7.8 number against -23 LUFS rather than -18
+    fn read(path: &Path) -> Self {
+        let Some(tagged) = lofty::read_from_path(path).ok() else {
+            return Self::default();
+        };
+        let Some(tag) = tagged.primary_tag().or(tagged.first_tag()) else {
+            return Self::default();
+        };
+        let value = |key: ItemKey| tag.get_string(&key).and_then(parse_gain);
+        let r128 = |key: &str| {
+            let value = tag.get_string(&ItemKey::Unknown(key.to_string()))?;
+            let q78: i16 = value.trim().parse().ok()?;
+            Some(f32::from(q78) / 256.0 + 5.0)
+        };
+        Self {
+            track: value(ItemKey::ReplayGainTrackGain).or_else(|| r128("R128_TRACK_GAIN")),
+            album: value(ItemKey::ReplayGainAlbumGain).or_else(|| r128("R128_ALBUM_GAIN")),
+            track_peak: value(ItemKey::ReplayGainTrackPeak),
+            album_peak: value(ItemKey::ReplayGainAlbumPeak),
+        }
+    }
+
+    // Album gain falls back to track gain for singles and partial tagging
+    fn pick(&self, mode: ReplayGainMode) -> Option<(f32, Option<f32>)> {
+        match mode {
+            ReplayGainMode::Album if self.album.is_some() => {
+                self.album.map(|gain| (gain, self.album_peak))
+            }
+            _ => self.track.map(|gain| (gain, self.track_peak)),
+        }
+    }
+
+    // The sample multiplier, held down so a boosted peak doesn't clip. `off`
+    // still computes track gain, for when `V` switches it on.
+    fn factor(&self, mode: ReplayGainMode) -> f32 {
+        let Some((gain, peak)) = self.pick(mode) else {
+            return 1.0;
+        };
+        let factor = 10f32.powf(gain / 20.0);
+        match peak.filter(|peak| *peak > 0.0) {
+            Some(peak) => factor.min(1.0 / peak),
+            None => factor,
+        }
+    }
+}
+
+fn parse_gain(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let number = value
+        .strip_suffix("dB")
+        .or_else(|| value.strip_suffix("db"))
+        .unwrap_or(value);
+    number.trim().parse().ok()
+}
+
+// Embedded art is written to a temp file like the Apple Music export; otherwise
+// a cover image next to the file is used.
+fn artwork_url(path: &Path) -> Result<Option<String>> {
+    let embedded = lofty::read_from_path(path).ok().and_then(|f| {
+        let tag = f.primary_tag().or(f.first_tag())?;
+        tag.pictures().first().map(|p| p.data().to_vec())
+    });
+    if let Some(data) = embedded {
+        let hash = format!("{:x}", Sha256::digest(path.to_string_lossy().as_bytes()));
+        let out = std::env::temp_dir().join(format!("amcli-local-artwork-{}.img", hash));
+        std::fs::write(&out, data)?;
+        return Ok(Some(format!("file://{}", out.display())));
+    }
+
+    let Some(dir) = path.parent() else {
+        return Ok(None);
+    };
+    let cover = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| {
+            let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            let ext = p.extension().and_then(|s| s.to_str()).unwrap_or_default();
+            COVER_FILE_NAMES.contains(&stem.to_lowercase().as_str())
+                && matches!(ext.to_lowercase().as_str(), "jpg" | "jpeg" | "png")
+        });
+    Ok(cover.map(|p| format!("file://{}", p.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn following_index_honours_repeat_mode() {
+        assert_eq!(following_index(1, 3, RepeatMode::Off), Some(2));
+        assert_eq!(following_index(2, 3, RepeatMode::Off), None);
+        assert_eq!(following_index(2, 3, RepeatMode::All), Some(0));
+        assert_eq!(following_index(2, 3, RepeatMode::One), Some(2));
+        assert_eq!(following_index(0, 0, RepeatMode::All), None);
+    }
+
+    #[test]
+    fn balance_turns_down_the_opposite_channel() {
+        let balance = Arc::new(AtomicU32::new(0.5f32.to_bits()));
+        let stereo = rodio::buffer::SamplesBuffer::new(2, 44_100, vec![1000i16; 4]);
+        let balanced = Balanced {
+            inner: Box::new(stereo),
+            balance: Arc::clone(&balance),
+            channel: 0,
+        };
+        assert_eq!(balanced.collect::<Vec<_>>(), vec![500, 1000, 500, 1000]);
+
+        // Mono has no sides to balance
+        let mono = rodio::buffer::SamplesBuffer::new(1, 44_100, vec![1000i16; 2]);
+        let balanced = Balanced {
+            inner: Box::new(mono),
+            balance,
+            channel: 0,
+        };
+        assert_eq!(balanced.collect::<Vec<_>>(), vec![1000, 1000]);
+    }
+
+    #[test]
+    fn crossfade_mixes_the_next_opening_into_the_tail() {
+        let crossfading = Arc::new(AtomicBool::new(false));
+        let track =
+            |samples: Vec<i16>| -> AudioSource { Box::new(SamplesBuffer::new(1, 10, samples)) };
+        let (mut tail, handle) = CrossfadeTail::wrap(
+            track(vec![1000; 40]),
+            Duration::ZERO,
+            Duration::from_secs(4),
+            Duration::from_secs(1),
+            &crossfading,
+        );
+        let handle = handle.unwrap();
+        assert!(handle.offer(SamplesBuffer::new(1, 10, vec![2000; 10])));
+        assert!(!handle.offer(SamplesBuffer::new(1, 10, vec![2000; 10])));
+
+        let head: Vec<i16> = tail.by_ref().take(31).collect();
+        assert!(head.iter().all(|&s| s == 1000));
+        assert!(crossfading.load(Ordering::Relaxed));
+        let rest: Vec<i16> = tail.by_ref().collect();
+        // Equal-power gains, from mostly this track to mostly the next
+        assert_eq!(rest, [1300, 1569, 1798, 1984, 2121, 2205, 2236, 2211, 2131]);
+        assert!(!crossfading.load(Ordering::Relaxed));
+
+        // Once the tail has begun, the next track just plays from its start
+        let (mut late, handle) = CrossfadeTail::wrap(
+            track(vec![1000; 40]),
+            Duration::ZERO,
+            Duration::from_secs(4),
+            Duration::from_secs(1),
+            &crossfading,
+        );
+        late.by_ref().take(35).for_each(drop);
+        assert!(!handle
+            .unwrap()
+            .offer(SamplesBuffer::new(1, 10, vec![2000; 10])));
+
+        // Too short to fade over
+        let (_, handle) = CrossfadeTail::wrap(
+            track(vec![1000; 15]),
+            Duration::ZERO,
+            Duration::from_millis(1500),
+            Duration::from_secs(1),
+            &crossfading,
+        );
+        assert!(handle.is_none());
+    }
+
+    #[test]
+    fn collects_audio_files_recursively_in_order() {
+        let root = std::env::temp_dir().join(format!("amcli-internal-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("B Album")).unwrap();
+        for name in ["B Album/02 Two.flac", "01 One.MP3", "notes.txt"] {
+            std::fs::write(root.join(name), b"").unwrap();
+        }
+
+        let files = collect_audio_files(&root).unwrap();
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(
+            files,
+            vec![root.join("01 One.MP3"), root.join("B Album/02 Two.flac")]
+        );
+    }
+
+    #[tokio::test]
+    async fn cue_sheets_stand_in_for_the_files_they_split() {
+        let root = std::env::temp_dir().join(format!("amcli-cue-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        for name in ["mix.flac", "single.mp3"] {
+            std::fs::write(root.join(name), b"").unwrap();
+        }
+        std::fs::write(
+            root.join("mix.cue"),
+            "PERFORMER \"DJ Test\"\nTITLE \"Late Set\"\nFILE \"mix.wav\" WAVE\n\
+             TRACK 01 AUDIO\nTITLE \"Opener\"\nINDEX 01 00:00:00\n\
+             TRACK 02 AUDIO\nTITLE \"Closer\"\nINDEX 01 04:30:00\n",
+        )
+        .unwrap();
+
+        // The sheet names a .wav that became a .flac
+        let files = collect_audio_files(&root).unwrap();
+        assert_eq!(files, vec![root.join("mix.cue"), root.join("single.mp3")]);
+        let library = LocalLibrary::new(&root).unwrap();
+        assert_eq!(library.len(), 3);
+        assert_eq!(library.entry(1).unwrap().path(), root.join("mix.flac"));
+
+        let closer = library.describe(1).await.unwrap().track;
+        assert_eq!(
+            (closer.name.as_str(), closer.artist.as_str()),
+            ("Closer", "DJ Test")
+        );
+        assert_eq!(closer.album, "Late Set");
+        assert_eq!(
+            library.track_id(1),
+            Some(format!("{}#02", root.join("mix.flac").display()))
+        );
+        assert_eq!(library.search("closer").await.unwrap(), Some(1));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn cue_spans_seek_within_and_stop_at_the_track() {
+        // 10 samples a second of their own index, so the values tell the time
+        let file = SamplesBuffer::new(1, 10, (0..100).collect::<Vec<i16>>());
+        let mut source: AudioSource = Box::new(file);
+        source.try_seek(Duration::from_secs(2)).unwrap();
+        let mut span = CueSpan::new(source, Duration::from_secs(2), Some(Duration::from_secs(3)));
+        assert_eq!(span.total_duration(), Some(Duration::from_secs(3)));
+        assert_eq!(span.next(), Some(20));
+
+        span.try_seek(Duration::from_secs(2)).unwrap();
+        assert_eq!(span.collect::<Vec<_>>(), (40..50).collect::<Vec<i16>>());
+    }
+
+    #[tokio::test]
+    async fn browsed_files_replace_or_extend_the_library() {
+        let root = std::env::temp_dir().join(format!("amcli-browsed-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("Live")).unwrap();
+        for name in ["01 One.mp3", "Live/02 Two.flac", "Live/03 Three.ogg"] {
+            std::fs::write(root.join(name), b"").unwrap();
+        }
+        let library = LocalLibrary::new(&root.join("01 One.mp3")).unwrap();
+
+        let added = expand_files(&[root.join("Live")]).await.unwrap();
+        library.append_files(added).unwrap();
+        assert_eq!(library.len(), 3);
+        assert_eq!(
+            library.entry(2).unwrap().path(),
+            root.join("Live/03 Three.ogg")
+        );
+
+        library
+            .replace_files(vec![root.join("Live/02 Two.flac")])
+            .unwrap();
+        assert_eq!(library.len(), 1);
+        assert!(library.entry(1).is_err());
+        assert!(expand_files(&[root.join("Empty")]).await.is_err());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn replay_gain_values_parse_with_or_without_units() {
+        assert_eq!(parse_gain("-6.20 dB"), Some(-6.2));
+        assert_eq!(parse_gain("+1.5"), Some(1.5));
+        assert_eq!(parse_gain("loud"), None);
+    }
+
+    #[test]
+    fn replay_gain_picks_album_or_track_gain_without_clipping() {
+        let tags = GainTags {
+            track: Some(-6.0),
+            album: Some(-3.0),
+            track_peak: Some(0.9),
+            album_peak: None,
+        };
+        assert_eq!(tags.pick(ReplayGainMode::Track), Some((-6.0, Some(0.9))));
+        assert_eq!(tags.pick(ReplayGainMode::Album), Some((-3.0, None)));
+        assert!((tags.factor(ReplayGainMode::Track) - 0.501).abs() < 0.001);
+
+        // A single has no album gain; a boost stops short of the peak
+        let single = GainTags {
+            track: Some(6.0),
+            track_peak: Some(0.8),
+            ..GainTags::default()
+        };
+        assert_eq!(single.factor(ReplayGainMode::Album), 1.25);
+        assert_eq!(GainTags::default().factor(ReplayGainMode::Track), 1.0);
+
+        let mut gained = ReplayGained {
+            inner: Box::new(SamplesBuffer::new(1, 10, vec![1000i16, 30_000])),
+            gain: 2.0,
+            enabled: Arc::new(AtomicBool::new(true)),
+        };
+        assert_eq!(gained.next(), Some(2000));
+        gained.enabled.store(false, Ordering::Relaxed);
+        assert_eq!(gained.next(), Some(30_000));
+    }
+
+    #[test]
+    fn untagged_files_fall_back_to_file_and_folder_names() {
+        let track = read_track(
+            Path::new("/music/Kind of Blue/Blue in Green.mp3"),
+            Some(Duration::from_secs(337)),
+        );
+        assert_eq!(track.name, "Blue in Green");
+        assert_eq!(track.album, "Kind of Blue");
+        assert_eq!(track.duration, Duration::from_secs(337));
+    }
+}
@@ -0,0 +1,397 @@
+// src/player/mod.rs
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::{PlayerBackend, PlayerConfig};
+use crate::library::LibraryItem;
+
+pub mod apple_music;
+#[cfg(feature = "internal-player")]
+mod cue;
+pub mod demo;
+#[cfg(feature = "internal-player")]
+pub mod internal;
+pub mod now_playing;
+mod output;
+pub mod podcasts;
+#[cfg(feature = "internal-player")]
+mod radio;
+pub mod recording;
+pub mod shairport;
+#[cfg(feature = "spotify")]
+pub mod spotify;
+pub mod subprocess;
+#[cfg(feature = "internal-player")]
+pub mod subsonic;
+
+/// Metadata for the track a player is on. `position` is how far into it
+/// playback is at the time of the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub name: String,
+    pub artist: String,
+    pub album: String,
+    pub duration: Duration,
+    pub position: Duration,
+}
+
+/// Containers and codecs the internal player decodes, by file extension.
+pub const AUDIO_EXTENSIONS: &[&str] = &["aac", "flac", "m4a", "mp3", "mp4", "oga", "ogg", "wav"];
+
+/// Whether the internal player can take `path`, going by its extension.
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Whether `path` is a cue sheet, which the internal player splits the audio
+/// file beside it by.
+pub fn is_cue_sheet(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("cue"))
+}
+
+/// Builds the backend selected by `[player] backend` in the config.
+pub async fn create_player(config: &PlayerConfig) -> Result<Box<dyn MediaPlayer>> {
+    match config.backend {
+        PlayerBackend::AppleMusic => Ok(Box::new(apple_music::AppleMusicController::new())),
+        PlayerBackend::Podcasts => Ok(Box::new(podcasts::PodcastsController::new())),
+        PlayerBackend::Plugin => {
+            let name = config.plugin.as_deref().ok_or_else(|| {
+                anyhow!("Set player.plugin to the name of an amcli-player-<name> executable")
+            })?;
+            Ok(Box::new(subprocess::SubprocessPlayer::spawn(name).await?))
+        }
+        PlayerBackend::NowPlaying => Ok(Box::new(now_playing::NowPlayingController::new())),
+        PlayerBackend::Shairport => Ok(Box::new(shairport::ShairportPlayer::open(
+            &config.shairport,
+        )?)),
+        #[cfg(feature = "internal-player")]
+        PlayerBackend::Internal => {
+            let music_dir = config
+                .music_dir
+                .as_deref()
+                .map(|dir| crate::playlist::expand_home(&dir.to_string_lossy()))
+                .or_else(dirs::audio_dir)
+                .ok_or_else(|| anyhow!("Set player.music_dir to use the internal player"))?;
+            Ok(Box::new(internal::InternalPlayer::local(
+                &music_dir, config,
+            )?))
+        }
+        #[cfg(feature = "internal-player")]
+        PlayerBackend::Subsonic => {
+            let subsonic = config.subsonic.as_ref().ok_or_else(|| {
+                anyhow!("Add a [player.subsonic] section with url, username, and password")
+            })?;
+            let library = subsonic::SubsonicLibrary::connect(subsonic).await?;
+            Ok(Box::new(internal::InternalPlayer::new(
+                Box::new(library),
+                config,
+            )?))
+        }
+        #[cfg(not(feature = "internal-player"))]
+        PlayerBackend::Internal | PlayerBackend::Subsonic => Err(anyhow!(
+            "This backend needs a build with `--features internal-player`"
+        )),
+        #[cfg(feature = "spotify")]
+        PlayerBackend::Spotify => Ok(Box::new(
+            spotify::SpotifyPlayer::connect(&config.spotify).await?,
+        )),
+        #[cfg(not(feature = "spotify"))]
+        PlayerBackend::Spotify => Err(anyhow!(
+            "The Spotify backend needs a build with `--features spotify`"
+        )),
+    }
+}
+
+/// A track plus its file on disk, when the player has one (streamed tracks don't).
+#[derive(Debug, Clone)]
+pub struct QueueEntry {
+    pub track: Track,
+    pub location: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepeatMode {
+    Off,
+    One,
+    All,
+}
+
+/// Everything the UI polls for, fetched together. `volume` and `repeat` are
+/// `None` when the player doesn't expose them.
+#[derive(Debug)]
+pub struct PlayerStatus {
+    pub track: Option<Track>,
+    pub volume: Option<u8>,
+    pub state: PlaybackState,
+    pub repeat: Option<RepeatMode>,
+}
+
+/// The player's own crossfade and Sound Check preferences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackOptions {
+    pub crossfade: bool,
+    /// Kept while crossfade is off, so turning it back on restores it.
+    pub crossfade_secs: u8,
+    pub sound_check: bool,
+}
+
+/// How the player evens out loudness between tracks, and what it knows about
+/// the current one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Normalization {
+    pub kind: NormalizationKind,
+    /// `None` when the player can't say without disrupting playback, as with
+    /// Music's Sound Check, which is only readable from its settings window.
+    pub enabled: Option<bool>,
+    /// The current track's gain in dB, when known.
+    pub gain_db: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationKind {
+    SoundCheck,
+    ReplayGain,
+}
+
+/// Playback rates offered by the speed keys, for podcasts and audiobooks.
+pub const PLAYBACK_RATES: &[f32] = &[0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
+/// A music player amcli can display and control.
+///
+/// Every backend implements the transport and metadata methods. The methods
+/// with default bodies are optional and report "not supported" errors unless a
+/// backend overrides them.
+#[async_trait]
+pub trait MediaPlayer: Send + Sync {
+    async fn play(&self) -> Result<()>;
+    async fn pause(&self) -> Result<()>;
+    async fn toggle(&self) -> Result<()>;
+    async fn next(&self) -> Result<()>;
+    async fn previous(&self) -> Result<()>;
+    async fn stop(&self) -> Result<()>;
+
+    /// `None` when nothing is loaded.
+    async fn get_current_track(&self) -> Result<Option<Track>>;
+    async fn get_playback_state(&self) -> Result<PlaybackState>;
+
+    /// Track, volume, and state in one call. The default runs the three queries
+    /// concurrently; backends that can fetch them at once should override it.
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        let (track_result, volume_result, state_result) = tokio::join!(
+            self.get_current_track(),
+            self.get_volume(),
+            self.get_playback_state()
+        );
+        Ok(PlayerStatus {
+            track: track_result.ok().flatten(),
+            volume: volume_result.ok(),
+            state: state_result.unwrap_or(PlaybackState::Stopped),
+            repeat: None,
+        })
+    }
+
+    /// Volume from 0 to 100.
+    async fn set_volume(&self, volume: u8) -> Result<()>;
+    async fn get_volume(&self) -> Result<u8>;
+    /// Moves relative to the current position; negative seeks back.
+    async fn seek(&self, seconds: i32) -> Result<()>;
+    /// Jumps to an absolute position in the current track.
+    async fn set_position(&self, _position: Duration) -> Result<()> {
+        Err(anyhow!(
+            "jumping to a position is not supported by this player"
+        ))
+    }
+    async fn set_shuffle(&self, enabled: bool) -> Result<()>;
+    async fn set_repeat(&self, mode: RepeatMode) -> Result<()>;
+    /// An `https://` or `file://` URL for the track's cover, if one is known.
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>>;
+
+    /// The player's stable ID for `track`, such as Music's persistent ID.
+    /// `None` when the player has moved on to another track.
+    async fn get_track_id(&self, _track: &Track) -> Result<Option<String>> {
+        Err(anyhow!("this player doesn't identify its tracks"))
+    }
+
+    /// Plays an internet radio stream in place of the queue, titled `name`
+    /// (or the station's own name when empty) until the stream sends song
+    /// titles.
+    async fn play_stream(&self, _url: &str, _name: &str) -> Result<()> {
+        Err(anyhow!("internet radio needs the internal player"))
+    }
+
+    /// Replaces the queue with local files, folders expanded in path order,
+    /// and plays the first. Returns how many tracks were queued.
+    async fn play_files(&self, _paths: &[PathBuf]) -> Result<usize> {
+        Err(anyhow!("this player can't play local files"))
+    }
+
+    /// Adds local files after everything already queued, folders expanded in
+    /// path order. Returns how many tracks were added.
+    async fn enqueue_files(&self, _paths: &[PathBuf]) -> Result<usize> {
+        Err(anyhow!("this player can't queue local files"))
+    }
+
+    /// A local file's tags, to preview it before it's queued.
+    async fn describe_file(&self, _path: &Path) -> Result<Track> {
+        Err(anyhow!("this player can't read local files"))
+    }
+
+    /// Searches the player's library and starts the best match. Returns false
+    /// when nothing matched.
+    async fn play_query(&self, _query: &str) -> Result<bool> {
+        Err(anyhow!("search is not supported by this player"))
+    }
+
+    /// Starts `album` by `artist`. Returns false when the player doesn't
+    /// have it. The default searches the library for both names, which lands
+    /// on one of the album's tracks rather than its first.
+    async fn play_album(&self, artist: &str, album: &str) -> Result<bool> {
+        self.play_query(&format!("{} {}", album, artist)).await
+    }
+
+    /// Everything in the player's library at once, for the quick switcher's
+    /// index. Players with big libraries implement the paged
+    /// [`get_library_track_ids`](Self::get_library_track_ids) instead; see
+    /// [`library::refresh`](crate::library::refresh).
+    async fn get_library(&self) -> Result<Vec<LibraryItem>> {
+        Err(anyhow!("this player has no library to browse"))
+    }
+
+    /// The ID of every track in the library, in library order.
+    async fn get_library_track_ids(&self) -> Result<Vec<String>> {
+        Err(anyhow!("this player can't list its tracks"))
+    }
+
+    /// Up to `limit` tracks from `offset` on, in the order of
+    /// [`get_library_track_ids`](Self::get_library_track_ids), with their IDs.
+    async fn get_library_tracks(&self, _offset: usize, _limit: usize) -> Result<Vec<LibraryItem>> {
+        Err(anyhow!("this player can't list its tracks"))
+    }
+
+    /// Names of the user's playlists.
+    async fn get_playlists(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Starts the library track with this ID. Returns false when it's gone.
+    async fn play_track_id(&self, _id: &str) -> Result<bool> {
+        Err(anyhow!("this player can't play tracks by ID"))
+    }
+
+    /// Starts the library playlist with this name from the top.
+    async fn play_playlist(&self, _name: &str) -> Result<()> {
+        Err(anyhow!("this player can't start playlists"))
+    }
+
+    /// Opens an Apple Music catalog link (a song, album, or station) in the
+    /// player and starts it.
+    async fn play_catalog_url(&self, _url: &str) -> Result<()> {
+        Err(anyhow!("this player can't play catalog links"))
+    }
+
+    async fn get_playback_options(&self) -> Result<PlaybackOptions> {
+        Err(anyhow!("this player's playback options aren't scriptable"))
+    }
+
+    async fn set_playback_options(&self, _options: PlaybackOptions) -> Result<()> {
+        Err(anyhow!("this player's playback options aren't scriptable"))
+    }
+
+    /// Whether the player keeps going with similar music once the queue runs
+    /// out (Music's Autoplay, the ∞ button in Playing Next).
+    async fn get_autoplay(&self) -> Result<bool> {
+        Err(anyhow!("this player has no autoplay setting"))
+    }
+
+    async fn set_autoplay(&self, _enabled: bool) -> Result<()> {
+        Err(anyhow!("this player has no autoplay setting"))
+    }
+
+    /// Loudness normalization for `track`, the one playing. Sound Check is
+    /// switched through [`set_playback_options`](Self::set_playback_options).
+    async fn get_normalization(&self, _track: &Track) -> Result<Normalization> {
+        Err(anyhow!("this player doesn't report loudness normalization"))
+    }
+
+    /// Switches ReplayGain on or off, for players that apply it themselves.
+    async fn set_normalization(&self, _enabled: bool) -> Result<()> {
+        Err(anyhow!("ReplayGain can't be switched in this player"))
+    }
+
+    /// Where the sound is going: AirPlay speakers, or the output device. The
+    /// default asks the system for its output device, which only works on
+    /// macOS and is slow enough that callers should poll it sparingly.
+    async fn get_output_device(&self) -> Result<String> {
+        output::system_output_device().await
+    }
+
+    /// Playback speed as a multiple of normal, 1.0.
+    async fn get_rate(&self) -> Result<f32> {
+        Err(anyhow!("this player can't change playback speed"))
+    }
+
+    async fn set_rate(&self, _rate: f32) -> Result<()> {
+        Err(anyhow!("this player can't change playback speed"))
+    }
+
+    /// Left/right balance, from -1.0 (left only) through 0.0 to 1.0 (right only).
+    async fn get_balance(&self) -> Result<f32> {
+        Err(anyhow!("this player can't adjust stereo balance"))
+    }
+
+    async fn set_balance(&self, _balance: f32) -> Result<()> {
+        Err(anyhow!("this player can't adjust stereo balance"))
+    }
+
+    /// Seconds moved by the seek keys; spoken-word players use longer steps.
+    fn seek_step(&self) -> i32 {
+        5
+    }
+
+    /// Whether one track is fading into the next right now.
+    fn is_crossfading(&self) -> bool {
+        false
+    }
+
+    /// Whether everything this player plays is spoken word, such as podcast
+    /// episodes or audiobooks, which get the longform seek steps whatever
+    /// their length.
+    fn is_spoken_word(&self) -> bool {
+        false
+    }
+
+    /// Tracks queued after the current one, in play order.
+    async fn get_queue(&self) -> Result<Vec<QueueEntry>> {
+        Err(anyhow!("the queue is not available from this player"))
+    }
+
+    /// Moves the queued track at `from` to `to`, both indexes into
+    /// [`get_queue`](Self::get_queue).
+    async fn move_queue_entry(&self, _from: usize, _to: usize) -> Result<()> {
+        Err(anyhow!("this player can't reorder its queue"))
+    }
+
+    /// Drops the queued track at `index` without playing it.
+    async fn remove_queue_entry(&self, _index: usize) -> Result<()> {
+        Err(anyhow!("this player can't remove tracks from its queue"))
+    }
+
+    /// Opens the player app if it isn't running. `true` when it had to be
+    /// launched; players without an app of their own do nothing.
+    async fn launch(&self) -> Result<bool> {
+        Ok(false)
+    }
+}
@@ -0,0 +1,353 @@
+// src/player/now_playing.rs
+use super::apple_music::{current_track_artwork_path, CommandRunner};
+use super::{MediaPlayer, PlaybackState, PlayerStatus, RepeatMode, Track};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+
+pub struct JxaRunner;
+
+#[async_trait]
+impl CommandRunner for JxaRunner {
+    async fn execute(&self, script: &str) -> Result<std::process::Output> {
+        let started = std::time::Instant::now();
+        let output = tokio::process::Command::new("osascript")
+            .args(["-l", "JavaScript", "-e"])
+            .arg(script)
+            .output()
+            .await;
+        crate::diagnostics::OSASCRIPT.record(started.elapsed());
+        output.map_err(|e| anyhow!(e))
+    }
+}
+
+// MediaRemote is private, so it's loaded at runtime and read through
+// MRNowPlayingRequest. Prints `null` when nothing has claimed Now Playing.
+const LOAD_MEDIA_REMOTE: &str = r#"
+ObjC.import('Foundation');
+$.NSBundle.bundleWithPath('/System/Library/PrivateFrameworks/MediaRemote.framework/').load;
+const request = $.NSClassFromString('MRNowPlayingRequest');
+const item = request.isNil() ? null : request.localNowPlayingItem;
+const info = !item || item.isNil() ? null : item.nowPlayingInfo;
+const field = (key) => info.objectForKey('kMRMediaRemoteNowPlayingInfo' + key);
+"#;
+
+const STATUS_SCRIPT: &str = r#"
+if (!info || info.isNil()) {
+    'null';
+} else {
+    const stamp = field('Timestamp');
+    JSON.stringify({
+        title: ObjC.unwrap(field('Title')),
+        artist: ObjC.unwrap(field('Artist')),
+        album: ObjC.unwrap(field('Album')),
+        duration: ObjC.unwrap(field('Duration')),
+        elapsed: ObjC.unwrap(field('ElapsedTime')),
+        rate: ObjC.unwrap(field('PlaybackRate')),
+        age: stamp.isNil() ? 0 : -stamp.timeIntervalSinceNow,
+        playing: request.localIsPlaying,
+        bundle: ObjC.unwrap(request.localNowPlayingPlayerPath.client.bundleIdentifier),
+        chapter: ObjC.unwrap(field('ChapterNumber')),
+        chapters: ObjC.unwrap(field('TotalChapterCount')),
+    });
+}
+"#;
+
+// NX_KEYTYPE_* codes posted as system-defined events, the same path the
+// keyboard's media keys take. Needs Accessibility access for the terminal.
+pub(super) const MEDIA_KEY_PLAY: u8 = 16;
+pub(super) const MEDIA_KEY_NEXT: u8 = 17;
+pub(super) const MEDIA_KEY_PREVIOUS: u8 = 18;
+
+fn media_key_script(code: u8) -> String {
+    format!(
+        r#"
+ObjC.import('AppKit');
+ObjC.import('CoreGraphics');
+for (const down of [true, false]) {{
+    const state = down ? 0xa : 0xb;
+    const event = $.NSEvent.otherEventWithTypeLocationModifierFlagsTimestampWindowNumberContextSubtypeData1Data2(
+        14, {{ x: 0, y: 0 }}, state << 8, 0, 0, null, 8, ({code} << 16) | (state << 8), -1);
+    $.CGEventPost(0, event.CGEvent);
+}}
+"#
+    )
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct NowPlayingInfo {
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    duration: Option<f64>,
+    elapsed: Option<f64>,
+    rate: Option<f64>,
+    // Seconds since MediaRemote last stamped `elapsed`
+    #[serde(default)]
+    age: f64,
+    playing: Option<bool>,
+    // App that owns the session, e.g. com.apple.podcasts
+    pub(super) bundle: Option<String>,
+    // Zero-based, as MediaRemote reports it
+    pub(super) chapter: Option<u32>,
+    pub(super) chapters: Option<u32>,
+}
+
+impl NowPlayingInfo {
+    fn is_playing(&self) -> bool {
+        self.playing.unwrap_or(self.rate.unwrap_or(0.0) > 0.0)
+    }
+
+    pub(super) fn rate(&self) -> Option<f32> {
+        self.rate.map(|rate| rate as f32)
+    }
+
+    pub(super) fn state(&self) -> PlaybackState {
+        if self.is_playing() {
+            PlaybackState::Playing
+        } else {
+            PlaybackState::Paused
+        }
+    }
+
+    pub(super) fn track(&self) -> Option<Track> {
+        let name = self.title.clone().filter(|t| !t.is_empty())?;
+        let duration = self.duration.unwrap_or(0.0).max(0.0);
+        // Apps only publish elapsed time on changes, so advance it ourselves
+        let mut position = self.elapsed.unwrap_or(0.0);
+        if self.is_playing() {
+            position += self.age.max(0.0) * self.rate.unwrap_or(1.0);
+        }
+        if duration > 0.0 {
+            position = position.min(duration);
+        }
+        Some(Track {
+            name,
+            artist: self.artist.clone().unwrap_or_default(),
+            album: self.album.clone().unwrap_or_default(),
+            duration: Duration::from_secs_f64(duration),
+            position: Duration::from_secs_f64(position.max(0.0)),
+        })
+    }
+}
+
+fn parse_status(output: &str) -> Result<Option<NowPlayingInfo>> {
+    Ok(serde_json::from_str(output)?)
+}
+
+// Shows whatever app owns the system Now Playing session (Safari, Podcasts,
+// IINA, ...). Control is limited to what media keys can do.
+pub struct NowPlayingController {
+    runner: Box<dyn CommandRunner>,
+}
+
+impl Default for NowPlayingController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NowPlayingController {
+    pub fn new() -> Self {
+        Self {
+            runner: Box::new(JxaRunner),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_runner(runner: Box<dyn CommandRunner>) -> Self {
+        Self { runner }
+    }
+
+    async fn execute_script(&self, script: &str) -> Result<String> {
+        let output = self.runner.execute(script).await?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(anyhow!(
+                "JXA script failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    pub(super) async fn status(&self) -> Result<Option<NowPlayingInfo>> {
+        let output = self
+            .execute_script(&format!("{}{}", LOAD_MEDIA_REMOTE, STATUS_SCRIPT))
+            .await?;
+        parse_status(&output)
+    }
+
+    pub(super) async fn press(&self, code: u8) -> Result<()> {
+        self.execute_script(&media_key_script(code)).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MediaPlayer for NowPlayingController {
+    async fn play(&self) -> Result<()> {
+        if self.get_playback_state().await? != PlaybackState::Playing {
+            self.press(MEDIA_KEY_PLAY).await?;
+        }
+        Ok(())
+    }
+
+    async fn pause(&self) -> Result<()> {
+        if self.get_playback_state().await? == PlaybackState::Playing {
+            self.press(MEDIA_KEY_PLAY).await?;
+        }
+        Ok(())
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        self.press(MEDIA_KEY_PLAY).await
+    }
+
+    async fn next(&self) -> Result<()> {
+        self.press(MEDIA_KEY_NEXT).await
+    }
+
+    async fn previous(&self) -> Result<()> {
+        self.press(MEDIA_KEY_PREVIOUS).await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.pause().await
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        Ok(self.status().await?.and_then(|info| info.track()))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        Ok(self
+            .status()
+            .await?
+            .map(|info| info.state())
+            .unwrap_or(PlaybackState::Stopped))
+    }
+
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        let info = self.status().await?;
+        Ok(PlayerStatus {
+            track: info.as_ref().and_then(|info| info.track()),
+            volume: None,
+            state: info
+                .map(|info| info.state())
+                .unwrap_or(PlaybackState::Stopped),
+            repeat: None,
+        })
+    }
+
+    async fn set_volume(&self, _volume: u8) -> Result<()> {
+        Err(anyhow!("volume is not available from Now Playing"))
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        Err(anyhow!("volume is not available from Now Playing"))
+    }
+
+    async fn seek(&self, _seconds: i32) -> Result<()> {
+        Err(anyhow!("seeking is not available from Now Playing"))
+    }
+
+    async fn set_position(&self, _position: Duration) -> Result<()> {
+        Err(anyhow!("seeking is not available from Now Playing"))
+    }
+
+    async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
+        Err(anyhow!("shuffle is not available from Now Playing"))
+    }
+
+    async fn set_repeat(&self, _mode: RepeatMode) -> Result<()> {
+        Err(anyhow!("repeat is not available from Now Playing"))
+    }
+
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>> {
+        let path = current_track_artwork_path(track);
+        let script = format!(
+            r#"{}
+const artwork = !info || info.isNil() ? null : field('ArtworkData');
+if (!artwork || artwork.isNil()) {{
+    '';
+}} else {{
+    artwork.writeToFileAtomically({path}, true);
+    {path};
+}}
+"#,
+            LOAD_MEDIA_REMOTE,
+            path = serde_json::to_string(&path.to_string_lossy())?
+        );
+        let exported = self.execute_script(&script).await?;
+        if exported.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(format!("file://{}", exported)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::apple_music::MockCommandRunner;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn mock_output(stdout: &str) -> std::process::Output {
+        std::process::Output {
+            status: ExitStatus::from_raw(0),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn status_advances_elapsed_time_while_playing() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute().returning(|_| {
+            Ok(mock_output(
+                r#"{"title":"Episode 12","artist":"A Podcast","duration":3600,
+                "elapsed":100,"rate":1,"age":2.5,"playing":true}"#,
+            ))
+        });
+
+        let controller = NowPlayingController::with_runner(Box::new(mock));
+        let status = controller.get_player_status().await.unwrap();
+        let track = status.track.unwrap();
+        assert_eq!(status.state, PlaybackState::Playing);
+        assert_eq!(track.name, "Episode 12");
+        assert_eq!(track.album, "");
+        assert_eq!(track.position, Duration::from_secs_f64(102.5));
+    }
+
+    #[tokio::test]
+    async fn nothing_playing_reports_stopped() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute().returning(|_| Ok(mock_output("null")));
+
+        let controller = NowPlayingController::with_runner(Box::new(mock));
+        assert!(controller.get_current_track().await.unwrap().is_none());
+        assert_eq!(
+            controller.get_playback_state().await.unwrap(),
+            PlaybackState::Stopped
+        );
+    }
+
+    #[tokio::test]
+    async fn next_posts_the_next_media_key() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains("(17 << 16)") && script.contains("CGEventPost")
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("")));
+
+        let controller = NowPlayingController::with_runner(Box::new(mock));
+        controller.next().await.unwrap();
+    }
+}
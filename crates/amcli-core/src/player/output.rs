@@ -0,0 +1,52 @@
+// src/player/output.rs
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+// The Mac's default output device, as listed in System Information. There's
+// no scriptable CoreAudio, and this takes a fraction of a second, so callers
+// should poll it sparingly.
+pub(super) async fn system_output_device() -> Result<String> {
+    if !cfg!(target_os = "macos") {
+        return Err(anyhow!("the system output device is only known on macOS"));
+    }
+    let output = tokio::process::Command::new("system_profiler")
+        .args(["SPAudioDataType", "-json"])
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "system_profiler failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    parse_system_profiler(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_system_profiler(json: &str) -> Result<String> {
+    let root: Value = serde_json::from_str(json)?;
+    root["SPAudioDataType"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|group| group["_items"].as_array().into_iter().flatten())
+        .find(|device| device["coreaudio_default_audio_output_device"] == "spaudio_yes")
+        .and_then(|device| device["_name"].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("no default output device"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_output_is_picked_from_system_profiler() {
+        let json = r#"{"SPAudioDataType": [{"_name": "Devices", "_items": [
+            {"_name": "MacBook Pro Microphone", "coreaudio_default_audio_input_device": "spaudio_yes"},
+            {"_name": "AirPods Pro", "coreaudio_default_audio_output_device": "spaudio_yes"},
+            {"_name": "MacBook Pro Speakers"}
+        ]}]}"#;
+        assert_eq!(parse_system_profiler(json).unwrap(), "AirPods Pro");
+        assert!(parse_system_profiler(r#"{"SPAudioDataType": []}"#).is_err());
+    }
+}
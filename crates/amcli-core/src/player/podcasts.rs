@@ -0,0 +1,318 @@
+// src/player/podcasts.rs
+use super::apple_music::{CommandRunner, OsascriptRunner};
+use super::now_playing::{
+    NowPlayingController, NowPlayingInfo, MEDIA_KEY_NEXT, MEDIA_KEY_PLAY, MEDIA_KEY_PREVIOUS,
+};
+use super::{MediaPlayer, PlaybackState, PlayerStatus, RepeatMode, Track};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::time::Duration;
+
+const PODCASTS_BUNDLE_ID: &str = "com.apple.podcasts";
+
+// Matches the app's own skip buttons, which are what seeking drives
+const SEEK_STEP_SECONDS: i32 = 30;
+
+// Speeds in the Controls > Playback Speed menu, which names them like "1.5×"
+const PLAYBACK_SPEEDS: &[f32] = &[0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
+// Podcasts has no scripting dictionary, so metadata comes from the Now Playing
+// session (ignored unless Podcasts owns it) and skips go through the app's
+// Controls menu, which works without bringing it to the front.
+pub struct PodcastsController {
+    runner: Box<dyn CommandRunner>,
+    now_playing: NowPlayingController,
+}
+
+impl Default for PodcastsController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PodcastsController {
+    pub fn new() -> Self {
+        Self {
+            runner: Box::new(OsascriptRunner),
+            now_playing: NowPlayingController::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_runners(
+        runner: Box<dyn CommandRunner>,
+        now_playing: Box<dyn CommandRunner>,
+    ) -> Self {
+        Self {
+            runner,
+            now_playing: NowPlayingController::with_runner(now_playing),
+        }
+    }
+
+    async fn status(&self) -> Result<Option<NowPlayingInfo>> {
+        Ok(self
+            .now_playing
+            .status()
+            .await?
+            .filter(|info| info.bundle.as_deref() == Some(PODCASTS_BUNDLE_ID)))
+    }
+
+    async fn click_control(&self, item: &str) -> Result<()> {
+        self.run(&format!(
+            r#"
+            tell application "System Events" to tell process "Podcasts"
+                click menu item "{}" of menu "Controls" of menu bar 1
+            end tell
+        "#,
+            item
+        ))
+        .await
+    }
+
+    async fn click_speed(&self, rate: f32) -> Result<()> {
+        self.run(&format!(
+            r#"
+            tell application "System Events" to tell process "Podcasts"
+                set speeds to menu 1 of menu item "Playback Speed" of menu "Controls" of menu bar 1
+                click (first menu item of speeds whose name starts with "{}")
+            end tell
+        "#,
+            speed_label(rate)
+        ))
+        .await
+    }
+
+    async fn run(&self, script: &str) -> Result<()> {
+        let output = self.runner.execute(script).await?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "AppleScript failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    // Playback keys reach whichever app owns Now Playing, so only send them
+    // while that's Podcasts.
+    async fn press(&self, code: u8) -> Result<()> {
+        if self.status().await?.is_none() {
+            return Err(anyhow!("Podcasts is not playing anything"));
+        }
+        self.now_playing.press(code).await
+    }
+}
+
+// "1.5×", or "1×" for normal speed
+fn speed_label(rate: f32) -> String {
+    format!("{}×", rate)
+}
+
+fn episode(info: &NowPlayingInfo) -> Option<Track> {
+    let mut track = info.track()?;
+    // Show the chapter in place of the album, which Podcasts fills with the show
+    // name again.
+    if let (Some(chapter), Some(chapters)) = (info.chapter, info.chapters) {
+        if chapters > 1 {
+            track.album = format!("Chapter {} of {}", chapter + 1, chapters);
+        }
+    }
+    Some(track)
+}
+
+#[async_trait]
+impl MediaPlayer for PodcastsController {
+    async fn play(&self) -> Result<()> {
+        if self.get_playback_state().await? != PlaybackState::Playing {
+            self.press(MEDIA_KEY_PLAY).await?;
+        }
+        Ok(())
+    }
+
+    async fn pause(&self) -> Result<()> {
+        if self.get_playback_state().await? == PlaybackState::Playing {
+            self.press(MEDIA_KEY_PLAY).await?;
+        }
+        Ok(())
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        self.press(MEDIA_KEY_PLAY).await
+    }
+
+    async fn next(&self) -> Result<()> {
+        self.press(MEDIA_KEY_NEXT).await
+    }
+
+    async fn previous(&self) -> Result<()> {
+        self.press(MEDIA_KEY_PREVIOUS).await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.pause().await
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        Ok(self.status().await?.as_ref().and_then(episode))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        Ok(self
+            .status()
+            .await?
+            .map(|info| info.state())
+            .unwrap_or(PlaybackState::Stopped))
+    }
+
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        let info = self.status().await?;
+        Ok(PlayerStatus {
+            track: info.as_ref().and_then(episode),
+            volume: None,
+            state: info
+                .map(|info| info.state())
+                .unwrap_or(PlaybackState::Stopped),
+            repeat: None,
+        })
+    }
+
+    async fn set_volume(&self, _volume: u8) -> Result<()> {
+        Err(anyhow!("volume is not available from Podcasts"))
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        Err(anyhow!("volume is not available from Podcasts"))
+    }
+
+    // The app skips by its own interval (30s forward, 15s back by default)
+    async fn seek(&self, seconds: i32) -> Result<()> {
+        if seconds >= 0 {
+            self.click_control("Skip Forward").await
+        } else {
+            self.click_control("Skip Back").await
+        }
+    }
+
+    async fn set_position(&self, _position: Duration) -> Result<()> {
+        Err(anyhow!("Podcasts can only skip forward or back"))
+    }
+
+    async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
+        Err(anyhow!("shuffle is not available from Podcasts"))
+    }
+
+    async fn set_repeat(&self, _mode: RepeatMode) -> Result<()> {
+        Err(anyhow!("repeat is not available from Podcasts"))
+    }
+
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>> {
+        self.now_playing.get_artwork_url(track).await
+    }
+
+    // MediaRemote reports a rate of 0 while paused, when the speed is unknown
+    async fn get_rate(&self) -> Result<f32> {
+        self.status()
+            .await?
+            .and_then(|info| info.rate())
+            .filter(|rate| *rate > 0.0)
+            .ok_or_else(|| anyhow!("Podcasts only reports its speed while playing"))
+    }
+
+    async fn set_rate(&self, rate: f32) -> Result<()> {
+        if !PLAYBACK_SPEEDS.contains(&rate) {
+            return Err(anyhow!("Podcasts doesn't offer {}", speed_label(rate)));
+        }
+        self.click_speed(rate).await
+    }
+
+    fn seek_step(&self) -> i32 {
+        SEEK_STEP_SECONDS
+    }
+
+    fn is_spoken_word(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::apple_music::MockCommandRunner;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn mock_output(stdout: &str) -> std::process::Output {
+        std::process::Output {
+            status: ExitStatus::from_raw(0),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: vec![],
+        }
+    }
+
+    fn now_playing(output: &'static str) -> Box<MockCommandRunner> {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .returning(move |_| Ok(mock_output(output)));
+        Box::new(mock)
+    }
+
+    #[tokio::test]
+    async fn reads_episode_and_chapter_from_podcasts_only() {
+        let controller = PodcastsController::with_runners(
+            Box::new(MockCommandRunner::new()),
+            now_playing(
+                r#"{"title":"Episode 12","artist":"A Podcast","album":"A Podcast",
+                "duration":3600,"elapsed":60,"playing":false,
+                "bundle":"com.apple.podcasts","chapter":2,"chapters":9}"#,
+            ),
+        );
+        let track = controller.get_current_track().await.unwrap().unwrap();
+        assert_eq!(track.artist, "A Podcast");
+        assert_eq!(track.album, "Chapter 3 of 9");
+
+        let controller = PodcastsController::with_runners(
+            Box::new(MockCommandRunner::new()),
+            now_playing(r#"{"title":"Some Video","playing":true,"bundle":"com.apple.Safari"}"#),
+        );
+        assert!(controller.get_current_track().await.unwrap().is_none());
+        assert!(controller.toggle().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn seeking_clicks_the_skip_controls() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains(r#"click menu item "Skip Back" of menu "Controls""#)
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("")));
+
+        let controller =
+            PodcastsController::with_runners(Box::new(mock), Box::new(MockCommandRunner::new()));
+        assert_eq!(controller.seek_step(), 30);
+        controller.seek(-controller.seek_step()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn speed_comes_from_the_playback_speed_menu() {
+        let mut mock = MockCommandRunner::new();
+        mock.expect_execute()
+            .with(mockall::predicate::function(|script: &str| {
+                script.contains(r#"whose name starts with "1.25×""#)
+            }))
+            .times(1)
+            .returning(|_| Ok(mock_output("")));
+
+        let controller = PodcastsController::with_runners(
+            Box::new(mock),
+            now_playing(
+                r#"{"title":"Episode 12","playing":true,"rate":1.5,"bundle":"com.apple.podcasts"}"#,
+            ),
+        );
+        assert_eq!(controller.get_rate().await.unwrap(), 1.5);
+        controller.set_rate(1.25).await.unwrap();
+        assert!(controller.set_rate(3.0).await.is_err());
+    }
+}
@@ -0,0 +1,347 @@
+// Internet radio: an endless HTTP stream handed to the decoder as it arrives,
+// with the Shoutcast/Icecast ("ICY") song titles taken out along the way.
+use anyhow::{anyhow, Context, Result};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const USER_AGENT: &str = concat!("AMCLI v", env!("CARGO_PKG_VERSION"));
+// Chunks buffered ahead of the decoder
+const BUFFERED_CHUNKS: usize = 64;
+// A station that sends nothing for this long has gone, and playback stops
+const STALL_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub struct RadioStream {
+    pub reader: StreamReader,
+    // The station's own name, from icy-name
+    pub name: Option<String>,
+    // The latest StreamTitle, usually "Artist - Title"
+    pub title: Arc<Mutex<Option<String>>>,
+}
+
+// Station links are often a .pls or .m3u playlist; the first stream in one is
+// followed, a couple of levels deep at most
+pub async fn open(url: &str) -> Result<RadioStream> {
+    let client = crate::network::stream_client(Some(USER_AGENT));
+    let mut url = url.trim().to_string();
+    let mut hops = 0;
+    let response = loop {
+        let response = client
+            .get(&url)
+            .header("Icy-MetaData", "1")
+            .send()
+            .await
+            .with_context(|| format!("Couldn't reach {}", url))?
+            .error_for_status()?;
+        if hops == 2 || !is_playlist(&url, &response) {
+            break response;
+        }
+        hops += 1;
+        let playlist = response.text().await?;
+        url = first_stream(&playlist).ok_or_else(|| anyhow!("No stream in {}", url))?;
+    };
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let metaint = header("icy-metaint").and_then(|v| v.parse().ok());
+    let name = header("icy-name");
+
+    let title = Arc::new(Mutex::new(None));
+    let (sender, receiver) = mpsc::channel(BUFFERED_CHUNKS);
+    let titles = Arc::clone(&title);
+    let mut response = response;
+    tokio::spawn(async move {
+        let mut demux = IcyDemux::new(metaint);
+        // Ends when the station hangs up or stalls, or the engine drops the reader
+        while let Ok(Ok(Some(chunk))) = tokio::time::timeout(STALL_TIMEOUT, response.chunk()).await
+        {
+            let (audio, stream_title) = demux.feed(&chunk);
+            if let Some(stream_title) = stream_title {
+                *titles.lock().unwrap_or_else(|e| e.into_inner()) =
+                    Some(stream_title).filter(|t| !t.is_empty());
+            }
+            if !audio.is_empty() && sender.send(audio).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(RadioStream {
+        reader: StreamReader {
+            receiver: Mutex::new(receiver),
+            chunk: Vec::new(),
+            offset: 0,
+            position: 0,
+        },
+        name,
+        title,
+    })
+}
+
+fn is_playlist(url: &str, response: &reqwest::Response) -> bool {
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_lowercase();
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    content_type.contains("mpegurl")
+        || content_type.contains("scpls")
+        || path.ends_with(".m3u")
+        || path.ends_with(".pls")
+}
+
+// "File1=http://…" in .pls, a bare URL line in .m3u
+fn first_stream(playlist: &str) -> Option<String> {
+    playlist.lines().find_map(|line| {
+        let line = line.trim();
+        let url = match line.split_once('=') {
+            Some((key, value)) if key.to_lowercase().starts_with("file") => value.trim(),
+            _ => line,
+        };
+        (url.starts_with("http://") || url.starts_with("https://")).then(|| url.to_string())
+    })
+}
+
+// Blocking reads for the decoder, which runs off the async runtime. Streams
+// only go forwards, so seeking just reports the position.
+pub struct StreamReader {
+    receiver: Mutex<mpsc::Receiver<Vec<u8>>>,
+    chunk: Vec<u8>,
+    offset: usize,
+    position: u64,
+}
+
+impl Read for StreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset == self.chunk.len() {
+            let next = self
+                .receiver
+                .get_mut()
+                .unwrap_or_else(|e| e.into_inner())
+                .blocking_recv();
+            match next {
+                Some(chunk) => (self.chunk, self.offset) = (chunk, 0),
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.offset);
+        buf[..n].copy_from_slice(&self.chunk[self.offset..self.offset + n]);
+        self.offset += n;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for StreamReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match pos {
+            SeekFrom::Current(0) => Ok(self.position),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "radio streams can't seek",
+            )),
+        }
+    }
+}
+
+// Every `metaint` bytes of audio, the station slips in a length byte (in
+// 16-byte blocks, usually 0) and that much metadata text.
+struct IcyDemux {
+    metaint: Option<usize>,
+    until_meta: usize,
+    meta: Vec<u8>,
+    // Metadata bytes still to come, once a length byte has been read
+    meta_left: Option<usize>,
+}
+
+impl IcyDemux {
+    fn new(metaint: Option<usize>) -> Self {
+        Self {
+            metaint: metaint.filter(|m| *m > 0),
+            until_meta: metaint.unwrap_or_default(),
+            meta: Vec::new(),
+            meta_left: None,
+        }
+    }
+
+    // The audio in `data`, and the StreamTitle of any metadata block it ended
+    fn feed(&mut self, mut data: &[u8]) -> (Vec<u8>, Option<String>) {
+        let Some(metaint) = self.metaint else {
+            return (data.to_vec(), None);
+        };
+        let mut audio = Vec::with_capacity(data.len());
+        let mut title = None;
+        while !data.is_empty() {
+            match self.meta_left {
+                None if self.until_meta > 0 => {
+                    let n = self.until_meta.min(data.len());
+                    audio.extend_from_slice(&data[..n]);
+                    data = &data[n..];
+                    self.until_meta -= n;
+                }
+                None => {
+                    let len = usize::from(data[0]) * 16;
+                    data = &data[1..];
+                    if len == 0 {
+                        self.until_meta = metaint;
+                    } else {
+                        self.meta.clear();
+                        self.meta_left = Some(len);
+                    }
+                }
+                Some(left) => {
+                    let n = left.min(data.len());
+                    self.meta.extend_from_slice(&data[..n]);
+                    data = &data[n..];
+                    if n < left {
+                        self.meta_left = Some(left - n);
+                        continue;
+                    }
+                    self.meta_left = None;
+                    self.until_meta = metaint;
+                    if let Some(stream_title) = stream_title(&String::from_utf8_lossy(&self.meta)) {
+                        title = Some(stream_title);
+                    }
+                }
+            }
+        }
+        (audio, title)
+    }
+}
+
+// `StreamTitle='Artist - Title';StreamUrl='';`, padded with NULs. Titles can
+// hold apostrophes, so the value runs to the `';` that closes it.
+fn stream_title(meta: &str) -> Option<String> {
+    let start = meta.find("StreamTitle='")? + "StreamTitle='".len();
+    let rest = meta[start..].trim_end_matches('\0');
+    let end = rest.find("';").unwrap_or(rest.trim_end_matches('\'').len());
+    Some(rest[..end].trim().to_string())
+}
+
+// "Artist - Title" into its parts; stations that send only a title get no artist
+pub fn split_title(title: &str) -> (&str, &str) {
+    match title.split_once(" - ") {
+        Some((artist, name)) => (artist.trim(), name.trim()),
+        None => ("", title.trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_blocks_are_taken_out_of_the_audio() {
+        let meta = b"StreamTitle='Nina Simone - Don't Let Me Be Misunderstood';StreamUrl='';";
+        let blocks = meta.len().div_ceil(16);
+        let mut stream = b"abcd".to_vec();
+        stream.push(blocks as u8);
+        stream.extend_from_slice(meta);
+        stream.resize(5 + blocks * 16, 0);
+        stream.extend_from_slice(b"efgh\0ijkl");
+
+        // However the bytes happen to arrive
+        for size in [1, 3, 7, stream.len()] {
+            let mut demux = IcyDemux::new(Some(4));
+            let (mut audio, mut titles) = (Vec::new(), Vec::new());
+            for chunk in stream.chunks(size) {
+                let (bytes, title) = demux.feed(chunk);
+                audio.extend(bytes);
+                titles.extend(title);
+            }
+            assert_eq!(audio, b"abcdefghijkl");
+            assert_eq!(titles, ["Nina Simone - Don't Let Me Be Misunderstood"]);
+        }
+        assert_eq!(
+            split_title("Nina Simone - Don't Let Me Be Misunderstood"),
+            ("Nina Simone", "Don't Let Me Be Misunderstood")
+        );
+        assert_eq!(split_title("Station ID"), ("", "Station ID"));
+    }
+
+    // A tiny WAV from a local "station", titled after the first 64 bytes
+    #[tokio::test]
+    async fn streams_decode_as_they_arrive_with_their_titles() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let samples: Vec<i16> = (0..400).map(|i| i * 10).collect();
+        let mut wav = Vec::new();
+        let data_len = samples.len() as u32 * 2;
+        for part in [&b"RIFF"[..], &(36 + data_len).to_le_bytes(), b"WAVEfmt "] {
+            wav.extend_from_slice(part);
+        }
+        for part in [16u32.to_le_bytes(), [1, 0, 1, 0], 8000u32.to_le_bytes()] {
+            wav.extend_from_slice(&part);
+        }
+        for part in [16000u32.to_le_bytes(), [2, 0, 16, 0]] {
+            wav.extend_from_slice(&part);
+        }
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.extend(samples.iter().flat_map(|s| s.to_le_bytes()));
+
+        let meta = b"StreamTitle='Alice Coltrane - Journey in Satchidananda';";
+        let blocks = meta.len().div_ceil(16);
+        let mut body = wav[..64].to_vec();
+        body.push(blocks as u8);
+        body.extend_from_slice(meta);
+        body.resize(65 + blocks * 16, 0);
+        for chunk in wav[64..].chunks(64) {
+            body.extend_from_slice(chunk);
+            body.push(0);
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/jazz", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            let head = "HTTP/1.0 200 OK\r\nContent-Type: audio/wav\r\n\
+                        icy-name: Spiritual Jazz\r\nicy-metaint: 64\r\n\r\n";
+            socket.write_all(head.as_bytes()).await.unwrap();
+            socket.write_all(&body).await.unwrap();
+        });
+
+        let stream = open(&url).await.unwrap();
+        assert_eq!(stream.name.as_deref(), Some("Spiritual Jazz"));
+        let reader = stream.reader;
+        let decoded: Vec<i16> =
+            tokio::task::spawn_blocking(move || rodio::Decoder::new(reader).unwrap().collect())
+                .await
+                .unwrap();
+        assert_eq!(decoded, samples);
+        assert_eq!(
+            stream.title.lock().unwrap().as_deref(),
+            Some("Alice Coltrane - Journey in Satchidananda")
+        );
+    }
+
+    #[test]
+    fn playlists_point_at_their_first_stream() {
+        let pls = "[playlist]\nNumberOfEntries=2\nFile1=https://ice.example/jazz\nTitle1=Jazz\n";
+        assert_eq!(
+            first_stream(pls).as_deref(),
+            Some("https://ice.example/jazz")
+        );
+        let m3u = "#EXTM3U\n#EXTINF:-1,Jazz\nhttp://ice.example/jazz.mp3\n";
+        assert_eq!(
+            first_stream(m3u).as_deref(),
+            Some("http://ice.example/jazz.mp3")
+        );
+        assert_eq!(first_stream("[playlist]\n"), None);
+    }
+}
@@ -0,0 +1,751 @@
+// src/player/recording.rs
+use super::subprocess::{repeat_mode_name, WireState, WireStatus, WireTrack};
+use super::{
+    MediaPlayer, Normalization, NormalizationKind, PlaybackOptions, PlaybackState, PlayerStatus,
+    QueueEntry, RepeatMode, Track,
+};
+use crate::library::LibraryItem;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+// One line of a session file: a player call, when it happened (seconds since
+// recording started), and what the player answered. Payloads use the plugin
+// wire format from PLUGINS.md.
+#[derive(Debug, Serialize, Deserialize)]
+struct Event {
+    at: f64,
+    method: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    params: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// Passes every call through to another backend and appends each response to a
+// newline-delimited JSON session file, for `--record`.
+pub struct RecordingPlayer {
+    inner: Box<dyn MediaPlayer>,
+    started: Instant,
+    file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+impl RecordingPlayer {
+    pub async fn create(inner: Box<dyn MediaPlayer>, path: &Path) -> Result<Self> {
+        let file = tokio::fs::File::create(path)
+            .await
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            inner,
+            started: Instant::now(),
+            file: tokio::sync::Mutex::new(file),
+        })
+    }
+
+    // A failed write is logged rather than returned, so recording never gets
+    // in the way of playback.
+    async fn log<T>(
+        &self,
+        method: &str,
+        params: Value,
+        result: Result<T>,
+        encode: impl FnOnce(&T) -> Value,
+    ) -> Result<T> {
+        let (result_value, error) = match &result {
+            Ok(value) => (Some(encode(value)), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+        let event = Event {
+            at: self.started.elapsed().as_secs_f64(),
+            method: method.to_string(),
+            params,
+            result: result_value,
+            error,
+        };
+        if let Ok(mut line) = serde_json::to_vec(&event) {
+            line.push(b'\n');
+            let mut file = self.file.lock().await;
+            let written = match file.write_all(&line).await {
+                Ok(()) => file.flush().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = written {
+                tracing::warn!("Failed to record {}: {}", method, e);
+            }
+        }
+        result
+    }
+
+    async fn log_command(&self, method: &str, params: Value, result: Result<()>) -> Result<()> {
+        self.log(method, params, result, |_| Value::Null).await
+    }
+}
+
+#[async_trait]
+impl MediaPlayer for RecordingPlayer {
+    async fn play(&self) -> Result<()> {
+        self.log_command("play", Value::Null, self.inner.play().await)
+            .await
+    }
+
+    async fn pause(&self) -> Result<()> {
+        self.log_command("pause", Value::Null, self.inner.pause().await)
+            .await
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        self.log_command("toggle", Value::Null, self.inner.toggle().await)
+            .await
+    }
+
+    async fn next(&self) -> Result<()> {
+        self.log_command("next", Value::Null, self.inner.next().await)
+            .await
+    }
+
+    async fn previous(&self) -> Result<()> {
+        self.log_command("previous", Value::Null, self.inner.previous().await)
+            .await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.log_command("stop", Value::Null, self.inner.stop().await)
+            .await
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        self.log(
+            "track",
+            Value::Null,
+            self.inner.get_current_track().await,
+            |track| json!(track.as_ref().map(WireTrack::from_track)),
+        )
+        .await
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        self.log(
+            "state",
+            Value::Null,
+            self.inner.get_playback_state().await,
+            |state| json!(WireState::from_state(*state)),
+        )
+        .await
+    }
+
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        self.log(
+            "status",
+            Value::Null,
+            self.inner.get_player_status().await,
+            |status| json!(WireStatus::from_status(status)),
+        )
+        .await
+    }
+
+    async fn set_volume(&self, volume: u8) -> Result<()> {
+        let result = self.inner.set_volume(volume).await;
+        self.log_command("set_volume", json!({ "volume": volume }), result)
+            .await
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        self.log(
+            "volume",
+            Value::Null,
+            self.inner.get_volume().await,
+            |volume| json!(volume),
+        )
+        .await
+    }
+
+    async fn seek(&self, seconds: i32) -> Result<()> {
+        let result = self.inner.seek(seconds).await;
+        self.log_command("seek", json!({ "seconds": seconds }), result)
+            .await
+    }
+
+    async fn set_position(&self, position: Duration) -> Result<()> {
+        let result = self.inner.set_position(position).await;
+        let params = json!({ "position": position.as_secs_f64() });
+        self.log_command("set_position", params, result).await
+    }
+
+    async fn set_shuffle(&self, enabled: bool) -> Result<()> {
+        let result = self.inner.set_shuffle(enabled).await;
+        self.log_command("set_shuffle", json!({ "enabled": enabled }), result)
+            .await
+    }
+
+    async fn set_repeat(&self, mode: RepeatMode) -> Result<()> {
+        let result = self.inner.set_repeat(mode).await;
+        let params = json!({ "mode": repeat_mode_name(mode) });
+        self.log_command("set_repeat", params, result).await
+    }
+
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>> {
+        self.log(
+            "artwork_url",
+            json!({ "track": WireTrack::from_track(track) }),
+            self.inner.get_artwork_url(track).await,
+            |url| json!(url),
+        )
+        .await
+    }
+
+    async fn play_stream(&self, url: &str, name: &str) -> Result<()> {
+        self.log_command(
+            "play_stream",
+            json!({ "url": url, "name": name }),
+            self.inner.play_stream(url, name).await,
+        )
+        .await
+    }
+
+    async fn play_files(&self, paths: &[PathBuf]) -> Result<usize> {
+        self.log(
+            "play_files",
+            json!({ "paths": paths }),
+            self.inner.play_files(paths).await,
+            |count| json!(count),
+        )
+        .await
+    }
+
+    async fn enqueue_files(&self, paths: &[PathBuf]) -> Result<usize> {
+        self.log(
+            "enqueue_files",
+            json!({ "paths": paths }),
+            self.inner.enqueue_files(paths).await,
+            |count| json!(count),
+        )
+        .await
+    }
+
+    async fn describe_file(&self, path: &Path) -> Result<Track> {
+        self.log(
+            "describe_file",
+            json!({ "path": path }),
+            self.inner.describe_file(path).await,
+            |track| json!(WireTrack::from_track(track)),
+        )
+        .await
+    }
+
+    async fn play_query(&self, query: &str) -> Result<bool> {
+        self.log(
+            "play_query",
+            json!({ "query": query }),
+            self.inner.play_query(query).await,
+            |found| json!(found),
+        )
+        .await
+    }
+
+    async fn play_album(&self, artist: &str, album: &str) -> Result<bool> {
+        self.log(
+            "play_album",
+            json!({ "artist": artist, "album": album }),
+            self.inner.play_album(artist, album).await,
+            |found| json!(found),
+        )
+        .await
+    }
+
+    async fn get_library(&self) -> Result<Vec<LibraryItem>> {
+        self.log(
+            "library",
+            Value::Null,
+            self.inner.get_library().await,
+            |items| json!(items),
+        )
+        .await
+    }
+
+    async fn get_library_track_ids(&self) -> Result<Vec<String>> {
+        self.log(
+            "library_track_ids",
+            Value::Null,
+            self.inner.get_library_track_ids().await,
+            |ids| json!(ids.len()),
+        )
+        .await
+    }
+
+    async fn get_library_tracks(&self, offset: usize, limit: usize) -> Result<Vec<LibraryItem>> {
+        self.log(
+            "library_tracks",
+            json!({ "offset": offset, "limit": limit }),
+            self.inner.get_library_tracks(offset, limit).await,
+            |tracks| json!(tracks.len()),
+        )
+        .await
+    }
+
+    async fn get_playlists(&self) -> Result<Vec<String>> {
+        self.log(
+            "playlists",
+            Value::Null,
+            self.inner.get_playlists().await,
+            |names| json!(names),
+        )
+        .await
+    }
+
+    async fn play_track_id(&self, id: &str) -> Result<bool> {
+        self.log(
+            "play_track_id",
+            json!({ "id": id }),
+            self.inner.play_track_id(id).await,
+            |found| json!(found),
+        )
+        .await
+    }
+
+    async fn get_playback_options(&self) -> Result<PlaybackOptions> {
+        self.log(
+            "playback_options",
+            Value::Null,
+            self.inner.get_playback_options().await,
+            playback_options_json,
+        )
+        .await
+    }
+
+    async fn set_playback_options(&self, options: PlaybackOptions) -> Result<()> {
+        self.log_command(
+            "set_playback_options",
+            playback_options_json(&options),
+            self.inner.set_playback_options(options).await,
+        )
+        .await
+    }
+
+    async fn get_track_id(&self, track: &Track) -> Result<Option<String>> {
+        self.log(
+            "track_id",
+            json!({ "track": WireTrack::from_track(track) }),
+            self.inner.get_track_id(track).await,
+            |id| json!(id),
+        )
+        .await
+    }
+
+    async fn get_output_device(&self) -> Result<String> {
+        self.log(
+            "output_device",
+            Value::Null,
+            self.inner.get_output_device().await,
+            |name| json!(name),
+        )
+        .await
+    }
+
+    async fn get_normalization(&self, track: &Track) -> Result<Normalization> {
+        self.log(
+            "normalization",
+            json!({ "track": WireTrack::from_track(track) }),
+            self.inner.get_normalization(track).await,
+            |normalization| {
+                json!({
+                    "kind": match normalization.kind {
+                        NormalizationKind::SoundCheck => "sound_check",
+                        NormalizationKind::ReplayGain => "replay_gain",
+                    },
+                    "enabled": normalization.enabled,
+                    "gain_db": normalization.gain_db,
+                })
+            },
+        )
+        .await
+    }
+
+    async fn get_rate(&self) -> Result<f32> {
+        self.log("rate", Value::Null, self.inner.get_rate().await, |rate| {
+            json!(rate)
+        })
+        .await
+    }
+
+    async fn set_rate(&self, rate: f32) -> Result<()> {
+        self.log_command(
+            "set_rate",
+            json!({ "rate": rate }),
+            self.inner.set_rate(rate).await,
+        )
+        .await
+    }
+
+    async fn get_autoplay(&self) -> Result<bool> {
+        self.log(
+            "autoplay",
+            Value::Null,
+            self.inner.get_autoplay().await,
+            |enabled| json!(enabled),
+        )
+        .await
+    }
+
+    async fn set_autoplay(&self, enabled: bool) -> Result<()> {
+        self.log_command(
+            "set_autoplay",
+            json!({ "enabled": enabled }),
+            self.inner.set_autoplay(enabled).await,
+        )
+        .await
+    }
+
+    async fn get_balance(&self) -> Result<f32> {
+        self.log(
+            "balance",
+            Value::Null,
+            self.inner.get_balance().await,
+            |balance| json!(balance),
+        )
+        .await
+    }
+
+    async fn set_normalization(&self, enabled: bool) -> Result<()> {
+        self.log_command(
+            "set_normalization",
+            json!({ "enabled": enabled }),
+            self.inner.set_normalization(enabled).await,
+        )
+        .await
+    }
+
+    async fn set_balance(&self, balance: f32) -> Result<()> {
+        self.log_command(
+            "set_balance",
+            json!({ "balance": balance }),
+            self.inner.set_balance(balance).await,
+        )
+        .await
+    }
+
+    async fn play_playlist(&self, name: &str) -> Result<()> {
+        self.log_command(
+            "play_playlist",
+            json!({ "name": name }),
+            self.inner.play_playlist(name).await,
+        )
+        .await
+    }
+
+    async fn play_catalog_url(&self, url: &str) -> Result<()> {
+        self.log_command(
+            "play_catalog_url",
+            json!({ "url": url }),
+            self.inner.play_catalog_url(url).await,
+        )
+        .await
+    }
+
+    async fn get_queue(&self) -> Result<Vec<QueueEntry>> {
+        self.log(
+            "queue",
+            Value::Null,
+            self.inner.get_queue().await,
+            |queue| json!(queue.iter().map(WireTrack::from_entry).collect::<Vec<_>>()),
+        )
+        .await
+    }
+
+    async fn move_queue_entry(&self, from: usize, to: usize) -> Result<()> {
+        let result = self.inner.move_queue_entry(from, to).await;
+        let params = json!({ "from": from, "to": to });
+        self.log_command("move_queue_entry", params, result).await
+    }
+
+    async fn remove_queue_entry(&self, index: usize) -> Result<()> {
+        let result = self.inner.remove_queue_entry(index).await;
+        let params = json!({ "index": index });
+        self.log_command("remove_queue_entry", params, result).await
+    }
+
+    fn seek_step(&self) -> i32 {
+        self.inner.seek_step()
+    }
+
+    fn is_crossfading(&self) -> bool {
+        self.inner.is_crossfading()
+    }
+
+    fn is_spoken_word(&self) -> bool {
+        self.inner.is_spoken_word()
+    }
+
+    async fn launch(&self) -> Result<bool> {
+        self.inner.launch().await
+    }
+}
+
+fn playback_options_json(options: &PlaybackOptions) -> Value {
+    json!({
+        "crossfade": options.crossfade,
+        "crossfade_secs": options.crossfade_secs,
+        "sound_check": options.sound_check,
+    })
+}
+
+// Elapsed replay time; stands still while paused.
+struct ReplayClock {
+    offset: f64,
+    resumed_at: Option<Instant>,
+}
+
+impl ReplayClock {
+    fn now(&self) -> f64 {
+        self.offset + self.resumed_at.map_or(0.0, |at| at.elapsed().as_secs_f64())
+    }
+}
+
+// Plays a `--record` session back in real time for `--replay`: every query
+// answers with what the recorded player said at the same point in the
+// session. Play, pause, and toggle freeze or resume the replay; other commands
+// are accepted and ignored.
+pub struct ReplayPlayer {
+    events: Vec<Event>,
+    clock: std::sync::Mutex<ReplayClock>,
+}
+
+impl ReplayPlayer {
+    pub async fn load(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid session file {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let events = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str::<Event>(line).with_context(|| format!("line {}", i + 1))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if events.is_empty() {
+            return Err(anyhow!("the session is empty"));
+        }
+        Ok(Self {
+            events,
+            clock: std::sync::Mutex::new(ReplayClock {
+                offset: 0.0,
+                resumed_at: Some(Instant::now()),
+            }),
+        })
+    }
+
+    fn now(&self) -> f64 {
+        self.clock.lock().unwrap_or_else(|e| e.into_inner()).now()
+    }
+
+    fn set_running(&self, running: bool) {
+        let mut clock = self.clock.lock().unwrap_or_else(|e| e.into_inner());
+        match (running, clock.resumed_at) {
+            (true, None) => clock.resumed_at = Some(Instant::now()),
+            (false, Some(_)) => {
+                clock.offset = clock.now();
+                clock.resumed_at = None;
+            }
+            _ => {}
+        }
+    }
+
+    // The most recent matching answer as of the current replay time, or the
+    // first one when the replay hasn't reached any yet.
+    fn latest(&self, method: &str, matches: impl Fn(&Event) -> bool) -> Option<&Event> {
+        let now = self.now();
+        let mut candidates = self
+            .events
+            .iter()
+            .filter(|event| event.method == method && matches(event));
+        let first = candidates.next()?;
+        Some(
+            std::iter::once(first)
+                .chain(candidates)
+                .take_while(|event| event.at <= now)
+                .last()
+                .unwrap_or(first),
+        )
+    }
+
+    fn reply<T: DeserializeOwned>(event: &Event) -> Result<T> {
+        match &event.error {
+            Some(error) => Err(anyhow!(error.clone())),
+            None => Ok(serde_json::from_value(
+                event.result.clone().unwrap_or(Value::Null),
+            )?),
+        }
+    }
+}
+
+#[async_trait]
+impl MediaPlayer for ReplayPlayer {
+    async fn play(&self) -> Result<()> {
+        self.set_running(true);
+        Ok(())
+    }
+
+    async fn pause(&self) -> Result<()> {
+        self.set_running(false);
+        Ok(())
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        let running = self
+            .clock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .resumed_at
+            .is_some();
+        self.set_running(!running);
+        Ok(())
+    }
+
+    async fn next(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn previous(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.set_running(false);
+        Ok(())
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        Ok(self.get_player_status().await?.track)
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        Ok(self.get_player_status().await?.state)
+    }
+
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        let event = self
+            .latest("status", |_| true)
+            .ok_or_else(|| anyhow!("No status in this session"))?;
+        Ok(Self::reply::<WireStatus>(event)?.into_status())
+    }
+
+    async fn set_volume(&self, _volume: u8) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        self.get_player_status()
+            .await?
+            .volume
+            .ok_or_else(|| anyhow!("No volume in this session"))
+    }
+
+    async fn seek(&self, _seconds: i32) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_position(&self, _position: Duration) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_repeat(&self, _mode: RepeatMode) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>> {
+        match self.latest("artwork_url", |event| {
+            event.params["track"]["name"].as_str() == Some(track.name.as_str())
+        }) {
+            Some(event) => Self::reply(event),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_queue(&self) -> Result<Vec<QueueEntry>> {
+        let event = self
+            .latest("queue", |_| true)
+            .ok_or_else(|| anyhow!("No queue in this session"))?;
+        let queue: Vec<WireTrack> = Self::reply(event)?;
+        Ok(queue.into_iter().map(WireTrack::into_entry).collect())
+    }
+
+    async fn get_library(&self) -> Result<Vec<LibraryItem>> {
+        let event = self
+            .latest("library", |_| true)
+            .ok_or_else(|| anyhow!("No library in this session"))?;
+        Self::reply(event)
+    }
+
+    async fn get_output_device(&self) -> Result<String> {
+        let event = self
+            .latest("output_device", |_| true)
+            .ok_or_else(|| anyhow!("No output device in this session"))?;
+        Self::reply(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::demo::DemoPlayer;
+
+    #[tokio::test]
+    async fn replays_a_recorded_session() {
+        let path = std::env::temp_dir().join(format!("amcli-session-{}.json", std::process::id()));
+        let recorder = RecordingPlayer::create(Box::new(DemoPlayer::new()), &path)
+            .await
+            .unwrap();
+        recorder.pause().await.unwrap();
+        let status = recorder.get_player_status().await.unwrap();
+        let track = status.track.unwrap();
+        let artwork = recorder.get_artwork_url(&track).await.unwrap();
+        recorder.get_queue().await.unwrap();
+        recorder.get_output_device().await.unwrap();
+        drop(recorder);
+
+        let replay = ReplayPlayer::load(&path).await.unwrap();
+        std::fs::remove_file(&path).ok();
+        let status = replay.get_player_status().await.unwrap();
+        assert_eq!(status.state, PlaybackState::Paused);
+        assert_eq!(status.volume, Some(70));
+        let replayed = status.track.unwrap();
+        assert_eq!(replayed.name, track.name);
+        assert_eq!(replayed.position, track.position);
+        assert_eq!(replay.get_artwork_url(&replayed).await.unwrap(), artwork);
+        assert_eq!(replay.get_queue().await.unwrap().len(), 3);
+        assert_eq!(replay.get_output_device().await.unwrap(), "Living Room");
+    }
+
+    #[test]
+    fn answers_with_the_latest_response_so_far() {
+        let session = r#"
+{"at":0.0,"method":"status","result":{"state":"playing","track":{"name":"One"}}}
+{"at":0.5,"method":"status","error":"Apple Music is not running"}
+{"at":3600.0,"method":"status","result":{"state":"playing","track":{"name":"Two"}}}
+"#;
+        let replay = ReplayPlayer::parse(session).unwrap();
+        let status = tokio_test::block_on(replay.get_player_status()).unwrap();
+        assert_eq!(status.track.unwrap().name, "One");
+
+        replay.clock.lock().unwrap().offset = 1.0;
+        let err = tokio_test::block_on(replay.get_player_status()).unwrap_err();
+        assert!(err.to_string().contains("not running"));
+
+        assert!(ReplayPlayer::parse("\n").is_err());
+    }
+}
@@ -0,0 +1,424 @@
+// src/player/shairport.rs
+use super::apple_music::current_track_artwork_path;
+use super::{MediaPlayer, PlaybackState, PlayerStatus, RepeatMode, Track};
+use crate::config::ShairportConfig;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+
+// RTP timestamps in `prgr` count frames at AirPlay's fixed sample rate
+const SAMPLE_RATE: u32 = 44_100;
+
+// How long to wait before reopening the pipe after shairport-sync closes it
+const REOPEN_DELAY: Duration = Duration::from_secs(1);
+
+const CONTROL_ERROR: &str = "AirPlay playback is controlled from the sending device";
+
+/// One `<item>` from the metadata pipe: a `core` (DAAP) or `ssnc`
+/// (shairport-sync) type, a four-character code, and the decoded payload.
+#[derive(Debug, PartialEq)]
+struct Item {
+    kind: String,
+    code: String,
+    data: Vec<u8>,
+}
+
+impl Item {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.data).into_owned()
+    }
+}
+
+// "636f7265" -> "core"
+fn decode_code(hex: &str) -> Option<String> {
+    let value = u32::from_str_radix(hex.trim(), 16).ok()?;
+    Some(String::from_utf8_lossy(&value.to_be_bytes()).into_owned())
+}
+
+fn tag<'a>(item: &'a str, name: &str) -> Option<&'a str> {
+    let open = item.find(&format!("<{}", name))?;
+    let start = open + item[open..].find('>')? + 1;
+    let end = start + item[start..].find(&format!("</{}>", name))?;
+    Some(&item[start..end])
+}
+
+// Takes the first complete `<item>...</item>` off the front of `buffer`,
+// dropping anything before it. `None` until one has fully arrived.
+fn take_item(buffer: &mut String) -> Option<Option<Item>> {
+    let start = buffer.find("<item>")?;
+    let end = start + buffer[start..].find("</item>")? + "</item>".len();
+    let raw: String = buffer.drain(..end).skip(start).collect();
+    let item = (|| {
+        let data = match tag(&raw, "data") {
+            Some(data) => {
+                let data: String = data.split_whitespace().collect();
+                base64::engine::general_purpose::STANDARD
+                    .decode(data)
+                    .ok()?
+            }
+            None => Vec::new(),
+        };
+        Some(Item {
+            kind: decode_code(tag(&raw, "type")?)?,
+            code: decode_code(tag(&raw, "code")?)?,
+            data,
+        })
+    })();
+    Some(item)
+}
+
+// What shairport-sync has told us so far. Metadata for a track arrives as a
+// bundle between `mdst` and `mden`, so it's collected in `pending` first.
+#[derive(Default)]
+struct NowPlaying {
+    track: Option<Track>,
+    pending: Option<Track>,
+    cover: Option<Vec<u8>>,
+    state: Option<PlaybackState>,
+    volume: Option<u8>,
+    // Last `prgr` position and when it arrived, extrapolated while playing
+    position: Duration,
+    reported_at: Option<Instant>,
+}
+
+impl NowPlaying {
+    fn position(&self) -> Duration {
+        let elapsed = match (self.state, self.reported_at) {
+            (Some(PlaybackState::Playing), Some(at)) => at.elapsed(),
+            _ => Duration::ZERO,
+        };
+        let position = self.position + elapsed;
+        match &self.track {
+            Some(track) if !track.duration.is_zero() => position.min(track.duration),
+            _ => position,
+        }
+    }
+
+    fn apply(&mut self, item: Item) {
+        match (item.kind.as_str(), item.code.as_str()) {
+            // The cover, if the track has one, follows in its own item
+            ("ssnc", "mdst") => {
+                self.cover = None;
+                self.pending = Some(Track {
+                    name: String::new(),
+                    artist: String::new(),
+                    album: String::new(),
+                    duration: Duration::ZERO,
+                    position: Duration::ZERO,
+                })
+            }
+            ("ssnc", "mden") => {
+                if let Some(next) = self.pending.take() {
+                    self.track = Some(next);
+                }
+            }
+            ("core", code) => {
+                let Some(pending) = self.pending.as_mut() else {
+                    return;
+                };
+                match code {
+                    "minm" => pending.name = item.text(),
+                    "asar" => pending.artist = item.text(),
+                    "asal" => pending.album = item.text(),
+                    "astm" => {
+                        if let Ok(bytes) = <[u8; 4]>::try_from(item.data.as_slice()) {
+                            pending.duration =
+                                Duration::from_millis(u64::from(u32::from_be_bytes(bytes)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            ("ssnc", "PICT") => self.cover = (!item.data.is_empty()).then_some(item.data),
+            // "start/current/end" in RTP frames, which wrap around
+            ("ssnc", "prgr") => {
+                let text = item.text();
+                let frames: Vec<u32> = text
+                    .trim()
+                    .split('/')
+                    .filter_map(|n| n.parse().ok())
+                    .collect();
+                if let [start, current, end] = frames[..] {
+                    let seconds = |frames: u32| {
+                        Duration::from_secs_f64(f64::from(frames) / f64::from(SAMPLE_RATE))
+                    };
+                    self.position = seconds(current.wrapping_sub(start));
+                    self.reported_at = Some(Instant::now());
+                    if let Some(track) = self.track.as_mut() {
+                        track.duration = seconds(end.wrapping_sub(start));
+                    }
+                }
+            }
+            // "airplay,volume,lowest,highest"; AirPlay's own scale runs from
+            // -30 to 0, with -144 for muted
+            ("ssnc", "pvol") => {
+                let text = item.text();
+                if let Some(Ok(airplay)) = text.split(',').next().map(|v| v.trim().parse::<f64>()) {
+                    let volume = ((airplay.clamp(-30.0, 0.0) + 30.0) / 30.0 * 100.0).round();
+                    self.volume = Some(volume as u8);
+                }
+            }
+            ("ssnc", "pbeg" | "prsm" | "pres") => self.resume(PlaybackState::Playing),
+            ("ssnc", "pfls" | "paus") => self.resume(PlaybackState::Paused),
+            ("ssnc", "pend" | "aend") => {
+                self.state = Some(PlaybackState::Stopped);
+                self.track = None;
+                self.cover = None;
+            }
+            _ => {}
+        }
+    }
+
+    // Keeps the extrapolated position where it was across a state change
+    fn resume(&mut self, state: PlaybackState) {
+        self.position = self.position();
+        self.reported_at = Some(Instant::now());
+        self.state = Some(state);
+    }
+}
+
+/// Shows whatever is being AirPlayed to shairport-sync on this machine, read
+/// from its metadata pipe. Playback is controlled from the sender, so this
+/// backend only displays.
+pub struct ShairportPlayer {
+    now_playing: Arc<Mutex<NowPlaying>>,
+}
+
+impl ShairportPlayer {
+    pub fn open(config: &ShairportConfig) -> Result<Self> {
+        let path = crate::playlist::expand_home(&config.metadata_pipe.to_string_lossy());
+        if !path.exists() {
+            return Err(anyhow!(
+                "No shairport-sync metadata pipe at {}; enable `metadata` in shairport-sync.conf",
+                path.display()
+            ));
+        }
+        let now_playing = Arc::new(Mutex::new(NowPlaying::default()));
+        tokio::spawn(read_pipe(path, Arc::downgrade(&now_playing)));
+        Ok(Self { now_playing })
+    }
+
+    fn now_playing(&self) -> std::sync::MutexGuard<'_, NowPlaying> {
+        self.now_playing.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Runs until the player is dropped. The pipe hits EOF whenever shairport-sync
+// restarts, so it's reopened rather than given up on.
+async fn read_pipe(path: PathBuf, now_playing: std::sync::Weak<Mutex<NowPlaying>>) {
+    let mut buffer = String::new();
+    let mut chunk = vec![0u8; 16 * 1024];
+    loop {
+        let mut pipe = match tokio::fs::File::open(&path).await {
+            Ok(pipe) => pipe,
+            Err(e) => {
+                tracing::debug!("Couldn't open {}: {}", path.display(), e);
+                if now_playing.strong_count() == 0 {
+                    return;
+                }
+                tokio::time::sleep(REOPEN_DELAY).await;
+                continue;
+            }
+        };
+        buffer.clear();
+        while let Ok(read @ 1..) = pipe.read(&mut chunk).await {
+            let Some(now_playing) = now_playing.upgrade() else {
+                return;
+            };
+            buffer.push_str(&String::from_utf8_lossy(&chunk[..read]));
+            let mut now_playing = now_playing.lock().unwrap_or_else(|e| e.into_inner());
+            while let Some(item) = take_item(&mut buffer) {
+                if let Some(item) = item {
+                    now_playing.apply(item);
+                }
+            }
+        }
+        if now_playing.strong_count() == 0 {
+            return;
+        }
+        tokio::time::sleep(REOPEN_DELAY).await;
+    }
+}
+
+async fn write_cover(path: &Path, cover: &[u8]) -> Result<String> {
+    tokio::fs::write(path, cover).await?;
+    Ok(format!("file://{}", path.display()))
+}
+
+#[async_trait]
+impl MediaPlayer for ShairportPlayer {
+    async fn play(&self) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn pause(&self) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn next(&self) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn previous(&self) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn stop(&self) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        let now_playing = self.now_playing();
+        Ok(now_playing.track.as_ref().map(|track| Track {
+            position: now_playing.position(),
+            ..track.clone()
+        }))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        Ok(self.now_playing().state.unwrap_or(PlaybackState::Stopped))
+    }
+
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        let volume = self.now_playing().volume;
+        Ok(PlayerStatus {
+            track: self.get_current_track().await?,
+            volume,
+            state: self.get_playback_state().await?,
+            repeat: None,
+        })
+    }
+
+    async fn set_volume(&self, _volume: u8) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        self.now_playing()
+            .volume
+            .ok_or_else(|| anyhow!("the AirPlay sender hasn't reported a volume"))
+    }
+
+    async fn seek(&self, _seconds: i32) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn set_position(&self, _position: Duration) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn set_repeat(&self, _mode: RepeatMode) -> Result<()> {
+        Err(anyhow!(CONTROL_ERROR))
+    }
+
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>> {
+        let Some(cover) = self.now_playing().cover.clone() else {
+            return Ok(None);
+        };
+        Ok(Some(
+            write_cover(&current_track_artwork_path(track), &cover).await?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(kind: &str, code: &str, data: &[u8]) -> String {
+        let hex = |s: &str| {
+            format!(
+                "{:08x}",
+                u32::from_be_bytes(s.as_bytes().try_into().unwrap())
+            )
+        };
+        let data = if data.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n<data encoding=\"base64\">\n{}</data>",
+                base64::engine::general_purpose::STANDARD.encode(data)
+            )
+        };
+        format!(
+            "<item><type>{}</type><code>{}</code><length>0</length>{}</item>\n",
+            hex(kind),
+            hex(code),
+            data
+        )
+    }
+
+    fn feed(now_playing: &mut NowPlaying, buffer: &mut String, text: &str) {
+        buffer.push_str(text);
+        while let Some(item) = take_item(buffer) {
+            now_playing.apply(item.unwrap());
+        }
+    }
+
+    #[test]
+    fn items_are_parsed_once_complete() {
+        let mut buffer = item("core", "minm", b"Teardrop");
+        buffer.truncate(buffer.len() - 10);
+        assert_eq!(take_item(&mut buffer.clone()), None);
+
+        let mut buffer = format!("junk{}", item("core", "minm", b"Teardrop"));
+        assert_eq!(
+            take_item(&mut buffer),
+            Some(Some(Item {
+                kind: "core".into(),
+                code: "minm".into(),
+                data: b"Teardrop".to_vec(),
+            }))
+        );
+        assert_eq!(buffer.trim(), "");
+    }
+
+    #[test]
+    fn a_metadata_bundle_becomes_the_track() {
+        let mut now_playing = NowPlaying::default();
+        let mut buffer = String::new();
+        let bundle = [
+            item("ssnc", "pbeg", b""),
+            item("ssnc", "mdst", b""),
+            item("core", "minm", b"Teardrop"),
+            item("core", "asar", b"Massive Attack"),
+            item("core", "asal", b"Mezzanine"),
+            item("ssnc", "mden", b""),
+            item("ssnc", "prgr", b"1000/442000/13231000"),
+            item("ssnc", "pvol", b"-15.00,-20.5,-96.30,0.00"),
+        ]
+        .concat();
+        // Split mid-item, as reads from the pipe can be
+        let (first, second) = bundle.split_at(bundle.len() / 2);
+        feed(&mut now_playing, &mut buffer, first);
+        assert!(now_playing.track.is_none());
+        feed(&mut now_playing, &mut buffer, second);
+
+        let track = now_playing.track.clone().unwrap();
+        assert_eq!(track.name, "Teardrop");
+        assert_eq!(track.artist, "Massive Attack");
+        assert_eq!(track.album, "Mezzanine");
+        assert_eq!(track.duration, Duration::from_secs(300));
+        assert_eq!(now_playing.state, Some(PlaybackState::Playing));
+        assert!(now_playing.position() >= Duration::from_secs(10));
+        assert_eq!(now_playing.volume, Some(50));
+
+        feed(&mut now_playing, &mut buffer, &item("ssnc", "pfls", b""));
+        assert_eq!(now_playing.state, Some(PlaybackState::Paused));
+        feed(&mut now_playing, &mut buffer, &item("ssnc", "pend", b""));
+        assert_eq!(now_playing.state, Some(PlaybackState::Stopped));
+        assert!(now_playing.track.is_none());
+    }
+}
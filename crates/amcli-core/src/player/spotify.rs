@@ -0,0 +1,315 @@
+// src/player/spotify.rs
+use super::{MediaPlayer, PlaybackState, RepeatMode, Track};
+use crate::config::SpotifyConfig;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use librespot::connect::{ConnectConfig, Spirc};
+use librespot::core::{
+    authentication::Credentials, cache::Cache, config::SessionConfig, session::Session,
+};
+use librespot::metadata::audio::{AudioItem, UniqueFields};
+use librespot::oauth::OAuthClientBuilder;
+use librespot::playback::{
+    audio_backend,
+    config::{AudioFormat, PlayerConfig},
+    mixer::{self, Mixer, MixerConfig},
+    player::{Player, PlayerEvent},
+};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const OAUTH_REDIRECT_URI: &str = "http://127.0.0.1:8898/login";
+const OAUTH_SCOPES: &[&str] = &["streaming"];
+
+// librespot reports volume on a u16 scale
+const VOLUME_MAX: u32 = u16::MAX as u32;
+
+// Reusable credentials live next to config.toml, not in the cache dir, so
+// clearing caches doesn't sign the device out.
+fn credentials_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("amcli")
+        .join("spotify")
+}
+
+fn open_cache() -> Result<Cache> {
+    let dir = credentials_dir();
+    Cache::new(Some(&dir), Some(&dir), None, None).context("Failed to open Spotify cache")
+}
+
+// Browser OAuth run from `amcli spotify-login`, outside the TUI, since it needs
+// the terminal for its prompt.
+pub async fn login() -> Result<String> {
+    let session_config = SessionConfig::default();
+    let token = OAuthClientBuilder::new(
+        &session_config.client_id,
+        OAUTH_REDIRECT_URI,
+        OAUTH_SCOPES.to_vec(),
+    )
+    .open_in_browser()
+    .build()?
+    .get_access_token_async()
+    .await?;
+
+    let session = Session::new(session_config, Some(open_cache()?));
+    session
+        .connect(Credentials::with_access_token(token.access_token), true)
+        .await?;
+    Ok(session.username())
+}
+
+#[derive(Default)]
+struct NowPlaying {
+    track: Option<Track>,
+    cover_url: Option<String>,
+    state: Option<PlaybackState>,
+    // Last position librespot reported and when, extrapolated while playing
+    position: Duration,
+    reported_at: Option<Instant>,
+}
+
+impl NowPlaying {
+    fn position(&self) -> Duration {
+        let elapsed = match (self.state, self.reported_at) {
+            (Some(PlaybackState::Playing), Some(at)) => at.elapsed(),
+            _ => Duration::ZERO,
+        };
+        let position = self.position + elapsed;
+        match &self.track {
+            Some(track) if !track.duration.is_zero() => position.min(track.duration),
+            _ => position,
+        }
+    }
+
+    fn report(&mut self, state: PlaybackState, position_ms: u32) {
+        self.state = Some(state);
+        self.position = Duration::from_millis(u64::from(position_ms));
+        self.reported_at = Some(Instant::now());
+    }
+
+    fn apply(&mut self, event: PlayerEvent) {
+        match event {
+            PlayerEvent::TrackChanged { audio_item } => {
+                self.cover_url = largest_cover(&audio_item);
+                self.track = Some(track_from_item(&audio_item));
+                self.position = Duration::ZERO;
+                self.reported_at = Some(Instant::now());
+            }
+            PlayerEvent::Playing { position_ms, .. } => {
+                self.report(PlaybackState::Playing, position_ms)
+            }
+            PlayerEvent::Paused { position_ms, .. } => {
+                self.report(PlaybackState::Paused, position_ms)
+            }
+            PlayerEvent::Seeked { position_ms, .. }
+            | PlayerEvent::PositionCorrection { position_ms, .. }
+            | PlayerEvent::PositionChanged { position_ms, .. } => {
+                let state = self.state.unwrap_or(PlaybackState::Playing);
+                self.report(state, position_ms);
+            }
+            PlayerEvent::Stopped { .. } => {
+                self.state = Some(PlaybackState::Stopped);
+                self.track = None;
+                self.cover_url = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn track_from_item(item: &AudioItem) -> Track {
+    let (artist, album) = match &item.unique_fields {
+        UniqueFields::Track { artists, album, .. } => (
+            artists
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            album.clone(),
+        ),
+        UniqueFields::Local { artists, album, .. } => (
+            artists.clone().unwrap_or_default(),
+            album.clone().unwrap_or_default(),
+        ),
+        UniqueFields::Episode { show_name, .. } => (show_name.clone(), show_name.clone()),
+    };
+    Track {
+        name: item.name.clone(),
+        artist,
+        album,
+        duration: Duration::from_millis(u64::from(item.duration_ms)),
+        position: Duration::ZERO,
+    }
+}
+
+fn largest_cover(item: &AudioItem) -> Option<String> {
+    item.covers
+        .iter()
+        .max_by_key(|c| c.width)
+        .map(|c| c.url.clone())
+}
+
+// Registers amcli as a Spotify Connect device; playback happens in-process and
+// can also be driven from any other Spotify client.
+pub struct SpotifyPlayer {
+    spirc: Spirc,
+    mixer: Arc<dyn Mixer>,
+    now_playing: Arc<Mutex<NowPlaying>>,
+}
+
+impl SpotifyPlayer {
+    pub async fn connect(config: &SpotifyConfig) -> Result<Self> {
+        let cache = open_cache()?;
+        let credentials = cache
+            .credentials()
+            .ok_or_else(|| anyhow!("Not signed in to Spotify; run `amcli spotify-login` first"))?;
+        let session = Session::new(SessionConfig::default(), Some(cache));
+
+        let sink_builder =
+            audio_backend::find(None).ok_or_else(|| anyhow!("No audio backend available"))?;
+        let mixer = mixer::find(None).ok_or_else(|| anyhow!("No mixer available"))?(
+            MixerConfig::default(),
+        )?;
+        let player = Player::new(
+            PlayerConfig::default(),
+            session.clone(),
+            mixer.get_soft_volume(),
+            move || sink_builder(None, AudioFormat::default()),
+        );
+
+        let now_playing = Arc::new(Mutex::new(NowPlaying::default()));
+        let mut events = player.get_player_event_channel();
+        let events_state = Arc::clone(&now_playing);
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                events_state
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .apply(event);
+            }
+        });
+
+        let connect_config = ConnectConfig {
+            name: config.device_name.clone(),
+            ..ConnectConfig::default()
+        };
+        let (spirc, spirc_task) =
+            Spirc::new(connect_config, session, credentials, player, mixer.clone()).await?;
+        tokio::spawn(spirc_task);
+        spirc.activate()?;
+
+        Ok(Self {
+            spirc,
+            mixer,
+            now_playing,
+        })
+    }
+
+    fn now_playing(&self) -> std::sync::MutexGuard<'_, NowPlaying> {
+        self.now_playing.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl MediaPlayer for SpotifyPlayer {
+    async fn play(&self) -> Result<()> {
+        Ok(self.spirc.play()?)
+    }
+
+    async fn pause(&self) -> Result<()> {
+        Ok(self.spirc.pause()?)
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        Ok(self.spirc.play_pause()?)
+    }
+
+    async fn next(&self) -> Result<()> {
+        Ok(self.spirc.next()?)
+    }
+
+    async fn previous(&self) -> Result<()> {
+        Ok(self.spirc.prev()?)
+    }
+
+    async fn stop(&self) -> Result<()> {
+        Ok(self.spirc.pause()?)
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        let now_playing = self.now_playing();
+        Ok(now_playing.track.as_ref().map(|track| Track {
+            position: now_playing.position(),
+            ..track.clone()
+        }))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        Ok(self.now_playing().state.unwrap_or(PlaybackState::Stopped))
+    }
+
+    async fn set_volume(&self, volume: u8) -> Result<()> {
+        let volume = u32::from(volume.min(100)) * VOLUME_MAX / 100;
+        Ok(self.spirc.set_volume(volume as u16)?)
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        let volume = u32::from(self.mixer.volume()) * 100;
+        Ok(((volume + VOLUME_MAX / 2) / VOLUME_MAX) as u8)
+    }
+
+    async fn seek(&self, seconds: i32) -> Result<()> {
+        let position = self.now_playing().position().as_secs_f64() + f64::from(seconds);
+        self.set_position(Duration::from_secs_f64(position.max(0.0)))
+            .await
+    }
+
+    async fn set_position(&self, position: Duration) -> Result<()> {
+        let position_ms = u32::try_from(position.as_millis()).unwrap_or(u32::MAX);
+        Ok(self.spirc.set_position_ms(position_ms)?)
+    }
+
+    async fn set_shuffle(&self, enabled: bool) -> Result<()> {
+        Ok(self.spirc.shuffle(enabled)?)
+    }
+
+    async fn set_repeat(&self, mode: RepeatMode) -> Result<()> {
+        self.spirc.repeat(mode == RepeatMode::All)?;
+        Ok(self.spirc.repeat_track(mode == RepeatMode::One)?)
+    }
+
+    async fn get_artwork_url(&self, _track: &Track) -> Result<Option<String>> {
+        Ok(self.now_playing().cover_url.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_extrapolates_only_while_playing() {
+        let mut now_playing = NowPlaying {
+            track: Some(Track {
+                name: "So What".into(),
+                artist: "Miles Davis".into(),
+                album: "Kind of Blue".into(),
+                duration: Duration::from_secs(562),
+                position: Duration::ZERO,
+            }),
+            ..NowPlaying::default()
+        };
+
+        now_playing.report(PlaybackState::Paused, 30_000);
+        now_playing.reported_at = Some(Instant::now() - Duration::from_secs(5));
+        assert_eq!(now_playing.position(), Duration::from_secs(30));
+
+        now_playing.state = Some(PlaybackState::Playing);
+        assert!(now_playing.position() >= Duration::from_secs(35));
+
+        now_playing.position = Duration::from_secs(600);
+        assert_eq!(now_playing.position(), Duration::from_secs(562));
+    }
+}
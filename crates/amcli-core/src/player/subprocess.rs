@@ -0,0 +1,478 @@
+// src/player/subprocess.rs
+use super::{MediaPlayer, PlaybackState, PlayerStatus, QueueEntry, RepeatMode, Track};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+pub const PLUGIN_PREFIX: &str = "amcli-player-";
+const PROTOCOL_VERSION: u32 = 1;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Executables named `amcli-player-<name>` on PATH, by name.
+pub fn discover_plugins() -> Vec<(String, PathBuf)> {
+    let mut plugins: Vec<(String, PathBuf)> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix(PLUGIN_PREFIX)?
+                .to_string();
+            is_executable(&entry.path()).then(|| (name, entry.path()))
+        })
+        .collect();
+    // Earlier PATH entries win, like a shell lookup
+    let mut seen = std::collections::HashSet::new();
+    plugins.retain(|(name, _)| seen.insert(name.clone()));
+    plugins.sort();
+    plugins
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+// Wire format for tracks: plain strings and seconds as floats. Also used for
+// recorded sessions.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(super) struct WireTrack {
+    name: String,
+    #[serde(default)]
+    artist: String,
+    #[serde(default)]
+    album: String,
+    #[serde(default)]
+    duration: f64,
+    #[serde(default)]
+    position: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<PathBuf>,
+}
+
+impl WireTrack {
+    pub(super) fn from_track(track: &Track) -> Self {
+        Self {
+            name: track.name.clone(),
+            artist: track.artist.clone(),
+            album: track.album.clone(),
+            duration: track.duration.as_secs_f64(),
+            position: track.position.as_secs_f64(),
+            location: None,
+        }
+    }
+
+    pub(super) fn from_entry(entry: &QueueEntry) -> Self {
+        Self {
+            location: entry.location.clone(),
+            ..Self::from_track(&entry.track)
+        }
+    }
+
+    pub(super) fn into_entry(self) -> QueueEntry {
+        QueueEntry {
+            location: self.location.clone(),
+            track: self.into_track(),
+        }
+    }
+
+    pub(super) fn into_track(self) -> Track {
+        Track {
+            name: self.name,
+            artist: self.artist,
+            album: self.album,
+            duration: Duration::from_secs_f64(self.duration.max(0.0)),
+            position: Duration::from_secs_f64(self.position.max(0.0)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum WireState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl WireState {
+    pub(super) fn from_state(state: PlaybackState) -> Self {
+        match state {
+            PlaybackState::Playing => Self::Playing,
+            PlaybackState::Paused => Self::Paused,
+            PlaybackState::Stopped => Self::Stopped,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum WireRepeat {
+    Off,
+    One,
+    All,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct WireStatus {
+    state: WireState,
+    #[serde(default)]
+    volume: Option<u8>,
+    #[serde(default)]
+    track: Option<WireTrack>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repeat: Option<WireRepeat>,
+}
+
+impl WireStatus {
+    pub(super) fn from_status(status: &PlayerStatus) -> Self {
+        Self {
+            state: WireState::from_state(status.state),
+            volume: status.volume,
+            track: status.track.as_ref().map(WireTrack::from_track),
+            repeat: status.repeat.map(|mode| match mode {
+                RepeatMode::Off => WireRepeat::Off,
+                RepeatMode::One => WireRepeat::One,
+                RepeatMode::All => WireRepeat::All,
+            }),
+        }
+    }
+
+    pub(super) fn into_status(self) -> PlayerStatus {
+        PlayerStatus {
+            track: self.track.map(WireTrack::into_track),
+            volume: self.volume,
+            state: playback_state(self.state),
+            repeat: self.repeat.map(|mode| match mode {
+                WireRepeat::Off => RepeatMode::Off,
+                WireRepeat::One => RepeatMode::One,
+                WireRepeat::All => RepeatMode::All,
+            }),
+        }
+    }
+}
+
+pub(super) fn repeat_mode_name(mode: RepeatMode) -> &'static str {
+    match mode {
+        RepeatMode::Off => "off",
+        RepeatMode::One => "one",
+        RepeatMode::All => "all",
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Hello {
+    protocol: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    id: u64,
+    #[serde(default)]
+    result: Value,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+struct Channel {
+    // Held so the plugin is killed when the player is dropped
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    next_id: u64,
+}
+
+impl Channel {
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let mut line = serde_json::to_string(&json!({
+            "id": id,
+            "method": method,
+            "params": params,
+        }))?;
+        line.push('\n');
+        self.stdin.write_all(line.as_bytes()).await?;
+        self.stdin.flush().await?;
+
+        // Responses to requests that timed out earlier may still be queued
+        loop {
+            let line = self
+                .stdout
+                .next_line()
+                .await?
+                .ok_or_else(|| anyhow!("plugin exited"))?;
+            let response: Response = serde_json::from_str(&line)
+                .with_context(|| format!("Malformed plugin response: {}", line))?;
+            if response.id != id {
+                continue;
+            }
+            return match response.error {
+                Some(error) => Err(anyhow!(error)),
+                None => Ok(response.result),
+            };
+        }
+    }
+}
+
+// Drives a third-party backend over newline-delimited JSON on stdin/stdout.
+// See PLUGINS.md for the protocol.
+pub struct SubprocessPlayer {
+    name: String,
+    channel: Mutex<Channel>,
+}
+
+impl SubprocessPlayer {
+    pub async fn spawn(name: &str) -> Result<Self> {
+        let path = discover_plugins()
+            .into_iter()
+            .find(|(plugin, _)| plugin == name)
+            .map(|(_, path)| path)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No {}{} executable on PATH (found: {})",
+                    PLUGIN_PREFIX,
+                    name,
+                    plugin_names()
+                )
+            })?;
+        Self::spawn_path(name, &path).await
+    }
+
+    async fn spawn_path(name: &str, path: &Path) -> Result<Self> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // The TUI owns the terminal; plugins should log to a file instead
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", path.display()))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("no plugin stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("no plugin stdout"))?;
+
+        let player = Self {
+            name: name.to_string(),
+            channel: Mutex::new(Channel {
+                _child: child,
+                stdin,
+                stdout: BufReader::new(stdout).lines(),
+                next_id: 0,
+            }),
+        };
+        let hello: Hello = player
+            .call("hello", json!({ "protocol": PROTOCOL_VERSION }))
+            .await?;
+        if hello.protocol != PROTOCOL_VERSION {
+            return Err(anyhow!(
+                "Plugin {} speaks protocol {}, amcli speaks {}",
+                name,
+                hello.protocol,
+                PROTOCOL_VERSION
+            ));
+        }
+        Ok(player)
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let mut channel = self.channel.lock().await;
+        let result = tokio::time::timeout(REQUEST_TIMEOUT, channel.request(method, params))
+            .await
+            .map_err(|_| anyhow!("Plugin {} timed out on {}", self.name, method))?
+            .with_context(|| format!("Plugin {} failed on {}", self.name, method))?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    async fn send(&self, method: &str, params: Value) -> Result<()> {
+        self.call::<Value>(method, params).await?;
+        Ok(())
+    }
+
+    async fn status(&self) -> Result<WireStatus> {
+        self.call("status", json!({})).await
+    }
+}
+
+fn plugin_names() -> String {
+    let names: Vec<String> = discover_plugins()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+fn playback_state(state: WireState) -> PlaybackState {
+    match state {
+        WireState::Playing => PlaybackState::Playing,
+        WireState::Paused => PlaybackState::Paused,
+        WireState::Stopped => PlaybackState::Stopped,
+    }
+}
+
+#[async_trait]
+impl MediaPlayer for SubprocessPlayer {
+    async fn play(&self) -> Result<()> {
+        self.send("play", json!({})).await
+    }
+
+    async fn pause(&self) -> Result<()> {
+        self.send("pause", json!({})).await
+    }
+
+    async fn toggle(&self) -> Result<()> {
+        self.send("toggle", json!({})).await
+    }
+
+    async fn next(&self) -> Result<()> {
+        self.send("next", json!({})).await
+    }
+
+    async fn previous(&self) -> Result<()> {
+        self.send("previous", json!({})).await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.send("stop", json!({})).await
+    }
+
+    async fn get_current_track(&self) -> Result<Option<Track>> {
+        Ok(self.status().await?.track.map(WireTrack::into_track))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState> {
+        Ok(playback_state(self.status().await?.state))
+    }
+
+    async fn get_player_status(&self) -> Result<PlayerStatus> {
+        Ok(self.status().await?.into_status())
+    }
+
+    async fn set_volume(&self, volume: u8) -> Result<()> {
+        self.send("set_volume", json!({ "volume": volume })).await
+    }
+
+    async fn get_volume(&self) -> Result<u8> {
+        self.status()
+            .await?
+            .volume
+            .ok_or_else(|| anyhow!("volume is not available from this plugin"))
+    }
+
+    async fn seek(&self, seconds: i32) -> Result<()> {
+        self.send("seek", json!({ "seconds": seconds })).await
+    }
+
+    async fn set_position(&self, position: Duration) -> Result<()> {
+        self.send(
+            "set_position",
+            json!({ "position": position.as_secs_f64() }),
+        )
+        .await
+    }
+
+    async fn set_shuffle(&self, enabled: bool) -> Result<()> {
+        self.send("set_shuffle", json!({ "enabled": enabled }))
+            .await
+    }
+
+    async fn set_repeat(&self, mode: RepeatMode) -> Result<()> {
+        self.send("set_repeat", json!({ "mode": repeat_mode_name(mode) }))
+            .await
+    }
+
+    async fn get_artwork_url(&self, track: &Track) -> Result<Option<String>> {
+        self.call(
+            "artwork_url",
+            json!({ "track": WireTrack::from_track(track) }),
+        )
+        .await
+    }
+
+    async fn play_query(&self, query: &str) -> Result<bool> {
+        self.call("play_query", json!({ "query": query })).await
+    }
+
+    async fn get_rate(&self) -> Result<f32> {
+        self.call("rate", json!({})).await
+    }
+
+    async fn set_rate(&self, rate: f32) -> Result<()> {
+        self.send("set_rate", json!({ "rate": rate })).await
+    }
+
+    async fn get_queue(&self) -> Result<Vec<QueueEntry>> {
+        let queue: Vec<WireTrack> = self.call("queue", json!({})).await?;
+        Ok(queue.into_iter().map(WireTrack::into_entry).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    // Answers hello, reports a fixed status, and rejects everything else.
+    const FAKE_PLUGIN: &str = r#"#!/bin/sh
+while IFS= read -r line; do
+    id=$(printf '%s' "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+    case "$line" in
+        *'"method":"hello"'*) printf '{"id":%s,"result":{"protocol":1}}\n' "$id" ;;
+        *'"method":"status"'*) printf '{"id":%s,"result":{"state":"playing","volume":40,"track":{"name":"So What","artist":"Miles Davis","duration":562.0,"position":12.5},"repeat":"all"}}\n' "$id" ;;
+        *) printf '{"id":%s,"error":"unsupported"}\n' "$id" ;;
+    esac
+done
+"#;
+
+    fn write_plugin(dir: &Path) -> PathBuf {
+        let path = dir.join(format!("{}fake", PLUGIN_PREFIX));
+        std::fs::write(&path, FAKE_PLUGIN).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn talks_to_a_plugin_over_stdio() {
+        let dir = std::env::temp_dir().join(format!("amcli-plugin-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = write_plugin(&dir);
+
+        let player = SubprocessPlayer::spawn_path("fake", &path).await.unwrap();
+        let status = player.get_player_status().await.unwrap();
+        assert_eq!(status.state, PlaybackState::Playing);
+        assert_eq!(status.volume, Some(40));
+        assert_eq!(status.repeat, Some(RepeatMode::All));
+        let track = status.track.unwrap();
+        assert_eq!(track.artist, "Miles Davis");
+        assert_eq!(track.position, Duration::from_secs_f64(12.5));
+
+        let err = player.next().await.unwrap_err();
+        assert!(format!("{:#}", err).contains("unsupported"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
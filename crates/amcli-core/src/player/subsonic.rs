@@ -0,0 +1,317 @@
+// src/player/subsonic.rs
+use super::internal::{AudioSource, Library};
+use super::{QueueEntry, Track};
+use crate::config::SubsonicConfig;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use rodio::Decoder;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::sync::RwLock;
+use std::time::Duration;
+
+const API_VERSION: &str = "1.16.1";
+const CLIENT_NAME: &str = "amcli";
+const STARTING_QUEUE_SIZE: &str = "100";
+const SEARCH_RESULT_SIZE: &str = "100";
+const COVER_ART_SIZE: &str = "600";
+const STREAM_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Deserialize)]
+struct Song {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    artist: String,
+    #[serde(default)]
+    album: String,
+    #[serde(default)]
+    duration: u64,
+    #[serde(rename = "coverArt")]
+    cover_art: Option<String>,
+}
+
+impl Song {
+    fn track(&self) -> Track {
+        Track {
+            name: self.title.clone(),
+            artist: self.artist.clone(),
+            album: self.album.clone(),
+            duration: Duration::from_secs(self.duration),
+            position: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    #[serde(rename = "subsonic-response")]
+    response: ResponseBody<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseBody<T> {
+    status: String,
+    error: Option<ApiError>,
+    #[serde(flatten)]
+    body: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    #[serde(default)]
+    code: i32,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SongList {
+    #[serde(default)]
+    song: Vec<Song>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RandomSongs {
+    #[serde(rename = "randomSongs", default)]
+    random_songs: SongList,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SearchResult {
+    #[serde(rename = "searchResult3", default)]
+    search_result: SongList,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Empty {}
+
+// Token auth from the Subsonic API: md5(password + salt), never the password itself.
+// One salt per session keeps cover art URLs stable for the artwork cache.
+struct SubsonicClient {
+    http: reqwest::Client,
+    base_url: String,
+    username: String,
+    token: String,
+    salt: String,
+}
+
+impl SubsonicClient {
+    fn new(config: &SubsonicConfig) -> Self {
+        let salt = session_salt();
+        Self {
+            http: crate::network::client(None, None),
+            base_url: config.url.trim_end_matches('/').to_string(),
+            username: config.username.clone(),
+            token: auth_token(&config.password, &salt),
+            salt,
+        }
+    }
+
+    fn url(&self, endpoint: &str, params: &[(&str, &str)]) -> String {
+        let mut url = format!(
+            "{}/rest/{}.view?u={}&t={}&s={}&v={}&c={}&f=json",
+            self.base_url,
+            endpoint,
+            urlencoding::encode(&self.username),
+            self.token,
+            self.salt,
+            API_VERSION,
+            CLIENT_NAME
+        );
+        for (key, value) in params {
+            url.push_str(&format!("&{}={}", key, urlencoding::encode(value)));
+        }
+        url
+    }
+
+    async fn get<T: DeserializeOwned>(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<T> {
+        let text = self
+            .http
+            .get(self.url(endpoint, params))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_response(&text)
+    }
+
+    async fn stream(&self, id: &str) -> Result<Vec<u8>> {
+        let bytes = self
+            .http
+            .get(self.url("stream", &[("id", id)]))
+            .timeout(STREAM_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(bytes.to_vec())
+    }
+}
+
+fn parse_response<T: DeserializeOwned>(text: &str) -> Result<T> {
+    let envelope: Envelope<T> =
+        serde_json::from_str(text).context("Unexpected Subsonic response")?;
+    let response = envelope.response;
+    if response.status != "ok" {
+        let error = response.error.unwrap_or(ApiError {
+            code: 0,
+            message: "request failed".into(),
+        });
+        return Err(anyhow!("Subsonic error {}: {}", error.code, error.message));
+    }
+    Ok(response.body)
+}
+
+fn auth_token(password: &str, salt: &str) -> String {
+    format!("{:x}", md5::compute(format!("{}{}", password, salt)))
+}
+
+fn session_salt() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let digest = Sha256::digest(format!("{}:{}", nanos, std::process::id()));
+    format!("{:x}", digest)[..12].to_string()
+}
+
+// Streams a Subsonic-compatible server (Navidrome, Airsonic, Jellyfin's plugin)
+// through the internal engine. Starts on a random selection; `:play` searches.
+pub struct SubsonicLibrary {
+    client: SubsonicClient,
+    songs: RwLock<Vec<Song>>,
+}
+
+impl SubsonicLibrary {
+    pub async fn connect(config: &SubsonicConfig) -> Result<Self> {
+        let client = SubsonicClient::new(config);
+        client
+            .get::<Empty>("ping", &[])
+            .await
+            .with_context(|| format!("Failed to reach Subsonic server at {}", config.url))?;
+        let random: RandomSongs = client
+            .get("getRandomSongs", &[("size", STARTING_QUEUE_SIZE)])
+            .await?;
+        Ok(Self {
+            client,
+            songs: RwLock::new(random.random_songs.song),
+        })
+    }
+
+    fn song(&self, index: usize) -> Result<Song> {
+        self.songs
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(index)
+            .cloned()
+            .ok_or_else(|| anyhow!("No track at position {}", index + 1))
+    }
+}
+
+#[async_trait]
+impl Library for SubsonicLibrary {
+    fn len(&self) -> usize {
+        self.songs.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    // The whole file is fetched before playback so seeking works like a local file.
+    async fn load(&self, index: usize) -> Result<(AudioSource, Track)> {
+        let song = self.song(index)?;
+        let data = self.client.stream(&song.id).await?;
+        let decoder = tokio::task::spawn_blocking(move || Decoder::new(Cursor::new(data)))
+            .await?
+            .with_context(|| format!("Unsupported stream for \"{}\"", song.title))?;
+        Ok((Box::new(decoder), song.track()))
+    }
+
+    async fn describe(&self, index: usize) -> Result<QueueEntry> {
+        Ok(QueueEntry {
+            track: self.song(index)?.track(),
+            location: None,
+        })
+    }
+
+    async fn artwork_url(&self, index: usize) -> Result<Option<String>> {
+        let song = self.song(index)?;
+        Ok(song.cover_art.map(|id| {
+            self.client
+                .url("getCoverArt", &[("id", &id), ("size", COVER_ART_SIZE)])
+        }))
+    }
+
+    async fn search(&self, query: &str) -> Result<Option<usize>> {
+        let result: SearchResult = self
+            .client
+            .get(
+                "search3",
+                &[
+                    ("query", query),
+                    ("songCount", SEARCH_RESULT_SIZE),
+                    ("artistCount", "0"),
+                    ("albumCount", "0"),
+                ],
+            )
+            .await?;
+        let songs = result.search_result.song;
+        if songs.is_empty() {
+            return Ok(None);
+        }
+        *self.songs.write().unwrap_or_else(|e| e.into_inner()) = songs;
+        Ok(Some(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> SubsonicClient {
+        SubsonicClient {
+            http: reqwest::Client::new(),
+            base_url: "https://music.example.com".into(),
+            username: "me & you".into(),
+            token: auth_token("sesame", "c19b2d"),
+            salt: "c19b2d".into(),
+        }
+    }
+
+    #[test]
+    fn token_matches_the_api_documentation_example() {
+        assert_eq!(
+            auth_token("sesame", "c19b2d"),
+            "26719a1196d2a940705a59634eb18eab"
+        );
+    }
+
+    #[test]
+    fn urls_carry_auth_and_encoded_params() {
+        assert_eq!(
+            client().url("search3", &[("query", "blue in green")]),
+            "https://music.example.com/rest/search3.view?u=me%20%26%20you\
+             &t=26719a1196d2a940705a59634eb18eab&s=c19b2d&v=1.16.1&c=amcli&f=json\
+             &query=blue%20in%20green"
+        );
+    }
+
+    #[test]
+    fn parses_songs_and_reports_api_errors() {
+        let ok = r#"{"subsonic-response":{"status":"ok","version":"1.16.1",
+            "randomSongs":{"song":[{"id":"42","title":"So What","artist":"Miles Davis",
+            "album":"Kind of Blue","duration":562,"coverArt":"al-7"}]}}}"#;
+        let songs = parse_response::<RandomSongs>(ok).unwrap().random_songs.song;
+        assert_eq!(songs[0].track().name, "So What");
+        assert_eq!(songs[0].track().duration, Duration::from_secs(562));
+        assert_eq!(songs[0].cover_art.as_deref(), Some("al-7"));
+
+        let failed = r#"{"subsonic-response":{"status":"failed",
+            "error":{"code":40,"message":"Wrong username or password"}}}"#;
+        let err = parse_response::<RandomSongs>(failed).unwrap_err();
+        assert!(err.to_string().contains("Wrong username or password"));
+    }
+}
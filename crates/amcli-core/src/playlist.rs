@@ -0,0 +1,170 @@
+// src/playlist.rs
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::player::QueueEntry;
+
+// Extended M3U. Both .m3u and .m3u8 are written as UTF-8, which is what current
+// players expect. Streamed tracks have no file, so they keep only the #EXTINF
+// metadata that importers match on.
+pub fn format_m3u(entries: &[QueueEntry]) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for entry in entries {
+        let track = &entry.track;
+        let title = if track.artist.is_empty() {
+            track.name.clone()
+        } else {
+            format!("{} - {}", track.artist, track.name)
+        };
+        let _ = writeln!(out, "#EXTINF:{},{}", track.duration.as_secs(), title);
+        if !track.album.is_empty() {
+            let _ = writeln!(out, "#EXTALB:{}", track.album);
+        }
+        if let Some(location) = &entry.location {
+            let _ = writeln!(out, "{}", location.display());
+        }
+    }
+    out
+}
+
+// A CSV field, quoted only when needed
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// One row per track, for spreadsheets
+pub fn format_csv(entries: &[QueueEntry]) -> String {
+    let mut out = String::from("artist,title,album,duration_secs,location\n");
+    for entry in entries {
+        let track = &entry.track;
+        let location = entry
+            .location
+            .as_ref()
+            .map(|l| l.display().to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{}",
+            csv_field(&track.artist),
+            csv_field(&track.name),
+            csv_field(&track.album),
+            track.duration.as_secs(),
+            csv_field(&location)
+        );
+    }
+    out
+}
+
+pub fn format_json(entries: &[QueueEntry]) -> String {
+    let tracks: Vec<serde_json::Value> = entries
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "artist": entry.track.artist,
+                "title": entry.track.name,
+                "album": entry.track.album,
+                "duration_secs": entry.track.duration.as_secs(),
+                "location": entry.location,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&tracks).unwrap_or_default() + "\n"
+}
+
+// The format follows the extension: .csv, .json, or M3U for anything else
+pub async fn write_export(path: &Path, entries: &[QueueEntry]) -> Result<()> {
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    let contents = match extension.as_deref() {
+        Some("csv") => format_csv(entries),
+        Some("json") => format_json(entries),
+        _ => format_m3u(entries),
+    };
+    write_file(path, contents).await
+}
+
+async fn write_file(path: &Path, contents: String) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, contents)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// Paths typed in the TUI don't go through a shell, so expand `~` ourselves.
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::Track;
+    use std::time::Duration;
+
+    fn entry(name: &str, artist: &str, location: Option<&str>) -> QueueEntry {
+        QueueEntry {
+            track: Track {
+                name: name.into(),
+                artist: artist.into(),
+                album: "Kind of Blue".into(),
+                duration: Duration::from_secs(337),
+                position: Duration::ZERO,
+            },
+            location: location.map(PathBuf::from),
+        }
+    }
+
+    #[test]
+    fn writes_extended_info_and_locations() {
+        let m3u = format_m3u(&[
+            entry("Blue in Green", "Miles Davis", Some("/Music/blue.m4a")),
+            entry("So What", "", None),
+        ]);
+        assert_eq!(
+            m3u,
+            "#EXTM3U\n\
+             #EXTINF:337,Miles Davis - Blue in Green\n\
+             #EXTALB:Kind of Blue\n\
+             /Music/blue.m4a\n\
+             #EXTINF:337,So What\n\
+             #EXTALB:Kind of Blue\n"
+        );
+    }
+
+    #[test]
+    fn csv_and_json_carry_the_same_fields() {
+        let entries = [
+            entry("Blue in Green", "Miles Davis", Some("/Music/blue.m4a")),
+            entry("So What", "Davis, Miles \"Live\"", None),
+        ];
+        assert_eq!(
+            format_csv(&entries),
+            "artist,title,album,duration_secs,location\n\
+             Miles Davis,Blue in Green,Kind of Blue,337,/Music/blue.m4a\n\
+             \"Davis, Miles \"\"Live\"\"\",So What,Kind of Blue,337,\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&format_json(&entries)).unwrap();
+        assert_eq!(json[0]["location"], "/Music/blue.m4a");
+        assert_eq!(json[1]["artist"], "Davis, Miles \"Live\"");
+        assert!(json[1]["location"].is_null());
+    }
+
+    #[test]
+    fn expands_home_prefix_only() {
+        assert_eq!(expand_home("queue.m3u"), PathBuf::from("queue.m3u"));
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_home("~/q.m3u8"), home.join("q.m3u8"));
+        }
+    }
+}
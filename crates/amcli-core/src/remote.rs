@@ -0,0 +1,371 @@
+// src/remote.rs
+use anyhow::{anyhow, Context, Result};
+use serde_json::json;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+
+use crate::config::HotkeysConfig;
+
+/// A media key, other remote control (headphones, Control Center, the Touch
+/// Bar), global hotkey, or MPRIS client acting while amcli is in the
+/// background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteCommand {
+    Play,
+    Pause,
+    TogglePlayPause,
+    Stop,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    // The rest only come from MPRIS
+    Seek(i32),
+    SetPosition(Duration),
+    SetVolume(u8),
+    Quit,
+}
+
+impl RemoteCommand {
+    fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "play" => Some(Self::Play),
+            "pause" => Some(Self::Pause),
+            "toggle" => Some(Self::TogglePlayPause),
+            "next" => Some(Self::Next),
+            "previous" => Some(Self::Previous),
+            "volume_up" => Some(Self::VolumeUp),
+            "volume_down" => Some(Self::VolumeDown),
+            _ => None,
+        }
+    }
+}
+
+// Both helpers are long-running JXA processes that print one command per
+// line on stdout.
+const EMIT: &str = r#"
+ObjC.import('Foundation');
+const out = $.NSFileHandle.fileHandleWithStandardOutput;
+const emit = (line) => {
+    out.writeData($(line + '\n').dataUsingEncoding($.NSUTF8StringEncoding));
+    return 0;
+};
+"#;
+
+// Registers with MPRemoteCommandCenter through target/action pairs. Claiming
+// a "playing" state is what makes macOS route the keys here rather than to
+// the last app that played audio.
+const MEDIA_KEYS_SCRIPT: &str = r#"
+$.NSBundle.bundleWithPath('/System/Library/Frameworks/MediaPlayer.framework').load;
+ObjC.registerSubclass({
+    name: 'AmcliRemoteTarget',
+    methods: {
+        'play:': { types: ['long', ['id']], implementation: () => emit('play') },
+        'pause:': { types: ['long', ['id']], implementation: () => emit('pause') },
+        'toggle:': { types: ['long', ['id']], implementation: () => emit('toggle') },
+        'next:': { types: ['long', ['id']], implementation: () => emit('next') },
+        'previous:': { types: ['long', ['id']], implementation: () => emit('previous') },
+    },
+});
+const target = $.AmcliRemoteTarget.alloc.init;
+const center = $.MPRemoteCommandCenter.sharedCommandCenter;
+center.playCommand.addTargetAction(target, 'play:');
+center.pauseCommand.addTargetAction(target, 'pause:');
+center.togglePlayPauseCommand.addTargetAction(target, 'toggle:');
+center.nextTrackCommand.addTargetAction(target, 'next:');
+center.previousTrackCommand.addTargetAction(target, 'previous:');
+$.MPNowPlayingInfoCenter.defaultCenter.playbackState = 1;
+$.NSRunLoop.currentRunLoop.run;
+"#;
+
+// Watches key presses in every app with a global NSEvent monitor, which
+// needs Accessibility access for the terminal. `HOTKEYS` is prepended as a
+// list of `{code, flags, command}`.
+const HOTKEYS_SCRIPT: &str = r#"
+ObjC.import('AppKit');
+const MODIFIERS = (1 << 17) | (1 << 18) | (1 << 19) | (1 << 20);
+$.NSEvent.addGlobalMonitorForEventsMatchingMaskHandler(1 << 10, (event) => {
+    const code = event.keyCode;
+    const flags = event.modifierFlags & MODIFIERS;
+    for (const hotkey of HOTKEYS) {
+        if (hotkey.code === code && hotkey.flags === flags) emit(hotkey.command);
+    }
+});
+$.NSApplication.sharedApplication;
+$.NSRunLoop.currentRunLoop.run;
+"#;
+
+// Used when `[hotkeys]` lists none
+const DEFAULT_HOTKEYS: &[(&str, &str)] = &[
+    ("play_pause", "ctrl+alt+space"),
+    ("next", "ctrl+alt+right"),
+    ("previous", "ctrl+alt+left"),
+    ("volume_up", "ctrl+alt+up"),
+    ("volume_down", "ctrl+alt+down"),
+];
+
+// NSEvent modifier flags
+const SHIFT: u32 = 1 << 17;
+const CTRL: u32 = 1 << 18;
+const ALT: u32 = 1 << 19;
+const CMD: u32 = 1 << 20;
+
+// macOS virtual key codes (kVK_*), which follow key position on an ANSI
+// layout rather than the character typed
+const KEY_CODES: &[(&str, u16)] = &[
+    ("a", 0),
+    ("s", 1),
+    ("d", 2),
+    ("f", 3),
+    ("h", 4),
+    ("g", 5),
+    ("z", 6),
+    ("x", 7),
+    ("c", 8),
+    ("v", 9),
+    ("b", 11),
+    ("q", 12),
+    ("w", 13),
+    ("e", 14),
+    ("r", 15),
+    ("y", 16),
+    ("t", 17),
+    ("1", 18),
+    ("2", 19),
+    ("3", 20),
+    ("4", 21),
+    ("6", 22),
+    ("5", 23),
+    ("=", 24),
+    ("9", 25),
+    ("7", 26),
+    ("-", 27),
+    ("8", 28),
+    ("0", 29),
+    ("]", 30),
+    ("o", 31),
+    ("u", 32),
+    ("[", 33),
+    ("i", 34),
+    ("p", 35),
+    ("enter", 36),
+    ("l", 37),
+    ("j", 38),
+    ("'", 39),
+    ("k", 40),
+    (";", 41),
+    ("\\", 42),
+    (",", 43),
+    ("/", 44),
+    ("n", 45),
+    ("m", 46),
+    (".", 47),
+    ("tab", 48),
+    ("space", 49),
+    ("`", 50),
+    ("esc", 53),
+    ("f5", 96),
+    ("f6", 97),
+    ("f7", 98),
+    ("f3", 99),
+    ("f8", 100),
+    ("f9", 101),
+    ("f11", 103),
+    ("f10", 109),
+    ("f12", 111),
+    ("home", 115),
+    ("pageup", 116),
+    ("f4", 118),
+    ("end", 119),
+    ("f2", 120),
+    ("pagedown", 121),
+    ("f1", 122),
+    ("left", 123),
+    ("right", 124),
+    ("down", 125),
+    ("up", 126),
+];
+
+#[derive(Debug, PartialEq)]
+struct Hotkey {
+    code: u16,
+    flags: u32,
+    command: &'static str,
+}
+
+// "ctrl+alt+space" -> key code and modifier flags. A bare key is rejected:
+// it would fire whenever that key is typed anywhere.
+fn parse_hotkey(spec: &str) -> Result<(u16, u32)> {
+    let spec = spec.trim().to_lowercase();
+    let mut parts: Vec<&str> = spec.split('+').collect();
+    let key = parts.pop().unwrap_or_default();
+    let mut flags = 0;
+    for modifier in parts {
+        flags |= match modifier {
+            "shift" => SHIFT,
+            "ctrl" | "control" => CTRL,
+            "alt" | "opt" | "option" => ALT,
+            "cmd" | "command" | "super" => CMD,
+            _ => return Err(anyhow!("Unknown modifier \"{}\"", modifier)),
+        };
+    }
+    let code = KEY_CODES
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, code)| *code)
+        .ok_or_else(|| anyhow!("Unknown key \"{}\"", key))?;
+    if flags & (CTRL | ALT | CMD) == 0 {
+        return Err(anyhow!("needs ctrl, alt, or cmd"));
+    }
+    Ok((code, flags))
+}
+
+fn hotkeys(config: &HotkeysConfig) -> Result<Vec<Hotkey>> {
+    let bindings: Vec<(&str, &str)> = if config.bindings.is_empty() {
+        DEFAULT_HOTKEYS.to_vec()
+    } else {
+        config
+            .bindings
+            .iter()
+            .map(|(action, spec)| (action.as_str(), spec.as_str()))
+            .collect()
+    };
+    bindings
+        .into_iter()
+        .filter(|(_, spec)| !spec.trim().is_empty())
+        .map(|(action, spec)| {
+            let command = match action {
+                "play_pause" => "toggle",
+                "next" => "next",
+                "previous" => "previous",
+                "volume_up" => "volume_up",
+                "volume_down" => "volume_down",
+                _ => return Err(anyhow!("Unknown hotkey action \"{}\"", action)),
+            };
+            let (code, flags) =
+                parse_hotkey(spec).with_context(|| format!("Invalid hotkey \"{}\"", spec))?;
+            Ok(Hotkey {
+                code,
+                flags,
+                command,
+            })
+        })
+        .collect()
+}
+
+/// Commands from the media key and hotkey helpers. The helpers are stopped
+/// when this is dropped.
+pub struct RemoteCommands {
+    receiver: mpsc::UnboundedReceiver<RemoteCommand>,
+    _helpers: Vec<Child>,
+}
+
+impl RemoteCommands {
+    /// Starts whichever helpers are turned on; `None` when neither is.
+    /// Only available on macOS.
+    pub fn spawn(media_keys: bool, hotkeys_config: &HotkeysConfig) -> Result<Option<Self>> {
+        if !media_keys && !hotkeys_config.enabled {
+            return Ok(None);
+        }
+        if !cfg!(target_os = "macos") {
+            return Err(anyhow!(
+                "media keys and global hotkeys are only supported on macOS"
+            ));
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut helpers = Vec::new();
+        if media_keys {
+            helpers.push(spawn_helper(MEDIA_KEYS_SCRIPT, sender.clone())?);
+        }
+        if hotkeys_config.enabled {
+            let hotkeys = json!(hotkeys(hotkeys_config)?
+                .iter()
+                .map(|h| json!({ "code": h.code, "flags": h.flags, "command": h.command }))
+                .collect::<Vec<_>>());
+            let script = format!("const HOTKEYS = {};\n{}", hotkeys, HOTKEYS_SCRIPT);
+            helpers.push(spawn_helper(&script, sender)?);
+        }
+        Ok(Some(Self {
+            receiver,
+            _helpers: helpers,
+        }))
+    }
+
+    /// The next pending command, without waiting.
+    pub fn try_next(&mut self) -> Option<RemoteCommand> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+fn spawn_helper(script: &str, sender: mpsc::UnboundedSender<RemoteCommand>) -> Result<Child> {
+    let mut helper = Command::new("osascript")
+        .args(["-l", "JavaScript", "-e"])
+        .arg(format!("{}{}", EMIT, script))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    let stdout = helper
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("remote command helper has no output"))?;
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some(command) = RemoteCommand::parse(&line) {
+                if sender.send(command).is_err() {
+                    break;
+                }
+            }
+        }
+        tracing::debug!("Remote command helper exited");
+    });
+    Ok(helper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helper_lines_map_to_commands() {
+        assert_eq!(
+            RemoteCommand::parse("toggle\n"),
+            Some(RemoteCommand::TogglePlayPause)
+        );
+        assert_eq!(
+            RemoteCommand::parse("previous"),
+            Some(RemoteCommand::Previous)
+        );
+        assert_eq!(RemoteCommand::parse("ready"), None);
+    }
+
+    #[test]
+    fn hotkeys_need_a_modifier_and_a_known_key() {
+        assert_eq!(parse_hotkey("ctrl+alt+space").unwrap(), (49, CTRL | ALT));
+        assert_eq!(parse_hotkey("Cmd+Shift+]").unwrap(), (30, CMD | SHIFT));
+        assert!(parse_hotkey("space").is_err());
+        assert!(parse_hotkey("shift+n").is_err());
+        assert!(parse_hotkey("hyper+n").is_err());
+        assert!(parse_hotkey("ctrl+f13").is_err());
+
+        assert_eq!(hotkeys(&HotkeysConfig::default()).unwrap().len(), 5);
+        let mut config = HotkeysConfig::default();
+        config.bindings.insert("next".into(), "ctrl+alt+n".into());
+        config.bindings.insert("previous".into(), "".into());
+        assert_eq!(
+            hotkeys(&config).unwrap(),
+            [Hotkey {
+                code: 45,
+                flags: CTRL | ALT,
+                command: "next"
+            }]
+        );
+        config.bindings.insert("shuffle".into(), "ctrl+s".into());
+        assert!(hotkeys(&config).is_err());
+    }
+}
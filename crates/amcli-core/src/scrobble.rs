@@ -0,0 +1,324 @@
+// Sends plays from the history to Last.fm or ListenBrainz, for listens made
+// while the network was down or before an account was set up.
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::history::Play;
+
+const LISTENBRAINZ_URL: &str = "https://api.listenbrainz.org/1/submit-listens";
+const LASTFM_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+const LASTFM_AUTH_URL: &str = "https://www.last.fm/api/auth/";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+// The most listens each service takes in one request
+const LISTENBRAINZ_BATCH: usize = 1000;
+const LASTFM_BATCH: usize = 50;
+// Last.fm doesn't count anything shorter
+const LASTFM_MIN_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Service {
+    ListenBrainz,
+    LastFm,
+}
+
+impl Service {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ListenBrainz => "ListenBrainz",
+            Self::LastFm => "Last.fm",
+        }
+    }
+}
+
+impl FromStr for Service {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "listenbrainz" => Ok(Self::ListenBrainz),
+            "lastfm" => Ok(Self::LastFm),
+            _ => bail!("Unknown service \"{}\" (listenbrainz or lastfm)", s),
+        }
+    }
+}
+
+/// What a service made of a backfill.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Backfill {
+    pub accepted: usize,
+    /// Turned down by the service, e.g. Last.fm's limit on old timestamps.
+    pub ignored: usize,
+    /// Never sent: plays without an artist, and for Last.fm ones too short
+    /// to count.
+    pub skipped: usize,
+}
+
+/// The plays `service` would take, keeping their order.
+pub fn submittable(service: Service, plays: &[Play]) -> Vec<&Play> {
+    plays
+        .iter()
+        .filter(|play| !play.artist.is_empty() && !play.name.is_empty())
+        .filter(|play| {
+            service != Service::LastFm
+                || play.duration_secs == 0
+                || play.duration_secs >= LASTFM_MIN_SECS
+        })
+        .collect()
+}
+
+pub async fn backfill(service: Service, config: &Config, plays: &[Play]) -> Result<Backfill> {
+    crate::network::ensure_online(service.name())?;
+    let sendable = submittable(service, plays);
+    let mut result = Backfill {
+        skipped: plays.len() - sendable.len(),
+        ..Backfill::default()
+    };
+    let client = crate::network::client(Some(REQUEST_TIMEOUT), None);
+    match service {
+        Service::ListenBrainz => {
+            let token = config
+                .scrobble
+                .listenbrainz_token
+                .as_deref()
+                .filter(|t| !t.is_empty())
+                .ok_or_else(|| anyhow!("Set scrobble.listenbrainz_token to use ListenBrainz"))?;
+            for batch in sendable.chunks(LISTENBRAINZ_BATCH) {
+                let response = client
+                    .post(LISTENBRAINZ_URL)
+                    .header("Authorization", format!("Token {}", token))
+                    .json(&listenbrainz_payload(batch))
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let json: Value = response.json().await.unwrap_or_default();
+                    bail!(
+                        "ListenBrainz: {}",
+                        json["error"].as_str().unwrap_or(status.as_str())
+                    );
+                }
+                result.accepted += batch.len();
+            }
+        }
+        Service::LastFm => {
+            let account = LastFm::from_config(config)?;
+            let session_key = config
+                .scrobble
+                .lastfm_session_key
+                .clone()
+                .filter(|k| !k.is_empty())
+                .ok_or_else(|| anyhow!("Run `amcli lastfm-login` first"))?;
+            for batch in sendable.chunks(LASTFM_BATCH) {
+                let mut params = scrobble_params(batch);
+                params.insert("sk".into(), session_key.clone());
+                let json = account.call(&client, "track.scrobble", params).await?;
+                let counts = &json["scrobbles"]["@attr"];
+                let count = |key: &str| {
+                    counts[key]
+                        .as_u64()
+                        .or_else(|| counts[key].as_str().and_then(|n| n.parse().ok()))
+                        .unwrap_or(0) as usize
+                };
+                result.accepted += count("accepted");
+                result.ignored += count("ignored");
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn listenbrainz_payload(plays: &[&Play]) -> Value {
+    let listens: Vec<Value> = plays
+        .iter()
+        .map(|play| {
+            let mut metadata = json!({
+                "artist_name": play.artist,
+                "track_name": play.name,
+                "additional_info": {
+                    "submission_client": "amcli",
+                    "submission_client_version": env!("CARGO_PKG_VERSION"),
+                },
+            });
+            if !play.album.is_empty() {
+                metadata["release_name"] = json!(play.album);
+            }
+            if play.duration_secs > 0 {
+                metadata["additional_info"]["duration_ms"] = json!(play.duration_secs * 1000);
+            }
+            json!({ "listened_at": play.at, "track_metadata": metadata })
+        })
+        .collect();
+    json!({ "listen_type": "import", "payload": listens })
+}
+
+// `artist[0]`, `track[0]`, … as track.scrobble takes a batch
+fn scrobble_params(plays: &[&Play]) -> BTreeMap<String, String> {
+    let mut params = BTreeMap::new();
+    for (i, play) in plays.iter().enumerate() {
+        params.insert(format!("artist[{}]", i), play.artist.clone());
+        params.insert(format!("track[{}]", i), play.name.clone());
+        params.insert(format!("timestamp[{}]", i), play.at.to_string());
+        if !play.album.is_empty() {
+            params.insert(format!("album[{}]", i), play.album.clone());
+        }
+        if play.duration_secs > 0 {
+            params.insert(format!("duration[{}]", i), play.duration_secs.to_string());
+        }
+    }
+    params
+}
+
+/// A Last.fm API account, for signed calls.
+pub struct LastFm {
+    api_key: String,
+    api_secret: String,
+}
+
+impl LastFm {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let api_key = config
+            .scrobble
+            .lastfm_api_key
+            .clone()
+            .or_else(|| config.artist_info.lastfm_api_key.clone())
+            .filter(|k| !k.is_empty());
+        let api_secret = config
+            .scrobble
+            .lastfm_api_secret
+            .clone()
+            .filter(|s| !s.is_empty());
+        match (api_key, api_secret) {
+            (Some(api_key), Some(api_secret)) => Ok(Self {
+                api_key,
+                api_secret,
+            }),
+            _ => bail!("Set scrobble.lastfm_api_key and scrobble.lastfm_api_secret to use Last.fm"),
+        }
+    }
+
+    /// A request token and the page where the user grants it access.
+    pub async fn request_token(&self) -> Result<(String, String)> {
+        let client = crate::network::client(Some(REQUEST_TIMEOUT), None);
+        let json = self.call(&client, "auth.getToken", BTreeMap::new()).await?;
+        let token = json["token"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Last.fm sent no token"))?
+            .to_string();
+        let url = format!(
+            "{}?api_key={}&token={}",
+            LASTFM_AUTH_URL, self.api_key, token
+        );
+        Ok((token, url))
+    }
+
+    /// The session key and user name for a token the user has allowed.
+    pub async fn session(&self, token: &str) -> Result<(String, String)> {
+        let client = crate::network::client(Some(REQUEST_TIMEOUT), None);
+        let params = BTreeMap::from([("token".to_string(), token.to_string())]);
+        let json = self.call(&client, "auth.getSession", params).await?;
+        let session = &json["session"];
+        match (session["key"].as_str(), session["name"].as_str()) {
+            (Some(key), Some(name)) => Ok((key.to_string(), name.to_string())),
+            _ => bail!("Last.fm sent no session"),
+        }
+    }
+
+    async fn call(
+        &self,
+        client: &reqwest::Client,
+        method: &str,
+        mut params: BTreeMap<String, String>,
+    ) -> Result<Value> {
+        params.insert("method".into(), method.into());
+        params.insert("api_key".into(), self.api_key.clone());
+        let signature = signature(&params, &self.api_secret);
+        params.insert("api_sig".into(), signature);
+        params.insert("format".into(), "json".into());
+        let json: Value = client
+            .post(LASTFM_URL)
+            .form(&params)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if let Some(code) = json["error"].as_u64() {
+            bail!(
+                "Last.fm: {} (error {})",
+                json["message"].as_str().unwrap_or("request failed"),
+                code
+            );
+        }
+        Ok(json)
+    }
+}
+
+// Every parameter but `format`, sorted by name, run together with the secret
+fn signature(params: &BTreeMap<String, String>, secret: &str) -> String {
+    let mut text: String = params
+        .iter()
+        .filter(|(name, _)| name.as_str() != "format")
+        .map(|(name, value)| format!("{}{}", name, value))
+        .collect();
+    text.push_str(secret);
+    format!("{:x}", md5::compute(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(name: &str, artist: &str, duration_secs: u64) -> Play {
+        Play {
+            at: 1_792_098_240,
+            name: name.into(),
+            artist: artist.into(),
+            album: "Kind of Blue".into(),
+            duration_secs,
+        }
+    }
+
+    #[test]
+    fn plays_are_batched_the_way_each_service_takes_them() {
+        let plays = [
+            play("So What", "Miles Davis", 562),
+            play("Station ID", "", 8),
+            play("Interlude", "Miles Davis", 12),
+        ];
+        let listens = submittable(Service::ListenBrainz, &plays);
+        assert_eq!(listens.len(), 2);
+        let payload = listenbrainz_payload(&listens);
+        assert_eq!(payload["listen_type"], "import");
+        assert_eq!(payload["payload"][0]["listened_at"], 1_792_098_240);
+        let metadata = &payload["payload"][0]["track_metadata"];
+        assert_eq!(metadata["release_name"], "Kind of Blue");
+        assert_eq!(metadata["additional_info"]["duration_ms"], 562_000);
+
+        // Last.fm doesn't take tracks under 30 seconds
+        let scrobbles = submittable(Service::LastFm, &plays);
+        assert_eq!(scrobbles.len(), 1);
+        let params = scrobble_params(&scrobbles);
+        assert_eq!(params["artist[0]"], "Miles Davis");
+        assert_eq!(params["timestamp[0]"], "1792098240");
+        assert_eq!(params["duration[0]"], "562");
+    }
+
+    #[test]
+    fn last_fm_calls_are_signed_over_sorted_parameters() {
+        let params = BTreeMap::from([
+            ("method".to_string(), "auth.getToken".to_string()),
+            ("api_key".to_string(), "key".to_string()),
+            ("format".to_string(), "json".to_string()),
+        ]);
+        // md5("api_keykeymethodauth.getTokensecret")
+        assert_eq!(
+            signature(&params, "secret"),
+            "b4705499705a550b07ca058a15bde9b0"
+        );
+        assert_eq!("lastfm".parse::<Service>().unwrap(), Service::LastFm);
+        assert!("spotify".parse::<Service>().is_err());
+    }
+}
@@ -0,0 +1,72 @@
+// src/session.rs
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::config::StartupView;
+
+/// How the UI looked when amcli last quit, brought back at startup with
+/// `[startup] restore_session`. Stored as JSON in amcli's data directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub show_queue: bool,
+    #[serde(default)]
+    pub big_text: bool,
+    #[serde(default)]
+    pub focus: StartupView,
+}
+
+impl Session {
+    /// `session.json`, or `session.<name>.json` for a config profile.
+    pub fn path(profile: Option<&str>) -> PathBuf {
+        let file = match profile {
+            Some(name) => format!("session.{}.json", name),
+            None => "session.json".into(),
+        };
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("amcli")
+            .join(file)
+    }
+
+    /// `None` when nothing has been saved yet.
+    pub async fn load(path: &Path) -> Result<Option<Self>> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(path, serde_json::to_string_pretty(self)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sessions_round_trip() {
+        let path = std::env::temp_dir().join(format!("amcli-session-{}.json", std::process::id()));
+        assert_eq!(Session::load(&path).await.unwrap(), None);
+
+        let session = Session {
+            theme: Some("green vfd".into()),
+            show_queue: true,
+            big_text: false,
+            focus: StartupView::Queue,
+        };
+        session.save(&path).await.unwrap();
+        assert_eq!(Session::load(&path).await.unwrap(), Some(session));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,105 @@
+// src/store.rs
+use anyhow::{anyhow, bail, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+/// A value kept as JSON in one file, the way bookmarks, favorites, notes,
+/// and the artwork lists are stored in amcli's data directory.
+///
+/// A default store has no file: it's what the UI falls back to when one
+/// can't be read, and it refuses to save so the file is left to be fixed.
+#[derive(Debug, Default)]
+pub struct JsonStore<T> {
+    path: PathBuf,
+    value: T,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> JsonStore<T> {
+    /// A missing file is an empty store; one that won't parse is an error
+    /// naming the file.
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        let value = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => {
+                serde_json::from_str(&content).map_err(|e| anyhow!("{}: {}", path.display(), e))?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+            Err(e) => bail!("{}: {}", path.display(), e),
+        };
+        Ok(Self { path, value })
+    }
+
+    /// Written beside the file and renamed over it, so a crash mid-write
+    /// can't leave it half written.
+    pub async fn save(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            bail!("Not saved: the file couldn't be read when amcli started");
+        }
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let content = serde_json::to_string_pretty(&self.value)?;
+        let staged = staged_path(&self.path);
+        tokio::fs::write(&staged, content).await?;
+        tokio::fs::rename(&staged, &self.path).await?;
+        Ok(())
+    }
+}
+
+impl<T> Deref for JsonStore<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for JsonStore<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+// `notes.json` is staged as `notes.json.tmp`, on the same filesystem so the
+// rename is atomic
+fn staged_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn unreadable_files_are_errors_and_saves_replace_the_file() {
+        let dir = std::env::temp_dir().join(format!("amcli-store-{}", std::process::id()));
+        let path = dir.join("store.json");
+        let mut store = JsonStore::<BTreeMap<String, u32>>::load(path.clone())
+            .await
+            .unwrap();
+        assert!(store.is_empty());
+        store.insert("plays".into(), 3);
+        store.save().await.unwrap();
+        assert!(!staged_path(&path).exists());
+        let loaded = JsonStore::<BTreeMap<String, u32>>::load(path.clone())
+            .await
+            .unwrap();
+        assert_eq!(loaded.get("plays"), Some(&3));
+
+        std::fs::write(&path, "{\"plays\": ").unwrap();
+        let error = JsonStore::<BTreeMap<String, u32>>::load(path.clone())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().starts_with(&path.display().to_string()));
+        // The stand-in doesn't write over the broken file
+        assert!(JsonStore::<BTreeMap<String, u32>>::default()
+            .save()
+            .await
+            .is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"plays\": ");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,304 @@
+// src/update.rs
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/juntaochi/amcli/releases/latest";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// The binary itself can take a while on a slow connection
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+// GitHub rejects API requests without a User-Agent
+const USER_AGENT: &str = concat!(
+    "AMCLI v",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/juntaochi/amcli)"
+);
+
+/// The version this build reports, compared against release tags.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A published GitHub release.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// Whether the tag is a higher version than `current`. Tags that aren't
+    /// semver never count as newer.
+    pub fn is_newer_than(&self, current: &str) -> bool {
+        is_newer(&self.tag_name, current)
+    }
+
+    // The tarball the release workflow builds for this machine
+    fn archive(&self) -> Result<&Asset> {
+        if std::env::consts::OS != "macos" {
+            bail!("Release builds are macOS only; update with `cargo install amcli` instead");
+        }
+        let arch = match std::env::consts::ARCH {
+            "aarch64" => "arm64",
+            "x86_64" => "x86_64",
+            other => bail!("No release builds for {}", other),
+        };
+        let name = archive_name(&self.tag_name, arch);
+        self.asset(&name)
+    }
+
+    fn asset(&self, name: &str) -> Result<&Asset> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| anyhow!("{} has no {}", self.tag_name, name))
+    }
+}
+
+fn archive_name(tag: &str, arch: &str) -> String {
+    format!("amcli-{}-{}-apple-darwin.tar.gz", tag, arch)
+}
+
+fn is_newer(tag: &str, current: &str) -> bool {
+    let parse = |v: &str| semver::Version::parse(v.trim().trim_start_matches('v')).ok();
+    match (parse(tag), parse(current)) {
+        (Some(tag), Some(current)) => tag > current,
+        _ => false,
+    }
+}
+
+/// Finds and installs amcli releases from GitHub.
+#[derive(Clone)]
+pub struct Updater {
+    client: reqwest::Client,
+    state_path: PathBuf,
+}
+
+// The last check, so restarts within a day don't ask GitHub again
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckState {
+    checked_at: u64,
+    latest: String,
+}
+
+impl CheckState {
+    fn is_fresh(&self, now: u64) -> bool {
+        now.saturating_sub(self.checked_at) < CHECK_INTERVAL.as_secs()
+    }
+}
+
+impl Default for Updater {
+    fn default() -> Self {
+        Self::new(crate::cache::update_check_path())
+    }
+}
+
+impl Updater {
+    pub fn new(state_path: PathBuf) -> Self {
+        Self {
+            client: crate::network::client(Some(REQUEST_TIMEOUT), Some(USER_AGENT)),
+            state_path,
+        }
+    }
+
+    pub async fn latest(&self) -> Result<Release> {
+        crate::network::ensure_online("update check")?;
+        let release = self
+            .client
+            .get(RELEASES_URL)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(release)
+    }
+
+    /// The newer release tag, if there is one. GitHub is asked at most once
+    /// a day; in between the last answer is reused.
+    pub async fn check(&self, current: &str) -> Result<Option<String>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let cached = tokio::fs::read_to_string(&self.state_path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str::<CheckState>(&content).ok())
+            .filter(|state| state.is_fresh(now));
+        let latest = match cached {
+            Some(state) => state.latest,
+            None => {
+                let latest = self.latest().await?.tag_name;
+                let state = CheckState {
+                    checked_at: now,
+                    latest: latest.clone(),
+                };
+                if let Some(dir) = self.state_path.parent() {
+                    tokio::fs::create_dir_all(dir).await?;
+                }
+                tokio::fs::write(&self.state_path, serde_json::to_string(&state)?).await?;
+                latest
+            }
+        };
+        Ok(is_newer(&latest, current).then_some(latest))
+    }
+
+    /// Downloads `release` for this machine, checks it against its published
+    /// SHA-256, and swaps it in for the binary at `exe`.
+    pub async fn install(&self, release: &Release, exe: &Path) -> Result<()> {
+        crate::network::ensure_online("self-update")?;
+        let archive = release.archive()?;
+        let checksum = release.asset(&format!("{}.sha256", archive.name))?;
+
+        let bytes = self.download(&archive.browser_download_url).await?;
+        let expected =
+            String::from_utf8_lossy(&self.download(&checksum.browser_download_url).await?)
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_lowercase();
+        verify_sha256(&bytes, &expected)?;
+
+        // Unpacked next to the binary so the final rename stays on one
+        // filesystem and can't leave a half-written executable behind
+        let dir = exe
+            .parent()
+            .ok_or_else(|| anyhow!("{} has no parent directory", exe.display()))?
+            .join(format!(".amcli-update-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await?;
+        let result = replace_binary(&bytes, &dir, exe).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        result.with_context(|| format!("Couldn't replace {}", exe.display()))
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let bytes = self
+            .client
+            .get(url)
+            .timeout(DOWNLOAD_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(bytes.to_vec())
+    }
+}
+
+fn verify_sha256(bytes: &[u8], expected: &str) -> Result<()> {
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    if expected.is_empty() || actual != expected {
+        bail!("Checksum mismatch: expected {}, got {}", expected, actual);
+    }
+    Ok(())
+}
+
+async fn replace_binary(archive: &[u8], dir: &Path, exe: &Path) -> Result<()> {
+    let tarball = dir.join("amcli.tar.gz");
+    tokio::fs::write(&tarball, archive).await?;
+    let status = tokio::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(&tarball)
+        .arg("-C")
+        .arg(dir)
+        .status()
+        .await?;
+    if !status.success() {
+        bail!("tar couldn't unpack the release archive");
+    }
+
+    let binary = dir.join("amcli");
+    if !tokio::fs::try_exists(&binary).await? {
+        bail!("The release archive has no amcli binary");
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).await?;
+    }
+    tokio::fs::rename(&binary, exe).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, names: &[&str]) -> Release {
+        Release {
+            tag_name: tag.into(),
+            assets: names
+                .iter()
+                .map(|name| Asset {
+                    name: name.to_string(),
+                    browser_download_url: format!("https://example.com/{}", name),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn only_higher_semver_tags_are_updates() {
+        assert!(release("v0.4.0", &[]).is_newer_than("0.3.1"));
+        assert!(release("0.3.2", &[]).is_newer_than("0.3.1"));
+        assert!(!release("v0.3.1", &[]).is_newer_than("0.3.1"));
+        assert!(!release("v0.3.0", &[]).is_newer_than("0.3.1"));
+        assert!(!release("nightly", &[]).is_newer_than("0.3.1"));
+    }
+
+    #[test]
+    fn assets_follow_the_release_workflow_names() {
+        let name = archive_name("v0.4.0", "arm64");
+        assert_eq!(name, "amcli-v0.4.0-arm64-apple-darwin.tar.gz");
+        let release = release("v0.4.0", &[&name, &format!("{}.sha256", name)]);
+        assert!(release.asset(&format!("{}.sha256", name)).is_ok());
+        assert!(release
+            .asset("amcli-v0.4.0-x86_64-apple-darwin.tar.gz")
+            .is_err());
+    }
+
+    #[test]
+    fn checksums_must_match() {
+        let digest = format!("{:x}", Sha256::digest(b"amcli"));
+        assert!(verify_sha256(b"amcli", &digest).is_ok());
+        assert!(verify_sha256(b"amcli!", &digest).is_err());
+        assert!(verify_sha256(b"amcli", "").is_err());
+    }
+
+    #[tokio::test]
+    async fn checks_reuse_the_answer_for_a_day() {
+        let path = std::env::temp_dir().join(format!("amcli-update-{}.json", std::process::id()));
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let state = CheckState {
+            checked_at: now - 60,
+            latest: "v9.0.0".into(),
+        };
+        tokio::fs::write(&path, serde_json::to_string(&state).unwrap())
+            .await
+            .unwrap();
+
+        // Answered from the file, so no request goes out
+        let updater = Updater::new(path.clone());
+        assert_eq!(updater.check("0.3.1").await.unwrap(), Some("v9.0.0".into()));
+        assert_eq!(updater.check("9.0.0").await.unwrap(), None);
+        assert!(!CheckState {
+            checked_at: now - CHECK_INTERVAL.as_secs(),
+            ..state
+        }
+        .is_fresh(now));
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
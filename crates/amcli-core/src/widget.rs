@@ -0,0 +1,380 @@
+// src/widget.rs
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::watch;
+
+use crate::player::{PlaybackState, Track};
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Where the running UI serves now-playing updates, next to its PID file.
+pub fn socket_path() -> PathBuf {
+    dirs::runtime_dir()
+        .or_else(dirs::data_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("amcli/widget.sock")
+}
+
+/// One now-playing update, sent over the socket as a line of JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WidgetState {
+    /// "playing", "paused", or "stopped".
+    pub state: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub artist: String,
+    #[serde(default)]
+    pub album: String,
+    /// Seconds.
+    #[serde(default)]
+    pub duration: u64,
+    #[serde(default)]
+    pub position: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub artwork_url: String,
+    /// The cover as a local file, once it has been downloaded into the
+    /// artwork cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artwork_path: Option<PathBuf>,
+}
+
+impl WidgetState {
+    pub fn new(state: PlaybackState, track: Option<&Track>, artwork_url: Option<&str>) -> Self {
+        let state = match (state, track) {
+            (_, None) | (PlaybackState::Stopped, _) => "stopped",
+            (PlaybackState::Playing, _) => "playing",
+            (PlaybackState::Paused, _) => "paused",
+        };
+        let mut widget = Self {
+            state: state.to_string(),
+            ..Self::default()
+        };
+        if let Some(track) = track {
+            widget.title = track.name.clone();
+            widget.artist = track.artist.clone();
+            widget.album = track.album.clone();
+            widget.duration = track.duration.as_secs();
+            widget.position = track.position.as_secs();
+            widget.artwork_url = artwork_url.unwrap_or_default().to_string();
+        }
+        widget
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.state == "stopped"
+    }
+}
+
+/// Output formats for `amcli widget`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetStyle {
+    /// `key=value` arguments for `sketchybar --set`.
+    Sketchybar,
+    /// The state as one line of JSON, for Übersicht's `command`.
+    Ubersicht,
+    /// "Artist — Title", or nothing when stopped.
+    Plain,
+}
+
+impl FromStr for WidgetStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sketchybar" => Ok(Self::Sketchybar),
+            "ubersicht" | "übersicht" | "json" => Ok(Self::Ubersicht),
+            "plain" => Ok(Self::Plain),
+            _ => bail!(
+                "Unknown widget style \"{}\" (sketchybar, ubersicht, or plain)",
+                s
+            ),
+        }
+    }
+}
+
+/// Formats `state` the way `style`'s tool reads it.
+pub fn render(style: WidgetStyle, state: &WidgetState) -> String {
+    match style {
+        WidgetStyle::Sketchybar => {
+            // Hidden rather than blank, so the bar closes up around it
+            if state.is_stopped() {
+                return "drawing=off".to_string();
+            }
+            let icon = if state.state == "playing" {
+                "▶"
+            } else {
+                "⏸"
+            };
+            let mut args = vec![
+                "drawing=on".to_string(),
+                format!("icon={}", shell_quote(icon)),
+                format!("label={}", shell_quote(&plain(state))),
+            ];
+            match &state.artwork_path {
+                Some(path) => args.push(format!(
+                    "background.image={}",
+                    shell_quote(&path.display().to_string())
+                )),
+                None => args.push("background.image=off".to_string()),
+            }
+            args.join(" ")
+        }
+        WidgetStyle::Ubersicht => serde_json::to_string(state).unwrap_or_default(),
+        WidgetStyle::Plain => plain(state),
+    }
+}
+
+fn plain(state: &WidgetState) -> String {
+    match (state.is_stopped(), state.artist.is_empty()) {
+        (true, _) => String::new(),
+        (false, true) => state.title.clone(),
+        (false, false) => format!("{} — {}", state.artist, state.title),
+    }
+}
+
+// Single quotes keep titles with spaces, `$`, or quotes in one argument when
+// a plugin script `eval`s the output
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Serves the latest [`WidgetState`] to every client of a Unix socket: each
+/// gets the current state on connecting and a line per change after that.
+/// Covers are downloaded into the artwork cache in the background so
+/// clients get a local path. Stops accepting once the server is dropped.
+pub struct WidgetServer {
+    sender: Arc<watch::Sender<WidgetState>>,
+}
+
+impl WidgetServer {
+    pub fn spawn(path: PathBuf) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Left by an instance that didn't get to clean up; the PID file
+        // already keeps two from running at once
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Couldn't listen on {}", path.display()))?;
+        let (sender, receiver) =
+            watch::channel(WidgetState::new(PlaybackState::Stopped, None, None));
+        let sender = Arc::new(sender);
+        tokio::spawn(accept(listener, receiver.clone()));
+        // Weak, so dropping the server still closes the channel
+        tokio::spawn(cache_covers(Arc::downgrade(&sender), receiver));
+        Ok(Self { sender })
+    }
+
+    /// Only sends what changed since the last call. The downloaded cover
+    /// carries over while the artwork URL stays the same.
+    pub fn publish(&self, mut state: WidgetState) {
+        self.sender.send_if_modified(|current| {
+            if current.artwork_url == state.artwork_url {
+                state.artwork_path = current.artwork_path.clone();
+            }
+            if *current == state {
+                return false;
+            }
+            *current = state;
+            true
+        });
+    }
+}
+
+async fn accept(listener: UnixListener, mut receiver: watch::Receiver<WidgetState>) {
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve(stream, receiver.clone()));
+                }
+                Err(e) => tracing::debug!("Widget socket accept failed: {}", e),
+            },
+            // The server was dropped
+            Err(_) = receiver.changed() => return,
+        }
+    }
+}
+
+// Until the client hangs up or the server goes away
+async fn serve(mut stream: UnixStream, mut receiver: watch::Receiver<WidgetState>) {
+    loop {
+        let line = {
+            let state = receiver.borrow_and_update();
+            serde_json::to_string(&*state).unwrap_or_default() + "\n"
+        };
+        if stream.write_all(line.as_bytes()).await.is_err() || receiver.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+// Downloads each new cover once and hands its path back to the server
+async fn cache_covers(
+    sender: Weak<watch::Sender<WidgetState>>,
+    mut receiver: watch::Receiver<WidgetState>,
+) {
+    let client = crate::network::client(Some(DOWNLOAD_TIMEOUT), None);
+    let mut last_url = String::new();
+    while receiver.changed().await.is_ok() {
+        let url = receiver.borrow_and_update().artwork_url.clone();
+        if url == last_url || url.is_empty() {
+            continue;
+        }
+        last_url = url.clone();
+        let path = match cached_cover(&client, &url, &crate::cache::artwork_dir()).await {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::debug!("Couldn't cache the widget cover: {}", e);
+                continue;
+            }
+        };
+        let Some(sender) = sender.upgrade() else {
+            return;
+        };
+        sender.send_if_modified(|state| {
+            if state.artwork_url != url {
+                return false;
+            }
+            state.artwork_path = Some(path);
+            true
+        });
+    }
+}
+
+// Local covers are used where they are; downloads are kept under `dir`,
+// named after the URL so each is fetched once
+async fn cached_cover(client: &reqwest::Client, url: &str, dir: &Path) -> Result<PathBuf> {
+    if let Some(path) = url.strip_prefix("file://") {
+        let path = PathBuf::from(path);
+        return if path.exists() {
+            Ok(path)
+        } else {
+            Err(anyhow!("{} doesn't exist", path.display()))
+        };
+    }
+    let extension = Path::new(url.split(['?', '#']).next().unwrap_or(url))
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| ["jpg", "jpeg", "png", "webp"].contains(&ext.to_lowercase().as_str()))
+        .unwrap_or("jpg")
+        .to_lowercase();
+    let hash = format!("{:x}", Sha256::digest(url));
+    let path = dir.join(format!("widget-{}.{}", hash, extension));
+    if path.exists() {
+        return Ok(path);
+    }
+    crate::network::ensure_online("widget cover download")?;
+    let bytes = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    tokio::fs::create_dir_all(dir).await?;
+    tokio::fs::write(&path, &bytes).await?;
+    Ok(path)
+}
+
+/// Reads the running UI's updates from `path`, calling `each` for every
+/// one: just the current state, or with `follow` every change until amcli
+/// quits.
+pub async fn read(path: &Path, follow: bool, mut each: impl FnMut(&WidgetState)) -> Result<()> {
+    let stream = UnixStream::connect(path)
+        .await
+        .with_context(|| format!("amcli isn't running (no socket at {})", path.display()))?;
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(line) = lines.next_line().await? {
+        each(&serde_json::from_str(&line)?);
+        if !follow {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing() -> WidgetState {
+        let track = Track {
+            name: "It's Alright".into(),
+            artist: "Artist".into(),
+            album: "Album".into(),
+            duration: Duration::from_secs(200),
+            position: Duration::from_secs(20),
+        };
+        WidgetState::new(
+            PlaybackState::Playing,
+            Some(&track),
+            Some("https://example.com/a.jpg"),
+        )
+    }
+
+    #[test]
+    fn styles_match_what_each_tool_reads() {
+        let mut state = playing();
+        assert_eq!(
+            render(WidgetStyle::Sketchybar, &state),
+            r"drawing=on icon='▶' label='Artist — It'\''s Alright' background.image=off"
+        );
+        state.artwork_path = Some(PathBuf::from("/cache/widget-1.jpg"));
+        assert!(render(WidgetStyle::Sketchybar, &state)
+            .ends_with("background.image='/cache/widget-1.jpg'"));
+        assert_eq!(render(WidgetStyle::Plain, &state), "Artist — It's Alright");
+        let json: WidgetState =
+            serde_json::from_str(&render(WidgetStyle::Ubersicht, &state)).unwrap();
+        assert_eq!(json, state);
+
+        let stopped = WidgetState::new(PlaybackState::Playing, None, None);
+        assert_eq!(render(WidgetStyle::Sketchybar, &stopped), "drawing=off");
+        assert_eq!(render(WidgetStyle::Plain, &stopped), "");
+        assert!("bar".parse::<WidgetStyle>().is_err());
+    }
+
+    #[tokio::test]
+    async fn clients_get_the_current_state_then_changes() {
+        let path = std::env::temp_dir().join(format!("amcli-widget-{}.sock", std::process::id()));
+        let server = WidgetServer::spawn(path.clone()).unwrap();
+        server.publish(playing());
+
+        let mut seen = Vec::new();
+        read(&path, false, |state| seen.push(state.clone()))
+            .await
+            .unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].title, "It's Alright");
+
+        // Following ends when the server goes away
+        let follow = tokio::spawn({
+            let path = path.clone();
+            async move {
+                let mut states = Vec::new();
+                read(&path, true, |state| states.push(state.state.clone()))
+                    .await
+                    .unwrap();
+                states
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        server.publish(WidgetState::new(PlaybackState::Stopped, None, None));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(server);
+        let states = tokio::time::timeout(Duration::from_secs(5), follow)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(states, ["playing", "stopped"]);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -15,17 +15,17 @@ NC='\033[0m' # No Color
 
 # 1. Format check
 echo "📝 Checking code formatting..."
-if cargo fmt -- --check; then
+if cargo fmt --all -- --check; then
     echo -e "${GREEN}✓${NC} Code is properly formatted"
 else
-    echo -e "${YELLOW}⚠${NC} Code needs formatting. Run: cargo fmt"
+    echo -e "${YELLOW}⚠${NC} Code needs formatting. Run: cargo fmt --all"
     exit 1
 fi
 echo ""
 
 # 2. Clippy check
 echo "🔍 Running Clippy linter..."
-if cargo clippy --all-features -- -D warnings; then
+if cargo clippy --workspace --all-features -- -D warnings; then
     echo -e "${GREEN}✓${NC} Clippy checks passed"
 else
     echo -e "${RED}✗${NC} Clippy found issues"
@@ -35,7 +35,7 @@ echo ""
 
 # 3. Tests
 echo "🧪 Running tests..."
-if cargo test --all-features; then
+if cargo test --workspace --all-features; then
     echo -e "${GREEN}✓${NC} All tests passed"
 else
     echo -e "${RED}✗${NC} Some tests failed"
@@ -45,7 +45,7 @@ echo ""
 
 # 4. Build
 echo "🏗️ Building project..."
-if cargo build --all-features; then
+if cargo build --workspace --all-features; then
     echo -e "${GREEN}✓${NC} Build successful"
 else
     echo -e "${RED}✗${NC} Build failed"
@@ -55,7 +55,7 @@ echo ""
 
 # 5. Documentation
 echo "📚 Checking documentation..."
-if cargo doc --no-deps --all-features; then
+if cargo doc --workspace --no-deps --all-features; then
     echo -e "${GREEN}✓${NC} Documentation generated"
 else
     echo -e "${YELLOW}⚠${NC} Documentation has issues"
@@ -0,0 +1,228 @@
+use anyhow::{bail, Result};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+// How long `--takeover` waits for the old instance to notice and quit; it
+// checks the lock on every poll, at most 2s apart
+const TAKEOVER_WAIT: Duration = Duration::from_secs(5);
+// Tries at claiming the file, 10ms apart, while another instance is still
+// writing its PID into it or a stale one is being cleared
+const CLAIM_ATTEMPTS: u32 = 50;
+
+// A PID file claiming the player for this process. Two instances would poll
+// and control the same player against each other, so a second one refuses to
+// start, or with `--takeover` claims the file and the first one quits once it
+// sees it's no longer the owner.
+pub(crate) struct InstanceLock {
+    path: PathBuf,
+    pid: u32,
+}
+
+impl InstanceLock {
+    pub(crate) fn default_path() -> PathBuf {
+        dirs::runtime_dir()
+            .or_else(dirs::data_dir)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("amcli/amcli.pid")
+    }
+
+    pub(crate) async fn acquire(path: PathBuf, takeover: bool) -> Result<Self> {
+        Self::acquire_with(path, std::process::id(), takeover, TAKEOVER_WAIT).await
+    }
+
+    async fn acquire_with(path: PathBuf, pid: u32, takeover: bool, wait: Duration) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let Some(other) = claim(&path, pid).await? else {
+            return Ok(Self { path, pid });
+        };
+        if !takeover {
+            bail!(
+                "amcli is already running (pid {}). Use --takeover to replace it.",
+                other
+            );
+        }
+
+        // Swapped in whole, so the old instance never reads a partial PID
+        let staged = path.with_extension(format!("pid.{}", pid));
+        tokio::fs::write(&staged, pid.to_string()).await?;
+        tokio::fs::rename(&staged, &path).await?;
+
+        eprintln!("Waiting for amcli (pid {}) to quit…", other);
+        let deadline = Instant::now() + wait;
+        while is_running(other) && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        if is_running(other) {
+            tracing::warn!("amcli (pid {}) is still running after takeover", other);
+        }
+        Ok(Self { path, pid })
+    }
+
+    // False once another instance has taken over. A file someone deleted
+    // doesn't count as a takeover.
+    pub(crate) async fn is_held(&self) -> bool {
+        read_pid(&self.path).await.is_none_or(|pid| pid == self.pid)
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let owned = std::fs::read_to_string(&self.path)
+            .is_ok_and(|content| content.trim().parse() == Ok(self.pid));
+        if owned {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+// Creates the file for `pid`, or returns the running instance holding it. A
+// file left by a crash, or by a process that has since gone, is stale and
+// removed, but only while it still names that process, so a fresh claim made
+// in between isn't swept away with it.
+async fn claim(path: &Path, pid: u32) -> Result<Option<u32>> {
+    let mut unreadable = 0;
+    for _ in 0..CLAIM_ATTEMPTS {
+        match create(path, pid).await {
+            Ok(()) => return Ok(None),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+        match read_pid(path).await {
+            Some(other) if other == pid => return Ok(None),
+            Some(other) if is_running(other) => return Ok(Some(other)),
+            Some(other) => {
+                if read_pid(path).await == Some(other) {
+                    remove(path).await?;
+                }
+            }
+            // Created but not yet written, unless it stays that way
+            None => {
+                unreadable += 1;
+                if unreadable == CLAIM_ATTEMPTS / 2 {
+                    remove(path).await?;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    }
+    bail!("Couldn't claim {}", path.display())
+}
+
+// Fails with AlreadyExists when the file is there, however close the race
+async fn create(path: &Path, pid: u32) -> std::io::Result<()> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .await?;
+    // tokio writes in the background until flushed, and the PID has to be
+    // readable by the time this returns
+    let written = match file.write_all(pid.to_string().as_bytes()).await {
+        Ok(()) => file.flush().await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = written {
+        let _ = tokio::fs::remove_file(path).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+async fn remove(path: &Path) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+async fn read_pid(path: &Path) -> Option<u32> {
+    tokio::fs::read_to_string(path)
+        .await
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+// Signal 0 sends nothing; it only reports whether the process exists. EPERM
+// means it does, under another user; ESRCH that it's gone.
+fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: kill with signal 0 only checks the target; nothing is delivered
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn a_second_instance_needs_takeover() {
+        let path = std::env::temp_dir().join(format!("amcli-lock-{}.pid", std::process::id()));
+        let mut other = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        tokio::fs::write(&path, other.id().to_string())
+            .await
+            .unwrap();
+
+        let err = InstanceLock::acquire(path.clone(), false)
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("already running"));
+
+        let lock =
+            InstanceLock::acquire_with(path.clone(), std::process::id(), true, Duration::ZERO)
+                .await
+                .unwrap();
+        assert!(lock.is_held().await);
+        // The old instance would now see it lost the lock
+        tokio::fs::write(&path, other.id().to_string())
+            .await
+            .unwrap();
+        assert!(!lock.is_held().await);
+        drop(lock);
+        assert!(path.exists());
+
+        // Once it's gone, its leftover file doesn't block anyone
+        other.kill().unwrap();
+        other.wait().unwrap();
+        let lock = InstanceLock::acquire(path.clone(), false).await.unwrap();
+        drop(lock);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn only_one_of_two_instances_starting_together_gets_the_lock() {
+        let path = std::env::temp_dir().join(format!("amcli-race-{}.pid", std::process::id()));
+        let mut other = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let (a, b) = tokio::join!(
+            InstanceLock::acquire_with(path.clone(), std::process::id(), false, Duration::ZERO),
+            InstanceLock::acquire_with(path.clone(), other.id(), false, Duration::ZERO),
+        );
+        assert!(a.is_ok() != b.is_ok());
+        drop((a, b));
+
+        // A file that never gets a PID is cleared rather than blocking forever
+        tokio::fs::write(&path, "").await.unwrap();
+        let lock = InstanceLock::acquire(path.clone(), false).await.unwrap();
+        assert!(lock.is_held().await);
+        drop(lock);
+        other.kill().unwrap();
+        other.wait().unwrap();
+    }
+}
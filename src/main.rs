@@ -1,25 +1,33 @@
 // src/main.rs
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event, KeyCode,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
-    backend::{Backend, CrosstermBackend},
+    backend::{Backend, CrosstermBackend, TestBackend},
     Terminal,
 };
 use std::io;
 
-mod artwork;
-mod config;
-mod lyrics;
-mod player;
+mod instance;
+mod signals;
 mod terminal_title;
 mod ui;
 
+use amcli_core::session::Session;
+use amcli_core::{cache, config, history, network, player, playlist, scrobble, update, widget};
+
+use crate::instance::InstanceLock;
+use crate::signals::Received;
+use crate::ui::events::AppEvent;
 use crate::ui::App;
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand};
+use std::path::PathBuf;
 use terminal_title::TerminalTitle;
 
 #[derive(Parser, Debug)]
@@ -27,18 +35,557 @@ use terminal_title::TerminalTitle;
 struct Args {
     #[arg(short, long)]
     config: Option<String>,
+
+    /// Play a built-in demo playlist instead of a real player
+    #[arg(long)]
+    demo: bool,
+
+    /// Log every player response to a session file, for bug reports
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Play back a session recorded with --record instead of a real player
+    #[arg(long, value_name = "FILE", conflicts_with = "demo")]
+    replay: Option<PathBuf>,
+
+    /// Skip online artwork and lyrics lookups; cached covers and local
+    /// lyrics providers still work
+    #[arg(long)]
+    offline: bool,
+
+    /// Use the named profile's config.<NAME>.toml instead of config.toml,
+    /// creating it from config.toml the first time
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Replace an amcli that is already running instead of refusing to start
+    #[arg(long)]
+    takeover: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Write the current Up Next queue to an M3U/M3U8 playlist, or CSV/JSON by extension
+    ExportQueue { path: PathBuf },
+    /// Render one frame to an ANSI text file, or standalone HTML for .html paths
+    Snapshot {
+        path: PathBuf,
+        /// Columns to render, defaulting to the current terminal's
+        #[arg(long)]
+        width: Option<u16>,
+        /// Rows to render, defaulting to the current terminal's
+        #[arg(long)]
+        height: Option<u16>,
+    },
+    /// Print a completion script, e.g. `amcli completions zsh > _amcli`
+    Completions { shell: clap_complete::Shell },
+    /// Print the man page as roff, e.g. `amcli man > amcli.1`
+    Man,
+    /// Download the latest GitHub release and replace this binary with it
+    SelfUpdate,
+    /// Read or change settings without opening the UI
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Print what the running amcli is playing for sketchybar, Übersicht, or
+    /// a plain status line
+    Widget {
+        /// sketchybar, ubersicht, or plain
+        #[arg(long, default_value = "plain")]
+        style: widget::WidgetStyle,
+        /// Keep printing a line per change until amcli quits
+        #[arg(long)]
+        follow: bool,
+    },
+    /// Show or clear what amcli has cached on disk
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Work with the log of what amcli has played
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
+    },
+    /// Export the play history, or send it to Last.fm or ListenBrainz
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    /// Allow amcli on Last.fm in the browser and save the session key that
+    /// `history backfill` scrobbles with
+    LastfmLogin,
+    /// Sign in to Spotify in the browser and cache credentials for the Spotify backend
+    #[cfg(feature = "spotify")]
+    SpotifyLogin,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print a setting or section, e.g. `ui.color_theme` or `artwork.crt`
+    Get { key: String },
+    /// Change a setting, e.g. `artwork.mosaic false`
+    Set { key: String, value: String },
+    /// Open the config file in $VISUAL or $EDITOR, then check it still loads
+    Edit,
+    /// Print where the config file is
+    Path,
+}
+
+#[derive(Subcommand, Debug)]
+enum SessionAction {
+    /// Write a day's plays as a timed setlist, to a file or stdout
+    Export {
+        /// Where to write it; Markdown for .md paths
+        path: Option<PathBuf>,
+        /// The day to export as YYYY-MM-DD, defaulting to today
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+        /// text or markdown, overriding the extension
+        #[arg(long)]
+        format: Option<history::SetlistFormat>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryAction {
+    /// Write plays as CSV or JSON, to a file or stdout
+    Export {
+        /// Where to write it; JSON for .json paths
+        path: Option<PathBuf>,
+        /// csv or json, overriding the extension
+        #[arg(long)]
+        format: Option<history::HistoryFormat>,
+        /// The first day to include as YYYY-MM-DD
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// The last day to include as YYYY-MM-DD
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+    },
+    /// Submit plays to Last.fm or ListenBrainz, e.g. ones made while offline
+    Backfill {
+        /// listenbrainz or lastfm
+        service: scrobble::Service,
+        /// The first day to send as YYYY-MM-DD
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// The last day to send as YYYY-MM-DD
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+        /// Send the plays in a JSON export instead of the history
+        #[arg(long)]
+        from: Option<PathBuf>,
+        /// List what would be sent without sending it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// Print where the cache is and how much each category takes up
+    Stats,
+    /// Delete cached files: everything, or one category such as `artwork`
+    Clear { category: Option<String> },
+}
+
+fn run_cache_command(action: &CacheAction) -> Result<()> {
+    let root = cache::root();
+    match action {
+        CacheAction::Stats => {
+            println!("{}", root.display());
+            let usage = cache::usage(&root);
+            for entry in &usage {
+                println!(
+                    "  {:<10}{:>8} files {:>10}",
+                    entry.category,
+                    entry.files,
+                    format_bytes(entry.bytes)
+                );
+            }
+            let bytes = usage.iter().map(|entry| entry.bytes).sum();
+            println!("  {:<10}{:>25}", "total", format_bytes(bytes));
+        }
+        CacheAction::Clear { category } => {
+            let freed = cache::clear(&root, category.as_deref())?;
+            let bytes = freed.iter().map(|entry| entry.bytes).sum();
+            println!("Freed {} from {}", format_bytes(bytes), root.display());
+        }
+    }
+    Ok(())
+}
+
+async fn run_session_command(action: &SessionAction) -> Result<()> {
+    let SessionAction::Export { path, date, format } = action;
+    let date = date.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let plays = history::PlayHistory::new(history::PlayHistory::default_path())
+        .day(date)
+        .await?;
+    if plays.is_empty() {
+        anyhow::bail!("Nothing was played on {}", date);
+    }
+    let markdown = path
+        .as_ref()
+        .and_then(|p| p.extension())
+        .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"));
+    let format = format.unwrap_or(if markdown {
+        history::SetlistFormat::Markdown
+    } else {
+        history::SetlistFormat::Text
+    });
+    let setlist = history::format_setlist(&plays, format);
+    match path {
+        Some(path) => {
+            tokio::fs::write(path, setlist).await?;
+            println!("Exported {} tracks to {}", plays.len(), path.display());
+        }
+        None => print!("{}", setlist),
+    }
+    Ok(())
+}
+
+async fn run_history_command(action: &HistoryAction, profile: Option<&str>) -> Result<()> {
+    let log = history::PlayHistory::new(history::PlayHistory::default_path());
+    match action {
+        HistoryAction::Export {
+            path,
+            format,
+            since,
+            until,
+        } => {
+            let plays = log.between(*since, *until).await?;
+            let json = path
+                .as_ref()
+                .and_then(|p| p.extension())
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+            let format = format.unwrap_or(if json {
+                history::HistoryFormat::Json
+            } else {
+                history::HistoryFormat::Csv
+            });
+            let contents = history::format_plays(&plays, format)?;
+            match path {
+                Some(path) => {
+                    tokio::fs::write(path, contents).await?;
+                    println!("Exported {} plays to {}", plays.len(), path.display());
+                }
+                None => print!("{}", contents),
+            }
+        }
+        HistoryAction::Backfill {
+            service,
+            since,
+            until,
+            from,
+            dry_run,
+        } => {
+            let plays = match from {
+                Some(path) => history::read_export(path).await?,
+                None => log.between(*since, *until).await?,
+            };
+            if plays.is_empty() {
+                anyhow::bail!("No plays to send");
+            }
+            if *dry_run {
+                let sendable = scrobble::submittable(*service, &plays);
+                for play in &sendable {
+                    let at = play
+                        .started()
+                        .map(|at| at.format("%Y-%m-%d %H:%M").to_string());
+                    println!(
+                        "{}  {} – {}",
+                        at.unwrap_or_default(),
+                        play.artist,
+                        play.name
+                    );
+                }
+                println!(
+                    "{} of {} plays would go to {}",
+                    sendable.len(),
+                    plays.len(),
+                    service.name()
+                );
+                return Ok(());
+            }
+            let config = config::Config::load_profile(profile).await?;
+            network::configure(&config.network)?;
+            let result = scrobble::backfill(*service, &config, &plays).await?;
+            println!(
+                "{} accepted {} of {} plays",
+                service.name(),
+                result.accepted,
+                plays.len()
+            );
+            if result.ignored > 0 {
+                println!("{} were turned down by {}", result.ignored, service.name());
+            }
+            if result.skipped > 0 {
+                println!(
+                    "{} were skipped as too short or missing an artist",
+                    result.skipped
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn lastfm_login(profile: Option<&str>) -> Result<()> {
+    let mut config = config::Config::load_profile(profile).await?;
+    network::configure(&config.network)?;
+    let account = scrobble::LastFm::from_config(&config)?;
+    let (token, url) = account.request_token().await?;
+    println!("Allow amcli on Last.fm, then press Enter:\n{}", url);
+    tokio::task::spawn_blocking(|| io::stdin().read_line(&mut String::new())).await??;
+    let (key, name) = account.session(&token).await?;
+    config.scrobble.lastfm_session_key = Some(key);
+    config.save().await?;
+    println!("Signed in to Last.fm as {}", name);
+    Ok(())
+}
+
+// "1.4 MB"
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+async fn run_config_command(action: &ConfigAction, profile: Option<&str>) -> Result<()> {
+    match action {
+        ConfigAction::Get { key } => {
+            let config = config::Config::load_profile(profile).await?;
+            match config.get(key)? {
+                toml::Value::String(text) => println!("{}", text),
+                toml::Value::Table(table) => print!("{}", toml::to_string_pretty(&table)?),
+                value => println!("{}", value),
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            let mut config = config::Config::load_profile(profile).await?;
+            config.set(key, value)?;
+            config.save().await?;
+        }
+        ConfigAction::Edit => {
+            // Writes the defaults first when there's no file yet
+            config::Config::load_profile(profile).await?;
+            let path = config::Config::path(profile).await?;
+            let editor = std::env::var("VISUAL")
+                .or_else(|_| std::env::var("EDITOR"))
+                .unwrap_or_else(|_| "vi".into());
+            // Run through the shell so editors with flags ("code --wait") work
+            let status = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(format!("{} \"$1\"", editor))
+                .arg(&editor)
+                .arg(&path)
+                .status()?;
+            if !status.success() {
+                anyhow::bail!("{} exited with {}", editor, status);
+            }
+            config::Config::load_profile(profile)
+                .await
+                .map_err(|e| anyhow::anyhow!("{} no longer loads: {}", path.display(), e))?;
+        }
+        ConfigAction::Path => println!("{}", config::Config::path(profile).await?.display()),
+    }
+    Ok(())
+}
+
+async fn self_update() -> Result<()> {
+    let updater = update::Updater::default();
+    let release = updater.latest().await?;
+    if !release.is_newer_than(update::CURRENT_VERSION) {
+        println!("amcli {} is up to date.", update::CURRENT_VERSION);
+        return Ok(());
+    }
+    let exe = std::env::current_exe()?;
+    println!(
+        "Updating amcli {} to {}…",
+        update::CURRENT_VERSION,
+        release.tag_name
+    );
+    updater.install(&release, &exe).await?;
+    println!("Installed {} at {}", release.tag_name, exe.display());
+    Ok(())
+}
+
+// The configured backend, or the demo or replay player, wrapped in a recorder
+// when `--record` is given
+async fn open_player(args: &Args, config: &config::Config) -> Result<Box<dyn player::MediaPlayer>> {
+    let player: Box<dyn player::MediaPlayer> = if let Some(path) = &args.replay {
+        Box::new(player::recording::ReplayPlayer::load(path).await?)
+    } else if args.demo {
+        Box::new(player::demo::DemoPlayer::new())
+    } else {
+        player::create_player(&config.player).await?
+    };
+    match &args.record {
+        Some(path) => Ok(Box::new(
+            player::recording::RecordingPlayer::create(player, path).await?,
+        )),
+        None => Ok(player),
+    }
 }
 
+// How long `amcli snapshot` waits for artwork before rendering without it
+const SNAPSHOT_ARTWORK_WAIT: std::time::Duration = std::time::Duration::from_secs(5);
+
 fn restore_terminal() {
     let _ = disable_raw_mode();
-    let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange
+    );
+    let _ = terminal_title::restore_title();
+}
+
+// Ctrl+Z: hand the terminal back to the shell, stop until `fg`, then take it
+// over again and redraw everything
+fn suspend<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
+where
+    <B as Backend>::Error: Send + Sync + 'static,
+{
+    restore_terminal();
+    terminal.show_cursor()?;
+    signals::stop();
+    terminal_title::save_title()?;
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
+    terminal.clear()?;
+    app.repaint();
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let _args = Args::parse();
+    let args = Args::parse();
     tracing_subscriber::fmt::init();
 
+    match &args.command {
+        Some(Commands::ExportQueue { path }) => {
+            let config = config::Config::load_profile(args.profile.as_deref()).await?;
+            network::configure(&config.network)?;
+            let player = open_player(&args, &config).await?;
+            let queue = player.get_queue().await?;
+            playlist::write_export(path, &queue).await?;
+            println!("Exported {} tracks to {}", queue.len(), path.display());
+            return Ok(());
+        }
+        Some(Commands::Snapshot {
+            path,
+            width,
+            height,
+        }) => {
+            let config = config::Config::load_profile(args.profile.as_deref()).await?;
+            network::configure(&config.network)?;
+            cache::configure(&config.general);
+            if args.offline {
+                network::set_offline(true);
+            }
+            let player = open_player(&args, &config).await?;
+            let mut app = if args.demo {
+                App::demo(player, config).await?
+            } else {
+                App::with_player_and_config(player, config).await?
+            };
+            app.skip_splash();
+            app.update().await?;
+            // Give the cover a moment to arrive
+            let deadline = std::time::Instant::now() + SNAPSHOT_ARTWORK_WAIT;
+            while app.is_loading_artwork() && std::time::Instant::now() < deadline {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                app.update().await?;
+            }
+            let (columns, rows) = crossterm::terminal::size().unwrap_or((120, 40));
+            let backend = TestBackend::new(width.unwrap_or(columns), height.unwrap_or(rows));
+            let mut terminal = Terminal::new(backend)?;
+            let frame = terminal.draw(|f| ui::draw(f, &mut app))?;
+            ui::snapshot::write(path, frame.buffer).await?;
+            println!("Saved snapshot to {}", path.display());
+            return Ok(());
+        }
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(*shell, &mut Args::command(), "amcli", &mut io::stdout());
+            return Ok(());
+        }
+        Some(Commands::Man) => {
+            clap_mangen::Man::new(Args::command()).render(&mut io::stdout())?;
+            return Ok(());
+        }
+        Some(Commands::Config { action }) => {
+            return run_config_command(action, args.profile.as_deref()).await;
+        }
+        Some(Commands::Widget { style, follow }) => {
+            // Nothing to show when amcli isn't running, which bars treat as
+            // stopped rather than an error
+            let result = widget::read(&widget::socket_path(), *follow, |state| {
+                println!("{}", widget::render(*style, state));
+            })
+            .await;
+            if let Err(e) = result {
+                tracing::debug!("{:#}", e);
+                if !*follow {
+                    let stopped =
+                        widget::WidgetState::new(player::PlaybackState::Stopped, None, None);
+                    println!("{}", widget::render(*style, &stopped));
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Cache { action }) => {
+            let config = config::Config::load_profile(args.profile.as_deref()).await?;
+            cache::configure(&config.general);
+            return run_cache_command(action);
+        }
+        Some(Commands::Session { action }) => return run_session_command(action).await,
+        Some(Commands::History { action }) => {
+            return run_history_command(action, args.profile.as_deref()).await
+        }
+        Some(Commands::LastfmLogin) => return lastfm_login(args.profile.as_deref()).await,
+        Some(Commands::SelfUpdate) => {
+            let config = config::Config::load_profile(args.profile.as_deref()).await?;
+            network::configure(&config.network)?;
+            cache::configure(&config.general);
+            return self_update().await;
+        }
+        #[cfg(feature = "spotify")]
+        Some(Commands::SpotifyLogin) => {
+            let username = player::spotify::login().await?;
+            println!("Signed in to Spotify as {}", username);
+            return Ok(());
+        }
+        None => {}
+    }
+
+    // One instance drives the player at a time; the demo and replays don't
+    // touch it
+    let lock = if args.demo || args.replay.is_some() {
+        None
+    } else {
+        Some(InstanceLock::acquire(InstanceLock::default_path(), args.takeover).await?)
+    };
+
     // Ensure terminal is restored even on panic
     let default_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
@@ -47,15 +594,39 @@ async fn main() -> Result<()> {
     }));
 
     // Setup terminal
+    terminal_title::save_title()?;
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
+    let mut signals = signals::listen()?;
 
-    // Create app and run it
-    let app = App::new().await?;
-    let res = run_app(&mut terminal, app).await;
+    // Create app and run it. Picking another profile drops the app, with its
+    // player and integrations, and starts over from that profile's config.
+    let mut profile = args.profile.clone();
+    let res = loop {
+        let mut app = match start_app(&args, profile.as_deref()).await {
+            Ok(app) => app,
+            Err(e) => break Err(e),
+        };
+        if let Err(e) = run_app(&mut terminal, &mut app, lock.as_ref(), &mut signals).await {
+            break Err(e);
+        }
+        match app.take_profile_switch() {
+            Some(next) => profile = next,
+            None => break Ok(()),
+        }
+        drop(app);
+        if let Err(e) = terminal.clear() {
+            break Err(e.into());
+        }
+    };
 
     // Restore terminal
     restore_terminal();
@@ -64,79 +635,141 @@ async fn main() -> Result<()> {
     if let Err(err) = res {
         println!("Error: {:?}", err);
     }
+    if let Some(lock) = &lock {
+        if !lock.is_held().await {
+            println!("Another amcli took over the player.");
+        }
+    }
 
     Ok(())
 }
 
-async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<()>
+// Loads `profile`'s config and applies its `[startup]` options
+async fn start_app(args: &Args, profile: Option<&str>) -> Result<App> {
+    let config = config::Config::load_profile(profile).await?;
+    network::configure(&config.network)?;
+    cache::configure(&config.general);
+    if args.offline {
+        network::set_offline(true);
+    }
+    let player = open_player(args, &config).await?;
+    if config.startup.launch_player {
+        if let Err(e) = player.launch().await {
+            tracing::warn!("Couldn't launch the player: {}", e);
+        }
+    }
+    let session = if config.startup.restore_session {
+        Session::load(&Session::path(profile))
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Couldn't read the saved session: {}", e);
+                None
+            })
+    } else {
+        None
+    };
+    // Apple Music, Podcasts, and Spotify already show up in the desktop's
+    // media controls on their own
+    let serve_mpris = cfg!(target_os = "linux")
+        && config.player.mpris
+        && matches!(
+            config.player.backend,
+            config::PlayerBackend::Internal | config::PlayerBackend::Subsonic
+        );
+    let mut app = if args.demo {
+        App::demo(player, config).await?
+    } else {
+        App::with_player_and_config(player, config).await?
+    };
+    app.apply_startup(session).await;
+    if !args.demo && args.replay.is_none() {
+        app.serve_widget();
+        app.log_history();
+        if serve_mpris {
+            app.serve_mpris().await;
+        }
+    }
+    Ok(app)
+}
+
+async fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    lock: Option<&InstanceLock>,
+    signals: &mut tokio::sync::mpsc::UnboundedReceiver<Received>,
+) -> Result<()>
 where
     <B as Backend>::Error: Send + Sync + 'static,
 {
     let mut last_update = std::time::Instant::now();
     let update_interval = std::time::Duration::from_millis(500);
+    // While the terminal is in the background only keep track state fresh
+    let background_update_interval = std::time::Duration::from_secs(2);
     let mut terminal_title = TerminalTitle::new();
     terminal_title.sync(app.get_current_track())?;
 
     loop {
-        if app.take_needs_full_repaint() {
+        while let Ok(received) = signals.try_recv() {
+            match received {
+                Received::Quit => app.publish(AppEvent::Quit),
+                Received::Suspend => {
+                    suspend(terminal, app)?;
+                    // The title was popped while stopped
+                    terminal_title = TerminalTitle::new();
+                }
+            }
+        }
+
+        if app.should_quit() {
+            if app.restores_session() {
+                if let Err(e) = app.session().save(&Session::path(app.profile())).await {
+                    tracing::warn!("Couldn't save the session: {}", e);
+                }
+            }
+            return Ok(());
+        }
+
+        let full_repaint = app.take_needs_full_repaint();
+        if full_repaint {
             terminal.clear()?;
         }
 
-        terminal.draw(|f| ui::draw(f, &mut app))?;
+        if app.is_focused() {
+            app.advance_artwork_animation();
+        }
+        // Skip drawing while nothing on screen has changed since the last frame
+        if app.is_focused() && (app.needs_redraw() || full_repaint) {
+            let started = std::time::Instant::now();
+            let frame = terminal.draw(|f| ui::draw(f, app))?;
+            app.record_frame_time(started.elapsed());
+            if let Some(path) = app.take_snapshot_request() {
+                app.save_snapshot(&path, frame.buffer).await;
+            }
+        }
+
+        // A chord prefix that is also bound on its own fires once the timeout passes
+        if let Some(action) = app.resolve_pending_key() {
+            app.perform(action).await;
+        }
 
         if event::poll(std::time::Duration::from_millis(50))? {
-            match event::read()? {
+            let event = event::read()?;
+            app.mark_dirty();
+            match event {
                 Event::Key(key) => {
-                    // Check for Ctrl+C first
-                    if key.code == KeyCode::Char('c')
+                    // Suspending needs the terminal; everything else is the app's
+                    if key.code == KeyCode::Char('z')
                         && key.modifiers.contains(event::KeyModifiers::CONTROL)
                     {
-                        return Ok(());
-                    }
-
-                    // Handle settings menu navigation if open
-                    if app.is_settings_open() {
-                        match key.code {
-                            KeyCode::Esc | KeyCode::Char('s') | KeyCode::Char('S') => {
-                                app.close_settings();
-                            }
-                            KeyCode::Up | KeyCode::Char('k') => {
-                                app.settings_navigate_up();
-                            }
-                            KeyCode::Down | KeyCode::Char('j') => {
-                                app.settings_navigate_down();
-                            }
-                            KeyCode::Enter | KeyCode::Char(' ') => {
-                                app.settings_select().await?;
-                            }
-                            _ => {}
-                        }
+                        suspend(terminal, app)?;
+                        terminal_title = TerminalTitle::new();
                     } else {
-                        // Normal app controls when settings not open
-                        match key.code {
-                            KeyCode::Char('q') => return Ok(()),
-                            KeyCode::Char('s') | KeyCode::Char('S') => app.toggle_settings_menu(),
-                            KeyCode::Char(' ') => app.toggle_playback().await?,
-                            KeyCode::Char(']') => app.next_track().await?,
-                            KeyCode::Char('[') => app.previous_track().await?,
-                            KeyCode::Char('=') | KeyCode::Char('+') => app.volume_up().await?,
-                            KeyCode::Char('-') | KeyCode::Char('_') => app.volume_down().await?,
-                            KeyCode::Char('m') => app.toggle_mute().await?,
-                            KeyCode::Right => app.seek_forward().await?,
-                            KeyCode::Left => app.seek_backward().await?,
-                            KeyCode::Char('.') => app.seek_forward().await?,
-                            KeyCode::Char(',') => app.seek_backward().await?,
-                            KeyCode::Char('k') | KeyCode::Up => app.navigate_up(),
-                            KeyCode::Char('j') | KeyCode::Down => app.navigate_down(),
-                            KeyCode::Char('h') => app.navigate_left(),
-                            KeyCode::Char('l') => app.navigate_right(),
-                            KeyCode::Char('r') => app.cycle_repeat().await?,
-                            KeyCode::Char('t') => app.next_theme().await?,
-                            KeyCode::Char('?') => app.toggle_help(),
-                            _ => {}
-                        }
+                        app.publish(AppEvent::Key(key));
                     }
                 }
+                Event::Resize(..) => app.handle_resize(),
+                Event::FocusGained => app.set_focused(true),
+                Event::FocusLost => app.set_focused(false),
                 Event::Mouse(_mouse) => {
                     // Mouse support placeholder - we'll implement detailed handling next
                     // For now, we just consume the event
@@ -145,10 +778,73 @@ where
             }
         }
 
-        if last_update.elapsed() >= update_interval {
+        // Keys, signals, and remote commands published since the last pass
+        app.handle_events().await?;
+
+        let interval = if app.is_focused() {
+            update_interval
+        } else {
+            background_update_interval
+        };
+        if last_update.elapsed() >= interval {
+            let started = std::time::Instant::now();
             app.update().await?;
+            app.record_update_time(started.elapsed());
             terminal_title.sync(app.get_current_track())?;
             last_update = std::time::Instant::now();
+            // Another instance started with --takeover
+            if let Some(lock) = lock {
+                if !lock.is_held().await {
+                    app.quit();
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completions_and_man_page_cover_every_subcommand() {
+        Args::command().debug_assert();
+
+        let mut script = Vec::new();
+        clap_complete::generate(
+            clap_complete::Shell::Zsh,
+            &mut Args::command(),
+            "amcli",
+            &mut script,
+        );
+        let script = String::from_utf8(script).unwrap();
+        let mut page = Vec::new();
+        clap_mangen::Man::new(Args::command())
+            .render(&mut page)
+            .unwrap();
+        let page = String::from_utf8(page).unwrap();
+        for name in [
+            "export-queue",
+            "snapshot",
+            "config",
+            "cache",
+            "session",
+            "history",
+            "lastfm-login",
+            "completions",
+            "man",
+        ] {
+            assert!(script.contains(name), "{}", name);
+            // roff escapes hyphens
+            assert!(page.contains(&name.replace('-', "\\-")), "{}", name);
+        }
+        assert!(script.contains("--takeover"));
+    }
+
+    #[test]
+    fn sizes_read_in_binary_units() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 + 400 * 1024), "3.4 MB");
+    }
+}
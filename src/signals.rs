@@ -0,0 +1,61 @@
+use anyhow::Result;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+// What the event loop should do about a signal it was sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Received {
+    // SIGTERM or SIGHUP (the terminal window closed): quit as if `q` was
+    // pressed, so the terminal and session are put back
+    Quit,
+    // SIGTSTP from `kill -TSTP`; Ctrl+Z itself arrives as a key in raw mode
+    Suspend,
+}
+
+// Listens in the background; the loop drains the receiver between frames
+pub(crate) fn listen() -> Result<UnboundedReceiver<Received>> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    let mut suspend = signal(SignalKind::from_raw(libc::SIGTSTP))?;
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let received = tokio::select! {
+                _ = terminate.recv() => Received::Quit,
+                _ = hangup.recv() => Received::Quit,
+                _ = suspend.recv() => Received::Suspend,
+            };
+            if tx.send(received).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(rx)
+}
+
+// Stops the process the way the shell's Ctrl+Z would. SIGTSTP has a handler
+// now, so this sends SIGSTOP, which can't be caught; it returns after `fg`.
+pub(crate) fn stop() {
+    // SAFETY: raise only sends a signal to the calling thread's process
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn termination_signals_ask_to_quit() {
+        let mut rx = listen().unwrap();
+        // SAFETY: the handler installed by listen() catches SIGHUP
+        unsafe {
+            libc::raise(libc::SIGHUP);
+        }
+        let received = tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(received, Some(Received::Quit));
+    }
+}
@@ -1,7 +1,11 @@
-use crate::player::Track;
+use amcli_core::player::Track;
 use crossterm::{execute, terminal::SetTitle};
 use std::io::{self, Write};
 
+// XTWINOPS title stack: push the user's title on startup, pop it on exit.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
 pub(crate) struct TerminalTitle {
     last_title: Option<String>,
 }
@@ -32,9 +36,30 @@ impl TerminalTitle {
     }
 }
 
+pub(crate) fn save_title() -> io::Result<()> {
+    write_sequence(io::stdout(), PUSH_TITLE)
+}
+
+pub(crate) fn restore_title() -> io::Result<()> {
+    write_sequence(io::stdout(), POP_TITLE)
+}
+
+fn write_sequence<W: Write>(mut writer: W, sequence: &str) -> io::Result<()> {
+    writer.write_all(sequence.as_bytes())?;
+    writer.flush()
+}
+
 pub(crate) fn title_for_track(track: Option<&Track>) -> String {
     match track {
-        Some(track) if !track.name.trim().is_empty() => format!("AMCLI: {}", track.name.trim()),
+        Some(track) if !track.name.trim().is_empty() => {
+            let name = track.name.trim();
+            let artist = track.artist.trim();
+            if artist.is_empty() {
+                format!("▶ {}", name)
+            } else {
+                format!("▶ {} – {}", artist, name)
+            }
+        }
         _ => "AMCLI".to_string(),
     }
 }
@@ -42,7 +67,7 @@ pub(crate) fn title_for_track(track: Option<&Track>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::player::Track;
+    use amcli_core::player::Track;
     use std::time::Duration;
 
     fn track(name: &str) -> Track {
@@ -56,8 +81,23 @@ mod tests {
     }
 
     #[test]
-    fn formats_current_track_as_amcli_title() {
-        assert_eq!(title_for_track(Some(&track("小情歌"))), "AMCLI: 小情歌");
+    fn formats_current_track_as_artist_and_title() {
+        assert_eq!(title_for_track(Some(&track("小情歌"))), "▶ Artist – 小情歌");
+    }
+
+    #[test]
+    fn formats_track_without_artist_as_title_only() {
+        let mut track = track("Untitled Demo");
+        track.artist = " ".into();
+        assert_eq!(title_for_track(Some(&track)), "▶ Untitled Demo");
+    }
+
+    #[test]
+    fn title_stack_sequences_push_and_pop() {
+        let mut output = Vec::new();
+        write_sequence(&mut output, PUSH_TITLE).unwrap();
+        write_sequence(&mut output, POP_TITLE).unwrap();
+        assert_eq!(output, b"\x1b[22;0t\x1b[23;0t");
     }
 
     #[test]
@@ -84,7 +124,7 @@ mod tests {
             .sync_with_writer(&mut output, Some(&track("Second Song")))
             .unwrap());
         let rendered = String::from_utf8(output).unwrap();
-        assert!(rendered.contains("AMCLI: First Song"));
-        assert!(rendered.contains("AMCLI: Second Song"));
+        assert!(rendered.contains("▶ Artist – First Song"));
+        assert!(rendered.contains("▶ Artist – Second Song"));
     }
 }
@@ -0,0 +1,189 @@
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDateTime, NaiveTime, Weekday};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use amcli_core::config::{Alarm, AlarmAction};
+
+// The wait is re-checked against the wall clock at least this often, so a
+// Mac waking from sleep doesn't fire late or skip an alarm
+const MAX_WAIT: Duration = Duration::from_secs(30);
+
+// An `[[alarms]]` entry with its time and days parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledAlarm {
+    pub time: NaiveTime,
+    days: Vec<Weekday>,
+    pub action: AlarmAction,
+    pub playlist: Option<String>,
+}
+
+impl ScheduledAlarm {
+    fn parse(alarm: &Alarm) -> Result<Self> {
+        let time = NaiveTime::parse_from_str(alarm.at.trim(), "%H:%M")
+            .map_err(|_| anyhow!("Expected HH:MM, got {:?}", alarm.at))?;
+        let days = alarm
+            .days
+            .iter()
+            .map(|day| {
+                day.parse::<Weekday>()
+                    .map_err(|_| anyhow!("Unknown day {:?}", day))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            time,
+            days,
+            action: alarm.action,
+            playlist: alarm.playlist.clone().filter(|p| !p.trim().is_empty()),
+        })
+    }
+
+    // The first time strictly after `now` this alarm goes off
+    fn next_after(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        (0..=7)
+            .map(|offset| (now.date() + ChronoDuration::days(offset)).and_time(self.time))
+            .find(|at| *at > now && (self.days.is_empty() || self.days.contains(&at.weekday())))
+    }
+
+    // "07:00 ▶ Morning", "23:00 ⏸"
+    pub fn describe(&self) -> String {
+        let glyph = match self.action {
+            AlarmAction::Play => "▶",
+            AlarmAction::Pause => "⏸",
+        };
+        match &self.playlist {
+            Some(playlist) => format!("{} {} {}", self.time.format("%H:%M"), glyph, playlist),
+            None => format!("{} {}", self.time.format("%H:%M"), glyph),
+        }
+    }
+}
+
+// The soonest alarm after `now`, and when
+pub fn next_alarm(
+    alarms: &[ScheduledAlarm],
+    now: NaiveDateTime,
+) -> Option<(NaiveDateTime, &ScheduledAlarm)> {
+    alarms
+        .iter()
+        .filter_map(|alarm| Some((alarm.next_after(now)?, alarm)))
+        .min_by_key(|(at, _)| *at)
+}
+
+/// Runs `[[alarms]]` from a background task, which hands each alarm to the
+/// app as it goes off. The task stops when this is dropped.
+pub struct AlarmClock {
+    alarms: Vec<ScheduledAlarm>,
+    receiver: mpsc::UnboundedReceiver<ScheduledAlarm>,
+    task: JoinHandle<()>,
+}
+
+impl AlarmClock {
+    /// `None` when no alarm is enabled. Alarms that don't parse are skipped
+    /// with a warning.
+    pub fn spawn(config: &[Alarm]) -> Option<Self> {
+        let alarms: Vec<ScheduledAlarm> = config
+            .iter()
+            .filter(|alarm| alarm.enabled)
+            .filter_map(|alarm| match ScheduledAlarm::parse(alarm) {
+                Ok(alarm) => Some(alarm),
+                Err(e) => {
+                    tracing::warn!("Ignoring alarm at {:?}: {}", alarm.at, e);
+                    None
+                }
+            })
+            .collect();
+        if alarms.is_empty() {
+            return None;
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(alarms.clone(), sender));
+        Some(Self {
+            alarms,
+            receiver,
+            task,
+        })
+    }
+
+    /// The next alarm to go off, for the on-screen indicator.
+    pub fn upcoming(&self) -> Option<&ScheduledAlarm> {
+        next_alarm(&self.alarms, Local::now().naive_local()).map(|(_, alarm)| alarm)
+    }
+
+    /// An alarm that has gone off since the last call, without waiting.
+    pub fn try_next(&mut self) -> Option<ScheduledAlarm> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Drop for AlarmClock {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run(alarms: Vec<ScheduledAlarm>, sender: mpsc::UnboundedSender<ScheduledAlarm>) {
+    let mut now = Local::now().naive_local();
+    while let Some((at, alarm)) = next_alarm(&alarms, now) {
+        loop {
+            let remaining = (at - Local::now().naive_local())
+                .to_std()
+                .unwrap_or_default();
+            if remaining.is_zero() {
+                break;
+            }
+            tokio::time::sleep(remaining.min(MAX_WAIT)).await;
+        }
+        if sender.send(alarm.clone()).is_err() {
+            return;
+        }
+        now = at;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn alarm(at: &str, days: &[&str]) -> Alarm {
+        Alarm {
+            at: at.into(),
+            action: AlarmAction::Play,
+            playlist: Some("Morning".into()),
+            days: days.iter().map(|d| d.to_string()).collect(),
+            enabled: true,
+        }
+    }
+
+    // 2026-10-15 is a Thursday
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn alarms_go_off_at_their_next_time_and_day() {
+        let daily = ScheduledAlarm::parse(&alarm("07:00", &[])).unwrap();
+        assert_eq!(daily.next_after(at(15, 6, 59)), Some(at(15, 7, 0)));
+        assert_eq!(daily.next_after(at(15, 7, 0)), Some(at(16, 7, 0)));
+
+        let weekdays = ScheduledAlarm::parse(&alarm("07:00", &["mon", "fri"])).unwrap();
+        assert_eq!(weekdays.next_after(at(16, 8, 0)), Some(at(19, 7, 0)));
+
+        let mut bedtime = alarm("23:00", &[]);
+        bedtime.action = AlarmAction::Pause;
+        bedtime.playlist = None;
+        let bedtime = ScheduledAlarm::parse(&bedtime).unwrap();
+        assert_eq!(bedtime.describe(), "23:00 ⏸");
+        let alarms = [weekdays, bedtime];
+        let (when, next) = next_alarm(&alarms, at(16, 8, 0)).unwrap();
+        assert_eq!(when, at(16, 23, 0));
+        assert_eq!(next.action, AlarmAction::Pause);
+
+        assert!(ScheduledAlarm::parse(&alarm("7am", &[])).is_err());
+        assert!(ScheduledAlarm::parse(&alarm("07:00", &["someday"])).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use ratatui_image::protocol::StatefulProtocol;
+use ratatui_image::{Resize, StatefulImage};
+
+use crate::ui::Theme;
+use amcli_core::artist::ArtistInfo;
+
+// Image column width, used once the popup is wide enough to spare it
+const IMAGE_WIDTH: u16 = 24;
+
+pub enum ArtistState {
+    Loading,
+    Found(ArtistInfo),
+    Missing,
+    Failed(String),
+}
+
+// Biography overlay for the now-playing artist, opened with `i`
+pub struct ArtistPanel {
+    pub artist: String,
+    pub state: ArtistState,
+    pub image: Option<StatefulProtocol>,
+    scroll: u16,
+}
+
+impl ArtistPanel {
+    pub fn new(artist: String) -> Self {
+        Self {
+            artist,
+            state: ArtistState::Loading,
+            image: None,
+            scroll: 0,
+        }
+    }
+
+    pub fn scroll(&mut self, delta: i16) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+
+    pub fn render(&mut self, f: &mut Frame, theme: Theme, is_jp: bool) {
+        let area = f.area();
+        let popup_width = 76.min(area.width.saturating_sub(4));
+        let popup_height = 20.min(area.height.saturating_sub(4));
+        let popup_area = Rect {
+            x: area.width.saturating_sub(popup_width) / 2,
+            y: area.height.saturating_sub(popup_height) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+        f.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(if theme.is_retro {
+                BorderType::Thick
+            } else {
+                BorderType::Rounded
+            })
+            .border_style(Style::default().fg(theme.accent))
+            .title(vec![
+                Span::styled(" [ ", Style::default().fg(theme.dim)),
+                Span::styled(
+                    self.artist.to_uppercase(),
+                    Style::default()
+                        .fg(theme.primary)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" ] ", Style::default().fg(theme.dim)),
+            ])
+            .title_alignment(Alignment::Center)
+            .style(Style::default().bg(theme.bg));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let text_area = match &mut self.image {
+            Some(image) if inner.width >= IMAGE_WIDTH * 2 => {
+                let [image_area, text_area] =
+                    Layout::horizontal([Constraint::Length(IMAGE_WIDTH), Constraint::Fill(1)])
+                        .spacing(2)
+                        .areas(inner);
+                f.render_stateful_widget(
+                    StatefulImage::default().resize(Resize::Fit(None)),
+                    image_area,
+                    image,
+                );
+                text_area
+            }
+            _ => inner,
+        };
+
+        let dim = Style::default().fg(theme.dim).add_modifier(Modifier::DIM);
+        let (text, style, similar, credit) = match &self.state {
+            ArtistState::Loading => (
+                (if is_jp { "読込中" } else { "LOADING" }).to_string(),
+                dim,
+                None,
+                None,
+            ),
+            ArtistState::Found(info) => (
+                info.summary.clone(),
+                Style::default().fg(theme.primary),
+                (!info.similar.is_empty()).then(|| info.similar.join(" · ")),
+                Some(info.source),
+            ),
+            ArtistState::Missing => (
+                (if is_jp {
+                    "情報なし"
+                } else {
+                    "NO ARTIST INFO"
+                })
+                .to_string(),
+                dim,
+                None,
+                None,
+            ),
+            ArtistState::Failed(error) => {
+                (error.clone(), Style::default().fg(theme.alert), None, None)
+            }
+        };
+        let mut lines = vec![Line::styled(text, style)];
+        if let Some(similar) = similar {
+            lines.push(Line::default());
+            lines.push(Line::from(vec![
+                Span::styled(
+                    if is_jp {
+                        "おすすめ: "
+                    } else {
+                        "YOU MIGHT ALSO LIKE: "
+                    },
+                    Style::default().fg(theme.accent),
+                ),
+                Span::styled(similar, Style::default().fg(theme.primary)),
+            ]));
+        }
+        f.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: true })
+                .scroll((self.scroll, 0)),
+            text_area,
+        );
+
+        let mut help = vec![Span::styled(
+            "↑↓ Scroll │ Esc Close",
+            Style::default().fg(theme.dim),
+        )];
+        if let Some(credit) = credit {
+            help.push(Span::styled(
+                format!(" │ {}", credit),
+                Style::default().fg(theme.dim),
+            ));
+        }
+        let help_area = Rect {
+            x: popup_area.x,
+            y: popup_area.bottom().saturating_sub(1),
+            width: popup_area.width,
+            height: 1,
+        };
+        f.render_widget(
+            Paragraph::new(Line::from(help)).alignment(Alignment::Center),
+            help_area,
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::ui::Theme;
+
+const HEIGHT: usize = 5;
+// Blank rows between wrapped banner lines
+const LINE_GAP: usize = 1;
+
+// A small block font in the spirit of figlet's "banner" faces. Each glyph is
+// five rows of equal width; '#' marks a filled cell.
+const FONT: &[(char, [&str; HEIGHT])] = &[
+    ('A', [" ## ", "#  #", "####", "#  #", "#  #"]),
+    ('B', ["### ", "#  #", "### ", "#  #", "### "]),
+    ('C', [" ###", "#   ", "#   ", "#   ", " ###"]),
+    ('D', ["### ", "#  #", "#  #", "#  #", "### "]),
+    ('E', ["####", "#   ", "### ", "#   ", "####"]),
+    ('F', ["####", "#   ", "### ", "#   ", "#   "]),
+    ('G', [" ###", "#   ", "# ##", "#  #", " ###"]),
+    ('H', ["#  #", "#  #", "####", "#  #", "#  #"]),
+    ('I', ["###", " # ", " # ", " # ", "###"]),
+    ('J', ["   #", "   #", "   #", "#  #", " ## "]),
+    ('K', ["#  #", "# # ", "##  ", "# # ", "#  #"]),
+    ('L', ["#   ", "#   ", "#   ", "#   ", "####"]),
+    ('M', ["#   #", "## ##", "# # #", "#   #", "#   #"]),
+    ('N', ["#   #", "##  #", "# # #", "#  ##", "#   #"]),
+    ('O', [" ## ", "#  #", "#  #", "#  #", " ## "]),
+    ('P', ["### ", "#  #", "### ", "#   ", "#   "]),
+    ('Q', [" ## ", "#  #", "#  #", "# # ", " # #"]),
+    ('R', ["### ", "#  #", "### ", "# # ", "#  #"]),
+    ('S', [" ###", "#   ", " ## ", "   #", "### "]),
+    ('T', ["#####", "  #  ", "  #  ", "  #  ", "  #  "]),
+    ('U', ["#  #", "#  #", "#  #", "#  #", " ## "]),
+    ('V', ["#   #", "#   #", "#   #", " # # ", "  #  "]),
+    ('W', ["#   #", "#   #", "# # #", "## ##", "#   #"]),
+    ('X', ["#   #", " # # ", "  #  ", " # # ", "#   #"]),
+    ('Y', ["#   #", " # # ", "  #  ", "  #  ", "  #  "]),
+    ('Z', ["####", "   #", " ## ", "#   ", "####"]),
+    ('0', [" ## ", "# ##", "#  #", "## #", " ## "]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', ["### ", "   #", " ## ", "#   ", "####"]),
+    ('3', ["### ", "   #", " ## ", "   #", "### "]),
+    ('4', ["#  #", "#  #", "####", "   #", "   #"]),
+    ('5', ["####", "#   ", "### ", "   #", "### "]),
+    ('6', [" ## ", "#   ", "### ", "#  #", " ## "]),
+    ('7', ["####", "   #", "  # ", " #  ", " #  "]),
+    ('8', [" ## ", "#  #", " ## ", "#  #", " ## "]),
+    ('9', [" ## ", "#  #", " ###", "   #", " ## "]),
+    ('.', [" ", " ", " ", " ", "#"]),
+    (',', [" ", " ", " ", "#", "#"]),
+    ('!', ["#", "#", "#", " ", "#"]),
+    ('?', ["### ", "   #", " ## ", "    ", " #  "]),
+    ('\'', ["#", "#", " ", " ", " "]),
+    ('"', ["# #", "# #", "   ", "   ", "   "]),
+    (':', [" ", "#", " ", "#", " "]),
+    ('-', ["   ", "   ", "###", "   ", "   "]),
+    ('&', [" #  ", "# # ", " #  ", "# # ", " # #"]),
+    ('(', [" #", "# ", "# ", "# ", " #"]),
+    (')', ["# ", " #", " #", " #", "# "]),
+    ('/', ["  #", "  #", " # ", "#  ", "#  "]),
+];
+
+fn glyph(ch: char) -> Option<&'static [&'static str; HEIGHT]> {
+    let ch = match ch {
+        '’' | '‘' => '\'',
+        '“' | '”' => '"',
+        '–' | '—' => '-',
+        _ => ch.to_ascii_uppercase(),
+    };
+    FONT.iter().find(|(c, _)| *c == ch).map(|(_, rows)| rows)
+}
+
+// Glyphs side by side with one blank column between them
+fn render_word(word: &str) -> Option<Vec<String>> {
+    let glyphs = word.chars().map(glyph).collect::<Option<Vec<_>>>()?;
+    Some(
+        (0..HEIGHT)
+            .map(|row| {
+                glyphs
+                    .iter()
+                    .map(|g| g[row].replace('#', "█"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect(),
+    )
+}
+
+fn width(rows: &[String]) -> usize {
+    rows.first().map_or(0, |row| row.chars().count())
+}
+
+/// `text` in block letters, word-wrapped to `max_width` columns. `None` when
+/// a character isn't in the font (such as Japanese) or a single word is too
+/// wide to fit.
+pub fn render(text: &str, max_width: usize) -> Option<Vec<String>> {
+    const WORD_GAP: &str = "   ";
+    let mut banner: Vec<Vec<String>> = Vec::new();
+    for word in text.split_whitespace() {
+        let rendered = render_word(word)?;
+        if width(&rendered) > max_width {
+            return None;
+        }
+        match banner.last_mut() {
+            Some(line) if width(line) + WORD_GAP.len() + width(&rendered) <= max_width => {
+                for (row, part) in line.iter_mut().zip(rendered) {
+                    row.push_str(WORD_GAP);
+                    row.push_str(&part);
+                }
+            }
+            _ => banner.push(rendered),
+        }
+    }
+    let mut lines = Vec::new();
+    for (i, line) in banner.into_iter().enumerate() {
+        if i > 0 {
+            lines.extend(std::iter::repeat_n(String::new(), LINE_GAP));
+        }
+        lines.extend(line);
+    }
+    (!lines.is_empty()).then_some(lines)
+}
+
+// Centered in `area`; falls back to plain bold text when the banner can't
+// be drawn or doesn't fit
+pub fn draw(f: &mut Frame, area: Rect, text: &str, theme: Theme) {
+    let style = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let (lines, paragraph) = match render(text, area.width as usize)
+        .filter(|lines| lines.len() <= area.height as usize)
+    {
+        Some(lines) => (
+            lines.len(),
+            Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>()),
+        ),
+        None => (
+            UnicodeWidthStr::width(text).div_ceil(area.width.max(1) as usize),
+            Paragraph::new(text.to_string()).wrap(Wrap { trim: true }),
+        ),
+    };
+    let [_, middle, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(lines as u16),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+    f.render_widget(paragraph.style(style).alignment(Alignment::Center), middle);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyphs_share_a_height_and_a_width_per_row() {
+        for (ch, rows) in FONT {
+            let width = rows[0].len();
+            assert!(rows.iter().all(|row| row.len() == width), "{:?}", ch);
+        }
+    }
+
+    #[test]
+    fn words_wrap_onto_new_banner_lines() {
+        let one_line = render("Hi you", 80).unwrap();
+        assert_eq!(one_line.len(), HEIGHT);
+        assert_eq!(one_line[0], "█  █ ███   █   █  ██  █  █");
+
+        let wrapped = render("Hi you", 16).unwrap();
+        assert_eq!(wrapped.len(), HEIGHT * 2 + LINE_GAP);
+        assert!(render("夜に駆ける", 80).is_none());
+        assert!(render("MOMENTUM", 16).is_none());
+    }
+}
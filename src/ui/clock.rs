@@ -0,0 +1,72 @@
+use chrono::{Datelike, NaiveDateTime};
+
+use amcli_core::config::ClockFormat;
+
+const WEEKDAYS_JP: [&str; 7] = ["月", "火", "水", "木", "金", "土", "日"];
+
+// Receiver-style readout: English defaults to a 12-hour clock, Japanese to 24-hour.
+pub fn format_clock(now: NaiveDateTime, format: ClockFormat, is_jp: bool) -> Option<String> {
+    let use_24h = match format {
+        ClockFormat::Off => return None,
+        ClockFormat::Auto => is_jp,
+        ClockFormat::TwelveHour => false,
+        ClockFormat::TwentyFourHour => true,
+    };
+
+    let time = if use_24h {
+        now.format("%H:%M:%S").to_string()
+    } else {
+        now.format("%I:%M:%S %p").to_string()
+    };
+
+    let date = if is_jp {
+        format!(
+            "{}月{}日({})",
+            now.month(),
+            now.day(),
+            WEEKDAYS_JP[now.weekday().num_days_from_monday() as usize]
+        )
+    } else {
+        now.format("%a %d %b").to_string().to_uppercase()
+    };
+
+    Some(format!("{}  {}", date, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 15)
+            .unwrap()
+            .and_hms_opt(hour, minute, 5)
+            .unwrap()
+    }
+
+    #[test]
+    fn english_auto_uses_twelve_hour_clock() {
+        assert_eq!(
+            format_clock(at(21, 4), ClockFormat::Auto, false).as_deref(),
+            Some("THU 15 OCT  09:04:05 PM")
+        );
+    }
+
+    #[test]
+    fn japanese_auto_uses_twenty_four_hour_clock() {
+        assert_eq!(
+            format_clock(at(21, 4), ClockFormat::Auto, true).as_deref(),
+            Some("10月15日(木)  21:04:05")
+        );
+    }
+
+    #[test]
+    fn explicit_format_overrides_language_default() {
+        assert_eq!(
+            format_clock(at(21, 4), ClockFormat::TwentyFourHour, false).as_deref(),
+            Some("THU 15 OCT  21:04:05")
+        );
+        assert!(format_clock(at(21, 4), ClockFormat::Off, false).is_none());
+    }
+}
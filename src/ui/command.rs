@@ -0,0 +1,630 @@
+use anyhow::{anyhow, Result};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Clear, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+use crate::ui::{Theme, THEMES};
+use amcli_core::config::Language;
+
+const COMMANDS: &[&str] = &[
+    "artwork",
+    "bookmark",
+    "bookmarks",
+    "export",
+    "lang",
+    "note",
+    "play",
+    "profile",
+    "quit",
+    "save-theme",
+    "seek",
+    "snapshot",
+    "station",
+    "theme",
+    "unbookmark",
+    "vol",
+];
+const LANGUAGES: &[&str] = &["en", "jp"];
+const EXPORT_SOURCES: &[&str] = &["favorites", "history", "queue"];
+const ARTWORK_ARGS: &[&str] = &["album ", "reset"];
+const STATION_ARGS: &[&str] = &["add "];
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Seek(Duration),
+    SeekRelative(i32),
+    SeekPercent(f32),
+    Volume(u8),
+    Theme(String),
+    SaveTheme(String),
+    Language(Language),
+    // "default" is config.toml; other names get created on first use
+    Profile(String),
+    Play(String),
+    Export(ExportSource, String),
+    // Empty names are filled in with the position
+    Bookmark(String),
+    Unbookmark(String),
+    ListBookmarks,
+    // A blank note removes the current track's note
+    Note(String),
+    // Empty paths get a timestamped file in the working directory
+    Snapshot(String),
+    // An image file or URL for the current track, or its whole album
+    Artwork { album: bool, source: String },
+    // Back to the player's own cover for the track and its album
+    ResetArtwork,
+    // Internet radio, played by name or saved to `[[stations]]`
+    Station(String),
+    AddStation { url: String, name: String },
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportSource {
+    Queue,
+    History,
+    Favorites,
+}
+
+pub fn parse_command(input: &str) -> Result<Command> {
+    let input = input.trim();
+    let (name, arg) = match input.split_once(char::is_whitespace) {
+        Some((name, arg)) => (name, arg.trim()),
+        None => (input, ""),
+    };
+
+    match name {
+        "seek" => {
+            if let Some(delta) = arg.strip_prefix('+') {
+                parse_seconds(delta).map(Command::SeekRelative)
+            } else if let Some(delta) = arg.strip_prefix('-') {
+                parse_seconds(delta).map(|s| Command::SeekRelative(-s))
+            } else if let Some(percent) = arg.strip_suffix('%') {
+                percent
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|p| (0.0..=100.0).contains(p))
+                    .map(Command::SeekPercent)
+                    .ok_or_else(|| anyhow!("Percentage must be 0-100%"))
+            } else {
+                parse_timestamp(arg)
+                    .map(Command::Seek)
+                    .ok_or_else(|| anyhow!("Invalid time: {}", arg))
+            }
+        }
+        "vol" | "volume" => arg
+            .parse::<u8>()
+            .ok()
+            .filter(|v| *v <= 100)
+            .map(Command::Volume)
+            .ok_or_else(|| anyhow!("Volume must be 0-100")),
+        "theme" if !arg.is_empty() => Ok(Command::Theme(arg.to_string())),
+        "theme" => Err(anyhow!("Usage: theme <name>")),
+        "save-theme" if !arg.is_empty() => Ok(Command::SaveTheme(arg.to_string())),
+        "save-theme" => Err(anyhow!("Usage: save-theme <name>")),
+        "lang" => match arg {
+            "en" => Ok(Command::Language(Language::English)),
+            "jp" | "ja" => Ok(Command::Language(Language::Japanese)),
+            _ => Err(anyhow!("Unknown language: {}", arg)),
+        },
+        "profile" if !arg.is_empty() => Ok(Command::Profile(arg.to_string())),
+        "profile" => Err(anyhow!("Usage: profile <name>")),
+        "play" if !arg.is_empty() => Ok(Command::Play(arg.to_string())),
+        "play" => Err(anyhow!("Usage: play <query>")),
+        "export" => parse_export(arg),
+        "bookmark" => Ok(Command::Bookmark(arg.to_string())),
+        "unbookmark" if !arg.is_empty() => Ok(Command::Unbookmark(arg.to_string())),
+        "unbookmark" => Err(anyhow!("Usage: unbookmark <name>")),
+        "bookmarks" => Ok(Command::ListBookmarks),
+        "note" => Ok(Command::Note(arg.to_string())),
+        "snapshot" => Ok(Command::Snapshot(arg.to_string())),
+        "artwork" => parse_artwork(arg),
+        "station" => parse_station(arg),
+        "q" | "quit" => Ok(Command::Quit),
+        "" => Err(anyhow!("Empty command")),
+        _ => Err(anyhow!("Unknown command: {}", name)),
+    }
+}
+
+// Accepts `ss`, `mm:ss`, or `hh:mm:ss`.
+pub fn parse_timestamp(value: &str) -> Option<Duration> {
+    let mut total = 0u64;
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+    for (i, part) in parts.iter().enumerate() {
+        let n: u64 = part.parse().ok()?;
+        if i > 0 && n >= 60 {
+            return None;
+        }
+        total = total.checked_mul(60)?.checked_add(n)?;
+    }
+    Some(Duration::from_secs(total))
+}
+
+// `export <file>` writes the queue; `export queue|history|favorites <file>`
+// picks the source.
+fn parse_export(arg: &str) -> Result<Command> {
+    let (source, path) = match arg.split_once(char::is_whitespace) {
+        Some(("queue", path)) => (ExportSource::Queue, path.trim()),
+        Some(("history", path)) => (ExportSource::History, path.trim()),
+        Some(("favorites", path)) => (ExportSource::Favorites, path.trim()),
+        _ => (ExportSource::Queue, arg),
+    };
+    if path.is_empty() || EXPORT_SOURCES.contains(&path) {
+        return Err(anyhow!(
+            "Usage: export [queue|history|favorites] <file.m3u|.csv|.json>"
+        ));
+    }
+    Ok(Command::Export(source, path.to_string()))
+}
+
+// `artwork <file|url>`, `artwork album <file|url>`, or `artwork reset`
+fn parse_artwork(arg: &str) -> Result<Command> {
+    let (album, source) = match arg.split_once(char::is_whitespace) {
+        Some(("album", source)) => (true, source.trim()),
+        _ => (false, arg),
+    };
+    match source {
+        "reset" if !album => Ok(Command::ResetArtwork),
+        "" | "album" | "reset" => Err(anyhow!(
+            "Usage: artwork [album] <file|url>, or artwork reset"
+        )),
+        source => Ok(Command::Artwork {
+            album,
+            source: source.to_string(),
+        }),
+    }
+}
+
+// `station <name>`, or `station add <url> [name]` named after the host when
+// no name is given
+fn parse_station(arg: &str) -> Result<Command> {
+    let usage = || anyhow!("Usage: station <name>, or station add <url> [name]");
+    let Some(rest) = arg
+        .strip_prefix("add")
+        .filter(|r| r.is_empty() || r.starts_with(' '))
+    else {
+        return match arg {
+            "" => Err(usage()),
+            name => Ok(Command::Station(name.to_string())),
+        };
+    };
+    let (url, name) = match rest.trim().split_once(char::is_whitespace) {
+        Some((url, name)) => (url, name.trim()),
+        None => (rest.trim(), ""),
+    };
+    let host = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))
+        .and_then(|rest| rest.split(['/', ':', '?']).next())
+        .filter(|host| !host.is_empty());
+    let Some(host) = host else {
+        return Err(if url.is_empty() {
+            usage()
+        } else {
+            anyhow!("Station URLs start with http:// or https://")
+        });
+    };
+    Ok(Command::AddStation {
+        url: url.to_string(),
+        name: if name.is_empty() { host } else { name }.to_string(),
+    })
+}
+
+fn parse_seconds(value: &str) -> Result<i32> {
+    parse_timestamp(value)
+        .and_then(|d| i32::try_from(d.as_secs()).ok())
+        .ok_or_else(|| anyhow!("Invalid offset: {}", value))
+}
+
+pub fn find_theme(themes: &[Theme], query: &str) -> Option<usize> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    themes
+        .iter()
+        .position(|t| t.name.to_lowercase() == query)
+        .or_else(|| {
+            themes
+                .iter()
+                .position(|t| t.name.to_lowercase().contains(&query))
+        })
+}
+
+#[derive(Debug, Default)]
+pub struct CommandLine {
+    pub is_active: bool,
+    input: String,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    completions: Vec<String>,
+    completion_index: usize,
+    message: Option<(String, bool)>,
+    // Saved custom themes, offered after the built-in ones by `theme <Tab>`
+    custom_themes: Vec<String>,
+    // `/` searches the lyrics instead of running a command
+    is_search: bool,
+}
+
+impl CommandLine {
+    pub fn open(&mut self) {
+        self.open_with("");
+    }
+
+    // Starts the line with `input` already typed, e.g. `seek ` for the G prompt
+    pub fn open_with(&mut self, input: &str) {
+        self.is_active = true;
+        self.is_search = false;
+        self.input = input.to_string();
+        self.history_index = None;
+        self.reset_completion();
+        self.message = None;
+    }
+
+    // Same line, but the input is a lyrics search and stays out of history
+    pub fn open_search(&mut self) {
+        self.open();
+        self.is_search = true;
+    }
+
+    pub fn is_search(&self) -> bool {
+        self.is_search
+    }
+
+    pub fn close(&mut self) {
+        self.is_active = false;
+        self.input.clear();
+        self.reset_completion();
+    }
+
+    pub fn push(&mut self, ch: char) {
+        self.input.push(ch);
+        self.reset_completion();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.input.pop().is_none() {
+            // Backspace on an empty line leaves command mode, like vim
+            self.close();
+        }
+        self.reset_completion();
+    }
+
+    // Take the entered command, recording it in history.
+    pub fn submit(&mut self) -> String {
+        let input = std::mem::take(&mut self.input);
+        let trimmed = input.trim();
+        if !self.is_search
+            && !trimmed.is_empty()
+            && self.history.last().map(String::as_str) != Some(trimmed)
+        {
+            self.history.push(trimmed.to_string());
+            if self.history.len() > HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+        }
+        self.close();
+        input
+    }
+
+    pub fn history_prev(&mut self) {
+        if self.is_search || self.history.is_empty() {
+            return;
+        }
+        let index = match self.history_index {
+            Some(i) => i.saturating_sub(1),
+            None => self.history.len() - 1,
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+        self.reset_completion();
+    }
+
+    pub fn history_next(&mut self) {
+        match self.history_index {
+            Some(i) if i + 1 < self.history.len() => {
+                self.history_index = Some(i + 1);
+                self.input = self.history[i + 1].clone();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.input.clear();
+            }
+            None => {}
+        }
+        self.reset_completion();
+    }
+
+    // First Tab completes to the first candidate; repeated Tabs cycle through the rest.
+    pub fn complete(&mut self) {
+        if self.is_search {
+            return;
+        }
+        if self.completions.is_empty() {
+            self.completions = completion_candidates(&self.input, &self.custom_themes);
+            self.completion_index = 0;
+        } else {
+            self.completion_index = (self.completion_index + 1) % self.completions.len();
+        }
+        if let Some(candidate) = self.completions.get(self.completion_index) {
+            self.input = candidate.clone();
+        }
+    }
+
+    pub fn set_message(&mut self, message: String, is_error: bool) {
+        self.message = Some((message, is_error));
+    }
+
+    pub fn set_custom_themes(&mut self, names: Vec<String>) {
+        self.custom_themes = names;
+    }
+
+    pub fn clear_message(&mut self) {
+        self.message = None;
+    }
+
+    fn reset_completion(&mut self) {
+        self.completions.clear();
+        self.completion_index = 0;
+    }
+
+    pub fn render(&self, f: &mut Frame, theme: Theme) {
+        let area = f.area();
+        if area.height == 0 {
+            return;
+        }
+        let line_area = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+
+        let line = if self.is_active {
+            Line::from(vec![
+                Span::styled(
+                    if self.is_search { "/" } else { ":" },
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(self.input.as_str(), Style::default().fg(theme.primary)),
+                Span::styled(" ", Style::default().bg(theme.primary).fg(theme.bg)),
+            ])
+        } else if let Some((message, is_error)) = &self.message {
+            let color = if *is_error { theme.alert } else { theme.dim };
+            Line::from(Span::styled(message.as_str(), Style::default().fg(color)))
+        } else {
+            return;
+        };
+
+        f.render_widget(Clear, line_area);
+        f.render_widget(
+            Paragraph::new(line).style(Style::default().bg(theme.bg)),
+            line_area,
+        );
+    }
+}
+
+fn completion_candidates(input: &str, custom_themes: &[String]) -> Vec<String> {
+    match input.split_once(' ') {
+        None => COMMANDS
+            .iter()
+            .filter(|c| c.starts_with(input))
+            .map(|c| format!("{} ", c))
+            .collect(),
+        Some((name, arg)) => {
+            let arg = arg.to_lowercase();
+            let options: Vec<String> = match name {
+                "theme" => THEMES
+                    .iter()
+                    .map(|t| t.name)
+                    .chain(custom_themes.iter().map(String::as_str))
+                    .map(str::to_lowercase)
+                    .collect(),
+                "lang" => LANGUAGES.iter().map(|l| l.to_string()).collect(),
+                "export" => EXPORT_SOURCES.iter().map(|s| format!("{} ", s)).collect(),
+                "artwork" => ARTWORK_ARGS.iter().map(|s| s.to_string()).collect(),
+                "station" => STATION_ARGS.iter().map(|s| s.to_string()).collect(),
+                _ => Vec::new(),
+            };
+            options
+                .into_iter()
+                .filter(|o| o.starts_with(&arg))
+                .map(|o| format!("{} {}", name, o))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_absolute_and_relative_seek() {
+        assert_eq!(
+            parse_command("seek 1:23").unwrap(),
+            Command::Seek(Duration::from_secs(83))
+        );
+        assert_eq!(
+            parse_command("seek -10").unwrap(),
+            Command::SeekRelative(-10)
+        );
+        assert!(parse_command("seek 1:75").is_err());
+        // Too long to fit in a u64 of seconds
+        assert!(parse_command("seek 999999999999999999:00").is_err());
+        assert_eq!(
+            parse_command("seek 25%").unwrap(),
+            Command::SeekPercent(25.0)
+        );
+        assert!(parse_command("seek 120%").is_err());
+        assert!(parse_command("seek half%").is_err());
+    }
+
+    #[test]
+    fn parses_volume_theme_and_language() {
+        assert_eq!(parse_command("vol 40").unwrap(), Command::Volume(40));
+        assert!(parse_command("vol 140").is_err());
+        assert_eq!(
+            parse_command("theme green").unwrap(),
+            Command::Theme("green".into())
+        );
+        assert_eq!(
+            find_theme(THEMES, "green"),
+            THEMES.iter().position(|t| t.name == "GREEN VFD")
+        );
+        assert!(find_theme(THEMES, "mauve").is_none());
+        assert_eq!(
+            parse_command("lang jp").unwrap(),
+            Command::Language(Language::Japanese)
+        );
+        assert_eq!(
+            parse_command("profile work").unwrap(),
+            Command::Profile("work".into())
+        );
+        assert!(parse_command("profile").is_err());
+    }
+
+    #[test]
+    fn play_keeps_the_full_query() {
+        assert_eq!(
+            parse_command("play  Blue in Green ").unwrap(),
+            Command::Play("Blue in Green".into())
+        );
+        assert!(parse_command("play").is_err());
+    }
+
+    #[test]
+    fn bookmark_names_are_optional_to_add_but_not_remove() {
+        assert_eq!(
+            parse_command("bookmark Second set").unwrap(),
+            Command::Bookmark("Second set".into())
+        );
+        assert_eq!(
+            parse_command("bookmark").unwrap(),
+            Command::Bookmark(String::new())
+        );
+        assert!(parse_command("unbookmark").is_err());
+        assert_eq!(
+            parse_command("note  opener, 124 BPM").unwrap(),
+            Command::Note("opener, 124 BPM".into())
+        );
+    }
+
+    #[test]
+    fn parses_export_source_and_path() {
+        assert_eq!(
+            parse_command("export ~/queue.m3u").unwrap(),
+            Command::Export(ExportSource::Queue, "~/queue.m3u".into())
+        );
+        assert_eq!(
+            parse_command("export history session.m3u8").unwrap(),
+            Command::Export(ExportSource::History, "session.m3u8".into())
+        );
+        assert_eq!(
+            parse_command("export favorites ~/starred.csv").unwrap(),
+            Command::Export(ExportSource::Favorites, "~/starred.csv".into())
+        );
+        assert!(parse_command("export history").is_err());
+    }
+
+    #[test]
+    fn parses_artwork_targets() {
+        assert_eq!(
+            parse_command("artwork ~/Pictures/cover.jpg").unwrap(),
+            Command::Artwork {
+                album: false,
+                source: "~/Pictures/cover.jpg".into()
+            }
+        );
+        assert_eq!(
+            parse_command("artwork album https://example.com/a b.png").unwrap(),
+            Command::Artwork {
+                album: true,
+                source: "https://example.com/a b.png".into()
+            }
+        );
+        assert_eq!(
+            parse_command("artwork reset").unwrap(),
+            Command::ResetArtwork
+        );
+        assert!(parse_command("artwork").is_err());
+        assert!(parse_command("artwork album").is_err());
+    }
+
+    #[test]
+    fn parses_stations_to_play_and_add() {
+        assert_eq!(
+            parse_command("station Radio Paradise").unwrap(),
+            Command::Station("Radio Paradise".into())
+        );
+        assert_eq!(
+            parse_command("station add https://stream.example.com:8000/jazz.mp3 Late Jazz")
+                .unwrap(),
+            Command::AddStation {
+                url: "https://stream.example.com:8000/jazz.mp3".into(),
+                name: "Late Jazz".into()
+            }
+        );
+        assert_eq!(
+            parse_command("station add http://ice.example.org/live").unwrap(),
+            Command::AddStation {
+                url: "http://ice.example.org/live".into(),
+                name: "ice.example.org".into()
+            }
+        );
+        // A station may well be called "Addis Ababa FM"
+        assert_eq!(
+            parse_command("station addis").unwrap(),
+            Command::Station("addis".into())
+        );
+        assert!(parse_command("station").is_err());
+        assert!(parse_command("station add").is_err());
+        assert!(parse_command("station add ftp://example.com/a").is_err());
+    }
+
+    #[test]
+    fn tab_cycles_command_and_argument_completions() {
+        let mut line = CommandLine::default();
+        line.open();
+        line.push('t');
+        line.complete();
+        assert_eq!(line.input, "theme ");
+
+        line.push('c');
+        line.complete();
+        assert_eq!(line.input, "theme cyan vfd");
+
+        line.set_custom_themes(vec!["Dusk".into()]);
+        line.open_with("theme d");
+        line.complete();
+        assert_eq!(line.input, "theme dusk");
+    }
+
+    #[test]
+    fn history_recalls_previous_commands() {
+        let mut line = CommandLine::default();
+        for cmd in ["vol 10", "vol 20"] {
+            line.open();
+            cmd.chars().for_each(|c| line.push(c));
+            line.submit();
+        }
+
+        line.open();
+        line.history_prev();
+        assert_eq!(line.input, "vol 20");
+        line.history_prev();
+        assert_eq!(line.input, "vol 10");
+        line.history_next();
+        assert_eq!(line.input, "vol 20");
+        line.history_next();
+        assert_eq!(line.input, "");
+    }
+}
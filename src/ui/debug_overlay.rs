@@ -0,0 +1,181 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+use crate::ui::Theme;
+use amcli_core::diagnostics::{self, TimerSummary};
+
+// What the F12 overlay shows: timings measured by the app and the core's
+// process-wide counters, plus what each background task is doing
+#[derive(Debug, Clone, Default)]
+pub struct DebugReport {
+    pub frame: TimerSummary,
+    pub update: TimerSummary,
+    pub osascript: TimerSummary,
+    pub artwork_cache: (u64, u64),
+    pub lyrics_cache: (u64, u64),
+    // Per lyrics provider: (found, missing, failed) and latency
+    pub providers: Vec<(String, (u64, u64, u64), TimerSummary)>,
+    pub tasks: Vec<(&'static str, String)>,
+}
+
+impl DebugReport {
+    pub fn new(
+        frame: TimerSummary,
+        update: TimerSummary,
+        tasks: Vec<(&'static str, String)>,
+    ) -> Self {
+        Self {
+            frame,
+            update,
+            osascript: diagnostics::OSASCRIPT.summary(),
+            artwork_cache: diagnostics::ARTWORK_CACHE.counts(),
+            lyrics_cache: diagnostics::LYRICS_CACHE.counts(),
+            providers: diagnostics::LYRICS_PROVIDERS
+                .entries()
+                .into_iter()
+                .map(|(name, outcomes)| (name, outcomes.counts(), outcomes.latency.summary()))
+                .collect(),
+            tasks,
+        }
+    }
+
+    pub fn rows(&self) -> Vec<(String, String)> {
+        let mut rows = vec![
+            ("render".to_string(), format_timer(&self.frame)),
+            ("poll".into(), format_timer(&self.update)),
+            ("osascript".into(), format_timer(&self.osascript)),
+            ("art cache".into(), format_hits(self.artwork_cache)),
+            ("lyr cache".into(), format_hits(self.lyrics_cache)),
+        ];
+        rows.extend(
+            self.providers
+                .iter()
+                .map(|(name, counts, latency)| (name.clone(), format_outcomes(*counts, latency))),
+        );
+        rows.extend(
+            self.tasks
+                .iter()
+                .map(|(name, state)| (name.to_string(), state.clone())),
+        );
+        rows
+    }
+}
+
+// "12ms avg 9.5ms max 48ms ×320"
+fn format_timer(timer: &TimerSummary) -> String {
+    if timer.calls == 0 {
+        return "-".into();
+    }
+    format!(
+        "{} avg {} max {} ×{}",
+        format_ms(timer.last),
+        format_ms(timer.average),
+        format_ms(timer.max),
+        timer.calls
+    )
+}
+
+fn format_ms(duration: Duration) -> String {
+    let ms = duration.as_secs_f64() * 1000.0;
+    if ms < 10.0 {
+        format!("{:.1}ms", ms)
+    } else {
+        format!("{:.0}ms", ms)
+    }
+}
+
+// "75% (3/4)"
+fn format_hits((hits, lookups): (u64, u64)) -> String {
+    if lookups == 0 {
+        return "-".into();
+    }
+    format!("{}% ({}/{})", hits * 100 / lookups, hits, lookups)
+}
+
+// "3 found 1 none 0 err · 120ms"
+fn format_outcomes((found, missing, failed): (u64, u64, u64), latency: &TimerSummary) -> String {
+    format!(
+        "{} found {} none {} err · {}",
+        found,
+        missing,
+        failed,
+        format_ms(latency.average)
+    )
+}
+
+// Top-right corner, over everything but the command line
+pub fn render(f: &mut Frame, report: &DebugReport, theme: Theme) {
+    let rows = report.rows();
+    let area = f.area();
+    let width = 48.min(area.width);
+    let height = (rows.len() as u16 + 2).min(area.height);
+    if width < 12 || height < 3 {
+        return;
+    }
+    let rect = Rect::new(area.right() - width, area.y, width, height);
+    f.render_widget(Clear, rect);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dim))
+        .title(Span::styled(
+            " DEBUG ",
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .style(Style::default().bg(theme.bg));
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(format!("{:<10}", label), Style::default().fg(theme.dim)),
+                Span::styled(value, Style::default().fg(theme.primary)),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines).block(block), rect);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_read_as_timings_and_hit_rates() {
+        let report = DebugReport {
+            frame: TimerSummary {
+                calls: 320,
+                last: Duration::from_micros(12_400),
+                average: Duration::from_micros(9_500),
+                max: Duration::from_millis(48),
+            },
+            artwork_cache: (3, 4),
+            providers: vec![(
+                "lrclib".into(),
+                (3, 1, 0),
+                TimerSummary {
+                    calls: 4,
+                    average: Duration::from_millis(120),
+                    ..Default::default()
+                },
+            )],
+            tasks: vec![("lyrics", "loading".into())],
+            ..Default::default()
+        };
+        let rows: Vec<(String, String)> = report.rows();
+        let row = |label: &str, value: &str| (label.to_string(), value.to_string());
+        assert_eq!(rows[0], row("render", "12ms avg 9.5ms max 48ms ×320"));
+        assert_eq!(rows[1], row("poll", "-"));
+        assert_eq!(rows[3], row("art cache", "75% (3/4)"));
+        assert_eq!(rows[4], row("lyr cache", "-"));
+        assert_eq!(rows[5], row("lrclib", "3 found 1 none 0 err · 120ms"));
+        assert_eq!(rows[6], row("lyrics", "loading"));
+    }
+}
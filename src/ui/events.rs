@@ -0,0 +1,131 @@
+use std::sync::{Arc, Mutex};
+
+use amcli_core::history::{Play, PlayHistory};
+use amcli_core::mqtt::{MqttPublisher, NowPlaying};
+use amcli_core::player::{PlaybackState, Track};
+use amcli_core::remote::RemoteCommand;
+use amcli_core::widget::{WidgetServer, WidgetState};
+use crossterm::event::KeyEvent;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+// Something that happened in the app. Whoever notices it publishes once;
+// everything that reacts to it subscribes instead of being called directly.
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    // A key pressed in the terminal
+    Key(KeyEvent),
+    // A media key or global hotkey
+    Remote(RemoteCommand),
+    // SIGTERM, SIGHUP, or anything else that should end the session
+    Quit,
+    // Every poll of the player
+    Status {
+        state: PlaybackState,
+        track: Option<Track>,
+        artwork_url: Option<String>,
+    },
+    // A different track started, or the same one started over
+    NewPlay(Track),
+}
+
+// Fan-out over unbounded channels; a subscriber whose receiver is gone is
+// dropped on the next publish
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<UnboundedSender<AppEvent>>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> UnboundedReceiver<AppEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.lock().push(tx);
+        rx
+    }
+
+    pub fn publish(&self, event: AppEvent) {
+        self.lock()
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<UnboundedSender<AppEvent>>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// `[mqtt]`: turns each poll into a now-playing message. Ends, taking the
+// publisher with it, once the bus that fed it is dropped.
+pub fn forward_to_mqtt(mqtt: MqttPublisher, mut events: UnboundedReceiver<AppEvent>) {
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            if let AppEvent::Status {
+                state,
+                track,
+                artwork_url,
+            } = event
+            {
+                mqtt.publish(NowPlaying::new(
+                    state,
+                    track.as_ref(),
+                    artwork_url.as_deref(),
+                ));
+            }
+        }
+    });
+}
+
+// `amcli widget`: the same polls for desktop bar widgets, over the socket
+pub fn forward_to_widget(server: WidgetServer, mut events: UnboundedReceiver<AppEvent>) {
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            if let AppEvent::Status {
+                state,
+                track,
+                artwork_url,
+            } = event
+            {
+                server.publish(WidgetState::new(
+                    state,
+                    track.as_ref(),
+                    artwork_url.as_deref(),
+                ));
+            }
+        }
+    });
+}
+
+// `[general] play_history`: logs each new play for `amcli session export`
+pub fn forward_to_history(history: PlayHistory, mut events: UnboundedReceiver<AppEvent>) {
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            if let AppEvent::NewPlay(track) = event {
+                if let Err(e) = history
+                    .append(&Play::new(&track, chrono::Local::now()))
+                    .await
+                {
+                    tracing::warn!("Couldn't log the play: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_subscriber_sees_each_event() {
+        let bus = EventBus::default();
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        drop(bus.subscribe());
+
+        bus.publish(AppEvent::Quit);
+        assert!(matches!(first.try_recv(), Ok(AppEvent::Quit)));
+        assert!(matches!(second.try_recv(), Ok(AppEvent::Quit)));
+        // The closed receiver was pruned
+        assert_eq!(bus.lock().len(), 2);
+    }
+}
@@ -0,0 +1,127 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use amcli_core::favorites::Favorite;
+
+use crate::ui::Theme;
+
+// Tracks starred with `f`, opened with `F` on top of everything else
+#[derive(Default)]
+pub struct FavoritesPanel {
+    selected: usize,
+}
+
+impl FavoritesPanel {
+    pub fn move_selection(&mut self, delta: isize, len: usize) {
+        let last = len.saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+    }
+
+    // Kept in range as items are removed
+    pub fn selected(&mut self, len: usize) -> usize {
+        self.selected = self.selected.min(len.saturating_sub(1));
+        self.selected
+    }
+
+    pub fn render(&self, f: &mut Frame, favorites: &[Favorite], theme: Theme, is_jp: bool) {
+        let area = f.area();
+        let popup_width = 64.min(area.width.saturating_sub(4));
+        let popup_height = 20.min(area.height.saturating_sub(4));
+        let popup_area = Rect {
+            x: area.width.saturating_sub(popup_width) / 2,
+            y: area.height.saturating_sub(popup_height) / 3,
+            width: popup_width,
+            height: popup_height,
+        };
+        f.render_widget(Clear, popup_area);
+
+        let title = if is_jp {
+            format!(" [ お気に入り {} ] ", favorites.len())
+        } else {
+            format!(" [ FAVORITES {} ] ", favorites.len())
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(if theme.is_retro {
+                BorderType::Thick
+            } else {
+                BorderType::Rounded
+            })
+            .border_style(Style::default().fg(theme.accent))
+            .title(Span::styled(
+                title,
+                Style::default()
+                    .fg(theme.primary)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .title_alignment(Alignment::Center)
+            .style(Style::default().bg(theme.bg));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let [list_area, help_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        if favorites.is_empty() {
+            let text = if is_jp {
+                "f で再生中の曲をお気に入りに追加"
+            } else {
+                "PRESS f TO STAR THE PLAYING TRACK"
+            };
+            f.render_widget(
+                Paragraph::new(text)
+                    .style(Style::default().fg(theme.dim))
+                    .alignment(Alignment::Center),
+                list_area,
+            );
+        } else {
+            // Keep the selection in view
+            let selected = self.selected.min(favorites.len() - 1);
+            let rows = list_area.height as usize;
+            let first = selected.saturating_sub(rows.saturating_sub(1));
+            let lines: Vec<Line> = favorites
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(rows)
+                .map(|(i, favorite)| favorite_line(favorite, i == selected, theme))
+                .collect();
+            f.render_widget(Paragraph::new(lines), list_area);
+        }
+
+        f.render_widget(
+            Paragraph::new("↑↓ Select │ ⏎ Play │ x Remove │ Esc Close")
+                .style(Style::default().fg(theme.dim))
+                .alignment(Alignment::Center),
+            help_area,
+        );
+    }
+}
+
+fn favorite_line(favorite: &Favorite, selected: bool, theme: Theme) -> Line<'static> {
+    let name_style = if selected {
+        Style::default()
+            .fg(theme.bg)
+            .bg(theme.accent)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.primary)
+    };
+    let mut spans = vec![
+        Span::styled("★ ", Style::default().fg(theme.accent)),
+        Span::styled(favorite.name.clone(), name_style),
+    ];
+    let detail = match (favorite.artist.is_empty(), favorite.album.is_empty()) {
+        (true, _) => String::new(),
+        (false, true) => format!("  {}", favorite.artist),
+        (false, false) => format!("  {} · {}", favorite.artist, favorite.album),
+    };
+    if !detail.is_empty() {
+        spans.push(Span::styled(detail, Style::default().fg(theme.dim)));
+    }
+    Line::from(spans)
+}
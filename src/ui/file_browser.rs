@@ -0,0 +1,371 @@
+use anyhow::{anyhow, Result};
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use std::path::{Path, PathBuf};
+
+use amcli_core::player::{is_audio_file, is_cue_sheet, Track};
+
+use crate::ui::{format_duration, Theme};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+impl BrowserEntry {
+    fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+// Folders and audio files below `[player] browse_roots`, opened with `o` for
+// the internal player
+#[derive(Debug, Default)]
+pub struct FileBrowser {
+    roots: Vec<PathBuf>,
+    // The folder being listed; `None` lists the roots themselves
+    dir: Option<PathBuf>,
+    entries: Vec<BrowserEntry>,
+    selected: usize,
+    // Tags of the selected file, once read
+    preview: Option<(PathBuf, Track)>,
+}
+
+impl FileBrowser {
+    // A single root is listed straight away; several start from a list of them
+    pub async fn open(roots: Vec<PathBuf>) -> Result<Self> {
+        if roots.is_empty() {
+            return Err(anyhow!("Set player.browse_roots to browse for files"));
+        }
+        let mut browser = Self {
+            roots,
+            ..Self::default()
+        };
+        let dir = (browser.roots.len() == 1).then(|| browser.roots[0].clone());
+        browser.list(dir).await?;
+        Ok(browser)
+    }
+
+    async fn list(&mut self, dir: Option<PathBuf>) -> Result<()> {
+        self.entries = match &dir {
+            Some(dir) => read_entries(dir).await?,
+            None => self
+                .roots
+                .iter()
+                .map(|root| BrowserEntry {
+                    path: root.clone(),
+                    is_dir: true,
+                })
+                .collect(),
+        };
+        self.dir = dir;
+        self.selected = 0;
+        Ok(())
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.entries.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+    }
+
+    pub fn selected(&self) -> Option<&BrowserEntry> {
+        self.entries.get(self.selected)
+    }
+
+    // Into the selected folder; false when a file is selected
+    pub async fn enter(&mut self) -> Result<bool> {
+        match self.selected() {
+            Some(entry) if entry.is_dir => {
+                let dir = entry.path.clone();
+                self.list(Some(dir)).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    // Back to the parent folder, with the one just left selected. Stops at the
+    // roots.
+    pub async fn up(&mut self) -> Result<()> {
+        let Some(dir) = self.dir.clone() else {
+            return Ok(());
+        };
+        let parent = if self.roots.contains(&dir) {
+            if self.roots.len() == 1 {
+                return Ok(());
+            }
+            None
+        } else {
+            dir.parent().map(Path::to_path_buf)
+        };
+        self.list(parent).await?;
+        if let Some(index) = self.entries.iter().position(|e| e.path == dir) {
+            self.selected = index;
+        }
+        Ok(())
+    }
+
+    // The selected file, when its tags haven't been read yet
+    pub fn needs_preview(&self) -> Option<&Path> {
+        let entry = self.selected().filter(|entry| !entry.is_dir)?;
+        match &self.preview {
+            Some((path, _)) if *path == entry.path => None,
+            _ => Some(&entry.path),
+        }
+    }
+
+    pub fn set_preview(&mut self, path: PathBuf, track: Track) {
+        self.preview = Some((path, track));
+    }
+
+    pub fn render(&self, f: &mut Frame, theme: Theme, is_jp: bool) {
+        let area = f.area();
+        let popup_width = 90.min(area.width.saturating_sub(4));
+        let popup_height = 24.min(area.height.saturating_sub(4));
+        let popup_area = Rect {
+            x: area.width.saturating_sub(popup_width) / 2,
+            y: area.height.saturating_sub(popup_height) / 3,
+            width: popup_width,
+            height: popup_height,
+        };
+        f.render_widget(Clear, popup_area);
+
+        let location = match &self.dir {
+            Some(dir) => dir.display().to_string(),
+            None if is_jp => "ルート".to_string(),
+            None => "ROOTS".to_string(),
+        };
+        let title = if is_jp {
+            format!(" [ ファイル: {} ] ", location)
+        } else {
+            format!(" [ FILES: {} ] ", location)
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(if theme.is_retro {
+                BorderType::Thick
+            } else {
+                BorderType::Rounded
+            })
+            .border_style(Style::default().fg(theme.accent))
+            .title(Span::styled(
+                title,
+                Style::default()
+                    .fg(theme.primary)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .title_alignment(Alignment::Center)
+            .style(Style::default().bg(theme.bg));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let [body_area, help_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        // The preview drops out first on narrow terminals
+        let (list_area, preview_area) = if body_area.width >= 60 {
+            let [list, preview] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Length(30)]).areas(body_area);
+            (list, Some(preview))
+        } else {
+            (body_area, None)
+        };
+
+        if self.entries.is_empty() {
+            let text = if is_jp {
+                "オーディオファイルがありません"
+            } else {
+                "NO AUDIO FILES HERE"
+            };
+            f.render_widget(
+                Paragraph::new(text)
+                    .style(Style::default().fg(theme.dim))
+                    .alignment(Alignment::Center),
+                list_area,
+            );
+        } else {
+            // Keep the selection in view
+            let rows = list_area.height as usize;
+            let first = self.selected.saturating_sub(rows.saturating_sub(1));
+            let lines: Vec<Line> = self
+                .entries
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(rows)
+                .map(|(i, entry)| entry_line(entry, i == self.selected, theme))
+                .collect();
+            f.render_widget(Paragraph::new(lines), list_area);
+        }
+
+        if let Some(preview_area) = preview_area {
+            let block = Block::default()
+                .borders(Borders::LEFT)
+                .border_style(Style::default().fg(theme.dim));
+            let text = self.preview_lines(theme, is_jp);
+            f.render_widget(
+                Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+                preview_area,
+            );
+        }
+
+        f.render_widget(
+            Paragraph::new("↑↓ Select │ ⏎ Open/Play │ ← Up │ a Add │ p Play All │ Esc Close")
+                .style(Style::default().fg(theme.dim))
+                .alignment(Alignment::Center),
+            help_area,
+        );
+    }
+
+    fn preview_lines(&self, theme: Theme, is_jp: bool) -> Vec<Line<'static>> {
+        let Some(entry) = self.selected() else {
+            return Vec::new();
+        };
+        let label = |text: &'static str| Span::styled(text, Style::default().fg(theme.dim));
+        if entry.is_dir {
+            return vec![
+                Line::from(Span::styled(
+                    format!(" {}/", entry.name()),
+                    Style::default()
+                        .fg(theme.primary)
+                        .add_modifier(Modifier::BOLD),
+                )),
+                Line::from(""),
+                Line::from(label(if is_jp {
+                    " ⏎ 開く · p 全て再生 · a 全て追加"
+                } else {
+                    " ⏎ open · p play all · a add all"
+                })),
+            ];
+        }
+        let Some((_, track)) = self
+            .preview
+            .as_ref()
+            .filter(|(path, _)| *path == entry.path)
+        else {
+            return Vec::new();
+        };
+        let kind = entry
+            .path
+            .extension()
+            .map(|e| e.to_string_lossy().to_uppercase())
+            .unwrap_or_default();
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!(" {}", track.name),
+                Style::default()
+                    .fg(theme.primary)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+        ];
+        for (name, value) in [
+            (" Artist ", track.artist.clone()),
+            (" Album  ", track.album.clone()),
+            (" Length ", format_duration(track.duration)),
+            (" Type   ", kind),
+        ] {
+            if value.is_empty() || (name == " Length " && track.duration.is_zero()) {
+                continue;
+            }
+            lines.push(Line::from(vec![
+                label(name),
+                Span::styled(value, Style::default().fg(theme.primary)),
+            ]));
+        }
+        lines
+    }
+}
+
+// Folders first, then audio files and cue sheets, each by name; hidden
+// entries are skipped
+async fn read_entries(dir: &Path) -> Result<Vec<BrowserEntry>> {
+    let mut read = tokio::fs::read_dir(dir)
+        .await
+        .map_err(|e| anyhow!("Couldn't open {}: {}", dir.display(), e))?;
+    let mut entries = Vec::new();
+    while let Some(entry) = read.next_entry().await? {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let is_dir = tokio::fs::metadata(&path)
+            .await
+            .is_ok_and(|meta| meta.is_dir());
+        if is_dir || is_audio_file(&path) || is_cue_sheet(&path) {
+            entries.push(BrowserEntry { path, is_dir });
+        }
+    }
+    entries.sort_by_key(|entry| (!entry.is_dir, entry.name().to_lowercase()));
+    Ok(entries)
+}
+
+fn entry_line(entry: &BrowserEntry, selected: bool, theme: Theme) -> Line<'static> {
+    let style = if selected {
+        Style::default()
+            .fg(theme.bg)
+            .bg(theme.accent)
+            .add_modifier(Modifier::BOLD)
+    } else if entry.is_dir {
+        Style::default().fg(theme.accent)
+    } else {
+        Style::default().fg(theme.primary)
+    };
+    let (icon, name) = if entry.is_dir {
+        ("▸ ", format!("{}/", entry.name()))
+    } else {
+        ("♪ ", entry.name())
+    };
+    Line::from(vec![
+        Span::styled(icon, Style::default().fg(theme.dim)),
+        Span::styled(name, style),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_folders_then_audio_files_and_walks_back_up() {
+        let root = std::env::temp_dir().join(format!("amcli-browser-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("Kind of Blue")).unwrap();
+        std::fs::create_dir_all(root.join(".cache")).unwrap();
+        for name in [
+            "Kind of Blue/So What.flac",
+            "b.mp3",
+            "A.m4a",
+            "cover.jpg",
+            "c.cue",
+        ] {
+            std::fs::write(root.join(name), b"").unwrap();
+        }
+
+        let mut browser = FileBrowser::open(vec![root.clone()]).await.unwrap();
+        let names: Vec<String> = browser.entries.iter().map(BrowserEntry::name).collect();
+        assert_eq!(names, ["Kind of Blue", "A.m4a", "b.mp3", "c.cue"]);
+        assert_eq!(browser.needs_preview(), None);
+
+        assert!(browser.enter().await.unwrap());
+        assert_eq!(
+            browser.needs_preview(),
+            Some(root.join("Kind of Blue/So What.flac").as_path())
+        );
+        assert!(!browser.enter().await.unwrap());
+
+        browser.up().await.unwrap();
+        assert_eq!(browser.selected().unwrap().path, root.join("Kind of Blue"));
+        // A lone root is as far up as it goes
+        browser.up().await.unwrap();
+        assert_eq!(browser.dir.as_deref(), Some(root.as_path()));
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,179 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Span,
+    Frame,
+};
+
+use crate::ui::Theme;
+
+// Panels in left-to-right, top-to-bottom order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    Artwork,
+    Metadata,
+    Lyrics,
+    Queue,
+}
+
+impl Panel {
+    pub fn label(self, is_jp: bool) -> &'static str {
+        match (self, is_jp) {
+            (Panel::Artwork, false) => "ART",
+            (Panel::Artwork, true) => "画像",
+            (Panel::Metadata, false) => "INFO",
+            (Panel::Metadata, true) => "情報",
+            (Panel::Lyrics, false) => "LYRICS",
+            (Panel::Lyrics, true) => "歌詞",
+            (Panel::Queue, false) => "QUEUE",
+            (Panel::Queue, true) => "次曲",
+        }
+    }
+}
+
+// Tracks which panel owns panel-specific keys. The layout reports which panels
+// it actually drew each frame so navigation skips anything hidden.
+#[derive(Debug)]
+pub struct FocusManager {
+    current: Panel,
+    visible: Vec<Panel>,
+    // The indicator stays hidden until the user first navigates
+    engaged: bool,
+}
+
+impl Default for FocusManager {
+    fn default() -> Self {
+        Self {
+            current: Panel::Metadata,
+            visible: vec![Panel::Metadata],
+            engaged: false,
+        }
+    }
+}
+
+impl FocusManager {
+    pub fn current(&self) -> Panel {
+        self.current
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    pub fn set_visible(&mut self, visible: Vec<Panel>) {
+        if !visible.contains(&self.current) {
+            self.current = visible.first().copied().unwrap_or(Panel::Metadata);
+        }
+        self.visible = visible;
+    }
+
+    pub fn focus_next(&mut self) {
+        self.step(1);
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.step(-1);
+    }
+
+    // Jumps straight to `panel` when it's on screen
+    pub fn focus(&mut self, panel: Panel) -> bool {
+        self.engaged = true;
+        if self.visible.contains(&panel) {
+            self.current = panel;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Starts on `panel` before the first frame reports what's visible; falls
+    // back like any hidden panel if it doesn't get drawn
+    pub fn start_on(&mut self, panel: Panel) {
+        self.engaged = true;
+        self.current = panel;
+    }
+
+    // Moves between vertically stacked panels; only metadata and lyrics share a column.
+    pub fn focus_below(&mut self) -> bool {
+        self.move_to(Panel::Metadata, Panel::Lyrics)
+    }
+
+    pub fn focus_above(&mut self) -> bool {
+        self.move_to(Panel::Lyrics, Panel::Metadata)
+    }
+
+    fn move_to(&mut self, from: Panel, to: Panel) -> bool {
+        self.engaged = true;
+        if self.current == from && self.visible.contains(&to) {
+            self.current = to;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn step(&mut self, delta: isize) {
+        self.engaged = true;
+        if self.visible.is_empty() {
+            return;
+        }
+        let len = self.visible.len() as isize;
+        let index = self
+            .visible
+            .iter()
+            .position(|p| *p == self.current)
+            .unwrap_or(0) as isize;
+        self.current = self.visible[(index + delta).rem_euclid(len) as usize];
+    }
+}
+
+// Accent corner brackets plus a tag, so the panel keeps all of its content area.
+pub fn draw_focus_indicator(f: &mut Frame, area: Rect, panel: Panel, theme: Theme, is_jp: bool) {
+    if area.width < 4 || area.height < 2 {
+        return;
+    }
+    let style = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let buf = f.buffer_mut();
+    let (left, right) = (area.x, area.right() - 1);
+    let (top, bottom) = (area.y, area.bottom() - 1);
+    buf.set_string(left, top, "┌", style);
+    buf.set_string(right, top, "┐", style);
+    buf.set_string(left, bottom, "└", style);
+    buf.set_string(right, bottom, "┘", style);
+
+    let tag = Span::styled(format!(" {} ", panel.label(is_jp)), style);
+    buf.set_span(left + 1, bottom, &tag, area.width - 2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn horizontal_navigation_wraps_through_visible_panels() {
+        let mut focus = FocusManager::default();
+        focus.set_visible(vec![Panel::Artwork, Panel::Metadata, Panel::Lyrics]);
+
+        focus.focus_next();
+        assert_eq!(focus.current(), Panel::Lyrics);
+        focus.focus_next();
+        assert_eq!(focus.current(), Panel::Artwork);
+        focus.focus_prev();
+        assert_eq!(focus.current(), Panel::Lyrics);
+        assert!(focus.is_engaged());
+    }
+
+    #[test]
+    fn hidden_panels_are_skipped_and_focus_falls_back() {
+        let mut focus = FocusManager::default();
+        focus.set_visible(vec![Panel::Metadata, Panel::Lyrics]);
+        assert!(focus.focus_below());
+        assert_eq!(focus.current(), Panel::Lyrics);
+
+        focus.set_visible(vec![Panel::Metadata]);
+        assert_eq!(focus.current(), Panel::Metadata);
+        assert!(!focus.focus_below());
+    }
+}
@@ -0,0 +1,151 @@
+use image::DynamicImage;
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::Paragraph,
+    Frame,
+};
+use ratatui_image::protocol::StatefulProtocol;
+use ratatui_image::{Resize, StatefulImage};
+
+use amcli_core::artwork::{self, recent::RecentAlbum};
+
+use crate::ui::Theme;
+
+// The attract-mode screen shown while nothing is playing: covers of recently
+// played albums, one of them selected to play with Enter.
+#[derive(Default)]
+pub struct IdleGrid {
+    albums: Vec<RecentAlbum>,
+    covers: Vec<DynamicImage>,
+    selected: usize,
+    // Set once the covers have been looked for, even if none could be shown
+    loaded: bool,
+    pub image: Option<StatefulProtocol>,
+}
+
+impl IdleGrid {
+    pub fn set(&mut self, loaded: Vec<(RecentAlbum, DynamicImage)>) {
+        (self.albums, self.covers) = loaded.into_iter().unzip();
+        self.selected = 0;
+        self.loaded = true;
+        self.image = None;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.image.is_some()
+    }
+
+    pub fn selected(&self) -> Option<&RecentAlbum> {
+        self.albums.get(self.selected)
+    }
+
+    // As square as the covers allow
+    fn columns(&self) -> usize {
+        ((self.covers.len() as f64).sqrt().ceil() as usize).max(1)
+    }
+
+    // Moves within the grid without wrapping; false when already at the edge
+    pub fn move_selection(&mut self, columns: isize, rows: isize) -> bool {
+        let width = self.columns() as isize;
+        let column = (self.selected as isize % width) + columns;
+        let row = (self.selected as isize / width) + rows;
+        let index = row * width + column;
+        if !(0..width).contains(&column) || row < 0 || index >= self.covers.len() as isize {
+            return false;
+        }
+        self.selected = index as usize;
+        true
+    }
+
+    // `None` until there's at least one cover to show
+    pub fn compose(&self, theme: Theme) -> Option<DynamicImage> {
+        (!self.covers.is_empty()).then(|| {
+            artwork::compose_grid(
+                &self.covers,
+                self.columns() as u32,
+                self.selected,
+                theme.bg,
+                theme.accent,
+            )
+        })
+    }
+}
+
+pub fn draw(f: &mut Frame, area: Rect, grid: &mut IdleGrid, theme: Theme) {
+    let [covers, caption] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+    if let Some(protocol) = grid.image.as_mut() {
+        let rect = super::artwork_protocol_rect(super::inset_rect(covers, 1), protocol);
+        f.render_stateful_widget(
+            StatefulImage::default().resize(Resize::Fit(None)),
+            rect,
+            protocol,
+        );
+    }
+    if let Some(album) = grid.selected() {
+        let text = format!("{} — {}", album.album, album.artist);
+        let style = Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD);
+        f.render_widget(
+            Paragraph::new(text)
+                .style(style)
+                .alignment(Alignment::Center),
+            caption,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn grid(count: usize) -> IdleGrid {
+        let mut grid = IdleGrid::default();
+        grid.set(
+            (0..count)
+                .map(|i| {
+                    let album = RecentAlbum {
+                        artist: "Artist".into(),
+                        album: i.to_string(),
+                        artwork_url: format!("https://covers/{}.jpg", i),
+                    };
+                    let cover = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+                    (album, DynamicImage::ImageRgba8(cover))
+                })
+                .collect(),
+        );
+        grid
+    }
+
+    #[test]
+    fn selection_stays_inside_the_grid() {
+        // Five covers make a 3x2 grid with one empty slot
+        let mut grid = grid(5);
+        assert!(!grid.move_selection(-1, 0));
+        assert!(!grid.move_selection(0, -1));
+        assert!(grid.move_selection(2, 0));
+        assert!(!grid.move_selection(1, 0));
+        assert!(!grid.move_selection(0, 1));
+        assert!(grid.move_selection(-1, 1));
+        assert_eq!(grid.selected().unwrap().album, "4");
+    }
+
+    #[test]
+    fn an_empty_history_has_nothing_to_show() {
+        let grid = grid(0);
+        assert!(grid.is_loaded());
+        assert!(grid.selected().is_none());
+        assert!(grid.compose(crate::ui::THEMES[0]).is_none());
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::ui::keymap::Action;
+use crate::ui::App;
+
+// A panel drawn over the main view that takes every key while it's open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+    ThemeEditor,
+    QuickSwitcher,
+    Favorites,
+    FileBrowser,
+    Stations,
+    ArtistPanel,
+    Settings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayAction {
+    Close,
+    // Selection in a list, or scrolling for the artist panel
+    Move(isize),
+    // The theme editor's color channel, or a setting's value
+    Adjust(i16),
+    SwitchTab(i32),
+    // Enter: play, open, or save whatever is selected
+    Confirm,
+    Remove,
+    // Up a directory in the file browser
+    Back,
+    Enqueue,
+    Play,
+    Type(char),
+    Backspace,
+}
+
+// j/k and the arrows move in every list; the quick switcher takes letters
+// as its query, so it moves with the arrows and fzf's Ctrl keys instead.
+// Keys an overlay doesn't use are dropped rather than reaching the keymap.
+pub fn overlay_action(overlay: Overlay, key: KeyEvent) -> Option<OverlayAction> {
+    use OverlayAction::*;
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    Some(match (overlay, key.code) {
+        (Overlay::QuickSwitcher, code) => match code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Confirm,
+            KeyCode::Up => Move(-1),
+            KeyCode::Down => Move(1),
+            KeyCode::Char('p' | 'k') if ctrl => Move(-1),
+            KeyCode::Char('n' | 'j') if ctrl => Move(1),
+            KeyCode::Backspace => Backspace,
+            KeyCode::Char(c) => Type(c),
+            _ => return None,
+        },
+        (_, KeyCode::Esc) => Close,
+        (_, KeyCode::Up | KeyCode::Char('k')) => Move(-1),
+        (_, KeyCode::Down | KeyCode::Char('j')) => Move(1),
+        (Overlay::ThemeEditor, code) => match code {
+            KeyCode::Left | KeyCode::Char('h') => Adjust(-8),
+            KeyCode::Right | KeyCode::Char('l') => Adjust(8),
+            KeyCode::Char('H') => Adjust(-1),
+            KeyCode::Char('L') => Adjust(1),
+            KeyCode::Enter => Confirm,
+            _ => return None,
+        },
+        (Overlay::Favorites, code) => match code {
+            KeyCode::Char('F') => Close,
+            KeyCode::Enter => Confirm,
+            KeyCode::Char('x') | KeyCode::Delete => Remove,
+            _ => return None,
+        },
+        (Overlay::FileBrowser, code) => match code {
+            KeyCode::Char('o') => Close,
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => Confirm,
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => Back,
+            KeyCode::Char('a') => Enqueue,
+            KeyCode::Char('p') => Play,
+            _ => return None,
+        },
+        (Overlay::Stations, code) => match code {
+            KeyCode::Char('w') => Close,
+            KeyCode::Enter => Confirm,
+            KeyCode::Char('x') | KeyCode::Delete => Remove,
+            _ => return None,
+        },
+        (Overlay::ArtistPanel, KeyCode::Char('i')) => Close,
+        (Overlay::Settings, code) => match code {
+            KeyCode::Char('s' | 'S') => Close,
+            KeyCode::Left | KeyCode::Char('h') => Adjust(-1),
+            KeyCode::Right | KeyCode::Char('l') => Adjust(1),
+            KeyCode::Tab => SwitchTab(1),
+            KeyCode::BackTab => SwitchTab(-1),
+            KeyCode::Enter | KeyCode::Char(' ') => Confirm,
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+impl App {
+    // Keys arrive over the event bus; the splash screen gets the first one,
+    // then the command line, an open overlay, and finally the keymap
+    pub(super) async fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        // Ctrl+C quits the same way `q` does, so the session is still saved
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.perform(Action::Quit).await;
+            return Ok(());
+        }
+        if self.is_splash_active() {
+            self.skip_splash();
+            return Ok(());
+        }
+        if self.is_command_mode() {
+            let line = &mut self.command_line;
+            match key.code {
+                KeyCode::Esc => line.close(),
+                KeyCode::Enter => self.submit_command().await?,
+                KeyCode::Tab => line.complete(),
+                KeyCode::Backspace => line.backspace(),
+                KeyCode::Up => line.history_prev(),
+                KeyCode::Down => line.history_next(),
+                KeyCode::Char(c) => line.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(overlay) = self.open_overlay() {
+            if let Some(action) = overlay_action(overlay, key) {
+                if let Err(e) = self.perform_overlay(overlay, action).await {
+                    self.command_line.set_message(e.to_string(), true);
+                }
+            }
+            return Ok(());
+        }
+        self.command_line.clear_message();
+        if let Some(action) = self.resolve_key(key) {
+            self.perform(action).await;
+        }
+        Ok(())
+    }
+
+    // The topmost overlay, when more than one is open
+    fn open_overlay(&self) -> Option<Overlay> {
+        [
+            (self.is_theme_editor_open(), Overlay::ThemeEditor),
+            (self.is_quick_switcher_open(), Overlay::QuickSwitcher),
+            (self.is_favorites_open(), Overlay::Favorites),
+            (self.is_file_browser_open(), Overlay::FileBrowser),
+            (self.is_stations_open(), Overlay::Stations),
+            (self.is_artist_panel_open(), Overlay::ArtistPanel),
+            (self.is_settings_open(), Overlay::Settings),
+        ]
+        .into_iter()
+        .find_map(|(open, overlay)| open.then_some(overlay))
+    }
+
+    async fn perform_overlay(&mut self, overlay: Overlay, action: OverlayAction) -> Result<()> {
+        use OverlayAction::*;
+        match (overlay, action) {
+            (Overlay::ThemeEditor, Close) => self.close_theme_editor(),
+            (Overlay::ThemeEditor, Move(delta)) => self.theme_editor_navigate(delta),
+            (Overlay::ThemeEditor, Adjust(delta)) => self.theme_editor_adjust(delta),
+            (Overlay::ThemeEditor, Confirm) => self.open_save_theme_prompt(),
+            (Overlay::QuickSwitcher, Close) => self.close_quick_switcher(),
+            (Overlay::QuickSwitcher, Confirm) => self.submit_quick_switcher().await?,
+            (Overlay::QuickSwitcher, Move(delta)) => self.quick_switcher_move(delta),
+            (Overlay::QuickSwitcher, Backspace) => self.quick_switcher_backspace(),
+            (Overlay::QuickSwitcher, Type(c)) => self.quick_switcher_push(c),
+            (Overlay::Favorites, Close) => self.close_favorites(),
+            (Overlay::Favorites, Move(delta)) => self.favorites_move(delta),
+            (Overlay::Favorites, Confirm) => self.play_selected_favorite().await?,
+            (Overlay::Favorites, Remove) => self.remove_selected_favorite().await?,
+            (Overlay::FileBrowser, Close) => self.close_file_browser(),
+            (Overlay::FileBrowser, Move(delta)) => self.file_browser_move(delta).await,
+            (Overlay::FileBrowser, Confirm) => self.file_browser_open().await,
+            (Overlay::FileBrowser, Back) => self.file_browser_up().await,
+            (Overlay::FileBrowser, Enqueue) => self.file_browser_enqueue().await,
+            (Overlay::FileBrowser, Play) => self.file_browser_play().await,
+            (Overlay::Stations, Close) => self.close_stations(),
+            (Overlay::Stations, Move(delta)) => self.stations_move(delta),
+            (Overlay::Stations, Confirm) => self.play_selected_station().await,
+            (Overlay::Stations, Remove) => self.remove_selected_station().await?,
+            (Overlay::ArtistPanel, Close) => self.close_artist_panel(),
+            (Overlay::ArtistPanel, Move(delta)) => self.artist_panel_scroll(delta as i16),
+            (Overlay::Settings, Close) => self.close_settings(),
+            (Overlay::Settings, Move(delta)) if delta < 0 => self.settings_navigate_up(),
+            (Overlay::Settings, Move(_)) => self.settings_navigate_down(),
+            (Overlay::Settings, Adjust(delta)) => self.settings_adjust(delta.into()).await?,
+            (Overlay::Settings, SwitchTab(delta)) => self.settings_switch_tab(delta),
+            (Overlay::Settings, Confirm) => self.settings_select().await?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn overlays_map_their_own_keys() {
+        use OverlayAction::*;
+        // Letters are the switcher's query, but move in the lists
+        let k = key(KeyCode::Char('k'));
+        assert_eq!(overlay_action(Overlay::QuickSwitcher, k), Some(Type('k')));
+        assert_eq!(overlay_action(Overlay::Favorites, k), Some(Move(-1)));
+        let ctrl_n = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert_eq!(
+            overlay_action(Overlay::QuickSwitcher, ctrl_n),
+            Some(Move(1))
+        );
+
+        // Each overlay's own key closes it, alongside Esc
+        for (overlay, c) in [
+            (Overlay::Favorites, 'F'),
+            (Overlay::FileBrowser, 'o'),
+            (Overlay::Stations, 'w'),
+            (Overlay::ArtistPanel, 'i'),
+            (Overlay::Settings, 's'),
+        ] {
+            assert_eq!(overlay_action(overlay, key(KeyCode::Char(c))), Some(Close));
+            assert_eq!(overlay_action(overlay, key(KeyCode::Esc)), Some(Close));
+        }
+
+        assert_eq!(
+            overlay_action(Overlay::ThemeEditor, key(KeyCode::Char('H'))),
+            Some(Adjust(-1))
+        );
+        assert_eq!(
+            overlay_action(Overlay::Settings, key(KeyCode::BackTab)),
+            Some(SwitchTab(-1))
+        );
+        // Unused keys are swallowed, not passed on to the keymap
+        assert_eq!(
+            overlay_action(Overlay::ArtistPanel, key(KeyCode::Char('q'))),
+            None
+        );
+    }
+}
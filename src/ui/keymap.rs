@@ -0,0 +1,534 @@
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::{Duration, Instant};
+
+use amcli_core::config::KeybindingsConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    ToggleSettings,
+    TogglePlayback,
+    NextTrack,
+    PreviousTrack,
+    VolumeUp,
+    VolumeDown,
+    VolumeUpFine,
+    VolumeDownFine,
+    ToggleMute,
+    SeekForward,
+    SeekBackward,
+    GoToStart,
+    JumpToTime,
+    NavigateUp,
+    NavigateDown,
+    NavigateLeft,
+    NavigateRight,
+    CycleRepeat,
+    NextTheme,
+    ToggleHelp,
+    CommandMode,
+    ToggleQueue,
+    ToggleBigText,
+    ArtistInfo,
+    PlaySimilar,
+    RateUp,
+    RateDown,
+    AddBookmark,
+    NextBookmark,
+    PreviousBookmark,
+    Snapshot,
+    DebugOverlay,
+    SearchLyrics,
+    LyricsNextMatch,
+    LyricsPreviousMatch,
+    LyricsSeek,
+    QueueMoveUp,
+    QueueMoveDown,
+    QueueRemove,
+    RetryArtwork,
+    QuickSwitcher,
+    JumpToArtist,
+    JumpToAlbum,
+    ToggleNormalization,
+    BalanceLeft,
+    BalanceRight,
+    ToggleFavorite,
+    Favorites,
+    EditNote,
+    FocusTimer,
+    FileBrowser,
+    Stations,
+}
+
+// Config names for every action, used by `[keybindings]` overrides.
+const ACTION_NAMES: &[(&str, Action)] = &[
+    ("quit", Action::Quit),
+    ("settings", Action::ToggleSettings),
+    ("play_pause", Action::TogglePlayback),
+    ("next", Action::NextTrack),
+    ("previous", Action::PreviousTrack),
+    ("volume_up", Action::VolumeUp),
+    ("volume_down", Action::VolumeDown),
+    ("volume_up_fine", Action::VolumeUpFine),
+    ("volume_down_fine", Action::VolumeDownFine),
+    ("mute", Action::ToggleMute),
+    ("seek_forward", Action::SeekForward),
+    ("seek_backward", Action::SeekBackward),
+    ("go_to_start", Action::GoToStart),
+    ("jump_to_time", Action::JumpToTime),
+    ("navigate_up", Action::NavigateUp),
+    ("navigate_down", Action::NavigateDown),
+    ("navigate_left", Action::NavigateLeft),
+    ("navigate_right", Action::NavigateRight),
+    ("repeat", Action::CycleRepeat),
+    ("theme", Action::NextTheme),
+    ("help", Action::ToggleHelp),
+    ("command", Action::CommandMode),
+    ("queue", Action::ToggleQueue),
+    ("big_text", Action::ToggleBigText),
+    ("artist_info", Action::ArtistInfo),
+    ("play_similar", Action::PlaySimilar),
+    ("rate_up", Action::RateUp),
+    ("rate_down", Action::RateDown),
+    ("bookmark", Action::AddBookmark),
+    ("next_bookmark", Action::NextBookmark),
+    ("previous_bookmark", Action::PreviousBookmark),
+    ("snapshot", Action::Snapshot),
+    ("debug_overlay", Action::DebugOverlay),
+    ("search_lyrics", Action::SearchLyrics),
+    ("lyrics_next_match", Action::LyricsNextMatch),
+    ("lyrics_previous_match", Action::LyricsPreviousMatch),
+    ("lyrics_seek", Action::LyricsSeek),
+    ("queue_move_up", Action::QueueMoveUp),
+    ("queue_move_down", Action::QueueMoveDown),
+    ("queue_remove", Action::QueueRemove),
+    ("retry_artwork", Action::RetryArtwork),
+    ("quick_switcher", Action::QuickSwitcher),
+    ("jump_to_artist", Action::JumpToArtist),
+    ("jump_to_album", Action::JumpToAlbum),
+    ("normalization", Action::ToggleNormalization),
+    ("balance_left", Action::BalanceLeft),
+    ("balance_right", Action::BalanceRight),
+    ("favorite", Action::ToggleFavorite),
+    ("favorites", Action::Favorites),
+    ("note", Action::EditNote),
+    ("focus_timer", Action::FocusTimer),
+    ("file_browser", Action::FileBrowser),
+    ("stations", Action::Stations),
+];
+
+const DEFAULT_BINDINGS: &[(&str, Action)] = &[
+    ("q", Action::Quit),
+    ("s", Action::ToggleSettings),
+    ("S", Action::ToggleSettings),
+    ("space", Action::TogglePlayback),
+    ("]", Action::NextTrack),
+    ("[", Action::PreviousTrack),
+    ("=", Action::VolumeUp),
+    ("-", Action::VolumeDown),
+    // Shifted: 1% at a time
+    ("+", Action::VolumeUpFine),
+    ("_", Action::VolumeDownFine),
+    ("m", Action::ToggleMute),
+    ("right", Action::SeekForward),
+    (".", Action::SeekForward),
+    ("left", Action::SeekBackward),
+    (",", Action::SeekBackward),
+    ("g g", Action::GoToStart),
+    ("G", Action::JumpToTime),
+    ("k", Action::NavigateUp),
+    ("up", Action::NavigateUp),
+    ("j", Action::NavigateDown),
+    ("down", Action::NavigateDown),
+    ("h", Action::NavigateLeft),
+    ("l", Action::NavigateRight),
+    ("r", Action::CycleRepeat),
+    ("t", Action::NextTheme),
+    ("?", Action::ToggleHelp),
+    (":", Action::CommandMode),
+    ("u", Action::ToggleQueue),
+    ("b", Action::ToggleBigText),
+    ("i", Action::ArtistInfo),
+    ("R", Action::PlaySimilar),
+    (">", Action::RateUp),
+    ("<", Action::RateDown),
+    ("B", Action::AddBookmark),
+    ("'", Action::NextBookmark),
+    ("\"", Action::PreviousBookmark),
+    ("P", Action::Snapshot),
+    ("f12", Action::DebugOverlay),
+    ("/", Action::SearchLyrics),
+    ("n", Action::LyricsNextMatch),
+    ("N", Action::LyricsPreviousMatch),
+    ("enter", Action::LyricsSeek),
+    ("K", Action::QueueMoveUp),
+    ("J", Action::QueueMoveDown),
+    ("x", Action::QueueRemove),
+    ("A", Action::RetryArtwork),
+    ("ctrl+p", Action::QuickSwitcher),
+    ("g a", Action::JumpToArtist),
+    ("g A", Action::JumpToAlbum),
+    ("V", Action::ToggleNormalization),
+    ("{", Action::BalanceLeft),
+    ("}", Action::BalanceRight),
+    ("f", Action::ToggleFavorite),
+    ("F", Action::Favorites),
+    ("a", Action::EditNote),
+    ("p", Action::FocusTimer),
+    ("o", Action::FileBrowser),
+    ("w", Action::Stations),
+];
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTION_NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, a)| *a)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyPress {
+    fn from_event(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        // Shifted characters arrive already uppercased/symbolised; shift only
+        // matters for keys that have no character of their own.
+        if !matches!(event.code, KeyCode::Char(_)) {
+            modifiers |= event.modifiers & KeyModifiers::SHIFT;
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+
+    fn parse(token: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = token;
+        loop {
+            if let Some(r) = rest.strip_prefix("ctrl+") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("alt+") {
+                modifiers |= KeyModifiers::ALT;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("shift+") {
+                modifiers |= KeyModifiers::SHIFT;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+
+        let code = match rest {
+            "space" => KeyCode::Char(' '),
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            f if f.len() > 1 && f.starts_with('f') => {
+                let n: u8 = f[1..]
+                    .parse()
+                    .map_err(|_| anyhow!("Unknown key: {}", token))?;
+                KeyCode::F(n)
+            }
+            s => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        modifiers.remove(KeyModifiers::SHIFT);
+                        KeyCode::Char(c.to_ascii_uppercase())
+                    }
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(anyhow!("Unknown key: {}", token)),
+                }
+            }
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "SPC".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "⏎".to_string(),
+            KeyCode::Esc => "ESC".to_string(),
+            KeyCode::Tab => "TAB".to_string(),
+            KeyCode::Backspace => "BS".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "HOME".to_string(),
+            KeyCode::End => "END".to_string(),
+            KeyCode::PageUp => "PGUP".to_string(),
+            KeyCode::PageDown => "PGDN".to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            _ => "?".to_string(),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("C-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("M-");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            label.push_str("S-");
+        }
+        label.push_str(&key);
+        label
+    }
+}
+
+fn parse_sequence(spec: &str, leader: KeyPress) -> Result<Vec<KeyPress>> {
+    let sequence = spec
+        .split_whitespace()
+        .map(|token| {
+            if token == "<leader>" {
+                Ok(leader)
+            } else {
+                KeyPress::parse(token)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if sequence.is_empty() {
+        return Err(anyhow!("Empty key sequence"));
+    }
+    Ok(sequence)
+}
+
+pub enum KeyResult {
+    Action(Action),
+    Pending,
+    Unbound,
+}
+
+pub struct Keymap {
+    bindings: Vec<(Vec<KeyPress>, Action)>,
+    pending: Vec<KeyPress>,
+    pending_since: Option<Instant>,
+    timeout: Duration,
+}
+
+impl Keymap {
+    pub fn from_config(config: &KeybindingsConfig) -> Self {
+        let leader = KeyPress::parse(&config.leader).unwrap_or_else(|e| {
+            tracing::warn!("[KEYMAP] invalid leader {:?}: {}", config.leader, e);
+            KeyPress {
+                code: KeyCode::Char(' '),
+                modifiers: KeyModifiers::NONE,
+            }
+        });
+
+        let mut bindings: Vec<(Vec<KeyPress>, Action)> = DEFAULT_BINDINGS
+            .iter()
+            .filter_map(|(spec, action)| Some((parse_sequence(spec, leader).ok()?, *action)))
+            .collect();
+
+        for (name, specs) in &config.bindings {
+            let Some(action) = Action::from_name(name) else {
+                tracing::warn!("[KEYMAP] unknown action in config: {}", name);
+                continue;
+            };
+            let sequences: Vec<Vec<KeyPress>> = specs
+                .iter()
+                .filter_map(|spec| match parse_sequence(spec, leader) {
+                    Ok(seq) => Some(seq),
+                    Err(e) => {
+                        tracing::warn!("[KEYMAP] invalid binding {:?} for {}: {}", spec, name, e);
+                        None
+                    }
+                })
+                .collect();
+            // A configured action replaces its defaults and steals its keys from others
+            bindings.retain(|(seq, a)| *a != action && !sequences.contains(seq));
+            bindings.extend(sequences.into_iter().map(|seq| (seq, action)));
+        }
+
+        Self {
+            bindings,
+            pending: Vec::new(),
+            pending_since: None,
+            timeout: Duration::from_millis(config.timeout_ms),
+        }
+    }
+
+    pub fn feed(&mut self, event: KeyEvent) -> KeyResult {
+        self.feed_at(event, Instant::now())
+    }
+
+    fn feed_at(&mut self, event: KeyEvent, now: Instant) -> KeyResult {
+        self.pending.push(KeyPress::from_event(event));
+        self.pending_since = Some(now);
+
+        let exact = self.exact_match();
+        let is_prefix = self
+            .bindings
+            .iter()
+            .any(|(seq, _)| seq.len() > self.pending.len() && seq.starts_with(&self.pending));
+
+        match (exact, is_prefix) {
+            (Some(action), false) => {
+                self.clear_pending();
+                KeyResult::Action(action)
+            }
+            (_, true) => KeyResult::Pending,
+            (None, false) => {
+                self.clear_pending();
+                KeyResult::Unbound
+            }
+        }
+    }
+
+    // Resolve an ambiguous sequence (e.g. a leader that is also bound on its own)
+    // once the chord timeout passes without another key.
+    pub fn tick(&mut self) -> Option<Action> {
+        self.tick_at(Instant::now())
+    }
+
+    fn tick_at(&mut self, now: Instant) -> Option<Action> {
+        let since = self.pending_since?;
+        if now.duration_since(since) < self.timeout {
+            return None;
+        }
+        let action = self.exact_match();
+        self.clear_pending();
+        action
+    }
+
+    pub fn pending_label(&self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let keys: Vec<String> = self.pending.iter().map(KeyPress::label).collect();
+        Some(format!("{} …", keys.join(" ")))
+    }
+
+    pub fn keys_for(&self, action: Action) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(seq, _)| {
+                seq.iter()
+                    .map(KeyPress::label)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    fn exact_match(&self) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(seq, _)| *seq == self.pending)
+            .map(|(_, a)| *a)
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending.clear();
+        self.pending_since = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn keymap(bindings: &[(&str, &[&str])]) -> Keymap {
+        let config = KeybindingsConfig {
+            bindings: bindings
+                .iter()
+                .map(|(name, specs)| {
+                    (
+                        name.to_string(),
+                        specs.iter().map(|s| s.to_string()).collect(),
+                    )
+                })
+                .collect(),
+            ..KeybindingsConfig::default()
+        };
+        Keymap::from_config(&config)
+    }
+
+    #[test]
+    fn single_keys_resolve_immediately() {
+        let mut keymap = keymap(&[]);
+        assert!(matches!(
+            keymap.feed(key('t')),
+            KeyResult::Action(Action::NextTheme)
+        ));
+        assert!(matches!(
+            keymap.feed(KeyEvent::new(KeyCode::Char('S'), KeyModifiers::SHIFT)),
+            KeyResult::Action(Action::ToggleSettings)
+        ));
+    }
+
+    #[test]
+    fn chord_waits_for_second_key() {
+        let mut keymap = keymap(&[]);
+        assert!(matches!(keymap.feed(key('g')), KeyResult::Pending));
+        assert_eq!(keymap.pending_label().as_deref(), Some("g …"));
+        assert!(matches!(
+            keymap.feed(key('g')),
+            KeyResult::Action(Action::GoToStart)
+        ));
+        assert!(keymap.pending_label().is_none());
+    }
+
+    #[test]
+    fn broken_chord_is_unbound_and_resets() {
+        let mut keymap = keymap(&[]);
+        keymap.feed(key('g'));
+        assert!(matches!(keymap.feed(key('x')), KeyResult::Unbound));
+        assert!(keymap.pending_label().is_none());
+    }
+
+    #[test]
+    fn leader_bound_alone_fires_after_timeout() {
+        let mut keymap = keymap(&[("theme", &["<leader> p"])]);
+        let start = Instant::now();
+
+        assert!(matches!(
+            keymap.feed_at(key(' '), start),
+            KeyResult::Pending
+        ));
+        assert_eq!(keymap.tick_at(start + Duration::from_millis(10)), None);
+        assert_eq!(
+            keymap.tick_at(start + Duration::from_secs(5)),
+            Some(Action::TogglePlayback)
+        );
+
+        keymap.feed_at(key(' '), start);
+        assert!(matches!(
+            keymap.feed_at(key('p'), start),
+            KeyResult::Action(Action::NextTheme)
+        ));
+    }
+
+    #[test]
+    fn configured_binding_replaces_defaults_and_steals_keys() {
+        let keymap = keymap(&[("mute", &["ctrl+m", "t"])]);
+        assert_eq!(keymap.keys_for(Action::ToggleMute), vec!["C-m", "t"]);
+        assert!(keymap.keys_for(Action::NextTheme).is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+use std::borrow::Cow;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// A UI string plus an abbreviation for tight spaces. Japanese glyphs take two
+// columns, so the same word needs a shorter form sooner than in English.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub full: Cow<'static, str>,
+    pub short: Cow<'static, str>,
+}
+
+impl Label {
+    pub fn new(full: impl Into<Cow<'static, str>>, short: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            full: full.into(),
+            short: short.into(),
+        }
+    }
+
+    // Same text both ways, for labels that are already as short as they get
+    pub fn fixed(text: &'static str) -> Self {
+        Self::new(text, text)
+    }
+
+    pub fn pick(en: Label, jp: Label, is_jp: bool) -> Self {
+        if is_jp {
+            jp
+        } else {
+            en
+        }
+    }
+
+    // The full form when it fits `width` columns, else the short one
+    pub fn fit(&self, width: usize) -> Option<&str> {
+        [&self.full, &self.short]
+            .into_iter()
+            .map(|text| text.as_ref())
+            .find(|text| UnicodeWidthStr::width(*text) <= width)
+    }
+}
+
+// Cuts `text` to `width` columns, ending in "…" when anything was dropped.
+// A wide glyph that would straddle the edge is dropped whole.
+pub fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if UnicodeWidthStr::width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+    let mut result = String::new();
+    let mut used = 0;
+    for ch in text.chars() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if used + ch_width + 1 > width {
+            break;
+        }
+        result.push(ch);
+        used += ch_width;
+    }
+    if width > 0 {
+        result.push('…');
+    }
+    Cow::Owned(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_fall_back_to_their_short_form() {
+        let label = Label::new("▶ 再生", "再生");
+        assert_eq!(label.fit(6), Some("▶ 再生"));
+        assert_eq!(label.fit(5), Some("再生"));
+        assert_eq!(label.fit(3), None);
+    }
+
+    #[test]
+    fn truncation_counts_columns_not_characters() {
+        assert_eq!(truncate("ALBUM", 5), "ALBUM");
+        assert_eq!(truncate("ALBUM", 4), "ALB…");
+        // A second wide glyph leaves no room for the ellipsis
+        assert_eq!(truncate("アルバム", 4), "ア…");
+        assert_eq!(truncate("アルバム", 0), "");
+    }
+}
@@ -0,0 +1,90 @@
+use amcli_core::lyrics::Lyrics;
+
+// Lines of the loaded lyrics that contain the query, and which one is shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct LyricsSearch {
+    pub query: String,
+    matches: Vec<usize>,
+    current: usize,
+}
+
+impl LyricsSearch {
+    // Case-insensitive; starts at the first match at or after `from` so a
+    // search lands ahead of the song rather than back at the top. `None`
+    // when nothing matches.
+    pub fn new(lyrics: &Lyrics, query: &str, from: usize) -> Option<Self> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return None;
+        }
+        let matches: Vec<usize> = lyrics
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.text.to_lowercase().contains(&needle))
+            .map(|(i, _)| i)
+            .collect();
+        if matches.is_empty() {
+            return None;
+        }
+        let current = matches.iter().position(|&i| i >= from).unwrap_or(0);
+        Some(Self {
+            query: query.trim().to_string(),
+            matches,
+            current,
+        })
+    }
+
+    // Index into the lyrics of the match being shown
+    pub fn line(&self) -> usize {
+        self.matches[self.current]
+    }
+
+    // Steps through the matches, wrapping at either end
+    pub fn step(&mut self, delta: isize) {
+        let len = self.matches.len() as isize;
+        self.current = (self.current as isize + delta).rem_euclid(len) as usize;
+    }
+
+    // "2/5"-style position for the status line
+    pub fn position(&self) -> String {
+        format!("{}/{}", self.current + 1, self.matches.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amcli_core::lyrics::LyricLine;
+    use std::time::Duration;
+
+    fn lyrics(lines: &[&str]) -> Lyrics {
+        Lyrics {
+            lines: lines
+                .iter()
+                .map(|text| LyricLine {
+                    text: text.to_string(),
+                    timestamp: Duration::ZERO,
+                })
+                .collect(),
+            metadata: Default::default(),
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn search_starts_at_the_current_line_and_wraps() {
+        let lyrics = lyrics(&["Hold on", "la la", "Hold ON tight", "end"]);
+        let mut search = LyricsSearch::new(&lyrics, "hold on", 1).unwrap();
+        assert_eq!(search.line(), 2);
+        assert_eq!(search.position(), "2/2");
+        search.step(1);
+        assert_eq!(search.line(), 0);
+        search.step(-1);
+        assert_eq!(search.line(), 2);
+
+        assert_eq!(LyricsSearch::new(&lyrics, "hold", 3).unwrap().line(), 0);
+        assert!(LyricsSearch::new(&lyrics, "missing", 0).is_none());
+        assert!(LyricsSearch::new(&lyrics, "  ", 0).is_none());
+    }
+}
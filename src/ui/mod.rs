@@ -1,29 +1,100 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use image::DynamicImage;
 use ratatui::{
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Gauge, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Gauge, Paragraph},
     Frame,
 };
 use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::task::JoinHandle;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::artwork::converter::ArtworkConverter;
-use crate::artwork::ArtworkManager;
-use crate::lyrics::{lrclib::LrclibProvider, netease::NeteaseProvider, Lyrics, LyricsManager};
-use crate::player::{apple_music::AppleMusicController, MediaPlayer, RepeatMode, Track};
+use amcli_core::artist::{ArtistInfo, ArtistInfoClient};
+use amcli_core::artwork::animated::AnimationFrame;
+use amcli_core::artwork::converter::ArtworkConverter;
+use amcli_core::artwork::itunes::ItunesArtworkSearch;
+use amcli_core::artwork::overrides::{self, ArtworkOverrides};
+use amcli_core::artwork::recent::{RecentAlbum, RecentAlbums};
+use amcli_core::artwork::{ArtworkManager, ArtworkResolution, ArtworkStyle, Mosaic};
+use amcli_core::bookmarks::{self, Bookmark, Bookmarks};
+use amcli_core::config::{AlarmAction, CustomTheme, HexColor, ShowNextStyle, StartupView, Station};
+use amcli_core::diagnostics::{self, Timer};
+use amcli_core::favorites::Favorites;
+use amcli_core::history::PlayHistory;
+use amcli_core::library::{self, ItemKind, LibraryIndex, LibraryItem};
+use amcli_core::lyrics::{
+    demo::DemoLyricsProvider, external::ExternalProvider, local::LocalLyricsProvider,
+    lrclib::LrclibProvider, netease::NeteaseProvider, Lyrics, LyricsManager,
+};
+use amcli_core::mpris::{MprisServer, MprisState};
+use amcli_core::mqtt::MqttPublisher;
+use amcli_core::musickit::MusicKitClient;
+use amcli_core::notes::Notes;
+use amcli_core::player::{
+    MediaPlayer, Normalization, NormalizationKind, PlaybackOptions, PlaybackState, QueueEntry,
+    RepeatMode, Track, PLAYBACK_RATES,
+};
+use amcli_core::remote::{RemoteCommand, RemoteCommands};
+use amcli_core::session::Session;
+use amcli_core::update::{self, Updater};
+use amcli_core::widget::{self, WidgetServer};
 use ratatui_image::protocol::StatefulProtocol;
 use ratatui_image::{Resize, StatefulImage};
 use throbber_widgets_tui::{Throbber, ThrobberState, WhichUse, BRAILLE_SIX_DOUBLE};
 
 // Settings module
+pub mod alarms;
+pub mod artist_panel;
+pub mod big_text;
+pub mod clock;
+pub mod command;
+pub mod debug_overlay;
+pub mod events;
+pub mod favorites;
+pub mod file_browser;
+pub mod focus;
+pub mod idle_grid;
+pub mod input;
+pub mod keymap;
+pub mod labels;
+pub mod lyrics_search;
+pub mod palette;
+pub mod pomodoro;
+pub mod schedule;
 pub mod settings;
-use settings::SettingsMenu;
+pub mod snapshot;
+pub mod splash;
+pub mod stations;
+pub mod switcher;
+pub mod tasks;
+pub mod theme_editor;
+use alarms::{AlarmClock, ScheduledAlarm};
+use artist_panel::{ArtistPanel, ArtistState};
+use command::{Command, CommandLine, ExportSource};
+use debug_overlay::DebugReport;
+use events::{AppEvent, EventBus};
+use favorites::FavoritesPanel;
+use file_browser::FileBrowser;
+use focus::{FocusManager, Panel};
+use idle_grid::IdleGrid;
+use keymap::{Action, KeyResult, Keymap};
+use labels::Label;
+use lyrics_search::LyricsSearch;
+use pomodoro::{FocusTimer, Phase};
+use settings::{SettingsMenu, SettingsValues};
+use splash::BootSplash;
+use stations::StationPicker;
+use switcher::{QuickSwitcher, Scope};
+use tasks::TaskSlot;
+use theme_editor::ThemeEditor;
 
 pub const COLOR_BG: Color = Color::Rgb(0, 0, 0);
 pub const COLOR_TEXT_DIM: Color = Color::Rgb(80, 60, 20);
@@ -38,7 +109,7 @@ const SPACING_NORMAL: u16 = 1; // 1-cell gap -- between sibling sections
 #[allow(dead_code)]
 const SPACING_SECTION: u16 = 2; // 2-cell gap -- between major sections
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Theme {
     pub name: &'static str,
     pub primary: Color,
@@ -47,6 +118,8 @@ pub struct Theme {
     pub alert: Color,
     pub bg: Color,
     pub is_retro: bool,
+    // CD-player style position readout on the progress bar
+    pub ticker: bool,
 }
 
 pub const THEME_AMBER_RETRO: Theme = Theme {
@@ -57,6 +130,7 @@ pub const THEME_AMBER_RETRO: Theme = Theme {
     alert: COLOR_ALERT,
     bg: COLOR_BG,
     is_retro: true,
+    ticker: true,
 };
 
 pub const THEME_GREEN_VFD: Theme = Theme {
@@ -67,6 +141,7 @@ pub const THEME_GREEN_VFD: Theme = Theme {
     alert: Color::Rgb(255, 100, 0),
     bg: COLOR_BG,
     is_retro: true,
+    ticker: true,
 };
 
 pub const THEME_CYAN_VFD: Theme = Theme {
@@ -77,6 +152,7 @@ pub const THEME_CYAN_VFD: Theme = Theme {
     alert: Color::Rgb(255, 50, 50),
     bg: COLOR_BG,
     is_retro: true,
+    ticker: true,
 };
 
 pub const THEME_RED_ALERT: Theme = Theme {
@@ -87,6 +163,7 @@ pub const THEME_RED_ALERT: Theme = Theme {
     alert: Color::Rgb(255, 255, 0),
     bg: COLOR_BG,
     is_retro: true,
+    ticker: true,
 };
 
 pub const THEME_MODERN_LIGHT: Theme = Theme {
@@ -97,6 +174,7 @@ pub const THEME_MODERN_LIGHT: Theme = Theme {
     alert: Color::Rgb(255, 59, 48),  // Terminal red
     bg: Color::Rgb(242, 242, 247),   // Terminal white
     is_retro: false,
+    ticker: false,
 };
 
 pub const THEME_TERMINAL_CLEAN: Theme = Theme {
@@ -107,6 +185,18 @@ pub const THEME_TERMINAL_CLEAN: Theme = Theme {
     alert: Color::Indexed(1),   // Terminal red
     bg: Color::Reset,           // Transparent - use terminal background
     is_retro: false,
+    ticker: false,
+};
+
+pub const THEME_HIGH_CONTRAST: Theme = Theme {
+    name: "HIGH CONTRAST",
+    primary: Color::Rgb(255, 255, 255),
+    dim: Color::Rgb(200, 200, 200),
+    accent: Color::Rgb(255, 255, 0),
+    alert: Color::Rgb(255, 90, 90),
+    bg: COLOR_BG,
+    is_retro: false,
+    ticker: false,
 };
 
 pub const THEMES: &[Theme] = &[
@@ -116,8 +206,106 @@ pub const THEMES: &[Theme] = &[
     THEME_RED_ALERT,
     THEME_MODERN_LIGHT,
     THEME_TERMINAL_CLEAN,
+    THEME_HIGH_CONTRAST,
 ];
 
+// Theme names stay `&'static str` so `Theme` remains `Copy`. Custom theme
+// names are leaked once per distinct name, and only a handful ever exist.
+fn intern(name: &str) -> &'static str {
+    static NAMES: std::sync::Mutex<Vec<&'static str>> = std::sync::Mutex::new(Vec::new());
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = names.iter().find(|n| **n == name) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+    names.push(interned);
+    interned
+}
+
+impl Theme {
+    fn from_custom(custom: &CustomTheme) -> Self {
+        let color = |c: HexColor| Color::Rgb(c.0[0], c.0[1], c.0[2]);
+        Self {
+            name: intern(&custom.name),
+            primary: color(custom.primary),
+            dim: color(custom.dim),
+            accent: color(custom.accent),
+            alert: color(custom.alert),
+            bg: color(custom.bg),
+            is_retro: custom.retro,
+            ticker: custom.retro,
+        }
+    }
+
+    // The terminal's own background (and any transparency) shows through,
+    // as with CLEAN
+    fn transparent(self) -> Self {
+        Self {
+            bg: Color::Reset,
+            ..self
+        }
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.bg == Color::Reset
+    }
+
+    fn to_custom(self, name: &str) -> CustomTheme {
+        let hex = |c: Color| HexColor(theme_editor::to_rgb(c));
+        CustomTheme {
+            name: name.to_string(),
+            primary: hex(self.primary),
+            dim: hex(self.dim),
+            accent: hex(self.accent),
+            alert: hex(self.alert),
+            bg: hex(self.bg),
+            retro: self.is_retro,
+        }
+    }
+}
+
+// Built-in themes followed by the ones saved from the theme editor
+fn theme_list(custom: &[CustomTheme]) -> Vec<Theme> {
+    THEMES
+        .iter()
+        .copied()
+        .chain(custom.iter().map(Theme::from_custom))
+        .collect()
+}
+
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+// Mosaic tile sizes offered in the settings menu; the gap goes up to half a tile
+const MOSAIC_TILE_SIZES: std::ops::RangeInclusive<u32> = 2..=32;
+// Within this much of a bookmark, jumping back goes to the one before it
+const BOOKMARK_BACK_GRACE: Duration = Duration::from_secs(3);
+// Music's crossfade slider range, in seconds
+const CROSSFADE_SECS: std::ops::RangeInclusive<u32> = 1..=12;
+// Percent per volume key press; the shifted keys step by 1
+const VOLUME_STEP: i16 = 5;
+const VOLUME_OVERLAY_DURATION: Duration = Duration::from_secs(1);
+const BALANCE_STEP: f32 = 0.1;
+// iTunes results considered when a cover is marked wrong
+const ARTWORK_CANDIDATES: usize = 10;
+// How stale the quick switcher's copy of the library may get
+const LIBRARY_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// Output routing rarely changes mid-track, and asking can take a while
+const OUTPUT_DEVICE_INTERVAL: Duration = Duration::from_secs(30);
+
+fn step(value: u32, delta: i32, range: std::ops::RangeInclusive<u32>) -> u32 {
+    value
+        .saturating_add_signed(delta)
+        .clamp(*range.start(), *range.end())
+}
+
+// A store whose file won't parse is swapped for an empty one that refuses
+// to save, and the error is kept to show once the UI is up
+fn unreadable<T: Default>(errors: &mut Vec<String>, what: &str, error: anyhow::Error) -> T {
+    tracing::warn!("Couldn't read {}: {}", what, error);
+    errors.push(format!("Couldn't read {}: {}", what, error));
+    T::default()
+}
+
 fn track_identity_changed(current: Option<&Track>, next: Option<&Track>) -> bool {
     match (current, next) {
         (Some(current), Some(next)) => {
@@ -131,6 +319,26 @@ fn track_identity_changed(current: Option<&Track>, next: Option<&Track>) -> bool
     }
 }
 
+// Landing this close to the start of the same track counts as playing it
+// again when it came from the last few seconds (repeat one) or from well
+// into the track (going back to the start); shorter jumps are seeks
+const RESTART_WINDOW: Duration = Duration::from_secs(10);
+const RESTART_MIN_JUMP: Duration = Duration::from_secs(30);
+
+// Streams and radio have no length, so there's no start to come back to
+fn track_restarted(current: Option<&Track>, next: Option<&Track>) -> bool {
+    let (Some(current), Some(next)) = (current, next) else {
+        return false;
+    };
+    if current.duration.is_zero() || next.duration.is_zero() {
+        return false;
+    }
+    let near_end = current.duration.saturating_sub(current.position) <= RESTART_WINDOW;
+    next.position < RESTART_WINDOW
+        && next.position < current.position
+        && (near_end || current.position - next.position >= RESTART_MIN_JUMP)
+}
+
 fn duration_changed(current: Duration, next: Duration) -> bool {
     current.abs_diff(next) > Duration::from_secs(1)
 }
@@ -146,7 +354,8 @@ pub struct MetadataCache {
 }
 
 pub struct App {
-    player: Box<dyn MediaPlayer>,
+    // Shared with background reads like the library refresh
+    player: Arc<dyn MediaPlayer>,
     current_track: Option<Track>,
     pub metadata_cache: Option<MetadataCache>,
     volume: u8,
@@ -154,147 +363,632 @@ pub struct App {
     is_muted: bool,
     show_help: bool,
     current_repeat_mode: RepeatMode,
+    // `None` until the player reports a speed; backends without one never do
+    playback_rate: Option<f32>,
+    // Sound Check or ReplayGain for the current track, read on each change
+    normalization: Option<Normalization>,
+    // Stereo balance, -1.0 to 1.0; `None` for backends that can't pan
+    balance: Option<f32>,
+    bookmarks: Bookmarks,
+    favorites: Favorites,
+    notes: Notes,
+    // Where the current track's bookmarks, notes, and artwork overrides are filed
+    // (Music's persistent ID when there is one), set on each track change
+    track_key: Option<String>,
+    artwork_overrides: ArtworkOverrides,
+    // Looks past a wrong cover for `retry_artwork`
+    artwork_search: ItunesArtworkSearch,
     artwork_manager: ArtworkManager,
     artwork_converter: ArtworkConverter,
     artwork_protocol: Option<StatefulProtocol>,
     current_artwork_url: Option<String>,
     is_loading_artwork: bool,
-    artwork_task: Option<JoinHandle<Result<DynamicImage>>>,
+    artwork_task: TaskSlot<Result<DynamicImage>>,
+    artwork_animation_task: TaskSlot<Result<Option<Vec<AnimationFrame>>>>,
+    // Frames after the one in `artwork_protocol`, rotated as the animation plays
+    artwork_frames: VecDeque<(StatefulProtocol, Duration)>,
+    artwork_frame_delay: Duration,
+    artwork_frame_shown_at: Instant,
+    // Only kept up with `[ui] idle_grid` on
+    recent_albums: RecentAlbums,
+    idle_grid: IdleGrid,
+    idle_grid_task: TaskSlot<Vec<(RecentAlbum, DynamicImage)>>,
     throbber_state: ThrobberState,
+    themes: Vec<Theme>,
     current_theme_index: usize,
+    // False on 256-color terminals, where themes use their fallback palettes
+    truecolor: bool,
+    // Open while colors are being edited; its draft replaces the active theme
+    theme_editor: Option<ThemeEditor>,
+    // Last day/night phase applied from `[ui.schedule]`, and when it was checked
+    schedule_phase: Option<schedule::Phase>,
+    schedule_checked_at: Option<Instant>,
     animation_frame: u32,
+    playback_state: PlaybackState,
+    // When `current_track.position` was reported, for interpolating between polls
+    position_sampled_at: Instant,
+    // Where `previous_track` jumped from, for the next poll to tell a restart
+    // from a move to the track before
+    jumped_from: Option<Duration>,
     lyrics_manager: Arc<LyricsManager>,
     current_lyrics: Option<Lyrics>,
     // True when the last lyrics fetch failed because the providers were unreachable,
     // as opposed to reachable-but-no-match. Drives "NO SIGNAL" vs "NO LYRICS".
     lyrics_unreachable: bool,
-    lyrics_task: Option<JoinHandle<Result<Option<Lyrics>>>>,
-    config: crate::config::Config,
+    lyrics_task: TaskSlot<Result<Option<Lyrics>>>,
+    artist_info: ArtistInfoClient,
+    // Apple Music catalog, when `[musickit]` has a developer token
+    musickit: Option<MusicKitClient>,
+    // What pollers, media keys, signals and `[mqtt]` publish and subscribe
+    // to; the inbox is the app's own subscription
+    events: EventBus,
+    inbox: UnboundedReceiver<AppEvent>,
+    // `[[alarms]]`, when any are enabled
+    alarm_clock: Option<AlarmClock>,
+    // `[focus]` intervals, while the timer runs
+    focus_timer: Option<FocusTimer>,
+    // Media keys and global hotkeys (`player.media_keys`, `[hotkeys]`)
+    remote_commands: Option<RemoteCommands>,
+    mpris: Option<MprisServer>,
+    // Open after `i`; its bio and image arrive through the two tasks below
+    artist_panel: Option<ArtistPanel>,
+    quick_switcher: Option<QuickSwitcher>,
+    favorites_panel: Option<FavoritesPanel>,
+    file_browser: Option<FileBrowser>,
+    station_picker: Option<StationPicker>,
+    library: LibraryIndex,
+    library_task: TaskSlot<Result<LibraryIndex>>,
+    // `None` until the quick switcher is first opened, so players are only
+    // asked for their library by people who search it
+    next_library_refresh: Option<Instant>,
+    // `[ui] show_output_device`: AirPlay speakers or the output device, as of
+    // the last check
+    output_device: Option<String>,
+    output_device_task: TaskSlot<Result<String>>,
+    next_output_check: Option<Instant>,
+    artist_info_task: Option<JoinHandle<Result<Option<ArtistInfo>>>>,
+    artist_image_task: Option<JoinHandle<Result<DynamicImage>>>,
+    // `[updates] check`: the newer release tag, once the check finds one
+    update_available: Option<String>,
+    update_task: Option<JoinHandle<Result<Option<String>>>>,
+    // When the volume last changed, for the overlay
+    volume_shown_at: Option<Instant>,
+    config: amcli_core::config::Config,
+    // `[controls.longform]` control bar labels, English then Japanese
+    longform_labels: [(Label, Label); 2],
     settings_menu: SettingsMenu,
+    profiles: Vec<String>,
+    // Set when another profile was picked; main restarts the UI with it
+    profile_switch: Option<Option<String>>,
+    // Crossfade and Sound Check, read when settings first open
+    playback_options: Option<PlaybackOptions>,
+    // Music's Autoplay, read along with them
+    autoplay: Option<bool>,
+    command_line: CommandLine,
+    keymap: Keymap,
+    focus: FocusManager,
+    // Manual lyrics offset from the current line while the lyrics panel has focus
+    lyrics_scroll: isize,
+    // Last `/` search through the loaded lyrics
+    lyrics_search: Option<LyricsSearch>,
+    // Tracks played since launch, oldest first
+    session_history: Vec<QueueEntry>,
+    show_queue: bool,
+    big_text: bool,
+    // `None` when the player couldn't report its queue
+    queue: Option<Vec<QueueEntry>>,
+    queue_selected: usize,
+    should_quit: bool,
+    boot_splash: Option<BootSplash>,
+    clock_text: Option<String>,
+    // Cleared on FocusLost: drawing and animation pause while state polling continues.
+    is_focused: bool,
     needs_full_repaint: bool,
+    // Written from the next frame drawn, by `:snapshot` or its key
+    snapshot_request: Option<PathBuf>,
+    show_debug_overlay: bool,
+    // Set by input, polls, and commands; the main loop skips drawing until
+    // something marks the app dirty or `frame_key` moves on
+    dirty: bool,
+    last_frame_key: Option<FrameKey>,
+    frame_timer: Timer,
+    update_timer: Timer,
 }
 
 impl App {
-    pub async fn new() -> Result<Self> {
-        let config = crate::config::Config::load().await?;
-        let player = Box::new(AppleMusicController::new());
-        Self::with_player_and_config(player, config).await
+    // Bundled lyrics only, so the demo player never touches the network
+    pub async fn demo(
+        player: Box<dyn MediaPlayer>,
+        config: amcli_core::config::Config,
+    ) -> Result<Self> {
+        let mut lyrics_manager = LyricsManager::new(20);
+        lyrics_manager.add_provider(Box::new(DemoLyricsProvider));
+        Self::with_player_config_and_lyrics_manager(player, config, lyrics_manager).await
     }
 
     #[allow(dead_code)]
     pub async fn with_player(player: Box<dyn MediaPlayer>) -> Result<Self> {
-        let config = crate::config::Config::load().await?;
+        let config = amcli_core::config::Config::load().await?;
         Self::with_player_and_config(player, config).await
     }
 
     pub async fn with_player_and_config(
         player: Box<dyn MediaPlayer>,
-        config: crate::config::Config,
+        config: amcli_core::config::Config,
     ) -> Result<Self> {
-        Self::with_player_config_and_lyrics_manager(player, config, Self::default_lyrics_manager())
-            .await
+        let lyrics_manager = Self::default_lyrics_manager(&config.lyrics);
+        Self::with_player_config_and_lyrics_manager(player, config, lyrics_manager).await
     }
 
-    fn default_lyrics_manager() -> LyricsManager {
+    fn default_lyrics_manager(config: &amcli_core::config::LyricsConfig) -> LyricsManager {
         let mut lyrics_manager = LyricsManager::new(20);
+        lyrics_manager.set_local(LocalLyricsProvider::new(&config.local_paths));
         lyrics_manager.add_provider(Box::new(LrclibProvider::new()));
         lyrics_manager.add_provider(Box::new(NeteaseProvider::new()));
+        for plugin in &config.plugins {
+            lyrics_manager.add_provider(Box::new(ExternalProvider::new(plugin)));
+        }
         lyrics_manager
     }
 
     async fn with_player_config_and_lyrics_manager(
         player: Box<dyn MediaPlayer>,
-        config: crate::config::Config,
+        config: amcli_core::config::Config,
         lyrics_manager: LyricsManager,
     ) -> Result<Self> {
         let volume = 50;
-        let cache_dir = dirs::cache_dir()
-            .unwrap_or_else(std::env::temp_dir)
-            .join("amcli/artwork");
+        let cache_dir = amcli_core::cache::artwork_dir();
 
         tokio::fs::create_dir_all(&cache_dir).await.ok();
 
         let lyrics_manager = Arc::new(lyrics_manager);
+        // A file that won't parse is reported and left alone; what changes
+        // this session isn't saved over it
+        let mut load_errors = Vec::new();
+        let bookmarks = Bookmarks::load(Bookmarks::default_path())
+            .await
+            .unwrap_or_else(|e| unreadable(&mut load_errors, "bookmarks", e));
+        let favorites = Favorites::load(Favorites::default_path())
+            .await
+            .unwrap_or_else(|e| unreadable(&mut load_errors, "favorites", e));
+        let notes = Notes::load(Notes::default_path())
+            .await
+            .unwrap_or_else(|e| unreadable(&mut load_errors, "notes", e));
+        let artwork_overrides = ArtworkOverrides::load(ArtworkOverrides::default_path())
+            .await
+            .unwrap_or_else(|e| unreadable(&mut load_errors, "artwork overrides", e));
+        let library = LibraryIndex::load(&amcli_core::cache::library_index_path())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Couldn't read the library index: {}", e);
+                LibraryIndex::default()
+            });
+        let recent_albums = if config.ui.idle_grid {
+            RecentAlbums::load(RecentAlbums::default_path())
+                .await
+                .unwrap_or_else(|e| unreadable(&mut load_errors, "recently played albums", e))
+        } else {
+            RecentAlbums::default()
+        };
+
+        let themes = theme_list(&config.ui.custom_themes);
+        // "default" and unknown names fall back to the first theme
+        let saved_theme = config.ui.color_theme.to_lowercase().replace('_', " ");
+        let theme_index = themes
+            .iter()
+            .position(|t| t.name.to_lowercase() == saved_theme)
+            .unwrap_or(0);
+        // Named profiles next to config.toml, for the settings menu
+        let profiles = amcli_core::config::Config::profiles()
+            .await
+            .unwrap_or_default();
+        let settings_menu = SettingsMenu::new(&SettingsValues {
+            config: &config,
+            profiles: &profiles,
+            theme_index,
+            total_themes: themes.len(),
+            offline: amcli_core::network::is_offline(),
+            playback: None,
+            autoplay: None,
+        });
 
-        let settings_menu = SettingsMenu::new(
-            config.general.language,
-            0, // current_theme_index will be set after App is created
-            THEMES.len(),
-            config.artwork.album,
-            config.artwork.mosaic,
+        let keymap = Keymap::from_config(&config.keybindings);
+        let (forward, backward) = (
+            config.controls.longform.forward_seconds,
+            config.controls.longform.backward_seconds,
         );
+        let longform_labels = [
+            (
+                Label::new(format!("SKIP {}", forward), format!("+{}", forward)),
+                Label::new(format!("{}秒送り", forward), format!("+{}", forward)),
+            ),
+            (
+                Label::new(format!("BACK {}", backward), format!("-{}", backward)),
+                Label::new(format!("{}秒戻し", backward), format!("-{}", backward)),
+            ),
+        ];
+        let mut command_line = CommandLine::default();
+        command_line.set_custom_themes(
+            config
+                .ui
+                .custom_themes
+                .iter()
+                .map(|t| t.name.clone())
+                .collect(),
+        );
+        if !load_errors.is_empty() {
+            command_line.set_message(load_errors.join("; "), true);
+        }
+
+        // The power-on sequence only fits the hardware-appliance themes
+        let boot_splash =
+            (config.ui.boot_splash && !config.ui.reduced_motion && themes[theme_index].is_retro)
+                .then(BootSplash::new);
+
+        let events = EventBus::default();
+        let inbox = events.subscribe();
+        if let Some(mqtt) = MqttPublisher::spawn(&config.mqtt) {
+            events::forward_to_mqtt(mqtt, events.subscribe());
+        }
+        if let Some(addr) = &config.metrics.prometheus {
+            if let Err(e) = diagnostics::serve_prometheus(addr).await {
+                tracing::warn!("Couldn't serve metrics on {}: {}", addr, e);
+            }
+        }
 
         Ok(Self {
-            player,
+            player: player.into(),
             current_track: None,
             volume,
             saved_volume: volume,
             is_muted: false,
             show_help: false,
             current_repeat_mode: RepeatMode::Off,
+            playback_rate: None,
+            normalization: None,
+            balance: None,
+            bookmarks,
+            favorites,
+            notes,
+            track_key: None,
+            artwork_overrides,
+            artwork_search: ItunesArtworkSearch::new(),
+            playback_state: PlaybackState::Stopped,
+            position_sampled_at: Instant::now(),
+            jumped_from: None,
             artwork_manager: ArtworkManager::new(cache_dir),
             artwork_converter: ArtworkConverter::with_mode(&config.artwork.mode)?,
             artwork_protocol: None,
             current_artwork_url: None,
             is_loading_artwork: false,
-            artwork_task: None,
+            artwork_task: TaskSlot::new(),
+            artwork_animation_task: TaskSlot::new(),
+            artwork_frames: VecDeque::new(),
+            artwork_frame_delay: Duration::ZERO,
+            artwork_frame_shown_at: Instant::now(),
+            recent_albums,
+            idle_grid: IdleGrid::default(),
+            idle_grid_task: TaskSlot::new(),
             throbber_state: ThrobberState::default(),
-            current_theme_index: 0,
+            themes,
+            current_theme_index: theme_index,
+            truecolor: palette::truecolor_enabled(config.ui.color_depth),
+            big_text: config.ui.big_text,
+            theme_editor: None,
+            schedule_phase: None,
+            schedule_checked_at: None,
             animation_frame: 0,
             lyrics_manager,
             current_lyrics: None,
             lyrics_unreachable: false,
-            lyrics_task: None,
+            lyrics_task: TaskSlot::new(),
+            artist_info: ArtistInfoClient::new(&config),
+            musickit: MusicKitClient::from_config(&config.musickit),
+            events,
+            inbox,
+            alarm_clock: AlarmClock::spawn(&config.alarms),
+            focus_timer: None,
+            remote_commands: RemoteCommands::spawn(config.player.media_keys, &config.hotkeys)
+                .inspect_err(|e| tracing::warn!("Media keys and hotkeys unavailable: {}", e))
+                .ok()
+                .flatten(),
+            mpris: None,
+            artist_panel: None,
+            quick_switcher: None,
+            favorites_panel: None,
+            file_browser: None,
+            station_picker: None,
+            next_library_refresh: None,
+            library,
+            library_task: TaskSlot::new(),
+            output_device: None,
+            output_device_task: TaskSlot::new(),
+            next_output_check: None,
+            artist_info_task: None,
+            artist_image_task: None,
+            update_available: None,
+            update_task: None,
+            volume_shown_at: None,
             config,
+            longform_labels,
             settings_menu,
+            profiles,
+            profile_switch: None,
+            playback_options: None,
+            autoplay: None,
+            command_line,
+            keymap,
+            focus: FocusManager::default(),
+            lyrics_scroll: 0,
+            lyrics_search: None,
+            session_history: Vec::new(),
+            show_queue: false,
+            queue: None,
+            queue_selected: 0,
+            should_quit: false,
+            boot_splash,
+            clock_text: None,
+            is_focused: true,
             needs_full_repaint: false,
+            snapshot_request: None,
+            show_debug_overlay: false,
+            dirty: true,
+            last_frame_key: None,
+            frame_timer: Timer::new(),
+            update_timer: Timer::new(),
             metadata_cache: None,
         })
     }
 
+    fn refresh_settings_menu(&mut self) {
+        self.settings_menu.refresh(&SettingsValues {
+            config: &self.config,
+            profiles: &self.profiles,
+            theme_index: self.current_theme_index,
+            total_themes: self.themes.len(),
+            offline: amcli_core::network::is_offline(),
+            playback: self.playback_options,
+            autoplay: self.autoplay,
+        });
+    }
+
     pub fn current_theme(&self) -> Theme {
-        THEMES[self.current_theme_index]
+        let theme = self.base_theme();
+        let theme = if self.truecolor {
+            theme
+        } else {
+            palette::fallback(theme)
+        };
+        if self.config.ui.transparent_background.applies_to(theme.name) {
+            theme.transparent()
+        } else {
+            theme
+        }
+    }
+
+    // The active theme (or the editor's draft) before display preferences
+    fn base_theme(&self) -> Theme {
+        match &self.theme_editor {
+            Some(editor) => editor.draft,
+            None => self.themes[self.current_theme_index],
+        }
+    }
+
+    // `[startup]`: the saved session's theme and layout, then the configured
+    // view, then resuming playback
+    pub async fn apply_startup(&mut self, session: Option<Session>) {
+        if let Some(session) = session {
+            if let Some(index) = session
+                .theme
+                .and_then(|name| command::find_theme(&self.themes, &name))
+            {
+                self.apply_theme(index);
+            }
+            self.big_text = session.big_text;
+            if session.show_queue {
+                self.toggle_queue().await;
+            }
+            self.start_on_view(session.focus);
+        }
+        match self.config.startup.view {
+            StartupView::Default => {}
+            StartupView::Queue if !self.show_queue => {
+                self.toggle_queue().await;
+                self.start_on_view(StartupView::Queue);
+            }
+            view => self.start_on_view(view),
+        }
+        if self.config.startup.resume
+            && !matches!(
+                self.player.get_playback_state().await,
+                Ok(PlaybackState::Playing)
+            )
+        {
+            if let Err(e) = self.player.play().await {
+                self.command_line
+                    .set_message(format!("Couldn't resume playback: {}", e), true);
+            }
+        }
+        if self.config.updates.check {
+            self.update_task = Some(tokio::spawn(async {
+                Updater::default().check(update::CURRENT_VERSION).await
+            }));
+        }
+    }
+
+    fn start_on_view(&mut self, view: StartupView) {
+        match view {
+            StartupView::Default => {}
+            StartupView::Lyrics => self.focus.start_on(Panel::Lyrics),
+            StartupView::Queue => self.focus.start_on(Panel::Queue),
+        }
+    }
+
+    // What `apply_startup` restores next time
+    pub fn session(&self) -> Session {
+        Session {
+            theme: Some(self.themes[self.current_theme_index].name.to_lowercase()),
+            show_queue: self.show_queue,
+            big_text: self.big_text,
+            focus: match self.focus.current() {
+                Panel::Lyrics => StartupView::Lyrics,
+                Panel::Queue => StartupView::Queue,
+                Panel::Artwork | Panel::Metadata => StartupView::Default,
+            },
+        }
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.config.profile.as_deref()
+    }
+
+    pub fn restores_session(&self) -> bool {
+        self.config.startup.restore_session
     }
 
     pub async fn next_theme(&mut self) -> Result<()> {
-        self.current_theme_index = (self.current_theme_index + 1) % THEMES.len();
-        self.current_artwork_url = None;
-        self.artwork_protocol = None;
-        self.needs_full_repaint = true;
+        self.apply_theme((self.current_theme_index + 1) % self.themes.len());
         self.update().await?;
         Ok(())
     }
 
+    fn apply_theme(&mut self, index: usize) {
+        self.current_theme_index = index;
+        self.refresh_settings_menu();
+        self.restyle_artwork();
+    }
+
+    // After a theme, mosaic, or CRT change, a cover already themed that way
+    // is shown straight from the cache; otherwise update() reloads it
+    fn restyle_artwork(&mut self) {
+        self.needs_full_repaint = true;
+        // Reloaded in the new style on the next update
+        self.idle_grid_task.cancel();
+        self.idle_grid.clear();
+        let url = self.current_artwork_url.take();
+        if let Some(url) = url.filter(|_| !self.animates_artwork()) {
+            let cached = self.artwork_manager.cached_artwork(
+                &url,
+                &self.artwork_style(),
+                self.artwork_converter.resolution(),
+            );
+            if let Some(img) = cached {
+                self.stop_artwork_tasks();
+                self.artwork_protocol = Some(self.artwork_converter.create_protocol(img));
+                self.is_loading_artwork = false;
+                self.current_artwork_url = Some(url);
+                return;
+            }
+        }
+        self.artwork_protocol = None;
+    }
+
+    async fn set_language(&mut self, language: amcli_core::config::Language) -> Result<()> {
+        self.config.general.language = language;
+        self.artist_info = ArtistInfoClient::new(&self.config);
+        self.refresh_settings_menu();
+        self.config.save().await
+    }
+
+    // Transport commands show their effect straight away instead of on the
+    // next poll, which then replaces it with whatever the player reports
     pub async fn toggle_playback(&mut self) -> Result<()> {
-        self.player.toggle().await
+        self.player.toggle().await?;
+        self.show_position(self.interpolated_position());
+        self.playback_state = match self.playback_state {
+            PlaybackState::Playing => PlaybackState::Paused,
+            _ => PlaybackState::Playing,
+        };
+        Ok(())
     }
 
     pub async fn next_track(&mut self) -> Result<()> {
         self.player.next().await?;
         self.clear_artwork_for_track_transition(true);
+        self.show_position(Some(Duration::ZERO));
         Ok(())
     }
 
+    // Music restarts the track instead when it's a few seconds in; either
+    // way playback starts from zero
     pub async fn previous_track(&mut self) -> Result<()> {
         self.player.previous().await?;
         self.clear_artwork_for_track_transition(true);
+        self.jumped_from = self.interpolated_position();
+        self.show_position(Some(Duration::ZERO));
         Ok(())
     }
 
-    pub async fn volume_up(&mut self) -> Result<()> {
-        self.volume = (self.volume + 5).min(100);
-        self.player.set_volume(self.volume).await?;
-        self.is_muted = false;
+    // The track stays the same, so a jump back to the start is a fresh play
+    // now rather than on the next poll, which would see the position already
+    // moved
+    async fn set_position(&mut self, position: Duration) -> Result<()> {
+        self.player.set_position(position).await?;
+        let from = self.current_track.clone().map(|track| Track {
+            position: self.interpolated_position().unwrap_or(track.position),
+            ..track
+        });
+        let to = from.clone().map(|track| Track { position, ..track });
+        self.show_position(Some(position));
+        if track_restarted(from.as_ref(), to.as_ref()) {
+            self.record_restart(to);
+        }
+        Ok(())
+    }
+
+    async fn seek_by(&mut self, seconds: i32) -> Result<()> {
+        self.player.seek(seconds).await?;
+        let position = self.interpolated_position().map(|position| {
+            if seconds < 0 {
+                position.saturating_sub(Duration::from_secs(seconds.unsigned_abs() as u64))
+            } else {
+                position + Duration::from_secs(seconds as u64)
+            }
+        });
+        self.show_position(position);
         Ok(())
     }
 
+    fn show_position(&mut self, position: Option<Duration>) {
+        if let (Some(track), Some(position)) = (&mut self.current_track, position) {
+            track.position = if track.duration.is_zero() {
+                position
+            } else {
+                position.min(track.duration)
+            };
+            self.position_sampled_at = Instant::now();
+        }
+        self.dirty = true;
+    }
+
+    pub async fn volume_up(&mut self) -> Result<()> {
+        self.adjust_volume(VOLUME_STEP).await
+    }
+
     pub async fn volume_down(&mut self) -> Result<()> {
-        self.volume = self.volume.saturating_sub(5);
+        self.adjust_volume(-VOLUME_STEP).await
+    }
+
+    async fn adjust_volume(&mut self, delta: i16) -> Result<()> {
+        self.volume = (self.volume as i16 + delta).clamp(0, 100) as u8;
         self.player.set_volume(self.volume).await?;
         self.is_muted = false;
+        self.show_volume();
         Ok(())
     }
 
+    fn show_volume(&mut self) {
+        self.volume_shown_at = Some(Instant::now());
+        self.dirty = true;
+    }
+
+    // The volume while its overlay is up
+    fn volume_overlay(&self) -> Option<u8> {
+        self.volume_shown_at
+            .filter(|shown| shown.elapsed() < VOLUME_OVERLAY_DURATION)
+            .map(|_| self.volume)
+    }
+
     pub async fn toggle_mute(&mut self) -> Result<()> {
         if self.is_muted {
             self.volume = self.saved_volume;
@@ -305,1168 +999,5437 @@ impl App {
             self.is_muted = true;
         }
         self.player.set_volume(self.volume).await?;
+        self.show_volume();
         Ok(())
     }
 
     pub async fn seek_forward(&mut self) -> Result<()> {
-        self.player.seek(5).await
+        let step = self
+            .longform_steps()
+            .map_or(self.player.seek_step(), |(forward, _)| forward);
+        self.seek_by(step).await
     }
 
     pub async fn seek_backward(&mut self) -> Result<()> {
-        self.player.seek(-5).await
+        let step = self
+            .longform_steps()
+            .map_or(self.player.seek_step(), |(_, backward)| backward);
+        self.seek_by(-step).await
     }
 
-    pub fn navigate_up(&mut self) {}
-    pub fn navigate_down(&mut self) {}
-    pub fn navigate_left(&mut self) {}
-    pub fn navigate_right(&mut self) {}
-
-    #[allow(dead_code)]
-    pub async fn toggle_shuffle(&mut self) -> Result<()> {
-        self.player.set_shuffle(true).await
+    // `[controls.longform]` steps while the player is spoken word or the
+    // current item runs long enough to be an episode or audiobook
+    fn longform_steps(&self) -> Option<(i32, i32)> {
+        let longform = &self.config.controls.longform;
+        let long = self.current_track.as_ref().is_some_and(|track| {
+            track.duration >= Duration::from_secs(longform.min_minutes.saturating_mul(60))
+        });
+        (longform.enabled && (long || self.player.is_spoken_word())).then_some((
+            longform.forward_seconds as i32,
+            longform.backward_seconds as i32,
+        ))
     }
 
-    pub async fn cycle_repeat(&mut self) -> Result<()> {
-        self.current_repeat_mode = match self.current_repeat_mode {
-            RepeatMode::Off => RepeatMode::All,
-            RepeatMode::All => RepeatMode::One,
-            RepeatMode::One => RepeatMode::Off,
-        };
-        self.player.set_repeat(self.current_repeat_mode).await
+    // Up/down scroll the lyrics while that panel is focused; elsewhere they move
+    // focus between stacked panels.
+    pub fn navigate_up(&mut self) {
+        if self.move_idle_grid_selection(0, -1) {
+            return;
+        }
+        if self.focus.current() == Panel::Queue {
+            self.queue_selected = self.queue_selected.saturating_sub(1);
+        } else if self.focus.current() == Panel::Lyrics && self.current_lyrics.is_some() {
+            self.lyrics_scroll -= 1;
+        } else {
+            self.focus.focus_above();
+        }
     }
 
-    pub fn toggle_help(&mut self) {
-        self.show_help = !self.show_help;
+    pub fn navigate_down(&mut self) {
+        if self.move_idle_grid_selection(0, 1) {
+            return;
+        }
+        if self.focus.current() == Panel::Queue {
+            let last = self.queue.as_ref().map_or(0, |q| q.len().saturating_sub(1));
+            self.queue_selected = (self.queue_selected + 1).min(last);
+        } else if self.focus.current() == Panel::Lyrics && self.current_lyrics.is_some() {
+            self.lyrics_scroll += 1;
+        } else {
+            self.focus.focus_below();
+        }
     }
 
-    pub fn toggle_settings_menu(&mut self) {
-        let was_open = self.settings_menu.is_open;
-        self.settings_menu.toggle();
-        if was_open {
-            self.needs_full_repaint = true;
+    pub fn navigate_left(&mut self) {
+        if self.move_idle_grid_selection(-1, 0) {
+            return;
         }
+        self.focus.focus_prev();
+        self.lyrics_scroll = 0;
     }
 
-    pub fn is_settings_open(&self) -> bool {
-        self.settings_menu.is_open
+    pub fn navigate_right(&mut self) {
+        if self.move_idle_grid_selection(1, 0) {
+            return;
+        }
+        self.focus.focus_next();
+        self.lyrics_scroll = 0;
     }
 
-    pub fn close_settings(&mut self) {
-        if self.settings_menu.is_open {
-            self.needs_full_repaint = true;
+    // The idle grid takes the arrow keys while it's up, unless the queue has
+    // focus
+    fn move_idle_grid_selection(&mut self, columns: isize, rows: isize) -> bool {
+        if !self.idle_grid.is_shown() || self.focus.current() == Panel::Queue {
+            return false;
         }
-        self.settings_menu.close();
+        if self.idle_grid.move_selection(columns, rows) {
+            self.render_idle_grid();
+        }
+        true
     }
 
-    pub fn take_needs_full_repaint(&mut self) -> bool {
-        std::mem::take(&mut self.needs_full_repaint)
+    fn render_idle_grid(&mut self) {
+        self.idle_grid.image = self
+            .idle_grid
+            .compose(self.current_theme())
+            .map(|img| self.artwork_converter.create_protocol(img));
+        self.needs_full_repaint = true;
     }
 
-    fn clear_artwork_for_track_transition(&mut self, show_loading: bool) {
-        self.current_artwork_url = None;
-        self.artwork_protocol = None;
-        self.is_loading_artwork = show_loading && self.config.artwork.album;
-        if let Some(task) = self.artwork_task.take() {
-            task.abort();
+    async fn play_idle_grid_selection(&mut self) -> Result<()> {
+        let Some(album) = self.idle_grid.selected().cloned() else {
+            return Ok(());
+        };
+        if !self.player.play_album(&album.artist, &album.album).await? {
+            self.command_line
+                .set_message(format!("No match for \"{}\"", album.album), true);
         }
-        self.needs_full_repaint = true;
+        Ok(())
     }
 
-    pub fn settings_navigate_up(&mut self) {
-        self.settings_menu.navigate_up();
+    // Looks the covers up the first time nothing is playing, and drops them
+    // once something is, so the next idle spell includes the new album
+    fn update_idle_grid(&mut self) {
+        if !self.config.ui.idle_grid {
+            return;
+        }
+        if self.current_track.is_some() {
+            if self.idle_grid.is_loaded() || self.idle_grid_task.is_running() {
+                self.idle_grid_task.cancel();
+                self.idle_grid.clear();
+                self.needs_full_repaint = true;
+            }
+            return;
+        }
+        if !self.idle_grid.is_loaded() && !self.idle_grid_task.is_running() {
+            let albums = self.recent_albums.albums().to_vec();
+            let manager = self.artwork_manager.clone();
+            let style = self.artwork_style();
+            self.idle_grid_task.start(async move {
+                let mut loaded = Vec::new();
+                for album in albums {
+                    match manager
+                        .get_artwork_themed_v2(
+                            &album.artwork_url,
+                            &style,
+                            ArtworkResolution::Thumbnail,
+                        )
+                        .await
+                    {
+                        Ok(cover) => loaded.push((album, cover)),
+                        Err(e) => tracing::debug!("Idle grid cover failed: {}", e),
+                    }
+                }
+                loaded
+            });
+        }
+        if let Some(result) = self.idle_grid_task.try_finish() {
+            let loaded = result.unwrap_or_else(|e| {
+                tracing::warn!("Idle grid task panicked: {}", e);
+                Vec::new()
+            });
+            self.idle_grid.set(loaded);
+            self.render_idle_grid();
+        }
     }
 
-    pub fn settings_navigate_down(&mut self) {
-        self.settings_menu.navigate_down();
+    // The line being sung, or 0 before anything is playing
+    fn current_lyrics_index(&self) -> usize {
+        match (&self.current_lyrics, self.interpolated_position()) {
+            (Some(lyrics), Some(position)) => lyrics.find_index(position),
+            _ => 0,
+        }
     }
 
-    pub async fn settings_select(&mut self) -> Result<()> {
-        use crate::ui::settings::SettingsItem;
+    fn open_lyrics_search(&mut self) {
+        if self.current_lyrics.is_none() {
+            self.command_line
+                .set_message("No lyrics to search".to_string(), true);
+            return;
+        }
+        self.focus.focus(Panel::Lyrics);
+        self.command_line.open_search();
+    }
 
-        if let Some(item) = self.settings_menu.get_selected_item() {
-            match item {
-                SettingsItem::Language { current } => {
-                    let new_lang = current.toggle();
-                    self.config.general.language = new_lang;
-                    self.settings_menu.update_language(new_lang);
-                    self.config.save().await?;
-                }
-                SettingsItem::Theme {
-                    current_index,
-                    total_themes,
-                } => {
-                    let new_index = (current_index + 1) % total_themes;
-                    self.current_theme_index = new_index;
-                    self.settings_menu.update_theme(new_index);
-                    self.current_artwork_url = None;
-                    self.artwork_protocol = None;
-                    self.needs_full_repaint = true;
-                    self.config.ui.color_theme = THEMES[new_index].name.to_lowercase();
-                    self.config.save().await?;
-                }
-                SettingsItem::Album { enabled } => {
-                    let new_enabled = !enabled;
-                    self.config.artwork.album = new_enabled;
-                    self.settings_menu.update_album(new_enabled);
-                    self.current_artwork_url = None;
-                    self.artwork_protocol = None;
-                    self.needs_full_repaint = true;
-                    self.config.save().await?;
-                }
-                SettingsItem::Mosaic { enabled } => {
-                    let new_enabled = !enabled;
-                    self.config.artwork.mosaic = new_enabled;
-                    self.settings_menu.update_mosaic(new_enabled);
-                    self.current_artwork_url = None;
-                    self.artwork_protocol = None;
-                    self.needs_full_repaint = true;
-                    self.config.save().await?;
-                }
-                SettingsItem::Close => {
-                    self.settings_menu.close();
-                    self.needs_full_repaint = true;
-                }
-            }
+    fn search_lyrics(&mut self, query: &str) {
+        let Some(lyrics) = &self.current_lyrics else {
+            return;
+        };
+        self.lyrics_search = LyricsSearch::new(lyrics, query, self.current_lyrics_index());
+        if self.lyrics_search.is_none() && !query.trim().is_empty() {
+            self.command_line
+                .set_message(format!("No match for \"{}\"", query.trim()), true);
         }
-        Ok(())
+        self.show_lyrics_match();
     }
 
-    #[allow(dead_code)]
-    pub fn is_showing_help(&self) -> bool {
-        self.show_help
+    // n/N only do something once a search has matched
+    fn step_lyrics_search(&mut self, delta: isize) {
+        if let Some(search) = &mut self.lyrics_search {
+            search.step(delta);
+            self.focus.focus(Panel::Lyrics);
+            self.show_lyrics_match();
+        }
     }
 
-    pub fn get_current_track(&self) -> Option<&Track> {
-        self.current_track.as_ref()
+    // Scrolls the lyrics to the current match and reports where it is
+    fn show_lyrics_match(&mut self) {
+        let Some(search) = &self.lyrics_search else {
+            return;
+        };
+        self.lyrics_scroll = search.line() as isize - self.current_lyrics_index() as isize;
+        self.command_line
+            .set_message(format!("/{}  {}", search.query, search.position()), false);
     }
 
-    #[allow(dead_code)]
-    pub fn get_volume(&self) -> u8 {
-        self.volume
+    // Enter on the lyrics panel plays from the line in the middle of it,
+    // whether a search or manual scrolling put it there
+    async fn seek_to_lyrics_line(&mut self) -> Result<()> {
+        if self.focus.current() != Panel::Lyrics {
+            return Ok(());
+        }
+        let Some(lyrics) = &self.current_lyrics else {
+            return Ok(());
+        };
+        let last = lyrics.lines.len().saturating_sub(1) as isize;
+        let index = (self.current_lyrics_index() as isize + self.lyrics_scroll).clamp(0, last);
+        let Some(line) = lyrics.lines.get(index as usize) else {
+            return Ok(());
+        };
+        self.set_position(line.timestamp).await?;
+        self.lyrics_scroll = 0;
+        Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn is_muted(&self) -> bool {
-        self.is_muted
+    pub async fn toggle_queue(&mut self) {
+        self.show_queue = !self.show_queue;
+        if self.show_queue {
+            self.refresh_queue().await;
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn get_repeat_mode(&self) -> RepeatMode {
-        self.current_repeat_mode
+    // The current lyric line, or the title when there are no lyrics or the
+    // song is between lines
+    fn big_text_line(&self) -> Option<&str> {
+        let track = self.current_track.as_ref()?;
+        let position = self.interpolated_position()?;
+        let line = self
+            .current_lyrics
+            .as_ref()
+            .and_then(|lyrics| lyrics.lines.get(lyrics.find_index(position)))
+            .map(|line| line.text.trim())
+            .filter(|text| !text.is_empty());
+        Some(line.unwrap_or(&track.name))
     }
 
-    pub async fn update(&mut self) -> Result<()> {
-        let status = self.player.get_player_status().await;
+    async fn refresh_queue(&mut self) {
+        self.queue = match self.player.get_queue().await {
+            Ok(queue) => Some(queue),
+            Err(e) => {
+                tracing::debug!("Queue fetch failed: {}", e);
+                None
+            }
+        };
+        let last = self.queue.as_ref().map_or(0, |q| q.len().saturating_sub(1));
+        self.queue_selected = self.queue_selected.min(last);
+    }
 
-        let (new_track, new_volume) = match status {
-            Ok(s) => {
-                tracing::debug!(
-                    "[UPDATE] status OK: track={}, vol={:?}",
-                    s.track.as_ref().map(|t| t.name.as_str()).unwrap_or("None"),
-                    s.volume
-                );
-                (s.track, s.volume)
+    // Shift+J/K carry the selected track along; only acts while the queue
+    // panel has focus.
+    async fn move_queue_selection(&mut self, delta: isize) {
+        let len = self.queue.as_ref().map_or(0, Vec::len);
+        if self.focus.current() != Panel::Queue || len == 0 {
+            return;
+        }
+        let from = self.queue_selected;
+        let to = from.saturating_add_signed(delta).min(len - 1);
+        if to == from {
+            return;
+        }
+        match self.player.move_queue_entry(from, to).await {
+            Ok(()) => self.queue_selected = to,
+            Err(e) => self.command_line.set_message(e.to_string(), true),
+        }
+        self.refresh_queue().await;
+    }
+
+    async fn remove_queue_selection(&mut self) {
+        if self.focus.current() != Panel::Queue || self.queue.as_ref().is_none_or(Vec::is_empty) {
+            return;
+        }
+        if let Err(e) = self.player.remove_queue_entry(self.queue_selected).await {
+            self.command_line.set_message(e.to_string(), true);
+        }
+        self.refresh_queue().await;
+    }
+
+    #[allow(dead_code)]
+    pub async fn toggle_shuffle(&mut self) -> Result<()> {
+        self.player.set_shuffle(true).await
+    }
+
+    // Cycles from the mode the player last reported, so a change made in the
+    // Music app isn't undone; players that don't report it cycle from ours
+    pub async fn cycle_repeat(&mut self) -> Result<()> {
+        let next = match self.current_repeat_mode {
+            RepeatMode::Off => RepeatMode::All,
+            RepeatMode::All => RepeatMode::One,
+            RepeatMode::One => RepeatMode::Off,
+        };
+        self.player.set_repeat(next).await?;
+        self.current_repeat_mode = next;
+        self.dirty = true;
+        Ok(())
+    }
+
+    // The player's cover for `track`, as corrected by the artwork overrides.
+    // A chosen image skips the player's lookup, and its iTunes search, entirely.
+    async fn artwork_url_for(&self, track: &Track) -> Option<String> {
+        let key = self.track_key.as_deref();
+        let album_key = overrides::album_key(track);
+        if let Some(image) = key.and_then(|key| self.artwork_overrides.chosen(key, &album_key)) {
+            return Some(image);
+        }
+        let found = match self.player.get_artwork_url(track).await {
+            Ok(url) => {
+                tracing::debug!("[UPDATE] artwork_url={:?}", url);
+                url
             }
             Err(e) => {
-                tracing::warn!("[UPDATE] get_player_status FAILED: {}", e);
-                (None, None)
+                tracing::debug!("[UPDATE] artwork fetch FAILED: {}", e);
+                None
             }
         };
+        match key {
+            Some(key) => self.artwork_overrides.resolve(key, &album_key, found),
+            None => found,
+        }
+    }
 
-        self.volume = new_volume.unwrap_or(self.volume);
+    // `:artwork [album] <file|url>`
+    async fn set_artwork(&mut self, album: bool, source: &str) -> Result<()> {
+        let (Some(key), Some(track)) = (self.track_key.clone(), self.current_track.as_ref()) else {
+            return Err(anyhow!("Nothing is playing"));
+        };
+        let image = overrides::image_source(source)?;
+        let key = if album {
+            overrides::album_key(track)
+        } else {
+            key
+        };
+        self.artwork_overrides.set_image(&key, &image);
+        self.artwork_overrides.save().await?;
+        let scope = if album { "album" } else { "track" };
+        self.command_line
+            .set_message(format!("Artwork set for this {}", scope), false);
+        Ok(())
+    }
 
-        let artwork_url = if let Some(ref track) = new_track {
-            match self.player.get_artwork_url(track).await {
-                Ok(url) => {
-                    tracing::debug!("[UPDATE] artwork_url={:?}", url);
-                    url
-                }
-                Err(e) => {
-                    tracing::debug!("[UPDATE] artwork fetch FAILED: {}", e);
-                    None
-                }
+    // `:artwork reset`: the track's choices and rejections, and its album's image
+    async fn reset_artwork(&mut self) -> Result<()> {
+        let (Some(key), Some(track)) = (self.track_key.clone(), self.current_track.as_ref()) else {
+            return Err(anyhow!("Nothing is playing"));
+        };
+        let album_key = overrides::album_key(track);
+        let cleared = self.artwork_overrides.clear(&key) | self.artwork_overrides.clear(&album_key);
+        if !cleared {
+            return Err(anyhow!("No artwork override for this track"));
+        }
+        self.artwork_overrides.save().await?;
+        self.command_line.set_message("Artwork reset".into(), false);
+        Ok(())
+    }
+
+    // "This cover is wrong": it's never shown for the track again, and the
+    // next iTunes result it hasn't been shown takes its place
+    async fn retry_artwork(&mut self) -> Result<()> {
+        let (Some(key), Some(track)) = (self.track_key.clone(), self.current_track.clone()) else {
+            return Err(anyhow!("Nothing is playing"));
+        };
+        if let Some(url) = &self.current_artwork_url {
+            self.artwork_overrides.reject(&key, url);
+        }
+        let rejected = self
+            .artwork_overrides
+            .get(&key)
+            .map(|entry| entry.rejected.clone())
+            .unwrap_or_default();
+        let next = match self
+            .artwork_search
+            .candidates(&track.artist, &track.name, ARTWORK_CANDIDATES)
+            .await
+        {
+            Ok(urls) => urls.into_iter().find(|url| !rejected.contains(url)),
+            Err(e) => {
+                tracing::debug!("Artwork search failed: {}", e);
+                None
             }
-        } else {
-            None
         };
+        match &next {
+            Some(url) => self.artwork_overrides.set_image(&key, url),
+            None => self
+                .command_line
+                .set_message("No other cover found".into(), false),
+        }
+        self.artwork_overrides.save().await?;
+        // The next poll picks up the change
+        self.mark_dirty();
+        Ok(())
+    }
 
-        let track_changed = track_identity_changed(self.current_track.as_ref(), new_track.as_ref());
-        tracing::debug!(
-            "[UPDATE] track_changed={}, has_lyrics={}, artwork_changed={}",
-            track_changed,
-            self.current_lyrics.is_some(),
-            artwork_url != self.current_artwork_url
+    async fn add_bookmark(&mut self, name: String) -> Result<()> {
+        let (Some(key), Some(position)) = (self.track_key.clone(), self.interpolated_position())
+        else {
+            return Err(anyhow!("Nothing is playing"));
+        };
+        let name = if name.is_empty() {
+            format_duration_seconds(position)
+        } else {
+            name
+        };
+        self.bookmarks.add(
+            &key,
+            Bookmark {
+                name: name.clone(),
+                position,
+            },
         );
+        self.bookmarks.save().await?;
+        self.command_line
+            .set_message(format!("Bookmarked \"{}\"", name), false);
+        Ok(())
+    }
 
-        if track_changed {
-            self.clear_artwork_for_track_transition(new_track.is_some() && artwork_url.is_some());
-            self.current_lyrics = None;
-            self.lyrics_unreachable = false;
-            if let Some(task) = self.lyrics_task.take() {
-                task.abort();
+    async fn toggle_favorite(&mut self) {
+        let (Some(key), Some(track)) = (self.track_key.clone(), self.current_track.clone()) else {
+            return self
+                .command_line
+                .set_message("Nothing is playing".to_string(), true);
+        };
+        let starred = self.favorites.toggle(&key, &track);
+        match self.favorites.save().await {
+            Ok(()) if starred => self
+                .command_line
+                .set_message(format!("★ Starred \"{}\"", track.name), false),
+            Ok(()) => self
+                .command_line
+                .set_message(format!("Unstarred \"{}\"", track.name), false),
+            Err(e) => self
+                .command_line
+                .set_message(format!("Couldn't save favorites: {}", e), true),
+        }
+    }
+
+    // The command line, filled in with the track's note to edit
+    fn edit_note(&mut self) {
+        let note = self
+            .track_key
+            .as_deref()
+            .and_then(|key| self.notes.get(key))
+            .unwrap_or_default();
+        self.command_line.open_with(&format!("note {}", note));
+    }
+
+    // Going back skips a bookmark just passed, so repeated presses keep moving
+    async fn jump_to_bookmark(&mut self, forward: bool) -> Result<()> {
+        let (Some(key), Some(position)) = (&self.track_key, self.interpolated_position()) else {
+            return Ok(());
+        };
+        let bookmark = if forward {
+            self.bookmarks.next(key, position)
+        } else {
+            self.bookmarks
+                .previous(key, position.saturating_sub(BOOKMARK_BACK_GRACE))
+        };
+        match bookmark.cloned() {
+            Some(bookmark) => {
+                self.set_position(bookmark.position).await?;
+                self.command_line.set_message(bookmark.name, false);
             }
+            None => self.command_line.set_message(
+                if forward {
+                    "No bookmarks ahead"
+                } else {
+                    "No bookmarks behind"
+                }
+                .to_string(),
+                true,
+            ),
+        }
+        Ok(())
+    }
 
-            if let Some(ref track) = new_track {
-                let lyrics_manager = self.lyrics_manager.clone();
-                let track_clone = track.clone();
-                let task =
-                    tokio::spawn(async move { lyrics_manager.get_lyrics(&track_clone).await });
-                self.lyrics_task = Some(task);
+    async fn run_alarm(&mut self, alarm: ScheduledAlarm) {
+        let result = match (alarm.action, &alarm.playlist) {
+            (AlarmAction::Play, Some(playlist)) => self.player.play_playlist(playlist).await,
+            (AlarmAction::Play, None) => self.player.play().await,
+            (AlarmAction::Pause, _) => self.player.pause().await,
+        };
+        match result {
+            Ok(()) => self
+                .command_line
+                .set_message(format!("Alarm {}", alarm.describe()), false),
+            Err(e) => self
+                .command_line
+                .set_message(format!("Alarm {} failed: {}", alarm.describe(), e), true),
+        }
+    }
+
+    async fn toggle_focus_timer(&mut self) {
+        if self.focus_timer.take().is_some() {
+            self.command_line
+                .set_message("Focus timer stopped".to_string(), false);
+            return;
+        }
+        self.focus_timer = Some(FocusTimer::start(&self.config.focus, Instant::now()));
+        self.run_focus_phase(Phase::Work).await;
+    }
+
+    // Work plays `work_playlist` or resumes; breaks switch to `break_playlist`
+    // or pause
+    async fn run_focus_phase(&mut self, phase: Phase) {
+        let Some(timer) = &self.focus_timer else {
+            return;
+        };
+        let minutes = timer.length(phase).as_secs() / 60;
+        let focus = &self.config.focus;
+        let playlist = if phase.is_break() {
+            focus.break_playlist.as_deref()
+        } else {
+            focus.work_playlist.as_deref()
+        }
+        .filter(|p| !p.trim().is_empty());
+        let result = match playlist {
+            Some(playlist) => self.player.play_playlist(playlist).await,
+            None if phase.is_break() => self.player.pause().await,
+            None => self.player.play().await,
+        };
+        let label = phase.label(false).to_lowercase();
+        match result {
+            Ok(()) => self
+                .command_line
+                .set_message(format!("Focus timer: {} min {}", minutes, label), false),
+            Err(e) => self.command_line.set_message(
+                format!("Focus timer couldn't start the {}: {}", label, e),
+                true,
+            ),
+        }
+    }
+
+    // Moves to the next speed in PLAYBACK_RATES, staying put at either end
+    async fn step_playback_rate(&mut self, faster: bool) {
+        let current = match self.playback_rate {
+            Some(rate) => rate,
+            None => match self.player.get_rate().await {
+                Ok(rate) => rate,
+                Err(e) => return self.command_line.set_message(e.to_string(), true),
+            },
+        };
+        let rate = if faster {
+            PLAYBACK_RATES.iter().find(|rate| **rate > current)
+        } else {
+            PLAYBACK_RATES.iter().rev().find(|rate| **rate < current)
+        }
+        .copied()
+        .unwrap_or(current);
+        match self.player.set_rate(rate).await {
+            Ok(()) => {
+                self.playback_rate = Some(rate);
+                self.command_line
+                    .set_message(format!("Speed {}", format_rate(rate)), false);
             }
+            Err(e) => self.command_line.set_message(e.to_string(), true),
         }
+    }
 
-        if let Some(task) = &mut self.lyrics_task {
-            if task.is_finished() {
-                if let Some(task) = self.lyrics_task.take() {
-                    match task.await {
-                        Ok(Ok(Some(lyrics))) => {
-                            self.current_lyrics = Some(lyrics);
-                            self.lyrics_unreachable = false;
-                        }
-                        Ok(Ok(None)) => self.lyrics_unreachable = false,
-                        Ok(Err(e)) => {
-                            tracing::debug!("Lyrics fetch failed: {}", e);
-                            self.lyrics_unreachable = true;
-                        }
-                        Err(e) => tracing::warn!("Lyrics task panicked: {}", e),
-                    }
-                }
+    async fn step_balance(&mut self, step: f32) {
+        let current = match self.balance {
+            Some(balance) => balance,
+            None => match self.player.get_balance().await {
+                Ok(balance) => balance,
+                Err(e) => return self.command_line.set_message(e.to_string(), true),
+            },
+        };
+        // Rounded so repeated steps land back on centre exactly
+        let balance = ((current + step) / BALANCE_STEP).round() * BALANCE_STEP;
+        let balance = balance.clamp(-1.0, 1.0);
+        match self.player.set_balance(balance).await {
+            Ok(()) => {
+                self.balance = Some(balance);
+                self.command_line
+                    .set_message(format!("Balance {}", format_balance(balance)), false);
             }
+            Err(e) => self.command_line.set_message(e.to_string(), true),
         }
+    }
 
-        self.current_track = new_track;
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+    }
 
-        if let Some(ref track) = self.current_track {
-            let mut cache = self.metadata_cache.take().unwrap_or_default();
-            if track_changed {
-                cache.name = track.name.to_uppercase();
-                cache.artist = track.artist.to_uppercase();
-                cache.album = track.album.to_uppercase();
-            }
-            let progress_percent = if track.duration.as_secs() > 0 {
-                ((track.position.as_secs_f64() / track.duration.as_secs_f64()) * 100.0) as u16
-            } else {
-                0
-            };
-            cache.duration_str = format!(
-                "{} / {}",
-                format_duration(track.position),
-                format_duration(track.duration)
-            );
-            cache.gauge_label = format!(
-                " {}/{} | {:02}% ",
-                format_duration_seconds(track.position),
-                format_duration_seconds(track.duration),
-                progress_percent
-            );
-            cache.progress_percent = progress_percent;
-            self.metadata_cache = Some(cache);
-        } else {
-            self.metadata_cache = None;
+    pub async fn toggle_settings_menu(&mut self) {
+        let was_open = self.settings_menu.is_open;
+        // Reading these can mean driving the player's own settings window,
+        // so only do it once per backend
+        if !was_open && self.playback_options.is_none() {
+            self.playback_options = self.player.get_playback_options().await.ok();
+            self.autoplay = self.player.get_autoplay().await.ok();
+            self.refresh_settings_menu();
+        }
+        self.settings_menu.toggle();
+        if was_open {
+            self.needs_full_repaint = true;
         }
+    }
 
-        self.throbber_state.calc_next();
-        self.animation_frame = self.animation_frame.wrapping_add(1);
-        if artwork_url != self.current_artwork_url {
-            self.current_artwork_url = artwork_url.clone();
-            if let Some(url) = artwork_url {
-                self.is_loading_artwork = true;
-                self.artwork_protocol = None;
-                self.needs_full_repaint = true;
-                let manager = self.artwork_manager.clone();
-                let theme = self.current_theme();
-                let config = self.config.clone();
-                let is_retro = theme.is_retro;
+    pub fn is_settings_open(&self) -> bool {
+        self.settings_menu.is_open
+    }
 
-                if let Some(task) = self.artwork_task.take() {
-                    task.abort();
-                }
+    pub fn close_settings(&mut self) {
+        if self.settings_menu.is_open {
+            self.needs_full_repaint = true;
+        }
+        self.settings_menu.close();
+    }
 
-                let task: JoinHandle<Result<DynamicImage>> = tokio::spawn(async move {
-                    // For modern themes (non-retro), swap dark/light to fix color inversion
-                    if is_retro {
-                        manager
-                            .get_artwork_themed_v2(
-                                &url,
-                                theme.dim,
-                                theme.primary,
-                                theme.name,
-                                config.artwork.mosaic,
-                                is_retro,
-                            )
-                            .await
-                    } else {
-                        manager
-                            .get_artwork_themed_v2(
-                                &url,
-                                theme.primary,
-                                theme.dim,
-                                theme.name,
-                                config.artwork.mosaic,
-                                is_retro,
-                            )
-                            .await
-                    }
-                });
-                self.artwork_task = Some(task);
-            } else {
-                self.artwork_protocol = None;
-                self.is_loading_artwork = false;
-                if let Some(task) = self.artwork_task.take() {
-                    task.abort();
-                }
-            }
+    pub fn resolve_key(&mut self, key: crossterm::event::KeyEvent) -> Option<Action> {
+        match self.keymap.feed(key) {
+            KeyResult::Action(action) => Some(action),
+            KeyResult::Pending | KeyResult::Unbound => None,
         }
+    }
 
-        if let Some(task) = &mut self.artwork_task {
-            if task.is_finished() {
-                if let Some(task) = self.artwork_task.take() {
-                    match task.await {
-                        Ok(Ok(img)) => {
-                            self.artwork_protocol =
-                                Some(self.artwork_converter.create_protocol(img));
-                            self.needs_full_repaint = true;
-                        }
-                        Ok(Err(e)) => {
-                            tracing::debug!("Artwork load failed: {}", e);
-                            self.current_artwork_url = None;
-                            self.artwork_protocol = None;
-                            self.needs_full_repaint = true;
-                        }
-                        Err(e) => {
-                            tracing::warn!("Artwork task panicked: {}", e);
-                            self.current_artwork_url = None;
-                            self.artwork_protocol = None;
-                            self.needs_full_repaint = true;
-                        }
-                    }
-                }
-                self.is_loading_artwork = false;
+    pub fn resolve_pending_key(&mut self) -> Option<Action> {
+        self.keymap.tick()
+    }
+
+    // A control the player doesn't have, or one that fails, is shown on the
+    // command line rather than ending the session
+    pub async fn perform(&mut self, action: Action) {
+        self.dirty = true;
+        if let Err(e) = self.perform_action(action).await {
+            self.command_line.set_message(e.to_string(), true);
+        }
+    }
+
+    async fn perform_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::ToggleSettings => self.toggle_settings_menu().await,
+            Action::TogglePlayback => self.toggle_playback().await?,
+            Action::NextTrack => self.next_track().await?,
+            Action::PreviousTrack => self.previous_track().await?,
+            Action::VolumeUp => self.volume_up().await?,
+            Action::VolumeDown => self.volume_down().await?,
+            Action::VolumeUpFine => self.adjust_volume(1).await?,
+            Action::VolumeDownFine => self.adjust_volume(-1).await?,
+            Action::ToggleMute => self.toggle_mute().await?,
+            Action::SeekForward => self.seek_forward().await?,
+            Action::SeekBackward => self.seek_backward().await?,
+            Action::GoToStart => self.set_position(Duration::ZERO).await?,
+            Action::NavigateUp => self.navigate_up(),
+            Action::NavigateDown => self.navigate_down(),
+            Action::NavigateLeft => self.navigate_left(),
+            Action::NavigateRight => self.navigate_right(),
+            Action::CycleRepeat => self.cycle_repeat().await?,
+            Action::NextTheme => self.next_theme().await?,
+            Action::ToggleHelp => self.toggle_help(),
+            Action::CommandMode => self.open_command_line(),
+            Action::JumpToTime => self.command_line.open_with("seek "),
+            Action::ToggleQueue => self.toggle_queue().await,
+            Action::ToggleBigText => self.big_text = !self.big_text,
+            Action::ArtistInfo => self.toggle_artist_panel(),
+            Action::PlaySimilar => self.play_similar().await,
+            Action::RateUp => self.step_playback_rate(true).await,
+            Action::RateDown => self.step_playback_rate(false).await,
+            Action::AddBookmark => self.command_line.open_with("bookmark "),
+            Action::NextBookmark => self.jump_to_bookmark(true).await?,
+            Action::PreviousBookmark => self.jump_to_bookmark(false).await?,
+            Action::Snapshot => self.snapshot_request = Some(snapshot::default_path()),
+            Action::DebugOverlay => self.show_debug_overlay = !self.show_debug_overlay,
+            Action::SearchLyrics => self.open_lyrics_search(),
+            Action::LyricsNextMatch => self.step_lyrics_search(1),
+            Action::LyricsPreviousMatch => self.step_lyrics_search(-1),
+            Action::LyricsSeek if self.idle_grid.is_shown() => {
+                self.play_idle_grid_selection().await?
             }
+            Action::LyricsSeek => self.seek_to_lyrics_line().await?,
+            Action::QueueMoveUp => self.move_queue_selection(-1).await,
+            Action::QueueMoveDown => self.move_queue_selection(1).await,
+            Action::QueueRemove => self.remove_queue_selection().await,
+            Action::RetryArtwork => self.retry_artwork().await?,
+            Action::QuickSwitcher => self.open_quick_switcher(None),
+            Action::JumpToArtist => self.jump_to_current(false),
+            Action::JumpToAlbum => self.jump_to_current(true),
+            Action::ToggleNormalization => self.toggle_normalization().await,
+            Action::BalanceLeft => self.step_balance(-BALANCE_STEP).await,
+            Action::BalanceRight => self.step_balance(BALANCE_STEP).await,
+            Action::ToggleFavorite => self.toggle_favorite().await,
+            Action::Favorites => self.open_favorites(),
+            Action::EditNote => self.edit_note(),
+            Action::FocusTimer => self.toggle_focus_timer().await,
+            Action::FileBrowser => self.open_file_browser().await,
+            Action::Stations => self.open_stations(),
         }
         Ok(())
     }
-}
 
-// The 8th argument (animation_frame) drives the current-line marquee; a param
-// struct for this single-caller draw helper would be over-engineering.
-#[allow(clippy::too_many_arguments)]
-fn draw_lyrics(
-    f: &mut Frame,
-    area: Rect,
-    track: &Track,
-    lyrics: Option<&Lyrics>,
-    unreachable: bool,
-    theme: Theme,
-    is_jp: bool,
-    animation_frame: u32,
-) {
-    let lyrics: &Lyrics = match lyrics {
-        Some(l) => l,
-        None => {
-            let message = if unreachable {
-                if is_jp {
-                    "信号なし"
-                } else {
-                    "NO SIGNAL"
-                }
-            } else if is_jp {
-                "歌詞なし"
-            } else {
-                "NO LYRICS AVAILABLE"
-            };
-            let p = Paragraph::new(message)
-                .style(Style::default().fg(theme.dim).add_modifier(Modifier::DIM))
-                .alignment(Alignment::Center);
-            let v_center = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Percentage(45),
-                    Constraint::Length(1),
-                    Constraint::Min(0),
-                ])
-                .split(area);
-            f.render_widget(p, v_center[1]);
+    pub fn open_command_line(&mut self) {
+        self.command_line.open();
+    }
+
+    pub fn is_command_mode(&self) -> bool {
+        self.command_line.is_active
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    // Quits the UI so main can start it again with `profile`'s config
+    fn switch_profile(&mut self, profile: Option<String>) {
+        if profile == self.config.profile {
             return;
         }
-    };
+        self.profile_switch = Some(profile);
+        self.should_quit = true;
+    }
 
-    let current_index = lyrics.find_index(track.position);
-    let h = area.height as usize;
-    let mid = h / 2;
+    // Now-playing for `amcli widget`. Only the instance that holds the player
+    // serves it, so a demo doesn't take the socket over.
+    pub fn serve_widget(&self) {
+        match WidgetServer::spawn(widget::socket_path()) {
+            Ok(server) => events::forward_to_widget(server, self.events.subscribe()),
+            Err(e) => tracing::warn!("Couldn't open the widget socket: {}", e),
+        }
+    }
 
-    let width = area.width as usize;
-    let mut lines = Vec::new();
-    for (i, line) in lyrics.lines.iter().enumerate() {
-        let distance = (i as isize - current_index as isize).unsigned_abs();
-        let style = if i == current_index {
-            // Tier 1: Current line -- accent color + bold (brightest)
-            Style::default()
-                .fg(theme.accent)
-                .add_modifier(Modifier::BOLD)
-        } else if distance <= 2 {
-            // Tier 2: Near lines (+-1-2) -- normal readable text
-            Style::default().fg(theme.primary)
-        } else {
-            // Tier 3: Far lines (beyond +-2) -- faded
-            Style::default().fg(theme.dim)
-        };
-        // Marquee-scroll only the current line when it overflows the width;
-        // other lines stay put (and are truncated by the paragraph as before).
-        let text: Cow<str> = if i == current_index {
-            scroll_text(&line.text, width, animation_frame)
-        } else {
-            Cow::Borrowed(line.text.as_str())
+    // Only the instance that holds the player logs plays, like the widget
+    pub fn log_history(&self) {
+        if self.config.general.play_history {
+            events::forward_to_history(
+                PlayHistory::new(PlayHistory::default_path()),
+                self.events.subscribe(),
+            );
+        }
+    }
+
+    // `[player] mpris`: lets the desktop's media controls drive the internal
+    // and Subsonic players, which have no system integration of their own
+    pub async fn serve_mpris(&mut self) {
+        match MprisServer::spawn().await {
+            Ok(server) => self.mpris = Some(server),
+            Err(e) => tracing::warn!("Couldn't register with MPRIS: {}", e),
+        }
+    }
+
+    pub fn take_profile_switch(&mut self) -> Option<Option<String>> {
+        self.profile_switch.take()
+    }
+
+    pub async fn submit_command(&mut self) -> Result<()> {
+        let is_search = self.command_line.is_search();
+        let input = self.command_line.submit();
+        if is_search {
+            self.search_lyrics(&input);
+            return Ok(());
+        }
+        if input.trim().is_empty() {
+            return Ok(());
+        }
+        let result = match command::parse_command(&input) {
+            Ok(cmd) => self.execute_command(cmd).await,
+            Err(e) => Err(e),
         };
-        lines.push(Line::from(Span::styled(text, style)));
+        if let Err(e) = result {
+            self.command_line.set_message(e.to_string(), true);
+        }
+        Ok(())
     }
 
-    let scroll = current_index.saturating_sub(mid) as u16;
-    let p = Paragraph::new(lines)
-        .alignment(Alignment::Center)
-        .scroll((scroll, 0));
+    async fn execute_command(&mut self, cmd: Command) -> Result<()> {
+        match cmd {
+            Command::Seek(position) => {
+                let position = match &self.current_track {
+                    Some(track) => position.min(track.duration),
+                    None => position,
+                };
+                self.set_position(position).await
+            }
+            Command::SeekRelative(seconds) => self.seek_by(seconds).await,
+            Command::SeekPercent(percent) => match &self.current_track {
+                Some(track) => {
+                    let position = track.duration.mul_f32(percent / 100.0);
+                    self.set_position(position.min(track.duration)).await
+                }
+                None => Err(anyhow!("Nothing is playing")),
+            },
+            Command::Volume(volume) => {
+                self.volume = volume;
+                self.is_muted = false;
+                self.player.set_volume(volume).await?;
+                self.show_volume();
+                Ok(())
+            }
+            Command::Theme(query) => {
+                let index = command::find_theme(&self.themes, &query)
+                    .ok_or_else(|| anyhow!("Unknown theme: {}", query))?;
+                self.apply_theme(index);
+                self.config.ui.color_theme = self.themes[index].name.to_lowercase();
+                self.config.save().await
+            }
+            Command::SaveTheme(name) => self.save_custom_theme(&name).await,
+            Command::Profile(name) => {
+                let profile = match name.as_str() {
+                    "default" => None,
+                    _ => {
+                        amcli_core::config::check_profile_name(&name)?;
+                        Some(name)
+                    }
+                };
+                self.switch_profile(profile);
+                Ok(())
+            }
+            Command::Language(language) => self.set_language(language).await,
+            Command::Play(query) => {
+                if !self.player.play_query(&query).await? {
+                    let message = self
+                        .catalog_suggestion(&query)
+                        .await
+                        .unwrap_or_else(|| format!("No match for \"{}\"", query));
+                    self.command_line.set_message(message, true);
+                }
+                Ok(())
+            }
+            Command::Export(source, path) => {
+                let entries = match source {
+                    ExportSource::Queue => self.player.get_queue().await?,
+                    ExportSource::History => self.session_history.clone(),
+                    ExportSource::Favorites => self.favorites.entries(),
+                };
+                let path = amcli_core::playlist::expand_home(&path);
+                amcli_core::playlist::write_export(&path, &entries).await?;
+                self.command_line.set_message(
+                    format!("Exported {} tracks to {}", entries.len(), path.display()),
+                    false,
+                );
+                Ok(())
+            }
+            Command::Bookmark(name) => self.add_bookmark(name).await,
+            Command::Unbookmark(name) => {
+                let key = self.track_key.as_deref().unwrap_or_default();
+                if self.bookmarks.remove(key, &name).is_none() {
+                    return Err(anyhow!("No bookmark named \"{}\"", name));
+                }
+                self.bookmarks.save().await
+            }
+            Command::ListBookmarks => {
+                let key = self.track_key.as_deref().unwrap_or_default();
+                let list = self
+                    .bookmarks
+                    .for_track(key)
+                    .iter()
+                    .map(|b| format!("{} {}", format_duration_seconds(b.position), b.name))
+                    .collect::<Vec<_>>();
+                if list.is_empty() {
+                    return Err(anyhow!("No bookmarks in this track"));
+                }
+                self.command_line.set_message(list.join(" · "), false);
+                Ok(())
+            }
+            Command::Note(note) => {
+                let Some(key) = self.track_key.clone() else {
+                    return Err(anyhow!("Nothing is playing"));
+                };
+                let previous = self.notes.set(&key, &note);
+                self.notes.save().await?;
+                let message = match (note.trim().is_empty(), previous) {
+                    (false, _) => "Note saved",
+                    (true, Some(_)) => "Note removed",
+                    (true, None) => "No note to remove",
+                };
+                self.command_line.set_message(message.to_string(), false);
+                Ok(())
+            }
+            Command::Snapshot(path) => {
+                self.snapshot_request = Some(if path.is_empty() {
+                    snapshot::default_path()
+                } else {
+                    amcli_core::playlist::expand_home(&path)
+                });
+                Ok(())
+            }
+            Command::Artwork { album, source } => self.set_artwork(album, &source).await,
+            Command::ResetArtwork => self.reset_artwork().await,
+            Command::Station(query) => {
+                let query = query.to_lowercase();
+                let station = self
+                    .config
+                    .stations
+                    .iter()
+                    .find(|s| s.name.to_lowercase() == query)
+                    .or_else(|| {
+                        self.config
+                            .stations
+                            .iter()
+                            .find(|s| s.name.to_lowercase().contains(&query))
+                    })
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No station named \"{}\"", query))?;
+                self.play_station(&station).await;
+                Ok(())
+            }
+            Command::AddStation { url, name } => {
+                if let Some(existing) = self.config.stations.iter().find(|s| s.url == url) {
+                    return Err(anyhow!("Already saved as \"{}\"", existing.name));
+                }
+                self.config.stations.push(Station {
+                    name: name.clone(),
+                    url,
+                });
+                self.config.save().await?;
+                self.command_line
+                    .set_message(format!("Added {} to the stations", name), false);
+                Ok(())
+            }
+            Command::Quit => {
+                self.should_quit = true;
+                Ok(())
+            }
+        }
+    }
 
-    f.render_widget(p, area);
-}
+    pub fn is_loading_artwork(&self) -> bool {
+        self.is_loading_artwork
+    }
 
-fn draw_chassis(f: &mut Frame, area: Rect, theme: Theme, is_jp: bool) -> Rect {
-    if theme.is_retro {
-        let chassis_block = Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Thick)
-            .border_style(Style::default().fg(theme.dim))
-            .title(vec![
-                Span::styled(" + ", Style::default().fg(theme.dim)),
-                Span::styled(
-                    format!(" ❖ MODEL: AMCLI // THEME: {} ", theme.name.to_uppercase()),
-                    Style::default()
-                        .fg(theme.primary)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(" + ", Style::default().fg(theme.dim)),
-            ])
-            .title_alignment(Alignment::Center)
-            .title_bottom(vec![
-                Span::styled(" + ", Style::default().fg(theme.dim)),
-                Span::styled(
-                    if is_jp {
-                        " 産業用音響機器 "
-                    } else {
-                        " INDUSTRIAL AUDIO COMPONENT "
-                    },
-                    Style::default().fg(theme.dim).add_modifier(Modifier::DIM),
-                ),
-                Span::styled(" + ", Style::default().fg(theme.dim)),
-            ])
-            .title_alignment(Alignment::Center);
+    // Render and poll times for the debug overlay, measured by the main loop
+    pub fn record_frame_time(&self, elapsed: Duration) {
+        self.frame_timer.record(elapsed);
+    }
 
-        let inner = chassis_block.inner(area);
-        f.render_widget(chassis_block, area);
+    pub fn record_update_time(&self, elapsed: Duration) {
+        self.update_timer.record(elapsed);
+        diagnostics::PLAYER_POLLS
+            .get(self.config.player.backend.label())
+            .record(elapsed);
+    }
 
-        for y in (inner.top()..inner.bottom()).step_by(2) {
-            let line = Paragraph::new(" ".repeat(inner.width as usize)).style(
-                Style::default()
-                    .bg(Color::Rgb(5, 5, 5))
-                    .add_modifier(Modifier::DIM),
-            );
-            f.render_widget(line, Rect::new(inner.left(), y, inner.width, 1));
+    fn debug_report(&self) -> DebugReport {
+        let running = |task: bool| task.then(|| "loading".to_string());
+        let artwork = running(self.artwork_task.is_running())
+            .or_else(|| {
+                (!self.artwork_frames.is_empty())
+                    .then(|| format!("animated, {} frames", self.artwork_frames.len() + 1))
+            })
+            .or_else(|| self.artwork_protocol.is_some().then(|| "ready".into()))
+            .unwrap_or_else(|| "none".into());
+        let lyrics = running(self.lyrics_task.is_running())
+            .or_else(|| {
+                self.current_lyrics
+                    .as_ref()
+                    .map(|lyrics| format!("{} lines", lyrics.lines.len()))
+            })
+            .or_else(|| self.lyrics_unreachable.then(|| "unreachable".into()))
+            .unwrap_or_else(|| "none".into());
+        let artist = running(self.artist_info_task.is_some() || self.artist_image_task.is_some())
+            .unwrap_or_else(|| "idle".into());
+        DebugReport::new(
+            self.frame_timer.summary(),
+            self.update_timer.summary(),
+            vec![("artwork", artwork), ("lyrics", lyrics), ("artist", artist)],
+        )
+    }
+
+    pub fn take_snapshot_request(&mut self) -> Option<PathBuf> {
+        self.snapshot_request.take()
+    }
+
+    pub async fn save_snapshot(&mut self, path: &Path, buffer: &Buffer) {
+        self.dirty = true;
+        match snapshot::write(path, buffer).await {
+            Ok(()) => self
+                .command_line
+                .set_message(format!("Saved snapshot to {}", path.display()), false),
+            Err(e) => self
+                .command_line
+                .set_message(format!("Couldn't save snapshot: {}", e), true),
         }
-        inner
-    } else {
-        area
     }
-}
 
-fn draw_screen_border(f: &mut Frame, area: Rect, theme: Theme) -> Rect {
-    if theme.is_retro {
-        let screen_block = Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Double)
-            .border_style(Style::default().fg(theme.dim));
-        let inner = screen_block.inner(area);
-        f.render_widget(screen_block, area);
-        inner
-    } else {
-        area
+    pub fn is_splash_active(&self) -> bool {
+        self.boot_splash.is_some()
     }
-}
 
-fn draw_idle(f: &mut Frame, area: Rect, theme: Theme, is_jp: bool) {
-    let idle_msg = if is_jp {
-        "メディア入力待機中..."
-    } else {
-        "WAITING FOR MEDIA INPUT..."
-    };
-    let insert_msg = if is_jp {
-        "テープまたはディスクを挿入してください"
-    } else {
-        "INSERT TAPE OR DISC"
-    };
-    let idle_text = vec![
-        Line::from(""),
-        Line::from(idle_msg),
-        Line::from(""),
-        Line::from(Span::styled(
-            insert_msg,
-            Style::default()
-                .fg(theme.alert)
-                .add_modifier(Modifier::SLOW_BLINK),
-        )),
-    ];
-    let idle_p = Paragraph::new(idle_text)
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(theme.dim))
-        .block(Block::default().padding(ratatui::widgets::Padding::new(0, 0, 5, 0)));
-    f.render_widget(idle_p, area);
-}
+    pub fn skip_splash(&mut self) {
+        if self.boot_splash.take().is_some() {
+            self.needs_full_repaint = true;
+        }
+    }
 
-fn draw_progress(f: &mut Frame, area: Rect, track: &Track, theme: Theme) {
-    let progress_percent = if track.duration.as_secs() > 0 {
-        ((track.position.as_secs_f64() / track.duration.as_secs_f64()) * 100.0) as u16
-    } else {
-        0
-    };
+    pub fn is_focused(&self) -> bool {
+        self.is_focused
+    }
 
-    let label = format!(
-        " {}/{} | {:02}% ",
-        format_duration_seconds(track.position),
-        format_duration_seconds(track.duration),
-        progress_percent
-    );
+    pub fn set_focused(&mut self, focused: bool) {
+        if focused && !self.is_focused {
+            // Terminal graphics may have been disturbed while we were not drawing
+            self.needs_full_repaint = true;
+        }
+        self.is_focused = focused;
+    }
 
-    let gauge = Gauge::default()
-        .block(
-            Block::default()
-                .borders(Borders::TOP | Borders::BOTTOM)
-                .border_style(Style::default().fg(theme.dim))
-                .title(vec![
-                    Span::styled(" [ ", Style::default().fg(theme.dim)),
-                    Span::styled(label, Style::default().fg(theme.dim)),
-                    Span::styled(" ] ", Style::default().fg(theme.dim)),
-                ]),
-        )
-        .gauge_style(Style::default().fg(theme.primary).bg(if theme.is_retro {
-            Color::Rgb(15, 15, 15)
-        } else {
-            theme.dim
-        }))
-        .percent(progress_percent.min(100))
-        .label("");
+    // Redraws at the new size right away instead of on the next poll, with
+    // the cover encoded again for the new area and cell size
+    pub fn handle_resize(&mut self) {
+        self.artwork_converter.refresh_font_size();
+        self.restyle_artwork();
+        self.mark_dirty();
+    }
 
-    f.render_widget(gauge, area);
-}
+    // After the terminal was handed back to the shell and taken over again
+    pub fn repaint(&mut self) {
+        self.needs_full_repaint = true;
+        self.mark_dirty();
+    }
 
-fn inset_rect(area: Rect, margin: u16) -> Rect {
-    Rect::new(
-        area.x.saturating_add(margin),
-        area.y.saturating_add(margin),
-        area.width.saturating_sub(margin.saturating_mul(2)),
-        area.height.saturating_sub(margin.saturating_mul(2)),
-    )
-}
+    pub fn take_needs_full_repaint(&mut self) -> bool {
+        std::mem::take(&mut self.needs_full_repaint)
+    }
 
-fn center_rect(area: Rect, width: u16, height: u16) -> Rect {
-    let width = width.min(area.width);
-    let height = height.min(area.height);
+    fn record_play(&mut self, track: &Track) {
+        self.session_history.push(QueueEntry {
+            track: Track {
+                position: Duration::ZERO,
+                ..track.clone()
+            },
+            location: None,
+        });
+    }
 
-    Rect::new(
-        area.x + area.width.saturating_sub(width) / 2,
-        area.y + area.height.saturating_sub(height) / 2,
-        width,
-        height,
-    )
-}
+    // Same lyrics, but a fresh play for the history
+    fn record_restart(&mut self, track: Option<Track>) {
+        self.lyrics_scroll = 0;
+        self.lyrics_search = None;
+        if let Some(track) = track {
+            self.events.publish(AppEvent::NewPlay(track));
+        }
+    }
 
-fn artwork_protocol_rect(bounds: Rect, protocol: &StatefulProtocol) -> Rect {
-    let fit = protocol.size_for(Resize::Fit(None), bounds);
-    center_rect(bounds, fit.width, fit.height)
-}
+    fn clear_artwork_for_track_transition(&mut self, show_loading: bool) {
+        self.current_artwork_url = None;
+        self.artwork_protocol = None;
+        self.is_loading_artwork = show_loading && self.config.artwork.album;
+        self.stop_artwork_tasks();
+        self.needs_full_repaint = true;
+    }
 
-fn draw_artwork(
-    f: &mut Frame,
-    area: Rect,
-    protocol: Option<&mut StatefulProtocol>,
-    is_loading: bool,
-    throbber_state: &mut ThrobberState,
-    theme: Theme,
-    is_jp: bool,
-) {
-    let art_bounds = inset_rect(area, 1);
+    fn fetch_lyrics(&mut self, track: Track) {
+        let lyrics_manager = self.lyrics_manager.clone();
+        self.lyrics_task
+            .start(async move { lyrics_manager.get_lyrics(&track).await });
+    }
 
-    if is_loading {
-        let art_rect = center_rect(art_bounds, 1, 1);
-        let loader = Throbber::default()
-            .throbber_set(BRAILLE_SIX_DOUBLE)
-            .use_type(WhichUse::Spin)
-            .style(Style::default().fg(theme.accent));
-        f.render_stateful_widget(loader, art_rect, throbber_state);
-    } else if let Some(protocol) = protocol {
-        let art_rect = artwork_protocol_rect(art_bounds, protocol);
-        let image = StatefulImage::default().resize(Resize::Fit(None));
-        f.render_stateful_widget(image, art_rect, protocol);
-    } else {
-        let no_sig_text = if is_jp { "信号なし" } else { "NO SIGNAL" };
-        let no_sig = Paragraph::new(no_sig_text)
-            .style(Style::default().fg(theme.dim).add_modifier(Modifier::DIM))
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::NONE));
-        let v_center = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(45),
-                Constraint::Length(1),
-                Constraint::Min(0),
-            ])
-            .split(art_bounds);
-        f.render_widget(no_sig, v_center[1]);
+    // Coming back online retries whatever offline mode left missing
+    async fn set_offline(&mut self, offline: bool) -> Result<()> {
+        amcli_core::network::set_offline(offline);
+        self.config.network.offline = offline;
+        self.refresh_settings_menu();
+        if !offline {
+            if let (None, Some(track)) = (&self.current_lyrics, &self.current_track) {
+                self.lyrics_unreachable = false;
+                self.fetch_lyrics(track.clone());
+            }
+            if self.artwork_protocol.is_none() {
+                self.current_artwork_url = None;
+            }
+        }
+        self.config.save().await
     }
-}
 
-fn draw_metadata(
-    f: &mut Frame,
-    area: Rect,
-    track: &Track,
-    animation_frame: u32,
-    is_two_columns: bool,
-    theme: Theme,
-    is_jp: bool,
-) {
-    let status_text = if is_jp {
-        "動作状態: "
-    } else {
-        "SYS.STATUS: "
-    };
-    let online_text = if is_jp { "稼働中" } else { "ONLINE" };
+    // Only saved once the new backend is up, so a bad choice doesn't stick
+    async fn switch_backend(&mut self, backend: amcli_core::config::PlayerBackend) -> Result<()> {
+        let mut player_config = self.config.player.clone();
+        player_config.backend = backend;
+        self.player = amcli_core::player::create_player(&player_config)
+            .await?
+            .into();
+        self.config.player = player_config;
+        self.playback_options = None;
+        self.autoplay = None;
+        self.playback_rate = None;
+        self.balance = None;
+        self.refresh_settings_menu();
+        self.current_track = None;
+        self.clear_artwork_for_track_transition(false);
+        self.config.save().await
+    }
 
-    // Only show status line for retro themes
-    let status_line = if theme.is_retro {
-        Some(Line::from(vec![
-            Span::styled(status_text, Style::default().fg(theme.dim)),
-            Span::styled(
-                online_text,
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw("  "),
-            Span::styled("PCM 44.1kHz / STEREO", Style::default().fg(theme.accent)),
-        ]))
-    } else {
-        None
-    };
+    // Switches theme only when the schedule crosses into day or night, so a
+    // manual pick holds until the next crossing
+    async fn apply_theme_schedule(&mut self) {
+        let Some(schedule) = self.config.ui.schedule.clone() else {
+            return;
+        };
+        if self.theme_editor.is_some()
+            || self
+                .schedule_checked_at
+                .is_some_and(|at| at.elapsed() < SCHEDULE_CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.schedule_checked_at = Some(Instant::now());
+        let dark = if schedule.follow_system {
+            schedule::system_is_dark().await
+        } else {
+            None
+        };
+        let phase = match schedule::phase_at(&schedule, chrono::Local::now().time(), dark) {
+            Ok(phase) => phase,
+            Err(e) => {
+                tracing::warn!("Ignoring [ui.schedule]: {}", e);
+                return;
+            }
+        };
+        if self.schedule_phase == Some(phase) {
+            return;
+        }
+        self.schedule_phase = Some(phase);
+        let name = schedule::theme_name(&schedule, phase);
+        match command::find_theme(&self.themes, name) {
+            Some(index) if index != self.current_theme_index => self.apply_theme(index),
+            Some(_) => {}
+            None => tracing::warn!("Scheduled theme not found: {}", name),
+        }
+    }
 
-    let labels = if is_jp {
-        vec!["曲名", "アーティスト", "アルバム"]
-    } else {
-        vec!["TRACK TITLE", "ARTIST", "ALBUM REFERENCE"]
-    };
+    // Artwork shares the active theme's look, so the artist image does too
+    fn artwork_style(&self) -> ArtworkStyle {
+        let theme = self.current_theme();
+        let crt = &self.config.artwork.crt;
+        // For modern themes (non-retro), swap dark/light to fix color inversion
+        let (dark, light) = if theme.is_retro {
+            (theme.dim, theme.primary)
+        } else {
+            (theme.primary, theme.dim)
+        };
+        ArtworkStyle {
+            theme_name: theme.name.to_string(),
+            dark,
+            light,
+            is_retro: theme.is_retro,
+            mosaic: self.config.artwork.mosaic.then_some(Mosaic {
+                tile_size: self.config.artwork.mosaic_tile_size,
+                gap: self.config.artwork.mosaic_gap,
+                shape: self.config.artwork.mosaic_shape,
+            }),
+            crt: crt.enabled.then(|| crt.clone()),
+        }
+    }
 
-    let values = [
-        track.name.to_uppercase(),
-        track.artist.to_uppercase(),
-        track.album.to_uppercase(),
-        format!(
-            "{} / {}",
-            format_duration(track.position),
-            format_duration(track.duration)
-        ),
-    ];
+    // A failed check stays quiet; it's retried next launch
+    async fn poll_update_check(&mut self) {
+        if let Some(task) = self.update_task.take_if(|task| task.is_finished()) {
+            match task.await {
+                Ok(Ok(latest)) => self.update_available = latest,
+                Ok(Err(e)) => tracing::debug!("Update check failed: {}", e),
+                Err(e) => tracing::warn!("Update check task panicked: {}", e),
+            }
+        }
+    }
 
-    let _available_height = area.height as usize;
-    let items_count = labels.len();
+    async fn poll_artist_panel(&mut self) {
+        if let Some(task) = self.artist_info_task.take_if(|task| task.is_finished()) {
+            let state = match task.await {
+                Ok(Ok(Some(info))) => {
+                    if let Some(url) = info.image_url.clone() {
+                        let manager = self.artwork_manager.clone();
+                        let style = self.artwork_style();
+                        let resolution = self.artwork_converter.resolution();
+                        self.artist_image_task = Some(tokio::spawn(async move {
+                            manager
+                                .get_artwork_themed_v2(&url, &style, resolution)
+                                .await
+                        }));
+                    }
+                    ArtistState::Found(info)
+                }
+                Ok(Ok(None)) => ArtistState::Missing,
+                Ok(Err(e)) => {
+                    tracing::debug!("Artist info fetch failed: {}", e);
+                    ArtistState::Failed(e.to_string())
+                }
+                Err(e) => {
+                    tracing::warn!("Artist info task panicked: {}", e);
+                    ArtistState::Failed(e.to_string())
+                }
+            };
+            if let Some(panel) = &mut self.artist_panel {
+                panel.state = state;
+            }
+        }
+        if let Some(task) = self.artist_image_task.take_if(|task| task.is_finished()) {
+            match task.await {
+                Ok(Ok(img)) => {
+                    if let Some(panel) = &mut self.artist_panel {
+                        panel.image = Some(self.artwork_converter.create_protocol(img));
+                        self.needs_full_repaint = true;
+                    }
+                }
+                Ok(Err(e)) => tracing::debug!("Artist image load failed: {}", e),
+                Err(e) => tracing::warn!("Artist image task panicked: {}", e),
+            }
+        }
+    }
 
-    if is_two_columns {
-        let col_layout = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
-            .spacing(SPACING_NORMAL)
-            .split(area);
+    // When `:play` finds nothing in the library, points at what the Apple
+    // Music catalog has under that name instead
+    async fn catalog_suggestion(&self, query: &str) -> Option<String> {
+        let songs = match self.musickit.as_ref()?.search_songs(query, 3).await {
+            Ok(songs) => songs,
+            Err(e) => {
+                tracing::debug!("Catalog search failed: {}", e);
+                return None;
+            }
+        };
+        let found = songs
+            .iter()
+            .map(|song| format!("{} — {}", song.name, song.artist))
+            .collect::<Vec<_>>();
+        (!found.is_empty()).then(|| format!("Not in your library. Catalog: {}", found.join(", ")))
+    }
 
-        let mid = items_count.div_ceil(2);
-        let col_width = col_layout[0].width.saturating_sub(6) as usize;
+    // Starts the catalog's radio station for the current song, which Apple
+    // fills with similar tracks
+    async fn play_similar(&mut self) {
+        let result = async {
+            let track = self
+                .current_track
+                .as_ref()
+                .ok_or_else(|| anyhow!("Nothing is playing"))?;
+            let musickit = self
+                .musickit
+                .as_ref()
+                .ok_or_else(|| anyhow!("Set musickit.developer_token to play similar songs"))?;
+            let url = musickit
+                .song_station_url(&track.name, &track.artist)
+                .await?
+                .ok_or_else(|| anyhow!("\"{}\" isn't in the Apple Music catalog", track.name))?;
+            self.player.play_catalog_url(&url).await?;
+            Ok::<_, anyhow::Error>(track.name.clone())
+        }
+        .await;
+        match result {
+            Ok(name) => self
+                .command_line
+                .set_message(format!("Playing songs like \"{}\"", name), false),
+            Err(e) => self.command_line.set_message(e.to_string(), true),
+        }
+    }
 
-        for col in 0..2 {
-            let start = if col == 0 { 0 } else { mid };
-            let end = if col == 0 { mid } else { items_count };
-            let mut lines = vec![Line::from("")];
+    pub fn publish(&self, event: AppEvent) {
+        self.events.publish(event);
+    }
 
-            if col == 0 {
-                if let Some(ref s_line) = status_line {
-                    lines.push(s_line.clone());
-                    lines.push(Line::from(vec![
-                        Span::raw("────────────────────────").fg(theme.dim)
-                    ]));
+    // Called every loop iteration, focused or not, so media keys feel immediate
+    pub async fn handle_events(&mut self) -> Result<()> {
+        while let Some(command) = self
+            .remote_commands
+            .as_mut()
+            .and_then(RemoteCommands::try_next)
+        {
+            self.events.publish(AppEvent::Remote(command));
+        }
+        while let Some(command) = self.mpris.as_mut().and_then(MprisServer::try_next) {
+            self.events.publish(AppEvent::Remote(command));
+        }
+        while let Ok(event) = self.inbox.try_recv() {
+            match event {
+                AppEvent::Remote(command) => {
+                    self.dirty = true;
+                    // Media keys and MPRIS clients can ask for controls the
+                    // player doesn't have; that's no reason to quit
+                    if let Err(e) = self.remote_command(command).await {
+                        tracing::warn!("Remote command {:?} failed: {}", command, e);
+                        self.command_line.set_message(e.to_string(), true);
+                    }
+                }
+                AppEvent::Key(key) => self.handle_key(key).await?,
+                AppEvent::Quit => self.quit(),
+                AppEvent::NewPlay(track) => self.record_play(&track),
+                AppEvent::Status {
+                    state,
+                    track,
+                    artwork_url,
+                } => {
+                    if let Some(mpris) = &self.mpris {
+                        mpris.publish(MprisState {
+                            state,
+                            track,
+                            artwork_url,
+                            volume: self.volume,
+                        });
+                    }
                 }
-            } else if theme.is_retro {
-                lines.push(Line::from(""));
-                lines.push(Line::from(""));
             }
+        }
+        Ok(())
+    }
 
-            for i in start..end {
-                lines.push(Line::from(Span::styled(
-                    labels[i],
-                    Style::default()
-                        .fg(theme.dim)
-                        .add_modifier(Modifier::ITALIC),
-                )));
+    async fn remote_command(&mut self, command: RemoteCommand) -> Result<()> {
+        match command {
+            RemoteCommand::Play => self.player.play().await?,
+            RemoteCommand::Pause => self.player.pause().await?,
+            RemoteCommand::TogglePlayPause => self.toggle_playback().await?,
+            RemoteCommand::Next => self.next_track().await?,
+            RemoteCommand::Previous => self.previous_track().await?,
+            RemoteCommand::VolumeUp => self.volume_up().await?,
+            RemoteCommand::VolumeDown => self.volume_down().await?,
+            RemoteCommand::Stop => self.player.stop().await?,
+            RemoteCommand::Seek(seconds) => self.seek_by(seconds).await?,
+            RemoteCommand::SetPosition(position) => {
+                self.execute_command(Command::Seek(position)).await?
+            }
+            RemoteCommand::SetVolume(volume) => {
+                self.execute_command(Command::Volume(volume)).await?
+            }
+            RemoteCommand::Quit => self.quit(),
+        }
+        Ok(())
+    }
 
-                let display_val = scroll_text(&values[i], col_width, animation_frame);
+    pub fn is_artist_panel_open(&self) -> bool {
+        self.artist_panel.is_some()
+    }
 
-                lines.push(Line::from(Span::styled(
-                    format!(" {} ", display_val),
-                    Style::default()
-                        .bg(theme.dim)
-                        .fg(theme.bg)
-                        .add_modifier(Modifier::BOLD),
-                )));
+    // Fetches lazily: nothing is looked up until the panel is first opened
+    // for an artist, and the client's cache makes reopening it instant
+    fn toggle_artist_panel(&mut self) {
+        if self.artist_panel.is_some() {
+            self.close_artist_panel();
+            return;
+        }
+        let Some(artist) = self
+            .current_track
+            .as_ref()
+            .map(|track| track.artist.clone())
+            .filter(|artist| !artist.trim().is_empty())
+        else {
+            self.command_line
+                .set_message("Nothing is playing".to_string(), true);
+            return;
+        };
+        let client = self.artist_info.clone();
+        let name = artist.clone();
+        self.artist_info_task = Some(tokio::spawn(async move { client.fetch(&name).await }));
+        self.artist_panel = Some(ArtistPanel::new(artist));
+        self.needs_full_repaint = true;
+    }
+
+    pub fn close_artist_panel(&mut self) {
+        if let Some(task) = self.artist_info_task.take() {
+            task.abort();
+        }
+        if let Some(task) = self.artist_image_task.take() {
+            task.abort();
+        }
+        self.artist_panel = None;
+        self.needs_full_repaint = true;
+    }
+
+    // Re-reads the player's library in the background every so often, so the
+    // quick switcher searches a cached copy instead of asking the player
+    async fn poll_library(&mut self) {
+        let due = self
+            .next_library_refresh
+            .is_some_and(|next| Instant::now() >= next);
+        if due && !self.library_task.is_running() {
+            self.next_library_refresh = Some(Instant::now() + LIBRARY_REFRESH_INTERVAL);
+            let player = self.player.clone();
+            let previous = self.library.clone();
+            self.library_task
+                .start(async move { library::refresh(&*player, &previous).await });
+        }
+        let Some(result) = self.library_task.try_finish() else {
+            return;
+        };
+        match result {
+            Ok(Ok(index)) => {
+                self.library = index;
+                let path = amcli_core::cache::library_index_path();
+                if let Err(e) = self.library.save(&path).await {
+                    tracing::warn!("Couldn't save the library index: {}", e);
+                }
+                self.search_quick_switcher();
             }
-            f.render_widget(
-                Paragraph::new(lines).block(Block::default().padding(
-                    ratatui::widgets::Padding::new(SPACING_NORMAL, SPACING_NORMAL, 0, 0),
-                )),
-                col_layout[col],
-            );
+            Ok(Err(e)) => tracing::debug!("Library refresh failed: {}", e),
+            Err(e) => tracing::warn!("Library task panicked: {}", e),
         }
-    } else {
-        let mut lines = vec![Line::from("")];
-        if let Some(ref s_line) = status_line {
-            lines.push(s_line.clone());
-            lines.push(Line::from(vec![Span::raw(
-                "──────────────────────────────────────",
-            )
-            .fg(theme.dim)]));
+    }
+
+    // Checked in the background on every track change (AirPlay routing often
+    // changes along with what's playing) and every so often in between
+    fn poll_output_device(&mut self, track_changed: bool) {
+        if !self.config.ui.show_output_device {
+            return;
         }
-        let col_width = area.width.saturating_sub(6) as usize;
+        let due = track_changed
+            || self
+                .next_output_check
+                .is_none_or(|next| Instant::now() >= next);
+        if due && !self.output_device_task.is_running() {
+            self.next_output_check = Some(Instant::now() + OUTPUT_DEVICE_INTERVAL);
+            let player = self.player.clone();
+            self.output_device_task
+                .start(async move { player.get_output_device().await });
+        }
+        match self.output_device_task.try_finish() {
+            Some(Ok(Ok(name))) => self.output_device = Some(name),
+            Some(Ok(Err(e))) => {
+                tracing::debug!("Output device unavailable: {}", e);
+                self.output_device = None;
+            }
+            Some(Err(e)) => tracing::warn!("Output device task panicked: {}", e),
+            None => {}
+        }
+    }
 
-        for i in 0..items_count {
-            lines.push(Line::from(Span::styled(
-                labels[i],
-                Style::default()
-                    .fg(theme.dim)
-                    .add_modifier(Modifier::ITALIC),
-            )));
+    // Tracks played this session, newest first, listed ahead of the library
+    fn recent_tracks(&self) -> Vec<LibraryItem> {
+        let mut recent: Vec<LibraryItem> = Vec::new();
+        for entry in self.session_history.iter().rev() {
+            // The indexed copy when there is one, so it plays by ID
+            let track = &entry.track;
+            let item = self
+                .library
+                .items
+                .iter()
+                .find(|item| {
+                    item.kind == ItemKind::Track
+                        && item.name == track.name
+                        && item.artist == track.artist
+                        && item.album == track.album
+                })
+                .cloned()
+                .unwrap_or_else(|| LibraryItem::new(ItemKind::Track, &track.name, &track.artist));
+            if !recent.contains(&item) {
+                recent.push(item);
+            }
+        }
+        recent
+    }
 
-            let display_val = scroll_text(&values[i], col_width, animation_frame);
+    // The switcher narrowed to what's playing, to pick more of the same
+    fn jump_to_current(&mut self, album: bool) {
+        let scope = self.current_track.as_ref().and_then(|track| {
+            if album && !track.album.trim().is_empty() {
+                Some(Scope::Album {
+                    artist: track.artist.clone(),
+                    album: track.album.clone(),
+                })
+            } else if !album && !track.artist.trim().is_empty() {
+                Some(Scope::Artist(track.artist.clone()))
+            } else {
+                None
+            }
+        });
+        match scope {
+            Some(scope) => self.open_quick_switcher(Some(scope)),
+            None => self
+                .command_line
+                .set_message("Nothing is playing".to_string(), true),
+        }
+    }
 
-            lines.push(Line::from(Span::styled(
-                format!(" {} ", display_val),
-                Style::default()
-                    .bg(theme.dim)
-                    .fg(theme.bg)
-                    .add_modifier(Modifier::BOLD),
-            )));
+    fn open_quick_switcher(&mut self, scope: Option<Scope>) {
+        // Straight away when the cached copy is missing or stale
+        if self.next_library_refresh.is_none() {
+            let wait = LIBRARY_REFRESH_INTERVAL.saturating_sub(self.library.age());
+            self.next_library_refresh = Some(Instant::now() + wait);
         }
-        f.render_widget(
-            Paragraph::new(lines).block(Block::default().padding(ratatui::widgets::Padding::new(
-                SPACING_NORMAL,
-                SPACING_NORMAL,
-                0,
-                0,
-            ))),
-            area,
-        );
+        self.quick_switcher = Some(QuickSwitcher::new(scope));
+        self.search_quick_switcher();
     }
-}
 
-fn draw_controls(f: &mut Frame, area: Rect, theme: Theme, is_jp: bool) {
-    let controls = if is_jp {
-        vec![
-            ("▶ 再生", "SPC"),
-            ("▶▶ 次", "]"),
-            ("◀◀ 前", "["),
-            ("音量＋", "+"),
-            ("音量－", "-"),
-            ("テーマ", "t"),
-            ("電源", "q"),
-        ]
-    } else {
-        vec![
-            ("PLAY", "SPC"),
-            ("SKIP", "]"),
-            ("PREV", "["),
-            ("VOL+", "+"),
-            ("VOL-", "-"),
-            ("THEME", "t"),
-            ("EXIT", "q"),
-        ]
-    };
+    fn search_quick_switcher(&mut self) {
+        let recent = self.recent_tracks();
+        if let Some(switcher) = &mut self.quick_switcher {
+            let rest = self
+                .library
+                .items
+                .iter()
+                .filter(|item| !recent.contains(item));
+            switcher.search(recent.iter().chain(rest));
+        }
+    }
 
-    let btn_layout = Layout::horizontal(vec![Constraint::Fill(1); controls.len()]).split(area);
+    pub fn is_quick_switcher_open(&self) -> bool {
+        self.quick_switcher.is_some()
+    }
 
-    for (i, (label, key)) in controls.iter().enumerate() {
-        if i < btn_layout.len() {
-            let btn_text = Line::from(vec![
-                Span::styled(
-                    format!(" {}", label),
-                    Style::default()
-                        .fg(theme.primary)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(format!(" [{}] ", key), Style::default().fg(theme.dim)),
-            ]);
+    pub fn close_quick_switcher(&mut self) {
+        self.quick_switcher = None;
+        self.needs_full_repaint = true;
+    }
 
-            let mut btn_block = Block::default()
-                .borders(Borders::ALL)
-                .border_type(if theme.is_retro {
-                    BorderType::Thick
-                } else {
-                    BorderType::Plain
-                })
-                .border_style(Style::default().fg(theme.dim));
+    pub fn quick_switcher_push(&mut self, c: char) {
+        if let Some(switcher) = &mut self.quick_switcher {
+            switcher.query.push(c);
+        }
+        self.search_quick_switcher();
+    }
 
-            if theme.is_retro {
-                btn_block = btn_block.bg(Color::Rgb(10, 10, 10));
+    pub fn quick_switcher_backspace(&mut self) {
+        if let Some(switcher) = &mut self.quick_switcher {
+            switcher.query.pop();
+        }
+        self.search_quick_switcher();
+    }
+
+    pub fn quick_switcher_move(&mut self, delta: isize) {
+        if let Some(switcher) = &mut self.quick_switcher {
+            switcher.move_selection(delta);
+        }
+    }
+
+    pub async fn submit_quick_switcher(&mut self) -> Result<()> {
+        let item = self
+            .quick_switcher
+            .as_ref()
+            .and_then(|switcher| switcher.selected().cloned());
+        let Some(item) = item else {
+            return Ok(());
+        };
+        self.close_quick_switcher();
+        let found = match item.kind {
+            ItemKind::Playlist => {
+                self.player.play_playlist(&item.name).await?;
+                true
             }
+            ItemKind::Album => self.player.play_album(&item.artist, &item.name).await?,
+            ItemKind::Track => match &item.id {
+                Some(id) => self.player.play_track_id(id).await?,
+                None => {
+                    let query = format!("{} {}", item.name, item.artist);
+                    self.player.play_query(&query).await?
+                }
+            },
+        };
+        if !found {
+            self.command_line
+                .set_message(format!("No match for \"{}\"", item.name), true);
+        }
+        Ok(())
+    }
 
-            let btn = Paragraph::new(btn_text)
-                .alignment(Alignment::Center)
-                .block(btn_block);
+    fn open_favorites(&mut self) {
+        self.favorites_panel = Some(FavoritesPanel::default());
+    }
 
-            f.render_widget(btn, btn_layout[i]);
+    pub fn is_favorites_open(&self) -> bool {
+        self.favorites_panel.is_some()
+    }
+
+    pub fn close_favorites(&mut self) {
+        self.favorites_panel = None;
+        self.needs_full_repaint = true;
+    }
+
+    pub fn favorites_move(&mut self, delta: isize) {
+        let len = self.favorites.items().len();
+        if let Some(panel) = &mut self.favorites_panel {
+            panel.move_selection(delta, len);
         }
     }
-}
 
-// Orchestrator: computes layout, dispatches to section renderers
-pub fn draw(f: &mut Frame, app: &mut App) {
-    let area = f.area();
-    let theme = app.current_theme();
-    let is_jp = app.config.general.language == crate::config::Language::Japanese;
-    f.render_widget(Block::default().style(Style::default().bg(theme.bg)), area);
+    fn selected_favorite(&mut self) -> Option<usize> {
+        let len = self.favorites.items().len();
+        let panel = self.favorites_panel.as_mut()?;
+        (len > 0).then(|| panel.selected(len))
+    }
 
-    let chassis_inner = draw_chassis(f, area, theme, is_jp);
-    // Collapse order as height shrinks: controls first, then progress bar
-    let show_controls = chassis_inner.height >= 19;
-    let show_progress = chassis_inner.height >= 16;
-    let (display_area, tuner_area, control_area) = if show_controls {
-        let [d, t, c] = Layout::vertical([
-            Constraint::Fill(1),
-            Constraint::Length(3),
-            Constraint::Length(3),
-        ])
-        .areas(chassis_inner);
-        (d, Some(t), Some(c))
-    } else if show_progress {
-        let [d, t] =
-            Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(chassis_inner);
-        (d, Some(t), None)
-    } else {
-        (chassis_inner, None, None)
-    };
+    // By the player's ID when it has one, falling back to a library search
+    pub async fn play_selected_favorite(&mut self) -> Result<()> {
+        let Some(index) = self.selected_favorite() else {
+            return Ok(());
+        };
+        let favorite = self.favorites.items()[index].clone();
+        self.close_favorites();
+        let found = match favorite.track_id() {
+            Some(id) => self.player.play_track_id(id).await.unwrap_or(false),
+            None => false,
+        };
+        let found = found
+            || self
+                .player
+                .play_query(&format!("{} {}", favorite.name, favorite.artist))
+                .await?;
+        if !found {
+            self.command_line
+                .set_message(format!("No match for \"{}\"", favorite.name), true);
+        }
+        Ok(())
+    }
 
-    let screen_inner = draw_screen_border(f, display_area, theme);
-    let show_artwork = app.config.artwork.album && display_area.width > 50;
-    let info_chunk = if show_artwork {
-        let available = screen_inner.width;
-        let artwork_constraints = if available >= 20 + 30 + SPACING_NORMAL {
-            [Constraint::Fill(3), Constraint::Fill(4)]
-        } else {
-            [Constraint::Min(20), Constraint::Fill(1)]
+    pub async fn remove_selected_favorite(&mut self) -> Result<()> {
+        let Some(index) = self.selected_favorite() else {
+            return Ok(());
         };
-        let [artwork_col, info_col] = Layout::horizontal(artwork_constraints)
-            .spacing(SPACING_NORMAL)
-            .areas(screen_inner);
-        draw_artwork(
-            f,
-            artwork_col,
-            app.artwork_protocol.as_mut(),
-            app.is_loading_artwork,
-            &mut app.throbber_state,
-            theme,
-            is_jp,
-        );
-        info_col
-    } else {
-        screen_inner
-    };
-    let has_lyrics = app.current_lyrics.is_some();
-    let info_height = info_chunk.height as usize;
-    let metadata_width = info_chunk.width;
-    let is_two_columns = show_artwork
-        && (metadata_width > 80 || (has_lyrics && info_height <= 14))
-        && metadata_width >= 40;
-    let meta_height = if is_two_columns { 5 } else { 8 };
-    let (metadata_area, lyrics_area) = if !show_artwork && has_lyrics {
-        let [meta, lyrics] = Layout::horizontal([Constraint::Fill(2), Constraint::Fill(3)])
-            .spacing(SPACING_NORMAL)
-            .areas(info_chunk);
-        (meta, lyrics)
-    } else if has_lyrics && info_height > meta_height + 2 {
-        let [meta, lyrics] =
-            Layout::vertical([Constraint::Length(meta_height as u16), Constraint::Fill(1)])
-                .spacing(SPACING_NORMAL)
-                .areas(info_chunk);
-        (meta, lyrics)
-    } else {
-        (info_chunk, Rect::default())
-    };
+        self.favorites.remove(index);
+        self.favorites.save().await
+    }
 
-    if let Some(track) = app.current_track.as_ref() {
-        draw_metadata(
-            f,
-            metadata_area,
-            track,
-            app.animation_frame,
-            is_two_columns,
-            theme,
-            is_jp,
-        );
-    } else {
-        draw_idle(f, info_chunk, theme, is_jp);
+    // `browse_roots`, else the folder the internal player was started on
+    fn browse_roots(&self) -> Vec<PathBuf> {
+        let player = &self.config.player;
+        let roots: Vec<PathBuf> = player
+            .browse_roots
+            .iter()
+            .map(|root| amcli_core::playlist::expand_home(&root.to_string_lossy()))
+            .collect();
+        if !roots.is_empty() {
+            return roots;
+        }
+        player
+            .music_dir
+            .as_deref()
+            .map(|dir| amcli_core::playlist::expand_home(&dir.to_string_lossy()))
+            .or_else(dirs::audio_dir)
+            .into_iter()
+            .collect()
     }
-    if lyrics_area.height > 2 {
-        if let Some(track) = app.current_track.as_ref() {
-            draw_lyrics(
-                f,
-                lyrics_area,
-                track,
-                app.current_lyrics.as_ref(),
-                app.lyrics_unreachable,
-                theme,
-                is_jp,
-                app.animation_frame,
+
+    async fn open_file_browser(&mut self) {
+        if self.config.player.backend != amcli_core::config::PlayerBackend::Internal {
+            self.command_line.set_message(
+                "The file browser needs the internal player".to_string(),
+                true,
             );
+            return;
         }
-    }
-    if let Some(tuner_area) = tuner_area {
-        if let Some(track) = app.get_current_track() {
-            draw_progress(f, tuner_area, track, theme);
+        match FileBrowser::open(self.browse_roots()).await {
+            Ok(browser) => {
+                self.file_browser = Some(browser);
+                self.refresh_file_preview().await;
+            }
+            Err(e) => self.command_line.set_message(e.to_string(), true),
         }
     }
-    if let Some(control_area) = control_area {
-        draw_controls(f, control_area, theme, is_jp);
-    }
 
-    // LAST: Settings overlay (z-order contract -- Ratatui has no z-index)
-    if app.settings_menu.is_open {
-        app.settings_menu.render(f, theme);
+    pub fn is_file_browser_open(&self) -> bool {
+        self.file_browser.is_some()
     }
-}
 
-fn format_duration_seconds(duration: Duration) -> String {
-    let total_seconds = duration.as_secs();
-    format!("{}s", total_seconds)
-}
+    pub fn close_file_browser(&mut self) {
+        self.file_browser = None;
+        self.needs_full_repaint = true;
+    }
 
-fn format_duration(duration: Duration) -> String {
-    let total_seconds = duration.as_secs();
-    let minutes = total_seconds / 60;
-    let seconds = total_seconds % 60;
-    format!("{:02}:{:02}", minutes, seconds)
-}
+    fn open_stations(&mut self) {
+        self.station_picker = Some(StationPicker::default());
+    }
 
-// Marquee scroll measured by display width (columns), so full-width CJK glyphs
-// trigger scrolling and fill the window correctly instead of overflowing.
-fn scroll_text<'a>(text: &'a str, width: usize, frame: u32) -> Cow<'a, str> {
-    if UnicodeWidthStr::width(text) <= width {
-        return Cow::Borrowed(text);
+    pub fn is_stations_open(&self) -> bool {
+        self.station_picker.is_some()
     }
 
-    let gap_len = 3;
-    // Offset advances one character per frame; wrap over text + gap.
-    let total_len = text.chars().count() + gap_len;
-    let offset = (frame as usize) % total_len;
+    pub fn close_stations(&mut self) {
+        self.station_picker = None;
+        self.needs_full_repaint = true;
+    }
 
-    let mut result = String::new();
-    let mut used = 0usize;
-    for ch in text
-        .chars()
-        .chain(std::iter::repeat_n(' ', gap_len))
-        .cycle()
-        .skip(offset)
-    {
-        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
-        if used + ch_width > width {
-            // A wide glyph won't fit the last column -- pad with a space to keep
-            // the rendered width stable rather than letting it overflow.
-            if used < width {
-                result.push(' ');
-            }
-            break;
+    pub fn stations_move(&mut self, delta: isize) {
+        let len = self.config.stations.len();
+        if let Some(picker) = &mut self.station_picker {
+            picker.move_selection(delta, len);
         }
-        result.push(ch);
-        used += ch_width;
     }
 
-    Cow::Owned(result)
-}
+    fn selected_station(&mut self) -> Option<usize> {
+        let len = self.config.stations.len();
+        let picker = self.station_picker.as_mut()?;
+        (len > 0).then(|| picker.selected(len))
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::player::{MediaPlayer, PlaybackState, RepeatMode, Track};
-    use async_trait::async_trait;
-    use image::{Rgba, RgbaImage};
-    use ratatui::backend::TestBackend;
-    use ratatui::Terminal;
+    // Streams have no length, and file the announced song under the station
+    fn on_air(&self) -> Option<&str> {
+        self.current_track
+            .as_ref()
+            .filter(|t| t.duration.is_zero())
+            .map(|t| t.album.as_str())
+    }
 
-    struct MockPlayer {
-        volume: u8,
-        artwork_url: Option<String>,
-        track: Track,
+    pub async fn play_selected_station(&mut self) {
+        let Some(index) = self.selected_station() else {
+            return;
+        };
+        let station = self.config.stations[index].clone();
+        self.close_stations();
+        self.play_station(&station).await;
     }
 
-    #[async_trait]
-    impl MediaPlayer for MockPlayer {
-        async fn play(&self) -> Result<()> {
-            Ok(())
-        }
-        async fn pause(&self) -> Result<()> {
-            Ok(())
-        }
-        async fn toggle(&self) -> Result<()> {
-            Ok(())
+    async fn play_station(&mut self, station: &Station) {
+        match self.player.play_stream(&station.url, &station.name).await {
+            Ok(()) => self
+                .command_line
+                .set_message(format!("Tuned in to {}", station.name), false),
+            Err(e) => self
+                .command_line
+                .set_message(format!("Couldn't play {}: {}", station.name, e), true),
         }
-        async fn next(&self) -> Result<()> {
-            Ok(())
-        }
-        async fn previous(&self) -> Result<()> {
-            Ok(())
+    }
+
+    pub async fn remove_selected_station(&mut self) -> Result<()> {
+        let Some(index) = self.selected_station() else {
+            return Ok(());
+        };
+        self.config.stations.remove(index);
+        self.config.save().await
+    }
+
+    // Tags for the selected file, read as it's selected
+    async fn refresh_file_preview(&mut self) {
+        let Some(path) = self
+            .file_browser
+            .as_ref()
+            .and_then(FileBrowser::needs_preview)
+            .map(Path::to_path_buf)
+        else {
+            return;
+        };
+        match self.player.describe_file(&path).await {
+            Ok(track) => {
+                if let Some(browser) = &mut self.file_browser {
+                    browser.set_preview(path, track);
+                }
+            }
+            Err(e) => tracing::debug!("No preview for {}: {}", path.display(), e),
         }
-        async fn stop(&self) -> Result<()> {
-            Ok(())
+    }
+
+    pub async fn file_browser_move(&mut self, delta: isize) {
+        if let Some(browser) = &mut self.file_browser {
+            browser.move_selection(delta);
         }
-        async fn get_current_track(&self) -> Result<Option<Track>> {
-            Ok(Some(self.track.clone()))
+        self.refresh_file_preview().await;
+    }
+
+    pub async fn file_browser_up(&mut self) {
+        let Some(browser) = &mut self.file_browser else {
+            return;
+        };
+        if let Err(e) = browser.up().await {
+            self.command_line.set_message(e.to_string(), true);
         }
-        async fn get_playback_state(&self) -> Result<PlaybackState> {
-            Ok(PlaybackState::Playing)
+        self.refresh_file_preview().await;
+    }
+
+    // Opens the selected folder, or plays the selected file
+    pub async fn file_browser_open(&mut self) {
+        let Some(browser) = &mut self.file_browser else {
+            return;
+        };
+        match browser.enter().await {
+            Ok(true) => self.refresh_file_preview().await,
+            Ok(false) => self.file_browser_play().await,
+            Err(e) => self.command_line.set_message(e.to_string(), true),
         }
-        async fn get_player_status(&self) -> Result<crate::player::PlayerStatus> {
-            Ok(crate::player::PlayerStatus {
-                track: Some(self.track.clone()),
-                volume: Some(self.volume),
-                state: PlaybackState::Playing,
-            })
+    }
+
+    // The selected file, or everything in the selected folder, replacing the
+    // queue
+    pub async fn file_browser_play(&mut self) {
+        let Some(entry) = self.file_browser.as_ref().and_then(FileBrowser::selected) else {
+            return;
+        };
+        let path = entry.path.clone();
+        match self.player.play_files(std::slice::from_ref(&path)).await {
+            Ok(count) => {
+                self.command_line
+                    .set_message(format!("Playing {}", tracks_label(count)), false);
+                self.refresh_queue().await;
+            }
+            Err(e) => self
+                .command_line
+                .set_message(format!("Couldn't play {}: {}", path.display(), e), true),
         }
-        async fn set_volume(&self, _volume: u8) -> Result<()> {
-            Ok(())
+    }
+
+    pub async fn file_browser_enqueue(&mut self) {
+        let Some(entry) = self.file_browser.as_ref().and_then(FileBrowser::selected) else {
+            return;
+        };
+        let path = entry.path.clone();
+        match self.player.enqueue_files(std::slice::from_ref(&path)).await {
+            Ok(count) => {
+                self.command_line
+                    .set_message(format!("Added {} to the queue", tracks_label(count)), false);
+                self.refresh_queue().await;
+            }
+            Err(e) => self
+                .command_line
+                .set_message(format!("Couldn't queue {}: {}", path.display(), e), true),
         }
-        async fn get_volume(&self) -> Result<u8> {
-            Ok(self.volume)
+    }
+
+    pub fn artist_panel_scroll(&mut self, delta: i16) {
+        if let Some(panel) = &mut self.artist_panel {
+            panel.scroll(delta);
         }
-        async fn seek(&self, _seconds: i32) -> Result<()> {
-            Ok(())
+    }
+
+    pub fn is_theme_editor_open(&self) -> bool {
+        self.theme_editor.is_some()
+    }
+
+    // Dropping the draft puts the saved theme back
+    pub fn close_theme_editor(&mut self) {
+        self.theme_editor = None;
+        self.needs_full_repaint = true;
+    }
+
+    pub fn theme_editor_navigate(&mut self, delta: isize) {
+        if let Some(editor) = &mut self.theme_editor {
+            editor.navigate(delta);
         }
-        async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
-            Ok(())
+    }
+
+    pub fn theme_editor_adjust(&mut self, delta: i16) {
+        if let Some(editor) = &mut self.theme_editor {
+            editor.adjust(delta);
         }
-        async fn set_repeat(&self, _mode: RepeatMode) -> Result<()> {
-            Ok(())
+    }
+
+    pub fn open_save_theme_prompt(&mut self) {
+        self.command_line.open_with("save-theme ");
+    }
+
+    // Saves the editor's draft (or the active theme) under `name`, replacing a
+    // custom theme of the same name, and switches to it
+    async fn save_custom_theme(&mut self, name: &str) -> Result<()> {
+        let name = name.trim();
+        if THEMES.iter().any(|t| t.name.eq_ignore_ascii_case(name)) {
+            return Err(anyhow!("{} is a built-in theme", name.to_uppercase()));
         }
-        async fn get_artwork_url(&self, _track: &Track) -> Result<Option<String>> {
-            Ok(self.artwork_url.clone())
+        let custom = self.base_theme().to_custom(name);
+        let custom_themes = &mut self.config.ui.custom_themes;
+        match custom_themes
+            .iter_mut()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+        {
+            Some(existing) => *existing = custom,
+            None => custom_themes.push(custom),
         }
+        self.command_line
+            .set_custom_themes(custom_themes.iter().map(|t| t.name.clone()).collect());
+        self.themes = theme_list(custom_themes);
+        self.theme_editor = None;
+        let index = command::find_theme(&self.themes, name).unwrap_or(0);
+        self.apply_theme(index);
+        self.config.ui.color_theme = name.to_lowercase();
+        self.command_line
+            .set_message(format!("Saved theme {}", name), false);
+        self.config.save().await
     }
 
-    fn test_track(name: &str) -> Track {
-        Track {
-            name: name.into(),
-            artist: "Test Artist".into(),
-            album: "Test Album".into(),
-            duration: Duration::from_secs(300),
-            position: Duration::from_secs(150),
-        }
+    pub fn settings_navigate_up(&mut self) {
+        self.settings_menu.navigate_up();
     }
 
-    fn mock_player(volume: u8) -> Box<dyn MediaPlayer> {
-        Box::new(MockPlayer {
-            volume,
-            artwork_url: Some("http://example.com/artwork.jpg".into()),
-            track: test_track("Test Song"),
-        })
+    pub fn settings_navigate_down(&mut self) {
+        self.settings_menu.navigate_down();
+    }
+
+    pub fn settings_switch_tab(&mut self, delta: i32) {
+        self.settings_menu.switch_tab(delta);
+    }
+
+    pub async fn settings_select(&mut self) -> Result<()> {
+        use crate::ui::settings::SettingsItem;
+
+        if let Some(item) = self.settings_menu.get_selected_item() {
+            match item {
+                SettingsItem::Language { current } => {
+                    self.set_language(current.toggle()).await?;
+                }
+                SettingsItem::Profile { .. } => {
+                    self.settings_adjust(1).await?;
+                }
+                SettingsItem::Theme {
+                    current_index,
+                    total_themes,
+                } => {
+                    let new_index = (current_index + 1) % total_themes;
+                    self.apply_theme(new_index);
+                    self.config.ui.color_theme = self.themes[new_index].name.to_lowercase();
+                    self.config.save().await?;
+                }
+                SettingsItem::Album { enabled } => {
+                    let new_enabled = !enabled;
+                    self.config.artwork.album = new_enabled;
+                    self.refresh_settings_menu();
+                    self.current_artwork_url = None;
+                    self.artwork_protocol = None;
+                    self.needs_full_repaint = true;
+                    self.config.save().await?;
+                }
+                SettingsItem::Mosaic { enabled } => {
+                    let new_enabled = !enabled;
+                    self.config.artwork.mosaic = new_enabled;
+                    self.refresh_settings_menu();
+                    self.restyle_artwork();
+                    self.config.save().await?;
+                }
+                SettingsItem::MosaicTileSize { .. }
+                | SettingsItem::MosaicGap { .. }
+                | SettingsItem::MosaicShape { .. } => {
+                    self.settings_adjust(1).await?;
+                }
+                SettingsItem::Backend { current } => {
+                    self.switch_backend(current.next()).await?;
+                }
+                SettingsItem::Crossfade { .. } => {
+                    self.update_playback_options(|o| o.crossfade = !o.crossfade)
+                        .await;
+                }
+                SettingsItem::SoundCheck { .. } => {
+                    self.update_playback_options(|o| o.sound_check = !o.sound_check)
+                        .await;
+                }
+                SettingsItem::Autoplay { enabled } => {
+                    self.set_autoplay(!enabled.unwrap_or_default()).await;
+                }
+                SettingsItem::CrossfadeDuration { .. } | SettingsItem::ShowNextStyle { .. } => {
+                    self.settings_adjust(1).await?;
+                }
+                SettingsItem::ShowNext { enabled } => {
+                    self.config.lyrics.show_next.enabled = !enabled;
+                    self.refresh_settings_menu();
+                    self.config.save().await?;
+                }
+                SettingsItem::Offline { enabled } => {
+                    self.set_offline(!enabled).await?;
+                }
+                SettingsItem::EditTheme => {
+                    self.settings_menu.close();
+                    self.theme_editor = Some(ThemeEditor::new(self.base_theme()));
+                }
+                SettingsItem::Close => {
+                    self.settings_menu.close();
+                    self.needs_full_repaint = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Left/right steps a setting with a range of values; on any other
+    // setting it moves between tabs
+    pub async fn settings_adjust(&mut self, delta: i32) -> Result<()> {
+        use crate::ui::settings::SettingsItem;
+
+        let Some(item) = self.settings_menu.get_selected_item().cloned() else {
+            return Ok(());
+        };
+        let artwork = &mut self.config.artwork;
+        match item {
+            SettingsItem::Theme {
+                current_index,
+                total_themes,
+            } => {
+                let new_index =
+                    (current_index as i32 + delta).rem_euclid(total_themes as i32) as usize;
+                self.apply_theme(new_index);
+                self.config.ui.color_theme = self.themes[new_index].name.to_lowercase();
+                return self.config.save().await;
+            }
+            SettingsItem::MosaicTileSize { size } => {
+                artwork.mosaic_tile_size = step(size, delta, MOSAIC_TILE_SIZES);
+                artwork.mosaic_gap = artwork.mosaic_gap.min(artwork.mosaic_tile_size / 2);
+            }
+            SettingsItem::MosaicGap { gap } => {
+                artwork.mosaic_gap = step(gap, delta, 0..=artwork.mosaic_tile_size / 2);
+            }
+            SettingsItem::MosaicShape { shape } => {
+                artwork.mosaic_shape = shape.toggle();
+            }
+            SettingsItem::CrossfadeDuration { .. } => {
+                self.update_playback_options(|o| {
+                    o.crossfade_secs = step(o.crossfade_secs.into(), delta, CROSSFADE_SECS) as u8
+                })
+                .await;
+                return Ok(());
+            }
+            SettingsItem::Profile { current, profiles } => {
+                // The default profile first, then the named ones
+                let choices: Vec<Option<String>> = std::iter::once(None)
+                    .chain(profiles.into_iter().map(Some))
+                    .collect();
+                let index = choices.iter().position(|p| *p == current).unwrap_or(0);
+                let next = (index as i32 + delta).rem_euclid(choices.len() as i32) as usize;
+                self.switch_profile(choices[next].clone());
+                return Ok(());
+            }
+            SettingsItem::ShowNextStyle { style } => {
+                self.config.lyrics.show_next.style = match style {
+                    ShowNextStyle::Highlight => ShowNextStyle::Slot,
+                    ShowNextStyle::Slot => ShowNextStyle::Highlight,
+                };
+                self.refresh_settings_menu();
+                return self.config.save().await;
+            }
+            _ => {
+                self.settings_switch_tab(delta);
+                return Ok(());
+            }
+        }
+
+        self.refresh_settings_menu();
+        self.restyle_artwork();
+        self.config.save().await
+    }
+
+    // Applies a change to the player's crossfade or Sound Check; failures
+    // (such as missing Accessibility access) go to the command line
+    async fn update_playback_options(&mut self, change: impl FnOnce(&mut PlaybackOptions)) {
+        let Some(mut options) = self.playback_options else {
+            return;
+        };
+        change(&mut options);
+        match self.player.set_playback_options(options).await {
+            Ok(()) => {
+                self.playback_options = Some(options);
+                self.refresh_settings_menu();
+            }
+            Err(e) => self
+                .command_line
+                .set_message(format!("Couldn't change playback settings: {}", e), true),
+        }
+    }
+
+    async fn set_autoplay(&mut self, enabled: bool) {
+        match self.player.set_autoplay(enabled).await {
+            Ok(()) => {
+                self.autoplay = Some(enabled);
+                self.refresh_settings_menu();
+            }
+            Err(e) => self
+                .command_line
+                .set_message(format!("Couldn't change Autoplay: {}", e), true),
+        }
+    }
+
+    // Sound Check goes through the player's playback options, read first if
+    // settings haven't been opened yet; players applying ReplayGain switch it
+    // directly
+    async fn toggle_normalization(&mut self) {
+        match self.normalization.map(|n| n.kind) {
+            Some(NormalizationKind::SoundCheck) => {
+                if self.playback_options.is_none() {
+                    match self.player.get_playback_options().await {
+                        Ok(options) => self.playback_options = Some(options),
+                        Err(e) => {
+                            self.command_line.set_message(e.to_string(), true);
+                            return;
+                        }
+                    }
+                }
+                self.update_playback_options(|o| o.sound_check = !o.sound_check)
+                    .await;
+                if let (Some(options), Some(normalization)) =
+                    (self.playback_options, self.normalization.as_mut())
+                {
+                    normalization.enabled = Some(options.sound_check);
+                }
+            }
+            Some(NormalizationKind::ReplayGain) => {
+                let enabled = !self.normalization.and_then(|n| n.enabled).unwrap_or(false);
+                match self.player.set_normalization(enabled).await {
+                    Ok(()) => {
+                        if let Some(normalization) = self.normalization.as_mut() {
+                            normalization.enabled = Some(enabled);
+                        }
+                    }
+                    Err(e) => self.command_line.set_message(e.to_string(), true),
+                }
+            }
+            None => self.command_line.set_message(
+                "This player doesn't report loudness normalization".to_string(),
+                true,
+            ),
+        }
+    }
+
+    // "SOUND CHECK ON -4.1 dB" for the format-info line. Whether Sound Check
+    // is on comes from the settings menu when the player can't say cheaply.
+    fn normalization_label(&self) -> Option<String> {
+        let normalization = self.normalization?;
+        let (name, from_options) = match normalization.kind {
+            NormalizationKind::SoundCheck => {
+                ("SOUND CHECK", self.playback_options.map(|o| o.sound_check))
+            }
+            NormalizationKind::ReplayGain => ("REPLAYGAIN", None),
+        };
+        let mut label = name.to_string();
+        match normalization.enabled.or(from_options) {
+            Some(true) => label.push_str(" ON"),
+            Some(false) => label.push_str(" OFF"),
+            None => {}
+        }
+        if let Some(gain) = normalization.gain_db {
+            label.push_str(&format!(" {:+.1} dB", gain));
+        }
+        Some(label)
+    }
+
+    // The last reported position, advanced by the time since while playing
+    fn interpolated_position(&self) -> Option<Duration> {
+        let track = self.current_track.as_ref()?;
+        if self.playback_state != PlaybackState::Playing {
+            return Some(track.position);
+        }
+        let elapsed = self
+            .position_sampled_at
+            .elapsed()
+            .mul_f32(self.playback_rate.unwrap_or(1.0));
+        let position = track.position + elapsed;
+        Some(if track.duration.is_zero() {
+            position
+        } else {
+            position.min(track.duration)
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn is_showing_help(&self) -> bool {
+        self.show_help
+    }
+
+    pub fn get_current_track(&self) -> Option<&Track> {
+        self.current_track.as_ref()
+    }
+
+    #[allow(dead_code)]
+    pub fn get_volume(&self) -> u8 {
+        self.volume
+    }
+
+    #[allow(dead_code)]
+    pub fn is_muted(&self) -> bool {
+        self.is_muted
+    }
+
+    #[allow(dead_code)]
+    pub fn get_repeat_mode(&self) -> RepeatMode {
+        self.current_repeat_mode
+    }
+
+    pub async fn update(&mut self) -> Result<()> {
+        self.dirty = true;
+        let jumped_from = self.jumped_from.take();
+        let status = self.player.get_player_status().await;
+
+        let (new_track, new_volume, new_state, new_repeat) = match status {
+            Ok(s) => {
+                tracing::debug!(
+                    "[UPDATE] status OK: track={}, vol={:?}",
+                    s.track.as_ref().map(|t| t.name.as_str()).unwrap_or("None"),
+                    s.volume
+                );
+                (s.track, s.volume, s.state, s.repeat)
+            }
+            Err(e) => {
+                tracing::warn!("[UPDATE] get_player_status FAILED: {}", e);
+                (None, None, PlaybackState::Stopped, None)
+            }
+        };
+        self.playback_state = new_state;
+        self.position_sampled_at = Instant::now();
+
+        self.volume = new_volume.unwrap_or(self.volume);
+        self.current_repeat_mode = new_repeat.unwrap_or(self.current_repeat_mode);
+
+        let track_changed = track_identity_changed(self.current_track.as_ref(), new_track.as_ref());
+        if track_changed {
+            self.track_key = match &new_track {
+                Some(track) => {
+                    let id = self.player.get_track_id(track).await.ok().flatten();
+                    Some(bookmarks::track_key(id.as_deref(), track))
+                }
+                None => None,
+            };
+            // Brought up as the track starts, so it's read while listening
+            if let Some(note) = self
+                .track_key
+                .as_deref()
+                .and_then(|key| self.notes.get(key))
+            {
+                self.command_line.set_message(format!("✎ {}", note), false);
+            }
+        }
+
+        let artwork_url = match &new_track {
+            Some(track) => self.artwork_url_for(track).await,
+            None => None,
+        };
+
+        self.events.publish(AppEvent::Status {
+            state: new_state,
+            track: new_track.clone(),
+            artwork_url: artwork_url.clone(),
+        });
+
+        tracing::debug!(
+            "[UPDATE] track_changed={}, has_lyrics={}, artwork_changed={}",
+            track_changed,
+            self.current_lyrics.is_some(),
+            artwork_url != self.current_artwork_url
+        );
+
+        if track_changed {
+            self.clear_artwork_for_track_transition(new_track.is_some() && artwork_url.is_some());
+            self.current_lyrics = None;
+            self.lyrics_unreachable = false;
+            self.lyrics_scroll = 0;
+            self.lyrics_search = None;
+            self.lyrics_task.cancel();
+
+            // Podcasts keeps its own speed per show, and only reports it while playing
+            if let Ok(rate) = self.player.get_rate().await {
+                self.playback_rate = Some(rate);
+            }
+            if let Ok(balance) = self.player.get_balance().await {
+                self.balance = Some(balance);
+            }
+            self.normalization = match &new_track {
+                Some(track) => self.player.get_normalization(track).await.ok(),
+                None => None,
+            };
+            if let Some(ref track) = new_track {
+                self.events.publish(AppEvent::NewPlay(track.clone()));
+                self.fetch_lyrics(track.clone());
+            }
+            if self.show_queue {
+                self.refresh_queue().await;
+            }
+        } else if track_restarted(
+            self.current_track
+                .clone()
+                .map(|track| Track {
+                    position: jumped_from.unwrap_or(track.position),
+                    ..track
+                })
+                .as_ref(),
+            new_track.as_ref(),
+        ) {
+            self.record_restart(new_track.clone());
+        }
+
+        if let Some(result) = self.lyrics_task.try_finish() {
+            match result {
+                Ok(Ok(Some(lyrics))) => {
+                    self.current_lyrics = Some(lyrics);
+                    self.lyrics_unreachable = false;
+                }
+                Ok(Ok(None)) => self.lyrics_unreachable = false,
+                Ok(Err(e)) => {
+                    tracing::debug!("Lyrics fetch failed: {}", e);
+                    self.lyrics_unreachable = true;
+                }
+                Err(e) => tracing::warn!("Lyrics task panicked: {}", e),
+            }
+        }
+
+        self.poll_artist_panel().await;
+        self.poll_update_check().await;
+        self.poll_library().await;
+        self.poll_output_device(track_changed);
+
+        self.current_track = new_track;
+
+        if let Some(ref track) = self.current_track {
+            let mut cache = self.metadata_cache.take().unwrap_or_default();
+            if track_changed {
+                cache.name = track.name.to_uppercase();
+                cache.artist = track.artist.to_uppercase();
+                cache.album = track.album.to_uppercase();
+            }
+            let progress_percent = if track.duration.as_secs() > 0 {
+                ((track.position.as_secs_f64() / track.duration.as_secs_f64()) * 100.0) as u16
+            } else {
+                0
+            };
+            cache.duration_str = format!(
+                "{} / {}",
+                format_duration(track.position),
+                format_duration(track.duration)
+            );
+            cache.gauge_label = format!(
+                " {}/{} | {:02}% ",
+                format_duration_seconds(track.position),
+                format_duration_seconds(track.duration),
+                progress_percent
+            );
+            cache.progress_percent = progress_percent;
+            self.metadata_cache = Some(cache);
+        } else {
+            self.metadata_cache = None;
+        }
+
+        self.apply_theme_schedule().await;
+        while let Some(alarm) = self.alarm_clock.as_mut().and_then(AlarmClock::try_next) {
+            self.run_alarm(alarm).await;
+        }
+        if let Some(phase) = self
+            .focus_timer
+            .as_mut()
+            .and_then(|timer| timer.advance(Instant::now()))
+        {
+            self.run_focus_phase(phase).await;
+        }
+        self.clock_text = clock::format_clock(
+            chrono::Local::now().naive_local(),
+            self.config.ui.clock,
+            self.config.general.language == amcli_core::config::Language::Japanese,
+        );
+
+        if self.is_focused {
+            // The spinner only shows while artwork loads
+            if self.is_loading_artwork {
+                self.throbber_state.calc_next();
+            }
+            // Marquees hold still while paused
+            if self.playback_state == PlaybackState::Playing {
+                self.animation_frame = self.animation_frame.wrapping_add(1);
+            }
+        }
+        if artwork_url != self.current_artwork_url {
+            self.current_artwork_url = artwork_url.clone();
+            if let Some(url) = artwork_url {
+                if let Some(track) = self
+                    .current_track
+                    .as_ref()
+                    .filter(|_| self.config.ui.idle_grid)
+                {
+                    if self.recent_albums.record(track, &url) {
+                        if let Err(e) = self.recent_albums.save().await {
+                            tracing::warn!("Couldn't save recently played albums: {}", e);
+                        }
+                    }
+                }
+                self.is_loading_artwork = true;
+                self.artwork_protocol = None;
+                self.needs_full_repaint = true;
+                let manager = self.artwork_manager.clone();
+                let style = self.artwork_style();
+                let resolution = self.artwork_converter.resolution();
+
+                self.stop_artwork_tasks();
+                if self.animates_artwork() {
+                    let manager = manager.clone();
+                    let url = url.clone();
+                    let style = style.clone();
+                    self.artwork_animation_task.start(async move {
+                        manager.get_animated_artwork_themed(&url, &style).await
+                    });
+                }
+
+                self.artwork_task.start(async move {
+                    manager
+                        .get_artwork_themed_v2(&url, &style, resolution)
+                        .await
+                });
+            } else {
+                self.artwork_protocol = None;
+                self.is_loading_artwork = false;
+                self.stop_artwork_tasks();
+            }
+        }
+
+        if let Some(result) = self.artwork_task.try_finish() {
+            match result {
+                // An animation that beat the still keeps playing
+                Ok(Ok(_)) if !self.artwork_frames.is_empty() => {}
+                Ok(Ok(img)) => {
+                    self.artwork_protocol = Some(self.artwork_converter.create_protocol(img));
+                    self.needs_full_repaint = true;
+                }
+                Ok(Err(e)) => {
+                    tracing::debug!("Artwork load failed: {}", e);
+                    self.current_artwork_url = None;
+                    self.artwork_protocol = None;
+                    self.needs_full_repaint = true;
+                }
+                Err(e) => {
+                    tracing::warn!("Artwork task panicked: {}", e);
+                    self.current_artwork_url = None;
+                    self.artwork_protocol = None;
+                    self.needs_full_repaint = true;
+                }
+            }
+            self.is_loading_artwork = false;
+        }
+
+        if let Some(result) = self.artwork_animation_task.try_finish() {
+            match result {
+                Ok(Ok(Some(frames))) => self.start_artwork_animation(frames),
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => tracing::debug!("Animated artwork load failed: {}", e),
+                Err(e) => tracing::warn!("Animated artwork task panicked: {}", e),
+            }
+        }
+        self.update_idle_grid();
+        Ok(())
+    }
+
+    fn animates_artwork(&self) -> bool {
+        self.config.artwork.animated
+            && !self.config.ui.reduced_motion
+            && self.artwork_converter.supports_animation()
+    }
+
+    fn stop_artwork_tasks(&mut self) {
+        self.artwork_task.cancel();
+        self.artwork_animation_task.cancel();
+        self.artwork_frames.clear();
+    }
+
+    fn start_artwork_animation(&mut self, frames: Vec<AnimationFrame>) {
+        let mut frames = frames.into_iter().map(|frame| {
+            (
+                self.artwork_converter.create_protocol(frame.image),
+                frame.delay,
+            )
+        });
+        let Some((first, delay)) = frames.next() else {
+            return;
+        };
+        self.artwork_frames = frames.collect();
+        self.artwork_protocol = Some(first);
+        self.artwork_frame_delay = delay;
+        self.artwork_frame_shown_at = Instant::now();
+        self.is_loading_artwork = false;
+        self.needs_full_repaint = true;
+    }
+
+    // Called every loop iteration; swaps in the next frame once the current
+    // one has been up for its delay.
+    pub fn advance_artwork_animation(&mut self) {
+        if self.artwork_protocol.is_none() {
+            self.artwork_frames.clear();
+            return;
+        }
+        if self.artwork_frames.is_empty()
+            || self.artwork_frame_shown_at.elapsed() < self.artwork_frame_delay
+        {
+            return;
+        }
+        if let (Some(current), Some((next, delay))) = (
+            self.artwork_protocol.as_mut(),
+            self.artwork_frames.pop_front(),
+        ) {
+            let shown = std::mem::replace(current, next);
+            self.artwork_frames
+                .push_back((shown, self.artwork_frame_delay));
+            self.artwork_frame_delay = delay;
+            self.artwork_frame_shown_at = Instant::now();
+            self.dirty = true;
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    // Whether the next frame could differ from the last one drawn. The boot
+    // splash always animates; otherwise the position (ticker, progress bar,
+    // time, lyric line), the marquee, and a pending key chord change on screen
+    // between polls.
+    pub fn needs_redraw(&mut self) -> bool {
+        let key = self.frame_key();
+        let changed = self.last_frame_key.as_ref() != Some(&key);
+        self.last_frame_key = Some(key);
+        std::mem::take(&mut self.dirty) || changed || self.boot_splash.is_some()
+    }
+
+    fn frame_key(&self) -> FrameKey {
+        let theme = self.current_theme();
+        let ticker = (theme.ticker && self.config.ui.ticker && !self.config.ui.reduced_motion)
+            .then(|| self.interpolated_position())
+            .flatten()
+            .map(|position| position.as_millis() / 100);
+        // The time label's seconds and the bar's percent
+        let progress = self.current_track.as_ref().and_then(|track| {
+            let position = self.interpolated_position()?;
+            Some((
+                position.as_secs(),
+                progress_percent(position, track.duration),
+            ))
+        });
+        let lyric = self
+            .current_lyrics
+            .as_ref()
+            .map(|_| self.current_lyrics_index());
+        let marquee = (!self.config.ui.reduced_motion).then_some(self.animation_frame);
+        (
+            ticker,
+            progress,
+            lyric,
+            marquee,
+            self.keymap.pending_label(),
+            self.volume_overlay().is_some(),
+        )
+    }
+}
+
+// Ticker tenths, the progress seconds and percent, the lyric line being sung,
+// the marquee frame, the pending chord label, and whether the volume overlay
+// is up
+type FrameKey = (
+    Option<u128>,
+    Option<(u64, u16)>,
+    Option<usize>,
+    Option<u32>,
+    Option<String>,
+    bool,
+);
+
+// animation_frame drives the current-line marquee and scroll_offset the manual
+// scroll; a param struct for this single-caller draw helper would be over-engineering.
+#[allow(clippy::too_many_arguments)]
+fn draw_lyrics(
+    f: &mut Frame,
+    area: Rect,
+    position: Duration,
+    lyrics: Option<&Lyrics>,
+    unreachable: bool,
+    theme: Theme,
+    is_jp: bool,
+    animation_frame: u32,
+    scroll_offset: isize,
+    show_next: Option<ShowNextStyle>,
+) {
+    let lyrics: &Lyrics = match lyrics {
+        Some(l) => l,
+        None => {
+            let message = if unreachable {
+                if is_jp {
+                    "信号なし"
+                } else {
+                    "NO SIGNAL"
+                }
+            } else if is_jp {
+                "歌詞なし"
+            } else {
+                "NO LYRICS AVAILABLE"
+            };
+            let p = Paragraph::new(message)
+                .style(Style::default().fg(theme.dim).add_modifier(Modifier::DIM))
+                .alignment(Alignment::Center);
+            let v_center = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage(45),
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                ])
+                .split(area);
+            f.render_widget(p, v_center[1]);
+            return;
+        }
+    };
+
+    let current_index = lyrics.find_index(position);
+    // Instrumental breaks are blank lines; skip past them to the next words
+    let next_index = lyrics
+        .lines
+        .iter()
+        .enumerate()
+        .skip(current_index + 1)
+        .find(|(_, line)| !line.text.trim().is_empty())
+        .map(|(i, _)| i);
+    let area = match (show_next, next_index) {
+        (Some(ShowNextStyle::Slot), Some(next)) if area.height > 3 => {
+            let [lyrics_area, slot] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+            let slot_line = Line::from(vec![
+                Span::styled(
+                    if is_jp { "次: " } else { "NEXT: " },
+                    Style::default().fg(theme.dim),
+                ),
+                Span::styled(
+                    lyrics.lines[next].text.as_str(),
+                    Style::default().fg(theme.primary),
+                ),
+            ]);
+            f.render_widget(Paragraph::new(slot_line).alignment(Alignment::Center), slot);
+            lyrics_area
+        }
+        _ => area,
+    };
+    let highlight_next = show_next == Some(ShowNextStyle::Highlight);
+    let h = area.height as usize;
+    let mid = h / 2;
+
+    let width = area.width as usize;
+    let mut lines = Vec::new();
+    for (i, line) in lyrics.lines.iter().enumerate() {
+        let distance = (i as isize - current_index as isize).unsigned_abs();
+        let style = if i == current_index {
+            // Tier 1: Current line -- accent color + bold (brightest)
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD)
+        } else if highlight_next && Some(i) == next_index {
+            // Upcoming line -- a step above its neighbors so singers can read ahead
+            Style::default()
+                .fg(theme.primary)
+                .add_modifier(Modifier::BOLD)
+        } else if distance <= 2 {
+            // Tier 2: Near lines (+-1-2) -- normal readable text
+            Style::default().fg(theme.primary)
+        } else {
+            // Tier 3: Far lines (beyond +-2) -- faded
+            Style::default().fg(theme.dim)
+        };
+        // Marquee-scroll only the current line when it overflows the width;
+        // other lines stay put (and are truncated by the paragraph as before).
+        let text: Cow<str> = if i == current_index {
+            scroll_text(&line.text, width, animation_frame)
+        } else {
+            Cow::Borrowed(line.text.as_str())
+        };
+        lines.push(Line::from(Span::styled(text, style)));
+    }
+
+    let last = lyrics.lines.len().saturating_sub(1) as isize;
+    let anchor = (current_index as isize + scroll_offset).clamp(0, last) as usize;
+    let scroll = anchor.saturating_sub(mid) as u16;
+    let p = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .scroll((scroll, 0));
+
+    f.render_widget(p, area);
+}
+
+// Upcoming tracks, numbered; the selection is only shown while focused
+fn draw_queue(
+    f: &mut Frame,
+    area: Rect,
+    queue: Option<&[QueueEntry]>,
+    selected: Option<usize>,
+    theme: Theme,
+    is_jp: bool,
+) {
+    let title = if is_jp { "次の曲" } else { "UP NEXT" };
+    let mut lines = vec![Line::from(Span::styled(
+        title,
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    let message = match queue {
+        None => Some(if is_jp {
+            "キュー取得不可"
+        } else {
+            "QUEUE UNAVAILABLE"
+        }),
+        Some([]) => Some(if is_jp {
+            "キューは空です"
+        } else {
+            "END OF QUEUE"
+        }),
+        Some(_) => None,
+    };
+    if let Some(message) = message {
+        lines.push(Line::from(Span::styled(
+            message,
+            Style::default().fg(theme.dim).add_modifier(Modifier::DIM),
+        )));
+    }
+    let queue = queue.unwrap_or_default();
+    // Keep the selection in view once the list outgrows the panel
+    let rows = (area.height as usize).saturating_sub(1).max(1);
+    let first = selected.map_or(0, |s| (s + 1).saturating_sub(rows));
+    for (i, entry) in queue.iter().enumerate().skip(first).take(rows) {
+        let style = if selected == Some(i) {
+            Style::default()
+                .fg(theme.bg)
+                .bg(theme.accent)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.primary)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:>2} ", i + 1), Style::default().fg(theme.dim)),
+            Span::styled(entry.track.name.clone(), style),
+            Span::styled(
+                format!(" · {}", entry.track.artist),
+                Style::default().fg(theme.dim),
+            ),
+        ]));
+    }
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_chassis(
+    f: &mut Frame,
+    area: Rect,
+    theme: Theme,
+    is_jp: bool,
+    clock: Option<&str>,
+    update: Option<&str>,
+    focus: Option<&str>,
+    reduced_motion: bool,
+    standby: bool,
+) -> Rect {
+    if theme.is_retro {
+        let mut chassis_block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Thick)
+            .border_style(Style::default().fg(theme.dim))
+            .title(vec![
+                Span::styled(" + ", Style::default().fg(theme.dim)),
+                Span::styled(
+                    format!(" ❖ MODEL: AMCLI // THEME: {} ", theme.name.to_uppercase()),
+                    Style::default()
+                        .fg(theme.primary)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" + ", Style::default().fg(theme.dim)),
+            ])
+            .title_alignment(Alignment::Center)
+            .title_bottom(vec![
+                Span::styled(" + ", Style::default().fg(theme.dim)),
+                if standby {
+                    Span::styled(
+                        if is_jp {
+                            " ⏹ 待機中 "
+                        } else {
+                            " ⏹ STANDBY "
+                        },
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    Span::styled(
+                        if is_jp {
+                            " 産業用音響機器 "
+                        } else {
+                            " INDUSTRIAL AUDIO COMPONENT "
+                        },
+                        Style::default().fg(theme.dim).add_modifier(Modifier::DIM),
+                    )
+                },
+                Span::styled(" + ", Style::default().fg(theme.dim)),
+            ])
+            .title_alignment(Alignment::Center);
+
+        if let Some(clock) = clock {
+            chassis_block = chassis_block.title_top(
+                Line::from(Span::styled(
+                    format!(" {} ", clock),
+                    Style::default().fg(theme.accent),
+                ))
+                .right_aligned(),
+            );
+        }
+
+        if let Some(tag) = update {
+            chassis_block = chassis_block.title_top(
+                Line::from(Span::styled(
+                    if is_jp {
+                        format!(" 更新あり {} ", tag)
+                    } else {
+                        format!(" UPDATE AVAILABLE {} ", tag)
+                    },
+                    Style::default().fg(theme.dim),
+                ))
+                .left_aligned(),
+            );
+        }
+
+        if let Some(focus) = focus {
+            chassis_block = chassis_block.title_bottom(
+                Line::from(Span::styled(
+                    format!(" ◷ {} ", focus),
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .right_aligned(),
+            );
+        }
+
+        let inner = chassis_block.inner(area);
+        f.render_widget(chassis_block, area);
+
+        // Scanlines would paint over a transparent background
+        if reduced_motion || theme.is_transparent() {
+            return inner;
+        }
+
+        for y in (inner.top()..inner.bottom()).step_by(2) {
+            let line = Paragraph::new(" ".repeat(inner.width as usize)).style(
+                Style::default()
+                    .bg(Color::Rgb(5, 5, 5))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(line, Rect::new(inner.left(), y, inner.width, 1));
+        }
+        inner
+    } else {
+        area
+    }
+}
+
+fn draw_screen_border(f: &mut Frame, area: Rect, theme: Theme) -> Rect {
+    if theme.is_retro {
+        let screen_block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(Style::default().fg(theme.dim));
+        let inner = screen_block.inner(area);
+        f.render_widget(screen_block, area);
+        inner
+    } else {
+        area
+    }
+}
+
+fn draw_idle(
+    f: &mut Frame,
+    area: Rect,
+    theme: Theme,
+    is_jp: bool,
+    reduced_motion: bool,
+    alarm: Option<&str>,
+) {
+    let idle_msg = if is_jp {
+        "メディア入力待機中..."
+    } else {
+        "WAITING FOR MEDIA INPUT..."
+    };
+    let insert_msg = if is_jp {
+        "テープまたはディスクを挿入してください"
+    } else {
+        "INSERT TAPE OR DISC"
+    };
+    let insert_style = if reduced_motion {
+        Style::default().fg(theme.alert)
+    } else {
+        Style::default()
+            .fg(theme.alert)
+            .add_modifier(Modifier::SLOW_BLINK)
+    };
+    let mut idle_text = vec![
+        Line::from(""),
+        Line::from(idle_msg),
+        Line::from(""),
+        Line::from(Span::styled(insert_msg, insert_style)),
+    ];
+    if let Some(alarm) = alarm {
+        idle_text.push(Line::from(""));
+        idle_text.push(Line::from(Span::styled(
+            alarm.to_string(),
+            Style::default().fg(theme.accent),
+        )));
+    }
+    let idle_p = Paragraph::new(idle_text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(theme.dim))
+        .block(Block::default().padding(ratatui::widgets::Padding::new(0, 0, 5, 0)));
+    f.render_widget(idle_p, area);
+}
+
+fn playback_glyph(state: PlaybackState) -> &'static str {
+    match state {
+        PlaybackState::Playing => "▶",
+        PlaybackState::Paused => "⏸",
+        PlaybackState::Stopped => "⏹",
+    }
+}
+
+// `position` is interpolated between polls, so the bar and time keep moving
+#[allow(clippy::too_many_arguments)]
+fn draw_progress(
+    f: &mut Frame,
+    area: Rect,
+    track: &Track,
+    position: Duration,
+    state: PlaybackState,
+    tags: &[String],
+    ticker: Option<Duration>,
+    theme: Theme,
+) {
+    let progress_percent = progress_percent(position, track.duration);
+
+    let mut label = format!(
+        " {} {}/{} | {:02}% ",
+        playback_glyph(state),
+        format_duration_seconds(position),
+        format_duration_seconds(track.duration),
+        progress_percent
+    );
+    for tag in tags {
+        label.push_str(&format!("| {} ", tag));
+    }
+
+    let mut block = Block::default()
+        .borders(Borders::TOP | Borders::BOTTOM)
+        .border_style(Style::default().fg(theme.dim))
+        .title(vec![
+            Span::styled(" [ ", Style::default().fg(theme.dim)),
+            Span::styled(label, Style::default().fg(theme.dim)),
+            Span::styled(" ] ", Style::default().fg(theme.dim)),
+        ]);
+    if let Some(position) = ticker {
+        block = block.title_top(
+            Line::from(Span::styled(
+                format!(" {} ", format_ticker(position)),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .right_aligned(),
+        );
+    }
+
+    let gauge = Gauge::default()
+        .block(block)
+        .gauge_style(Style::default().fg(theme.primary).bg(if theme.is_retro {
+            Color::Rgb(15, 15, 15)
+        } else {
+            theme.dim
+        }))
+        .percent(progress_percent.min(100))
+        .label("");
+
+    f.render_widget(gauge, area);
+}
+
+fn progress_percent(position: Duration, duration: Duration) -> u16 {
+    if duration.as_secs() > 0 {
+        ((position.as_secs_f64() / duration.as_secs_f64()) * 100.0) as u16
+    } else {
+        0
+    }
+}
+
+// A star for favorites, a mark for notes, speed when it isn't normal, the
+// last bookmark passed, the next alarm, the focus timer when there's no
+// chassis bar to show it, a crossfade under way, and where the sound is going
+fn progress_tags(app: &App, track: &Track) -> Vec<String> {
+    let mut tags = Vec::new();
+    if let Some(key) = app.track_key.as_deref() {
+        if app.favorites.contains(key) {
+            tags.push("★".into());
+        }
+        if app.notes.get(key).is_some() {
+            tags.push("✎ NOTE".into());
+        }
+    }
+    if let Some(rate) = app.playback_rate.filter(|rate| *rate != 1.0) {
+        tags.push(format_rate(rate));
+    }
+    match app.current_repeat_mode {
+        RepeatMode::Off => {}
+        RepeatMode::All => tags.push("↻ ALL".into()),
+        RepeatMode::One => tags.push("↻ ONE".into()),
+    }
+    if let Some(key) = &app.track_key {
+        if let Some(bookmark) = app
+            .bookmarks
+            .previous(key, track.position + Duration::from_millis(500))
+        {
+            tags.push(format!("◆ {}", bookmark.name));
+        }
+    }
+    if let Some(alarm) = app.alarm_clock.as_ref().and_then(AlarmClock::upcoming) {
+        tags.push(format!("⏰ {}", alarm.describe()));
+    }
+    if let Some(timer) = app
+        .focus_timer
+        .as_ref()
+        .filter(|_| !app.current_theme().is_retro)
+    {
+        let is_jp = app.config.general.language == amcli_core::config::Language::Japanese;
+        tags.push(format!("◷ {}", timer.describe(Instant::now(), is_jp)));
+    }
+    if app.player.is_crossfading() {
+        tags.push("⇄ CROSSFADE".into());
+    }
+    if let Some(device) = &app.output_device {
+        tags.push(format!("🔊 {}", device));
+    }
+    tags
+}
+
+// "1.5×", "0.75×"
+fn format_rate(rate: f32) -> String {
+    format!("{}×", rate)
+}
+
+// "L30", "C", "R100"
+fn format_balance(balance: f32) -> String {
+    let percent = (balance.abs() * 100.0).round() as u8;
+    match percent {
+        0 => "C".to_string(),
+        _ if balance < 0.0 => format!("L{}", percent),
+        _ => format!("R{}", percent),
+    }
+}
+
+fn inset_rect(area: Rect, margin: u16) -> Rect {
+    Rect::new(
+        area.x.saturating_add(margin),
+        area.y.saturating_add(margin),
+        area.width.saturating_sub(margin.saturating_mul(2)),
+        area.height.saturating_sub(margin.saturating_mul(2)),
+    )
+}
+
+fn center_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    )
+}
+
+fn artwork_protocol_rect(bounds: Rect, protocol: &StatefulProtocol) -> Rect {
+    let fit = protocol.size_for(Resize::Fit(None), bounds);
+    center_rect(bounds, fit.width, fit.height)
+}
+
+fn draw_artwork(
+    f: &mut Frame,
+    area: Rect,
+    protocol: Option<&mut StatefulProtocol>,
+    is_loading: bool,
+    throbber_state: Option<&mut ThrobberState>,
+    theme: Theme,
+    is_jp: bool,
+) {
+    let art_bounds = inset_rect(area, 1);
+
+    if is_loading {
+        // No throbber state means reduced motion: show a static marker instead
+        if let Some(throbber_state) = throbber_state {
+            let art_rect = center_rect(art_bounds, 1, 1);
+            let loader = Throbber::default()
+                .throbber_set(BRAILLE_SIX_DOUBLE)
+                .use_type(WhichUse::Spin)
+                .style(Style::default().fg(theme.accent));
+            f.render_stateful_widget(loader, art_rect, throbber_state);
+        } else {
+            let loading_text = if is_jp { "読込中" } else { "LOADING" };
+            let art_rect = center_rect(art_bounds, art_bounds.width, 1);
+            let loading = Paragraph::new(loading_text)
+                .style(Style::default().fg(theme.accent))
+                .alignment(Alignment::Center);
+            f.render_widget(loading, art_rect);
+        }
+    } else if let Some(protocol) = protocol {
+        let art_rect = artwork_protocol_rect(art_bounds, protocol);
+        let image = StatefulImage::default().resize(Resize::Fit(None));
+        f.render_stateful_widget(image, art_rect, protocol);
+    } else {
+        let no_sig_text = if is_jp { "信号なし" } else { "NO SIGNAL" };
+        let no_sig = Paragraph::new(no_sig_text)
+            .style(Style::default().fg(theme.dim).add_modifier(Modifier::DIM))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::NONE));
+        let v_center = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(45),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ])
+            .split(art_bounds);
+        f.render_widget(no_sig, v_center[1]);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_metadata(
+    f: &mut Frame,
+    area: Rect,
+    track: &Track,
+    animation_frame: u32,
+    is_two_columns: bool,
+    theme: Theme,
+    is_jp: bool,
+    normalization: Option<String>,
+) {
+    let status_text = if is_jp {
+        "動作状態: "
+    } else {
+        "SYS.STATUS: "
+    };
+    let online_text = if is_jp { "稼働中" } else { "ONLINE" };
+
+    // Only show status line for retro themes
+    let status_line = if theme.is_retro {
+        let mut spans = vec![
+            Span::styled(status_text, Style::default().fg(theme.dim)),
+            Span::styled(
+                online_text,
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("  "),
+            Span::styled("PCM 44.1kHz / STEREO", Style::default().fg(theme.accent)),
+        ];
+        if let Some(label) = normalization {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(label, Style::default().fg(theme.dim)));
+        }
+        Some(Line::from(spans))
+    } else {
+        None
+    };
+
+    let labels = if is_jp {
+        vec!["曲名", "アーティスト", "アルバム"]
+    } else {
+        vec!["TRACK TITLE", "ARTIST", "ALBUM REFERENCE"]
+    };
+
+    let values = [
+        track.name.to_uppercase(),
+        track.artist.to_uppercase(),
+        track.album.to_uppercase(),
+        format!(
+            "{} / {}",
+            format_duration(track.position),
+            format_duration(track.duration)
+        ),
+    ];
+
+    let _available_height = area.height as usize;
+    let items_count = labels.len();
+
+    if is_two_columns {
+        let col_layout = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
+            .spacing(SPACING_NORMAL)
+            .split(area);
+
+        let mid = items_count.div_ceil(2);
+        let col_width = col_layout[0].width.saturating_sub(6) as usize;
+
+        for col in 0..2 {
+            let start = if col == 0 { 0 } else { mid };
+            let end = if col == 0 { mid } else { items_count };
+            let mut lines = vec![Line::from("")];
+
+            if col == 0 {
+                if let Some(ref s_line) = status_line {
+                    lines.push(s_line.clone());
+                    lines.push(Line::from(vec![
+                        Span::raw("────────────────────────").fg(theme.dim)
+                    ]));
+                }
+            } else if theme.is_retro {
+                lines.push(Line::from(""));
+                lines.push(Line::from(""));
+            }
+
+            for i in start..end {
+                lines.push(Line::from(Span::styled(
+                    labels[i],
+                    Style::default()
+                        .fg(theme.dim)
+                        .add_modifier(Modifier::ITALIC),
+                )));
+
+                let display_val = scroll_text(&values[i], col_width, animation_frame);
+
+                lines.push(Line::from(Span::styled(
+                    format!(" {} ", display_val),
+                    Style::default()
+                        .bg(theme.dim)
+                        .fg(theme.bg)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            }
+            f.render_widget(
+                Paragraph::new(lines).block(Block::default().padding(
+                    ratatui::widgets::Padding::new(SPACING_NORMAL, SPACING_NORMAL, 0, 0),
+                )),
+                col_layout[col],
+            );
+        }
+    } else {
+        let mut lines = vec![Line::from("")];
+        if let Some(ref s_line) = status_line {
+            lines.push(s_line.clone());
+            lines.push(Line::from(vec![Span::raw(
+                "──────────────────────────────────────",
+            )
+            .fg(theme.dim)]));
+        }
+        let col_width = area.width.saturating_sub(6) as usize;
+
+        for i in 0..items_count {
+            lines.push(Line::from(Span::styled(
+                labels[i],
+                Style::default()
+                    .fg(theme.dim)
+                    .add_modifier(Modifier::ITALIC),
+            )));
+
+            let display_val = scroll_text(&values[i], col_width, animation_frame);
+
+            lines.push(Line::from(Span::styled(
+                format!(" {} ", display_val),
+                Style::default()
+                    .bg(theme.dim)
+                    .fg(theme.bg)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        f.render_widget(
+            Paragraph::new(lines).block(Block::default().padding(ratatui::widgets::Padding::new(
+                SPACING_NORMAL,
+                SPACING_NORMAL,
+                0,
+                0,
+            ))),
+            area,
+        );
+    }
+}
+
+// Which key hints the control bar shows; follows whatever currently owns input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HintContext {
+    Main,
+    Panel(Panel),
+    Settings,
+    ThemeEditor,
+    Command,
+}
+
+const MAIN_HINT_ACTIONS: &[Action] = &[
+    Action::TogglePlayback,
+    Action::NextTrack,
+    Action::PreviousTrack,
+    Action::VolumeUp,
+    Action::VolumeDown,
+    Action::NextTheme,
+    Action::Quit,
+];
+
+// Short forms kick in when the control bar buttons get narrow
+fn hint_label(action: Action, is_jp: bool) -> Label {
+    match (action, is_jp) {
+        (Action::TogglePlayback, true) => Label::new("▶ 再生", "再生"),
+        (Action::TogglePlayback, false) => Label::new("PLAY", "▶"),
+        (Action::NextTrack, true) => Label::new("▶▶ 次", "次"),
+        (Action::NextTrack, false) => Label::new("SKIP", "▶▶"),
+        (Action::PreviousTrack, true) => Label::new("◀◀ 前", "前"),
+        (Action::PreviousTrack, false) => Label::new("PREV", "◀◀"),
+        (Action::VolumeUp, true) => Label::new("音量＋", "＋"),
+        (Action::VolumeUp, false) => Label::new("VOL+", "+"),
+        (Action::VolumeDown, true) => Label::new("音量－", "－"),
+        (Action::VolumeDown, false) => Label::new("VOL-", "-"),
+        (Action::NextTheme, true) => Label::new("テーマ", "色"),
+        (Action::NextTheme, false) => Label::new("THEME", "THM"),
+        (Action::Quit, true) => Label::new("電源", "切"),
+        (Action::Quit, false) => Label::new("EXIT", "X"),
+        (_, true) => Label::fixed("操作"),
+        (_, false) => Label::new("ACTION", "ACT"),
+    }
+}
+
+fn hint_context(app: &App) -> HintContext {
+    if app.command_line.is_active {
+        HintContext::Command
+    } else if app.theme_editor.is_some() {
+        HintContext::ThemeEditor
+    } else if app.settings_menu.is_open {
+        HintContext::Settings
+    } else if app.focus.is_engaged() {
+        HintContext::Panel(app.focus.current())
+    } else {
+        HintContext::Main
+    }
+}
+
+// Main-context hints come from the keymap, so remapped keys show up here and
+// actions with no binding drop out.
+fn control_hints(app: &App, is_jp: bool) -> Vec<(Label, String)> {
+    let fixed = |hints: &[(Label, Label, &str)]| {
+        hints
+            .iter()
+            .map(|(en, jp, key)| (Label::pick(en.clone(), jp.clone(), is_jp), key.to_string()))
+            .collect()
+    };
+    // Longform items swap the track skip buttons for the seek steps
+    let longform = app.longform_steps().is_some();
+    let main_hints = move || {
+        MAIN_HINT_ACTIONS.iter().filter_map(move |&action| {
+            let (action, label) = match action {
+                Action::NextTrack if longform => {
+                    let (en, jp) = app.longform_labels[0].clone();
+                    (Action::SeekForward, Label::pick(en, jp, is_jp))
+                }
+                Action::PreviousTrack if longform => {
+                    let (en, jp) = app.longform_labels[1].clone();
+                    (Action::SeekBackward, Label::pick(en, jp, is_jp))
+                }
+                _ => (action, hint_label(action, is_jp)),
+            };
+            let key = app.keymap.keys_for(action).into_iter().next()?;
+            Some((label, key))
+        })
+    };
+    match hint_context(app) {
+        HintContext::Main if app.idle_grid.is_shown() => {
+            let mut hints: Vec<(Label, String)> = Vec::new();
+            if let Some(key) = app.keymap.keys_for(Action::LyricsSeek).into_iter().next() {
+                hints.push((
+                    Label::pick(
+                        Label::new("PLAY ALBUM", "PLAY"),
+                        Label::new("再生", "再"),
+                        is_jp,
+                    ),
+                    key,
+                ));
+            }
+            hints.extend(main_hints());
+            hints.truncate(MAIN_HINT_ACTIONS.len());
+            hints
+        }
+        HintContext::Main => main_hints().collect(),
+        // Panel hints lead, and the main hints fill the remaining buttons
+        HintContext::Panel(panel) => {
+            let pair = |a: Action, b: Action| {
+                let first = |action| app.keymap.keys_for(action).into_iter().next();
+                Some(format!("{}/{}", first(a)?, first(b)?))
+            };
+            let label = |en, jp| Label::pick(en, jp, is_jp);
+            let mut hints: Vec<(Label, String)> = Vec::new();
+            if let Some(keys) = pair(Action::NavigateLeft, Action::NavigateRight) {
+                hints.push((
+                    label(Label::new("PANEL", "PNL"), Label::new("切替", "切")),
+                    keys,
+                ));
+            }
+            if panel == Panel::Lyrics {
+                if let Some(keys) = pair(Action::NavigateUp, Action::NavigateDown) {
+                    hints.push((
+                        label(Label::new("SCROLL", "SCR"), Label::new("送り", "送")),
+                        keys,
+                    ));
+                }
+                if app.lyrics_search.is_some() {
+                    if let Some(keys) = pair(Action::LyricsNextMatch, Action::LyricsPreviousMatch) {
+                        hints.push((
+                            label(Label::new("MATCH", "HIT"), Label::new("次候補", "次")),
+                            keys,
+                        ));
+                    }
+                } else if let Some(key) =
+                    app.keymap.keys_for(Action::SearchLyrics).into_iter().next()
+                {
+                    hints.push((
+                        label(Label::new("SEARCH", "FIND"), Label::new("検索", "検")),
+                        key,
+                    ));
+                }
+                if let Some(key) = app.keymap.keys_for(Action::LyricsSeek).into_iter().next() {
+                    hints.push((
+                        label(Label::new("PLAY LINE", "GO"), Label::new("頭出し", "頭")),
+                        key,
+                    ));
+                }
+            }
+            if panel == Panel::Queue {
+                if let Some(keys) = pair(Action::QueueMoveUp, Action::QueueMoveDown) {
+                    hints.push((
+                        label(Label::new("MOVE", "MV"), Label::new("並替", "並")),
+                        keys,
+                    ));
+                }
+                if let Some(key) = app.keymap.keys_for(Action::QueueRemove).into_iter().next() {
+                    hints.push((
+                        label(Label::new("REMOVE", "DEL"), Label::new("削除", "削")),
+                        key,
+                    ));
+                }
+            }
+            hints.extend(main_hints());
+            hints.truncate(MAIN_HINT_ACTIONS.len());
+            hints
+        }
+        HintContext::Settings => fixed(&[
+            (Label::new("MOVE", "MV"), Label::new("移動", "移"), "↑↓"),
+            (Label::new("TAB", "TAB"), Label::new("分類", "分"), "TAB"),
+            (Label::new("SELECT", "SEL"), Label::new("選択", "選"), "⏎"),
+            (Label::new("CLOSE", "X"), Label::new("閉じる", "閉"), "ESC"),
+        ]),
+        HintContext::ThemeEditor => fixed(&[
+            (Label::new("CHANNEL", "CH"), Label::new("選択", "選"), "↑↓"),
+            (Label::new("ADJUST", "ADJ"), Label::new("調整", "調"), "←→"),
+            (Label::new("SAVE", "SV"), Label::new("保存", "保"), "⏎"),
+            (Label::new("CANCEL", "X"), Label::new("取消", "消"), "ESC"),
+        ]),
+        HintContext::Command => fixed(&[
+            (Label::fixed("RUN"), Label::new("実行", "実"), "⏎"),
+            (
+                Label::new("COMPLETE", "CMP"),
+                Label::new("補完", "補"),
+                "TAB",
+            ),
+            (Label::new("HISTORY", "HIS"), Label::new("履歴", "履"), "↑↓"),
+            (Label::new("CANCEL", "X"), Label::new("取消", "消"), "ESC"),
+        ]),
+    }
+}
+
+// Each button shows " LABEL [KEY] ", swapping in the short label and then
+// dropping it altogether as the buttons narrow
+fn draw_controls(f: &mut Frame, area: Rect, controls: &[(Label, String)], theme: Theme) {
+    let btn_layout = Layout::horizontal(vec![Constraint::Fill(1); controls.len()]).split(area);
+
+    for (i, (label, key)) in controls.iter().enumerate() {
+        if i < btn_layout.len() {
+            let inner = btn_layout[i].width.saturating_sub(2) as usize;
+            let key_text = format!(" [{}] ", key);
+            let label_width = inner.saturating_sub(UnicodeWidthStr::width(key_text.as_str()) + 1);
+            let btn_text = match label.fit(label_width) {
+                Some(label) => Line::from(vec![
+                    Span::styled(
+                        format!(" {}", label),
+                        Style::default()
+                            .fg(theme.primary)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(key_text, Style::default().fg(theme.dim)),
+                ]),
+                None => Line::from(Span::styled(
+                    labels::truncate(&format!("[{}]", key), inner).into_owned(),
+                    Style::default().fg(theme.dim),
+                )),
+            };
+
+            let mut btn_block = Block::default()
+                .borders(Borders::ALL)
+                .border_type(if theme.is_retro {
+                    BorderType::Thick
+                } else {
+                    BorderType::Plain
+                })
+                .border_style(Style::default().fg(theme.dim));
+
+            if theme.is_retro && !theme.is_transparent() {
+                btn_block = btn_block.bg(Color::Rgb(10, 10, 10));
+            }
+
+            let btn = Paragraph::new(btn_text)
+                .alignment(Alignment::Center)
+                .block(btn_block);
+
+            f.render_widget(btn, btn_layout[i]);
+        }
+    }
+}
+
+// Orchestrator: computes layout, dispatches to section renderers
+pub fn draw(f: &mut Frame, app: &mut App) {
+    let area = f.area();
+    let theme = app.current_theme();
+    let is_jp = app.config.general.language == amcli_core::config::Language::Japanese;
+    f.render_widget(Block::default().style(Style::default().bg(theme.bg)), area);
+
+    if app.boot_splash.as_ref().is_some_and(|s| s.is_finished()) {
+        app.skip_splash();
+    }
+    if let Some(splash) = app.boot_splash.as_ref() {
+        splash.render(f, area, theme, is_jp);
+        return;
+    }
+
+    let reduced_motion = app.config.ui.reduced_motion;
+    // Frame zero pins every marquee to its leading window
+    let animation_frame = if reduced_motion {
+        0
+    } else {
+        app.animation_frame
+    };
+
+    let focus = app
+        .focus_timer
+        .as_ref()
+        .map(|timer| timer.describe(Instant::now(), is_jp));
+    let chassis_inner = draw_chassis(
+        f,
+        area,
+        theme,
+        is_jp,
+        app.clock_text.as_deref(),
+        app.update_available.as_deref(),
+        focus.as_deref(),
+        reduced_motion,
+        app.playback_state == PlaybackState::Stopped,
+    );
+    // Collapse order as height shrinks: controls first, then progress bar
+    let show_controls = chassis_inner.height >= 19;
+    let show_progress = chassis_inner.height >= 16;
+    let (display_area, tuner_area, control_area) = if show_controls {
+        let [d, t, c] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .areas(chassis_inner);
+        (d, Some(t), Some(c))
+    } else if show_progress {
+        let [d, t] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(chassis_inner);
+        (d, Some(t), None)
+    } else {
+        (chassis_inner, None, None)
+    };
+
+    let screen_inner = draw_screen_border(f, display_area, theme);
+    // The queue takes a column on the right when there's room for it
+    let (screen_inner, queue_area) = if app.show_queue && screen_inner.width >= 72 {
+        let [main, queue] = Layout::horizontal([Constraint::Fill(1), Constraint::Length(32)])
+            .spacing(SPACING_NORMAL)
+            .areas(screen_inner);
+        (main, queue)
+    } else {
+        (screen_inner, Rect::default())
+    };
+    // Big text takes over the screen and leaves the regular panels no room
+    let big_text_area = if app.big_text && app.current_track.is_some() {
+        screen_inner
+    } else {
+        Rect::default()
+    };
+    // So do the idle album covers
+    let idle_grid_area = if app.idle_grid.is_shown() && app.current_track.is_none() {
+        screen_inner
+    } else {
+        Rect::default()
+    };
+    let screen_inner = if big_text_area.is_empty() && idle_grid_area.is_empty() {
+        screen_inner
+    } else {
+        Rect::default()
+    };
+    let show_artwork =
+        app.config.artwork.album && display_area.width > 50 && !screen_inner.is_empty();
+    let mut artwork_area = Rect::default();
+    let info_chunk = if show_artwork {
+        let available = screen_inner.width;
+        let artwork_constraints = if available >= 20 + 30 + SPACING_NORMAL {
+            [Constraint::Fill(3), Constraint::Fill(4)]
+        } else {
+            [Constraint::Min(20), Constraint::Fill(1)]
+        };
+        let [artwork_col, info_col] = Layout::horizontal(artwork_constraints)
+            .spacing(SPACING_NORMAL)
+            .areas(screen_inner);
+        artwork_area = artwork_col;
+        draw_artwork(
+            f,
+            artwork_col,
+            app.artwork_protocol.as_mut(),
+            app.is_loading_artwork,
+            (!reduced_motion).then_some(&mut app.throbber_state),
+            theme,
+            is_jp,
+        );
+        info_col
+    } else {
+        screen_inner
+    };
+    let has_lyrics = app.current_lyrics.is_some();
+    let info_height = info_chunk.height as usize;
+    let metadata_width = info_chunk.width;
+    let is_two_columns = show_artwork
+        && (metadata_width > 80 || (has_lyrics && info_height <= 14))
+        && metadata_width >= 40;
+    let meta_height = if is_two_columns { 5 } else { 8 };
+    let (metadata_area, lyrics_area) = if !show_artwork && has_lyrics {
+        let [meta, lyrics] = Layout::horizontal([Constraint::Fill(2), Constraint::Fill(3)])
+            .spacing(SPACING_NORMAL)
+            .areas(info_chunk);
+        (meta, lyrics)
+    } else if has_lyrics && info_height > meta_height + 2 {
+        let [meta, lyrics] =
+            Layout::vertical([Constraint::Length(meta_height as u16), Constraint::Fill(1)])
+                .spacing(SPACING_NORMAL)
+                .areas(info_chunk);
+        (meta, lyrics)
+    } else {
+        (info_chunk, Rect::default())
+    };
+
+    if let Some(track) = app.current_track.as_ref() {
+        draw_metadata(
+            f,
+            metadata_area,
+            track,
+            animation_frame,
+            is_two_columns,
+            theme,
+            is_jp,
+            app.normalization_label(),
+        );
+    } else {
+        let alarm = app
+            .alarm_clock
+            .as_ref()
+            .and_then(AlarmClock::upcoming)
+            .map(|alarm| format!("⏰ {}", alarm.describe()));
+        draw_idle(
+            f,
+            info_chunk,
+            theme,
+            is_jp,
+            reduced_motion,
+            alarm.as_deref(),
+        );
+    }
+    if lyrics_area.height > 2 {
+        // Between polls, so line changes land on time instead of up to a
+        // poll late
+        if let Some(position) = app.interpolated_position() {
+            draw_lyrics(
+                f,
+                lyrics_area,
+                position,
+                app.current_lyrics.as_ref(),
+                app.lyrics_unreachable,
+                theme,
+                is_jp,
+                animation_frame,
+                app.lyrics_scroll,
+                app.config
+                    .lyrics
+                    .show_next
+                    .enabled
+                    .then_some(app.config.lyrics.show_next.style),
+            );
+        }
+    }
+    if let Some(text) = app.big_text_line().filter(|_| !big_text_area.is_empty()) {
+        big_text::draw(f, big_text_area, text, theme);
+    }
+    if !idle_grid_area.is_empty() {
+        idle_grid::draw(f, idle_grid_area, &mut app.idle_grid, theme);
+    }
+    // Track info and lyrics fade back while nothing is playing
+    if app.current_track.is_some() && app.playback_state != PlaybackState::Playing {
+        for area in [info_chunk, big_text_area] {
+            f.buffer_mut()
+                .set_style(area, Style::default().add_modifier(Modifier::DIM));
+        }
+    }
+    if let Some(tuner_area) = tuner_area {
+        if let Some(track) = app.get_current_track() {
+            let ticker = (theme.ticker && app.config.ui.ticker && !reduced_motion)
+                .then(|| app.interpolated_position())
+                .flatten();
+            let position = app.interpolated_position().unwrap_or(track.position);
+            draw_progress(
+                f,
+                tuner_area,
+                track,
+                position,
+                app.playback_state,
+                &progress_tags(app, track),
+                ticker,
+                theme,
+            );
+        }
+    }
+
+    if !queue_area.is_empty() {
+        let selected = (app.focus.current() == Panel::Queue).then_some(app.queue_selected);
+        draw_queue(f, queue_area, app.queue.as_deref(), selected, theme, is_jp);
+    }
+
+    let lyrics_visible = lyrics_area.height > 2 && app.current_track.is_some();
+    let panels = [
+        (Panel::Artwork, artwork_area),
+        (Panel::Metadata, metadata_area),
+        (
+            Panel::Lyrics,
+            if lyrics_visible {
+                lyrics_area
+            } else {
+                Rect::default()
+            },
+        ),
+        (Panel::Queue, queue_area),
+    ];
+    app.focus.set_visible(
+        panels
+            .iter()
+            .filter(|(_, rect)| !rect.is_empty())
+            .map(|(panel, _)| *panel)
+            .collect(),
+    );
+    if app.focus.is_engaged() {
+        if let Some((panel, rect)) = panels.iter().find(|(p, _)| *p == app.focus.current()) {
+            focus::draw_focus_indicator(f, *rect, *panel, theme, is_jp);
+        }
+    }
+
+    if let Some(control_area) = control_area {
+        let hints = control_hints(app, is_jp);
+        // Retro panels get a balance meter at the end of the button row
+        let control_area = match app.balance {
+            Some(balance) if theme.is_retro && control_area.width >= 60 => {
+                let [buttons, meter] =
+                    Layout::horizontal([Constraint::Fill(1), Constraint::Length(15)])
+                        .areas(control_area);
+                draw_balance(f, meter, balance, theme, is_jp);
+                buttons
+            }
+            _ => control_area,
+        };
+        draw_controls(f, control_area, &hints, theme);
+    }
+
+    // LAST: Settings overlay (z-order contract -- Ratatui has no z-index)
+    if app.settings_menu.is_open {
+        app.settings_menu.render(f, theme, is_jp);
+    }
+    if let Some(editor) = &app.theme_editor {
+        editor.render(f, theme, is_jp);
+    }
+    if let Some(panel) = &mut app.artist_panel {
+        panel.render(f, theme, is_jp);
+    }
+    if let Some(panel) = &app.favorites_panel {
+        panel.render(f, app.favorites.items(), theme, is_jp);
+    }
+    if let Some(browser) = &app.file_browser {
+        browser.render(f, theme, is_jp);
+    }
+    if let Some(picker) = &app.station_picker {
+        picker.render(f, &app.config.stations, app.on_air(), theme, is_jp);
+    }
+    if let Some(switcher) = &app.quick_switcher {
+        switcher.render(f, theme, is_jp, app.library_task.is_running());
+    }
+    if let Some(volume) = app.volume_overlay() {
+        draw_volume_overlay(f, volume, app.is_muted, theme, is_jp);
+    }
+    if app.show_debug_overlay {
+        debug_overlay::render(f, &app.debug_report(), theme);
+    }
+    app.command_line.render(f, theme);
+    if let Some(pending) = app.keymap.pending_label() {
+        draw_pending_keys(f, &pending, theme);
+    }
+}
+
+// "L ───┼──●─ R", the dot marking where the balance sits
+fn draw_balance(f: &mut Frame, area: Rect, balance: f32, theme: Theme, is_jp: bool) {
+    let scale = area.width.saturating_sub(6) as usize;
+    if scale < 3 {
+        return;
+    }
+    let marker = (((balance + 1.0) / 2.0) * (scale - 1) as f32).round() as usize;
+    let line: String = (0..scale)
+        .map(|i| match i {
+            _ if i == marker => '●',
+            _ if i == scale / 2 => '┼',
+            _ => '─',
+        })
+        .collect();
+    let (left, right) = if is_jp { ("左", "右") } else { ("L ", " R") };
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(theme.dim));
+    if !theme.is_transparent() {
+        block = block.bg(Color::Rgb(10, 10, 10));
+    }
+    let meter = Paragraph::new(Line::from(vec![
+        Span::styled(left, Style::default().fg(theme.dim)),
+        Span::styled(
+            line,
+            Style::default()
+                .fg(theme.primary)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(right, Style::default().fg(theme.dim)),
+    ]))
+    .alignment(Alignment::Center)
+    .block(block);
+    f.render_widget(meter, area);
+}
+
+// Centered above the controls for a moment after the volume changes
+fn draw_volume_overlay(f: &mut Frame, volume: u8, muted: bool, theme: Theme, is_jp: bool) {
+    let area = f.area();
+    let width = 36.min(area.width);
+    if width < 12 || area.height < 5 {
+        return;
+    }
+    let rect = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.bottom() - 5,
+        width,
+        3,
+    );
+    let label = match (muted, is_jp) {
+        (true, true) => "消音".to_string(),
+        (true, false) => "MUTE".to_string(),
+        (false, true) => format!("音量 {}%", volume),
+        (false, false) => format!("VOL {}%", volume),
+    };
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.dim))
+                .style(Style::default().bg(theme.bg)),
+        )
+        .gauge_style(Style::default().fg(theme.primary).bg(theme.bg))
+        .percent(volume.min(100) as u16)
+        .label(Span::styled(
+            label,
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ));
+    f.render_widget(Clear, rect);
+    f.render_widget(gauge, rect);
+}
+
+// Bottom-right indicator while a multi-key sequence is in progress
+fn draw_pending_keys(f: &mut Frame, pending: &str, theme: Theme) {
+    let area = f.area();
+    let width = (UnicodeWidthStr::width(pending) as u16 + 2).min(area.width);
+    if area.height == 0 || width == 0 {
+        return;
+    }
+    let rect = Rect::new(area.right() - width, area.bottom() - 1, width, 1);
+    f.render_widget(
+        Paragraph::new(format!(" {} ", pending)).style(
+            Style::default()
+                .fg(theme.bg)
+                .bg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ),
+        rect,
+    );
+}
+
+fn format_duration_seconds(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    format!("{}s", total_seconds)
+}
+
+// MM:SS.d, like a CD player's display
+fn format_ticker(position: Duration) -> String {
+    let tenths = position.as_millis() / 100;
+    format!(
+        "{:02}:{:02}.{}",
+        tenths / 600,
+        tenths / 10 % 60,
+        tenths % 10
+    )
+}
+
+// "1 track", "12 tracks"
+fn tracks_label(count: usize) -> String {
+    match count {
+        1 => "1 track".to_string(),
+        n => format!("{} tracks", n),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    let minutes = total_seconds / 60;
+    let seconds = total_seconds % 60;
+    format!("{:02}:{:02}", minutes, seconds)
+}
+
+// Marquee scroll measured by display width (columns), so full-width CJK glyphs
+// trigger scrolling and fill the window correctly instead of overflowing.
+fn scroll_text<'a>(text: &'a str, width: usize, frame: u32) -> Cow<'a, str> {
+    if UnicodeWidthStr::width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+
+    let gap_len = 3;
+    // Offset advances one character per frame; wrap over text + gap.
+    let total_len = text.chars().count() + gap_len;
+    let offset = (frame as usize) % total_len;
+
+    let mut result = String::new();
+    let mut used = 0usize;
+    for ch in text
+        .chars()
+        .chain(std::iter::repeat_n(' ', gap_len))
+        .cycle()
+        .skip(offset)
+    {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if used + ch_width > width {
+            // A wide glyph won't fit the last column -- pad with a space to keep
+            // the rendered width stable rather than letting it overflow.
+            if used < width {
+                result.push(' ');
+            }
+            break;
+        }
+        result.push(ch);
+        used += ch_width;
+    }
+
+    Cow::Owned(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amcli_core::player::{MediaPlayer, PlaybackState, RepeatMode, Track};
+    use async_trait::async_trait;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use image::{Rgba, RgbaImage};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    struct MockPlayer {
+        volume: u8,
+        artwork_url: Option<String>,
+        crossfading: bool,
+        track: Track,
+    }
+
+    #[async_trait]
+    impl MediaPlayer for MockPlayer {
+        async fn play(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn pause(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn toggle(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn next(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn previous(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn get_current_track(&self) -> Result<Option<Track>> {
+            Ok(Some(self.track.clone()))
+        }
+        async fn get_playback_state(&self) -> Result<PlaybackState> {
+            Ok(PlaybackState::Playing)
+        }
+        async fn get_player_status(&self) -> Result<amcli_core::player::PlayerStatus> {
+            Ok(amcli_core::player::PlayerStatus {
+                track: Some(self.track.clone()),
+                volume: Some(self.volume),
+                state: PlaybackState::Playing,
+                repeat: Some(RepeatMode::Off),
+            })
+        }
+        async fn set_volume(&self, _volume: u8) -> Result<()> {
+            Ok(())
+        }
+        async fn get_volume(&self) -> Result<u8> {
+            Ok(self.volume)
+        }
+        async fn seek(&self, _seconds: i32) -> Result<()> {
+            Ok(())
+        }
+        async fn set_position(&self, _position: Duration) -> Result<()> {
+            Ok(())
+        }
+        async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
+            Ok(())
+        }
+        async fn set_repeat(&self, _mode: RepeatMode) -> Result<()> {
+            Ok(())
+        }
+        async fn get_artwork_url(&self, _track: &Track) -> Result<Option<String>> {
+            Ok(self.artwork_url.clone())
+        }
+        async fn play_files(&self, paths: &[PathBuf]) -> Result<usize> {
+            Ok(paths.len())
+        }
+        async fn enqueue_files(&self, paths: &[PathBuf]) -> Result<usize> {
+            Ok(paths.len())
+        }
+        async fn describe_file(&self, path: &Path) -> Result<Track> {
+            Ok(test_track(&path.file_stem().unwrap().to_string_lossy()))
+        }
+        fn is_crossfading(&self) -> bool {
+            self.crossfading
+        }
+        async fn play_stream(&self, _url: &str, _name: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn set_normalization(&self, _enabled: bool) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    // Like Now Playing: it reports a track and takes media keys, but can't
+    // set the volume, seek, shuffle, or repeat
+    struct LimitedPlayer {
+        track: Track,
+    }
+
+    #[async_trait]
+    impl MediaPlayer for LimitedPlayer {
+        async fn play(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn pause(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn toggle(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn next(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn previous(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn get_current_track(&self) -> Result<Option<Track>> {
+            Ok(Some(self.track.clone()))
+        }
+        async fn get_playback_state(&self) -> Result<PlaybackState> {
+            Ok(PlaybackState::Playing)
+        }
+        async fn set_volume(&self, _volume: u8) -> Result<()> {
+            Err(anyhow::anyhow!("volume is not available"))
+        }
+        async fn get_volume(&self) -> Result<u8> {
+            Err(anyhow::anyhow!("volume is not available"))
+        }
+        async fn seek(&self, _seconds: i32) -> Result<()> {
+            Err(anyhow::anyhow!("seeking is not available"))
+        }
+        async fn set_shuffle(&self, _enabled: bool) -> Result<()> {
+            Err(anyhow::anyhow!("shuffle is not available"))
+        }
+        async fn set_repeat(&self, _mode: RepeatMode) -> Result<()> {
+            Err(anyhow::anyhow!("repeat is not available"))
+        }
+        async fn get_artwork_url(&self, _track: &Track) -> Result<Option<String>> {
+            Ok(None)
+        }
+    }
+
+    fn limited_player() -> Box<dyn MediaPlayer> {
+        Box::new(LimitedPlayer {
+            track: test_track("Test Song"),
+        })
+    }
+
+    fn test_track(name: &str) -> Track {
+        Track {
+            name: name.into(),
+            artist: "Test Artist".into(),
+            album: "Test Album".into(),
+            duration: Duration::from_secs(300),
+            position: Duration::from_secs(150),
+        }
+    }
+
+    fn mock_player(volume: u8) -> Box<dyn MediaPlayer> {
+        Box::new(MockPlayer {
+            volume,
+            artwork_url: Some("http://example.com/artwork.jpg".into()),
+            crossfading: false,
+            track: test_track("Test Song"),
+        })
+    }
+
+    fn test_config() -> amcli_core::config::Config {
+        let mut config = amcli_core::config::Config::default();
+        config.ui.boot_splash = false;
+        config.ui.color_depth = amcli_core::config::ColorDepth::TrueColor;
+        config
+    }
+
+    fn hint_labels(app: &App, is_jp: bool) -> Vec<(String, String)> {
+        control_hints(app, is_jp)
+            .into_iter()
+            .map(|(label, key)| (label.full.into_owned(), key))
+            .collect()
+    }
+
+    // The command line as drawn on the bottom row
+    fn prompt(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = buffer.content().len() - buffer.area.width as usize;
+        buffer.content()[row..]
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    async fn test_app(player: Box<dyn MediaPlayer>) -> App {
+        App::with_player_config_and_lyrics_manager(player, test_config(), LyricsManager::new(1))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn keys_on_the_bus_reach_the_open_overlay_first() {
+        let mut app = test_app(mock_player(70)).await;
+        let press = |app: &App, code: KeyCode, modifiers: KeyModifiers| {
+            app.publish(AppEvent::Key(KeyEvent::new(code, modifiers)));
+        };
+        press(&app, KeyCode::Char('F'), KeyModifiers::SHIFT);
+        app.handle_events().await.unwrap();
+        assert!(app.is_favorites_open());
+        // `q` would quit from the main view, but the list swallows it
+        press(&app, KeyCode::Char('q'), KeyModifiers::NONE);
+        app.handle_events().await.unwrap();
+        assert!(app.is_favorites_open() && !app.should_quit());
+        press(&app, KeyCode::Esc, KeyModifiers::NONE);
+        app.handle_events().await.unwrap();
+        assert!(!app.is_favorites_open());
+
+        press(&app, KeyCode::Char('c'), KeyModifiers::CONTROL);
+        app.handle_events().await.unwrap();
+        assert!(app.should_quit());
+    }
+
+    #[tokio::test]
+    async fn controls_the_player_lacks_are_reported_not_fatal() {
+        let mut app = test_app(limited_player()).await;
+        app.update().await.unwrap();
+        for action in [Action::VolumeUp, Action::SeekForward, Action::CycleRepeat] {
+            app.perform(action).await;
+            assert!(!app.should_quit());
+        }
+        assert_eq!(prompt(&mut app), "repeat is not available");
+        // The same key pressed in the terminal
+        app.publish(AppEvent::Key(KeyEvent::new(
+            KeyCode::Right,
+            KeyModifiers::NONE,
+        )));
+        app.handle_events().await.unwrap();
+        assert_eq!(prompt(&mut app), "seeking is not available");
+        assert!(!app.should_quit());
+    }
+
+    #[tokio::test]
+    async fn remote_commands_the_player_lacks_are_reported_not_fatal() {
+        let mut app = test_app(limited_player()).await;
+        for command in [RemoteCommand::SetVolume(40), RemoteCommand::Seek(10)] {
+            app.publish(AppEvent::Remote(command));
+        }
+        app.handle_events().await.unwrap();
+        assert!(!app.should_quit());
+        assert_eq!(prompt(&mut app), "seeking is not available");
+    }
+
+    #[tokio::test]
+    async fn jumps_the_player_cant_make_are_reported_not_fatal() {
+        // Podcasts and Now Playing can only skip, not jump to a position
+        let mut app = test_app(limited_player()).await;
+        app.update().await.unwrap();
+        app.perform(Action::GoToStart).await;
+        assert!(!app.should_quit());
+        assert_eq!(
+            prompt(&mut app),
+            "jumping to a position is not supported by this player"
+        );
+
+        let key = app.track_key.clone().unwrap();
+        app.bookmarks.add(
+            &key,
+            Bookmark {
+                name: "Outro".into(),
+                position: Duration::from_secs(280),
+            },
+        );
+        app.command_line.clear_message();
+        app.perform(Action::NextBookmark).await;
+        assert!(!app.should_quit());
+        assert_eq!(
+            prompt(&mut app),
+            "jumping to a position is not supported by this player"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_app_initialization() {
+        let player = mock_player(70);
+        let mut app = test_app(player).await;
+        assert_eq!(app.get_volume(), 50);
+        assert!(!app.is_muted());
+
+        app.update().await.unwrap();
+        assert_eq!(app.get_volume(), 70);
+    }
+
+    #[tokio::test]
+    async fn test_ui_rendering() {
+        let player = mock_player(70);
+        let mut app = test_app(player).await;
+        app.update().await.unwrap();
+
+        let backend = TestBackend::new(120, 40);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let content = format!("{:?}", buffer).to_uppercase();
+        assert!(content.contains("TEST"));
+        assert!(content.contains("SONG"));
+        assert!(content.contains("ARTIST"));
+    }
+
+    #[tokio::test]
+    async fn demo_player_renders_track_and_lyrics() {
+        let mut lyrics_manager = LyricsManager::new(1);
+        lyrics_manager.add_provider(Box::new(DemoLyricsProvider));
+        let player = Box::new(amcli_core::player::demo::DemoPlayer::new());
+        player.pause().await.unwrap();
+        let mut app =
+            App::with_player_config_and_lyrics_manager(player, test_config(), lyrics_manager)
+                .await
+                .unwrap();
+
+        app.update().await.unwrap();
+        while app.lyrics_task.is_running() {
+            tokio::task::yield_now().await;
+            app.update().await.unwrap();
+        }
+        assert!(app.current_lyrics.is_some());
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer()).to_uppercase();
+        assert!(content.contains("NEON TIDE"));
+        assert!(content.contains("THE VELVET CIRCUIT"));
+    }
+
+    #[tokio::test]
+    async fn a_newer_release_shows_in_the_chassis_bar() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update_task = Some(tokio::spawn(async { Ok(Some("v9.9.0".to_string())) }));
+        while !app.update_task.as_ref().unwrap().is_finished() {
+            tokio::task::yield_now().await;
+        }
+        app.update().await.unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("UPDATE AVAILABLE v9.9.0"));
+    }
+
+    #[tokio::test]
+    async fn the_focus_timer_plays_pauses_for_breaks_and_shows_in_the_chassis_bar() {
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        app.player.pause().await.unwrap();
+        app.perform(Action::FocusTimer).await;
+        app.update().await.unwrap();
+        assert_eq!(app.playback_state, PlaybackState::Playing);
+
+        // The bottom edge is under the command line while it has a message
+        app.command_line.clear_message();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("◷ FOCUS 2"));
+
+        // A finished work interval pauses for the break
+        let started = Instant::now() - Duration::from_secs(25 * 60);
+        app.focus_timer = Some(FocusTimer::start(&app.config.focus, started));
+        app.update().await.unwrap();
+        app.update().await.unwrap();
+        assert!(app
+            .focus_timer
+            .as_ref()
+            .unwrap()
+            .describe(Instant::now(), false)
+            .starts_with("BREAK 05:00"));
+        assert_eq!(app.playback_state, PlaybackState::Paused);
+
+        app.perform(Action::FocusTimer).await;
+        assert!(app.focus_timer.is_none());
+    }
+
+    #[tokio::test]
+    async fn stations_are_picked_from_the_panel_or_by_name() {
+        let mut app = test_app(mock_player(70)).await;
+        app.config.stations = vec![
+            Station {
+                name: "Radio Paradise".into(),
+                url: "https://stream.radioparadise.com/flac".into(),
+            },
+            Station {
+                name: "SomaFM Groove Salad".into(),
+                url: "https://ice1.somafm.com/groovesalad-128-mp3".into(),
+            },
+        ];
+
+        app.perform(Action::Stations).await;
+        assert!(app.is_stations_open());
+        app.stations_move(1);
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("[ RADIO 2 ]"));
+        assert!(content.contains("SomaFM Groove Salad  https://ice1.somafm.com"));
+
+        app.play_selected_station().await;
+        assert!(!app.is_stations_open());
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("Tuned in to SomaFM Groove Salad"));
+
+        app.execute_command(Command::Station("paradise".into()))
+            .await
+            .unwrap();
+        assert!(app
+            .execute_command(Command::Station("kexp".into()))
+            .await
+            .is_err());
+        // The same stream isn't saved twice under another name
+        let err = app
+            .execute_command(Command::AddStation {
+                url: "https://stream.radioparadise.com/flac".into(),
+                name: "RP".into(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Already saved as \"Radio Paradise\"");
+    }
+
+    #[tokio::test]
+    async fn the_file_browser_previews_and_queues_local_files() {
+        let root = std::env::temp_dir().join(format!("amcli-app-browser-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("Kind of Blue")).unwrap();
+        std::fs::write(root.join("Kind of Blue/So What.flac"), b"").unwrap();
+        let mut app = test_app(mock_player(70)).await;
+
+        // Only the internal player plays local files
+        app.perform(Action::FileBrowser).await;
+        assert!(!app.is_file_browser_open());
+
+        app.config.player.backend = amcli_core::config::PlayerBackend::Internal;
+        app.config.player.browse_roots = vec![root.clone()];
+        app.perform(Action::FileBrowser).await;
+        app.file_browser_open().await;
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("♪ So What.flac"));
+        assert!(content.contains("Artist Test Artist"));
+
+        app.file_browser_up().await;
+        app.file_browser_enqueue().await;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("▸ Kind of Blue/"));
+        assert!(content.contains("Added 1 track to the queue"));
+        app.close_file_browser();
+        assert!(!app.is_file_browser_open());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn f12_shows_render_and_poll_timings() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        app.record_update_time(Duration::from_millis(40));
+        app.perform(Action::DebugOverlay).await;
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("DEBUG"));
+        assert!(content.contains("40ms avg 40ms max 40ms ×1"));
+
+        app.perform(Action::DebugOverlay).await;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(!format!("{:?}", terminal.backend().buffer()).contains("DEBUG"));
+    }
+
+    #[tokio::test]
+    async fn frames_are_only_redrawn_after_something_changes() {
+        let mut app = test_app(mock_player(70)).await;
+        app.skip_splash();
+        // The ticker would redraw every tenth of a second while playing
+        app.config.ui.ticker = false;
+        assert!(app.needs_redraw());
+        assert!(!app.needs_redraw());
+
+        app.update().await.unwrap();
+        assert!(app.needs_redraw());
+        assert!(!app.needs_redraw());
+
+        app.perform(Action::ToggleHelp).await;
+        assert!(app.needs_redraw());
+        assert!(!app.needs_redraw());
+    }
+
+    #[tokio::test]
+    async fn a_playing_track_redraws_without_the_ticker() {
+        let mut app = test_app(mock_player(70)).await;
+        app.skip_splash();
+        app.config.ui.ticker = false;
+        app.update().await.unwrap();
+        app.playback_state = PlaybackState::Playing;
+        assert!(app.needs_redraw());
+        assert!(!app.needs_redraw());
+
+        // A second on, the time label and bar have moved though nothing polled
+        app.position_sampled_at -= Duration::from_secs(1);
+        assert!(app.needs_redraw());
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("151s/300s"));
+    }
+
+    #[tokio::test]
+    async fn closing_settings_requests_one_full_repaint() {
+        let player = mock_player(70);
+        let mut app = test_app(player).await;
+
+        app.toggle_settings_menu().await;
+        app.close_settings();
+
+        assert!(app.take_needs_full_repaint());
+        assert!(!app.take_needs_full_repaint());
+    }
+
+    #[tokio::test]
+    async fn startup_restores_the_session_then_opens_the_configured_view() {
+        let mut app = test_app(mock_player(70)).await;
+        let session = Session {
+            theme: Some("green vfd".into()),
+            show_queue: false,
+            big_text: true,
+            focus: StartupView::Lyrics,
+        };
+        app.config.startup.view = StartupView::Queue;
+        app.apply_startup(Some(session)).await;
+        assert_eq!(app.current_theme().primary, THEME_GREEN_VFD.primary);
+        assert!(app.big_text && app.show_queue);
+        assert_eq!(app.focus.current(), Panel::Queue);
+
+        let saved = app.session();
+        assert_eq!(saved.theme.as_deref(), Some("green vfd"));
+        assert_eq!(saved.focus, StartupView::Queue);
+        assert!(saved.show_queue && saved.big_text);
+    }
+
+    #[tokio::test]
+    async fn picking_a_profile_quits_to_restart_with_it() {
+        let mut app = test_app(mock_player(70)).await;
+        assert!(app
+            .execute_command(Command::Profile("../etc".into()))
+            .await
+            .is_err());
+        app.execute_command(Command::Profile("default".into()))
+            .await
+            .unwrap();
+        assert!(!app.should_quit());
+
+        app.profiles = vec!["chill".into(), "work".into()];
+        app.refresh_settings_menu();
+        app.settings_menu.open();
+        app.settings_navigate_down();
+        app.settings_adjust(-1).await.unwrap();
+        assert!(app.should_quit());
+        assert_eq!(app.take_profile_switch(), Some(Some("work".into())));
+        assert_eq!(app.take_profile_switch(), None);
+    }
+
+    #[tokio::test]
+    async fn settings_tabs_scroll_and_explain_the_selection() {
+        let mut app = test_app(mock_player(70)).await;
+        let render = |app: &mut App, height| {
+            let mut terminal = Terminal::new(TestBackend::new(120, height)).unwrap();
+            terminal.draw(|f| draw(f, app)).unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+
+        app.toggle_settings_menu().await;
+        let content = render(&mut app, 40);
+        assert!(content.contains(" General ") && content.contains(" Network "));
+        assert!(content.contains("Language") && !content.contains("Album Artwork"));
+        assert!(content.contains("Language for menus"));
+
+        // Left/right off a stepper moves between tabs; Tab always does
+        app.settings_adjust(1).await.unwrap();
+        assert_eq!(app.settings_menu.tab, settings::SettingsTab::Appearance);
+        app.settings_switch_tab(1);
+        app.settings_adjust(-1).await.unwrap();
+        assert_eq!(app.settings_menu.tab, settings::SettingsTab::Appearance);
+        app.settings_switch_tab(-2);
+        assert_eq!(app.settings_menu.tab, settings::SettingsTab::Network);
+
+        // A short terminal scrolls the list to keep the selection in view
+        app.settings_menu.select_tab(settings::SettingsTab::Artwork);
+        let content = render(&mut app, 12);
+        assert!(content.contains("Album Artwork") && content.contains('▼'));
+        for _ in 0..5 {
+            app.settings_navigate_down();
+        }
+        let content = render(&mut app, 12);
+        assert!(!content.contains("Album Artwork") && content.contains('▲'));
+        assert!(content.contains("Close"));
+    }
+
+    #[tokio::test]
+    async fn crossfade_settings_only_show_when_the_player_reports_them() {
+        let mut app = test_app(mock_player(70)).await;
+        let render = |app: &mut App| {
+            let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+            terminal.draw(|f| draw(f, app)).unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+
+        app.toggle_settings_menu().await;
+        app.settings_menu.select_tab(settings::SettingsTab::Player);
+        assert!(!render(&mut app).contains("Crossfade"));
+
+        app.playback_options = Some(PlaybackOptions {
+            crossfade: false,
+            crossfade_secs: 6,
+            sound_check: true,
+        });
+        app.refresh_settings_menu();
+        let content = render(&mut app);
+        assert!(content.contains("Crossfade") && content.contains("Sound Check"));
+        assert!(!content.contains("6s"));
+
+        app.playback_options = Some(PlaybackOptions {
+            crossfade: true,
+            crossfade_secs: 6,
+            sound_check: true,
+        });
+        app.refresh_settings_menu();
+        assert!(render(&mut app).contains("◀ 6s ▶"));
+    }
+
+    #[tokio::test]
+    async fn autoplay_is_read_and_toggled_from_the_player_tab() {
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        app.toggle_settings_menu().await;
+        assert_eq!(app.autoplay, Some(true));
+        app.settings_menu.select_tab(settings::SettingsTab::Player);
+        while !matches!(
+            app.settings_menu.get_selected_item(),
+            Some(settings::SettingsItem::Autoplay { .. })
+        ) {
+            app.settings_menu.navigate_down();
+        }
+        app.settings_select().await.unwrap();
+        assert_eq!(app.autoplay, Some(false));
+        assert!(!app.player.get_autoplay().await.unwrap());
+
+        // Hidden when the player can't report it
+        let mut app = test_app(mock_player(70)).await;
+        app.toggle_settings_menu().await;
+        app.settings_menu.select_tab(settings::SettingsTab::Player);
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(!format!("{:?}", terminal.backend().buffer()).contains("Autoplay"));
+    }
+
+    #[tokio::test]
+    async fn repeat_follows_the_player_and_cycles_from_its_mode() {
+        let mut app = test_app(mock_player(70)).await;
+        // Set to one here, then turned off in the Music app
+        app.current_repeat_mode = RepeatMode::One;
+        app.update().await.unwrap();
+        assert_eq!(app.get_repeat_mode(), RepeatMode::Off);
+
+        app.perform(Action::CycleRepeat).await;
+        assert_eq!(app.get_repeat_mode(), RepeatMode::All);
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("↻ ALL"));
+    }
+
+    #[tokio::test]
+    async fn long_items_get_podcast_seek_steps_and_labels() {
+        let mut track = test_track("Episode 12");
+        track.duration = Duration::from_secs(60 * 60);
+        let player = Box::new(MockPlayer {
+            volume: 70,
+            artwork_url: None,
+            crossfading: false,
+            track,
+        });
+        let mut app = test_app(player).await;
+        app.update().await.unwrap();
+        app.playback_state = PlaybackState::Paused;
+        let polled = Duration::from_secs(150);
+
+        app.perform(Action::SeekForward).await;
+        assert_eq!(
+            app.interpolated_position(),
+            Some(polled + Duration::from_secs(30))
+        );
+        app.perform(Action::SeekBackward).await;
+        assert_eq!(
+            app.interpolated_position(),
+            Some(polled + Duration::from_secs(15))
+        );
+        let hints = hint_labels(&app, false);
+        assert!(hints.contains(&("SKIP 30".to_string(), "→".to_string())));
+        assert!(hints.contains(&("BACK 15".to_string(), "←".to_string())));
+
+        app.config.controls.longform.enabled = false;
+        assert!(hint_labels(&app, false)
+            .iter()
+            .any(|(label, _)| *label == "SKIP"));
+    }
+
+    #[tokio::test]
+    async fn transport_commands_show_before_the_next_poll() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        app.playback_state = PlaybackState::Paused;
+        let polled = Duration::from_secs(150);
+
+        app.perform(Action::SeekForward).await;
+        assert_eq!(
+            app.interpolated_position(),
+            Some(polled + Duration::from_secs(5))
+        );
+        app.perform(Action::GoToStart).await;
+        assert_eq!(app.interpolated_position(), Some(Duration::ZERO));
+        app.perform(Action::SeekBackward).await;
+        assert_eq!(app.interpolated_position(), Some(Duration::ZERO));
+        app.execute_command(Command::Seek(Duration::from_secs(60)))
+            .await
+            .unwrap();
+        assert_eq!(app.interpolated_position(), Some(Duration::from_secs(60)));
+
+        app.perform(Action::TogglePlayback).await;
+        assert_eq!(app.playback_state, PlaybackState::Playing);
+        app.perform(Action::TogglePlayback).await;
+        assert_eq!(app.playback_state, PlaybackState::Paused);
+        app.perform(Action::NextTrack).await;
+        assert_eq!(app.interpolated_position(), Some(Duration::ZERO));
+
+        // The poll has the last word
+        app.update().await.unwrap();
+        assert_eq!(app.get_current_track().unwrap().position, polled);
+    }
+
+    #[tokio::test]
+    async fn next_track_clears_artwork_immediately() {
+        let player = mock_player(70);
+        let mut app = test_app(player).await;
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([255, 255, 255, 255])));
+
+        app.current_artwork_url = Some("old-artwork".into());
+        app.artwork_protocol = Some(app.artwork_converter.create_protocol(img));
+
+        app.next_track().await.unwrap();
+
+        assert!(app.current_artwork_url.is_none());
+        assert!(app.artwork_protocol.is_none());
+        assert!(app.is_loading_artwork);
+        assert!(app.take_needs_full_repaint());
+    }
+
+    #[tokio::test]
+    async fn resizing_redraws_at_once_and_reencodes_the_cover() {
+        let mut app = test_app(mock_player(70)).await;
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([255, 255, 255, 255])));
+        app.current_artwork_url = Some("cover".into());
+        app.artwork_protocol = Some(app.artwork_converter.create_protocol(img));
+        app.needs_redraw();
+
+        app.handle_resize();
+
+        assert!(app.needs_redraw());
+        assert!(app.take_needs_full_repaint());
+        // Not in the cache, so the next update loads it again for the new size
+        assert!(app.artwork_protocol.is_none());
+        assert!(app.current_artwork_url.is_none());
+    }
+
+    #[tokio::test]
+    async fn animated_artwork_cycles_frames_until_cleared() {
+        let mut app = test_app(mock_player(70)).await;
+        let frame = |shade, ms| AnimationFrame {
+            image: DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([shade, 0, 0, 255]))),
+            delay: Duration::from_millis(ms),
+        };
+        app.start_artwork_animation(vec![frame(0, 0), frame(255, 60_000)]);
+        assert_eq!(app.artwork_frames.len(), 1);
+
+        app.advance_artwork_animation();
+        assert_eq!(app.artwork_frame_delay, Duration::from_secs(60));
+        assert_eq!(app.artwork_frames[0].1, Duration::ZERO);
+
+        // Holds the frame until its delay passes
+        app.advance_artwork_animation();
+        assert_eq!(app.artwork_frame_delay, Duration::from_secs(60));
+
+        app.next_track().await.unwrap();
+        assert!(app.artwork_frames.is_empty());
+    }
+
+    #[tokio::test]
+    async fn track_change_clears_stale_artwork_even_without_url_change() {
+        let player = Box::new(MockPlayer {
+            volume: 70,
+            artwork_url: None,
+            crossfading: false,
+            track: test_track("New Song"),
+        });
+        let mut app = test_app(player).await;
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([255, 255, 255, 255])));
+
+        app.current_track = Some(test_track("Old Song"));
+        app.current_artwork_url = None;
+        app.artwork_protocol = Some(app.artwork_converter.create_protocol(img));
+
+        app.update().await.unwrap();
+
+        assert_eq!(
+            app.current_track.as_ref().map(|track| track.name.as_str()),
+            Some("New Song")
+        );
+        assert!(app.current_artwork_url.is_none());
+        assert!(app.artwork_protocol.is_none());
+        assert!(!app.is_loading_artwork);
+        assert!(app.take_needs_full_repaint());
+    }
+
+    #[tokio::test]
+    async fn boot_splash_covers_the_ui_until_skipped() {
+        let mut config = test_config();
+        config.ui.boot_splash = true;
+        let mut app = App::with_player_config_and_lyrics_manager(
+            mock_player(70),
+            config,
+            LyricsManager::new(1),
+        )
+        .await
+        .unwrap();
+        app.update().await.unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(!content.contains("TEST SONG"));
+
+        app.skip_splash();
+        assert!(!app.is_splash_active());
+        assert!(app.take_needs_full_repaint());
+
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("TEST SONG"));
+    }
+
+    #[tokio::test]
+    async fn losing_focus_freezes_animation_and_regaining_it_repaints() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        app.take_needs_full_repaint();
+
+        app.set_focused(false);
+        let frame = app.animation_frame;
+        app.update().await.unwrap();
+        assert_eq!(app.animation_frame, frame);
+        assert!(app.current_track.is_some());
+
+        app.set_focused(true);
+        assert!(app.take_needs_full_repaint());
+        app.update().await.unwrap();
+        assert_eq!(app.animation_frame, frame.wrapping_add(1));
+    }
+
+    #[tokio::test]
+    async fn paused_and_stopped_states_show_on_the_display() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("▶ 150s/300s"));
+        assert!(!content.contains("STANDBY"));
+
+        app.playback_state = PlaybackState::Paused;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        assert!(format!("{:?}", buffer).contains("⏸"));
+        // The ticker holds the reported position while paused
+        assert!(format!("{:?}", buffer).contains("02:30.0"));
+        let title = buffer
+            .content()
+            .iter()
+            .find(|cell| cell.symbol() == "T" && cell.bg == app.current_theme().dim)
+            .unwrap();
+        assert!(title.modifier.contains(Modifier::DIM));
+
+        app.playback_state = PlaybackState::Stopped;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("STANDBY"));
+    }
+
+    #[tokio::test]
+    async fn reduced_motion_pins_marquee_and_drops_scanlines() {
+        let mut config = test_config();
+        config.ui.reduced_motion = true;
+        config.ui.clock = amcli_core::config::ClockFormat::Off;
+        let mut app = App::with_player_config_and_lyrics_manager(
+            Box::new(MockPlayer {
+                volume: 70,
+                artwork_url: None,
+                crossfading: false,
+                track: test_track("A Title Long Enough To Overflow The Narrow Metadata Column"),
+            }),
+            config,
+            LyricsManager::new(1),
+        )
+        .await
+        .unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 30)).unwrap();
+        app.update().await.unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let first = format!("{:?}", terminal.backend().buffer());
+        for _ in 0..3 {
+            app.update().await.unwrap();
+        }
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let later = format!("{:?}", terminal.backend().buffer());
+
+        assert_eq!(first, later);
+        assert!(!later.contains("Rgb(5, 5, 5)"));
+    }
+
+    #[tokio::test]
+    async fn volume_changes_flash_a_gauge_and_shift_steps_finely() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+
+        let plus = KeyEvent::new(KeyCode::Char('+'), KeyModifiers::SHIFT);
+        let action = app.resolve_key(plus).unwrap();
+        app.perform(action).await;
+        assert_eq!(app.get_volume(), 71);
+        app.perform(Action::VolumeDown).await;
+        assert_eq!(app.get_volume(), 66);
+
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("VOL 66%"));
+
+        app.needs_redraw();
+        app.volume_shown_at = Some(Instant::now() - VOLUME_OVERLAY_DURATION);
+        assert!(app.needs_redraw());
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(!format!("{:?}", terminal.backend().buffer()).contains("VOL 66%"));
+    }
+
+    #[tokio::test]
+    async fn command_line_applies_volume_and_reports_errors() {
+        let mut app = test_app(mock_player(70)).await;
+
+        app.open_command_line();
+        "vol 40".chars().for_each(|c| app.command_line.push(c));
+        app.submit_command().await.unwrap();
+        assert_eq!(app.get_volume(), 40);
+        assert!(!app.is_command_mode());
+
+        app.open_command_line();
+        "bogus".chars().for_each(|c| app.command_line.push(c));
+        app.submit_command().await.unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("Unknown command: bogus"));
+    }
+
+    #[tokio::test]
+    async fn capital_g_opens_a_prefilled_seek_prompt() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = test_app(mock_player(70)).await;
+        let key = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(app.resolve_key(key), Some(Action::JumpToTime));
+        app.perform(Action::JumpToTime).await;
+        assert!(app.is_command_mode());
+        assert_eq!(prompt(&mut app), ":seek");
+    }
+
+    #[tokio::test]
+    async fn pending_chord_is_shown_until_completed() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = test_app(mock_player(70)).await;
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+
+        assert_eq!(app.resolve_key(g), None);
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("g …"));
+
+        assert_eq!(app.resolve_key(g), Some(Action::GoToStart));
+        app.perform(Action::GoToStart).await;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(!format!("{:?}", terminal.backend().buffer()).contains("g …"));
+    }
+
+    #[tokio::test]
+    async fn control_hints_follow_keymap_and_context() {
+        let mut config = test_config();
+        config
+            .keybindings
+            .bindings
+            .insert("next".into(), vec!["n".into()]);
+        let mut app = App::with_player_config_and_lyrics_manager(
+            mock_player(70),
+            config,
+            LyricsManager::new(1),
+        )
+        .await
+        .unwrap();
+
+        let hints = hint_labels(&app, false);
+        assert!(hints.contains(&("SKIP".to_string(), "n".to_string())));
+        assert!(hints.contains(&("PLAY".to_string(), "SPC".to_string())));
+
+        app.toggle_settings_menu().await;
+        let hints = hint_labels(&app, false);
+        assert_eq!(hints[0], ("MOVE".to_string(), "↑↓".to_string()));
+
+        app.close_settings();
+        app.open_command_line();
+        assert_eq!(
+            hint_labels(&app, true)[0],
+            ("実行".to_string(), "⏎".to_string())
+        );
     }
 
-    async fn test_app(player: Box<dyn MediaPlayer>) -> App {
-        App::with_player_config_and_lyrics_manager(
-            player,
-            crate::config::Config::default(),
+    #[tokio::test]
+    async fn narrow_terminals_shorten_japanese_labels() {
+        let mut config = test_config();
+        config.general.language = amcli_core::config::Language::Japanese;
+        let mut app = App::with_player_config_and_lyrics_manager(
+            mock_player(70),
+            config,
             LyricsManager::new(1),
         )
         .await
-        .unwrap()
+        .unwrap();
+        let render = |app: &mut App, width| {
+            let mut terminal = Terminal::new(TestBackend::new(width, 30)).unwrap();
+            terminal.draw(|f| draw(f, app)).unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+
+        assert!(render(&mut app, 160).contains("▶ 再生"));
+        let narrow = render(&mut app, 100);
+        assert!(!narrow.contains("▶ 再生") && narrow.contains("再生"));
+
+        app.toggle_settings_menu().await;
+        app.settings_menu.select_tab(settings::SettingsTab::Artwork);
+        assert!(render(&mut app, 80).contains("Album Artwork"));
+        let narrow = render(&mut app, 40);
+        assert!(!narrow.contains("Album Artwork") && narrow.contains("アルバム"));
     }
 
     #[tokio::test]
-    async fn test_app_initialization() {
-        let player = mock_player(70);
-        let mut app = test_app(player).await;
-        assert_eq!(app.get_volume(), 50);
-        assert!(!app.is_muted());
+    async fn big_text_shows_the_current_lyric_over_the_title() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        app.perform(Action::ToggleBigText).await;
+        assert_eq!(app.big_text_line(), Some("Test Song"));
+
+        app.current_lyrics = Some(Lyrics {
+            lines: vec![amcli_core::lyrics::LyricLine {
+                text: "La la".into(),
+                timestamp: Duration::ZERO,
+            }],
+            metadata: Default::default(),
+            offset: 0,
+        });
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("█     ██"));
+        assert!(!content.contains("TEST SONG"));
+    }
+
+    #[tokio::test]
+    async fn i_opens_a_bio_of_the_playing_artist() {
+        let mut app = test_app(mock_player(70)).await;
+        app.perform(Action::ArtistInfo).await;
+        assert!(!app.is_artist_panel_open());
 
         app.update().await.unwrap();
-        assert_eq!(app.get_volume(), 70);
+        app.perform(Action::ArtistInfo).await;
+        app.artist_info_task.take().unwrap().abort();
+        let panel = app.artist_panel.as_mut().unwrap();
+        assert_eq!(panel.artist, "Test Artist");
+        panel.state = ArtistState::Found(ArtistInfo {
+            name: "Test Artist".into(),
+            summary: "Test Artist is a band.".into(),
+            image_url: None,
+            similar: vec!["Other Artist".into()],
+            source: "Wikipedia",
+        });
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("TEST ARTIST"));
+        assert!(content.contains("Test Artist is a band."));
+        assert!(content.contains("YOU MIGHT ALSO LIKE: Other Artist"));
+
+        app.perform(Action::ArtistInfo).await;
+        assert!(!app.is_artist_panel_open());
     }
 
     #[tokio::test]
-    async fn test_ui_rendering() {
-        let player = mock_player(70);
-        let mut app = test_app(player).await;
+    async fn play_similar_needs_a_musickit_token() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        app.perform(Action::PlaySimilar).await;
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("Set musickit.developer_token to play similar songs"));
+    }
+
+    #[tokio::test]
+    async fn speed_keys_step_through_playback_rates() {
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
         app.update().await.unwrap();
+        app.perform(Action::RateDown).await;
+        app.perform(Action::RateDown).await;
+        assert_eq!(app.playback_rate, Some(0.75));
+        for _ in 0..6 {
+            app.perform(Action::RateUp).await;
+        }
+        assert_eq!(app.playback_rate, Some(2.0));
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("| 2× "));
 
-        let backend = TestBackend::new(120, 40);
-        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = test_app(mock_player(70)).await;
+        app.perform(Action::RateUp).await;
+        assert_eq!(app.playback_rate, None);
+    }
 
+    #[tokio::test]
+    async fn bookmarks_are_added_by_name_and_jumped_between() {
+        let path =
+            std::env::temp_dir().join(format!("amcli-app-bookmarks-{}.json", std::process::id()));
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        app.bookmarks = Bookmarks::load(path.clone()).await.unwrap();
+        app.player.pause().await.unwrap();
+        app.update().await.unwrap();
+
+        app.command_line.open_with("bookmark Verse");
+        app.submit_command().await.unwrap();
+        app.player
+            .set_position(Duration::from_secs(5))
+            .await
+            .unwrap();
+        app.update().await.unwrap();
+        app.perform(Action::NextBookmark).await;
+        let track = app.player.get_current_track().await.unwrap().unwrap();
+        assert_eq!(track.position.as_secs(), 38);
+
+        app.update().await.unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
         terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("◆ Verse"));
 
-        let buffer = terminal.backend().buffer();
-        let content = format!("{:?}", buffer).to_uppercase();
-        assert!(content.contains("TEST"));
-        assert!(content.contains("SONG"));
-        assert!(content.contains("ARTIST"));
+        app.perform(Action::PreviousBookmark).await;
+        let track = app.player.get_current_track().await.unwrap().unwrap();
+        assert_eq!(track.position.as_secs(), 38);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn upcoming_lyric_gets_its_own_slot_or_highlight() {
+        let lyrics = Lyrics {
+            lines: ["First", "", "Second", "Third"]
+                .iter()
+                .enumerate()
+                .map(|(i, text)| amcli_core::lyrics::LyricLine {
+                    text: text.to_string(),
+                    timestamp: Duration::from_secs(i as u64 * 10),
+                })
+                .collect(),
+            metadata: Default::default(),
+            offset: 0,
+        };
+        let track = Track {
+            position: Duration::from_secs(5),
+            ..test_track("Test Song")
+        };
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        let mut render = |show_next| {
+            terminal
+                .draw(|f| {
+                    let area = f.area();
+                    draw_lyrics(
+                        f,
+                        area,
+                        track.position,
+                        Some(&lyrics),
+                        false,
+                        THEME_AMBER_RETRO,
+                        false,
+                        0,
+                        0,
+                        show_next,
+                    )
+                })
+                .unwrap()
+                .buffer
+                .clone()
+        };
+
+        let buffer = render(Some(ShowNextStyle::Slot));
+        assert!(format!("{:?}", buffer).contains("NEXT: Second"));
+
+        // "Second" sits two rows below the current line
+        let buffer = render(Some(ShowNextStyle::Highlight));
+        let second = buffer
+            .content()
+            .iter()
+            .position(|cell| cell.symbol() == "S")
+            .unwrap();
+        assert!(buffer.content()[second].modifier.contains(Modifier::BOLD));
+        let buffer = render(None);
+        assert!(!buffer.content()[second].modifier.contains(Modifier::BOLD));
     }
 
     #[tokio::test]
-    async fn closing_settings_requests_one_full_repaint() {
-        let player = mock_player(70);
-        let mut app = test_app(player).await;
+    async fn slash_searches_lyrics_and_enter_seeks_to_the_match() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        app.current_lyrics = Some(Lyrics {
+            lines: ["Intro", "Hold on", "Verse", "hold on again"]
+                .iter()
+                .enumerate()
+                .map(|(i, text)| amcli_core::lyrics::LyricLine {
+                    text: text.to_string(),
+                    timestamp: Duration::from_secs(i as u64 * 100),
+                })
+                .collect(),
+            metadata: Default::default(),
+            offset: 0,
+        });
+        app.focus.set_visible(vec![Panel::Metadata, Panel::Lyrics]);
+
+        // 150s into the track the current line is "Hold on", index 1
+        app.perform(Action::SearchLyrics).await;
+        assert!(app.command_line.is_search());
+        assert_eq!(app.focus.current(), Panel::Lyrics);
+        "HOLD".chars().for_each(|c| app.command_line.push(c));
+        app.submit_command().await.unwrap();
+        assert_eq!(app.lyrics_scroll, 0);
+
+        app.perform(Action::LyricsNextMatch).await;
+        assert_eq!(app.lyrics_scroll, 2);
+        app.perform(Action::LyricsSeek).await;
+        assert_eq!(
+            app.current_track.as_ref().unwrap().position,
+            Duration::from_secs(300)
+        );
+        assert_eq!(app.lyrics_scroll, 0);
 
-        app.toggle_settings_menu();
-        app.close_settings();
+        app.perform(Action::SearchLyrics).await;
+        "chorus".chars().for_each(|c| app.command_line.push(c));
+        app.submit_command().await.unwrap();
+        assert!(app.lyrics_search.is_none());
+    }
 
-        assert!(app.take_needs_full_repaint());
-        assert!(!app.take_needs_full_repaint());
+    #[tokio::test]
+    async fn navigation_moves_focus_and_scrolls_lyrics() {
+        let mut app = test_app(mock_player(70)).await;
+        app.current_lyrics = Some(Lyrics {
+            lines: Vec::new(),
+            metadata: Default::default(),
+            offset: 0,
+        });
+        app.focus
+            .set_visible(vec![Panel::Artwork, Panel::Metadata, Panel::Lyrics]);
+        assert!(!app.focus.is_engaged());
+
+        app.navigate_down();
+        assert_eq!(app.focus.current(), Panel::Lyrics);
+        app.navigate_down();
+        app.navigate_down();
+        assert_eq!(app.lyrics_scroll, 2);
+        assert_eq!(
+            hint_labels(&app, false)[1],
+            ("SCROLL".to_string(), "k/j".to_string())
+        );
+
+        app.navigate_right();
+        assert_eq!(app.focus.current(), Panel::Artwork);
+        assert_eq!(app.lyrics_scroll, 0);
     }
 
     #[tokio::test]
-    async fn next_track_clears_artwork_immediately() {
-        let player = mock_player(70);
-        let mut app = test_app(player).await;
-        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([255, 255, 255, 255])));
+    async fn sound_check_shows_in_the_format_line_and_toggles() {
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        app.update().await.unwrap();
+        assert_eq!(
+            app.normalization_label().as_deref(),
+            Some("SOUND CHECK ON -4.1 dB")
+        );
 
-        app.current_artwork_url = Some("old-artwork".into());
-        app.artwork_protocol = Some(app.artwork_converter.create_protocol(img));
+        app.perform(Action::ToggleNormalization).await;
+        assert_eq!(app.playback_options.map(|o| o.sound_check), Some(false));
+        assert_eq!(
+            app.normalization_label().as_deref(),
+            Some("SOUND CHECK OFF -4.1 dB")
+        );
 
-        app.next_track().await.unwrap();
+        // Music can't say whether it's on until its settings are read
+        app.normalization = Some(Normalization {
+            kind: NormalizationKind::SoundCheck,
+            enabled: None,
+            gain_db: None,
+        });
+        app.playback_options = None;
+        assert_eq!(app.normalization_label().as_deref(), Some("SOUND CHECK"));
+    }
 
-        assert!(app.current_artwork_url.is_none());
-        assert!(app.artwork_protocol.is_none());
-        assert!(app.is_loading_artwork);
-        assert!(app.take_needs_full_repaint());
+    #[tokio::test]
+    async fn replay_gain_toggles_in_players_that_apply_it() {
+        let mut app = test_app(mock_player(70)).await;
+        app.normalization = Some(Normalization {
+            kind: NormalizationKind::ReplayGain,
+            enabled: Some(true),
+            gain_db: Some(-6.2),
+        });
+        app.perform(Action::ToggleNormalization).await;
+        assert_eq!(
+            app.normalization_label().as_deref(),
+            Some("REPLAYGAIN OFF -6.2 dB")
+        );
+        app.perform(Action::ToggleNormalization).await;
+        assert_eq!(
+            app.normalization_label().as_deref(),
+            Some("REPLAYGAIN ON -6.2 dB")
+        );
     }
 
     #[tokio::test]
-    async fn track_change_clears_stale_artwork_even_without_url_change() {
-        let player = Box::new(MockPlayer {
+    async fn balance_keys_pan_and_show_the_meter() {
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        app.update().await.unwrap();
+        assert_eq!(app.balance, Some(0.0));
+        for _ in 0..3 {
+            app.perform(Action::BalanceLeft).await;
+        }
+        assert_eq!(app.balance.map(format_balance).as_deref(), Some("L30"));
+        for _ in 0..3 {
+            app.perform(Action::BalanceRight).await;
+        }
+        assert_eq!(app.balance, Some(0.0));
+        for _ in 0..12 {
+            app.perform(Action::BalanceRight).await;
+        }
+        assert_eq!(app.balance, Some(1.0));
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("L ────┼───● R"));
+
+        let mut app = test_app(mock_player(70)).await;
+        app.perform(Action::BalanceRight).await;
+        assert_eq!(app.balance, None);
+    }
+
+    #[tokio::test]
+    async fn favorites_are_starred_listed_and_played() {
+        let path =
+            std::env::temp_dir().join(format!("amcli-app-favorites-{}.json", std::process::id()));
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        app.favorites = Favorites::load(path.clone()).await.unwrap();
+        app.update().await.unwrap();
+        let starred = app.current_track.clone().unwrap();
+        app.perform(Action::ToggleFavorite).await;
+        assert!(progress_tags(&app, &starred).contains(&"★".to_string()));
+
+        app.perform(Action::NextTrack).await;
+        app.update().await.unwrap();
+        let track = app.current_track.clone().unwrap();
+        assert_ne!(track.name, starred.name);
+        assert!(!progress_tags(&app, &track).contains(&"★".to_string()));
+
+        app.perform(Action::Favorites).await;
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("FAVORITES 1") && content.contains(&starred.name));
+
+        app.play_selected_favorite().await.unwrap();
+        assert!(!app.is_favorites_open());
+        let playing = app.player.get_current_track().await.unwrap().unwrap();
+        assert_eq!(playing.name, starred.name);
+
+        app.perform(Action::Favorites).await;
+        app.remove_selected_favorite().await.unwrap();
+        assert!(app.favorites.items().is_empty());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn notes_are_edited_from_the_command_line_and_flagged() {
+        let path =
+            std::env::temp_dir().join(format!("amcli-app-notes-{}.json", std::process::id()));
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        app.notes = Notes::load(path.clone()).await.unwrap();
+        app.update().await.unwrap();
+        let track = app.current_track.clone().unwrap();
+
+        app.perform(Action::EditNote).await;
+        assert_eq!(prompt(&mut app), ":note");
+        app.command_line.open_with("note opener, 124 BPM");
+        app.submit_command().await.unwrap();
+        assert!(progress_tags(&app, &track).contains(&"✎ NOTE".to_string()));
+
+        // Editing starts from the saved note, and clearing it removes it
+        app.perform(Action::EditNote).await;
+        assert_eq!(prompt(&mut app), ":note opener, 124 BPM");
+        app.command_line.open_with("note ");
+        app.submit_command().await.unwrap();
+        assert!(!progress_tags(&app, &track).contains(&"✎ NOTE".to_string()));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn a_crossfade_shows_on_the_progress_bar() {
+        let track = test_track("Outro");
+        let app = test_app(Box::new(MockPlayer {
             volume: 70,
             artwork_url: None,
-            track: test_track("New Song"),
-        });
-        let mut app = test_app(player).await;
-        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([255, 255, 255, 255])));
+            crossfading: true,
+            track: track.clone(),
+        }))
+        .await;
+        assert!(progress_tags(&app, &track).contains(&"⇄ CROSSFADE".to_string()));
 
-        app.current_track = Some(test_track("Old Song"));
-        app.current_artwork_url = None;
-        app.artwork_protocol = Some(app.artwork_converter.create_protocol(img));
+        let app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        assert!(!progress_tags(&app, &track).contains(&"⇄ CROSSFADE".to_string()));
+    }
+
+    #[tokio::test]
+    async fn output_device_shows_on_the_progress_bar() {
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        for _ in 0..10 {
+            app.update().await.unwrap();
+            if app.output_device.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(app.output_device.as_deref(), Some("Living Room"));
+        let track = app.current_track.clone().unwrap();
+        assert!(progress_tags(&app, &track).contains(&"🔊 Living Room".to_string()));
 
+        app.config.ui.show_output_device = false;
+        app.output_device = None;
         app.update().await.unwrap();
+        assert!(app.output_device.is_none());
+    }
+
+    #[tokio::test]
+    async fn queue_panel_reorders_and_removes_upcoming_tracks() {
+        let mut app = test_app(Box::new(amcli_core::player::demo::DemoPlayer::new())).await;
+        let names = |app: &App| -> Vec<String> {
+            app.queue
+                .iter()
+                .flatten()
+                .map(|e| e.track.name.clone())
+                .collect()
+        };
+        app.perform(Action::ToggleQueue).await;
+        app.focus
+            .set_visible(vec![Panel::Metadata, Panel::Lyrics, Panel::Queue]);
+        app.navigate_left();
+        assert_eq!(app.focus.current(), Panel::Queue);
 
+        app.perform(Action::QueueMoveDown).await;
         assert_eq!(
-            app.current_track.as_ref().map(|track| track.name.as_str()),
-            Some("New Song")
+            names(&app),
+            ["Cathode Bloom", "Paper Satellites", "Slow Weather"]
+        );
+        assert_eq!(app.queue_selected, 1);
+        app.perform(Action::QueueRemove).await;
+        assert_eq!(names(&app), ["Cathode Bloom", "Slow Weather"]);
+        assert_eq!(app.queue_selected, 1);
+        assert_eq!(
+            hint_labels(&app, false)[1],
+            ("MOVE".to_string(), "K/J".to_string())
         );
-        assert!(app.current_artwork_url.is_none());
-        assert!(app.artwork_protocol.is_none());
-        assert!(!app.is_loading_artwork);
-        assert!(app.take_needs_full_repaint());
     }
 
     #[test]
@@ -1487,12 +6450,271 @@ mod tests {
         assert!(track_identity_changed(Some(&current), Some(&next)));
     }
 
+    #[test]
+    fn restarts_are_told_apart_from_seeks() {
+        let at = |secs| Track {
+            position: Duration::from_secs(secs),
+            ..test_track("Same Song")
+        };
+        // Repeat one wrapping around a 300s track
+        assert!(track_restarted(Some(&at(297)), Some(&at(1))));
+        // Back to the start from the middle
+        assert!(track_restarted(Some(&at(150)), Some(&at(0))));
+        // Seeking back a few seconds, or to later in the track
+        assert!(!track_restarted(Some(&at(12)), Some(&at(7))));
+        assert!(!track_restarted(Some(&at(150)), Some(&at(60))));
+        assert!(!track_restarted(Some(&at(0)), Some(&at(1))));
+        assert!(!track_restarted(None, Some(&at(0))));
+        // A stream's position wobbling near zero
+        let live = |secs| Track {
+            duration: Duration::ZERO,
+            ..at(secs)
+        };
+        assert!(!track_restarted(Some(&live(40)), Some(&live(2))));
+    }
+
+    #[tokio::test]
+    async fn lyrics_advance_between_polls() {
+        let mut app = test_app(mock_player(70)).await;
+        app.update().await.unwrap();
+        app.current_lyrics = Some(Lyrics {
+            lines: [149, 151]
+                .iter()
+                .map(|secs| amcli_core::lyrics::LyricLine {
+                    text: format!("at {}", secs),
+                    timestamp: Duration::from_secs(*secs),
+                })
+                .collect(),
+            metadata: Default::default(),
+            offset: 0,
+        });
+        assert_eq!(app.current_lyrics_index(), 0);
+        app.needs_redraw();
+
+        // Polled at 150s, 1.5s ago, and still playing
+        app.position_sampled_at = Instant::now() - Duration::from_millis(1500);
+        assert_eq!(app.current_lyrics_index(), 1);
+        assert!(app.needs_redraw());
+        assert_eq!(app.big_text_line(), Some("at 151"));
+    }
+
+    #[tokio::test]
+    async fn a_repeated_track_is_a_new_play() {
+        let player = Box::new(MockPlayer {
+            volume: 70,
+            artwork_url: None,
+            crossfading: false,
+            track: Track {
+                position: Duration::from_secs(1),
+                ..test_track("Test Song")
+            },
+        });
+        let mut app = test_app(player).await;
+        app.update().await.unwrap();
+        // History subscribes to the bus, so it fills in once events are handled
+        assert!(app.session_history.is_empty());
+        app.handle_events().await.unwrap();
+        assert_eq!(app.session_history.len(), 1);
+
+        // Last poll near the end; repeat one brought it back to the start
+        app.current_track.as_mut().unwrap().position = Duration::from_secs(298);
+        app.lyrics_scroll = 3;
+        app.update().await.unwrap();
+        app.handle_events().await.unwrap();
+        assert_eq!(app.session_history.len(), 2);
+        assert_eq!(app.lyrics_scroll, 0);
+
+        app.update().await.unwrap();
+        app.handle_events().await.unwrap();
+        assert_eq!(app.session_history.len(), 2);
+
+        app.publish(AppEvent::Quit);
+        app.handle_events().await.unwrap();
+        assert!(app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn restarting_from_amcli_is_a_new_play() {
+        // The player reports the track back at its start
+        let player = Box::new(MockPlayer {
+            volume: 70,
+            artwork_url: None,
+            crossfading: false,
+            track: Track {
+                position: Duration::from_secs(1),
+                ..test_track("Test Song")
+            },
+        });
+        let mut app = test_app(player).await;
+        app.update().await.unwrap();
+        app.playback_state = PlaybackState::Paused;
+        app.handle_events().await.unwrap();
+        assert_eq!(app.session_history.len(), 1);
+
+        // The jump shows at once, so the next poll can't tell it happened
+        app.current_track.as_mut().unwrap().position = Duration::from_secs(150);
+        app.perform(Action::GoToStart).await;
+        app.handle_events().await.unwrap();
+        assert_eq!(app.session_history.len(), 2);
+        app.update().await.unwrap();
+        app.handle_events().await.unwrap();
+        assert_eq!(app.session_history.len(), 2);
+
+        // Music restarts rather than going back when well into the track
+        app.current_track.as_mut().unwrap().position = Duration::from_secs(150);
+        app.previous_track().await.unwrap();
+        app.update().await.unwrap();
+        app.handle_events().await.unwrap();
+        assert_eq!(app.session_history.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn quick_switcher_searches_recent_tracks_and_the_library() {
+        let mut app = test_app(mock_player(70)).await;
+        app.library = LibraryIndex::new(vec![
+            LibraryItem::new(ItemKind::Playlist, "Focus", ""),
+            LibraryItem::new(ItemKind::Album, "Kind of Blue", "Miles Davis"),
+        ]);
+        app.update().await.unwrap();
+        app.handle_events().await.unwrap();
+
+        app.perform(Action::QuickSwitcher).await;
+        assert!(app.is_quick_switcher_open());
+        // Nothing typed yet: this session's tracks come first
+        let selected = |app: &App| app.quick_switcher.as_ref()?.selected().cloned();
+        assert_eq!(selected(&app).unwrap().name, "Test Song");
+
+        for c in "blue".chars() {
+            app.quick_switcher_push(c);
+        }
+        assert_eq!(selected(&app).unwrap().name, "Kind of Blue");
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("GO TO"));
+        assert!(content.contains("Miles Davis"));
+
+        app.quick_switcher_push('z');
+        assert!(selected(&app).is_none());
+        app.quick_switcher_backspace();
+        assert!(selected(&app).is_some());
+        app.close_quick_switcher();
+        assert!(!app.is_quick_switcher_open());
+    }
+
+    #[tokio::test]
+    async fn idle_grid_shows_recent_albums_until_something_plays() {
+        let mut config = test_config();
+        config.ui.idle_grid = true;
+        let mut app = App::with_player_config_and_lyrics_manager(
+            mock_player(70),
+            config,
+            LyricsManager::new(1),
+        )
+        .await
+        .unwrap();
+        let path =
+            std::env::temp_dir().join(format!("amcli-idle-grid-{}.json", std::process::id()));
+        app.recent_albums = RecentAlbums::load(path.clone()).await.unwrap();
+
+        let album = |name: &str| RecentAlbum {
+            artist: "Test Artist".into(),
+            album: name.into(),
+            artwork_url: format!("https://covers/{}.jpg", name),
+        };
+        let cover = || DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 8, Rgba([0, 0, 0, 255])));
+        app.idle_grid
+            .set(vec![(album("First"), cover()), (album("Second"), cover())]);
+        app.render_idle_grid();
+        app.perform(Action::NavigateRight).await;
+        assert_eq!(app.idle_grid.selected().unwrap().album, "Second");
+        assert_eq!(
+            hint_labels(&app, false)[0],
+            ("PLAY ALBUM".to_string(), "⏎".into())
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let content = format!("{:?}", terminal.backend().buffer());
+        assert!(content.contains("Second — Test Artist"));
+        assert!(!content.contains("INSERT TAPE"));
+
+        // Playing something clears the grid and files its album
+        app.update().await.unwrap();
+        assert!(!app.idle_grid.is_shown());
+        assert_eq!(app.recent_albums.albums()[0].album, "Test Album");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn artwork_overrides_follow_the_track_key() {
+        let searched = "https://is1-ssl.mzstatic.com/wrong/600x600bb.jpg";
+        let player = Box::new(MockPlayer {
+            volume: 70,
+            artwork_url: Some(searched.into()),
+            crossfading: false,
+            track: test_track("Test Song"),
+        });
+        let mut app = test_app(player).await;
+        let path = std::env::temp_dir().join(format!(
+            "amcli-app-artwork-overrides-{}.json",
+            std::process::id()
+        ));
+        app.artwork_overrides = ArtworkOverrides::load(path.clone()).await.unwrap();
+        app.update().await.unwrap();
+        assert_eq!(app.current_artwork_url.as_deref(), Some(searched));
+
+        // Marked wrong: gone from this track on the next poll
+        let key = app.track_key.clone().unwrap();
+        app.artwork_overrides.reject(&key, searched);
+        app.update().await.unwrap();
+        assert_eq!(app.current_artwork_url, None);
+
+        // A chosen cover wins over whatever the player finds. It has to load,
+        // or a failed load clears it again before the checks below
+        let cover = std::env::temp_dir().join(format!(
+            "amcli-app-artwork-override-{}.png",
+            std::process::id()
+        ));
+        RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]))
+            .save(&cover)
+            .unwrap();
+        let chosen = format!("file://{}", cover.display());
+        app.artwork_overrides.set_image(&key, &chosen);
+        app.update().await.unwrap();
+        assert_eq!(app.current_artwork_url.as_deref(), Some(chosen.as_str()));
+
+        // `:artwork album` covers the rest of the album; the track's own
+        // choice still wins until it's reset
+        app.execute_command(Command::Artwork {
+            album: true,
+            source: "https://example.com/album.jpg".into(),
+        })
+        .await
+        .unwrap();
+        app.update().await.unwrap();
+        assert_eq!(app.current_artwork_url.as_deref(), Some(chosen.as_str()));
+        app.execute_command(Command::ResetArtwork).await.unwrap();
+        app.update().await.unwrap();
+        assert_eq!(app.current_artwork_url.as_deref(), Some(searched));
+        assert!(app
+            .execute_command(Command::Artwork {
+                album: false,
+                source: "/nonexistent/cover.png".into(),
+            })
+            .await
+            .is_err());
+        let _ = tokio::fs::remove_file(&path).await;
+        let _ = tokio::fs::remove_file(&cover).await;
+    }
+
     #[tokio::test]
     async fn failed_artwork_load_clears_current_url_so_it_can_retry() {
         let missing_url = "file:///tmp/amcli-missing-artwork-for-retry-test.png";
         let player = Box::new(MockPlayer {
             volume: 70,
             artwork_url: Some(missing_url.into()),
+            crossfading: false,
             track: test_track("Test Song"),
         });
         let mut app = test_app(player).await;
@@ -1571,4 +6793,92 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn settings_skip_mosaic_tuning_while_the_mosaic_is_off() {
+        use settings::SettingsItem;
+
+        let mut config = amcli_core::config::Config::default();
+        config.artwork.mosaic = false;
+        fn values(config: &amcli_core::config::Config) -> SettingsValues<'_> {
+            SettingsValues {
+                config,
+                profiles: &[],
+                theme_index: 0,
+                total_themes: THEMES.len(),
+                offline: false,
+                playback: None,
+                autoplay: None,
+            }
+        }
+        let mut menu = SettingsMenu::new(&values(&config));
+        menu.select_tab(settings::SettingsTab::Artwork);
+        for _ in 0..5 {
+            menu.navigate_down();
+        }
+        assert!(matches!(
+            menu.get_selected_item(),
+            Some(SettingsItem::Close)
+        ));
+
+        config.artwork.mosaic = true;
+        menu.refresh(&values(&config));
+        menu.navigate_up();
+        assert!(matches!(
+            menu.get_selected_item(),
+            Some(SettingsItem::MosaicShape { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn theme_editor_previews_until_cancelled() {
+        let mut app = test_app(mock_player(70)).await;
+        app.theme_editor = Some(ThemeEditor::new(app.current_theme()));
+        app.theme_editor_adjust(-8);
+        assert_eq!(app.current_theme().primary, Color::Rgb(247, 176, 0));
+
+        app.close_theme_editor();
+        assert_eq!(app.current_theme().primary, THEME_AMBER_RETRO.primary);
+    }
+
+    #[tokio::test]
+    async fn transparent_background_applies_to_the_listed_themes() {
+        let mut app = test_app(mock_player(70)).await;
+        app.config.ui.transparent_background =
+            amcli_core::config::TransparentBackground::Themes(vec!["green vfd".into()]);
+        assert_eq!(app.current_theme().bg, COLOR_BG);
+
+        app.apply_theme(1);
+        assert_eq!(app.current_theme().bg, Color::Reset);
+        assert_eq!(app.current_theme().primary, THEME_GREEN_VFD.primary);
+    }
+
+    #[test]
+    fn saved_themes_follow_the_built_in_ones() {
+        let custom = THEME_CYAN_VFD.to_custom("Night Shift");
+        let themes = theme_list(&[custom]);
+        assert_eq!(themes.len(), THEMES.len() + 1);
+        let saved = themes[THEMES.len()];
+        assert_eq!(saved.name, "Night Shift");
+        assert_eq!(saved.accent, THEME_CYAN_VFD.accent);
+        assert!(saved.is_retro);
+        assert_eq!(
+            command::find_theme(&themes, "night shift"),
+            Some(THEMES.len())
+        );
+    }
+
+    #[test]
+    fn ticker_shows_tenths_of_a_second() {
+        assert_eq!(format_ticker(Duration::from_millis(754_321)), "12:34.3");
+        assert_eq!(format_ticker(Duration::ZERO), "00:00.0");
+    }
+
+    #[test]
+    fn settings_steps_stay_in_range() {
+        assert_eq!(step(2, -1, MOSAIC_TILE_SIZES), 2);
+        assert_eq!(step(8, 1, MOSAIC_TILE_SIZES), 9);
+        assert_eq!(step(0, -1, 0..=4), 0);
+        assert_eq!(step(4, 1, 0..=4), 4);
+    }
 }
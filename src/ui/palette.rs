@@ -0,0 +1,126 @@
+use ratatui::style::Color;
+
+use crate::ui::theme_editor::to_rgb;
+use crate::ui::{Theme, THEMES};
+use amcli_core::config::ColorDepth;
+
+// Hand-picked xterm-256 colors (primary, dim, accent, alert, bg) for the
+// built-in themes. Nearest-match tends to lose the hue of the dim shades,
+// which carry most of the VFD look. Background is 16 rather than 0, since
+// terminal color schemes remap the first 16 entries.
+const FALLBACKS: &[(&str, [u8; 5])] = &[
+    ("AMBER VFD", [214, 94, 220, 203, 16]),
+    ("GREEN VFD", [47, 22, 83, 202, 16]),
+    ("CYAN VFD", [51, 23, 33, 203, 16]),
+    ("RED ALERT", [203, 52, 210, 226, 16]),
+    ("MODERN", [234, 242, 33, 203, 255]),
+    ("HIGH CONTRAST", [231, 251, 226, 203, 16]),
+];
+
+// COLORTERM is the usual signal; a few terminals only identify themselves
+// through TERM or TERM_PROGRAM.
+pub fn supports_truecolor(var: impl Fn(&str) -> Option<String>) -> bool {
+    if var("COLORTERM").is_some_and(|v| matches!(v.as_str(), "truecolor" | "24bit")) {
+        return true;
+    }
+    if var("TERM").is_some_and(|v| v.ends_with("-direct") || v.ends_with("-truecolor")) {
+        return true;
+    }
+    var("TERM_PROGRAM")
+        .is_some_and(|v| matches!(v.as_str(), "iTerm.app" | "WezTerm" | "ghostty" | "vscode"))
+}
+
+pub fn truecolor_enabled(depth: ColorDepth) -> bool {
+    match depth {
+        ColorDepth::Auto => supports_truecolor(|name| std::env::var(name).ok()),
+        ColorDepth::TrueColor => true,
+        ColorDepth::Ansi256 => false,
+    }
+}
+
+// Built-in themes get their curated palette; anything else (custom or
+// mid-edit) maps each RGB color to the nearest 256-color entry.
+pub fn fallback(theme: Theme) -> Theme {
+    let curated = FALLBACKS
+        .iter()
+        .find(|(name, _)| THEMES.iter().any(|t| t.name == *name && *t == theme));
+    if let Some((_, [primary, dim, accent, alert, bg])) = curated {
+        return Theme {
+            primary: Color::Indexed(*primary),
+            dim: Color::Indexed(*dim),
+            accent: Color::Indexed(*accent),
+            alert: Color::Indexed(*alert),
+            bg: Color::Indexed(*bg),
+            ..theme
+        };
+    }
+    Theme {
+        primary: nearest(theme.primary),
+        dim: nearest(theme.dim),
+        accent: nearest(theme.accent),
+        alert: nearest(theme.alert),
+        bg: nearest(theme.bg),
+        ..theme
+    }
+}
+
+// Indexed and default colors pass through untouched
+fn nearest(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let distance = |[cr, cg, cb]: [u8; 3]| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, cr) + d(g, cg) + d(b, cb)
+    };
+    let index = (16..=255u8)
+        .min_by_key(|&i| distance(to_rgb(Color::Indexed(i))))
+        .unwrap_or(16);
+    Color::Indexed(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{THEME_AMBER_RETRO, THEME_TERMINAL_CLEAN};
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn truecolor_is_detected_from_the_environment() {
+        assert!(supports_truecolor(env(&[("COLORTERM", "truecolor")])));
+        assert!(supports_truecolor(env(&[("TERM", "xterm-direct")])));
+        assert!(!supports_truecolor(env(&[
+            ("TERM", "xterm-256color"),
+            ("TERM_PROGRAM", "Apple_Terminal"),
+        ])));
+    }
+
+    #[test]
+    fn built_in_themes_use_their_curated_palette() {
+        for (name, _) in FALLBACKS {
+            assert!(THEMES.iter().any(|t| t.name == *name), "{}", name);
+        }
+        let amber = fallback(THEME_AMBER_RETRO);
+        assert_eq!(amber.dim, Color::Indexed(94));
+        assert_eq!(amber.bg, Color::Indexed(16));
+        assert_eq!(fallback(THEME_TERMINAL_CLEAN), THEME_TERMINAL_CLEAN);
+    }
+
+    #[test]
+    fn edited_themes_map_to_the_nearest_color() {
+        let edited = Theme {
+            primary: Color::Rgb(250, 170, 10),
+            ..THEME_AMBER_RETRO
+        };
+        let mapped = fallback(edited);
+        assert_eq!(mapped.primary, Color::Indexed(214));
+        assert_eq!(mapped.bg, Color::Indexed(16));
+    }
+}
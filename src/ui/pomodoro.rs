@@ -0,0 +1,155 @@
+use std::time::{Duration, Instant};
+
+use amcli_core::config::FocusConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    Break,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn is_break(self) -> bool {
+        self != Phase::Work
+    }
+
+    pub fn label(self, is_jp: bool) -> &'static str {
+        match (self, is_jp) {
+            (Phase::Work, false) => "FOCUS",
+            (Phase::Work, true) => "集中",
+            (Phase::Break, false) => "BREAK",
+            (Phase::Break, true) => "休憩",
+            (Phase::LongBreak, false) => "LONG BREAK",
+            (Phase::LongBreak, true) => "長休憩",
+        }
+    }
+}
+
+// The `[focus]` intervals, counted down from when each one started. Polled
+// from the update loop, which acts on the phase it hands back.
+#[derive(Debug)]
+pub struct FocusTimer {
+    work: Duration,
+    short_break: Duration,
+    long_break: Duration,
+    long_break_every: u32,
+    phase: Phase,
+    // Work intervals finished so far
+    completed: u32,
+    ends_at: Instant,
+}
+
+impl FocusTimer {
+    // Starts on a work interval. Anything shorter than a minute counts as one.
+    pub fn start(config: &FocusConfig, now: Instant) -> Self {
+        let minutes = |m: u64| Duration::from_secs(m.max(1) * 60);
+        let work = minutes(config.work_minutes);
+        Self {
+            work,
+            short_break: minutes(config.break_minutes),
+            long_break: minutes(config.long_break_minutes),
+            long_break_every: config.long_break_every,
+            phase: Phase::Work,
+            completed: 0,
+            ends_at: now + work,
+        }
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.ends_at.saturating_duration_since(now)
+    }
+
+    // The phase just entered, once the current one is over. The next interval
+    // runs from `now`, so a Mac waking from sleep starts it in full.
+    pub fn advance(&mut self, now: Instant) -> Option<Phase> {
+        if now < self.ends_at {
+            return None;
+        }
+        self.phase = match self.phase {
+            Phase::Work => {
+                self.completed += 1;
+                if self.long_break_every > 0 && self.completed.is_multiple_of(self.long_break_every)
+                {
+                    Phase::LongBreak
+                } else {
+                    Phase::Break
+                }
+            }
+            Phase::Break | Phase::LongBreak => Phase::Work,
+        };
+        self.ends_at = now + self.length(self.phase);
+        Some(self.phase)
+    }
+
+    pub fn length(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Work => self.work,
+            Phase::Break => self.short_break,
+            Phase::LongBreak => self.long_break,
+        }
+    }
+
+    // "FOCUS 18:42", "BREAK 04:10", rounded up so 00:00 is the very end
+    pub fn describe(&self, now: Instant, is_jp: bool) -> String {
+        let secs = self.remaining(now).as_millis().div_ceil(1000);
+        format!(
+            "{} {:02}:{:02}",
+            self.phase.label(is_jp),
+            secs / 60,
+            secs % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(long_break_every: u32) -> FocusConfig {
+        FocusConfig {
+            work_minutes: 25,
+            break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every,
+            ..FocusConfig::default()
+        }
+    }
+
+    #[test]
+    fn intervals_alternate_with_a_long_break_every_few() {
+        let mut now = Instant::now();
+        let mut timer = FocusTimer::start(&config(2), now);
+        assert_eq!(timer.describe(now, false), "FOCUS 25:00");
+        assert_eq!(timer.advance(now + Duration::from_secs(60)), None);
+        assert_eq!(
+            timer.describe(now + Duration::from_millis(90_500), false),
+            "FOCUS 23:30"
+        );
+
+        let mut phases = Vec::new();
+        for _ in 0..4 {
+            now += timer.remaining(now);
+            phases.push(timer.advance(now).unwrap());
+        }
+        assert_eq!(
+            phases,
+            [Phase::Break, Phase::Work, Phase::LongBreak, Phase::Work]
+        );
+
+        // Late by an hour, the break still runs its full length
+        now += Duration::from_secs(3600) + timer.remaining(now);
+        assert_eq!(timer.advance(now), Some(Phase::Break));
+        assert_eq!(timer.remaining(now), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn no_long_breaks_when_turned_off() {
+        let mut now = Instant::now();
+        let mut timer = FocusTimer::start(&config(0), now);
+        for _ in 0..10 {
+            now += timer.remaining(now);
+            assert_ne!(timer.advance(now), Some(Phase::LongBreak));
+        }
+    }
+}
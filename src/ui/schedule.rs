@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveTime;
+
+use amcli_core::config::ThemeSchedule;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Day,
+    Night,
+}
+
+pub fn theme_name(schedule: &ThemeSchedule, phase: Phase) -> &str {
+    match phase {
+        Phase::Day => &schedule.day,
+        Phase::Night => &schedule.night,
+    }
+}
+
+// The system appearance wins when it's followed and known; otherwise day runs
+// from `day_start` up to `night_start`, wrapping past midnight if needed.
+pub fn phase_at(
+    schedule: &ThemeSchedule,
+    now: NaiveTime,
+    system_dark: Option<bool>,
+) -> Result<Phase> {
+    if let (true, Some(dark)) = (schedule.follow_system, system_dark) {
+        return Ok(if dark { Phase::Night } else { Phase::Day });
+    }
+    let parse = |value: &str| {
+        NaiveTime::parse_from_str(value, "%H:%M")
+            .map_err(|_| anyhow!("Expected HH:MM, got {:?}", value))
+    };
+    let day_start = parse(&schedule.day_start)?;
+    let night_start = parse(&schedule.night_start)?;
+    let is_day = if day_start <= night_start {
+        day_start <= now && now < night_start
+    } else {
+        now >= day_start || now < night_start
+    };
+    Ok(if is_day { Phase::Day } else { Phase::Night })
+}
+
+// `AppleInterfaceStyle` only exists while dark mode is on. `None` off macOS.
+pub async fn system_is_dark() -> Option<bool> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let output = tokio::process::Command::new("defaults")
+        .args(["read", "-g", "AppleInterfaceStyle"])
+        .output()
+        .await
+        .ok()?;
+    Some(output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "Dark")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(day_start: &str, night_start: &str) -> ThemeSchedule {
+        ThemeSchedule {
+            day: "modern".into(),
+            night: "amber vfd".into(),
+            day_start: day_start.into(),
+            night_start: night_start.into(),
+            follow_system: false,
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn day_runs_between_the_switch_times() {
+        let schedule = schedule("07:00", "19:30");
+        assert_eq!(phase_at(&schedule, at(7, 0), None).unwrap(), Phase::Day);
+        assert_eq!(phase_at(&schedule, at(19, 29), None).unwrap(), Phase::Day);
+        assert_eq!(phase_at(&schedule, at(19, 30), None).unwrap(), Phase::Night);
+        assert_eq!(phase_at(&schedule, at(2, 0), None).unwrap(), Phase::Night);
+        assert_eq!(theme_name(&schedule, Phase::Night), "amber vfd");
+
+        // A night shift's "day" wraps past midnight
+        let schedule = self::schedule("22:00", "06:00");
+        assert_eq!(phase_at(&schedule, at(23, 0), None).unwrap(), Phase::Day);
+        assert_eq!(phase_at(&schedule, at(12, 0), None).unwrap(), Phase::Night);
+    }
+
+    #[test]
+    fn system_appearance_overrides_the_clock_when_followed() {
+        let mut schedule = schedule("07:00", "19:00");
+        schedule.follow_system = true;
+        assert_eq!(
+            phase_at(&schedule, at(12, 0), Some(true)).unwrap(),
+            Phase::Night
+        );
+        assert_eq!(phase_at(&schedule, at(12, 0), None).unwrap(), Phase::Day);
+
+        schedule.day_start = "7am".into();
+        assert!(phase_at(&schedule, at(12, 0), None).is_err());
+    }
+}
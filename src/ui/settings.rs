@@ -2,75 +2,457 @@ use ratatui::{
     layout::{Alignment, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
-use crate::config::Language;
+use crate::ui::labels::{self, Label};
 use crate::ui::Theme;
+use amcli_core::config::{Config, Language, MosaicShape, PlayerBackend, ShowNextStyle};
+use amcli_core::player::PlaybackOptions;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone)]
 pub struct SettingsMenu {
     pub is_open: bool,
     pub selected_index: usize,
+    pub tab: SettingsTab,
     items: Vec<SettingsItem>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsTab {
+    General,
+    Appearance,
+    Artwork,
+    Lyrics,
+    Player,
+    Network,
+}
+
+impl SettingsTab {
+    pub const ALL: [SettingsTab; 6] = [
+        SettingsTab::General,
+        SettingsTab::Appearance,
+        SettingsTab::Artwork,
+        SettingsTab::Lyrics,
+        SettingsTab::Player,
+        SettingsTab::Network,
+    ];
+
+    fn label(self, is_jp: bool) -> &'static str {
+        let (en, jp) = match self {
+            SettingsTab::General => ("General", "一般"),
+            SettingsTab::Appearance => ("Appearance", "表示"),
+            SettingsTab::Artwork => ("Artwork", "アート"),
+            SettingsTab::Lyrics => ("Lyrics", "歌詞"),
+            SettingsTab::Player => ("Player", "再生"),
+            SettingsTab::Network => ("Network", "通信"),
+        };
+        if is_jp {
+            jp
+        } else {
+            en
+        }
+    }
+
+    // Wraps around at either end
+    fn step(self, delta: i32) -> Self {
+        let index = Self::ALL.iter().position(|&tab| tab == self).unwrap_or(0);
+        let count = Self::ALL.len() as i32;
+        Self::ALL[(index as i32 + delta).rem_euclid(count) as usize]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum SettingsItem {
     Language {
         current: Language,
     },
+    // `None` is config.toml; `profiles` are the named ones to step through
+    Profile {
+        current: Option<String>,
+        profiles: Vec<String>,
+    },
     Theme {
         current_index: usize,
         total_themes: usize,
     },
+    EditTheme,
     Album {
         enabled: bool,
     },
     Mosaic {
         enabled: bool,
     },
+    MosaicTileSize {
+        size: u32,
+    },
+    MosaicGap {
+        gap: u32,
+    },
+    MosaicShape {
+        shape: MosaicShape,
+    },
+    Backend {
+        current: PlayerBackend,
+    },
+    // The player's own preferences; `None` (and hidden) when it can't report them
+    Crossfade {
+        enabled: Option<bool>,
+    },
+    CrossfadeDuration {
+        secs: Option<u8>,
+    },
+    SoundCheck {
+        enabled: Option<bool>,
+    },
+    Autoplay {
+        enabled: Option<bool>,
+    },
+    ShowNext {
+        enabled: bool,
+    },
+    ShowNextStyle {
+        style: ShowNextStyle,
+    },
+    Offline {
+        enabled: bool,
+    },
     Close,
 }
 
-impl SettingsMenu {
-    pub fn new(
-        language: Language,
-        theme_index: usize,
-        total_themes: usize,
-        album: bool,
-        mosaic: bool,
-    ) -> Self {
-        let items = vec![
-            SettingsItem::Language { current: language },
+impl SettingsItem {
+    // Where the setting lives; `None` shows it on every tab
+    fn tab(&self) -> Option<SettingsTab> {
+        Some(match self {
+            SettingsItem::Language { .. } | SettingsItem::Profile { .. } => SettingsTab::General,
+            SettingsItem::Theme { .. } | SettingsItem::EditTheme => SettingsTab::Appearance,
+            SettingsItem::Album { .. }
+            | SettingsItem::Mosaic { .. }
+            | SettingsItem::MosaicTileSize { .. }
+            | SettingsItem::MosaicGap { .. }
+            | SettingsItem::MosaicShape { .. } => SettingsTab::Artwork,
+            SettingsItem::ShowNext { .. } | SettingsItem::ShowNextStyle { .. } => {
+                SettingsTab::Lyrics
+            }
+            SettingsItem::Backend { .. }
+            | SettingsItem::Crossfade { .. }
+            | SettingsItem::CrossfadeDuration { .. }
+            | SettingsItem::SoundCheck { .. }
+            | SettingsItem::Autoplay { .. } => SettingsTab::Player,
+            SettingsItem::Offline { .. } => SettingsTab::Network,
+            SettingsItem::Close => return None,
+        })
+    }
+
+    // One or two lines under the list explaining the selected setting
+    fn help(&self, is_jp: bool) -> &'static str {
+        let (en, jp) = match self {
+            SettingsItem::Language { .. } => (
+                "Language for menus, hints, and messages.",
+                "メニュー、ヒント、メッセージの言語。",
+            ),
+            SettingsItem::Profile { .. } => (
+                "Config profile, with its own theme, player, keys, and integrations. Create one with :profile <name>.",
+                "設定プロファイル。テーマ・プレーヤー・キー設定などを個別に保持。:profile <名前> で作成。",
+            ),
+            SettingsItem::Theme { .. } => (
+                "Color theme. ←→ steps through built-in and saved themes.",
+                "配色テーマ。←→ で内蔵・保存済みテーマを切り替え。",
+            ),
+            SettingsItem::EditTheme => (
+                "Tune the current theme's colors and save them as a new theme.",
+                "現在のテーマの色を調整し、新しいテーマとして保存。",
+            ),
+            SettingsItem::Album { .. } => (
+                "Show the album cover next to the track info.",
+                "曲情報の横にアルバムアートを表示。",
+            ),
+            SettingsItem::Mosaic { .. } => (
+                "Draw the cover as a grid of colored tiles.",
+                "アルバムアートをタイル状に描画。",
+            ),
+            SettingsItem::MosaicTileSize { .. } => (
+                "Size of each mosaic tile, in cover pixels.",
+                "モザイクタイルの大きさ(ピクセル)。",
+            ),
+            SettingsItem::MosaicGap { .. } => (
+                "Space between tiles, up to half a tile.",
+                "タイル同士の間隔(タイルの半分まで)。",
+            ),
+            SettingsItem::MosaicShape { .. } => {
+                ("Square or round tiles.", "四角または丸いタイル。")
+            }
+            SettingsItem::ShowNext { .. } => (
+                "Call out the upcoming lyric line so you can read ahead.",
+                "次の歌詞行を目立たせて先読みしやすくする。",
+            ),
+            SettingsItem::ShowNextStyle { .. } => (
+                "Highlight the next line in place, or repeat it under the lyrics.",
+                "次の行をその場で強調するか、歌詞の下に表示。",
+            ),
+            SettingsItem::Backend { .. } => {
+                ("Which player amcli controls.", "操作するプレーヤー。")
+            }
+            SettingsItem::Crossfade { .. } => (
+                "The player's own crossfade between songs.",
+                "プレーヤーの曲間クロスフェード。",
+            ),
+            SettingsItem::CrossfadeDuration { .. } => (
+                "How long songs overlap when crossfading.",
+                "クロスフェードで曲が重なる長さ。",
+            ),
+            SettingsItem::SoundCheck { .. } => (
+                "The player's Sound Check, which evens out loudness.",
+                "プレーヤーの音量自動調整。",
+            ),
+            SettingsItem::Autoplay { .. } => (
+                "Keep playing similar music after the queue ends.",
+                "キューの再生後も似た曲を自動再生。",
+            ),
+            SettingsItem::Offline { .. } => (
+                "Skip artwork, lyrics, and artist lookups over the network.",
+                "アートワーク・歌詞・アーティスト情報を取得しない。",
+            ),
+            SettingsItem::Close => ("Close the settings.", "設定を閉じる。"),
+        };
+        if is_jp {
+            jp
+        } else {
+            en
+        }
+    }
+
+    // Row text as (label, value). Rows read "English / 日本語" when the menu
+    // is wide enough, then only the UI language, then get cut off.
+    fn text(&self, width: usize, is_jp: bool) -> (String, String) {
+        let same = |value: String| Some((value.clone(), value));
+        let toggle = |enabled: bool| {
+            let (en, jp) = if enabled {
+                ("ON", "オン")
+            } else {
+                ("OFF", "オフ")
+            };
+            Some((en.to_string(), jp.to_string()))
+        };
+        let (indent, (en, jp), value) = match self {
+            SettingsItem::Language { current } => {
+                let name = match current {
+                    Language::English => "English",
+                    Language::Japanese => "日本語",
+                };
+                (false, ("Language", "言語"), same(name.into()))
+            }
+            SettingsItem::Profile { current, .. } => {
+                let name = current.as_deref().unwrap_or("default");
+                (
+                    false,
+                    ("Profile", "プロファイル"),
+                    same(format!("◀ {} ▶", name)),
+                )
+            }
             SettingsItem::Theme {
-                current_index: theme_index,
+                current_index,
                 total_themes,
-            },
-            SettingsItem::Album { enabled: album },
-            SettingsItem::Mosaic { enabled: mosaic },
-            SettingsItem::Close,
-        ];
+            } => (
+                false,
+                ("Theme", "テーマ"),
+                same(format!("{} / {}", current_index + 1, total_themes)),
+            ),
+            SettingsItem::EditTheme => (true, ("Edit Colors", "色を編集"), None),
+            SettingsItem::Album { enabled } => {
+                (false, ("Album Artwork", "アルバム"), toggle(*enabled))
+            }
+            SettingsItem::Mosaic { enabled } => {
+                (false, ("Mosaic Artwork", "モザイク"), toggle(*enabled))
+            }
+            SettingsItem::MosaicTileSize { size } => (
+                true,
+                ("Tile Size", "タイル"),
+                same(format!("◀ {}px ▶", size)),
+            ),
+            SettingsItem::MosaicGap { gap } => {
+                (true, ("Gap", "間隔"), same(format!("◀ {}px ▶", gap)))
+            }
+            SettingsItem::MosaicShape { shape } => (
+                true,
+                ("Shape", "形状"),
+                same(format!("◀ {} ▶", shape.label())),
+            ),
+            SettingsItem::Backend { current } => (
+                false,
+                ("Player", "プレーヤー"),
+                same(current.label().to_string()),
+            ),
+            SettingsItem::Crossfade { enabled } => (
+                false,
+                ("Crossfade", "クロスフェード"),
+                toggle(enabled.unwrap_or_default()),
+            ),
+            SettingsItem::CrossfadeDuration { secs } => (
+                true,
+                ("Duration", "長さ"),
+                same(format!("◀ {}s ▶", secs.unwrap_or_default())),
+            ),
+            SettingsItem::SoundCheck { enabled } => (
+                false,
+                ("Sound Check", "音量自動調整"),
+                toggle(enabled.unwrap_or_default()),
+            ),
+            SettingsItem::Autoplay { enabled } => (
+                false,
+                ("Autoplay", "自動再生"),
+                toggle(enabled.unwrap_or_default()),
+            ),
+            SettingsItem::ShowNext { enabled } => {
+                (false, ("Show Next Line", "次の行"), toggle(*enabled))
+            }
+            SettingsItem::ShowNextStyle { style } => {
+                let (en, jp) = match style {
+                    ShowNextStyle::Highlight => ("Highlight", "強調"),
+                    ShowNextStyle::Slot => ("Slot", "下段"),
+                };
+                (
+                    true,
+                    ("Style", "表示"),
+                    Some((format!("◀ {} ▶", en), format!("◀ {} ▶", jp))),
+                )
+            }
+            SettingsItem::Offline { enabled } => {
+                (false, ("Offline", "オフライン"), toggle(*enabled))
+            }
+            SettingsItem::Close => (false, ("Close", "閉じる"), None),
+        };
+        let indent = if indent { "  " } else { "" };
+        let (value_en, value_jp) = value.unwrap_or_default();
+        let value_both = if value_en == value_jp {
+            value_en.clone()
+        } else {
+            format!("{} / {}", value_en, value_jp)
+        };
+        let value_local = if is_jp { value_jp } else { value_en };
+        // Rows render as "  label: " + " value ", or "  label  " without a value
+        let room = |value: &str| {
+            let padding = if value.is_empty() { 4 } else { 6 };
+            width.saturating_sub(UnicodeWidthStr::width(value) + padding)
+        };
+
+        let both = format!("{}{} / {}", indent, en, jp);
+        if UnicodeWidthStr::width(both.as_str()) <= room(&value_both) {
+            return (both, value_both);
+        }
+        let local = format!("{}{}", indent, if is_jp { jp } else { en });
+        let label = labels::truncate(&local, room(&value_local)).into_owned();
+        (label, value_local)
+    }
+}
 
+// What the menu shows, gathered from the app each time a setting changes
+pub struct SettingsValues<'a> {
+    pub config: &'a Config,
+    pub profiles: &'a [String],
+    pub theme_index: usize,
+    pub total_themes: usize,
+    pub offline: bool,
+    pub playback: Option<PlaybackOptions>,
+    pub autoplay: Option<bool>,
+}
+
+// Menu order within each tab. Each entry reads its current value, so adding
+// or moving a setting only touches this list, `SettingsItem::tab`, and
+// `settings_select`.
+const LAYOUT: &[fn(&SettingsValues) -> SettingsItem] = &[
+    |v| SettingsItem::Language {
+        current: v.config.general.language,
+    },
+    |v| SettingsItem::Profile {
+        current: v.config.profile.clone(),
+        profiles: v.profiles.to_vec(),
+    },
+    |v| SettingsItem::Theme {
+        current_index: v.theme_index,
+        total_themes: v.total_themes,
+    },
+    |_| SettingsItem::EditTheme,
+    |v| SettingsItem::Album {
+        enabled: v.config.artwork.album,
+    },
+    |v| SettingsItem::Mosaic {
+        enabled: v.config.artwork.mosaic,
+    },
+    |v| SettingsItem::MosaicTileSize {
+        size: v.config.artwork.mosaic_tile_size,
+    },
+    |v| SettingsItem::MosaicGap {
+        gap: v.config.artwork.mosaic_gap,
+    },
+    |v| SettingsItem::MosaicShape {
+        shape: v.config.artwork.mosaic_shape,
+    },
+    |v| SettingsItem::Backend {
+        current: v.config.player.backend,
+    },
+    |v| SettingsItem::Crossfade {
+        enabled: v.playback.map(|p| p.crossfade),
+    },
+    |v| SettingsItem::CrossfadeDuration {
+        secs: v.playback.map(|p| p.crossfade_secs),
+    },
+    |v| SettingsItem::SoundCheck {
+        enabled: v.playback.map(|p| p.sound_check),
+    },
+    |v| SettingsItem::Autoplay {
+        enabled: v.autoplay,
+    },
+    |v| SettingsItem::ShowNext {
+        enabled: v.config.lyrics.show_next.enabled,
+    },
+    |v| SettingsItem::ShowNextStyle {
+        style: v.config.lyrics.show_next.style,
+    },
+    |v| SettingsItem::Offline { enabled: v.offline },
+    |_| SettingsItem::Close,
+];
+
+impl SettingsMenu {
+    pub fn new(values: &SettingsValues) -> Self {
         Self {
             is_open: false,
             selected_index: 0,
-            items,
+            tab: SettingsTab::General,
+            items: LAYOUT.iter().map(|item| item(values)).collect(),
         }
     }
 
+    // Re-reads every value; the selection stays where it is
+    pub fn refresh(&mut self, values: &SettingsValues) {
+        self.items = LAYOUT.iter().map(|item| item(values)).collect();
+    }
+
     pub fn toggle(&mut self) {
-        self.is_open = !self.is_open;
         if self.is_open {
-            self.selected_index = 0;
+            self.close();
+        } else {
+            self.open();
         }
     }
 
-    #[allow(dead_code)]
     pub fn open(&mut self) {
         self.is_open = true;
-        self.selected_index = 0;
+        self.select_tab(SettingsTab::General);
+    }
+
+    // Shows `tab` with its first setting selected
+    pub fn select_tab(&mut self, tab: SettingsTab) {
+        self.tab = tab;
+        self.selected_index = (0..self.items.len())
+            .find(|&i| self.is_visible(i))
+            .unwrap_or(0);
+    }
+
+    pub fn switch_tab(&mut self, delta: i32) {
+        self.select_tab(self.tab.step(delta));
     }
 
     pub fn close(&mut self) {
@@ -78,77 +460,62 @@ impl SettingsMenu {
     }
 
     pub fn navigate_up(&mut self) {
-        if self.selected_index > 0 {
-            self.selected_index -= 1;
-            if self.should_skip_current_item() {
-                if self.selected_index > 0 {
-                    self.selected_index -= 1;
-                } else {
-                    self.selected_index += 1;
-                }
-            }
+        if let Some(index) = (0..self.selected_index).rev().find(|&i| self.is_visible(i)) {
+            self.selected_index = index;
         }
     }
 
     pub fn navigate_down(&mut self) {
-        if self.selected_index < self.items.len() - 1 {
-            self.selected_index += 1;
-            if self.should_skip_current_item() {
-                if self.selected_index < self.items.len() - 1 {
-                    self.selected_index += 1;
-                } else {
-                    self.selected_index -= 1;
-                }
-            }
+        if let Some(index) =
+            (self.selected_index + 1..self.items.len()).find(|&i| self.is_visible(i))
+        {
+            self.selected_index = index;
         }
     }
 
-    fn should_skip_current_item(&self) -> bool {
-        let album_enabled = self
-            .items
-            .iter()
-            .find_map(|item| {
-                if let SettingsItem::Album { enabled } = item {
-                    Some(*enabled)
-                } else {
-                    None
-                }
-            })
-            .unwrap_or(true);
-
-        if let Some(SettingsItem::Mosaic { .. }) = self.items.get(self.selected_index) {
-            return !album_enabled;
-        }
-        false
+    fn flag(&self, pick: fn(&SettingsItem) -> Option<bool>) -> bool {
+        self.items.iter().find_map(pick).unwrap_or(true)
     }
 
-    pub fn update_language(&mut self, language: Language) {
-        if let Some(item) = self.items.get_mut(0) {
-            *item = SettingsItem::Language { current: language };
-        }
-    }
-
-    pub fn update_theme(&mut self, theme_index: usize) {
-        if let Some(SettingsItem::Theme { total_themes, .. }) = self.items.get(1) {
-            let total = *total_themes;
-            if let Some(item) = self.items.get_mut(1) {
-                *item = SettingsItem::Theme {
-                    current_index: theme_index,
-                    total_themes: total,
-                };
+    // Only the current tab's settings show. Mosaic settings are hidden along
+    // with the artwork, and its tuning along with the mosaic. Crossfade
+    // length and the next-line style likewise follow their toggles.
+    fn is_visible(&self, index: usize) -> bool {
+        if let Some(tab) = self.items.get(index).and_then(SettingsItem::tab) {
+            if tab != self.tab {
+                return false;
             }
         }
-    }
-
-    pub fn update_album(&mut self, enabled: bool) {
-        if let Some(item) = self.items.get_mut(2) {
-            *item = SettingsItem::Album { enabled };
-        }
-    }
-
-    pub fn update_mosaic(&mut self, enabled: bool) {
-        if let Some(item) = self.items.get_mut(3) {
-            *item = SettingsItem::Mosaic { enabled };
+        let album = self.flag(|item| match item {
+            SettingsItem::Album { enabled } => Some(*enabled),
+            _ => None,
+        });
+        let mosaic = self.flag(|item| match item {
+            SettingsItem::Mosaic { enabled } => Some(*enabled),
+            _ => None,
+        });
+        match self.items.get(index) {
+            Some(SettingsItem::Mosaic { .. }) => album,
+            Some(
+                SettingsItem::MosaicTileSize { .. }
+                | SettingsItem::MosaicGap { .. }
+                | SettingsItem::MosaicShape { .. },
+            ) => album && mosaic,
+            Some(
+                SettingsItem::Crossfade { enabled }
+                | SettingsItem::SoundCheck { enabled }
+                | SettingsItem::Autoplay { enabled },
+            ) => enabled.is_some(),
+            Some(SettingsItem::CrossfadeDuration { .. }) => self.flag(|item| match item {
+                SettingsItem::Crossfade { enabled } => Some(enabled.unwrap_or_default()),
+                _ => None,
+            }),
+            Some(SettingsItem::ShowNextStyle { .. }) => self.flag(|item| match item {
+                SettingsItem::ShowNext { enabled } => Some(*enabled),
+                _ => None,
+            }),
+            Some(_) => true,
+            None => false,
         }
     }
 
@@ -171,12 +538,12 @@ impl SettingsMenu {
         None
     }
 
-    pub fn render(&self, f: &mut Frame, theme: Theme) {
+    pub fn render(&self, f: &mut Frame, theme: Theme, is_jp: bool) {
         let area = f.area();
 
         // Create centered overlay
         let popup_width = 60.min(area.width - 4);
-        let popup_height = 12.min(area.height - 4);
+        let popup_height = 16.min(area.height - 4);
 
         let popup_area = Rect {
             x: (area.width.saturating_sub(popup_width)) / 2,
@@ -213,63 +580,37 @@ impl SettingsMenu {
         let inner = block.inner(popup_area);
         f.render_widget(block, popup_area);
 
-        // Render menu items
-        let mut list_items = Vec::new();
+        // Tabs on top, the current tab's settings, then help for the
+        // selected one
+        let tabs_area = Rect { height: 1, ..inner };
+        f.render_widget(self.tab_bar(inner.width as usize, theme, is_jp), tabs_area);
+        let help_height = 2.min(inner.height.saturating_sub(3));
+        let list_area = Rect {
+            y: inner.y + 2,
+            height: inner.height.saturating_sub(help_height + 3),
+            ..inner
+        };
+        let help_area = Rect {
+            y: inner.bottom() - help_height,
+            height: help_height,
+            ..inner
+        };
 
-        let album_enabled = self
-            .items
+        // Scroll just far enough to keep the selection in view
+        let visible: Vec<usize> = (0..self.items.len())
+            .filter(|&i| self.is_visible(i))
+            .collect();
+        let rows = list_area.height as usize;
+        let position = visible
             .iter()
-            .find_map(|item| {
-                if let SettingsItem::Album { enabled } = item {
-                    Some(*enabled)
-                } else {
-                    None
-                }
-            })
-            .unwrap_or(true);
-
-        for (i, item) in self.items.iter().enumerate() {
-            if let SettingsItem::Mosaic { .. } = item {
-                if !album_enabled {
-                    continue;
-                }
-            }
-
+            .position(|&i| i == self.selected_index)
+            .unwrap_or(0);
+        let offset = scroll_offset(position, rows);
+        let mut list_items = Vec::new();
+        for &i in visible.iter().skip(offset).take(rows) {
+            let item = &self.items[i];
             let is_selected = i == self.selected_index;
-            let (label, value) = match item {
-                SettingsItem::Language { current } => {
-                    let lang_str = match current {
-                        Language::English => "English",
-                        Language::Japanese => "日本語",
-                    };
-                    ("Language / 言語", lang_str.to_string())
-                }
-                SettingsItem::Theme {
-                    current_index,
-                    total_themes,
-                } => (
-                    "Theme / テーマ",
-                    format!("{} / {}", current_index + 1, total_themes),
-                ),
-                SettingsItem::Album { enabled } => {
-                    let status = if *enabled {
-                        "ON / オン"
-                    } else {
-                        "OFF / オフ"
-                    };
-                    ("Album Artwork / アルバム", status.to_string())
-                }
-                SettingsItem::Mosaic { enabled } => {
-                    let status = if *enabled {
-                        "ON / オン"
-                    } else {
-                        "OFF / オフ"
-                    };
-                    ("Mosaic Artwork / モザイク", status.to_string())
-                }
-                SettingsItem::Close => ("Close / 閉じる", String::new()),
-            };
-
+            let (label, value) = item.text(inner.width as usize, is_jp);
             let line = if value.is_empty() {
                 vec![Span::styled(
                     format!("  {}  ", label),
@@ -314,11 +655,39 @@ impl SettingsMenu {
             list_items.push(ListItem::new(Line::from(line)));
         }
 
-        let list = List::new(list_items).block(Block::default());
-        f.render_widget(list, inner);
+        f.render_widget(List::new(list_items), list_area);
+
+        // Arrows on the list's right edge when settings are scrolled out of view
+        let more_above = offset > 0;
+        let more_below = offset + rows < visible.len();
+        for (shown, y, arrow) in [
+            (more_above, list_area.y, "▲"),
+            (more_below, list_area.bottom().saturating_sub(1), "▼"),
+        ] {
+            if shown && rows > 0 {
+                let area = Rect::new(list_area.right().saturating_sub(1), y, 1, 1);
+                f.render_widget(
+                    Paragraph::new(arrow).style(Style::default().fg(theme.accent)),
+                    area,
+                );
+            }
+        }
+
+        if let Some(item) = self.get_selected_item() {
+            let help = Paragraph::new(format!("  {}", item.help(is_jp)))
+                .style(Style::default().fg(theme.dim))
+                .wrap(Wrap { trim: false });
+            f.render_widget(help, help_area);
+        }
 
         // Add help text at the bottom
-        let help_text = "↑↓/jk: Navigate  │  Enter/Space: Select  │  Esc/S: Close";
+        let help_label = Label::new(
+            "↑↓ Navigate │ ←→ Adjust │ Tab Category │ ⏎ Select │ Esc Close",
+            "↑↓ │ ←→ │ Tab │ ⏎ │ Esc",
+        );
+        let help_text = help_label
+            .fit(popup_area.width as usize)
+            .unwrap_or_default();
         let help = Paragraph::new(help_text)
             .style(Style::default().fg(theme.dim))
             .alignment(Alignment::Center);
@@ -332,4 +701,48 @@ impl SettingsMenu {
 
         f.render_widget(help, help_area);
     }
+
+    // Every tab's name when they fit, else just the current one
+    fn tab_bar(&self, width: usize, theme: Theme, is_jp: bool) -> Paragraph<'static> {
+        let selected = Style::default()
+            .fg(theme.bg)
+            .bg(theme.accent)
+            .add_modifier(Modifier::BOLD);
+        let labels: Vec<String> = SettingsTab::ALL
+            .iter()
+            .map(|tab| format!(" {} ", tab.label(is_jp)))
+            .collect();
+        let total: usize = labels
+            .iter()
+            .map(|l| UnicodeWidthStr::width(l.as_str()))
+            .sum();
+        let line = if total <= width {
+            Line::from(
+                SettingsTab::ALL
+                    .iter()
+                    .zip(labels)
+                    .map(|(&tab, label)| {
+                        let style = if tab == self.tab {
+                            selected
+                        } else {
+                            Style::default().fg(theme.dim)
+                        };
+                        Span::styled(label, style)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            Line::from(vec![
+                Span::styled("◀ ", Style::default().fg(theme.dim)),
+                Span::styled(format!(" {} ", self.tab.label(is_jp)), selected),
+                Span::styled(" ▶", Style::default().fg(theme.dim)),
+            ])
+        };
+        Paragraph::new(line).alignment(Alignment::Center)
+    }
+}
+
+// First row to draw so that the row at `position` shows in `rows` rows
+fn scroll_offset(position: usize, rows: usize) -> usize {
+    (position + 1).saturating_sub(rows.max(1))
 }
@@ -0,0 +1,300 @@
+use anyhow::Result;
+use ratatui::{
+    buffer::{Buffer, Cell, CellDiffOption},
+    style::{Color, Modifier},
+};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use unicode_width::UnicodeWidthStr;
+
+// Page colors for cells drawn with the terminal's default colors
+const DEFAULT_FG: (u8, u8, u8) = (0xd0, 0xd0, 0xd0);
+const DEFAULT_BG: (u8, u8, u8) = (0x00, 0x00, 0x00);
+
+// xterm's first 16 colors, in ANSI order
+const BASE_COLORS: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Ansi,
+    Html,
+}
+
+impl Format {
+    // `.html` / `.htm` get a standalone page, anything else ANSI text
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                Format::Html
+            }
+            _ => Format::Ansi,
+        }
+    }
+}
+
+// `amcli-20261015-070000.html` in the working directory
+pub fn default_path() -> PathBuf {
+    PathBuf::from(format!(
+        "amcli-{}.html",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+pub async fn write(path: &Path, buffer: &Buffer) -> Result<()> {
+    let content = render(buffer, Format::from_path(path));
+    tokio::fs::write(path, content).await?;
+    Ok(())
+}
+
+pub fn render(buffer: &Buffer, format: Format) -> String {
+    match format {
+        Format::Ansi => to_ansi(buffer),
+        Format::Html => to_html(buffer),
+    }
+}
+
+// Each row's cells, leaving out the columns covered by a wide glyph to their
+// left. Artwork drawn with a graphics protocol (Kitty, Sixel, iTerm2) has no
+// cell contents and comes out blank.
+fn rows(buffer: &Buffer) -> impl Iterator<Item = Vec<(&str, &Cell)>> {
+    let width = buffer.area.width as usize;
+    buffer.content.chunks(width.max(1)).map(|row| {
+        let mut covered = 0;
+        let mut cells = Vec::with_capacity(row.len());
+        for cell in row {
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            let symbol = cell.symbol();
+            if cell.diff_option == CellDiffOption::Skip || symbol.contains('\x1b') {
+                cells.push((" ", cell));
+                continue;
+            }
+            covered = symbol.width().saturating_sub(1);
+            cells.push((symbol, cell));
+        }
+        cells
+    })
+}
+
+fn to_ansi(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for row in rows(buffer) {
+        let mut current = None;
+        for (symbol, cell) in row {
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if current != Some(style) {
+                out.push_str(&sgr(cell));
+                current = Some(style);
+            }
+            out.push_str(symbol);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+fn sgr(cell: &Cell) -> String {
+    let mut codes = vec!["0".to_string()];
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if cell.modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(code) = ansi_color(cell.fg, false) {
+        codes.push(code);
+    }
+    if let Some(code) = ansi_color(cell.bg, true) {
+        codes.push(code);
+    }
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn ansi_color(color: Color, background: bool) -> Option<String> {
+    let offset = if background { 10 } else { 0 };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => {
+            return Some(format!("{};2;{};{};{}", 38 + offset, r, g, b));
+        }
+        Color::Indexed(i) => return Some(format!("{};5;{}", 38 + offset, i)),
+        named => match base_index(named) {
+            Some(i @ 0..=7) => 30 + i as u16,
+            Some(i) => 90 + (i as u16 - 8),
+            None => return None,
+        },
+    };
+    Some((code + offset).to_string())
+}
+
+fn base_index(color: Color) -> Option<u8> {
+    Some(match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        _ => return None,
+    })
+}
+
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(i @ 0..=15) => Some(BASE_COLORS[i as usize]),
+        // The 6×6×6 cube, then a 24-step gray ramp
+        Color::Indexed(i @ 16..=231) => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let i = i - 16;
+            Some((level(i / 36), level(i / 6 % 6), level(i % 6)))
+        }
+        Color::Indexed(i) => {
+            let gray = 8 + (i - 232) * 10;
+            Some((gray, gray, gray))
+        }
+        named => base_index(named).map(|i| BASE_COLORS[i as usize]),
+    }
+}
+
+fn to_html(buffer: &Buffer) -> String {
+    let mut body = String::new();
+    for row in rows(buffer) {
+        let mut run = String::new();
+        let mut run_style = None;
+        for (symbol, cell) in row {
+            let style = css(cell);
+            if run_style.as_ref() != Some(&style) {
+                push_span(&mut body, run_style.as_deref(), &run);
+                run.clear();
+                run_style = Some(style);
+            }
+            run.push_str(symbol);
+        }
+        push_span(&mut body, run_style.as_deref(), &run);
+        body.push('\n');
+    }
+    let hex = |(r, g, b): (u8, u8, u8)| format!("#{:02x}{:02x}{:02x}", r, g, b);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>amcli</title>\n\
+         <style>\nbody {{ margin: 0; padding: 1em; background: {bg}; }}\n\
+         pre {{ margin: 0; color: {fg}; font-family: ui-monospace, Menlo, monospace; \
+         font-size: 14px; line-height: 1.15; }}\n</style>\n</head>\n<body>\n<pre>{body}</pre>\n\
+         </body>\n</html>\n",
+        bg = hex(DEFAULT_BG),
+        fg = hex(DEFAULT_FG),
+        body = body,
+    )
+}
+
+fn push_span(out: &mut String, style: Option<&str>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let text = text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    match style {
+        Some(style) if !style.is_empty() => {
+            let _ = write!(out, "<span style=\"{}\">{}</span>", style, text);
+        }
+        _ => out.push_str(&text),
+    }
+}
+
+fn css(cell: &Cell) -> String {
+    let (mut fg, mut bg) = (rgb(cell.fg), rgb(cell.bg));
+    if cell.modifier.contains(Modifier::REVERSED) {
+        (fg, bg) = (
+            Some(bg.unwrap_or(DEFAULT_BG)),
+            Some(fg.unwrap_or(DEFAULT_FG)),
+        );
+    }
+    let mut style = String::new();
+    if let Some((r, g, b)) = fg {
+        let _ = write!(style, "color:#{:02x}{:02x}{:02x};", r, g, b);
+    }
+    if let Some((r, g, b)) = bg {
+        let _ = write!(style, "background:#{:02x}{:02x}{:02x};", r, g, b);
+    }
+    if cell.modifier.contains(Modifier::BOLD) {
+        style.push_str("font-weight:bold;");
+    }
+    if cell.modifier.contains(Modifier::DIM) {
+        style.push_str("opacity:0.6;");
+    }
+    if cell.modifier.contains(Modifier::ITALIC) {
+        style.push_str("font-style:italic;");
+    }
+    match (
+        cell.modifier.contains(Modifier::UNDERLINED),
+        cell.modifier.contains(Modifier::CROSSED_OUT),
+    ) {
+        (true, true) => style.push_str("text-decoration:underline line-through;"),
+        (true, false) => style.push_str("text-decoration:underline;"),
+        (false, true) => style.push_str("text-decoration:line-through;"),
+        (false, false) => {}
+    }
+    style
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    #[test]
+    fn snapshots_keep_colors_and_escape_html() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "<a&b>", Style::default().fg(Color::Rgb(255, 136, 0)));
+        buffer.set_string(0, 1, "日本", Style::default().bg(Color::Indexed(196)));
+
+        let ansi = render(&buffer, Format::Ansi);
+        assert!(ansi.starts_with("\x1b[0;38;2;255;136;0m<a&b>\x1b[0m \x1b[0m\n"));
+        assert!(ansi.contains("\x1b[0;48;5;196m日本\x1b[0m  \x1b[0m\n"));
+
+        let html = render(&buffer, Format::Html);
+        assert!(html.contains("<span style=\"color:#ff8800;\">&lt;a&amp;b&gt;</span> \n"));
+        assert!(html.contains("<span style=\"background:#ff0000;\">日本</span>  \n"));
+
+        assert_eq!(Format::from_path(Path::new("shot.HTML")), Format::Html);
+        assert_eq!(Format::from_path(Path::new("shot.ans")), Format::Ansi);
+    }
+}
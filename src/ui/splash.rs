@@ -0,0 +1,133 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+use std::time::{Duration, Instant};
+
+use crate::ui::Theme;
+
+// Segment test runs first, then one check line appears per step.
+const SEGMENT_TEST_DURATION: Duration = Duration::from_millis(600);
+const CHECK_LINE_INTERVAL: Duration = Duration::from_millis(250);
+const READY_HOLD: Duration = Duration::from_millis(400);
+
+const CHECK_LINES_EN: &[&str] = &[
+    "AMCLI SYSTEM CHECK…",
+    "DISPLAY DRIVER ...... OK",
+    "AUDIO BUS ........... OK",
+    "LYRICS LINK ......... OK",
+    "ARTWORK DSP ......... OK",
+    "READY",
+];
+
+const CHECK_LINES_JP: &[&str] = &[
+    "AMCLI システムチェック…",
+    "表示装置 ............ 正常",
+    "音響バス ............ 正常",
+    "歌詞回線 ............ 正常",
+    "画像処理 ............ 正常",
+    "準備完了",
+];
+
+pub struct BootSplash {
+    started_at: Instant,
+}
+
+impl BootSplash {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.started_at.elapsed() >= total_duration()
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, theme: Theme, is_jp: bool) {
+        let elapsed = self.started_at.elapsed();
+        let check_lines = if is_jp {
+            CHECK_LINES_JP
+        } else {
+            CHECK_LINES_EN
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Thick)
+            .border_style(Style::default().fg(theme.dim))
+            .style(Style::default().bg(theme.bg));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let mut lines = Vec::new();
+        if elapsed < SEGMENT_TEST_DURATION {
+            // All segments lit, like a VFD's power-on self test
+            let segment_style = Style::default()
+                .fg(theme.primary)
+                .add_modifier(Modifier::BOLD);
+            lines.push(Line::from(Span::styled("88:88:88", segment_style)));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "▮▮▮▮▮▮▮▮▮▮▮▮▮▮▮▮▮▮▮▮",
+                segment_style,
+            )));
+        } else {
+            let visible = visible_check_lines(elapsed, check_lines.len());
+            for (i, text) in check_lines.iter().take(visible).enumerate() {
+                let style = if i == 0 || i + 1 == check_lines.len() {
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.primary)
+                };
+                lines.push(Line::from(Span::styled(*text, style)));
+            }
+        }
+
+        let height = lines.len() as u16;
+        let [_, content, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(height),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+        f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), content);
+    }
+}
+
+fn total_duration() -> Duration {
+    SEGMENT_TEST_DURATION + CHECK_LINE_INTERVAL * CHECK_LINES_EN.len() as u32 + READY_HOLD
+}
+
+fn visible_check_lines(elapsed: Duration, total: usize) -> usize {
+    let since_test = elapsed.saturating_sub(SEGMENT_TEST_DURATION);
+    let steps = (since_test.as_millis() / CHECK_LINE_INTERVAL.as_millis()) as usize + 1;
+    steps.min(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_lines_appear_one_per_interval() {
+        let total = CHECK_LINES_EN.len();
+
+        assert_eq!(visible_check_lines(SEGMENT_TEST_DURATION, total), 1);
+        assert_eq!(
+            visible_check_lines(SEGMENT_TEST_DURATION + CHECK_LINE_INTERVAL * 2, total),
+            3
+        );
+        assert_eq!(visible_check_lines(total_duration(), total), total);
+    }
+
+    #[test]
+    fn english_and_japanese_check_lists_stay_in_step() {
+        assert_eq!(CHECK_LINES_EN.len(), CHECK_LINES_JP.len());
+    }
+}
@@ -0,0 +1,133 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use amcli_core::config::Station;
+
+use crate::ui::Theme;
+
+// `[[stations]]`, opened with `w` and played through the internal player
+#[derive(Default)]
+pub struct StationPicker {
+    selected: usize,
+}
+
+impl StationPicker {
+    pub fn move_selection(&mut self, delta: isize, len: usize) {
+        let last = len.saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+    }
+
+    // Kept in range as stations are removed
+    pub fn selected(&mut self, len: usize) -> usize {
+        self.selected = self.selected.min(len.saturating_sub(1));
+        self.selected
+    }
+
+    // `playing` is the station on air, marked in the list
+    pub fn render(
+        &self,
+        f: &mut Frame,
+        stations: &[Station],
+        playing: Option<&str>,
+        theme: Theme,
+        is_jp: bool,
+    ) {
+        let area = f.area();
+        let popup_width = 64.min(area.width.saturating_sub(4));
+        let popup_height = 16.min(area.height.saturating_sub(4));
+        let popup_area = Rect {
+            x: area.width.saturating_sub(popup_width) / 2,
+            y: area.height.saturating_sub(popup_height) / 3,
+            width: popup_width,
+            height: popup_height,
+        };
+        f.render_widget(Clear, popup_area);
+
+        let title = if is_jp {
+            format!(" [ ラジオ {} ] ", stations.len())
+        } else {
+            format!(" [ RADIO {} ] ", stations.len())
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(if theme.is_retro {
+                BorderType::Thick
+            } else {
+                BorderType::Rounded
+            })
+            .border_style(Style::default().fg(theme.accent))
+            .title(Span::styled(
+                title,
+                Style::default()
+                    .fg(theme.primary)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .title_alignment(Alignment::Center)
+            .style(Style::default().bg(theme.bg));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let [list_area, help_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        if stations.is_empty() {
+            let text = if is_jp {
+                ":station add <URL> [名前] で局を追加"
+            } else {
+                "ADD ONE WITH :station add <url> [name]"
+            };
+            f.render_widget(
+                Paragraph::new(text)
+                    .style(Style::default().fg(theme.dim))
+                    .alignment(Alignment::Center),
+                list_area,
+            );
+        } else {
+            // Keep the selection in view
+            let selected = self.selected.min(stations.len() - 1);
+            let rows = list_area.height as usize;
+            let first = selected.saturating_sub(rows.saturating_sub(1));
+            let lines: Vec<Line> = stations
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(rows)
+                .map(|(i, station)| {
+                    let on_air = playing == Some(station.name.as_str());
+                    station_line(station, i == selected, on_air, theme)
+                })
+                .collect();
+            f.render_widget(Paragraph::new(lines), list_area);
+        }
+
+        f.render_widget(
+            Paragraph::new("↑↓ Select │ ⏎ Play │ x Remove │ Esc Close")
+                .style(Style::default().fg(theme.dim))
+                .alignment(Alignment::Center),
+            help_area,
+        );
+    }
+}
+
+fn station_line(station: &Station, selected: bool, on_air: bool, theme: Theme) -> Line<'static> {
+    let name_style = if selected {
+        Style::default()
+            .fg(theme.bg)
+            .bg(theme.accent)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.primary)
+    };
+    Line::from(vec![
+        Span::styled(
+            if on_air { "▶ " } else { "  " },
+            Style::default().fg(theme.accent),
+        ),
+        Span::styled(station.name.clone(), name_style),
+        Span::styled(format!("  {}", station.url), Style::default().fg(theme.dim)),
+    ])
+}
@@ -0,0 +1,267 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use amcli_core::library::{self, ItemKind, LibraryItem};
+
+use crate::ui::Theme;
+
+// More than fit on screen, so scrolling down doesn't run out early
+const RESULT_LIMIT: usize = 50;
+
+// Narrows the switcher to one artist's or one album's albums and tracks
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scope {
+    Artist(String),
+    Album { artist: String, album: String },
+}
+
+impl Scope {
+    fn contains(&self, item: &LibraryItem) -> bool {
+        let same = |a: &str, b: &str| a.to_lowercase() == b.to_lowercase();
+        match (self, item.kind) {
+            (_, ItemKind::Playlist) => false,
+            (Scope::Artist(artist), _) => same(&item.artist, artist),
+            (Scope::Album { artist, album }, ItemKind::Album) => {
+                same(&item.artist, artist) && same(&item.name, album)
+            }
+            (Scope::Album { artist, album }, ItemKind::Track) => {
+                same(&item.artist, artist) && same(&item.album, album)
+            }
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Scope::Artist(artist) => artist.clone(),
+            Scope::Album { artist, album } => format!("{} · {}", album, artist),
+        }
+    }
+}
+
+// Ctrl+P search over recent tracks, playlists, and albums, opened on top of
+// everything else. Results are re-ranked on every keystroke.
+pub struct QuickSwitcher {
+    pub query: String,
+    scope: Option<Scope>,
+    results: Vec<LibraryItem>,
+    selected: usize,
+}
+
+impl QuickSwitcher {
+    // Empty until the first `search`
+    pub fn new(scope: Option<Scope>) -> Self {
+        Self {
+            query: String::new(),
+            scope,
+            results: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn search<'a>(&mut self, items: impl IntoIterator<Item = &'a LibraryItem>) {
+        self.results = match &self.scope {
+            None => library::rank(&self.query, items, RESULT_LIMIT),
+            Some(scope) => {
+                // Albums ahead of tracks, so Enter on an empty query plays
+                // the whole thing
+                let mut items: Vec<_> = items.into_iter().filter(|i| scope.contains(i)).collect();
+                items.sort_by_key(|item| item.kind);
+                library::rank(&self.query, items, RESULT_LIMIT)
+            }
+        }
+        .into_iter()
+        .cloned()
+        .collect();
+        self.selected = 0;
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.results.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+    }
+
+    pub fn selected(&self) -> Option<&LibraryItem> {
+        self.results.get(self.selected)
+    }
+
+    pub fn render(&self, f: &mut Frame, theme: Theme, is_jp: bool, indexing: bool) {
+        let area = f.area();
+        let popup_width = 64.min(area.width.saturating_sub(4));
+        let popup_height = 20.min(area.height.saturating_sub(4));
+        let popup_area = Rect {
+            x: area.width.saturating_sub(popup_width) / 2,
+            y: area.height.saturating_sub(popup_height) / 3,
+            width: popup_width,
+            height: popup_height,
+        };
+        f.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(if theme.is_retro {
+                BorderType::Thick
+            } else {
+                BorderType::Rounded
+            })
+            .border_style(Style::default().fg(theme.accent))
+            .title(Span::styled(
+                if is_jp { " [ 検索 ] " } else { " [ GO TO ] " },
+                Style::default()
+                    .fg(theme.primary)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .title_alignment(Alignment::Center)
+            .style(Style::default().bg(theme.bg));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let [input_area, list_area, help_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        let mut input = Vec::new();
+        if let Some(scope) = &self.scope {
+            input.push(Span::styled(
+                format!("[{}] ", scope.label()),
+                Style::default().fg(theme.dim),
+            ));
+        }
+        input.extend([
+            Span::styled("> ", Style::default().fg(theme.accent)),
+            Span::styled(self.query.clone(), Style::default().fg(theme.primary)),
+            Span::styled("█", Style::default().fg(theme.accent)),
+        ]);
+        f.render_widget(Paragraph::new(Line::from(input)), input_area);
+
+        if self.results.is_empty() {
+            let text = match (indexing, is_jp) {
+                (true, true) => "ライブラリ読込中",
+                (true, false) => "INDEXING LIBRARY",
+                (false, true) => "該当なし",
+                (false, false) => "NO MATCHES",
+            };
+            f.render_widget(
+                Paragraph::new(text)
+                    .style(Style::default().fg(theme.dim))
+                    .alignment(Alignment::Center),
+                list_area,
+            );
+        } else {
+            // Keep the selection in view
+            let rows = list_area.height as usize;
+            let first = self.selected.saturating_sub(rows.saturating_sub(1));
+            let lines: Vec<Line> = self
+                .results
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(rows)
+                .map(|(i, item)| result_line(item, i == self.selected, theme, is_jp))
+                .collect();
+            f.render_widget(Paragraph::new(lines), list_area);
+        }
+
+        f.render_widget(
+            Paragraph::new("↑↓ Select │ ⏎ Play │ Esc Close")
+                .style(Style::default().fg(theme.dim))
+                .alignment(Alignment::Center),
+            help_area,
+        );
+    }
+}
+
+fn result_line(item: &LibraryItem, selected: bool, theme: Theme, is_jp: bool) -> Line<'static> {
+    let kind = match (item.kind, is_jp) {
+        (ItemKind::Playlist, false) => "PLAYLIST",
+        (ItemKind::Album, false) => "ALBUM",
+        (ItemKind::Track, false) => "TRACK",
+        (ItemKind::Playlist, true) => "プレイリスト",
+        (ItemKind::Album, true) => "アルバム",
+        (ItemKind::Track, true) => "曲",
+    };
+    let name_style = if selected {
+        Style::default()
+            .fg(theme.bg)
+            .bg(theme.accent)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.primary)
+    };
+    let mut spans = vec![
+        Span::styled(format!("{:<9}", kind), Style::default().fg(theme.dim)),
+        Span::styled(item.name.clone(), name_style),
+    ];
+    let detail = match (item.artist.is_empty(), item.album.is_empty()) {
+        (true, _) => String::new(),
+        (false, true) => format!("  {}", item.artist),
+        (false, false) => format!("  {} · {}", item.artist, item.album),
+    };
+    if !detail.is_empty() {
+        spans.push(Span::styled(detail, Style::default().fg(theme.dim)));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_reranks_and_resets_the_selection() {
+        let items = [
+            LibraryItem::new(ItemKind::Track, "So What", "Miles Davis"),
+            LibraryItem::new(ItemKind::Playlist, "Focus", ""),
+            LibraryItem::new(ItemKind::Album, "Kind of Blue", "Miles Davis"),
+        ];
+        let mut switcher = QuickSwitcher::new(None);
+        switcher.search(&items);
+        switcher.move_selection(5);
+        assert_eq!(switcher.selected().unwrap().name, "Kind of Blue");
+
+        switcher.query = "blue".into();
+        switcher.search(&items);
+        assert_eq!(switcher.selected().unwrap().name, "Kind of Blue");
+        switcher.move_selection(1);
+        assert_eq!(switcher.selected().unwrap().name, "Kind of Blue");
+
+        switcher.query = "zzz".into();
+        switcher.search(&items);
+        assert!(switcher.selected().is_none());
+    }
+
+    #[test]
+    fn scopes_keep_to_one_artist_or_album() {
+        let items = [
+            LibraryItem::track("1", "So What", "Miles Davis", "Kind of Blue"),
+            LibraryItem::track("2", "Solar", "Miles Davis", "Walkin'"),
+            LibraryItem::track("3", "Naima", "John Coltrane", "Giant Steps"),
+            LibraryItem::new(ItemKind::Album, "Kind of Blue", "Miles Davis"),
+            LibraryItem::new(ItemKind::Playlist, "Miles Davis Essentials", ""),
+        ];
+        let names = |switcher: &QuickSwitcher| -> Vec<String> {
+            switcher.results.iter().map(|i| i.name.clone()).collect()
+        };
+
+        let mut artist = QuickSwitcher::new(Some(Scope::Artist("miles davis".into())));
+        artist.search(&items);
+        assert_eq!(names(&artist), ["Kind of Blue", "So What", "Solar"]);
+
+        let album = Scope::Album {
+            artist: "Miles Davis".into(),
+            album: "Kind of Blue".into(),
+        };
+        let mut switcher = QuickSwitcher::new(Some(album));
+        switcher.search(&items);
+        assert_eq!(names(&switcher), ["Kind of Blue", "So What"]);
+        switcher.query = "what".into();
+        switcher.search(&items);
+        assert_eq!(names(&switcher), ["So What"]);
+    }
+}
@@ -0,0 +1,109 @@
+use std::future::Future;
+use tokio::task::{Id, JoinError, JoinSet};
+use tokio_util::sync::CancellationToken;
+
+// One kind of background load (the cover, the lyrics) of which only the
+// latest matters. Starting a load cancels the one before it, and only the
+// latest load's result is ever handed back, however the others finish, so a
+// burst of track skips can't leave a stale cover or lyrics on screen.
+pub struct TaskSlot<T> {
+    tasks: JoinSet<Option<T>>,
+    token: CancellationToken,
+    current: Option<Id>,
+}
+
+impl<T: Send + 'static> TaskSlot<T> {
+    pub fn new() -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            token: CancellationToken::new(),
+            current: None,
+        }
+    }
+
+    pub fn start<F>(&mut self, future: F)
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        self.cancel();
+        let token = self.token.clone();
+        let handle = self.tasks.spawn(async move {
+            tokio::select! {
+                biased;
+                _ = token.cancelled() => None,
+                value = future => Some(value),
+            }
+        });
+        self.current = Some(handle.id());
+    }
+
+    // The running load stops at its next await point; nothing it returns
+    // is kept
+    pub fn cancel(&mut self) {
+        if self.current.take().is_some() {
+            self.token.cancel();
+            self.token = CancellationToken::new();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.current.is_some()
+    }
+
+    // The latest load's result once it's done, without waiting. Finished
+    // cancelled loads are cleared out along the way.
+    pub fn try_finish(&mut self) -> Option<Result<T, JoinError>> {
+        while let Some(joined) = self.tasks.try_join_next_with_id() {
+            let (id, result) = match joined {
+                Ok((id, Some(value))) => (id, Ok(value)),
+                Ok((_, None)) => continue,
+                Err(e) => (e.id(), Err(e)),
+            };
+            if self.current == Some(id) {
+                self.current = None;
+                return Some(result);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn finish<T: Send + 'static>(slot: &mut TaskSlot<T>) -> Option<Result<T, JoinError>> {
+        for _ in 0..100 {
+            if let Some(result) = slot.try_finish() {
+                return Some(result);
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        None
+    }
+
+    #[tokio::test]
+    async fn only_the_latest_load_is_handed_back() {
+        let mut slot = TaskSlot::new();
+        slot.start(async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            "first"
+        });
+        // Finishes first, but was superseded before anyone took it
+        slot.start(async { "second" });
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        slot.start(async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            "third"
+        });
+        assert!(slot.is_running());
+        assert_eq!(finish(&mut slot).await.unwrap().unwrap(), "third");
+        assert!(!slot.is_running());
+
+        slot.start(async { "cancelled" });
+        slot.cancel();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(slot.try_finish().is_none());
+    }
+}
@@ -0,0 +1,210 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::ui::Theme;
+
+const FIELDS: [(&str, &str); 5] = [
+    ("PRIMARY", "主色"),
+    ("DIM", "淡色"),
+    ("ACCENT", "強調"),
+    ("ALERT", "警告"),
+    ("BACKGROUND", "背景"),
+];
+const CHANNELS: [&str; 3] = ["R", "G", "B"];
+
+// Tweaks a copy of the active theme one RGB channel at a time. The app draws
+// with the draft while the editor is open, so every change previews live.
+#[derive(Debug, Clone)]
+pub struct ThemeEditor {
+    pub draft: Theme,
+    // Field * 3 + channel
+    selected: usize,
+}
+
+impl ThemeEditor {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            draft: theme,
+            selected: 0,
+        }
+    }
+
+    pub fn navigate(&mut self, delta: isize) {
+        let count = (FIELDS.len() * CHANNELS.len()) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, count - 1) as usize;
+    }
+
+    pub fn adjust(&mut self, delta: i16) {
+        let channel = self.selected % CHANNELS.len();
+        let color = self.field_mut(self.selected / CHANNELS.len());
+        let mut rgb = to_rgb(*color);
+        rgb[channel] = (rgb[channel] as i16 + delta).clamp(0, 255) as u8;
+        *color = Color::Rgb(rgb[0], rgb[1], rgb[2]);
+    }
+
+    fn field(&self, index: usize) -> Color {
+        [
+            self.draft.primary,
+            self.draft.dim,
+            self.draft.accent,
+            self.draft.alert,
+            self.draft.bg,
+        ][index]
+    }
+
+    fn field_mut(&mut self, index: usize) -> &mut Color {
+        match index {
+            0 => &mut self.draft.primary,
+            1 => &mut self.draft.dim,
+            2 => &mut self.draft.accent,
+            3 => &mut self.draft.alert,
+            _ => &mut self.draft.bg,
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame, theme: Theme, is_jp: bool) {
+        let area = f.area();
+        let popup_width = 52.min(area.width.saturating_sub(4));
+        let popup_height = 11.min(area.height.saturating_sub(4));
+        let popup_area = Rect {
+            x: area.width.saturating_sub(popup_width) / 2,
+            y: area.height.saturating_sub(popup_height) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+        f.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(if theme.is_retro {
+                BorderType::Thick
+            } else {
+                BorderType::Rounded
+            })
+            .border_style(Style::default().fg(theme.accent))
+            .title(vec![
+                Span::styled(" [ ", Style::default().fg(theme.dim)),
+                Span::styled(
+                    if is_jp {
+                        "テーマ編集"
+                    } else {
+                        "THEME EDITOR"
+                    },
+                    Style::default()
+                        .fg(theme.primary)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" ] ", Style::default().fg(theme.dim)),
+            ])
+            .title_alignment(Alignment::Center)
+            .style(Style::default().bg(theme.bg));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let mut lines = vec![Line::from(Span::styled(
+            format!("  {}", theme.name),
+            Style::default().fg(theme.dim),
+        ))];
+        for (index, (en, jp)) in FIELDS.iter().enumerate() {
+            let color = self.field(index);
+            let mut spans = vec![
+                Span::styled(
+                    format!("  {:<11}", if is_jp { *jp } else { *en }),
+                    Style::default().fg(theme.primary),
+                ),
+                Span::styled("  ", Style::default().bg(color)),
+                Span::raw(" "),
+            ];
+            for (channel, (label, value)) in CHANNELS.iter().zip(to_rgb(color)).enumerate() {
+                let style = if self.selected == index * CHANNELS.len() + channel {
+                    Style::default()
+                        .fg(theme.bg)
+                        .bg(theme.accent)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.dim)
+                };
+                spans.push(Span::styled(format!(" {} {:>3} ", label, value), style));
+            }
+            lines.push(Line::from(spans));
+        }
+        f.render_widget(Paragraph::new(lines), inner);
+
+        let help = Paragraph::new("↑↓ Channel │ ←→ ±8 │ H/L ±1 │ Enter Save │ Esc Cancel")
+            .style(Style::default().fg(theme.dim))
+            .alignment(Alignment::Center);
+        let help_area = Rect {
+            x: popup_area.x,
+            y: popup_area.bottom().saturating_sub(1),
+            width: popup_area.width,
+            height: 1,
+        };
+        f.render_widget(help, help_area);
+    }
+}
+
+// Indexed colors resolve through the standard xterm palette; the terminal
+// default has no fixed value, so editing it starts from black.
+pub fn to_rgb(color: Color) -> [u8; 3] {
+    const ANSI: [[u8; 3]; 16] = [
+        [0, 0, 0],
+        [205, 0, 0],
+        [0, 205, 0],
+        [205, 205, 0],
+        [0, 0, 238],
+        [205, 0, 205],
+        [0, 205, 205],
+        [229, 229, 229],
+        [127, 127, 127],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [92, 92, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+    match color {
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Indexed(i @ 0..=15) => ANSI[i as usize],
+        Color::Indexed(i @ 16..=231) => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let i = i - 16;
+            [level(i / 36), level(i / 6 % 6), level(i % 6)]
+        }
+        Color::Indexed(i) => [8 + (i - 232) * 10; 3],
+        _ => [0, 0, 0],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::THEME_TERMINAL_CLEAN;
+
+    #[test]
+    fn adjusting_a_channel_saturates_and_converts_to_rgb() {
+        let mut editor = ThemeEditor::new(THEME_TERMINAL_CLEAN);
+        editor.adjust(8);
+        assert_eq!(editor.draft.primary, Color::Rgb(8, 0, 238));
+        editor.navigate(2);
+        editor.adjust(100);
+        assert_eq!(editor.draft.primary, Color::Rgb(8, 0, 255));
+
+        editor.navigate(100);
+        editor.adjust(-1);
+        assert_eq!(editor.draft.bg, Color::Rgb(0, 0, 0));
+        assert_eq!(editor.draft.accent, THEME_TERMINAL_CLEAN.accent);
+    }
+
+    #[test]
+    fn indexed_colors_map_through_the_xterm_palette() {
+        assert_eq!(to_rgb(Color::Indexed(196)), [255, 0, 0]);
+        assert_eq!(to_rgb(Color::Indexed(244)), [128, 128, 128]);
+    }
+}